@@ -4,6 +4,9 @@ use std::path::Path;
 
 fn main() {
     // Process LALRPOP grammar
+    // (the rerun-if-changed lines below replace cargo's default of rerunning
+    // on any change, so the grammar has to be listed too)
+    println!("cargo:rerun-if-changed=src/theme/theme.lalrpop");
     lalrpop::process_root().unwrap();
 
     // Copy theme files and tasks.toml to the output directory
@@ -1,17 +1,22 @@
 //! Animation system with bezier easing curves
 //!
-//! Provides smooth animations for window transitions with configurable easing.
+//! Provides smooth animations for window transitions with configurable easing,
+//! and the style transitions themes declare with `transition`.
 
+use std::collections::HashMap;
 use std::time::Instant;
 
+use crate::theme::types::{Color, Gradient, GradientStop, Paint};
+
 /// Bezier easing function type
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum Easing {
     /// Linear interpolation (no easing)
     Linear,
     /// Ease in (slow start)
     EaseIn,
     /// Ease out (slow end)
+    #[default]
     EaseOut,
     /// Ease in and out (slow start and end)
     EaseInOut,
@@ -19,12 +24,6 @@ pub enum Easing {
     CubicBezier(f32, f32, f32, f32),
 }
 
-impl Default for Easing {
-    fn default() -> Self {
-        Easing::EaseOut
-    }
-}
-
 impl Easing {
     /// Standard easing presets (CSS-like)
     pub const EASE: Easing = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
@@ -42,7 +41,12 @@ impl Easing {
 
     /// Parse easing from string name
     pub fn from_name(name: &str) -> Self {
-        match name.to_lowercase().replace('-', "_").as_str() {
+        Self::named(name).unwrap_or(Easing::EASE_OUT_EXPO) // Default
+    }
+
+    /// Look up an easing by name; None if there is no such preset
+    pub fn named(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().replace('-', "_").as_str() {
             "linear" => Easing::Linear,
             "ease" => Easing::EASE,
             "ease_in" | "easein" => Easing::EASE_IN,
@@ -52,8 +56,8 @@ impl Easing {
             "ease_out_quart" | "easeoutquart" => Easing::EASE_OUT_QUART,
             "ease_out_expo" | "easeoutexpo" => Easing::EASE_OUT_EXPO,
             "ease_out_back" | "easeoutback" => Easing::EASE_OUT_BACK,
-            _ => Easing::EASE_OUT_EXPO, // Default
-        }
+            _ => return None,
+        })
     }
 
     /// Calculate the eased value for a given progress (0.0 to 1.0)
@@ -303,9 +307,293 @@ impl WindowAnimator {
     }
 }
 
+/// One entry of a theme's `transition` list: how long a change of a style
+/// property takes, and its easing
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    /// The property, or `all`
+    pub property: String,
+    pub duration_ms: u32,
+    pub easing: Easing,
+}
+
+impl Transition {
+    /// The transition for a property: as in CSS, the last entry naming it
+    /// or `all`
+    pub fn find<'a>(transitions: &'a [Transition], property: &str) -> Option<&'a Transition> {
+        transitions
+            .iter()
+            .rev()
+            .find(|t| t.property == property || t.property == "all")
+    }
+}
+
+/// A style value a transition can run between
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimatedValue {
+    /// Distances, opacity and other numbers
+    Number(f32),
+    Color(Color),
+    Paint(Paint),
+}
+
+impl AnimatedValue {
+    /// The value `t` of the way from `self` to `to` (0.0 is `self`)
+    ///
+    /// Colors are interpolated with premultiplied alpha, so fading in from
+    /// transparent doesn't pass through black. A gradient turns into a color
+    /// or another gradient with as many stops smoothly; other changes of
+    /// kind happen half-way.
+    pub fn interpolate(&self, to: &Self, t: f32) -> Self {
+        match (self, to) {
+            (AnimatedValue::Number(a), AnimatedValue::Number(b)) => {
+                AnimatedValue::Number(a + (b - a) * t)
+            }
+            (AnimatedValue::Color(a), AnimatedValue::Color(b)) => {
+                AnimatedValue::Color(lerp_color(a, b, t))
+            }
+            (AnimatedValue::Paint(a), AnimatedValue::Paint(b)) => match lerp_paint(a, b, t) {
+                Some(paint) => AnimatedValue::Paint(paint),
+                None if t < 0.5 => self.clone(),
+                None => to.clone(),
+            },
+            _ if t < 0.5 => self.clone(),
+            _ => to.clone(),
+        }
+    }
+}
+
+impl From<f32> for AnimatedValue {
+    fn from(value: f32) -> Self {
+        AnimatedValue::Number(value)
+    }
+}
+
+impl From<Color> for AnimatedValue {
+    fn from(color: Color) -> Self {
+        AnimatedValue::Color(color)
+    }
+}
+
+impl From<Paint> for AnimatedValue {
+    fn from(paint: Paint) -> Self {
+        AnimatedValue::Paint(paint)
+    }
+}
+
+impl TryFrom<AnimatedValue> for f32 {
+    type Error = AnimatedValue;
+
+    fn try_from(value: AnimatedValue) -> Result<Self, Self::Error> {
+        match value {
+            AnimatedValue::Number(n) => Ok(n),
+            other => Err(other),
+        }
+    }
+}
+
+impl TryFrom<AnimatedValue> for Color {
+    type Error = AnimatedValue;
+
+    fn try_from(value: AnimatedValue) -> Result<Self, Self::Error> {
+        match value {
+            AnimatedValue::Color(color) => Ok(color),
+            other => Err(other),
+        }
+    }
+}
+
+impl TryFrom<AnimatedValue> for Paint {
+    type Error = AnimatedValue;
+
+    fn try_from(value: AnimatedValue) -> Result<Self, Self::Error> {
+        match value {
+            AnimatedValue::Paint(paint) => Ok(paint),
+            other => Err(other),
+        }
+    }
+}
+
+/// Interpolate two colors with premultiplied alpha
+fn lerp_color(from: &Color, to: &Color, t: f32) -> Color {
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+    let alpha = lerp(from.a, to.a).clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return to.with_alpha(0.0);
+    }
+    let channel = |a: f32, b: f32| (lerp(a * from.a, b * to.a) / alpha).clamp(0.0, 1.0);
+    Color {
+        r: channel(from.r, to.r),
+        g: channel(from.g, to.g),
+        b: channel(from.b, to.b),
+        a: alpha,
+    }
+}
+
+/// Interpolate two paints, if they have the same shape; a solid color
+/// takes the shape of the gradient it turns into or out of
+fn lerp_paint(from: &Paint, to: &Paint, t: f32) -> Option<Paint> {
+    let (from, to) = match (from, to) {
+        (Paint::Solid(a), Paint::Solid(b)) => return Some(Paint::Solid(lerp_color(a, b, t))),
+        (Paint::Gradient(a), Paint::Gradient(b)) => (a.clone(), b.clone()),
+        (Paint::Solid(color), Paint::Gradient(b)) => (solid_gradient(*color, b), b.clone()),
+        (Paint::Gradient(a), Paint::Solid(color)) => (a.clone(), solid_gradient(*color, a)),
+    };
+    if from.kind != to.kind || from.stops.len() != to.stops.len() {
+        return None;
+    }
+
+    let stops = from
+        .stops
+        .iter()
+        .zip(&to.stops)
+        .map(|(a, b)| GradientStop {
+            position: a.position + (b.position - a.position) * t,
+            color: lerp_color(&a.color, &b.color, t),
+        })
+        .collect();
+    Some(Paint::Gradient(Gradient {
+        kind: to.kind,
+        stops,
+    }))
+}
+
+/// A gradient of a single color, with the kind and stops of `shape`
+fn solid_gradient(color: Color, shape: &Gradient) -> Gradient {
+    Gradient {
+        kind: shape.kind,
+        stops: shape
+            .stops
+            .iter()
+            .map(|stop| GradientStop {
+                position: stop.position,
+                color,
+            })
+            .collect(),
+    }
+}
+
+/// A transition in progress
+#[derive(Debug, Clone)]
+struct RunningTransition {
+    /// Eased progress from 0.0 to 1.0
+    progress: Animation,
+    from: AnimatedValue,
+    to: AnimatedValue,
+}
+
+impl RunningTransition {
+    fn value(&self) -> AnimatedValue {
+        self.from.interpolate(&self.to, self.progress.value())
+    }
+}
+
+/// The transitions running on one widget, or one item of a list or grid,
+/// by property
+///
+/// Widgets report property changes with `change` when their state changes,
+/// draw with `get`, and are ticked by the animation timer until `tick`
+/// returns false.
+#[derive(Debug, Clone, Default)]
+pub struct AnimatedProperties {
+    running: HashMap<String, RunningTransition>,
+}
+
+impl AnimatedProperties {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note that a property's value changed from `from` to `to`
+    ///
+    /// Starts a transition if `transitions` has one for the property;
+    /// otherwise the change shows at once. A transition starts from the
+    /// value currently shown, so one interrupted half-way turns around
+    /// smoothly.
+    pub fn change(
+        &mut self,
+        transitions: &[Transition],
+        property: &str,
+        from: impl Into<AnimatedValue>,
+        to: impl Into<AnimatedValue>,
+    ) {
+        let to = to.into();
+        let from = match self.running.get(property) {
+            Some(running) => running.value(),
+            None => from.into(),
+        };
+        match Transition::find(transitions, property) {
+            Some(transition) if transition.duration_ms > 0 && from != to => {
+                let progress = Animation::new(0.0, 1.0, transition.duration_ms, transition.easing);
+                self.running.insert(
+                    property.to_string(),
+                    RunningTransition { progress, from, to },
+                );
+            }
+            _ => {
+                self.running.remove(property);
+            }
+        }
+    }
+
+    /// Point a running transition at a property's new value, after a theme
+    /// reload restyled the widget
+    ///
+    /// A transition already running to `to` carries on as it was. One
+    /// running to an old value turns towards `to` from the value shown,
+    /// with the reloaded `transitions`, instead of jumping to its end.
+    pub fn retarget(
+        &mut self,
+        transitions: &[Transition],
+        property: &str,
+        to: impl Into<AnimatedValue>,
+    ) {
+        let to = to.into();
+        match self.running.get(property) {
+            Some(running) if running.to != to => {
+                self.change(transitions, property, to.clone(), to);
+            }
+            _ => {}
+        }
+    }
+
+    /// The value to draw for a property whose value is now `value`
+    ///
+    /// That is the transition's current value while one is running towards
+    /// `value`, and `value` itself otherwise.
+    pub fn get<T>(&self, property: &str, value: T) -> T
+    where
+        T: Clone + Into<AnimatedValue> + TryFrom<AnimatedValue>,
+    {
+        self.running
+            .get(property)
+            .filter(|running| running.to == value.clone().into())
+            .and_then(|running| T::try_from(running.value()).ok())
+            .unwrap_or(value)
+    }
+
+    /// Drop finished transitions; returns true while any are running
+    pub fn tick(&mut self) -> bool {
+        self.running
+            .retain(|_, running| !running.progress.is_complete());
+        !self.running.is_empty()
+    }
+
+    /// Check if any transition is running
+    pub fn is_animating(&self) -> bool {
+        self.running.values().any(|r| !r.progress.is_complete())
+    }
+
+    /// Stop all transitions, showing their end values
+    pub fn clear(&mut self) {
+        self.running.clear();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::theme::types::GradientKind;
 
     #[test]
     fn test_linear_easing() {
@@ -335,4 +623,112 @@ mod tests {
         assert!((easing.ease(0.0) - 0.0).abs() < 0.01);
         assert!((easing.ease(1.0) - 1.0).abs() < 0.01);
     }
+
+    fn transition(property: &str, duration_ms: u32) -> Transition {
+        Transition {
+            property: property.to_string(),
+            duration_ms,
+            easing: Easing::Linear,
+        }
+    }
+
+    #[test]
+    fn test_find_transition() {
+        let transitions = [transition("all", 100), transition("text-color", 200)];
+        let find = |property| Transition::find(&transitions, property).map(|t| t.duration_ms);
+        assert_eq!(find("text-color"), Some(200));
+        assert_eq!(find("background-color"), Some(100));
+        assert_eq!(
+            Transition::find(&transitions[1..], "background-color"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_interpolate_values() {
+        let half = |from: AnimatedValue, to: AnimatedValue| from.interpolate(&to, 0.5);
+        assert_eq!(half(2.0.into(), 4.0.into()), AnimatedValue::Number(3.0));
+
+        // Fading in from transparent black keeps the target's color
+        let red = Color::from_f32(1.0, 0.0, 0.0, 1.0);
+        assert_eq!(
+            half(Color::TRANSPARENT.into(), red.into()),
+            AnimatedValue::Color(red.with_alpha(0.5))
+        );
+
+        // A color turns into a gradient stop by stop
+        let gradient = Paint::Gradient(Gradient {
+            kind: GradientKind::Linear { angle: 90.0 },
+            stops: vec![
+                GradientStop {
+                    position: 0.0,
+                    color: Color::WHITE,
+                },
+                GradientStop {
+                    position: 1.0,
+                    color: red,
+                },
+            ],
+        });
+        let AnimatedValue::Paint(Paint::Gradient(mid)) =
+            half(Paint::Solid(Color::WHITE).into(), gradient.into())
+        else {
+            panic!("expected a gradient");
+        };
+        assert_eq!(mid.stops[0].color, Color::WHITE);
+        assert_eq!(mid.stops[1].color, Color::from_f32(1.0, 0.5, 0.5, 1.0));
+
+        // Values of different kinds switch half-way
+        assert_eq!(
+            AnimatedValue::Number(1.0).interpolate(&red.into(), 0.25),
+            AnimatedValue::Number(1.0)
+        );
+    }
+
+    #[test]
+    fn test_animated_properties() {
+        let red = Color::from_f32(1.0, 0.0, 0.0, 1.0);
+        let blue = Color::from_f32(0.0, 0.0, 1.0, 1.0);
+        let transitions = [
+            transition("background-color", 60_000),
+            transition("border-width", 0),
+        ];
+        let mut animated = AnimatedProperties::new();
+
+        // Properties without a running transition show their value at once
+        animated.change(&transitions, "text-color", red, blue);
+        animated.change(&transitions, "border-width", 1.0, 2.0);
+        assert!(!animated.is_animating());
+        assert_eq!(animated.get("text-color", blue), blue);
+
+        animated.change(&transitions, "background-color", red, blue);
+        assert!(animated.tick());
+        let shown = animated.get("background-color", blue);
+        assert!(shown.r > 0.9 && shown.b < 0.1, "{:?}", shown);
+        // A value the transition isn't running to shows as it is
+        assert_eq!(animated.get("background-color", red), red);
+
+        // Turning around starts from the value shown
+        animated.change(&transitions, "background-color", blue, red);
+        assert!(animated.get("background-color", red).r > 0.9);
+
+        // A reload that keeps the target leaves the transition alone, and
+        // one that changes it turns the transition towards the new value
+        animated.change(&transitions, "background-color", red, blue);
+        let shown = animated.get("background-color", blue);
+        animated.retarget(&transitions, "background-color", blue);
+        assert_eq!(animated.get("background-color", blue), shown);
+        let green = Color::from_f32(0.0, 1.0, 0.0, 1.0);
+        animated.retarget(&transitions, "background-color", green);
+        assert!(animated.is_animating());
+        let retargeted = animated.get("background-color", green);
+        assert!(retargeted.r > 0.9 && retargeted.g < 0.1, "{:?}", retargeted);
+        // Properties that weren't moving aren't started
+        animated.retarget(&transitions, "text-color", green);
+        assert_eq!(animated.get("text-color", red), red);
+
+        animated.clear();
+        assert!(!animated.tick());
+        assert_eq!(animated.get("background-color", blue), blue);
+    }
 }
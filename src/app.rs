@@ -1,6 +1,8 @@
 //! Application state machine for Wolfy
 
+use std::cell::RefCell;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
 use windows::Win32::Graphics::Direct2D::ID2D1Bitmap;
@@ -17,14 +19,20 @@ use crate::platform::win32::{
     reposition_window, resize_window, set_wallpaper, translate_message, Event, ImageLoader,
     MouseButton, PollingFileWatcher, Renderer, WindowConfig,
 };
+use crate::query::Query;
+use crate::render::DisplayList;
+use crate::state::AppState;
 use crate::task_runner::{TaskRunner, TaskStatus};
 use crate::tasks::{find_tasks_config, load_tasks_config, TaskItemState, TaskPanelPosition};
+use crate::theme::hyde;
 use crate::theme::tree::ThemeTree;
-use crate::theme::types::{Color, ImageScale, LayoutContext, Rect};
+use crate::theme::types::{Color, ImageScale, LayoutContext, Paint, Rect};
+use crate::widget::overlay::HOVERED_ANCHOR;
 use crate::widget::{
-    ClockConfig, ClockPosition, CornerRadii, ElementData, ElementStyle, EventResult, GridItem,
-    GridView, GridViewStyle, ListView, ListViewStyle, TailView, TailViewHit, TailViewStyle,
-    TaskPanelState, TaskPanelStyle, Textbox, Widget, WidgetState, WidgetStyle,
+    launcher_widget_path, restyle_launcher, ChildLayout, CornerRadii, ElementData, ElementStyle,
+    EventResult, GridItem, GridView, GridViewStyle, Hit, ListView, ListViewStyle, TailView,
+    TailViewHit, TailViewStyle, TaskPanelState, TaskPanelStyle, Textbox, ThemeLayout, UITree,
+    Widget, WidgetState, WidgetStyle,
 };
 
 /// Cursor blink timer ID
@@ -52,80 +60,18 @@ const TIMER_TAIL_REFRESH: usize = 6;
 /// Tail view refresh interval in milliseconds
 const TAIL_REFRESH_MS: u32 = 200;
 
+/// Overlay anchors for the list, grid and task panel the app draws itself:
+/// themes can `anchor-to` them, and the mouse is hit-tested against them
+/// under the overlays
+const LISTVIEW_ANCHOR: &str = "listview";
+const GRIDVIEW_ANCHOR: &str = "gridview";
+const TASK_PANEL_ANCHOR: &str = "task-panel";
+
 /// Application version from Cargo.toml
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Fuzzy match a query against a target string
-/// Returns Some(score) if matched, None if not matched
-/// Higher scores indicate better matches
-fn fuzzy_match(target: &str, query: &str) -> Option<i32> {
-    if query.is_empty() {
-        return Some(0);
-    }
-
-    let target_chars: Vec<char> = target.chars().collect();
-    let query_chars: Vec<char> = query.chars().collect();
-
-    // Check if all query chars appear in order in target
-    let mut target_idx = 0;
-    let mut query_idx = 0;
-    let mut score = 0i32;
-    let mut consecutive = 0;
-    let mut last_match_idx: Option<usize> = None;
-
-    while query_idx < query_chars.len() && target_idx < target_chars.len() {
-        if target_chars[target_idx] == query_chars[query_idx] {
-            // Matched a character
-
-            // Bonus for consecutive matches
-            if let Some(last) = last_match_idx {
-                if target_idx == last + 1 {
-                    consecutive += 1;
-                    score += consecutive * 5; // Growing bonus for consecutive
-                } else {
-                    consecutive = 0;
-                }
-            }
-
-            // Bonus for matching at start
-            if target_idx == 0 {
-                score += 15;
-            }
-
-            // Bonus for matching after separator (space, -, _, etc)
-            if target_idx > 0 {
-                let prev = target_chars[target_idx - 1];
-                if prev == ' ' || prev == '-' || prev == '_' || prev == '.' {
-                    score += 10;
-                }
-            }
-
-            // Bonus for matching uppercase in camelCase
-            if target_chars[target_idx].is_uppercase() {
-                score += 5;
-            }
-
-            last_match_idx = Some(target_idx);
-            query_idx += 1;
-            score += 1; // Base score for each match
-        }
-        target_idx += 1;
-    }
-
-    // All query chars must be found
-    if query_idx == query_chars.len() {
-        // Bonus for shorter targets (more precise match)
-        score += (100 - target_chars.len().min(100) as i32);
-
-        // Bonus if query matches a significant portion of target
-        let coverage = (query_chars.len() * 100) / target_chars.len().max(1);
-        score += coverage as i32 / 2;
-
-        Some(score)
-    } else {
-        None
-    }
-}
+/// Command-line flag passed by `restart_app` to reopen the last shown mode
+pub const RESTORE_MODE_ARG: &str = "--restore-mode";
 
 /// Get the HyDE themes directory path
 fn hyde_themes_dir() -> Option<PathBuf> {
@@ -153,6 +99,35 @@ fn hyde_themes_dir() -> Option<PathBuf> {
     None
 }
 
+/// Load core.rasi layered with the colors of `theme_name` (catppuccin_mocha if None)
+///
+/// Uses themes/<name>.rasi when it exists. Otherwise the colors are generated
+/// from the HyDE theme directory, so every HyDE theme can be picked.
+fn load_layered_theme(core_path: &Path, theme_name: Option<&str>) -> Result<ThemeTree, String> {
+    let theme_filename = theme_name
+        .map(ThemeTree::theme_name_to_filename)
+        .unwrap_or_else(|| "catppuccin_mocha".to_string());
+    let theme_colors_path = find_config_file(&format!("themes/{}.rasi", theme_filename));
+
+    let name = match theme_name {
+        Some(name) if !theme_colors_path.exists() => name,
+        _ => {
+            log!("  Loading layered theme: {:?} + {:?}", core_path, theme_colors_path);
+            return ThemeTree::load_layered(&[core_path, &theme_colors_path])
+                .map_err(|e| e.to_string());
+        }
+    };
+
+    let dir = hyde_themes_dir()
+        .map(|dir| dir.join(name))
+        .ok_or_else(|| format!("no {:?} and no HyDE themes directory", theme_colors_path))?;
+    log!("  No {:?}, generating theme colors from {:?}", theme_colors_path, dir);
+    let source = hyde::generate(&dir).map_err(|e| e.to_string())?;
+    let mut theme = ThemeTree::load(core_path).map_err(|e| e.to_string())?;
+    theme.merge(ThemeTree::parse(&source).map_err(|e| e.to_string())?);
+    Ok(theme)
+}
+
 /// Represents a HyDE theme with its metadata
 #[derive(Clone, Debug)]
 pub struct HydeTheme {
@@ -286,585 +261,40 @@ fn find_first_image(dir: &Path) -> Option<PathBuf> {
     images.into_iter().next()
 }
 
-/// Child widget layout info
-#[derive(Clone, Debug)]
-pub struct ChildLayout {
-    /// Widget name
-    pub name: String,
-    /// Calculated bounds
-    pub bounds: Rect,
-    /// Whether this widget expands
-    pub expand: bool,
-    /// Fixed width (if not expanding)
-    pub fixed_width: Option<f32>,
-}
-
-/// Theme-derived layout settings
-#[derive(Clone, Debug)]
-pub struct ThemeLayout {
-    /// Window border radius
-    pub window_border_radius: f32,
-    /// Window border color
-    pub window_border_color: Color,
-    /// Window background color (tint behind everything)
-    pub window_background_color: Color,
-    /// Mainbox padding
-    pub mainbox_padding: f32,
-    /// Mainbox children names (from theme)
-    pub mainbox_children: Vec<String>,
-    /// Wallpaper panel width
-    pub wallpaper_panel_width: f32,
-    /// Wallpaper panel background color (overlay)
-    pub wallpaper_panel_bg: Color,
-    /// Wallpaper panel corner radii (per-corner)
-    pub wallpaper_panel_radii: CornerRadii,
-    /// Wallpaper panel diagonal edge offset (0 = no diagonal, positive = slant from top-right)
-    pub wallpaper_panel_diagonal: f32,
-    /// Wallpaper panel fade width (how wide the feathered edge is along the diagonal)
-    pub wallpaper_panel_fade_width: f32,
-    /// Wallpaper panel fade color (color to fade TO at the diagonal edge)
-    pub wallpaper_panel_fade_color: Option<Color>,
-    /// Wallpaper panel fade opacity multiplier (0.0-1.0, controls how solid the fade is)
-    pub wallpaper_panel_fade_opacity: f32,
-    /// Listbox background color
-    pub listbox_bg: Color,
-    /// Listbox corner radii (per-corner)
-    pub listbox_radii: CornerRadii,
-    /// Listbox padding
-    pub listbox_padding: f32,
-    /// Listview padding
-    pub listview_padding_top: f32,
-    pub listview_padding_right: f32,
-    pub listview_padding_bottom: f32,
-    pub listview_padding_left: f32,
-    /// Animation duration in milliseconds (0 = no animation)
-    pub animation_duration_ms: u32,
-    /// Animation easing type (ease-out, ease-in, ease-in-out, linear)
-    pub animation_easing: String,
-    /// Clock configuration for wallpaper panel overlay
-    pub clock_config: ClockConfig,
-}
-
-impl ThemeLayout {
-    /// Check if wallpaper panel should be shown
-    pub fn show_wallpaper_panel(&self) -> bool {
-        self.mainbox_children.iter().any(|c| c == "wallpaper-panel")
-    }
-
-    /// Check if a specific widget is in mainbox children
-    pub fn has_child(&self, name: &str) -> bool {
-        self.mainbox_children.iter().any(|c| c == name)
-    }
-
-    /// Get the fixed width for a widget (scaled)
-    pub fn get_widget_width(&self, name: &str, scale: f32) -> Option<f32> {
-        match name {
-            "wallpaper-panel" => Some(self.wallpaper_panel_width * scale),
-            _ => None, // Widget expands
-        }
-    }
-
-    /// Check if a widget should expand
-    pub fn widget_expands(&self, name: &str) -> bool {
-        match name {
-            "wallpaper-panel" => false, // Fixed width
-            "listbox" => true,          // Expands to fill remaining space
-            _ => true,                  // Default to expanding
-        }
-    }
-
-    /// Calculate bounds for each child in mainbox (horizontal layout)
-    pub fn calculate_mainbox_children_bounds(
-        &self,
-        content_x: f32,
-        content_y: f32,
-        content_width: f32,
-        content_height: f32,
-        scale: f32,
-    ) -> Vec<ChildLayout> {
-        let mut layouts = Vec::new();
-        let mut used_width = 0.0;
-        let mut expand_count = 0;
-
-        // First pass: calculate fixed widths and count expanding widgets
-        for name in &self.mainbox_children {
-            let expand = self.widget_expands(name);
-            let fixed_width = self.get_widget_width(name, scale);
-
-            if !expand {
-                if let Some(w) = fixed_width {
-                    used_width += w;
-                }
-            } else {
-                expand_count += 1;
-            }
-
-            layouts.push(ChildLayout {
-                name: name.clone(),
-                bounds: Rect::zero(), // Will be calculated in second pass
-                expand,
-                fixed_width,
-            });
-        }
-
-        // Second pass: distribute remaining width to expanding widgets
-        let remaining_width = content_width - used_width;
-        let expand_width = if expand_count > 0 {
-            remaining_width / expand_count as f32
-        } else {
-            0.0
-        };
-
-        let mut x = content_x;
-        for layout in &mut layouts {
-            let width = if layout.expand {
-                expand_width
-            } else {
-                layout.fixed_width.unwrap_or(expand_width)
-            };
+/// Match score bonus per unit of log-frecency (a consecutive match char is ~20)
+const FRECENCY_SCORE_WEIGHT: f64 = 8.0;
 
-            layout.bounds = Rect::new(x, content_y, width, content_height);
-            x += width;
-        }
+/// Match score bonus per unit of log-affinity for an item learned for the query
+/// (two picks for the same prefix outweigh a word-boundary advantage)
+const QUERY_AFFINITY_WEIGHT: f64 = 32.0;
 
-        layouts
+/// Convert a history sort index into a search score bonus
+///
+/// Logarithmic so a heavily used app can outrank a slightly better match but
+/// never a much better one. Apps that were never launched get no bonus.
+fn frecency_bonus(sort_index: f64) -> i32 {
+    if sort_index <= 0.0 {
+        return 0;
     }
+    (sort_index.ln_1p() * FRECENCY_SCORE_WEIGHT).round() as i32
 }
 
-impl Default for ThemeLayout {
-    fn default() -> Self {
-        Self {
-            window_border_radius: 16.0,
-            window_border_color: Color::from_hex("#f97e72").unwrap_or(Color::WHITE),
-            window_background_color: Color::TRANSPARENT, // Default to no tint
-            mainbox_padding: 20.0,
-            mainbox_children: vec!["wallpaper-panel".to_string(), "listbox".to_string()],
-            wallpaper_panel_width: 456.0,
-            wallpaper_panel_bg: Color::from_hex("#262335e6").unwrap_or(Color::BLACK),
-            wallpaper_panel_radii: CornerRadii::uniform(16.0),
-            wallpaper_panel_diagonal: 0.0,     // No diagonal by default
-            wallpaper_panel_fade_width: 100.0, // Fade gradient width along diagonal edge
-            wallpaper_panel_fade_color: None,  // None = use listbox_bg color
-            wallpaper_panel_fade_opacity: 0.7, // Default opacity multiplier (0.7 = softer fade)
-            listbox_bg: Color::from_hex("#262335e6").unwrap_or(Color::BLACK),
-            listbox_radii: CornerRadii::uniform(16.0),
-            listbox_padding: 0.0,
-            listview_padding_top: 16.0,
-            listview_padding_right: 32.0,
-            listview_padding_bottom: 16.0,
-            listview_padding_left: 32.0,
-            animation_duration_ms: 200,
-            animation_easing: "ease-out-expo".to_string(),
-            clock_config: ClockConfig::default(),
-        }
-    }
+/// Convert a learned query affinity into a search score bonus
+fn query_affinity_bonus(affinity: f64) -> i32 {
+    (affinity.max(0.0).ln_1p() * QUERY_AFFINITY_WEIGHT).round() as i32
 }
 
-impl ThemeLayout {
-    /// Load layout settings from theme
-    pub fn from_theme(theme: &ThemeTree) -> Self {
-        let default = Self::default();
-
-        // Get mainbox children from theme (or use defaults)
-        let mainbox_children = {
-            let children = theme.get_children("mainbox");
-            if children.is_empty() {
-                default.mainbox_children.clone()
-            } else {
-                children
-            }
-        };
-
-        // Helper to read corner radii from theme
-        let read_corner_radii = |widget: &str, default_radii: CornerRadii| -> CornerRadii {
-            let base =
-                theme.get_number(widget, None, "border-radius", default_radii.top_left as f64)
-                    as f32;
-            CornerRadii {
-                top_left: theme.get_number(widget, None, "border-top-left-radius", base as f64)
-                    as f32,
-                top_right: theme.get_number(widget, None, "border-top-right-radius", base as f64)
-                    as f32,
-                bottom_right: theme.get_number(
-                    widget,
-                    None,
-                    "border-bottom-right-radius",
-                    base as f64,
-                ) as f32,
-                bottom_left: theme.get_number(
-                    widget,
-                    None,
-                    "border-bottom-left-radius",
-                    base as f64,
-                ) as f32,
-            }
-        };
-
-        let layout = Self {
-            window_border_radius: theme.get_number(
-                "window",
-                None,
-                "border-radius",
-                default.window_border_radius as f64,
-            ) as f32,
-            window_border_color: theme.get_color(
-                "window",
-                None,
-                "border-color",
-                default.window_border_color,
-            ),
-            window_background_color: theme.get_color(
-                "window",
-                None,
-                "background-color",
-                default.window_background_color,
-            ),
-            mainbox_padding: theme.get_number(
-                "mainbox",
-                None,
-                "padding",
-                default.mainbox_padding as f64,
-            ) as f32,
-            mainbox_children,
-            wallpaper_panel_width: theme.get_number(
-                "wallpaper-panel",
-                None,
-                "width",
-                default.wallpaper_panel_width as f64,
-            ) as f32,
-            wallpaper_panel_bg: theme.get_color(
-                "wallpaper-panel",
-                None,
-                "background-color",
-                default.wallpaper_panel_bg,
-            ),
-            wallpaper_panel_radii: read_corner_radii(
-                "wallpaper-panel",
-                default.wallpaper_panel_radii,
-            ),
-            wallpaper_panel_diagonal: {
-                let val = theme.get_number(
-                    "wallpaper-panel",
-                    None,
-                    "diagonal-edge",
-                    default.wallpaper_panel_diagonal as f64,
-                ) as f32;
-                log!("ThemeLayout: wallpaper_panel_diagonal = {}", val);
-                val
-            },
-            wallpaper_panel_fade_width: {
-                let val = theme.get_number(
-                    "wallpaper-panel",
-                    None,
-                    "fade-width",
-                    default.wallpaper_panel_fade_width as f64,
-                ) as f32;
-                log!("ThemeLayout: wallpaper_panel_fade_width = {}", val);
-                val
-            },
-            wallpaper_panel_fade_color: theme.get_color_opt("wallpaper-panel", None, "fade-color"),
-            wallpaper_panel_fade_opacity: theme.get_number(
-                "wallpaper-panel",
-                None,
-                "fade-opacity",
-                default.wallpaper_panel_fade_opacity as f64,
-            ) as f32,
-            listbox_bg: theme.get_color("listbox", None, "background-color", default.listbox_bg),
-            listbox_radii: read_corner_radii("listbox", default.listbox_radii),
-            listbox_padding: theme.get_number(
-                "listbox",
-                None,
-                "padding-top",
-                default.listbox_padding as f64,
-            ) as f32,
-            listview_padding_top: theme.get_number(
-                "listview",
-                None,
-                "padding-top",
-                default.listview_padding_top as f64,
-            ) as f32,
-            listview_padding_right: theme.get_number(
-                "listview",
-                None,
-                "padding-right",
-                default.listview_padding_right as f64,
-            ) as f32,
-            listview_padding_bottom: theme.get_number(
-                "listview",
-                None,
-                "padding-bottom",
-                default.listview_padding_bottom as f64,
-            ) as f32,
-            listview_padding_left: theme.get_number(
-                "listview",
-                None,
-                "padding-left",
-                default.listview_padding_left as f64,
-            ) as f32,
-            animation_duration_ms: theme.get_number(
-                "window",
-                None,
-                "animation-duration",
-                default.animation_duration_ms as f64,
-            ) as u32,
-            animation_easing: theme.get_string(
-                "window",
-                None,
-                "animation-easing",
-                &default.animation_easing,
-            ),
-            clock_config: ClockConfig {
-                enabled: theme.get_bool(
-                    "wallpaper-panel",
-                    None,
-                    "clock-enabled",
-                    default.clock_config.enabled,
-                ),
-                position: ClockPosition::from_str(&theme.get_string(
-                    "wallpaper-panel",
-                    None,
-                    "clock-position",
-                    "top-right",
-                )),
-                time_format: theme.get_string(
-                    "wallpaper-panel",
-                    None,
-                    "clock-format",
-                    &default.clock_config.time_format,
-                ),
-                date_format: theme.get_string(
-                    "wallpaper-panel",
-                    None,
-                    "clock-date-format",
-                    &default.clock_config.date_format,
-                ),
-                font_family: theme.get_string(
-                    "wallpaper-panel",
-                    None,
-                    "clock-font-family",
-                    &default.clock_config.font_family,
-                ),
-                font_size: theme.get_number(
-                    "wallpaper-panel",
-                    None,
-                    "clock-font-size",
-                    default.clock_config.font_size as f64,
-                ) as f32,
-                date_font_size: theme.get_number(
-                    "wallpaper-panel",
-                    None,
-                    "clock-date-font-size",
-                    default.clock_config.date_font_size as f64,
-                ) as f32,
-                text_color: theme.get_color(
-                    "wallpaper-panel",
-                    None,
-                    "clock-text-color",
-                    default.clock_config.text_color,
-                ),
-                shadow_color: theme.get_color(
-                    "wallpaper-panel",
-                    None,
-                    "clock-shadow-color",
-                    default.clock_config.shadow_color,
-                ),
-                shadow_offset: (
-                    theme.get_number(
-                        "wallpaper-panel",
-                        None,
-                        "clock-shadow-offset-x",
-                        default.clock_config.shadow_offset.0 as f64,
-                    ) as f32,
-                    theme.get_number(
-                        "wallpaper-panel",
-                        None,
-                        "clock-shadow-offset-y",
-                        default.clock_config.shadow_offset.1 as f64,
-                    ) as f32,
-                ),
-                padding: theme.get_number(
-                    "wallpaper-panel",
-                    None,
-                    "clock-padding",
-                    default.clock_config.padding as f64,
-                ) as f32,
-            },
-        };
-
-        // Debug log the loaded theme layout
-        log!(
-            "ThemeLayout::from_theme - window_background_color: r={:.3} g={:.3} b={:.3} a={:.3}",
-            layout.window_background_color.r,
-            layout.window_background_color.g,
-            layout.window_background_color.b,
-            layout.window_background_color.a
-        );
-        log!(
-            "ThemeLayout::from_theme - animation: {}ms, easing={}",
-            layout.animation_duration_ms,
-            layout.animation_easing
-        );
-
-        layout
-    }
-}
-
-/// Load task panel style from theme
-fn load_task_panel_style(theme: &ThemeTree) -> TaskPanelStyle {
-    let default = TaskPanelStyle::default();
-
-    let style = TaskPanelStyle {
-        enabled: theme.get_bool("task-panel", None, "enabled", default.enabled),
-        position: {
-            let pos = theme.get_string("task-panel", None, "position", "left");
-            match pos.to_lowercase().as_str() {
-                "right" => TaskPanelPosition::Right,
-                _ => TaskPanelPosition::Left,
-            }
-        },
-
-        // Colors
-        background_color: theme.get_color(
-            "task-panel",
-            None,
-            "background-color",
-            default.background_color,
-        ),
-        icon_color: theme.get_color("task-panel", None, "icon-color", default.icon_color),
-        icon_color_hover: theme.get_color(
-            "task-panel",
-            None,
-            "icon-color-hover",
-            default.icon_color_hover,
-        ),
-        group_icon_color: theme.get_color(
-            "task-panel",
-            None,
-            "group-icon-color",
-            default.group_icon_color,
-        ),
-        text_color: theme.get_color("task-panel", None, "text-color", default.text_color),
-        text_color_hover: theme.get_color(
-            "task-panel",
-            None,
-            "text-color-hover",
-            default.text_color_hover,
-        ),
-        item_background_color: theme.get_color(
-            "task-panel",
-            None,
-            "item-background-color",
-            default.item_background_color,
-        ),
-        selected_background_color: theme.get_color(
-            "task-panel",
-            None,
-            "selected-background-color",
-            default.selected_background_color,
-        ),
-        tree_line_color: theme.get_color(
-            "task-panel",
-            None,
-            "tree-line-color",
-            default.tree_line_color,
-        ),
-        chevron_color: theme.get_color("task-panel", None, "chevron-color", default.chevron_color),
-
-        // Typography
-        icon_font_family: theme.get_string(
-            "task-panel",
-            None,
-            "icon-font-family",
-            &default.icon_font_family,
-        ),
-        text_font_family: theme.get_string(
-            "task-panel",
-            None,
-            "text-font-family",
-            &default.text_font_family,
-        ),
-        icon_size: theme.get_number("task-panel", None, "icon-size", default.icon_size as f64)
-            as f32,
-        text_size: theme.get_number("task-panel", None, "text-size", default.text_size as f64)
-            as f32,
-
-        // Dimensions
-        compact_width: theme.get_number(
-            "task-panel",
-            None,
-            "compact-width",
-            default.compact_width as f64,
-        ) as f32,
-        expanded_width: theme.get_number(
-            "task-panel",
-            None,
-            "expanded-width",
-            default.expanded_width as f64,
-        ) as f32,
-        item_height: theme.get_number(
-            "task-panel",
-            None,
-            "item-height",
-            default.item_height as f64,
-        ) as f32,
-        item_corner_radius: theme.get_number(
-            "task-panel",
-            None,
-            "item-corner-radius",
-            default.item_corner_radius as f64,
-        ) as f32,
-        padding: theme.get_number("task-panel", None, "padding", default.padding as f64) as f32,
-        group_spacing: theme.get_number(
-            "task-panel",
-            None,
-            "group-spacing",
-            default.group_spacing as f64,
-        ) as f32,
-        item_spacing: theme.get_number(
-            "task-panel",
-            None,
-            "item-spacing",
-            default.item_spacing as f64,
-        ) as f32,
-        border_radius: theme.get_number(
-            "task-panel",
-            None,
-            "border-radius",
-            default.border_radius as f64,
-        ) as f32,
-        sub_item_indent: theme.get_number(
-            "task-panel",
-            None,
-            "sub-item-indent",
-            default.sub_item_indent as f64,
-        ) as f32,
-
-        // Icons
-        chevron_collapsed: theme.get_string(
-            "task-panel",
-            None,
-            "chevron-collapsed",
-            &default.chevron_collapsed,
-        ),
-        chevron_expanded: theme.get_string(
-            "task-panel",
-            None,
-            "chevron-expanded",
-            &default.chevron_expanded,
-        ),
-        tree_branch: theme.get_string("task-panel", None, "tree-branch", &default.tree_branch),
-        tree_corner: theme.get_string("task-panel", None, "tree-corner", &default.tree_corner),
-    };
-
-    log!(
-        "Task panel style loaded: icon_font='{}', compact_width={}, expanded_width={}, enabled={}",
-        style.icon_font_family,
-        style.compact_width,
-        style.expanded_width,
-        style.enabled
-    );
-
-    style
+/// State of a task panel row, for its themed colors
+fn task_row_state(hovered: bool, selected: bool, status: Option<&TaskStatus>) -> WidgetState {
+    let mut state = WidgetState::NORMAL;
+    state.set(WidgetState::HOVER, hovered);
+    state.set(WidgetState::SELECTED, selected);
+    match status {
+        Some(TaskStatus::Running) => state |= WidgetState::ACTIVE,
+        Some(TaskStatus::Failed) => state |= WidgetState::URGENT,
+        _ => {}
+    }
+    state
 }
 
 /// Application state
@@ -877,8 +307,12 @@ pub struct App {
     gridview: GridView,
     /// All available items (unfiltered)
     all_items: Vec<ElementData>,
+    /// All grid items for the current picker mode (unfiltered)
+    all_grid_items: Vec<GridItem>,
     /// Usage history for sorting
     history: History,
+    /// State shared with the picker windows and persisted across restarts
+    app_state: Rc<RefCell<AppState>>,
     layout_ctx: LayoutContext,
     style: WidgetStyle,
     /// Theme-derived layout settings
@@ -887,6 +321,8 @@ pub struct App {
     background_bitmap: Option<ID2D1Bitmap>,
     /// Path that was used to load the background bitmap (for cache invalidation)
     background_bitmap_path: Option<String>,
+    /// Blur radius the background bitmap was blurred with
+    background_bitmap_blur: f32,
     /// File watcher for theme hot-reload
     theme_watcher: Option<PollingFileWatcher>,
     /// Window animator for fade effects
@@ -905,11 +341,18 @@ pub struct App {
     task_runner: TaskRunner,
     /// Tail view widget for showing task output
     tailview: TailView,
+    /// Themed overlays drawn over the window: the tooltip and the version
+    /// watermark
+    overlay: UITree,
 }
 
 impl App {
     /// Create new application
-    pub fn new(hwnd: HWND, config: WindowConfig) -> Result<Self, windows::core::Error> {
+    pub fn new(
+        hwnd: HWND,
+        config: WindowConfig,
+        app_state: Rc<RefCell<AppState>>,
+    ) -> Result<Self, windows::core::Error> {
         log!("App::new() starting, hwnd={:?}", hwnd);
 
         log!("  Creating Renderer...");
@@ -923,11 +366,15 @@ impl App {
             dpi_info.scale_factor
         );
 
-        let layout_ctx = LayoutContext {
+        let (monitor_width, monitor_height) = win32::get_monitor_size();
+        let mut layout_ctx = LayoutContext {
             dpi: dpi_info.dpi as f32,
             scale_factor: dpi_info.scale_factor,
-            base_font_size: 16.0,
             parent_size: config.width as f32,
+            viewport_width: monitor_width as f32,
+            viewport_height: monitor_height as f32,
+            // `em` and `ch` units follow the theme's font, see `apply_base_font`
+            ..LayoutContext::default()
         };
 
         // Load theme: core.rasi + current theme colors
@@ -943,7 +390,7 @@ impl App {
                     Some(t)
                 }
                 Err(e) => {
-                    log!("  Failed to load layered theme: {:?}, trying default.rasi", e);
+                    log!("  Failed to load layered theme: {}, trying default.rasi", e);
                     None
                 }
             };
@@ -958,17 +405,23 @@ impl App {
                     Some(t)
                 }
                 Err(e) => {
-                    log!("  Failed to load theme: {:?}, using defaults", e);
+                    log!("  Failed to load theme: {}, using defaults", e);
                     None
                 }
             };
             (t, legacy_path)
         };
+        if let Some(theme) = &theme {
+            win32::apply_base_font(&mut layout_ctx, theme);
+        }
 
         // Create textbox with theme style
         let style = theme
             .as_ref()
-            .map(|t| WidgetStyle::from_theme_textbox(t, None))
+            .map(|t| {
+                let path = launcher_widget_path(t, "textbox");
+                WidgetStyle::from_theme_textbox_at(t, &path, None)
+            })
             .unwrap_or_default();
         log!(
             "  Textbox style: font_size={}, font_family={}",
@@ -979,16 +432,22 @@ impl App {
         let mut textbox = Textbox::new()
             .with_placeholder("Type to search...")
             .with_style(style.clone());
-        textbox.set_state(WidgetState::Focused);
+        textbox.set_state(WidgetState::FOCUSED);
 
         // Load listview and element styles from theme
         let listview_style = theme
             .as_ref()
-            .map(|t| ListViewStyle::from_theme(t, None))
+            .map(|t| {
+                let path = launcher_widget_path(t, "listview");
+                ListViewStyle::from_theme_at(t, &path, None)
+            })
             .unwrap_or_default();
         let element_style = theme
             .as_ref()
-            .map(|t| ElementStyle::from_theme(t, None))
+            .map(|t| {
+                let path = format!("{} element", launcher_widget_path(t, "listview"));
+                ElementStyle::from_theme_at(t, &path, None)
+            })
             .unwrap_or_default();
 
         let listview = ListView::new()
@@ -1034,9 +493,14 @@ impl App {
             })
             .collect();
 
-        // Create theme file watcher for hot-reload
-        log!("  Creating theme file watcher for: {:?}", theme_path);
-        let theme_watcher = Some(PollingFileWatcher::new(&theme_path));
+        // Create theme file watcher for hot-reload (theme files plus their imports)
+        let watch_paths = theme
+            .as_ref()
+            .map(|t| t.sources.clone())
+            .filter(|sources| !sources.is_empty())
+            .unwrap_or_else(|| vec![theme_path.clone()]);
+        log!("  Creating theme file watcher for: {:?}", watch_paths);
+        let theme_watcher = Some(PollingFileWatcher::with_paths(&watch_paths));
 
         // Create window animator from theme settings
         let easing = Easing::from_name(&theme_layout.animation_easing);
@@ -1066,7 +530,7 @@ impl App {
         // Load task panel style from theme (or use defaults)
         let task_panel_style = theme
             .as_ref()
-            .map(|t| load_task_panel_style(t))
+            .map(|t| TaskPanelStyle::from_theme(t))
             .unwrap_or_default();
 
         // Initialize task runner and cleanup old log files
@@ -1080,6 +544,12 @@ impl App {
             tailview.set_button_style(task_panel_style.clone());
         }
 
+        // Overlays are widgets of their own, so themes can style and place them
+        let default_theme = ThemeTree::new();
+        let mut overlay =
+            UITree::from_theme(theme.as_ref().unwrap_or(&default_theme), layout_ctx.clone());
+        overlay.set_text("version", &format!("v{}", VERSION));
+
         log!("App::new() completed successfully");
         Ok(Self {
             hwnd,
@@ -1089,12 +559,15 @@ impl App {
             listview,
             gridview,
             all_items,
+            all_grid_items: Vec::new(),
             history,
+            app_state,
             layout_ctx,
             style,
             theme_layout,
             background_bitmap: None,
             background_bitmap_path: None,
+            background_bitmap_blur: 0.0,
             theme_watcher,
             animator,
             is_visible: false,
@@ -1104,6 +577,7 @@ impl App {
             current_theme: None,
             task_runner,
             tailview,
+            overlay,
         })
     }
 
@@ -1149,6 +623,14 @@ impl App {
         }
     }
 
+    /// Advance the list rows' and grid tiles' state transitions; returns
+    /// true while any are running
+    fn tick_transitions(&mut self) -> bool {
+        let list = self.listview.tick();
+        let grid = self.gridview.tick();
+        list || grid
+    }
+
     /// Start clock update timer (if clock is enabled)
     fn start_clock_timer(&self) {
         if self.theme_layout.clock_config.enabled {
@@ -1279,11 +761,17 @@ impl App {
             }
             WM_TIMER if wparam.0 == TIMER_ANIMATION => {
                 // Update animation state
-                if self.animator.update() {
+                let fading = self.animator.update();
+                if fading {
                     // Still animating - just update opacity without re-rendering content
                     let opacity = self.animator.get_opacity();
                     let _ = self.renderer.update_opacity_only(opacity);
-                } else {
+                }
+                if self.tick_transitions() {
+                    // Item state transitions need the content re-rendered
+                    self.renderer.mark_dirty();
+                    invalidate_window(self.hwnd);
+                } else if !fading {
                     // Animation complete - stop the timer and start clock timer
                     self.stop_animation_timer();
                     self.start_clock_timer();
@@ -1487,7 +975,7 @@ impl App {
         }
 
         // In grid modes, route navigation + Enter + mouse wheel to gridview.
-        // Textbox still handles typing (for future filtering), but we don't use listview.
+        // Textbox still handles typing (to filter the grid), but we don't use listview.
         if self.current_mode.uses_grid_view() {
             // Handle mouse wheel for grid scrolling
             if let Event::MouseWheel { .. } = event {
@@ -1512,41 +1000,7 @@ impl App {
 
                         // Handle grid submit based on current mode
                         if result.submit {
-                            if let Some(item) = self.gridview.selected_item().cloned() {
-                                log!(
-                                    "Grid submit (mode={:?}): '{}' ({})",
-                                    self.current_mode,
-                                    item.title,
-                                    item.user_data
-                                );
-
-                                match self.current_mode {
-                                    Mode::ThemePicker => {
-                                        // Set current theme and switch to WallpaperPicker
-                                        self.current_theme = Some(item.user_data.clone());
-                                        log!("Selected theme: {}", item.user_data);
-
-                                        // Reload the theme styling with new colors
-                                        self.reload_theme();
-
-                                        self.current_mode = Mode::WallpaperPicker;
-                                        self.on_mode_changed();
-                                        // Force repaint
-                                        self.renderer.mark_dirty();
-                                        invalidate_window(self.hwnd);
-                                    }
-                                    Mode::WallpaperPicker => {
-                                        // Set wallpaper (user_data contains the full path)
-                                        log!("Setting wallpaper: {}", item.user_data);
-                                        self.set_wallpaper(&item.user_data);
-                                        win32::hide_window(self.hwnd);
-                                        self.is_visible = false;
-                                    }
-                                    Mode::Launcher | Mode::TailView => {
-                                        // Should not happen, but handle gracefully
-                                    }
-                                }
-                            }
+                            self.submit_grid_item();
                             result.submit = false;
                         }
 
@@ -1571,6 +1025,13 @@ impl App {
             } => {
                 return self.handle_mouse_click(*x as f32, *y as f32);
             }
+            Event::MouseUp {
+                x,
+                y,
+                button: MouseButton::Left,
+            } => {
+                return self.handle_mouse_release(*x as f32, *y as f32);
+            }
             Event::MouseWheel { delta, .. } => {
                 // Route mouse wheel to listview in launcher mode (not grid view)
                 // Scroll regardless of mouse position - the list is the main content
@@ -1878,14 +1339,18 @@ impl App {
         if let Some(data) = self.listview.selected_data() {
             let command = data.user_data.clone();
             let name = data.text.clone();
+            let query = self.textbox.text().to_string();
             log!("Launching: {} ({})", name, command);
 
             // Try to launch the application
             if let Err(e) = self.launch_app(&command) {
                 log!("Failed to launch {}: {:?}", command, e);
             } else {
-                // Record successful launch in history
-                self.history.record_launch(&command);
+                // Record successful launch in history, learning the typed query
+                self.history.record_launch_for_query(&command, &query);
+                self.app_state
+                    .borrow_mut()
+                    .remember_selection(Mode::Launcher, &command);
             }
         }
 
@@ -1894,11 +1359,47 @@ impl App {
         self.stop_cursor_timer();
     }
 
-    /// Handle mouse move for task panel hover
+    /// The list, grid or task panel under the mouse (one of the `*_ANCHOR`s),
+    /// None if an overlay like the tooltip covers it
+    fn anchor_at(&self, x: f32, y: f32) -> Option<&str> {
+        match self.overlay.hit_test(x, y)? {
+            Hit::Anchor(name) => Some(name),
+            Hit::Widget(_) => None,
+        }
+    }
+
+    /// Handle mouse move for list, grid and task panel hover
     fn handle_mouse_move(&mut self, x: f32, y: f32) -> EventResult {
+        // Only what's on top under the mouse is hovered. The task panel's
+        // rows are inside it, so the hovered row's anchor is never on top.
+        let anchor = self.anchor_at(x, y);
+        let over_list = matches!(anchor, Some(LISTVIEW_ANCHOR | GRIDVIEW_ANCHOR));
+        let over_task_panel = anchor == Some(TASK_PANEL_ANCHOR);
+
+        // Highlight the list row or grid tile under the mouse
+        let hover_changed = if self.current_mode.uses_grid_view() {
+            let index = if over_list {
+                self.gridview.index_at(x, y)
+            } else {
+                None
+            };
+            self.gridview.set_hovered(index)
+        } else {
+            let index = if over_list {
+                self.listview.hit_test(x, y)
+            } else {
+                None
+            };
+            self.listview.set_hovered(index)
+        };
+
         if let Some(ref mut task_panel) = self.task_panel {
             let old_hovered = task_panel.hovered_item;
-            task_panel.hovered_item = task_panel.hit_test(x, y);
+            task_panel.hovered_item = if over_task_panel {
+                task_panel.hit_test(x, y)
+            } else {
+                None
+            };
 
             // Repaint if hover state changed
             if old_hovered != task_panel.hovered_item {
@@ -1911,29 +1412,81 @@ impl App {
                 };
             }
         }
+        if hover_changed {
+            return EventResult::repaint();
+        }
         EventResult::none()
     }
 
+    /// Choose the grid's selected item, according to the current mode
+    fn submit_grid_item(&mut self) {
+        if let Some(item) = self.gridview.selected_item().cloned() {
+            log!(
+                "Grid submit (mode={:?}): '{}' ({})",
+                self.current_mode,
+                item.title,
+                item.user_data
+            );
+
+            match self.current_mode {
+                Mode::ThemePicker => {
+                    // Set current theme and switch to WallpaperPicker
+                    self.current_theme = Some(item.title.clone());
+                    log!("Selected theme: {}", item.title);
+                    {
+                        let mut state = self.app_state.borrow_mut();
+                        state.set_current_theme(Some(item.title.clone()));
+                        state.remember_selection(Mode::ThemePicker, &item.user_data);
+                        // Reloaded right here rather than on the next show
+                        state.theme_needs_reload = false;
+                    }
+
+                    // Reload the theme styling with new colors
+                    self.reload_theme();
+
+                    self.current_mode = Mode::WallpaperPicker;
+                    self.on_mode_changed();
+                    // Force repaint
+                    self.renderer.mark_dirty();
+                    invalidate_window(self.hwnd);
+                }
+                Mode::WallpaperPicker => {
+                    // Set wallpaper (user_data contains the full path)
+                    log!("Setting wallpaper: {}", item.user_data);
+                    self.set_wallpaper(&item.user_data);
+                    self.app_state.borrow_mut().choose_wallpaper(&item.user_data);
+                    win32::hide_window(self.hwnd);
+                    self.is_visible = false;
+                }
+                Mode::Launcher | Mode::TailView => {
+                    // Should not happen, but handle gracefully
+                }
+            }
+        }
+    }
+
     /// Handle mouse click for task panel and listview
     fn handle_mouse_click(&mut self, x: f32, y: f32) -> EventResult {
-        // Check if click is in listview first (only for non-grid modes)
-        if !self.current_mode.uses_grid_view() && self.listview.contains_point(x, y) {
-            if let Some(idx) = self.listview.hit_test(x, y) {
-                // Select and launch the clicked item
-                self.listview.select(idx);
-                log!("Listview item {} clicked", idx);
-                return EventResult {
-                    needs_repaint: true,
-                    consumed: true,
-                    text_changed: false,
-                    submit: true,  // Trigger the item launch
-                    cancel: false,
-                };
+        // The click goes to what's on top under the mouse
+        let anchor = self.anchor_at(x, y);
+        let over_list = anchor == Some(LISTVIEW_ANCHOR);
+        let over_grid = anchor == Some(GRIDVIEW_ANCHOR);
+        let over_task_panel = anchor == Some(TASK_PANEL_ANCHOR);
+
+        // Press the row or tile under the mouse; releasing the button over
+        // it launches it (see handle_mouse_release)
+        if self.current_mode.uses_grid_view() {
+            if let Some(idx) = self.gridview.index_at(x, y).filter(|_| over_grid) {
+                self.gridview.press(idx);
+                return EventResult::repaint();
             }
+        } else if let Some(idx) = self.listview.hit_test(x, y).filter(|_| over_list) {
+            self.listview.press(idx);
+            return EventResult::repaint();
         }
 
         // Check if click is in task panel
-        if let Some(ref mut task_panel) = self.task_panel {
+        if let Some(task_panel) = self.task_panel.as_mut().filter(|_| over_task_panel) {
             if let Some(item_idx) = task_panel.hit_test(x, y) {
                 // Get item info before mutating
                 let item_state = task_panel.item_states.get(item_idx).cloned();
@@ -2026,6 +1579,46 @@ impl App {
         EventResult::none()
     }
 
+    /// Handle mouse button release: releasing it over the row or tile it
+    /// was pressed on launches that item
+    fn handle_mouse_release(&mut self, x: f32, y: f32) -> EventResult {
+        let anchor = self.anchor_at(x, y);
+
+        if self.current_mode.uses_grid_view() {
+            if self.gridview.pressed_index().is_none() {
+                return EventResult::none();
+            }
+            let index = self
+                .gridview
+                .index_at(x, y)
+                .filter(|_| anchor == Some(GRIDVIEW_ANCHOR));
+            if self.gridview.release(index) {
+                log!("Grid item {:?} clicked", index);
+                self.submit_grid_item();
+            }
+            return EventResult::repaint();
+        }
+
+        if self.listview.pressed_index().is_none() {
+            return EventResult::none();
+        }
+        let index = self
+            .listview
+            .hit_test(x, y)
+            .filter(|_| anchor == Some(LISTVIEW_ANCHOR));
+        if self.listview.release(index) {
+            log!("Listview item {:?} clicked", index);
+            return EventResult {
+                needs_repaint: true,
+                consumed: true,
+                text_changed: false,
+                submit: true, // Trigger the item launch
+                cancel: false,
+            };
+        }
+        EventResult::repaint()
+    }
+
     /// Run a PowerShell script in a visible terminal window
     fn run_powershell_script(&self, script: &str) -> Result<(), windows::core::Error> {
         use std::os::windows::process::CommandExt;
@@ -2435,8 +2028,8 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
             let temp_dir = std::env::temp_dir();
             let batch_path = temp_dir.join("wolfy_restart.bat");
             let batch_content = format!(
-                "@echo off\r\nping -n 2 localhost >nul\r\nstart \"\" \"{}\"\r\ndel \"%~f0\"\r\n",
-                exe_str
+                "@echo off\r\nping -n 2 localhost >nul\r\nstart \"\" \"{}\" {}\r\ndel \"%~f0\"\r\n",
+                exe_str, RESTORE_MODE_ARG
             );
             log!("Batch file: {:?}", batch_path);
             log!("Batch content: {}", batch_content);
@@ -2566,48 +2159,56 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
         log!("on_cancel() completed");
     }
 
-    /// Handle text changes - filter the list with fuzzy matching
+    /// Handle text changes - filter the list (or grid) with the extended query syntax
     fn on_text_changed(&mut self) {
-        let query = self.textbox.text().to_lowercase();
-        log!("on_text_changed() called, query='{}'", query);
+        let text = self.textbox.text().to_string();
+        log!("on_text_changed() called, query='{}'", text);
+        let query = Query::parse(&text);
+
+        if self.current_mode.uses_grid_view() {
+            self.filter_grid_items(&query);
+            return;
+        }
 
         if query.is_empty() {
             // Show all items when no search query
             self.listview.set_items(self.all_items.clone());
         } else {
-            // Filter and score items using fuzzy matching
+            // Filter and score items (case-smart, text ranks above subtext),
+            // nudging frecently launched apps and apps previously chosen for
+            // this query up among similar matches
             let mut scored: Vec<(i32, ElementData)> = self
                 .all_items
                 .iter()
                 .filter_map(|item| {
-                    let text_lower = item.text.to_lowercase();
-                    let subtext_lower = item.subtext.as_ref().map(|s| s.to_lowercase());
-
-                    // Try fuzzy match on text
-                    if let Some(score) = fuzzy_match(&text_lower, &query) {
-                        return Some((score, item.clone()));
-                    }
-
-                    // Try fuzzy match on subtext
-                    if let Some(subtext) = &subtext_lower {
-                        if let Some(score) = fuzzy_match(subtext, &query) {
-                            // Subtext matches get lower priority
-                            return Some((score - 100, item.clone()));
-                        }
-                    }
-
-                    None
+                    let m = query.matches(&item.text, item.subtext.as_deref())?;
+                    let score = m.score
+                        + frecency_bonus(self.history.sort_index(&item.user_data))
+                        + query_affinity_bonus(self.history.query_affinity(&text, &item.user_data));
+                    // Keep text positions for highlighting
+                    Some((score, item.clone().with_match_positions(m.positions)))
                 })
                 .collect();
 
-            // Sort by score (higher is better)
-            scored.sort_by(|a, b| b.0.cmp(&a.0));
+            // Sort by score (higher is better), then prefer shorter (more precise) names
+            scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.text.len().cmp(&b.1.text.len())));
 
             let filtered: Vec<ElementData> = scored.into_iter().map(|(_, item)| item).collect();
             self.listview.set_items(filtered);
         }
     }
 
+    /// Filter the picker grid by title and subtitle
+    fn filter_grid_items(&mut self, query: &Query) {
+        let filtered = GridItem::filter(&self.all_grid_items, query);
+        log!(
+            "  Grid filter: {} of {} items",
+            filtered.len(),
+            self.all_grid_items.len()
+        );
+        self.gridview.set_items(filtered);
+    }
+
     /// Set the current theme name (used when syncing with AppState)
     pub fn set_current_theme(&mut self, theme_name: Option<String>) {
         log!("App::set_current_theme({:?})", theme_name);
@@ -2618,12 +2219,7 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
     fn load_current_theme(&self) -> Option<ThemeTree> {
         let core_path = find_config_file("core.rasi");
         if core_path.exists() {
-            let theme_filename = self.current_theme
-                .as_ref()
-                .map(|name| ThemeTree::theme_name_to_filename(name))
-                .unwrap_or_else(|| "catppuccin_mocha".to_string());
-            let theme_colors_path = find_config_file(&format!("themes/{}.rasi", theme_filename));
-            ThemeTree::load_layered(&[&core_path, &theme_colors_path]).ok()
+            load_layered_theme(&core_path, self.current_theme.as_deref()).ok()
         } else {
             let theme_path = find_config_file("default.rasi");
             ThemeTree::load(&theme_path).ok()
@@ -2638,20 +2234,13 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
         let core_path = find_config_file("core.rasi");
         let theme = if core_path.exists() {
             // Use current theme, or default to catppuccin_mocha
-            let theme_filename = self.current_theme
-                .as_ref()
-                .map(|name| ThemeTree::theme_name_to_filename(name))
-                .unwrap_or_else(|| "catppuccin_mocha".to_string());
-            let theme_colors_path = find_config_file(&format!("themes/{}.rasi", theme_filename));
-
-            log!("  Loading layered theme: {:?} + {:?}", core_path, theme_colors_path);
-            match ThemeTree::load_layered(&[&core_path, &theme_colors_path]) {
+            match load_layered_theme(&core_path, self.current_theme.as_deref()) {
                 Ok(t) => {
                     log!("  Layered theme reloaded successfully");
                     t
                 }
                 Err(e) => {
-                    log!("  Failed to reload layered theme: {:?}", e);
+                    log!("  Failed to reload layered theme (keeping current theme): {}", e);
                     return;
                 }
             }
@@ -2669,34 +2258,46 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                     t
                 }
                 Err(e) => {
-                    log!("  Failed to reload theme: {:?}", e);
+                    log!("  Failed to reload theme (keeping current theme): {}", e);
                     return;
                 }
             }
         };
 
-        // Update textbox style
-        self.style = WidgetStyle::from_theme_textbox(&theme, None);
-        self.textbox.set_style(self.style.clone());
+        // Broken rules were skipped; everything else is applied as usual
+        if !theme.diagnostics.is_empty() {
+            log!(
+                "  Theme loaded with {} error(s), affected rules skipped",
+                theme.diagnostics.len()
+            );
+        }
+
+        // Imports may have changed, so watch the files this load actually used
+        if let Some(ref mut watcher) = self.theme_watcher {
+            if !theme.sources.is_empty() {
+                watcher.set_paths(&theme.sources);
+                log!("  Watching theme files: {:?}", theme.sources);
+            }
+        }
+
+        // Restyle the textbox, list and grid in place, at their place in the
+        // layout so descendant rules apply; the query, selection, scroll
+        // position and running transitions carry on
+        restyle_launcher(
+            &theme,
+            &mut self.textbox,
+            &mut self.listview,
+            &mut self.gridview,
+        );
+        self.style = self.textbox.style().clone();
         log!(
-            "  Updated textbox style: font_size={}, font_family={}",
+            "  Restyled launcher widgets: font_size={}, font_family={}",
             self.style.font_size,
             self.style.font_family
         );
 
-        // Update listview and element styles
-        let listview_style = ListViewStyle::from_theme(&theme, None);
-        let element_style = ElementStyle::from_theme(&theme, None);
-        self.listview.set_style(listview_style);
-        self.listview.set_element_style(element_style);
-        log!("  Updated listview styles");
-
-        // Update gridview style
-        let gridview_style = GridViewStyle::from_theme(&theme, None);
-        self.gridview.set_style(gridview_style);
-        log!("  Updated gridview style");
-
         // Update theme layout settings
+        win32::apply_base_font(&mut self.layout_ctx, &theme);
         self.theme_layout = ThemeLayout::from_theme(&theme);
         log!(
             "  Updated theme layout: border_radius={}, wallpaper_width={}, mainbox_padding={}, children={:?}",
@@ -2706,8 +2307,14 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
             self.theme_layout.mainbox_children
         );
 
+        // Restyle the overlays in place too; a newly added version watermark
+        // still needs its text
+        self.overlay.set_layout_ctx(self.layout_ctx.clone());
+        self.overlay.rebuild(&theme);
+        self.overlay.set_text("version", &format!("v{}", VERSION));
+
         // Update task panel style
-        self.task_panel_style = load_task_panel_style(&theme);
+        self.task_panel_style = TaskPanelStyle::from_theme(&theme);
         log!(
             "  Updated task panel style: enabled={}, compact_width={}, expanded_width={}, icon_size={}",
             self.task_panel_style.enabled,
@@ -2763,6 +2370,7 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
         self.renderer.handle_dpi_change(new_dpi)?;
         self.layout_ctx.dpi = new_dpi as f32;
         self.layout_ctx.scale_factor = new_dpi as f32 / 96.0;
+        self.overlay.set_layout_ctx(self.layout_ctx.clone());
         reposition_window(self.hwnd, &self.config);
         Ok(())
     }
@@ -2785,6 +2393,12 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
         // Clear with transparent (layered window)
         self.renderer.clear(Color::TRANSPARENT);
 
+        // The list, grid and task panel anchor again as they're drawn, and
+        // the task panel anchors the tooltip if an item is still hovered
+        for anchor in [HOVERED_ANCHOR, TASK_PANEL_ANCHOR, LISTVIEW_ANCHOR, GRIDVIEW_ANCHOR] {
+            self.overlay.clear_anchor(anchor);
+        }
+
         // Get client size
         let (width, height) = win32::get_client_size(self.hwnd);
         log!("  Client size: {}x{}", width, height);
@@ -2832,7 +2446,9 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
         // In tail view mode, render only the tail view and skip other widgets
         if self.current_mode.uses_tail_view() {
             let tail_rect = Rect::new(content_x, content_y, content_width, content_height);
-            let _ = self.tailview.render(&mut self.renderer, tail_rect, &self.layout_ctx);
+            let mut list = DisplayList::new();
+            self.tailview.render(&mut list, tail_rect, &self.layout_ctx);
+            let _ = self.renderer.draw_list(&list);
 
             // Draw mainbox border
             let mainbox_bounds = D2D_RECT_F {
@@ -2864,7 +2480,7 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                 content_y,
                 content_width,
                 content_height,
-                scale,
+                &self.layout_ctx,
             )
         } else {
             vec![ChildLayout {
@@ -2919,23 +2535,21 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                 // Keep widget bounds up-to-date for keyboard navigation/scroll logic
                 if self.current_mode.uses_grid_view() {
                     self.gridview.arrange(listview_rect, &self.layout_ctx);
+                    self.overlay.set_anchor(GRIDVIEW_ANCHOR, listview_rect);
                     if !self.gridview.is_empty() {
                         log!("  Rendering gridview ({} items)...", self.gridview.len());
-                        let _ = self.gridview.render(
-                            &mut self.renderer,
-                            listview_rect,
-                            &self.layout_ctx,
-                        );
+                        let mut list = DisplayList::new();
+                        self.gridview.render(&mut list, listview_rect, &self.layout_ctx);
+                        let _ = self.renderer.draw_list(&list);
                     }
                 } else {
                     self.listview.arrange(listview_rect, &self.layout_ctx);
+                    self.overlay.set_anchor(LISTVIEW_ANCHOR, listview_rect);
                     if !self.listview.is_empty() {
                         log!("  Rendering listview ({} items)...", self.listview.len());
-                        let _ = self.listview.render(
-                            &mut self.renderer,
-                            listview_rect,
-                            &self.layout_ctx,
-                        );
+                        let mut list = DisplayList::new();
+                        self.listview.render(&mut list, listview_rect, &self.layout_ctx);
+                        let _ = self.renderer.draw_list(&list);
                     }
                 }
             }
@@ -2956,13 +2570,18 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
             border_width,
         );
 
-        // Draw version watermark in bottom right corner
-        self.draw_version_watermark(width, height, mainbox_padding);
+        // Tooltip, version watermark and other overlays on top of everything
+        self.draw_overlays(width, height);
 
         log!("  Calling end_draw()...");
         let opacity = self.animator.get_opacity();
         let result = self.renderer.end_draw_with_opacity(opacity);
         log!("  end_draw() result: {:?}, opacity: {}", result, opacity);
+
+        // A hover or selection change started state transitions
+        if self.tick_transitions() {
+            self.start_animation_timer();
+        }
     }
 
     /// Draw a widget's background based on its name
@@ -2986,6 +2605,7 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
             }
             "listbox" => {
                 let radii = self.theme_layout.listbox_radii.scaled(scale);
+                let background = self.theme_layout.listbox_bg.clone();
                 let diagonal = if self.current_mode.has_wallpaper_panel() {
                     self.theme_layout.wallpaper_panel_diagonal * scale
                 } else {
@@ -2998,7 +2618,7 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                     bounds.y,
                     bounds.width + diagonal, // Add the diagonal to width to compensate
                     bounds.height,
-                    self.theme_layout.listbox_bg,
+                    &background,
                     radii,
                 );
             }
@@ -3008,42 +2628,12 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
         }
     }
 
-    /// Draw version watermark in bottom right corner
-    fn draw_version_watermark(&mut self, width: i32, height: i32, padding: f32) {
-        use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-
-        let version_text = format!("v{}", VERSION);
-        let font_size = 10.0 * self.layout_ctx.scale_factor;
-
-        // Create a small text format for the version
-        let text_format = match self
-            .renderer
-            .create_text_format("Segoe UI", font_size, false, false)
-        {
-            Ok(fmt) => fmt,
-            Err(_) => return,
-        };
-
-        // Position in bottom right, inside the mainbox padding
-        let text_width = 60.0 * self.layout_ctx.scale_factor;
-        let text_height = 14.0 * self.layout_ctx.scale_factor;
-        let margin = 4.0 * self.layout_ctx.scale_factor;
-
-        let rect = D2D_RECT_F {
-            left: width as f32 - padding - text_width - margin,
-            top: height as f32 - padding - text_height - margin,
-            right: width as f32 - padding - margin,
-            bottom: height as f32 - padding - margin,
-        };
-
-        // Draw with low opacity as a subtle watermark
-        let watermark_color = Color::from_f32(1.0, 1.0, 1.0, 0.3);
-        let _ = self.renderer.draw_text_right_aligned(
-            &version_text,
-            &text_format,
-            rect,
-            watermark_color,
-        );
+    /// Lay out and draw the overlay widgets over the whole window
+    fn draw_overlays(&mut self, width: i32, height: i32) {
+        self.overlay.layout(Rect::new(0.0, 0.0, width as f32, height as f32));
+        let mut list = DisplayList::new();
+        self.overlay.render_overlays(&mut list);
+        let _ = self.renderer.draw_list(&list);
     }
 
     /// Draw the left wallpaper panel (no overlay - clean wallpaper)
@@ -3090,13 +2680,12 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
         let _ = self.renderer.fill_rect(bounds, fallback_bg);
 
         // Draw wallpaper image using cover mode to fill entire panel
+        let blur = self.theme_layout.wallpaper_panel_backdrop_blur * self.layout_ctx.scale_factor;
         self.draw_background_image_in_rect(
             "auto".to_string(),
             ImageScale::Both, // Cover mode - ensures full width and height coverage
-            x,
-            y,
-            width,
-            height,
+            Rect::new(x, y, width, height),
+            blur,
         );
 
         // Draw the fade gradient overlay along the diagonal edge
@@ -3116,7 +2705,7 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
             let base_color = self
                 .theme_layout
                 .wallpaper_panel_fade_color
-                .unwrap_or(self.theme_layout.listbox_bg);
+                .unwrap_or_else(|| self.theme_layout.listbox_bg.color());
 
             // Apply configurable opacity multiplier
             let fade_color = Color::from_f32(
@@ -3319,17 +2908,37 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
             None => return,
         };
 
-        // Show expanded sidebar only when focused
-        let show_expanded = task_panel.focused && task_panel.is_expanded();
+        // Show expanded sidebar only when focused; compact mode shows group
+        // icons only, with a tooltip for the hovered group
+        if !(task_panel.focused && task_panel.is_expanded()) {
+            let mut list = DisplayList::new();
+            task_panel.render_compact(
+                &mut list,
+                Rect::new(panel_x, panel_y, panel_width, panel_height),
+                style,
+                scale,
+            );
+            let tooltip = task_panel.hovered_item.and_then(|index| {
+                let (group, task) = task_panel.get_task_at_index(index)?;
+                let bounds = task_panel.item_states.get(index)?.bounds;
+                let text = task.map_or(&group.name, |t| &t.name).clone();
+                Some((text, bounds))
+            });
+            let panel_bounds = task_panel.panel_bounds;
+            let _ = self.renderer.draw_list(&list);
+            // The tooltip overlay is anchored to the hovered row
+            if let Some((text, bounds)) = tooltip {
+                self.overlay.set_anchor(HOVERED_ANCHOR, bounds);
+                self.overlay.set_text("tooltip", &text);
+            }
+            self.overlay.set_anchor(TASK_PANEL_ANCHOR, panel_bounds);
+            return;
+        }
 
         // Scale dimensions
         let outer_padding = style.padding * scale;
         let inner_padding = style.padding * scale;
-        let panel_width_scaled = if show_expanded {
-            style.expanded_width * scale
-        } else {
-            style.compact_width * scale
-        };
+        let panel_width_scaled = style.expanded_width * scale;
         let panel_content_height = panel_height - 2.0 * outer_padding;
         let border_radius = style.border_radius * scale;
 
@@ -3356,6 +2965,7 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
             panel_width_scaled,
             panel_content_height,
         );
+        self.overlay.set_anchor(TASK_PANEL_ANCHOR, task_panel.panel_bounds);
 
         // Draw panel background
         let bg_rect = D2D_RECT_F {
@@ -3424,104 +3034,6 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
 
         let mut y = panel_top + inner_padding;
 
-        // Compact mode: group icons only
-        if !show_expanded {
-            for (group_idx, group) in groups.iter().enumerate() {
-                let item_index = task_panel.item_states.len();
-                let is_hovered = hovered == Some(item_index);
-                let is_selected = is_focused && selected == Some(item_index);
-
-                // Background behind icon
-                let row_rect = D2D_RECT_F {
-                    left: panel_left + inner_padding,
-                    top: y,
-                    right: panel_left + panel_width_scaled - inner_padding,
-                    bottom: y + row_height,
-                };
-
-                let bg = if is_selected {
-                    style.selected_background_color
-                } else if is_hovered {
-                    style.item_background_color
-                } else {
-                    Color::TRANSPARENT
-                };
-                if bg.a > 0.0 {
-                    let _ = self
-                        .renderer
-                        .fill_rounded_rect(row_rect, item_radius, item_radius, bg);
-                }
-
-                let icon_color = if is_hovered || is_selected {
-                    style.icon_color_hover
-                } else {
-                    style.group_icon_color
-                };
-                // In compact mode, shift icon left a few pixels to visually center it in the hover rect
-                // This compensates for font rendering and visual perception
-                let icon_offset = 3.0 * scale;
-                let icon_rect = D2D_RECT_F {
-                    left: row_rect.left - icon_offset,
-                    top: row_rect.top,
-                    right: row_rect.right - icon_offset,
-                    bottom: row_rect.bottom,
-                };
-                let _ = self.renderer.draw_text_centered(
-                    &group.icon,
-                    &icon_format,
-                    icon_rect,
-                    icon_color,
-                );
-
-                task_panel.item_states.push(TaskItemState {
-                    group_index: group_idx,
-                    task_index: None,
-                    bounds: Rect::new(
-                        row_rect.left,
-                        row_rect.top,
-                        row_rect.right - row_rect.left,
-                        row_height,
-                    ),
-                    is_group_header: true,
-                });
-
-                y += row_height + group_spacing;
-            }
-
-            // Tooltip only in compact mode; collect first to avoid borrow conflicts
-            let tooltip_data: Option<(String, f32, f32)> =
-                if let Some(hovered_idx) = task_panel.hovered_item {
-                    if let Some((group, task)) = task_panel.get_task_at_index(hovered_idx) {
-                        if let Some(item_state) = task_panel.item_states.get(hovered_idx) {
-                            let tooltip_text = if let Some(t) = task {
-                                t.name.clone()
-                            } else {
-                                group.name.clone()
-                            };
-                            Some((
-                                tooltip_text,
-                                item_state.bounds.x + item_state.bounds.width + 6.0 * scale,
-                                item_state.bounds.y,
-                            ))
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-            // Release task_panel borrow before drawing tooltip
-            let _ = task_panel;
-            if let Some((text, x, y)) = tooltip_data {
-                self.draw_tooltip(&text, x, y, scale);
-            }
-
-            return;
-        }
-
         // Expanded mode
         for (group_idx, group) in groups.iter().enumerate() {
             let has_tasks = !group.tasks.is_empty();
@@ -3539,13 +3051,8 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                 bottom: y + row_height,
             };
 
-            let bg = if is_selected {
-                style.selected_background_color
-            } else if is_hovered {
-                style.item_background_color
-            } else {
-                Color::TRANSPARENT
-            };
+            let row_state = task_row_state(is_hovered, is_selected, None);
+            let bg = style.row_background_color.get(row_state);
             if bg.a > 0.0 {
                 let _ = self
                     .renderer
@@ -3561,11 +3068,7 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                 bottom: row_rect.bottom,
             };
 
-            let group_icon_color = if is_hovered || is_selected {
-                style.icon_color_hover
-            } else {
-                style.group_icon_color
-            };
+            let group_icon_color = style.group_icon_color.get(row_state);
             let _ = self.renderer.draw_text_centered(
                 &group.icon,
                 &icon_format,
@@ -3606,11 +3109,7 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                 },
                 bottom: row_rect.bottom,
             };
-            let text_color = if is_hovered || is_selected {
-                style.text_color_hover
-            } else {
-                style.text_color
-            };
+            let text_color = style.text_color.get(row_state);
             let _ = self
                 .renderer
                 .draw_text(&group.name, &text_format, label_rect, text_color);
@@ -3644,13 +3143,11 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                         bottom: y + row_height,
                     };
 
-                    let bg = if is_selected {
-                        style.selected_background_color
-                    } else if is_hovered {
-                        style.item_background_color
-                    } else {
-                        Color::TRANSPARENT
-                    };
+                    // Running tasks are `active`, failed ones `urgent`
+                    let task_key = format!("{}:{}", group.name, task.name);
+                    let status = task_statuses.get(&task_key);
+                    let row_state = task_row_state(is_hovered, is_selected, status);
+                    let bg = style.row_background_color.get(row_state);
                     if bg.a > 0.0 {
                         let _ =
                             self.renderer
@@ -3685,18 +3182,13 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                         right: task_row.right - 6.0 * scale,
                         bottom: task_row.bottom,
                     };
-                    let text_color = if is_hovered || is_selected {
-                        style.text_color_hover
-                    } else {
-                        style.text_color
-                    };
+                    let text_color = style.text_color.get(row_state);
                     let _ =
                         self.renderer
                             .draw_text(&task.name, &text_format, label_rect, text_color);
 
                     // Status indicator dot (if task has run status)
-                    let task_key = format!("{}:{}", group.name, task.name);
-                    if let Some(status) = task_statuses.get(&task_key) {
+                    if let Some(status) = status {
                         // Choose color based on status
                         let (dot_color, should_pulse) = match status {
                             TaskStatus::Running => (
@@ -3757,70 +3249,19 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
         task_panel.apply_pending_selection();
     }
 
-    /// Draw a tooltip near the given position
-    fn draw_tooltip(&mut self, text: &str, x: f32, y: f32, scale: f32) {
-        use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-
-        let font_size = 12.0 * scale;
-        let padding = 8.0 * scale; // Increased padding for better appearance
-        let bg_color = Color::from_f32(0.1, 0.1, 0.1, 0.95);
-        let text_color = Color::WHITE;
-
-        let text_format = match self.renderer.create_text_format(
-            &self.task_panel_style.text_font_family,
-            font_size,
-            false,
-            false,
-        ) {
-            Ok(f) => f,
-            Err(_) => return,
-        };
-
-        // Measure text - use large max width to prevent wrapping
-        let (text_width, text_height) =
-            match self
-                .renderer
-                .measure_text(text, &text_format, 500.0 * scale, 50.0 * scale)
-            {
-                Ok((w, h)) => (w, h),
-                Err(_) => return,
-            };
-
-        // Draw background
-        let bg_rect = D2D_RECT_F {
-            left: x,
-            top: y,
-            right: x + text_width + 2.0 * padding,
-            bottom: y + text_height + 2.0 * padding,
-        };
-        let _ = self
-            .renderer
-            .fill_rounded_rect(bg_rect, 4.0 * scale, 4.0 * scale, bg_color);
-
-        // Draw text
-        let text_rect = D2D_RECT_F {
-            left: x + padding,
-            top: y + padding,
-            right: x + padding + text_width,
-            bottom: y + padding + text_height,
-        };
-        let _ = self
-            .renderer
-            .draw_text(text, &text_format, text_rect, text_color);
-    }
-
-    /// Draw the right panel with solid color and per-corner radii
+    /// Draw the right panel with a color or gradient and per-corner radii
     fn draw_right_panel(
         &mut self,
         x: f32,
         y: f32,
         width: f32,
         height: f32,
-        color: crate::theme::types::Color,
+        paint: &Paint,
         radii: CornerRadii,
     ) {
         use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
 
+        let color = paint.color();
         log!(
             "  draw_right_panel: x={}, y={}, w={}, h={}, color=({},{},{},{}), radii=({},{},{},{})",
             x,
@@ -3845,21 +3286,25 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
         };
 
         // Use per-corner radii
-        let _ = self
-            .renderer
-            .fill_rounded_rect_corners(bounds, radii, color);
+        let _ = self.renderer.fill_paint(bounds, radii, paint);
     }
 
     /// Draw the background image in a specific rectangle, loading and caching as needed
+    ///
+    /// `blur` is a blur radius in pixels (0 = sharp), see `Renderer::blur_bitmap`.
     fn draw_background_image_in_rect(
         &mut self,
         path: String,
         scale: ImageScale,
-        x: f32,
-        y: f32,
-        width: f32,
-        height: f32,
+        rect: Rect,
+        blur: f32,
     ) {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = rect;
         log!(
             "  draw_background_image_in_rect: path={}, x={}, y={}, w={}, h={}",
             path,
@@ -3889,7 +3334,9 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
 
         // Check if we need to reload the bitmap
         let need_reload = match &self.background_bitmap_path {
-            Some(cached_path) => cached_path != &resolved_path,
+            Some(cached_path) => {
+                cached_path != &resolved_path || self.background_bitmap_blur != blur
+            }
             None => true,
         };
 
@@ -3944,8 +3391,24 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                 }
             };
 
+            // Blur it for backdrop-blur, converting the radius to image pixels
+            // for the image as it covers the rect
+            let bitmap = if blur > 0.0 {
+                let cover = (width / loaded.width() as f32).max(height / loaded.height() as f32);
+                match self.renderer.blur_bitmap(&bitmap, blur / cover) {
+                    Ok(blurred) => blurred.unwrap_or(bitmap),
+                    Err(e) => {
+                        log!("  Failed to blur bitmap: {:?}", e);
+                        bitmap
+                    }
+                }
+            } else {
+                bitmap
+            };
+
             self.background_bitmap = Some(bitmap);
             self.background_bitmap_path = Some(resolved_path);
+            self.background_bitmap_blur = blur;
         }
 
         // Draw the cached bitmap in the specified rect
@@ -4103,7 +3566,7 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                 // Reset textbox and show all apps
                 self.textbox.clear();
                 self.listview.set_items(self.all_items.clone());
-                self.textbox.set_state(WidgetState::Focused);
+                self.textbox.set_state(WidgetState::FOCUSED);
             }
             Mode::ThemePicker => {
                 log!("  ThemePicker mode - scanning HyDE themes");
@@ -4114,15 +3577,21 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                 let items: Vec<GridItem> = themes
                     .into_iter()
                     .map(|theme| {
-                        let mut item = GridItem::new(&theme.name, &theme.name);
+                        let mut item =
+                            GridItem::new(&theme.name, theme.path.to_string_lossy().to_string());
                         if let Some(thumb) = theme.thumbnail {
                             item = item.with_image(thumb.to_string_lossy().to_string());
                         }
+                        // The theme in use is styled as `gridview.active`
+                        if self.current_theme.as_deref() == Some(theme.name.as_str()) {
+                            item = item.with_state(WidgetState::ACTIVE);
+                        }
                         item
                     })
                     .collect();
 
                 log!("  Loaded {} themes into grid", items.len());
+                self.all_grid_items = items.clone();
                 self.gridview.set_items(items);
             }
             Mode::WallpaperPicker => {
@@ -4151,21 +3620,48 @@ Get-Content -Path '{}' -Wait -Tail 50"#,
                 };
 
                 log!("  Loaded {} wallpapers into grid", items.len());
+                self.all_grid_items = items.clone();
                 self.gridview.set_items(items);
             }
             Mode::TailView => {
                 // TailView is set up via enter_tail_view(), not here
                 // Just keep the textbox unfocused
-                self.textbox.set_state(WidgetState::Normal);
+                self.textbox.set_state(WidgetState::NORMAL);
             }
         }
 
+        self.restore_selection();
+
         // Reset task panel focus when switching modes
         if let Some(ref mut task_panel) = self.task_panel {
             task_panel.set_focus(false);
         }
     }
 
+    /// Select the item chosen last time in this mode (persisted across
+    /// restarts)
+    fn restore_selection(&mut self) {
+        let state = self.app_state.borrow();
+        let Some(data) = state.preferred_selection(self.current_mode) else {
+            return;
+        };
+        if self.current_mode.uses_grid_view() {
+            let index = self
+                .all_grid_items
+                .iter()
+                .position(|item| item.user_data == data);
+            if let Some(index) = index {
+                self.gridview.select(index);
+            }
+        } else if let Some(index) = self
+            .all_items
+            .iter()
+            .position(|item| item.user_data == data)
+        {
+            self.listview.select(index);
+        }
+    }
+
     /// Hide the window
     pub fn hide(&mut self) {
         if !self.is_visible {
@@ -0,0 +1,844 @@
+//! Command-line tools that run without opening a window (no Windows dependencies)
+//!
+//! ```text
+//! wolfy --check-theme [--rofi] <file>...   Validate a theme and its imports
+//! wolfy --generate-theme <dir> [<file>]     Generate a theme from a HyDE theme
+//! wolfy --fmt [--check] <file>...           Format theme files in place
+//! wolfy --explain <widget[:state]> <property> [<file>...]
+//!                                           Show where a property's value comes from
+//! wolfy --render [<option>...] <out.png>    Render a mode to a PNG without a window
+//!                                           (not on Windows)
+//! wolfy --help                              Show this help
+//! ```
+//!
+//! Any other arguments start the launcher as usual.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::log::find_config_file;
+use crate::mode::Mode;
+#[cfg(not(windows))]
+use crate::screenshot::{self, Fixtures};
+#[cfg(not(windows))]
+use crate::tasks::TasksConfig;
+use crate::theme::schema::Severity;
+use crate::theme::{format, hyde};
+use crate::theme::{Dialect, ThemeTree};
+
+const USAGE: &str = "\
+Usage: wolfy [COMMAND]
+
+Without a command, starts the launcher (Windows only).
+
+Commands:
+  --check-theme [--rofi] <file>...
+                            Validate a theme and its imports against the schema.
+                            Several files are layered like the launcher does,
+                            e.g. core.rasi themes/catppuccin_mocha.rasi
+                            With --rofi, the files are stock rofi themes and
+                            are checked as Wolfy would translate them
+  --generate-theme <dir> [<file>]
+                            Generate a Wolfy theme from a HyDE theme directory
+                            (its rofi.theme, kitty.theme and waybar.theme),
+                            e.g. \"hyde/themes/Tokyo Night\" themes/tokyo_night.rasi
+                            Prints it if no output file is given
+  --fmt [--check] <file>...
+                            Rewrite theme files in the canonical format,
+                            keeping comments (also `wolfy fmt`)
+                            With --check, only list the files that would
+                            change, failing if there are any
+  --explain <widget[:state]> <property> [<file>...]
+                            Show how a widget's property gets its value: the
+                            rules that set it with their file, line and layer,
+                            and the global tokens it resolves through,
+                            e.g. element:selected background-color
+                            The files are layered like for --check-theme;
+                            without any, core.rasi and the default theme
+                            from the config directory are used
+  --render [<option>...] <out.png>
+                            Render a mode with sample content to a PNG, without
+                            a window (also `wolfy render`; not on Windows,
+                            where the launcher draws it for real). Options:
+                              --theme <file>    A theme layered over core.rasi
+                                                from the config directory,
+                                                can be repeated (default:
+                                                themes/catppuccin_mocha.rasi)
+                              --mode <mode>     launcher, theme-picker or
+                                                wallpaper-picker
+                              --size <w>x<h>    Window size in logical pixels
+                                                (default: as the launcher
+                                                sizes the mode's window)
+                              --scale <factor>  DPI scale factor (default: 1)
+                              --images <dir>    PNGs for the picker items and
+                                                the wallpaper (default:
+                                                generated gradients)
+                              --tasks <file>    A tasks.toml for the task panel
+                            The same options always give the same image, e.g.
+                            for golden-image tests
+  --help                    Show this help
+";
+
+/// Exit codes
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_ISSUES: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+/// A command-line command
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Theme layers, later files overriding earlier ones
+    CheckTheme(Vec<PathBuf>, Dialect),
+    /// HyDE theme directory, and the file to write (stdout if None)
+    GenerateTheme(PathBuf, Option<PathBuf>),
+    /// Theme files to format, and whether to only check them
+    Format(Vec<PathBuf>, bool),
+    /// Widget path, state, property and the theme layers to explain it in
+    /// (the installed core.rasi and default theme if empty)
+    Explain {
+        widget: String,
+        state: Option<String>,
+        property: String,
+        files: Vec<PathBuf>,
+    },
+    /// Render a mode with sample content to a PNG
+    Render {
+        /// Themes layered over core.rasi (the default theme if empty)
+        themes: Vec<PathBuf>,
+        mode: Mode,
+        /// Window size in logical pixels (the mode's window size if None)
+        size: Option<(u32, u32)>,
+        scale: f32,
+        /// Directory of fixture images (generated ones if None)
+        images: Option<PathBuf>,
+        tasks: Option<PathBuf>,
+        output: PathBuf,
+    },
+    Help,
+    /// A recognized command with bad arguments
+    Usage(String),
+}
+
+impl Command {
+    /// Parse command-line arguments (without the program name)
+    ///
+    /// Returns None if the arguments don't name a command, in which case
+    /// the launcher should start.
+    pub fn parse(args: &[String]) -> Option<Command> {
+        let (first, rest) = args.split_first()?;
+        match first.as_str() {
+            "--check-theme" => {
+                let (dialect, files) = match rest.split_first() {
+                    Some((flag, files)) if flag == "--rofi" => (Dialect::Rofi, files),
+                    _ => (Dialect::Wolfy, rest),
+                };
+                Some(if files.is_empty() {
+                    Command::Usage("--check-theme needs at least one file".to_string())
+                } else {
+                    Command::CheckTheme(files.iter().map(PathBuf::from).collect(), dialect)
+                })
+            }
+            "--generate-theme" => Some(match rest {
+                [dir] => Command::GenerateTheme(PathBuf::from(dir), None),
+                [dir, file] => {
+                    Command::GenerateTheme(PathBuf::from(dir), Some(PathBuf::from(file)))
+                }
+                _ => Command::Usage(
+                    "--generate-theme needs a HyDE theme directory and an optional output file"
+                        .to_string(),
+                ),
+            }),
+            "--fmt" | "fmt" => {
+                let (check, files) = match rest.split_first() {
+                    Some((flag, files)) if flag == "--check" => (true, files),
+                    _ => (false, rest),
+                };
+                Some(if files.is_empty() {
+                    Command::Usage("--fmt needs at least one file".to_string())
+                } else {
+                    Command::Format(files.iter().map(PathBuf::from).collect(), check)
+                })
+            }
+            "--explain" | "explain" => Some(match rest {
+                [target, property, files @ ..] => {
+                    // `element:selected`, `element.selected` or `element:selected:urgent`
+                    let (widget, state) = match target.split_once([':', '.']) {
+                        Some((widget, state)) => (widget, Some(state.replace(':', "."))),
+                        None => (target.as_str(), None),
+                    };
+                    Command::Explain {
+                        widget: widget.to_string(),
+                        state,
+                        property: property.clone(),
+                        files: files.iter().map(PathBuf::from).collect(),
+                    }
+                }
+                _ => Command::Usage("--explain needs a widget and a property".to_string()),
+            }),
+            "--render" | "render" => Some(parse_render(rest).unwrap_or_else(Command::Usage)),
+            "--help" | "-h" => Some(Command::Help),
+            _ => None,
+        }
+    }
+
+    /// Run the command, writing its report to stdout; returns the exit code
+    pub fn run(&self) -> i32 {
+        self.run_with(&mut io::stdout().lock())
+    }
+
+    /// Run the command, writing its report to `out`
+    pub fn run_with(&self, out: &mut impl Write) -> i32 {
+        let result = match self {
+            Command::CheckTheme(paths, dialect) => check_theme(paths, *dialect, out),
+            Command::GenerateTheme(dir, file) => generate_theme(dir, file.as_deref(), out),
+            Command::Format(paths, check) => format_themes(paths, *check, out),
+            Command::Explain {
+                widget,
+                state,
+                property,
+                files,
+            } => explain(widget, state.as_deref(), property, files, out),
+            #[cfg(not(windows))]
+            Command::Render {
+                themes,
+                mode,
+                size,
+                scale,
+                images,
+                tasks,
+                output,
+            } => render(
+                themes,
+                *mode,
+                *size,
+                *scale,
+                images.as_deref(),
+                tasks.as_deref(),
+                output,
+                out,
+            ),
+            #[cfg(windows)]
+            Command::Render { .. } => {
+                writeln!(out, "error: --render isn't available on Windows").map(|_| EXIT_USAGE)
+            }
+            Command::Help => write!(out, "{}", USAGE).map(|_| EXIT_OK),
+            Command::Usage(message) => {
+                write!(out, "error: {}\n\n{}", message, USAGE).map(|_| EXIT_USAGE)
+            }
+        };
+        result.unwrap_or(EXIT_USAGE)
+    }
+}
+
+/// Parse the options and output file of `--render`
+fn parse_render(args: &[String]) -> Result<Command, String> {
+    let mut themes = Vec::new();
+    let mut mode = Mode::Launcher;
+    let mut size = None;
+    let mut scale = 1.0;
+    let mut images = None;
+    let mut tasks = None;
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            if output.replace(PathBuf::from(arg)).is_some() {
+                return Err("--render takes a single output file".to_string());
+            }
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value", arg))?;
+        match arg.as_str() {
+            "--theme" => themes.push(PathBuf::from(value)),
+            "--mode" => {
+                mode = Mode::from_state_key(value)
+                    .filter(|mode| !mode.uses_tail_view())
+                    .ok_or_else(|| format!("unknown mode `{}`", value))?
+            }
+            "--size" => {
+                let parsed = value
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|&(w, h)| w > 0 && h > 0);
+                size = Some(
+                    parsed.ok_or_else(|| format!("bad size `{}`, expected e.g. 928x480", value))?,
+                );
+            }
+            "--scale" => {
+                scale = value
+                    .parse()
+                    .ok()
+                    .filter(|scale: &f32| *scale > 0.0 && *scale <= 8.0)
+                    .ok_or_else(|| format!("bad scale factor `{}`", value))?
+            }
+            "--images" => images = Some(PathBuf::from(value)),
+            "--tasks" => tasks = Some(PathBuf::from(value)),
+            _ => return Err(format!("unknown --render option {}", arg)),
+        }
+    }
+
+    Ok(Command::Render {
+        themes,
+        mode,
+        size,
+        scale,
+        images,
+        tasks,
+        output: output.ok_or("--render needs an output file")?,
+    })
+}
+
+/// Parse, resolve and lint theme files, layered in order
+///
+/// Exits with `EXIT_ISSUES` if there are errors; warnings alone pass.
+fn check_theme(paths: &[PathBuf], dialect: Dialect, out: &mut impl Write) -> io::Result<i32> {
+    let mut theme = ThemeTree::new();
+    for path in paths {
+        let layer = match dialect {
+            Dialect::Wolfy => ThemeTree::load(path),
+            Dialect::Rofi => ThemeTree::load_rofi(path),
+        };
+        match layer {
+            Ok(layer) => theme.merge(layer),
+            Err(e) => {
+                writeln!(out, "error: {}: {}", path.display(), e)?;
+                return Ok(EXIT_ISSUES);
+            }
+        }
+    }
+
+    for diagnostic in &theme.diagnostics {
+        writeln!(out, "error: {}\n", diagnostic)?;
+    }
+    for warning in &theme.warnings {
+        writeln!(out, "warning: {}\n", warning)?;
+    }
+
+    let issues = theme.validate();
+    for issue in &issues {
+        writeln!(out, "{}", issue)?;
+    }
+
+    let errors = theme.diagnostics.len()
+        + issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error)
+            .count();
+    let warnings = issues.len() + theme.diagnostics.len() + theme.warnings.len() - errors;
+    let names: Vec<_> = paths.iter().map(|p| p.display().to_string()).collect();
+    writeln!(
+        out,
+        "{}: {} error(s), {} warning(s) in {} file(s)",
+        names.join(" + "),
+        errors,
+        warnings,
+        theme.sources.len()
+    )?;
+
+    Ok(if errors > 0 { EXIT_ISSUES } else { EXIT_OK })
+}
+
+/// Print how a widget's property gets its value in the layered theme files
+fn explain(
+    widget: &str,
+    state: Option<&str>,
+    property: &str,
+    paths: &[PathBuf],
+    out: &mut impl Write,
+) -> io::Result<i32> {
+    let paths = if paths.is_empty() {
+        vec![
+            find_config_file("core.rasi"),
+            find_config_file("themes/catppuccin_mocha.rasi"),
+        ]
+    } else {
+        paths.to_vec()
+    };
+
+    let mut theme = ThemeTree::new();
+    for path in &paths {
+        match ThemeTree::load(path) {
+            Ok(layer) => theme.merge(layer),
+            Err(e) => {
+                writeln!(out, "error: {}: {}", path.display(), e)?;
+                return Ok(EXIT_ISSUES);
+            }
+        }
+    }
+
+    write!(out, "{}", theme.explain(widget, state, property))?;
+    Ok(EXIT_OK)
+}
+
+/// Render a mode to a PNG file with the layered themes and fixtures
+#[cfg(not(windows))]
+#[allow(clippy::too_many_arguments)]
+fn render(
+    themes: &[PathBuf],
+    mode: Mode,
+    size: Option<(u32, u32)>,
+    scale: f32,
+    images: Option<&Path>,
+    tasks: Option<&Path>,
+    output: &Path,
+    out: &mut impl Write,
+) -> io::Result<i32> {
+    // A theme can be complete on its own, so core.rasi is only layered
+    // under it if it's installed
+    let mut paths: Vec<_> = Some(find_config_file("core.rasi"))
+        .filter(|core| core.exists())
+        .into_iter()
+        .collect();
+    if themes.is_empty() {
+        paths.push(find_config_file("themes/catppuccin_mocha.rasi"));
+    }
+    paths.extend(themes.iter().cloned());
+
+    let mut theme = ThemeTree::new();
+    for path in &paths {
+        match ThemeTree::load(path) {
+            Ok(layer) => theme.merge(layer),
+            Err(e) => {
+                writeln!(out, "error: {}: {}", path.display(), e)?;
+                return Ok(EXIT_ISSUES);
+            }
+        }
+    }
+
+    let mut fixtures = Fixtures::sample();
+    if let Some(dir) = images {
+        if let Err(e) = fixtures.load_images(dir) {
+            writeln!(out, "error: {}: {}", dir.display(), e)?;
+            return Ok(EXIT_ISSUES);
+        }
+    }
+    if let Some(path) = tasks {
+        let config = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|source| toml::from_str::<TasksConfig>(&source).map_err(|e| e.to_string()));
+        match config {
+            Ok(config) => fixtures.tasks = Some(config),
+            Err(e) => {
+                writeln!(out, "error: {}: {}", path.display(), e)?;
+                return Ok(EXIT_ISSUES);
+            }
+        }
+    }
+
+    let size = size.unwrap_or_else(|| screenshot::window_size(&theme, mode));
+    let Some(pixmap) = screenshot::render(&theme, mode, &fixtures, size, scale) else {
+        writeln!(
+            out,
+            "error: can't render a {}x{} image at scale {}",
+            size.0, size.1, scale
+        )?;
+        return Ok(EXIT_ISSUES);
+    };
+    if let Err(e) = pixmap.encode_png().and_then(|data| fs::write(output, data)) {
+        writeln!(out, "error: {}: {}", output.display(), e)?;
+        return Ok(EXIT_ISSUES);
+    }
+    writeln!(
+        out,
+        "wrote {} ({}x{})",
+        output.display(),
+        pixmap.width(),
+        pixmap.height()
+    )?;
+    Ok(EXIT_OK)
+}
+
+/// Generate a theme from a HyDE theme directory, to `file` or `out`
+fn generate_theme(dir: &Path, file: Option<&Path>, out: &mut impl Write) -> io::Result<i32> {
+    let source = match hyde::generate(dir) {
+        Ok(source) => source,
+        Err(e) => {
+            writeln!(out, "error: {}", e)?;
+            return Ok(EXIT_ISSUES);
+        }
+    };
+
+    match file {
+        Some(file) => match fs::write(file, source) {
+            Ok(()) => writeln!(out, "wrote {}", file.display())?,
+            Err(e) => {
+                writeln!(out, "error: {}: {}", file.display(), e)?;
+                return Ok(EXIT_ISSUES);
+            }
+        },
+        None => write!(out, "{}", source)?,
+    }
+    Ok(EXIT_OK)
+}
+
+/// Format theme files in place, or with `check` list those that would change
+///
+/// Exits with `EXIT_ISSUES` if a file can't be formatted, or when checking
+/// if any file isn't formatted.
+fn format_themes(paths: &[PathBuf], check: bool, out: &mut impl Write) -> io::Result<i32> {
+    let mut code = EXIT_OK;
+    for path in paths {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                writeln!(out, "error: {}: {}", path.display(), e)?;
+                code = EXIT_ISSUES;
+                continue;
+            }
+        };
+        let formatted = match format::format(&source, Some(path)) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                writeln!(out, "error: {}: {}", path.display(), e)?;
+                code = EXIT_ISSUES;
+                continue;
+            }
+        };
+
+        if formatted == source {
+            continue;
+        }
+        if check {
+            writeln!(out, "would reformat {}", path.display())?;
+            code = EXIT_ISSUES;
+        } else if let Err(e) = fs::write(path, formatted) {
+            writeln!(out, "error: {}: {}", path.display(), e)?;
+            code = EXIT_ISSUES;
+        } else {
+            writeln!(out, "formatted {}", path.display())?;
+        }
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(not(windows))]
+    use crate::render::software::Pixmap;
+    use std::fs;
+    use std::path::Path;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(Command::parse(&args(&[])), None);
+        assert_eq!(Command::parse(&args(&["--restore-mode"])), None);
+        assert_eq!(
+            Command::parse(&args(&["--check-theme", "a.rasi"])),
+            Some(Command::CheckTheme(
+                vec![PathBuf::from("a.rasi")],
+                Dialect::Wolfy
+            ))
+        );
+        assert_eq!(
+            Command::parse(&args(&["--check-theme", "core.rasi", "theme.rasi"])),
+            Some(Command::CheckTheme(
+                vec![PathBuf::from("core.rasi"), PathBuf::from("theme.rasi")],
+                Dialect::Wolfy
+            ))
+        );
+        assert_eq!(
+            Command::parse(&args(&["--check-theme", "--rofi", "rofi.theme"])),
+            Some(Command::CheckTheme(
+                vec![PathBuf::from("rofi.theme")],
+                Dialect::Rofi
+            ))
+        );
+        assert!(matches!(
+            Command::parse(&args(&["--check-theme"])),
+            Some(Command::Usage(_))
+        ));
+        assert!(matches!(
+            Command::parse(&args(&["--check-theme", "--rofi"])),
+            Some(Command::Usage(_))
+        ));
+        assert_eq!(
+            Command::parse(&args(&[
+                "--generate-theme",
+                "hyde/themes/Nord",
+                "nord.rasi"
+            ])),
+            Some(Command::GenerateTheme(
+                PathBuf::from("hyde/themes/Nord"),
+                Some(PathBuf::from("nord.rasi"))
+            ))
+        );
+        assert!(matches!(
+            Command::parse(&args(&["--generate-theme"])),
+            Some(Command::Usage(_))
+        ));
+        assert_eq!(
+            Command::parse(&args(&["fmt", "--check", "a.rasi"])),
+            Some(Command::Format(vec![PathBuf::from("a.rasi")], true))
+        );
+        assert_eq!(
+            Command::parse(&args(&["--fmt", "a.rasi"])),
+            Some(Command::Format(vec![PathBuf::from("a.rasi")], false))
+        );
+        assert!(matches!(
+            Command::parse(&args(&["--fmt", "--check"])),
+            Some(Command::Usage(_))
+        ));
+        assert_eq!(
+            Command::parse(&args(&[
+                "--explain",
+                "element:selected",
+                "background-color"
+            ])),
+            Some(Command::Explain {
+                widget: "element".to_string(),
+                state: Some("selected".to_string()),
+                property: "background-color".to_string(),
+                files: vec![],
+            })
+        );
+        assert_eq!(
+            Command::parse(&args(&[
+                "explain",
+                "listview element:selected:urgent",
+                "text-color",
+                "a.rasi"
+            ])),
+            Some(Command::Explain {
+                widget: "listview element".to_string(),
+                state: Some("selected.urgent".to_string()),
+                property: "text-color".to_string(),
+                files: vec![PathBuf::from("a.rasi")],
+            })
+        );
+        assert!(matches!(
+            Command::parse(&args(&["--explain", "element"])),
+            Some(Command::Usage(_))
+        ));
+        assert_eq!(
+            Command::parse(&args(&[
+                "render",
+                "--theme",
+                "core.rasi",
+                "--theme",
+                "a.rasi",
+                "--mode",
+                "theme-picker",
+                "--size",
+                "800x600",
+                "--scale",
+                "1.5",
+                "out.png"
+            ])),
+            Some(Command::Render {
+                themes: vec![PathBuf::from("core.rasi"), PathBuf::from("a.rasi")],
+                mode: Mode::ThemePicker,
+                size: Some((800, 600)),
+                scale: 1.5,
+                images: None,
+                tasks: None,
+                output: PathBuf::from("out.png"),
+            })
+        );
+        for bad in [
+            &["--render"][..],
+            &["--render", "a.png", "b.png"],
+            &["--render", "--mode", "tail-view", "a.png"],
+            &["--render", "--size", "800", "a.png"],
+            &["--render", "--scale", "0", "a.png"],
+            &["--render", "--colors", "x", "a.png"],
+            &["--render", "a.png", "--theme"],
+        ] {
+            assert!(
+                matches!(Command::parse(&args(bad)), Some(Command::Usage(_))),
+                "{:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_theme() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("theme.rasi");
+
+        let colors = dir.path().join("colors.rasi");
+        fs::write(&path, "element { text-color: accent; }").unwrap();
+        fs::write(&colors, "* { accent: #fff; }").unwrap();
+        let check = |paths: &[&Path]| {
+            let mut out = Vec::new();
+            let paths = paths.iter().map(|p| p.to_path_buf()).collect();
+            let code = Command::CheckTheme(paths, Dialect::Wolfy).run_with(&mut out);
+            (code, String::from_utf8(out).unwrap())
+        };
+
+        // The token comes from the second layer
+        let (code, report) = check(&[&path, &colors]);
+        assert_eq!(code, EXIT_OK);
+        assert!(report.ends_with("0 error(s), 0 warning(s) in 2 file(s)\n"));
+        assert_eq!(check(&[&path]).0, EXIT_ISSUES);
+
+        fs::write(
+            &path,
+            "element { text-colour: #fff; font-size: red; }\nwindow { width 1; }",
+        )
+        .unwrap();
+        let (code, report) = check(&[&path]);
+        assert_eq!(code, EXIT_ISSUES);
+        assert!(report.contains(":2:16: unexpected `1`"), "{}", report);
+        assert!(report.contains("error: element { font-size }: expected a number"));
+        assert!(report.contains("warning: element { text-colour }: unknown property"));
+        assert!(report.ends_with("2 error(s), 1 warning(s) in 1 file(s)\n"));
+    }
+
+    #[test]
+    fn test_check_rofi_theme() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rofi.theme");
+        fs::write(
+            &path,
+            "$HOME/.config/rofi/theme.rasi\n\
+             * { main-bg: #11111be6; border-color: transparent; }\n\
+             window { border: 2px solid; fullscreen: false; }\n\
+             element-text { text-color: inherit; }",
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        let code = Command::CheckTheme(vec![path], Dialect::Rofi).run_with(&mut out);
+        let report = String::from_utf8(out).unwrap();
+        assert_eq!(code, EXIT_OK, "{}", report);
+        assert!(report.contains("rofi property `fullscreen` is not supported"));
+        assert!(report.ends_with("0 error(s), 1 warning(s) in 1 file(s)\n"));
+    }
+
+    #[test]
+    fn test_generate_theme() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let hyde = Path::new(env!("CARGO_MANIFEST_DIR")).join("hyde/themes/Tokyo Night");
+        let file = dir.path().join("tokyo_night.rasi");
+
+        let mut out = Vec::new();
+        let code = Command::GenerateTheme(hyde, Some(file.clone())).run_with(&mut out);
+        assert_eq!(code, EXIT_OK);
+        assert!(String::from_utf8(out).unwrap().starts_with("wrote "));
+        let theme = ThemeTree::load(&file).unwrap();
+        assert!(theme.globals.contains_key("accent-primary"));
+
+        let mut out = Vec::new();
+        let code = Command::GenerateTheme(dir.path().to_path_buf(), None).run_with(&mut out);
+        assert_eq!(code, EXIT_ISSUES);
+        assert!(String::from_utf8(out).unwrap().contains("rofi.theme"));
+    }
+
+    #[test]
+    fn test_format_themes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("theme.rasi");
+        fs::write(
+            &path,
+            "/* Colors */
+* { accent:#fff; }",
+        )
+        .unwrap();
+        let run = |check| {
+            let mut out = Vec::new();
+            let code = Command::Format(vec![path.clone()], check).run_with(&mut out);
+            (code, String::from_utf8(out).unwrap())
+        };
+
+        let (code, report) = run(true);
+        assert_eq!(code, EXIT_ISSUES);
+        assert!(report.starts_with("would reformat "), "{}", report);
+
+        let (code, report) = run(false);
+        assert_eq!(code, EXIT_OK);
+        assert!(report.starts_with("formatted "), "{}", report);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "/* Colors */\n* {\n    accent: #ffffff;\n}\n"
+        );
+        assert_eq!(run(true), (EXIT_OK, String::new()));
+
+        fs::write(&path, "* { accent #fff; }").unwrap();
+        let (code, report) = run(false);
+        assert_eq!(code, EXIT_ISSUES);
+        assert!(report.contains("unexpected `#fff`"), "{}", report);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_render() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let output = dir.path().join("launcher.png");
+        let tasks = dir.path().join("tasks.toml");
+        fs::write(&tasks, "[[groups]]\nname = \"Dev\"\nicon = \"D\"\n").unwrap();
+        let render = |tasks: &Path| {
+            let mut out = Vec::new();
+            let code = Command::Render {
+                themes: vec![root.join("core.rasi"), root.join("themes/tokyo_night.rasi")],
+                mode: Mode::Launcher,
+                size: Some((400, 300)),
+                scale: 2.0,
+                images: None,
+                tasks: Some(tasks.to_path_buf()),
+                output: output.clone(),
+            }
+            .run_with(&mut out);
+            (code, String::from_utf8(out).unwrap())
+        };
+
+        let (code, report) = render(&tasks);
+        assert_eq!(code, EXIT_OK, "{}", report);
+        assert!(report.ends_with("launcher.png (800x600)\n"), "{}", report);
+        let first = fs::read(&output).unwrap();
+        let image = Pixmap::decode_png(&first).unwrap();
+        assert_eq!((image.width(), image.height()), (800, 600));
+        // Rendering is deterministic, for golden images
+        render(&tasks);
+        assert_eq!(fs::read(&output).unwrap(), first);
+
+        fs::write(&tasks, "[[groups]]\nname = 1\n").unwrap();
+        let (code, report) = render(&tasks);
+        assert_eq!(code, EXIT_ISSUES);
+        assert!(report.starts_with("error: "), "{}", report);
+    }
+
+    #[test]
+    fn test_explain() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let core = dir.path().join("core.rasi");
+        let colors = dir.path().join("colors.rasi");
+        fs::write(
+            &core,
+            "element { background-color: transparent; }\n\
+             element.selected { background-color: accent; }",
+        )
+        .unwrap();
+        fs::write(&colors, "* { accent: #7aa2f7; }").unwrap();
+
+        let mut out = Vec::new();
+        let code = Command::Explain {
+            widget: "element".to_string(),
+            state: Some("selected".to_string()),
+            property: "background-color".to_string(),
+            files: vec![core, colors],
+        }
+        .run_with(&mut out);
+        let report = String::from_utf8(out).unwrap();
+        assert_eq!(code, EXIT_OK);
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(lines.len(), 5, "{}", report);
+        assert_eq!(lines[0], "element.selected { background-color }");
+        assert!(lines[1].starts_with("  element.selected { background-color: accent; }  "));
+        assert!(lines[1].ends_with("core.rasi:2 (layer 0), wins"));
+        assert!(lines[2].ends_with("core.rasi:1 (layer 0), overridden"));
+        assert!(lines[3].starts_with("  accent: #7aa2f7;  "));
+        assert!(lines[3].ends_with("colors.rasi:1 (layer 1)"));
+        assert_eq!(lines[4], "  = #7aa2f7");
+    }
+}
@@ -15,12 +15,17 @@ use crate::app::VERSION;
 use crate::log::find_config_file;
 use crate::mode::Mode;
 use crate::platform::win32::{
-    get_monitor_width, invalidate_window, resize_window, translate_message, Renderer, WindowConfig,
+    apply_base_font, get_monitor_size, get_monitor_width, invalidate_window,
+    monitor_layout_context, resize_window, translate_message, Renderer, WindowConfig,
 };
+use crate::query::Query;
+use crate::render::DisplayList;
 use crate::state::{scan_hyde_themes, scan_theme_wallpapers, AppState, HydeTheme};
 use crate::theme::tree::ThemeTree;
 use crate::theme::types::{Color, LayoutContext, Rect};
-use crate::widget::{EventResult, GridItem, GridView, GridViewStyle, Widget, WidgetState};
+use crate::widget::{
+    window_size, EventResult, GridItem, GridView, GridViewStyle, UITree, Widget, WidgetState,
+};
 
 /// Animation timer ID
 const TIMER_ANIMATION: usize = 3;
@@ -96,6 +101,13 @@ pub struct GridWindow {
     /// Window dimensions from theme
     window_width: i32,
     window_height: i32,
+    /// All items for the current mode (unfiltered)
+    all_items: Vec<GridItem>,
+    /// Type-to-filter query text
+    filter: String,
+    /// Themed overlays drawn over the grid, like the version watermark and
+    /// the filter query
+    overlay: UITree,
 }
 
 impl GridWindow {
@@ -110,11 +122,15 @@ impl GridWindow {
         let renderer = Renderer::new(hwnd)?;
         let dpi_info = renderer.dpi();
 
-        let layout_ctx = LayoutContext {
+        let (monitor_width, monitor_height) = get_monitor_size();
+        let mut layout_ctx = LayoutContext {
             dpi: dpi_info.dpi as f32,
             scale_factor: dpi_info.scale_factor,
-            base_font_size: 16.0,
             parent_size: 1920.0, // Will be updated
+            viewport_width: monitor_width as f32,
+            viewport_height: monitor_height as f32,
+            // `em` and `ch` units follow the theme's font, see `apply_base_font`
+            ..LayoutContext::default()
         };
 
         // Determine theme file based on mode
@@ -127,6 +143,9 @@ impl GridWindow {
         log!("  Loading theme from {:?}", theme_path);
 
         let theme = ThemeTree::load(&theme_path).ok();
+        if let Some(theme) = &theme {
+            apply_base_font(&mut layout_ctx, theme);
+        }
 
         // Load styles from theme
         let style = theme
@@ -142,19 +161,22 @@ impl GridWindow {
         let gridview = GridView::new().with_style(gridview_style);
 
         // Get window dimensions from theme
-        let window_width = theme
-            .as_ref()
-            .map(|t| t.get_number("window", None, "width", 1920.0) as i32)
-            .unwrap_or(1920);
-        let window_height = theme
+        let (window_width, window_height) = theme
             .as_ref()
-            .map(|t| t.get_number("window", None, "height", 520.0) as i32)
-            .unwrap_or(520);
+            .map(|t| window_size(t, &monitor_layout_context(t), (1920.0, 520.0)))
+            .unwrap_or((1920.0, 520.0));
+        let (window_width, window_height) = (window_width as i32, window_height as i32);
 
         // Create animator
         let easing = Easing::from_name(&style.animation_easing);
         let animator = WindowAnimator::new(style.animation_duration_ms, easing);
 
+        let mut overlay = UITree::from_theme(
+            theme.as_ref().unwrap_or(&ThemeTree::new()),
+            layout_ctx.clone(),
+        );
+        overlay.set_text("version", &format!("v{}", VERSION));
+
         log!("GridWindow::new() completed for {:?}", mode);
         Ok(Self {
             hwnd,
@@ -169,6 +191,9 @@ impl GridWindow {
             theme_path,
             window_width,
             window_height,
+            all_items: Vec::new(),
+            filter: String::new(),
+            overlay,
         })
     }
 
@@ -208,8 +233,10 @@ impl GridWindow {
         let monitor_width = get_monitor_width();
         resize_window(self.hwnd, monitor_width, self.window_height, 0.5);
 
-        // Load content based on mode
+        // Load content based on mode (clearing any previous filter)
+        self.filter.clear();
         self.load_content();
+        self.restore_selection();
 
         // Start fade-in animation BEFORE showing window
         self.animator.start_fade_in();
@@ -272,7 +299,8 @@ impl GridWindow {
 
         log!("Loaded {} themes into grid", items.len());
         drop(state); // Release borrow before calling set_items
-        self.gridview.set_items(items);
+        self.all_items = items;
+        self.apply_filter();
     }
 
     /// Load wallpapers for the currently selected theme
@@ -300,12 +328,48 @@ impl GridWindow {
                 items.len(),
                 theme_name
             );
-            self.gridview.set_items(items);
+            self.all_items = items;
         } else {
             // No theme selected - show empty grid with message
             log!("No theme selected, showing empty wallpaper grid");
-            self.gridview.set_items(Vec::new());
+            self.all_items = Vec::new();
         }
+        self.apply_filter();
+    }
+
+    /// Select the item chosen last time (persisted across restarts)
+    fn restore_selection(&mut self) {
+        let state = self.app_state.borrow();
+        let index = state.preferred_selection(self.mode).and_then(|data| {
+            self.all_items
+                .iter()
+                .position(|item| item.user_data == data)
+        });
+        drop(state);
+
+        if let Some(index) = index {
+            self.gridview.select(index);
+        }
+    }
+
+    /// Filter the grid items with the current type-to-filter query
+    fn apply_filter(&mut self) {
+        let items = GridItem::filter(&self.all_items, &Query::parse(&self.filter));
+        log!(
+            "GridWindow filter '{}': {} of {} items",
+            self.filter,
+            items.len(),
+            self.all_items.len()
+        );
+        self.gridview.set_items(items);
+
+        // Shown by the filter overlay while there's a query
+        let label = if self.filter.is_empty() {
+            String::new()
+        } else {
+            format!("Filter: {}", self.filter)
+        };
+        self.overlay.set_text("filter", &label);
     }
 
     /// Handle window procedure messages
@@ -390,6 +454,22 @@ impl GridWindow {
                     cancel: true,
                 };
             }
+            // Type-to-filter
+            Event::Char(c) if !c.is_control() => {
+                self.filter.push(*c);
+                self.apply_filter();
+                return EventResult::repaint();
+            }
+            Event::KeyDown {
+                key: KeyCode::Backspace,
+                ..
+            } => {
+                if self.filter.pop().is_some() {
+                    self.apply_filter();
+                    return EventResult::repaint();
+                }
+                return EventResult::consumed();
+            }
             _ => {}
         }
 
@@ -405,17 +485,16 @@ impl GridWindow {
                     // Set current theme and set the first wallpaper from that theme
                     let theme_name = item.title.clone();
                     log!("Theme selected: {}", theme_name);
-                    self.app_state
-                        .borrow_mut()
-                        .set_current_theme(Some(theme_name.clone()));
+                    let mut state = self.app_state.borrow_mut();
 
-                    // Set the first wallpaper from this theme
-                    let wallpapers = scan_theme_wallpapers(&theme_name);
-                    if let Some(first_wallpaper) = wallpapers.first() {
-                        let wallpaper_path = first_wallpaper.to_string_lossy().to_string();
-                        log!("Setting first wallpaper from theme: {}", wallpaper_path);
+                    // Restore the wallpaper last used with this theme, or its first one
+                    let wallpaper_path = state.choose_theme(&theme_name, &item.user_data);
+                    if let Some(wallpaper_path) = wallpaper_path {
+                        log!("Setting wallpaper from theme: {}", wallpaper_path);
                         crate::platform::win32::set_wallpaper(&wallpaper_path);
+                        state.remember_wallpaper(&wallpaper_path);
                     }
+                    drop(state);
 
                     self.hide();
                 }
@@ -424,6 +503,11 @@ impl GridWindow {
                     let wallpaper_path = item.user_data.clone();
                     log!("Wallpaper selected: {}", wallpaper_path);
                     crate::platform::win32::set_wallpaper(&wallpaper_path);
+
+                    self.app_state
+                        .borrow_mut()
+                        .choose_wallpaper(&wallpaper_path);
+
                     self.hide();
                 }
                 Mode::Launcher | Mode::TailView => {
@@ -440,7 +524,8 @@ impl GridWindow {
 
     /// Handle animation tick
     fn on_animation_tick(&mut self) {
-        if !self.animator.update() {
+        let transitions = self.gridview.tick();
+        if !self.animator.update() && !transitions {
             // Animation complete
             self.stop_animation_timer();
 
@@ -480,15 +565,24 @@ impl GridWindow {
 
         // Arrange and render gridview
         self.gridview.arrange(bounds, &self.layout_ctx);
-        self.gridview
-            .render(&mut self.renderer, bounds, &self.layout_ctx)?;
+        let mut list = DisplayList::new();
+        self.gridview.render(&mut list, bounds, &self.layout_ctx);
+        self.renderer.draw_list(&list)?;
 
-        // Draw version watermark in bottom right corner
-        self.draw_version_watermark(size.0, size.1);
+        // Version watermark, filter query and other overlays
+        self.overlay.layout(bounds);
+        let mut list = DisplayList::new();
+        self.overlay.render_overlays(&mut list);
+        self.renderer.draw_list(&list)?;
 
         // End render
         self.renderer.end_draw()?;
 
+        // A hover or selection change started tile transitions
+        if self.gridview.tick() {
+            self.start_animation_timer();
+        }
+
         Ok(())
     }
 
@@ -501,49 +595,18 @@ impl GridWindow {
             let gridview_style = GridViewStyle::from_theme(&theme, None);
             self.gridview.set_style(gridview_style);
 
-            self.window_width = theme.get_number("window", None, "width", 1920.0) as i32;
-            self.window_height = theme.get_number("window", None, "height", 520.0) as i32;
+            apply_base_font(&mut self.layout_ctx, &theme);
+            self.overlay.set_layout_ctx(self.layout_ctx.clone());
+            let (width, height) =
+                window_size(&theme, &monitor_layout_context(&theme), (1920.0, 520.0));
+            self.window_width = width as i32;
+            self.window_height = height as i32;
 
             let easing = Easing::from_name(&self.style.animation_easing);
             self.animator = WindowAnimator::new(self.style.animation_duration_ms, easing);
-        }
-    }
-
-    /// Draw version watermark in bottom right corner
-    fn draw_version_watermark(&mut self, width: i32, height: i32) {
-        use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-
-        let version_text = format!("v{}", VERSION);
-        let font_size = 10.0 * self.layout_ctx.scale_factor;
-
-        // Create a small text format for the version
-        let text_format = match self
-            .renderer
-            .create_text_format("Segoe UI", font_size, false, false)
-        {
-            Ok(fmt) => fmt,
-            Err(_) => return,
-        };
 
-        // Position in bottom right with some margin
-        let text_width = 60.0 * self.layout_ctx.scale_factor;
-        let text_height = 14.0 * self.layout_ctx.scale_factor;
-        let margin = 8.0 * self.layout_ctx.scale_factor;
-
-        let rect = D2D_RECT_F {
-            left: width as f32 - text_width - margin,
-            top: height as f32 - text_height - margin,
-            right: width as f32 - margin,
-            bottom: height as f32 - margin,
-        };
-
-        // Draw with low opacity as a subtle watermark
-        let watermark_color = Color::from_f32(1.0, 1.0, 1.0, 0.3);
-        let _ = self.renderer.draw_text_right_aligned(
-            &version_text,
-            &text_format,
-            rect,
-            watermark_color,
-        );
+            self.overlay.rebuild(&theme);
+            self.overlay.set_text("version", &format!("v{}", VERSION));
+        }
     }
 }
@@ -1,29 +1,213 @@
-//! Frequency-based usage history tracking (rofi-inspired)
+//! Frecency-based usage history tracking (rofi/zoxide-inspired)
 //!
-//! Stores app launch counts in a simple text file format:
+//! Each entry stores a launch count, the last-used timestamp and a short list of
+//! recent launch timestamps (Unix seconds):
 //! ```text
-//! 15 firefox.exe
-//! 8 notepad.exe
-//! 3 calculator
+//! # wolfy-history v2
+//! 15 1767225600 1767225600,1767139200 C:\ProgramData\...\Firefox.lnk
+//! 3 1764547200 - calculator
 //! ```
 //!
-//! Higher counts mean more frequently used apps, which appear first in the list.
+//! Entries are ranked by frecency: the sum of the exponentially decayed
+//! weights of the recent launches, plus the older launches decayed as if they
+//! all happened at the oldest recent one. An app used often last year stays
+//! below one used regularly this week, even after a stray launch today.
+//!
+//! The history also learns which item was chosen for which typed query prefix,
+//! so an item picked repeatedly after typing "te" ranks first for "te". These
+//! associations follow the entries in a `# queries` section, one per line as
+//! `count last_used app_id<TAB>prefix`.
+//!
+//! The legacy format (`count app_id` per line, no header) is migrated on load.
 
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Write};
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::persist;
 
 /// Maximum number of entries to keep in history
 const MAX_HISTORY_SIZE: usize = 100;
 
-/// Frequency-based usage history
-#[derive(Debug, Default)]
+/// Number of recent launch timestamps kept per entry
+const MAX_RECENT_LAUNCHES: usize = 10;
+
+/// Maximum number of (query prefix, app) associations to keep
+const MAX_QUERY_AFFINITIES: usize = 500;
+
+/// Longest query prefix (in chars) that is learned
+const MAX_PREFIX_CHARS: usize = 16;
+
+/// Default frecency half-life: a launch's weight halves every 14 days
+pub const DEFAULT_HALF_LIFE_DAYS: f64 = 14.0;
+
+/// First line of a v2 history file
+const FORMAT_HEADER: &str = "# wolfy-history v2";
+
+/// Marks the start of the query affinity section
+const QUERIES_HEADER: &str = "# queries";
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Exponential decay weight of an event `age_secs` old
+fn decay(age_secs: u64, half_life_days: f64) -> f64 {
+    let half_life_secs = (half_life_days * SECONDS_PER_DAY).max(1.0);
+    0.5f64.powf(age_secs as f64 / half_life_secs)
+}
+
+/// Normalize typed text into a learnable query prefix
+fn normalize_query(query: &str) -> String {
+    query
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| *c != '\t')
+        .take(MAX_PREFIX_CHARS)
+        .collect()
+}
+
+/// Current time as Unix seconds
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Usage record for a single app
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryEntry {
+    /// Total number of launches
+    pub count: u32,
+    /// Unix seconds of the most recent launch
+    pub last_used: u64,
+    /// Unix seconds of the most recent launches (oldest first); the other
+    /// launches all happened no later than the oldest of these
+    pub recent: Vec<u64>,
+}
+
+impl HistoryEntry {
+    /// Frecency score at `now`: the decayed weight of each recent launch,
+    /// plus the older launches decayed from the oldest recent one
+    pub fn frecency(&self, now: u64, half_life_days: f64) -> f64 {
+        let decay = |t: u64| decay(now.saturating_sub(t), half_life_days);
+
+        let recent: f64 = self.recent.iter().map(|&t| decay(t)).sum();
+        // Migrated entries only know when they were last used
+        let older_than = self.recent.iter().min().copied().unwrap_or(self.last_used);
+        let older = self.count.saturating_sub(self.recent.len() as u32);
+
+        recent + older as f64 * decay(older_than)
+    }
+
+    /// Parse a v2 line: `count last_used recent app_id`
+    fn parse_line(line: &str) -> Option<(String, Self)> {
+        let mut parts = line.splitn(4, ' ');
+        let count = parts.next()?.parse::<u32>().ok()?;
+        let last_used = parts.next()?.parse::<u64>().ok()?;
+        let recent = match parts.next()? {
+            "-" => Vec::new(),
+            list => list
+                .split(',')
+                .map(|t| t.parse::<u64>().ok())
+                .collect::<Option<Vec<_>>>()?,
+        };
+        let app_id = parts.next()?;
+        if app_id.is_empty() {
+            return None;
+        }
+
+        Some((
+            app_id.to_string(),
+            Self {
+                count,
+                last_used,
+                recent,
+            },
+        ))
+    }
+
+    /// Format as a v2 line
+    fn to_line(&self, app_id: &str) -> String {
+        let recent = if self.recent.is_empty() {
+            "-".to_string()
+        } else {
+            self.recent
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        format!("{} {} {} {}", self.count, self.last_used, recent, app_id)
+    }
+}
+
+/// How often an app was chosen after typing a given query prefix
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QueryAffinity {
+    /// Number of times the app was chosen for the prefix
+    pub count: u32,
+    /// Unix seconds of the most recent choice
+    pub last_used: u64,
+}
+
+impl QueryAffinity {
+    /// Decayed affinity score at `now`
+    pub fn score(&self, now: u64, half_life_days: f64) -> f64 {
+        self.count as f64 * decay(now.saturating_sub(self.last_used), half_life_days)
+    }
+
+    /// Parse a query line: `count last_used app_id<TAB>prefix`
+    fn parse_line(line: &str) -> Option<((String, String), Self)> {
+        let mut parts = line.splitn(3, ' ');
+        let count = parts.next()?.parse::<u32>().ok()?;
+        let last_used = parts.next()?.parse::<u64>().ok()?;
+        let (app_id, prefix) = parts.next()?.split_once('\t')?;
+        if app_id.is_empty() || prefix.is_empty() {
+            return None;
+        }
+
+        Some((
+            (prefix.to_string(), app_id.to_string()),
+            Self { count, last_used },
+        ))
+    }
+}
+
+/// A change recorded since the last save, replayed onto the file when saving
+#[derive(Debug, Clone)]
+enum PendingEvent {
+    Launch {
+        app_id: String,
+        timestamp: u64,
+    },
+    Query {
+        query: String,
+        app_id: String,
+        timestamp: u64,
+    },
+}
+
+/// Frecency-based usage history
+#[derive(Debug)]
 pub struct History {
-    /// Map from app ID to launch count
-    entries: HashMap<String, u32>,
+    /// Map from app ID to usage record
+    entries: HashMap<String, HistoryEntry>,
+    /// Map from (query prefix, app ID) to how often that app was chosen for it
+    queries: HashMap<(String, String), QueryAffinity>,
+    /// Changes not yet written to the history file
+    pending: Vec<PendingEvent>,
     /// Path to the history file
     path: PathBuf,
+    /// Frecency half-life in days
+    half_life_days: f64,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl History {
@@ -31,10 +215,24 @@ impl History {
     pub fn new() -> Self {
         Self {
             entries: HashMap::new(),
+            queries: HashMap::new(),
+            pending: Vec::new(),
             path: PathBuf::new(),
+            half_life_days: DEFAULT_HALF_LIFE_DAYS,
         }
     }
 
+    /// Set the frecency half-life in days (how quickly old launches lose weight)
+    pub fn with_half_life_days(mut self, days: f64) -> Self {
+        self.half_life_days = days;
+        self
+    }
+
+    /// Get the frecency half-life in days
+    pub fn half_life_days(&self) -> f64 {
+        self.half_life_days
+    }
+
     /// Load history from the default location (%APPDATA%\wolfy\history.txt)
     pub fn load_default() -> Self {
         if let Some(app_data) = dirs::data_dir() {
@@ -46,62 +244,158 @@ impl History {
         }
     }
 
-    /// Load history from a specific file
+    /// Load history from a specific file, migrating the legacy format if needed
+    ///
+    /// Falls back to the `.bak` copy if the file is missing or was truncated.
     pub fn load(path: &Path) -> Self {
         let mut history = Self {
-            entries: HashMap::new(),
             path: path.to_path_buf(),
+            ..Self::new()
         };
 
         // Try to read the history file
-        if let Ok(file) = File::open(path) {
-            let reader = BufReader::new(file);
-            for line in reader.lines().map_while(Result::ok) {
-                // Parse "count app_id" format
+        if let Ok(contents) = persist::read(path) {
+            history.parse(&contents);
+            crate::log!(
+                "Loaded {} history entries and {} query associations from {:?}",
+                history.entries.len(),
+                history.queries.len(),
+                path
+            );
+        }
+
+        history
+    }
+
+    /// Parse history file contents into this history
+    fn parse(&mut self, contents: &str) {
+        let mut lines = contents.lines().peekable();
+
+        if lines.peek().map(|l| l.trim()) == Some(FORMAT_HEADER) {
+            lines.next();
+            let mut in_queries = false;
+            for line in lines {
+                if line.trim() == QUERIES_HEADER {
+                    in_queries = true;
+                } else if in_queries {
+                    if let Some((key, affinity)) = QueryAffinity::parse_line(line) {
+                        self.queries.insert(key, affinity);
+                    }
+                } else if let Some((app_id, entry)) = HistoryEntry::parse_line(line) {
+                    self.entries.insert(app_id, entry);
+                }
+            }
+        } else {
+            // Legacy "count app_id" format: use the file's modification time
+            // as the best guess for when these apps were last used
+            let last_used = fs::metadata(&self.path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or_else(now_secs);
+
+            for line in lines {
                 if let Some((count_str, app_id)) = line.split_once(' ') {
                     if let Ok(count) = count_str.parse::<u32>() {
-                        history.entries.insert(app_id.to_string(), count);
+                        self.entries.insert(
+                            app_id.to_string(),
+                            HistoryEntry {
+                                count,
+                                last_used,
+                                recent: Vec::new(),
+                            },
+                        );
                     }
                 }
             }
             crate::log!(
-                "Loaded {} history entries from {:?}",
-                history.entries.len(),
-                path
+                "Migrating {} legacy history entries from {:?}",
+                self.entries.len(),
+                self.path
             );
         }
+    }
 
-        history
+    /// Serialize to the v2 file format, pruned to the size caps
+    fn serialize(&self) -> String {
+        // Sort entries by frecency (descending) and take top MAX_HISTORY_SIZE
+        let now = now_secs();
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(id, e)| (id, e, e.frecency(now, self.half_life_days)))
+            .collect();
+        entries.sort_by(|a, b| b.2.total_cmp(&a.2));
+        entries.truncate(MAX_HISTORY_SIZE);
+
+        // Same for query associations, capped at MAX_QUERY_AFFINITIES
+        let mut queries: Vec<_> = self
+            .queries
+            .iter()
+            .map(|(key, a)| (key, a, a.score(now, self.half_life_days)))
+            .collect();
+        queries.sort_by(|a, b| b.2.total_cmp(&a.2));
+        queries.truncate(MAX_QUERY_AFFINITIES);
+
+        let mut out = String::new();
+        out.push_str(FORMAT_HEADER);
+        out.push('\n');
+        for (app_id, entry, _) in entries {
+            out.push_str(&entry.to_line(app_id));
+            out.push('\n');
+        }
+        if !queries.is_empty() {
+            out.push_str(QUERIES_HEADER);
+            out.push('\n');
+        }
+        for ((prefix, app_id), a, _) in queries {
+            out.push_str(&format!(
+                "{} {} {}\t{}\n",
+                a.count, a.last_used, app_id, prefix
+            ));
+        }
+        out
     }
 
     /// Save history to file
-    pub fn save(&self) {
+    ///
+    /// Under an advisory lock, re-reads the file and replays the launches
+    /// recorded since the last save on top of it, so concurrent instances
+    /// merge their history instead of overwriting each other's.
+    pub fn save(&mut self) {
         if self.path.as_os_str().is_empty() {
             return;
         }
 
-        // Ensure directory exists
-        if let Some(parent) = self.path.parent() {
-            if let Err(e) = fs::create_dir_all(parent) {
-                crate::log!("Failed to create history directory: {:?}", e);
-                return;
+        let _lock = match persist::lock(&self.path) {
+            Ok(lock) => Some(lock),
+            Err(e) => {
+                // Still save: losing a race is better than losing the launch
+                crate::log!("Failed to lock history file: {:?}", e);
+                None
             }
-        }
-
-        // Sort entries by count (descending) and take top MAX_HISTORY_SIZE
-        let mut entries: Vec<_> = self.entries.iter().collect();
-        entries.sort_by(|a, b| b.1.cmp(a.1));
-        entries.truncate(MAX_HISTORY_SIZE);
+        };
 
-        // Write to file
-        match File::create(&self.path) {
-            Ok(mut file) => {
-                for (app_id, count) in entries {
-                    if let Err(e) = writeln!(file, "{} {}", count, app_id) {
-                        crate::log!("Failed to write history entry: {:?}", e);
-                        break;
-                    }
+        // Merge unsaved launches into the current on-disk history
+        let mut merged = Self::load(&self.path).with_half_life_days(self.half_life_days);
+        for event in std::mem::take(&mut self.pending) {
+            match event {
+                PendingEvent::Launch { app_id, timestamp } => {
+                    merged.apply_launch(&app_id, timestamp)
                 }
+                PendingEvent::Query {
+                    query,
+                    app_id,
+                    timestamp,
+                } => merged.apply_query(&query, &app_id, timestamp),
+            }
+        }
+        self.entries = merged.entries;
+        self.queries = merged.queries;
+
+        match persist::write_atomic(&self.path, self.serialize().as_bytes()) {
+            Ok(()) => {
                 crate::log!(
                     "Saved {} history entries to {:?}",
                     self.entries.len().min(MAX_HISTORY_SIZE),
@@ -109,33 +403,123 @@ impl History {
                 );
             }
             Err(e) => {
-                crate::log!("Failed to create history file: {:?}", e);
+                crate::log!("Failed to write history file: {:?}", e);
             }
         }
     }
 
-    /// Record a launch (increment count for app_id)
+    /// Record a launch now (increment count and timestamp it)
     pub fn record_launch(&mut self, app_id: &str) {
-        let count = self.entries.entry(app_id.to_string()).or_insert(0);
-        *count += 1;
-        crate::log!("Recorded launch for '{}', count now: {}", app_id, *count);
+        self.record_launch_for_query(app_id, "");
+    }
+
+    /// Record a launch now, learning that `app_id` was chosen for the typed `query`
+    pub fn record_launch_for_query(&mut self, app_id: &str, query: &str) {
+        let now = now_secs();
+        self.record_launch_at(app_id, now);
+        self.record_query_at(query, app_id, now);
 
         // Save immediately
         self.save();
     }
 
+    /// Learn that `app_id` was chosen after typing `query`, without saving
+    ///
+    /// Every prefix of the query is learned, so picking an app after typing
+    /// "term" also boosts it for "te".
+    pub fn record_query_at(&mut self, query: &str, app_id: &str, timestamp: u64) {
+        let query = normalize_query(query);
+        if query.is_empty() {
+            return;
+        }
+        self.apply_query(&query, app_id, timestamp);
+        self.pending.push(PendingEvent::Query {
+            query,
+            app_id: app_id.to_string(),
+            timestamp,
+        });
+    }
+
+    /// Record a launch at a specific time (Unix seconds) without saving
+    pub fn record_launch_at(&mut self, app_id: &str, timestamp: u64) {
+        self.apply_launch(app_id, timestamp);
+        self.pending.push(PendingEvent::Launch {
+            app_id: app_id.to_string(),
+            timestamp,
+        });
+        crate::log!(
+            "Recorded launch for '{}', count now: {}",
+            app_id,
+            self.entries[app_id].count
+        );
+    }
+
+    /// Add a normalized query's prefixes to the affinity map
+    fn apply_query(&mut self, query: &str, app_id: &str, timestamp: u64) {
+        for (end, _) in query.char_indices().skip(1).chain([(query.len(), ' ')]) {
+            let affinity = self
+                .queries
+                .entry((query[..end].to_string(), app_id.to_string()))
+                .or_default();
+            affinity.count += 1;
+            affinity.last_used = affinity.last_used.max(timestamp);
+        }
+    }
+
+    /// Add a launch to the entry map
+    fn apply_launch(&mut self, app_id: &str, timestamp: u64) {
+        let entry = self.entries.entry(app_id.to_string()).or_default();
+        if entry.recent.is_empty() {
+            // A migrated entry's launches are dated to when it was last used,
+            // so they keep their age once newer launches arrive
+            let migrated = (entry.count as usize).min(MAX_RECENT_LAUNCHES);
+            entry.recent = vec![entry.last_used; migrated];
+        }
+        entry.count += 1;
+        entry.last_used = entry.last_used.max(timestamp);
+        entry.recent.push(timestamp);
+        if entry.recent.len() > MAX_RECENT_LAUNCHES {
+            let excess = entry.recent.len() - MAX_RECENT_LAUNCHES;
+            entry.recent.drain(..excess);
+        }
+    }
+
     /// Get the launch count for an app (None if never launched)
     pub fn get_count(&self, app_id: &str) -> Option<u32> {
-        self.entries.get(app_id).copied()
+        self.entries.get(app_id).map(|e| e.count)
     }
 
-    /// Get the sort index for an app (higher = more frequent, None = never used)
+    /// Get the full usage record for an app
+    pub fn get_entry(&self, app_id: &str) -> Option<&HistoryEntry> {
+        self.entries.get(app_id)
+    }
+
+    /// Get the frecency score for an app at a specific time (None if never launched)
+    pub fn frecency_at(&self, app_id: &str, now: u64) -> Option<f64> {
+        self.entries
+            .get(app_id)
+            .map(|e| e.frecency(now, self.half_life_days))
+    }
+
+    /// Get the learned affinity between a typed query and an app at a specific time
+    ///
+    /// Returns 0.0 if the app was never chosen for this query.
+    pub fn query_affinity_at(&self, query: &str, app_id: &str, now: u64) -> f64 {
+        let key = (normalize_query(query), app_id.to_string());
+        self.queries
+            .get(&key)
+            .map_or(0.0, |a| a.score(now, self.half_life_days))
+    }
+
+    /// Get the learned affinity between a typed query and an app
+    pub fn query_affinity(&self, query: &str, app_id: &str) -> f64 {
+        self.query_affinity_at(query, app_id, now_secs())
+    }
+
+    /// Get the sort index for an app (higher = more frecent, -1.0 = never used)
     /// This is used for sorting: apps with higher sort index appear first
-    pub fn sort_index(&self, app_id: &str) -> i32 {
-        match self.entries.get(app_id) {
-            Some(count) => *count as i32,
-            None => -1, // Not in history
-        }
+    pub fn sort_index(&self, app_id: &str) -> f64 {
+        self.frecency_at(app_id, now_secs()).unwrap_or(-1.0)
     }
 }
 
@@ -145,6 +529,8 @@ mod tests {
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    const DAY: u64 = 86_400;
+
     #[test]
     fn test_history_new() {
         let history = History::new();
@@ -172,16 +558,139 @@ mod tests {
         let mut history = History::new();
 
         // Not in history = -1
-        assert_eq!(history.sort_index("unknown"), -1);
+        assert_eq!(history.sort_index("unknown"), -1.0);
 
-        // In history = count
+        // Launched just now: frecency is close to the raw count
         history.record_launch("app1");
         history.record_launch("app1");
         history.record_launch("app1");
-        assert_eq!(history.sort_index("app1"), 3);
+        assert!((history.sort_index("app1") - 3.0).abs() < 0.01);
 
         history.record_launch("app2");
-        assert_eq!(history.sort_index("app2"), 1);
+        assert!(history.sort_index("app1") > history.sort_index("app2"));
+    }
+
+    #[test]
+    fn test_frecency_decay() {
+        let now = 1000 * DAY;
+        let mut history = History::new().with_half_life_days(14.0);
+
+        // 500 launches a year ago vs 10 this week
+        for i in 0..500 {
+            history.record_launch_at("old-favourite", now - 365 * DAY + i);
+        }
+        for i in 0..10 {
+            history.record_launch_at("daily-driver", now - i * DAY / 2);
+        }
+
+        let old = history.frecency_at("old-favourite", now).unwrap();
+        let recent = history.frecency_at("daily-driver", now).unwrap();
+        assert!(recent > old, "recent={} old={}", recent, old);
+
+        // A stray launch today doesn't lift the old favourite back above the
+        // app used every day this week
+        history.record_launch_at("old-favourite", now);
+        let old = history.frecency_at("old-favourite", now).unwrap();
+        assert!(recent > old, "recent={} old={}", recent, old);
+        assert!(old < 1.01, "old={}", old);
+
+        // One half-life halves the score
+        let entry = HistoryEntry {
+            count: 8,
+            last_used: now - 14 * DAY,
+            recent: vec![now - 14 * DAY],
+        };
+        assert!((entry.frecency(now, 14.0) - 4.0).abs() < 0.001);
+
+        // Only the most recent launches are kept
+        assert_eq!(
+            history.get_entry("old-favourite").unwrap().recent.len(),
+            MAX_RECENT_LAUNCHES
+        );
+    }
+
+    #[test]
+    fn test_query_affinity_learns_prefixes() {
+        let now = 1000 * DAY;
+        let mut history = History::new();
+
+        history.record_query_at("Te", "terminal", now);
+        history.record_query_at("term", "terminal", now);
+        history.record_query_at("te", "text-editor", now);
+
+        // "te" was typed (or extended) twice before picking the terminal
+        assert_eq!(history.query_affinity_at("te", "terminal", now), 2.0);
+        assert_eq!(history.query_affinity_at("TE ", "terminal", now), 2.0);
+        assert_eq!(history.query_affinity_at("term", "terminal", now), 1.0);
+        assert_eq!(history.query_affinity_at("te", "text-editor", now), 1.0);
+        assert_eq!(history.query_affinity_at("tex", "terminal", now), 0.0);
+
+        // Empty queries learn nothing
+        history.record_query_at("  ", "terminal", now);
+        assert!(!history.queries.keys().any(|(prefix, _)| prefix.is_empty()));
+
+        // Affinity decays like launch frecency
+        let later = now + 14 * DAY;
+        assert!((history.query_affinity_at("te", "terminal", later) - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_query_affinity_persist_and_prune() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut history = History::load(temp_file.path());
+        let now = now_secs();
+
+        history.record_launch_at("C:\\Apps\\Windows Terminal.lnk", now);
+        history.record_query_at("win term", "C:\\Apps\\Windows Terminal.lnk", now);
+        for i in 0..MAX_QUERY_AFFINITIES {
+            history.record_query_at(&format!("q{}", i), "other", now - DAY);
+        }
+        history.save();
+
+        let reloaded = History::load(temp_file.path());
+        assert_eq!(reloaded.queries.len(), MAX_QUERY_AFFINITIES);
+        assert_eq!(
+            reloaded.query_affinity_at("win term", "C:\\Apps\\Windows Terminal.lnk", now),
+            1.0
+        );
+        assert_eq!(
+            reloaded.get_count("C:\\Apps\\Windows Terminal.lnk"),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_concurrent_instances_merge() {
+        let temp_file = NamedTempFile::new().unwrap();
+
+        // Two instances load the same (empty) history
+        let mut first = History::load(temp_file.path());
+        let mut second = History::load(temp_file.path());
+
+        first.record_launch_at("firefox", 100);
+        first.record_launch_at("notepad", 100);
+        first.save();
+
+        // The second instance never saw the first's launches
+        second.record_launch_at("firefox", 200);
+        second.record_query_at("fi", "firefox", 200);
+        second.save();
+
+        let reloaded = History::load(temp_file.path());
+        assert_eq!(reloaded.get_count("firefox"), Some(2));
+        assert_eq!(reloaded.get_count("notepad"), Some(1));
+        assert_eq!(
+            reloaded.get_entry("firefox").unwrap().recent,
+            vec![100, 200]
+        );
+        assert_eq!(reloaded.query_affinity_at("fi", "firefox", 200), 1.0);
+
+        // Saving again without new launches doesn't double count
+        second.save();
+        assert_eq!(
+            History::load(temp_file.path()).get_count("firefox"),
+            Some(2)
+        );
     }
 
     #[test]
@@ -200,4 +709,54 @@ mod tests {
         assert_eq!(history.get_count("calc.exe"), Some(1));
         assert_eq!(history.get_count("unknown"), None);
     }
+
+    #[test]
+    fn test_legacy_migration_round_trip() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "7 C:\\Program Files\\App\\app one.lnk").unwrap();
+        temp_file.flush().unwrap();
+
+        // Legacy entries get a last-used time but no recent launches
+        let mut history = History::load(temp_file.path());
+        let entry = history
+            .get_entry("C:\\Program Files\\App\\app one.lnk")
+            .unwrap();
+        assert_eq!(entry.count, 7);
+        assert!(entry.last_used > 0);
+        assert!(entry.recent.is_empty());
+
+        // Relaunching a migrated entry keeps its old launches at their age
+        let mut migrated = History::new();
+        migrated.entries.insert(
+            "old-favourite".to_string(),
+            HistoryEntry {
+                count: 500,
+                last_used: 1000 * DAY - 365 * DAY,
+                recent: Vec::new(),
+            },
+        );
+        migrated.record_launch_at("old-favourite", 1000 * DAY);
+        let score = migrated.frecency_at("old-favourite", 1000 * DAY).unwrap();
+        assert!(score < 1.01, "score={}", score);
+
+        // Saving writes the v2 format, which loads back identically
+        history.record_launch_at("calc", 1234);
+        history.save();
+        let content = fs::read_to_string(temp_file.path()).unwrap();
+        assert!(content.starts_with(FORMAT_HEADER));
+
+        let reloaded = History::load(temp_file.path());
+        assert_eq!(
+            reloaded.get_entry("C:\\Program Files\\App\\app one.lnk"),
+            history.get_entry("C:\\Program Files\\App\\app one.lnk")
+        );
+        assert_eq!(
+            reloaded.get_entry("calc"),
+            Some(&HistoryEntry {
+                count: 1,
+                last_used: 1234,
+                recent: vec![1234],
+            })
+        );
+    }
 }
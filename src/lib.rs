@@ -13,12 +13,33 @@ pub mod log;
 // Expose theme module for testing
 pub mod theme;
 
+// Command-line tools (no Windows dependencies)
+pub mod cli;
+
 // Animation system (no Windows dependencies)
 pub mod animation;
 
 // Usage history tracking (no Windows dependencies)
 pub mod history;
 
+// Operating modes (no Windows dependencies)
+pub mod mode;
+
+// Fuzzy matching and scoring (no Windows dependencies)
+pub mod matcher;
+
+// Crash-safe file persistence (no Windows dependencies)
+pub mod persist;
+
+// Extended search query syntax (no Windows dependencies)
+pub mod query;
+
+// Session state persisted across restarts (no Windows dependencies)
+pub mod session;
+
+// State shared between the windows (no Windows dependencies)
+pub mod state;
+
 // Task runner configuration (no Windows dependencies)
 pub mod tasks;
 
@@ -30,12 +51,19 @@ pub mod task_runner;
 #[cfg(windows)]
 pub mod pty;
 
-// Terminal emulator state wrapper (Windows only)
-#[cfg(windows)]
+// Terminal emulator state wrapper (PTYs are Windows only)
 pub mod terminal;
 
-// Widget base types (no Windows dependencies)
-pub mod widget {
-    pub mod base;
-    pub use base::*;
-}
+// Input events, and the win32 platform layer on Windows
+pub mod platform;
+
+// Backend-neutral display lists and the software renderer
+pub mod render;
+
+// Widgets, painting into display lists (no Windows dependencies)
+pub mod widget;
+
+// Headless rendering of a mode to a PNG (not on Windows, where the launcher
+// draws it for real)
+#[cfg(not(windows))]
+pub mod screenshot;
@@ -9,47 +9,94 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+// Other platforms build just the command-line tools, which come from the
+// library; only the launcher needs the modules compiled into the binary
+#[cfg(windows)]
 #[macro_use]
 extern crate lalrpop_util;
 
+#[cfg(windows)]
 #[macro_use]
 mod log;
 
+#[cfg(windows)]
 mod animation;
+#[cfg(windows)]
 mod app;
+#[cfg(windows)]
+mod cli;
+#[cfg(windows)]
 mod grid_window;
+#[cfg(windows)]
 mod history;
+#[cfg(windows)]
+mod matcher;
+#[cfg(windows)]
 mod mode;
+#[cfg(windows)]
+mod persist;
+#[cfg(windows)]
 mod platform;
+#[cfg(windows)]
 mod pty;
+#[cfg(windows)]
+mod query;
+#[cfg(windows)]
+mod render;
+#[cfg(windows)]
+mod session;
+#[cfg(windows)]
 mod state;
+#[cfg(windows)]
 mod task_runner;
+#[cfg(windows)]
 mod tasks;
+#[cfg(windows)]
 mod terminal;
+#[cfg(windows)]
 mod theme;
+#[cfg(windows)]
 mod widget;
 
+#[cfg(windows)]
 use std::cell::RefCell;
+#[cfg(windows)]
 use std::rc::Rc;
 
+#[cfg(windows)]
 use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+#[cfg(windows)]
 use windows::Win32::UI::WindowsAndMessaging::{
     DispatchMessageW, GetMessageW, TranslateMessage, MSG, WM_HOTKEY,
 };
 
-use app::App;
+#[cfg(windows)]
+use app::{App, RESTORE_MODE_ARG};
+#[cfg(windows)]
+use cli::Command;
+#[cfg(windows)]
 use grid_window::GridWindow;
+#[cfg(windows)]
 use log::find_config_file;
+#[cfg(windows)]
 use mode::Mode;
+#[cfg(windows)]
 use platform::win32::{
     self, create_window, default_mode_hotkeys, enable_dpi_awareness, get_monitor_width,
     register_window_class, set_window_callback, unregister_hotkeys, unregister_window_class,
     WindowConfig,
 };
+#[cfg(windows)]
 use state::AppState;
+#[cfg(windows)]
 use theme::tree::ThemeTree;
+#[cfg(windows)]
+use widget::{window_size, Placement};
+#[cfg(not(windows))]
+use wolfy::cli::{self, Command};
 
 /// Manages all application windows
+#[cfg(windows)]
 struct WindowManager {
     /// Launcher window (Ctrl+0)
     launcher: Rc<RefCell<App>>,
@@ -58,10 +105,10 @@ struct WindowManager {
     /// Wallpaper picker window (Ctrl+2)
     wallpaper_picker: Rc<RefCell<GridWindow>>,
     /// Shared application state
-    #[allow(dead_code)]
     app_state: Rc<RefCell<AppState>>,
 }
 
+#[cfg(windows)]
 impl WindowManager {
     /// Handle a window message, routing to the appropriate window
     fn handle_message(
@@ -128,6 +175,8 @@ impl WindowManager {
             }
         }
 
+        self.app_state.borrow_mut().remember_mode(mode);
+
         match mode {
             Mode::Launcher => {
                 // Hide grid windows, show launcher
@@ -159,6 +208,41 @@ impl WindowManager {
 }
 
 fn main() {
+    // Command-line tools run without a window (and without touching the log)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = Command::parse(&args) {
+        #[cfg(windows)]
+        attach_parent_console();
+        std::process::exit(command.run());
+    }
+
+    #[cfg(windows)]
+    run_launcher();
+
+    #[cfg(not(windows))]
+    {
+        eprintln!(
+            "wolfy: the launcher requires Windows; see `wolfy --help` for the tools available here"
+        );
+        std::process::exit(cli::EXIT_USAGE);
+    }
+}
+
+/// Attach to the console of the shell that started us
+///
+/// Release builds use the windows subsystem, so without this the output of
+/// command-line tools would go nowhere.
+#[cfg(windows)]
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Create the windows, register hotkeys and run the message loop
+#[cfg(windows)]
+fn run_launcher() {
     // Initialize logging first
     log::init();
     log!("main() starting - multi-window architecture");
@@ -186,17 +270,24 @@ fn main() {
     // Load launcher theme to determine window dimensions
     let theme_path = find_config_file("default.rasi");
     log!("Loading launcher theme from {:?}", theme_path);
-    let (launcher_width, launcher_height) = match ThemeTree::load(&theme_path) {
+    let (launcher_width, launcher_height, launcher_placement) = match ThemeTree::load(&theme_path) {
         Ok(theme) => {
             log!("Theme loaded successfully");
-            let width = theme.get_number("window", None, "width", 928.0) as i32;
-            let height = theme.get_number("window", None, "height", 480.0) as i32;
+            let ctx = win32::monitor_layout_context(&theme);
+            let (width, height) = window_size(&theme, &ctx, (928.0, 480.0));
+            let (width, height) = (width as i32, height as i32);
             log!("Launcher window size: {}x{}", width, height);
-            (width, height)
+            // `window { location }` places the window on the monitor, rofi-style
+            let placement = theme.get_resolved("window", None, "location").map(|_| {
+                let mut placement = Placement::default();
+                placement.load(&theme, "window");
+                placement
+            });
+            (width, height, placement)
         }
         Err(e) => {
-            log!("Failed to load theme: {:?}, using defaults", e);
-            (928, 480)
+            log!("Failed to load theme: {}, using defaults", e);
+            (928, 480, None)
         }
     };
 
@@ -207,14 +298,20 @@ fn main() {
     // Load theme picker dimensions
     let theme_picker_path = find_config_file("theme_picker.rasi");
     let theme_picker_height = match ThemeTree::load(&theme_picker_path) {
-        Ok(theme) => theme.get_number("window", None, "height", 520.0) as i32,
+        Ok(theme) => {
+            let ctx = win32::monitor_layout_context(&theme);
+            window_size(&theme, &ctx, (0.0, 520.0)).1 as i32
+        }
         Err(_) => 520,
     };
 
     // Load wallpaper picker dimensions
     let wallpaper_picker_path = find_config_file("wallpaper_picker.rasi");
     let wallpaper_picker_height = match ThemeTree::load(&wallpaper_picker_path) {
-        Ok(theme) => theme.get_number("window", None, "height", 650.0) as i32,
+        Ok(theme) => {
+            let ctx = win32::monitor_layout_context(&theme);
+            window_size(&theme, &ctx, (0.0, 650.0)).1 as i32
+        }
         Err(_) => 650,
     };
 
@@ -223,6 +320,7 @@ fn main() {
         width: launcher_width,
         height: launcher_height,
         vertical_position: 0.5, // True center of screen
+        placement: launcher_placement,
     };
     log!(
         "Creating launcher window: {}x{}",
@@ -247,6 +345,7 @@ fn main() {
         width: monitor_width,
         height: theme_picker_height,
         vertical_position: 0.5, // Centered vertically
+        placement: None,
     };
     log!(
         "Creating theme picker window: {}x{}",
@@ -272,6 +371,7 @@ fn main() {
         width: monitor_width,
         height: wallpaper_picker_height,
         vertical_position: 0.5, // Centered vertically
+        placement: None,
     };
     log!(
         "Creating wallpaper picker window: {}x{}",
@@ -305,7 +405,7 @@ fn main() {
 
     // --- Create App objects ---
     log!("Creating App (launcher)...");
-    let launcher = match App::new(launcher_hwnd, launcher_config) {
+    let launcher = match App::new(launcher_hwnd, launcher_config, app_state.clone()) {
         Ok(a) => {
             log!("Launcher App created successfully");
             Rc::new(RefCell::new(a))
@@ -385,6 +485,15 @@ fn main() {
     log!("Starting file watch timer for theme hot-reload...");
     launcher.borrow().start_file_watch_timer();
 
+    // After an F6 restart, reopen the mode that was showing
+    if std::env::args().any(|arg| arg == RESTORE_MODE_ARG) {
+        let last_mode = window_manager.borrow().app_state.borrow().last_mode();
+        if let Some(mode) = last_mode {
+            log!("Restoring last mode after restart: {}", mode.display_name());
+            window_manager.borrow_mut().show_mode(mode);
+        }
+    }
+
     log!(
         "Wolfy started (multi-window). Hotkeys: Ctrl+0 (launcher), Ctrl+1 (theme), Ctrl+2 (wallpaper). F5=reload theme, F6=restart app."
     );
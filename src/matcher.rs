@@ -0,0 +1,560 @@
+//! Fuzzy matching with fzf-style scoring (no Windows dependencies)
+//!
+//! Implements a Smith-Waterman style local alignment of a pattern against a
+//! target string, modelled on fzf's v2 algorithm:
+//! - Every matched character earns a base score
+//! - Gaps between matched characters are penalized (start + extension)
+//! - Characters at word boundaries, after delimiters and camelCase humps earn bonuses
+//! - Consecutive runs inherit the bonus of the character that started the run
+//!
+//! Matching is case-smart by default: an all-lowercase pattern matches
+//! case-insensitively, a pattern with any uppercase character is case-sensitive.
+//!
+//! The returned [`Match`] carries the matched character positions so callers
+//! can highlight them when rendering.
+
+/// Score for each matched character
+const SCORE_MATCH: i32 = 16;
+/// Penalty for opening a gap between two matched characters
+const SCORE_GAP_START: i32 = -3;
+/// Penalty for each additional character in a gap
+const SCORE_GAP_EXTENSION: i32 = -1;
+
+/// Bonus for a match right after a non-word character (e.g. `-`, `.`)
+const BONUS_BOUNDARY: i32 = SCORE_MATCH / 2;
+/// Bonus for a match right after whitespace (or at the start of the text)
+const BONUS_BOUNDARY_WHITE: i32 = BONUS_BOUNDARY + 2;
+/// Bonus for a match right after a path/field delimiter (`/`, `\`, `:`, ...)
+const BONUS_BOUNDARY_DELIMITER: i32 = BONUS_BOUNDARY + 1;
+/// Bonus for matching a non-word character itself
+const BONUS_NON_WORD: i32 = SCORE_MATCH / 2;
+/// Bonus for a camelCase hump or the first digit of a number
+const BONUS_CAMEL_123: i32 = BONUS_BOUNDARY + SCORE_GAP_EXTENSION;
+/// Minimum bonus for each character of a consecutive run
+const BONUS_CONSECUTIVE: i32 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+/// The first pattern character's bonus is multiplied by this
+const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+
+/// Marker for "no alignment possible" in the DP tables
+const NONE: i32 = i32::MIN / 2;
+
+/// How character case is compared
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaseMatching {
+    /// Case-insensitive unless the pattern contains an uppercase character
+    #[default]
+    Smart,
+    /// Always case-sensitive
+    Sensitive,
+    /// Always case-insensitive
+    Insensitive,
+}
+
+/// Result of a successful fuzzy match
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Match {
+    /// Match score (higher is better)
+    pub score: i32,
+    /// Character indices (not byte offsets) in the target that matched, ascending
+    pub positions: Vec<usize>,
+}
+
+impl Match {
+    /// Collapse matched positions into half-open character ranges `(start, end)`
+    /// so consecutive matches can be rendered as a single highlighted run
+    pub fn ranges(&self) -> Vec<(usize, usize)> {
+        positions_to_ranges(&self.positions)
+    }
+}
+
+/// Collapse ascending character positions into half-open `(start, end)` runs
+pub fn positions_to_ranges(positions: &[usize]) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &pos in positions {
+        match ranges.last_mut() {
+            Some((_, end)) if *end == pos => *end = pos + 1,
+            _ => ranges.push((pos, pos + 1)),
+        }
+    }
+    ranges
+}
+
+/// Character class used to compute boundary bonuses
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CharClass {
+    White,
+    NonWord,
+    Delimiter,
+    Lower,
+    Upper,
+    Letter,
+    Number,
+}
+
+impl CharClass {
+    fn of(c: char) -> Self {
+        if c.is_ascii_lowercase() {
+            CharClass::Lower
+        } else if c.is_ascii_uppercase() {
+            CharClass::Upper
+        } else if c.is_ascii_digit() {
+            CharClass::Number
+        } else if c.is_whitespace() {
+            CharClass::White
+        } else if matches!(c, '/' | '\\' | ',' | ':' | ';' | '|') {
+            CharClass::Delimiter
+        } else if c.is_lowercase() {
+            CharClass::Lower
+        } else if c.is_uppercase() {
+            CharClass::Upper
+        } else if c.is_alphabetic() {
+            CharClass::Letter
+        } else if c.is_numeric() {
+            CharClass::Number
+        } else {
+            CharClass::NonWord
+        }
+    }
+
+    fn is_word(self) -> bool {
+        !matches!(
+            self,
+            CharClass::White | CharClass::NonWord | CharClass::Delimiter
+        )
+    }
+}
+
+/// Bonus for matching a character of class `cur` that follows class `prev`
+fn bonus_for(prev: CharClass, cur: CharClass) -> i32 {
+    if cur.is_word() {
+        match prev {
+            CharClass::White => return BONUS_BOUNDARY_WHITE,
+            CharClass::Delimiter => return BONUS_BOUNDARY_DELIMITER,
+            CharClass::NonWord => return BONUS_BOUNDARY,
+            _ => {}
+        }
+    }
+
+    if (prev == CharClass::Lower && cur == CharClass::Upper)
+        || (prev != CharClass::Number && cur == CharClass::Number)
+    {
+        return BONUS_CAMEL_123;
+    }
+
+    match cur {
+        CharClass::NonWord | CharClass::Delimiter => BONUS_NON_WORD,
+        CharClass::White => BONUS_BOUNDARY_WHITE,
+        _ => 0,
+    }
+}
+
+/// Fuzzy matcher with configurable case handling
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Matcher {
+    case: CaseMatching,
+}
+
+impl Matcher {
+    /// Create a case-smart matcher
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the case matching mode
+    pub fn with_case(mut self, case: CaseMatching) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Whether comparisons for this pattern should ignore case
+    fn ignores_case(&self, pattern: &str) -> bool {
+        match self.case {
+            CaseMatching::Smart => !pattern.chars().any(char::is_uppercase),
+            CaseMatching::Sensitive => false,
+            CaseMatching::Insensitive => true,
+        }
+    }
+
+    /// Split text into chars and case-fold both text and pattern as configured
+    /// Returns (original text chars, folded text chars, folded pattern chars)
+    fn prepare(&self, text: &str, pattern: &str) -> (Vec<char>, Vec<char>, Vec<char>) {
+        let ignore_case = self.ignores_case(pattern);
+        let fold = |c: char| {
+            if ignore_case {
+                c.to_lowercase().next().unwrap_or(c)
+            } else {
+                c
+            }
+        };
+
+        let text_chars: Vec<char> = text.chars().collect();
+        let folded: Vec<char> = text_chars.iter().map(|&c| fold(c)).collect();
+        let pattern_chars: Vec<char> = pattern.chars().map(fold).collect();
+        (text_chars, folded, pattern_chars)
+    }
+
+    /// Fuzzy match `pattern` against `text`
+    ///
+    /// Returns None if the pattern characters don't all appear in order.
+    /// An empty pattern matches everything with score 0.
+    pub fn fuzzy(&self, text: &str, pattern: &str) -> Option<Match> {
+        if pattern.is_empty() {
+            return Some(Match::default());
+        }
+
+        let (text_chars, folded, pattern_chars) = self.prepare(text, pattern);
+
+        let n = text_chars.len();
+        let m = pattern_chars.len();
+        if m > n {
+            return None;
+        }
+
+        // Quick reject and narrowing: find the first and last column the
+        // alignment could possibly use
+        let mut first = None;
+        let mut pi = 0;
+        for (j, &c) in folded.iter().enumerate() {
+            if c == pattern_chars[pi] {
+                if first.is_none() {
+                    first = Some(j);
+                }
+                pi += 1;
+                if pi == m {
+                    break;
+                }
+            }
+        }
+        if pi < m {
+            return None;
+        }
+        let first = first.unwrap_or(0);
+        let last = folded
+            .iter()
+            .rposition(|&c| c == pattern_chars[m - 1])
+            .unwrap_or(n - 1);
+
+        let bonus = column_bonuses(&text_chars);
+
+        // DP tables, row-major [i * n + j]
+        // m_score: best score with pattern[i] matched exactly at text[j]
+        // m_run:   bonus of the first character of the consecutive run ending at (i, j)
+        // m_prev:  column of pattern[i - 1]'s match for backtracking
+        // g_score: best score with pattern[i] matched strictly before j (gap open at j)
+        // g_col:   column of that match
+        let size = m * n;
+        let mut m_score = vec![NONE; size];
+        let mut m_run = vec![0i32; size];
+        let mut m_prev = vec![usize::MAX; size];
+        let mut g_score = vec![NONE; size];
+        let mut g_col = vec![usize::MAX; size];
+
+        for (i, &pc) in pattern_chars.iter().enumerate() {
+            let row = i * n;
+            for j in first.max(i)..=last {
+                let idx = row + j;
+
+                if folded[j] == pc {
+                    let b = bonus[j];
+                    if i == 0 {
+                        m_score[idx] = SCORE_MATCH + b * BONUS_FIRST_CHAR_MULTIPLIER;
+                        m_run[idx] = b;
+                    } else if j > 0 {
+                        let up = idx - n - 1;
+
+                        // Option 1: extend a consecutive run from (i-1, j-1)
+                        let diag = if m_score[up] > NONE {
+                            let run_bonus = m_run[up];
+                            let (bonus_here, run) = if b >= BONUS_BOUNDARY && b > run_bonus {
+                                // A strong boundary restarts the run
+                                (b, b)
+                            } else {
+                                (b.max(BONUS_CONSECUTIVE).max(run_bonus), run_bonus)
+                            };
+                            Some((m_score[up] + SCORE_MATCH + bonus_here, run))
+                        } else {
+                            None
+                        };
+
+                        // Option 2: close a gap after pattern[i-1] matched earlier
+                        let gap = if g_score[up] > NONE {
+                            Some((g_score[up] + SCORE_MATCH + b, g_col[up]))
+                        } else {
+                            None
+                        };
+
+                        match (diag, gap) {
+                            (Some((ds, run)), Some((gs, _))) if ds >= gs => {
+                                m_score[idx] = ds;
+                                m_run[idx] = run;
+                                m_prev[idx] = j - 1;
+                            }
+                            (_, Some((gs, col))) => {
+                                m_score[idx] = gs;
+                                m_run[idx] = b;
+                                m_prev[idx] = col;
+                            }
+                            (Some((ds, run)), None) => {
+                                m_score[idx] = ds;
+                                m_run[idx] = run;
+                                m_prev[idx] = j - 1;
+                            }
+                            (None, None) => {}
+                        }
+                    }
+                }
+
+                // Gap state for the next column: either open a gap right after a
+                // match at j, or extend an existing gap
+                if j + 1 < n {
+                    let open = if m_score[idx] > NONE {
+                        m_score[idx] + SCORE_GAP_START
+                    } else {
+                        NONE
+                    };
+                    let extend = if g_score[idx] > NONE {
+                        g_score[idx] + SCORE_GAP_EXTENSION
+                    } else {
+                        NONE
+                    };
+                    let next = idx + 1;
+                    if open >= extend && open > NONE {
+                        g_score[next] = open;
+                        g_col[next] = j;
+                    } else if extend > NONE {
+                        g_score[next] = extend;
+                        g_col[next] = g_col[idx];
+                    }
+                }
+            }
+        }
+
+        // Pick the best end column for the last pattern character
+        let last_row = (m - 1) * n;
+        let (end, score) = (first..=last)
+            .filter(|&j| m_score[last_row + j] > NONE)
+            .map(|j| (j, m_score[last_row + j]))
+            .fold(None, |best: Option<(usize, i32)>, (j, s)| match best {
+                Some((_, bs)) if bs >= s => best,
+                _ => Some((j, s)),
+            })?;
+
+        // Backtrack through the chosen predecessors
+        let mut positions = vec![0usize; m];
+        let mut j = end;
+        for i in (0..m).rev() {
+            positions[i] = j;
+            if i > 0 {
+                j = m_prev[i * n + j];
+            }
+        }
+
+        Some(Match { score, positions })
+    }
+
+    /// Fuzzy match returning only the score
+    pub fn score(&self, text: &str, pattern: &str) -> Option<i32> {
+        self.fuzzy(text, pattern).map(|m| m.score)
+    }
+
+    /// Exact substring match, scoring the best-placed occurrence
+    pub fn exact(&self, text: &str, pattern: &str) -> Option<Match> {
+        self.anchored(text, pattern, |_, _| true)
+    }
+
+    /// Match `pattern` at the start of `text`
+    pub fn prefix(&self, text: &str, pattern: &str) -> Option<Match> {
+        self.anchored(text, pattern, |start, _| start == 0)
+    }
+
+    /// Match `pattern` at the end of `text`
+    pub fn suffix(&self, text: &str, pattern: &str) -> Option<Match> {
+        // Trailing whitespace is ignored, like fzf
+        let text = text.trim_end();
+        let n = text.chars().count();
+        self.anchored(text, pattern, |start, len| start + len == n)
+    }
+
+    /// Match when `text` equals `pattern` (subject to case matching)
+    pub fn equal(&self, text: &str, pattern: &str) -> Option<Match> {
+        let text = text.trim();
+        let n = text.chars().count();
+        self.anchored(text, pattern, |start, len| start == 0 && len == n)
+    }
+
+    /// Contiguous match of `pattern` at the best start position accepted by `accept(start, len)`
+    fn anchored(
+        &self,
+        text: &str,
+        pattern: &str,
+        accept: impl Fn(usize, usize) -> bool,
+    ) -> Option<Match> {
+        if pattern.is_empty() {
+            return Some(Match::default());
+        }
+
+        let (text_chars, folded, pattern_chars) = self.prepare(text, pattern);
+        let m = pattern_chars.len();
+        if m > folded.len() {
+            return None;
+        }
+
+        let bonus = column_bonuses(&text_chars);
+        let (start, score) = folded
+            .windows(m)
+            .enumerate()
+            .filter(|(start, window)| *window == pattern_chars.as_slice() && accept(*start, m))
+            .map(|(start, _)| (start, run_score(&bonus[start..start + m])))
+            .fold(None, |best: Option<(usize, i32)>, (s, score)| match best {
+                Some((_, bs)) if bs >= score => best,
+                _ => Some((s, score)),
+            })?;
+
+        Some(Match {
+            score,
+            positions: (start..start + m).collect(),
+        })
+    }
+}
+
+/// Boundary bonus for matching each character of `text_chars`
+fn column_bonuses(text_chars: &[char]) -> Vec<i32> {
+    let mut prev_class = CharClass::White;
+    text_chars
+        .iter()
+        .map(|&c| {
+            let class = CharClass::of(c);
+            let bonus = bonus_for(prev_class, class);
+            prev_class = class;
+            bonus
+        })
+        .collect()
+}
+
+/// Score a contiguous run of matched characters with the given per-column bonuses,
+/// using the same rules as a consecutive run in the fuzzy alignment
+fn run_score(bonus: &[i32]) -> i32 {
+    let Some((&first, rest)) = bonus.split_first() else {
+        return 0;
+    };
+
+    let mut score = SCORE_MATCH + first * BONUS_FIRST_CHAR_MULTIPLIER;
+    let mut run_bonus = first;
+    for &b in rest {
+        if b >= BONUS_BOUNDARY && b > run_bonus {
+            run_bonus = b;
+            score += SCORE_MATCH + b;
+        } else {
+            score += SCORE_MATCH + b.max(BONUS_CONSECUTIVE).max(run_bonus);
+        }
+    }
+    score
+}
+
+/// Fuzzy match `pattern` against `text` with case-smart matching
+pub fn fuzzy_match(text: &str, pattern: &str) -> Option<Match> {
+    Matcher::new().fuzzy(text, pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_pattern_matches() {
+        let m = fuzzy_match("anything", "").unwrap();
+        assert_eq!(m.score, 0);
+        assert!(m.positions.is_empty());
+    }
+
+    #[test]
+    fn test_no_match() {
+        assert!(fuzzy_match("notepad", "xyz").is_none());
+        assert!(fuzzy_match("ab", "abc").is_none());
+        // Order matters
+        assert!(fuzzy_match("abc", "cba").is_none());
+    }
+
+    #[test]
+    fn test_positions() {
+        let m = fuzzy_match("Windows Terminal", "wt").unwrap();
+        assert_eq!(m.positions, vec![0, 8]);
+
+        let m = fuzzy_match("notepad", "pad").unwrap();
+        assert_eq!(m.positions, vec![4, 5, 6]);
+        assert_eq!(m.ranges(), vec![(4, 7)]);
+    }
+
+    #[test]
+    fn test_prefers_word_boundaries() {
+        // "vsc" should align with the word starts, not the first 's' and 'c' seen
+        let m = fuzzy_match("Visual Studio Code", "vsc").unwrap();
+        assert_eq!(m.positions, vec![0, 7, 14]);
+    }
+
+    #[test]
+    fn test_camel_case_bonus() {
+        let camel = fuzzy_match("FileExplorer", "fe").unwrap();
+        let plain = fuzzy_match("Fileexplorer", "fe").unwrap();
+        assert!(camel.score > plain.score);
+        assert_eq!(camel.positions, vec![0, 4]);
+    }
+
+    #[test]
+    fn test_consecutive_beats_scattered() {
+        let consecutive = fuzzy_match("terminal", "term").unwrap();
+        let scattered = fuzzy_match("the error manager", "term").unwrap();
+        assert!(consecutive.score > scattered.score);
+    }
+
+    #[test]
+    fn test_smart_case() {
+        // Lowercase pattern is case-insensitive
+        assert!(fuzzy_match("Firefox", "fire").is_some());
+        // Uppercase in pattern makes it case-sensitive
+        assert!(fuzzy_match("firefox", "Fire").is_none());
+        assert!(fuzzy_match("Firefox", "Fire").is_some());
+
+        let insensitive = Matcher::new().with_case(CaseMatching::Insensitive);
+        assert!(insensitive.fuzzy("firefox", "Fire").is_some());
+    }
+
+    #[test]
+    fn test_unicode_positions_are_char_indices() {
+        let m = fuzzy_match("Café Noir", "cn").unwrap();
+        assert_eq!(m.positions, vec![0, 5]);
+    }
+
+    #[test]
+    fn test_anchored_matches() {
+        let matcher = Matcher::new();
+
+        let m = matcher.exact("Windows Terminal", "term").unwrap();
+        assert_eq!(m.positions, vec![8, 9, 10, 11]);
+        assert!(matcher.exact("Windows Terminal", "wt").is_none());
+
+        assert!(matcher.prefix("Windows Terminal", "win").is_some());
+        assert!(matcher.prefix("Windows Terminal", "term").is_none());
+
+        let m = matcher.suffix("Windows Terminal", "nal").unwrap();
+        assert_eq!(m.positions, vec![13, 14, 15]);
+        assert!(matcher.suffix("Windows Terminal", "win").is_none());
+
+        assert!(matcher.equal("notepad", "notepad").is_some());
+        assert!(matcher.equal("notepad++", "notepad").is_none());
+
+        // Exact matches at word boundaries outscore ones mid-word
+        let boundary = matcher.exact("git bash", "bash").unwrap();
+        let inner = matcher.exact("gitbash", "bash").unwrap();
+        assert!(boundary.score > inner.score);
+    }
+
+    #[test]
+    fn test_positions_to_ranges() {
+        assert_eq!(positions_to_ranges(&[]), Vec::<(usize, usize)>::new());
+        assert_eq!(
+            positions_to_ranges(&[0, 1, 2, 5, 7, 8]),
+            vec![(0, 3), (5, 6), (7, 9)]
+        );
+    }
+}
@@ -5,6 +5,7 @@
 //! - ThemePicker: Grid of Hyde themes (Ctrl+1)
 //! - WallpaperPicker: Grid of wallpapers (Ctrl+2)
 
+#[cfg(windows)]
 use crate::platform::win32::{HOTKEY_ID_LAUNCHER, HOTKEY_ID_THEME, HOTKEY_ID_WALLPAPER};
 
 /// Operating mode for Wolfy
@@ -24,6 +25,7 @@ pub enum Mode {
 impl Mode {
     /// Get the hotkey ID associated with this mode
     /// TailView doesn't have a hotkey (it's entered via task panel click)
+    #[cfg(windows)]
     pub fn hotkey_id(&self) -> i32 {
         match self {
             Mode::Launcher => HOTKEY_ID_LAUNCHER,
@@ -34,6 +36,7 @@ impl Mode {
     }
 
     /// Create a Mode from a hotkey ID
+    #[cfg(windows)]
     pub fn from_hotkey_id(id: i32) -> Option<Mode> {
         match id {
             HOTKEY_ID_LAUNCHER => Some(Mode::Launcher),
@@ -72,6 +75,27 @@ impl Mode {
             Mode::TailView => "Task Output",
         }
     }
+
+    /// Stable key used for this mode in state.toml
+    pub fn state_key(&self) -> &'static str {
+        match self {
+            Mode::Launcher => "launcher",
+            Mode::ThemePicker => "theme-picker",
+            Mode::WallpaperPicker => "wallpaper-picker",
+            Mode::TailView => "tail-view",
+        }
+    }
+
+    /// Parse a mode from its state.toml key
+    pub fn from_state_key(key: &str) -> Option<Mode> {
+        match key {
+            "launcher" => Some(Mode::Launcher),
+            "theme-picker" => Some(Mode::ThemePicker),
+            "wallpaper-picker" => Some(Mode::WallpaperPicker),
+            "tail-view" => Some(Mode::TailView),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -79,6 +103,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(windows)]
     fn test_mode_from_hotkey_id() {
         assert_eq!(
             Mode::from_hotkey_id(HOTKEY_ID_LAUNCHER),
@@ -110,6 +135,19 @@ mod tests {
         assert!(!Mode::WallpaperPicker.has_wallpaper_panel());
     }
 
+    #[test]
+    fn test_mode_state_key_round_trip() {
+        for mode in [
+            Mode::Launcher,
+            Mode::ThemePicker,
+            Mode::WallpaperPicker,
+            Mode::TailView,
+        ] {
+            assert_eq!(Mode::from_state_key(mode.state_key()), Some(mode));
+        }
+        assert_eq!(Mode::from_state_key("unknown"), None);
+    }
+
     #[test]
     fn test_default_mode() {
         assert_eq!(Mode::default(), Mode::Launcher);
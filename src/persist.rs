@@ -0,0 +1,199 @@
+//! Crash-safe file persistence (no Windows dependencies)
+//!
+//! Small helpers for the files Wolfy rewrites at runtime (history, state):
+//! - `write_atomic` writes to a temp file in the same directory, fsyncs it and
+//!   renames it over the original, keeping the previous version as `<file>.bak`
+//! - `read` falls back to the `.bak` copy when the primary file is missing or empty
+//! - `lock` takes an advisory lock on `<file>.lock` so concurrent instances can
+//!   re-read, merge and write without clobbering each other
+//!
+//! Typical read-modify-write:
+//! ```ignore
+//! let _lock = persist::lock(&path)?;
+//! let current = persist::read(&path).unwrap_or_default();
+//! persist::write_atomic(&path, merge(current).as_bytes())?;
+//! ```
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long `lock` waits for another instance before giving up
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Delay between lock attempts
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Append a suffix to a path's file name (`history.txt` -> `history.txt.bak`)
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Path of the backup kept next to `path`
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+/// Path of the advisory lock file for `path`
+pub fn lock_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".lock")
+}
+
+/// An exclusive advisory lock, released when dropped
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+/// Take an exclusive advisory lock for `path`, waiting up to `LOCK_TIMEOUT`
+///
+/// The lock is held on a sibling `.lock` file because `write_atomic` replaces
+/// the data file itself. Returns `ErrorKind::WouldBlock` on timeout.
+pub fn lock(path: &Path) -> io::Result<FileLock> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path(path))?;
+
+    let deadline = Instant::now() + LOCK_TIMEOUT;
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(FileLock { file }),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(LOCK_RETRY_INTERVAL);
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    format!("timed out waiting for lock on {:?}", path),
+                ));
+            }
+            Err(TryLockError::Error(e)) => return Err(e),
+        }
+    }
+}
+
+/// Atomically replace `path` with `contents`, keeping the old file as `.bak`
+///
+/// Readers see either the old or the new contents, never a truncated file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Write and flush to disk before anything touches the original
+    let tmp_path = with_suffix(path, &format!(".tmp.{}", std::process::id()));
+    let result = (|| {
+        let mut tmp = File::create(&tmp_path)?;
+        tmp.write_all(contents)?;
+        tmp.sync_all()
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    // Keep the previous version (only if it has content worth keeping)
+    if fs::metadata(path).is_ok_and(|m| m.len() > 0) {
+        if let Err(e) = fs::copy(path, backup_path(path)) {
+            crate::log!("Failed to back up {:?}: {:?}", path, e);
+        }
+    }
+
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Read `path`, falling back to its `.bak` copy if it is missing or empty
+pub fn read(path: &Path) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) if !contents.trim().is_empty() => Ok(contents),
+        primary => match fs::read_to_string(backup_path(path)) {
+            Ok(backup) if !backup.trim().is_empty() => {
+                crate::log!("Recovered {:?} from backup", path);
+                Ok(backup)
+            }
+            _ => primary,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_atomic_keeps_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested").join("state.txt");
+
+        write_atomic(&path, b"first").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+        assert!(!backup_path(&path).exists());
+
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "first");
+
+        // No temp files left behind
+        let leftovers: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().contains(".tmp."))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_read_falls_back_to_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.txt");
+
+        assert!(read(&path).is_err());
+
+        write_atomic(&path, b"good").unwrap();
+        write_atomic(&path, b"newer").unwrap();
+        assert_eq!(read(&path).unwrap(), "newer");
+
+        // Simulate a crash that truncated the primary file
+        File::create(&path).unwrap();
+        assert_eq!(read(&path).unwrap(), "good");
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read(&path).unwrap(), "good");
+    }
+
+    #[test]
+    fn test_lock_is_exclusive() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.txt");
+
+        let guard = lock(&path).unwrap();
+        let other = File::open(lock_path(&path)).unwrap();
+        assert!(matches!(other.try_lock(), Err(TryLockError::WouldBlock)));
+
+        drop(guard);
+        assert!(other.try_lock().is_ok());
+    }
+}
@@ -1,9 +1,14 @@
 //! Platform abstraction layer
 //!
-//! Currently only Windows (win32) is supported.
+//! Input events are platform-independent; the rest is currently only
+//! implemented for Windows (win32).
+
+pub mod event;
 
 #[cfg(target_os = "windows")]
 pub mod win32;
 
+pub use event::*;
+
 #[cfg(target_os = "windows")]
 pub use win32::*;
@@ -155,24 +155,26 @@ fn is_wslg_app(name: &str) -> bool {
         || name_lower.contains("(kali")
 }
 
-/// Sort apps by usage frequency (descending) then alphabetically
+/// Sort apps by frecency (descending) then alphabetically
+///
+/// History is keyed by launch target, which is what `App::on_submit` records.
 fn sort_apps(apps: &mut [AppEntry], history: Option<&History>) {
     apps.sort_by(|a, b| {
         match history {
             Some(h) => {
-                let idx_a = h.sort_index(&a.id);
-                let idx_b = h.sort_index(&b.id);
+                let idx_a = h.sort_index(&a.launch_target);
+                let idx_b = h.sort_index(&b.launch_target);
 
-                // Both in history: sort by frequency (descending)
-                if idx_a >= 0 && idx_b >= 0 {
-                    return idx_b.cmp(&idx_a);
+                // Both in history: sort by frecency (descending)
+                if idx_a >= 0.0 && idx_b >= 0.0 {
+                    return idx_b.total_cmp(&idx_a);
                 }
 
                 // One in history: history item first
-                if idx_a >= 0 {
+                if idx_a >= 0.0 {
                     return std::cmp::Ordering::Less;
                 }
-                if idx_b >= 0 {
+                if idx_b >= 0.0 {
                     return std::cmp::Ordering::Greater;
                 }
 
@@ -4,54 +4,83 @@ use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 /// Simple polling-based file watcher
-/// Checks file modification time to detect changes
+/// Checks file modification times to detect changes in a set of files
 pub struct PollingFileWatcher {
-    path: PathBuf,
-    last_modified: Option<SystemTime>,
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
 }
 
 impl PollingFileWatcher {
     /// Create a new file watcher for the given path
     pub fn new(path: &Path) -> Self {
-        let last_modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        Self::with_paths(&[path.to_path_buf()])
+    }
 
-        Self {
-            path: path.to_path_buf(),
-            last_modified,
-        }
+    /// Create a file watcher for several files (e.g. a theme and its imports)
+    pub fn with_paths(paths: &[PathBuf]) -> Self {
+        let mut watcher = Self { files: Vec::new() };
+        watcher.set_paths(paths);
+        watcher
+    }
+
+    /// Replace the set of watched files
+    /// Files that were already watched keep their last known modification time
+    pub fn set_paths(&mut self, paths: &[PathBuf]) {
+        self.files = paths
+            .iter()
+            .map(|path| {
+                let last_modified = self
+                    .files
+                    .iter()
+                    .find(|(p, _)| p == path)
+                    .map(|(_, t)| *t)
+                    .unwrap_or_else(|| modified_time(path));
+                (path.clone(), last_modified)
+            })
+            .collect();
     }
 
-    /// Check if the file has been modified since last check
-    /// Returns true if the file was modified (and updates internal state)
+    /// Check if any file has been modified since last check
+    /// Returns true if a file was modified (and updates internal state)
     pub fn check_modified(&mut self) -> bool {
-        let current = std::fs::metadata(&self.path)
-            .and_then(|m| m.modified())
-            .ok();
-
-        match (&self.last_modified, &current) {
-            (Some(last), Some(curr)) if curr > last => {
-                self.last_modified = current;
-                true
-            }
-            (None, Some(_)) => {
-                // File appeared or became readable
-                self.last_modified = current;
-                true
+        let mut modified = false;
+
+        for (path, last_modified) in &mut self.files {
+            let current = modified_time(path);
+
+            match (&*last_modified, &current) {
+                (Some(last), Some(curr)) if curr > last => {
+                    *last_modified = current;
+                    modified = true;
+                }
+                (None, Some(_)) => {
+                    // File appeared or became readable
+                    *last_modified = current;
+                    modified = true;
+                }
+                _ => {}
             }
-            _ => false,
         }
+
+        modified
     }
 
-    /// Get the watched file path
+    /// Get the first watched file path
     pub fn path(&self) -> &Path {
-        &self.path
+        self.files
+            .first()
+            .map(|(p, _)| p.as_path())
+            .unwrap_or(Path::new(""))
     }
 
-    /// Reset the last modified time to current
+    /// Reset the last modified times to current
     /// Useful after a reload to prevent immediate re-trigger
     pub fn reset(&mut self) {
-        self.last_modified = std::fs::metadata(&self.path)
-            .and_then(|m| m.modified())
-            .ok();
+        for (path, last_modified) in &mut self.files {
+            *last_modified = modified_time(path);
+        }
     }
 }
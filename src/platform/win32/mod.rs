@@ -26,12 +26,12 @@ pub use hotkey::{
 };
 pub use icon::{CachedIcon, IconLoader};
 pub use image::{ImageLoader, LoadedImage};
-pub use render::Renderer;
+pub use render::{apply_base_font, char_width, DirectWriteMeasure, Renderer};
 pub use shortcut::{parse_lnk, ShortcutInfo};
 pub use wallpaper::{get_wallpaper_path, set_wallpaper};
 pub use window::{
-    clear_window_callback, create_window, destroy_window, get_client_size, get_monitor_width,
-    hide_window, invalidate_window, is_window_visible, register_window_class, reposition_window,
-    resize_window, set_window_callback, set_window_opacity, show_window, toggle_window,
-    unregister_window_class, WindowConfig,
+    clear_window_callback, create_window, destroy_window, get_client_size, get_monitor_size,
+    get_monitor_width, hide_window, invalidate_window, is_window_visible, monitor_layout_context,
+    register_window_class, reposition_window, resize_window, set_window_callback,
+    set_window_opacity, show_window, toggle_window, unregister_window_class, WindowConfig,
 };
@@ -1,6 +1,8 @@
 //! Direct2D rendering for Wolfy with per-pixel alpha support
 
 use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
 
 use windows::core::Error;
 use windows::Win32::Foundation::{HWND, POINT, SIZE};
@@ -16,10 +18,101 @@ use windows::Win32::UI::WindowsAndMessaging::{UpdateLayeredWindow, ULW_ALPHA};
 
 use super::dpi::DpiInfo;
 use super::icon::IconLoader;
-use super::image::LoadedImage;
+use super::image::{ImageLoader, LoadedImage};
 use super::window::get_client_size;
-use crate::theme::types::Color;
-use crate::widget::CornerRadii;
+use crate::render::{shadow_layers, DisplayList, DrawCommand, TextAlign, TextMeasure, TextRun};
+use crate::theme::tree::ThemeTree;
+use crate::theme::types::{Color, GradientKind, ImageScale, LayoutContext, Paint, Rect, Shadow};
+use crate::widget::{base_font, CornerRadii};
+
+/// Text measured with DirectWrite, as the renderer draws it
+#[derive(Debug)]
+pub struct DirectWriteMeasure {
+    factory: IDWriteFactory,
+}
+
+impl DirectWriteMeasure {
+    pub fn new() -> Result<Self, Error> {
+        let factory = unsafe { DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED)? };
+        Ok(Self { factory })
+    }
+
+    /// Width of a single line of text in pixels
+    pub fn measure(
+        &self,
+        text: &str,
+        font_family: &str,
+        font_size: f32,
+        bold: bool,
+    ) -> Result<f32, Error> {
+        let family: Vec<u16> = font_family
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let text: Vec<u16> = text.encode_utf16().collect();
+        let weight = if bold {
+            DWRITE_FONT_WEIGHT_BOLD
+        } else {
+            DWRITE_FONT_WEIGHT_REGULAR
+        };
+
+        unsafe {
+            let format = self.factory.CreateTextFormat(
+                windows::core::PCWSTR(family.as_ptr()),
+                None,
+                weight,
+                DWRITE_FONT_STYLE_NORMAL,
+                DWRITE_FONT_STRETCH_NORMAL,
+                font_size,
+                windows::core::w!("en-US"),
+            )?;
+            let layout = self
+                .factory
+                .CreateTextLayout(&text, &format, f32::MAX, f32::MAX)?;
+            let mut metrics = DWRITE_TEXT_METRICS::default();
+            layout.GetMetrics(&mut metrics)?;
+            Ok(metrics.widthIncludingTrailingWhitespace)
+        }
+    }
+}
+
+impl TextMeasure for DirectWriteMeasure {
+    fn text_width(&self, text: &str, font_family: &str, font_size: f32, bold: bool) -> f32 {
+        self.measure(text, font_family, font_size, bold)
+            .unwrap_or_else(|e| {
+                log!("Failed to measure text in {}: {:?}", font_family, e);
+                0.0
+            })
+    }
+}
+
+/// Width of "0" in a font, in unscaled pixels: the size of a `ch` unit
+pub fn char_width(font_family: &str, font_size: f32) -> Result<f32, Error> {
+    DirectWriteMeasure::new()?.measure("0", font_family, font_size, false)
+}
+
+/// Take the font `em` and `ch` units are relative to from the theme,
+/// measuring its "0" for `ch`, and measure text with DirectWrite
+pub fn apply_base_font(ctx: &mut LayoutContext, theme: &ThemeTree) {
+    let (family, size) = base_font(theme);
+    ctx.base_font_size = size;
+    let measure = match DirectWriteMeasure::new() {
+        Ok(measure) => measure,
+        Err(e) => {
+            log!("Failed to create a DirectWrite factory: {:?}", e);
+            return;
+        }
+    };
+    match measure.measure("0", &family, size, false) {
+        Ok(width) => ctx.char_width = width,
+        Err(e) => log!(
+            "Failed to measure the width of \"0\" in {}: {:?}",
+            family,
+            e
+        ),
+    }
+    ctx.text_measure = Some(Rc::new(measure));
+}
 
 /// A cached brush key
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -141,6 +234,10 @@ pub struct Renderer {
     icon_loader: IconLoader,
     /// Whether content needs to be re-rendered (false = just update opacity)
     content_dirty: bool,
+    /// Text formats of display list text runs, by family, size, weight and alignment
+    text_formats: HashMap<(String, u32, bool, TextAlign), IDWriteTextFormat>,
+    /// Display list images by path, scale, blur and size (`None` if loading failed)
+    image_cache: HashMap<(String, ImageScale, u32, u32, u32), Option<ID2D1Bitmap>>,
 }
 
 impl Renderer {
@@ -171,6 +268,8 @@ impl Renderer {
             dpi,
             icon_loader: IconLoader::new(),
             content_dirty: true,
+            text_formats: HashMap::new(),
+            image_cache: HashMap::new(),
         };
 
         log!("Renderer::new() completed (render_target=None, will create lazily)");
@@ -212,6 +311,7 @@ impl Renderer {
         };
 
         self.brush_cache.clear();
+        self.image_cache.clear();
 
         log!("  Calling CreateDCRenderTarget...");
         unsafe {
@@ -260,6 +360,7 @@ impl Renderer {
                 self.offscreen = None;
                 self.brush_cache.clear();
                 self.icon_loader.clear_cache();
+                self.image_cache.clear();
             }
         }
 
@@ -282,6 +383,7 @@ impl Renderer {
         self.offscreen = None;
         self.brush_cache.clear();
         self.icon_loader.clear_cache();
+        self.image_cache.clear();
         self.content_dirty = true;
         Ok(())
     }
@@ -293,6 +395,7 @@ impl Renderer {
         self.offscreen = None;
         self.brush_cache.clear();
         self.icon_loader.clear_cache();
+        self.image_cache.clear();
         self.content_dirty = true;
         Ok(())
     }
@@ -947,6 +1050,173 @@ impl Renderer {
         Ok(())
     }
 
+    /// Create a brush for a paint over `rect`
+    ///
+    /// Solid colors come from the brush cache; gradient brushes are laid out
+    /// for the rect, so they're created on every call.
+    fn create_paint_brush(&mut self, rect: D2D_RECT_F, paint: &Paint) -> Result<ID2D1Brush, Error> {
+        let gradient = match paint {
+            Paint::Solid(color) => return Ok(self.get_brush(*color)?.into()),
+            Paint::Gradient(gradient) => gradient,
+        };
+
+        let target = self
+            .render_target
+            .as_ref()
+            .ok_or_else(|| Error::from_win32())?;
+
+        let bounds = Rect::new(
+            rect.left,
+            rect.top,
+            rect.right - rect.left,
+            rect.bottom - rect.top,
+        );
+        let stops: Vec<D2D1_GRADIENT_STOP> = gradient
+            .stops
+            .iter()
+            .map(|stop| D2D1_GRADIENT_STOP {
+                position: stop.position,
+                color: D2D1_COLOR_F {
+                    r: stop.color.r,
+                    g: stop.color.g,
+                    b: stop.color.b,
+                    a: stop.color.a,
+                },
+            })
+            .collect();
+
+        unsafe {
+            let gradient_stops = target.CreateGradientStopCollection(
+                &stops,
+                D2D1_GAMMA_2_2,
+                D2D1_EXTEND_MODE_CLAMP,
+            )?;
+
+            match gradient.kind {
+                GradientKind::Linear { .. } => {
+                    let ((x0, y0), (x1, y1)) = gradient.linear_points(bounds);
+                    let props = D2D1_LINEAR_GRADIENT_BRUSH_PROPERTIES {
+                        startPoint: D2D_POINT_2F { x: x0, y: y0 },
+                        endPoint: D2D_POINT_2F { x: x1, y: y1 },
+                    };
+                    Ok(target
+                        .CreateLinearGradientBrush(&props, None, &gradient_stops)?
+                        .into())
+                }
+                GradientKind::Radial { .. } => {
+                    let (radius_x, radius_y) = gradient.radial_radii(bounds);
+                    let props = D2D1_RADIAL_GRADIENT_BRUSH_PROPERTIES {
+                        center: D2D_POINT_2F {
+                            x: bounds.x + bounds.width / 2.0,
+                            y: bounds.y + bounds.height / 2.0,
+                        },
+                        gradientOriginOffset: D2D_POINT_2F { x: 0.0, y: 0.0 },
+                        radiusX: radius_x,
+                        radiusY: radius_y,
+                    };
+                    Ok(target
+                        .CreateRadialGradientBrush(&props, None, &gradient_stops)?
+                        .into())
+                }
+            }
+        }
+    }
+
+    /// Fill a rectangle with per-corner radii using a solid color or gradient
+    pub fn fill_paint(
+        &mut self,
+        rect: D2D_RECT_F,
+        radii: CornerRadii,
+        paint: &Paint,
+    ) -> Result<(), Error> {
+        if !paint.is_visible() {
+            return Ok(());
+        }
+
+        let brush = self.create_paint_brush(rect, paint)?;
+
+        if let Some(ref target) = self.render_target {
+            unsafe {
+                if !radii.is_uniform() {
+                    let path = self.create_rounded_rect_path(rect, radii)?;
+                    target.FillGeometry(&path, &brush, None);
+                } else if radii.top_left > 0.0 {
+                    let rounded = D2D1_ROUNDED_RECT {
+                        rect,
+                        radiusX: radii.top_left,
+                        radiusY: radii.top_left,
+                    };
+                    target.FillRoundedRectangle(&rounded, &brush);
+                } else {
+                    target.FillRectangle(&rect, &brush);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw (stroke) a rectangle border with per-corner radii using a solid
+    /// color or gradient
+    pub fn draw_paint(
+        &mut self,
+        rect: D2D_RECT_F,
+        radii: CornerRadii,
+        paint: &Paint,
+        stroke_width: f32,
+    ) -> Result<(), Error> {
+        if !paint.is_visible() || stroke_width <= 0.0 {
+            return Ok(());
+        }
+
+        let brush = self.create_paint_brush(rect, paint)?;
+
+        if let Some(ref target) = self.render_target {
+            unsafe {
+                if !radii.is_uniform() {
+                    let path = self.create_rounded_rect_path(rect, radii)?;
+                    target.DrawGeometry(&path, &brush, stroke_width, None);
+                } else if radii.top_left > 0.0 {
+                    let rounded = D2D1_ROUNDED_RECT {
+                        rect,
+                        radiusX: radii.top_left,
+                        radiusY: radii.top_left,
+                    };
+                    target.DrawRoundedRectangle(&rounded, &brush, stroke_width, None);
+                } else {
+                    target.DrawRectangle(&rect, &brush, stroke_width, None);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw the box shadow of a rectangle with per-corner radii
+    ///
+    /// `shadow` must already be scaled to pixels. The blur is approximated
+    /// with the stacked layers from `Shadow::layers()`. Unlike CSS the shadow
+    /// isn't cut out under the box, so draw it before an opaque background.
+    pub fn draw_shadow(
+        &mut self,
+        rect: D2D_RECT_F,
+        radii: CornerRadii,
+        shadow: &Shadow,
+    ) -> Result<(), Error> {
+        if shadow.color.a <= 0.0 {
+            return Ok(());
+        }
+
+        let bounds = Rect::new(
+            rect.left,
+            rect.top,
+            rect.right - rect.left,
+            rect.bottom - rect.top,
+        );
+        for (layer, layer_radii, color) in shadow_layers(bounds, radii, shadow) {
+            self.fill_paint(to_d2d(layer), layer_radii, &Paint::Solid(color))?;
+        }
+        Ok(())
+    }
+
     /// Push a rounded rectangle clip with per-corner radii using a geometry layer
     pub fn push_rounded_clip_corners(
         &mut self,
@@ -1267,6 +1537,70 @@ impl Renderer {
         Ok(())
     }
 
+    /// Draw text with highlighted runs (e.g. fuzzy match positions)
+    /// `ranges` are half-open `(start, end)` character indices into `text`
+    pub fn draw_text_highlighted(
+        &mut self,
+        text: &str,
+        format: &IDWriteTextFormat,
+        rect: D2D_RECT_F,
+        color: Color,
+        highlight_color: Color,
+        ranges: &[(usize, usize)],
+    ) -> Result<(), Error> {
+        if ranges.is_empty() {
+            return self.draw_text(text, format, rect, color);
+        }
+
+        let brush = self.get_brush(color)?;
+        let highlight_brush = self.get_brush(highlight_color)?;
+        let text_wide: Vec<u16> = text.encode_utf16().collect();
+
+        // Map character indices to UTF-16 offsets for DirectWrite text ranges
+        let mut utf16_offsets: Vec<u32> = Vec::with_capacity(text.len() + 1);
+        let mut offset = 0u32;
+        for c in text.chars() {
+            utf16_offsets.push(offset);
+            offset += c.len_utf16() as u32;
+        }
+        utf16_offsets.push(offset);
+
+        if let Some(ref target) = self.render_target {
+            unsafe {
+                let layout = self.dwrite_factory.CreateTextLayout(
+                    &text_wide,
+                    format,
+                    rect.right - rect.left,
+                    rect.bottom - rect.top,
+                )?;
+
+                for &(start, end) in ranges {
+                    let (Some(&s), Some(&e)) = (utf16_offsets.get(start), utf16_offsets.get(end))
+                    else {
+                        continue;
+                    };
+                    let range = DWRITE_TEXT_RANGE {
+                        startPosition: s,
+                        length: e - s,
+                    };
+                    layout.SetDrawingEffect(&highlight_brush, range)?;
+                    layout.SetFontWeight(DWRITE_FONT_WEIGHT_BOLD, range)?;
+                }
+
+                target.DrawTextLayout(
+                    D2D_POINT_2F {
+                        x: rect.left,
+                        y: rect.top,
+                    },
+                    &layout,
+                    &brush,
+                    D2D1_DRAW_TEXT_OPTIONS_NONE,
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Draw text centered within a rect
     pub fn draw_text_centered(
         &mut self,
@@ -1563,6 +1897,70 @@ impl Renderer {
         Ok(())
     }
 
+    /// Create a blurred copy of a bitmap, for `backdrop-blur`
+    ///
+    /// The bitmap is halved with linear filtering until one pixel covers
+    /// about `radius` source pixels, and the small result is meant to be
+    /// drawn stretched (e.g. with `draw_bitmap_cover`), which smooths it out
+    /// again. Returns `None` when there's nothing to blur.
+    pub fn blur_bitmap(
+        &self,
+        bitmap: &ID2D1Bitmap,
+        radius: f32,
+    ) -> Result<Option<ID2D1Bitmap>, Error> {
+        let Some(ref target) = self.render_target else {
+            return Ok(None);
+        };
+        if radius < 2.0 {
+            return Ok(None);
+        }
+
+        let mut current = bitmap.clone();
+        let mut covered = 1.0;
+        while covered * 2.0 <= radius {
+            let size = unsafe { current.GetPixelSize() };
+            if size.width < 2 || size.height < 2 {
+                break;
+            }
+            let half = D2D_SIZE_U {
+                width: size.width / 2,
+                height: size.height / 2,
+            };
+
+            unsafe {
+                let scratch = target.CreateCompatibleRenderTarget(
+                    None,
+                    Some(&half),
+                    None,
+                    D2D1_COMPATIBLE_RENDER_TARGET_OPTIONS_NONE,
+                )?;
+                let dest_size = scratch.GetSize();
+                let dest = D2D_RECT_F {
+                    left: 0.0,
+                    top: 0.0,
+                    right: dest_size.width,
+                    bottom: dest_size.height,
+                };
+
+                scratch.BeginDraw();
+                scratch.Clear(None);
+                scratch.DrawBitmap(
+                    &current,
+                    Some(&dest),
+                    1.0,
+                    D2D1_BITMAP_INTERPOLATION_MODE_LINEAR,
+                    None,
+                );
+                scratch.EndDraw(None, None)?;
+
+                current = scratch.GetBitmap()?;
+            }
+            covered *= 2.0;
+        }
+
+        Ok(Some(current))
+    }
+
     /// Get render target (for advanced bitmap operations)
     pub fn render_target(&self) -> Option<&ID2D1DCRenderTarget> {
         self.render_target.as_ref()
@@ -1593,6 +1991,269 @@ impl Renderer {
             false
         }
     }
+
+    /// Play back a display list built by widgets
+    pub fn draw_list(&mut self, list: &DisplayList) -> Result<(), Error> {
+        // The layer of each open clip or opacity layer, popped by `Pop`
+        let mut layers: Vec<Option<ID2D1Layer>> = Vec::new();
+
+        for command in list.commands() {
+            match command {
+                DrawCommand::Fill { rect, radii, paint } => {
+                    self.fill_paint(to_d2d(*rect), *radii, paint)?;
+                }
+                DrawCommand::Stroke {
+                    rect,
+                    radii,
+                    paint,
+                    width,
+                } => {
+                    self.draw_paint(to_d2d(*rect), *radii, paint, *width)?;
+                }
+                DrawCommand::Shadow {
+                    rect,
+                    radii,
+                    shadow,
+                } => {
+                    self.draw_shadow(to_d2d(*rect), *radii, shadow)?;
+                }
+                DrawCommand::Line {
+                    from,
+                    to,
+                    color,
+                    width,
+                } => {
+                    self.draw_line(from.0, from.1, to.0, to.1, *color, *width)?;
+                }
+                DrawCommand::Text(run) => {
+                    let format = self.run_text_format(run)?;
+                    self.draw_text_highlighted(
+                        &run.text,
+                        &format,
+                        to_d2d(run.rect),
+                        run.color,
+                        run.highlight_color,
+                        &run.highlights,
+                    )?;
+                }
+                DrawCommand::TextSelection {
+                    run,
+                    start,
+                    end,
+                    color,
+                } => {
+                    let start_x = self.run_caret_x(run, *start)?;
+                    let end_x = self.run_caret_x(run, *end)?;
+                    let selection = rect(start_x, run.rect.y, end_x - start_x, run.rect.height);
+                    self.fill_rect(selection, *color)?;
+                }
+                DrawCommand::Caret {
+                    run,
+                    index,
+                    color,
+                    width,
+                } => {
+                    let x = self.run_caret_x(run, *index)?;
+                    let bottom = run.rect.y + run.rect.height;
+                    self.draw_line(x, run.rect.y, x, bottom, *color, *width)?;
+                }
+                DrawCommand::Image {
+                    path,
+                    rect,
+                    scale,
+                    blur,
+                    placeholder,
+                } => match self.image_bitmap(path, *rect, *scale, *blur) {
+                    Some(bitmap) => self.draw_bitmap_cover(&bitmap, to_d2d(*rect), 1.0)?,
+                    None => {
+                        if let Some(color) = placeholder {
+                            self.fill_rect(to_d2d(*rect), *color)?;
+                        }
+                    }
+                },
+                DrawCommand::Icon { path, rect } => {
+                    self.draw_icon(path, to_d2d(*rect), 1.0);
+                }
+                DrawCommand::PushClip { rect, radii } => {
+                    let layer = self.push_rounded_clip_corners(to_d2d(*rect), *radii)?;
+                    layers.push(layer);
+                }
+                DrawCommand::PushLayer { opacity } => {
+                    let layer = self.push_opacity_layer(*opacity)?;
+                    layers.push(layer);
+                }
+                DrawCommand::Pop => {
+                    if let Some(Some(_layer)) = layers.pop() {
+                        self.pop_layer();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Push a layer that draws its content with an opacity
+    /// Returns a layer that must be popped with pop_layer()
+    pub fn push_opacity_layer(&mut self, opacity: f32) -> Result<Option<ID2D1Layer>, Error> {
+        if let Some(ref target) = self.render_target {
+            unsafe {
+                let layer = target.CreateLayer(None)?;
+
+                let layer_params = D2D1_LAYER_PARAMETERS {
+                    contentBounds: D2D_RECT_F {
+                        left: f32::NEG_INFINITY,
+                        top: f32::NEG_INFINITY,
+                        right: f32::INFINITY,
+                        bottom: f32::INFINITY,
+                    },
+                    geometricMask: std::mem::ManuallyDrop::new(None),
+                    maskAntialiasMode: D2D1_ANTIALIAS_MODE_PER_PRIMITIVE,
+                    maskTransform: windows::Foundation::Numerics::Matrix3x2::identity(),
+                    opacity,
+                    opacityBrush: std::mem::ManuallyDrop::new(None),
+                    layerOptions: D2D1_LAYER_OPTIONS_NONE,
+                };
+                target.PushLayer(&layer_params, &layer);
+
+                return Ok(Some(layer));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The text format for a text run, created on first use
+    fn run_text_format(&mut self, run: &TextRun) -> Result<IDWriteTextFormat, Error> {
+        let key = (
+            run.font_family.clone(),
+            run.font_size.to_bits(),
+            run.bold,
+            run.align,
+        );
+        if let Some(format) = self.text_formats.get(&key) {
+            return Ok(format.clone());
+        }
+
+        let format = self.create_text_format(&run.font_family, run.font_size, run.bold, false)?;
+        let alignment = match run.align {
+            TextAlign::Leading => DWRITE_TEXT_ALIGNMENT_LEADING,
+            TextAlign::Center => DWRITE_TEXT_ALIGNMENT_CENTER,
+            TextAlign::Trailing => DWRITE_TEXT_ALIGNMENT_TRAILING,
+        };
+        unsafe {
+            format.SetTextAlignment(alignment)?;
+        }
+
+        self.text_formats.insert(key, format.clone());
+        Ok(format)
+    }
+
+    /// X position of the caret before a character of a text run
+    fn run_caret_x(&mut self, run: &TextRun, index: usize) -> Result<f32, Error> {
+        let format = self.run_text_format(run)?;
+        // DirectWrite positions are UTF-16 offsets
+        let position = run.text.chars().take(index).map(char::len_utf16).sum();
+        let x = self.get_caret_position(
+            &run.text,
+            &format,
+            position,
+            run.rect.width,
+            run.rect.height,
+        )?;
+        Ok(run.rect.x + x)
+    }
+
+    /// The bitmap for a display list image, loaded on first use
+    ///
+    /// Returns `None` if the image can't be loaded, which is cached too.
+    fn image_bitmap(
+        &mut self,
+        path: &str,
+        rect: Rect,
+        scale: ImageScale,
+        blur: f32,
+    ) -> Option<ID2D1Bitmap> {
+        let width = rect.width.max(1.0) as u32;
+        let height = rect.height.max(1.0) as u32;
+
+        // Only the dimensions the image is scaled to matter
+        let (key_width, key_height) = match scale {
+            ImageScale::None => (0, 0),
+            ImageScale::Width => (width, 0),
+            ImageScale::Height => (0, height),
+            ImageScale::Both => (width, height),
+        };
+        let key = (
+            path.to_string(),
+            scale,
+            blur.to_bits(),
+            key_width,
+            key_height,
+        );
+        if let Some(bitmap) = self.image_cache.get(&key) {
+            return bitmap.clone();
+        }
+
+        log!("Renderer loading image: {} ({}x{})", path, width, height);
+        let bitmap = self.load_image_bitmap(path, width, height, scale, blur);
+        self.image_cache.insert(key, bitmap.clone());
+        bitmap
+    }
+
+    fn load_image_bitmap(
+        &self,
+        path: &str,
+        width: u32,
+        height: u32,
+        scale: ImageScale,
+        blur: f32,
+    ) -> Option<ID2D1Bitmap> {
+        let loader = match ImageLoader::new() {
+            Ok(l) => l,
+            Err(e) => {
+                log!("Failed to create ImageLoader: {:?}", e);
+                return None;
+            }
+        };
+
+        // Load with appropriate scaling
+        let file = Path::new(path);
+        let loaded = match scale {
+            ImageScale::None => loader.load_from_file(file),
+            ImageScale::Width => loader.load_scaled(file, width, 0, ImageScale::Width),
+            ImageScale::Height => loader.load_scaled(file, 0, height, ImageScale::Height),
+            ImageScale::Both => loader.load_cover(file, width, height),
+        };
+        let loaded = match loaded {
+            Ok(l) => l,
+            Err(e) => {
+                log!("Failed to load image '{}': {:?}", path, e);
+                return None;
+            }
+        };
+
+        let bitmap = match self.create_bitmap(&loaded) {
+            Ok(b) => b,
+            Err(e) => {
+                log!("Failed to create D2D bitmap: {:?}", e);
+                return None;
+            }
+        };
+        if blur <= 0.0 {
+            return Some(bitmap);
+        }
+
+        // Convert the blur radius to image pixels for the image as it covers
+        // the rect
+        let cover_scale =
+            (width as f32 / loaded.width() as f32).max(height as f32 / loaded.height() as f32);
+        match self.blur_bitmap(&bitmap, blur / cover_scale) {
+            Ok(blurred) => Some(blurred.unwrap_or(bitmap)),
+            Err(e) => {
+                log!("Failed to blur bitmap: {:?}", e);
+                Some(bitmap)
+            }
+        }
+    }
 }
 
 /// Helper to create a D2D rect from position and size
@@ -1605,6 +2266,11 @@ pub fn rect(x: f32, y: f32, width: f32, height: f32) -> D2D_RECT_F {
     }
 }
 
+/// Convert a layout rect to a D2D rect
+fn to_d2d(r: Rect) -> D2D_RECT_F {
+    rect(r.x, r.y, r.width, r.height)
+}
+
 /// Helper to inset a rect
 pub fn inset_rect(r: D2D_RECT_F, inset: f32) -> D2D_RECT_F {
     D2D_RECT_F {
@@ -14,6 +14,10 @@ use windows::Win32::UI::Input::KeyboardAndMouse::SetFocus;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
 use super::dpi::{scale_px, DpiInfo};
+use super::render::apply_base_font;
+use crate::theme::tree::ThemeTree;
+use crate::theme::types::{LayoutContext, Rect};
+use crate::widget::{Placement, Size};
 
 /// Custom message to request window hide (deferred to avoid re-entrancy)
 pub const WM_APP_HIDE: u32 = WM_APP + 1;
@@ -27,6 +31,9 @@ pub struct WindowConfig {
     pub height: i32,
     /// Vertical position: 0.0 = top, 0.5 = center, 1.0 = bottom
     pub vertical_position: f32,
+    /// Placement on the monitor from the theme's `window { location }`;
+    /// overrides `vertical_position`
+    pub placement: Option<Placement>,
 }
 
 impl Default for WindowConfig {
@@ -35,6 +42,7 @@ impl Default for WindowConfig {
             width: 600,
             height: 48,
             vertical_position: 0.5, // True center of screen
+            placement: None,
         }
     }
 }
@@ -215,6 +223,23 @@ fn calculate_window_rect(config: &WindowConfig, dpi: u32) -> RECT {
         let scaled_width = scale_px(config.width, dpi);
         let scaled_height = scale_px(config.height, dpi);
 
+        if let Some(placement) = &config.placement {
+            let monitor = Rect::new(
+                monitor_area.left as f32,
+                monitor_area.top as f32,
+                monitor_width as f32,
+                monitor_height as f32,
+            );
+            let size = Size::new(scaled_width as f32, scaled_height as f32);
+            let rect = placement.scaled(dpi as f32 / 96.0).place(size, monitor);
+            return RECT {
+                left: rect.x as i32,
+                top: rect.y as i32,
+                right: rect.x as i32 + scaled_width,
+                bottom: rect.y as i32 + scaled_height,
+            };
+        }
+
         // Center horizontally on full monitor
         let x = monitor_area.left + (monitor_width - scaled_width) / 2;
 
@@ -449,3 +474,37 @@ pub fn get_monitor_width() -> i32 {
         width * 96 / dpi as i32
     }
 }
+
+/// Layout context of the primary monitor in logical pixels, for sizing a
+/// window from its theme (see `widget::window_size`)
+pub fn monitor_layout_context(theme: &ThemeTree) -> LayoutContext {
+    let (width, height) = get_monitor_size();
+    let dpi = unsafe { GetDpiForSystem() } as f32;
+    let (width, height) = (width as f32 * 96.0 / dpi, height as f32 * 96.0 / dpi);
+    let mut ctx = LayoutContext {
+        parent_size: width,
+        viewport_width: width,
+        viewport_height: height,
+        ..LayoutContext::default()
+    };
+    apply_base_font(&mut ctx, theme);
+    ctx
+}
+
+/// Get the primary monitor's size in physical pixels
+pub fn get_monitor_size() -> (i32, i32) {
+    unsafe {
+        let monitor = MonitorFromWindow(HWND::default(), MONITOR_DEFAULTTOPRIMARY);
+        let mut monitor_info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        let _ = GetMonitorInfoW(monitor, &mut monitor_info);
+
+        let monitor_area = monitor_info.rcMonitor;
+        (
+            monitor_area.right - monitor_area.left,
+            monitor_area.bottom - monitor_area.top,
+        )
+    }
+}
@@ -0,0 +1,300 @@
+//! Extended search query syntax (fzf-style, no Windows dependencies)
+//!
+//! A query is a list of space-separated terms that must all match (AND).
+//! Terms separated by `|` form an OR group. Each term can use an operator:
+//!
+//! | Syntax     | Meaning                              |
+//! |------------|--------------------------------------|
+//! | `fire`     | Fuzzy match                          |
+//! | `'fire`    | Exact substring match                |
+//! | `^fire`    | Prefix match                         |
+//! | `fox$`     | Suffix match                         |
+//! | `^fox$`    | Whole-string match                   |
+//! | `!fire`    | Exclude items containing `fire`      |
+//! | `!^fire`   | Exclude items starting with `fire`   |
+//! | `!fox$`    | Exclude items ending with `fox`      |
+//!
+//! A literal space can be written as `\ `. Terms are matched against an item's
+//! text and subtext; only text matches produce highlight positions.
+
+use crate::matcher::{Match, Matcher};
+
+/// How a single term is matched
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TermKind {
+    /// Fuzzy subsequence match
+    Fuzzy,
+    /// Exact substring match (`'term`)
+    Exact,
+    /// Anchored at the start (`^term`)
+    Prefix,
+    /// Anchored at the end (`term$`)
+    Suffix,
+    /// Whole-string match (`^term$`)
+    Equal,
+}
+
+/// A single query term
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Term {
+    pub kind: TermKind,
+    pub text: String,
+    /// Exclude items this term matches (`!term`)
+    pub negated: bool,
+}
+
+impl Term {
+    /// Parse a single whitespace-free token
+    fn parse(token: &str) -> Option<Self> {
+        let mut text = token;
+        let negated = text.starts_with('!');
+        if negated {
+            text = &text[1..];
+        }
+
+        let mut kind = if negated {
+            TermKind::Exact
+        } else {
+            TermKind::Fuzzy
+        };
+
+        if let Some(rest) = text.strip_prefix('\'') {
+            text = rest;
+            kind = TermKind::Exact;
+        } else if let Some(rest) = text.strip_prefix('^') {
+            text = rest;
+            kind = TermKind::Prefix;
+        }
+
+        if text.len() > 1 && text.ends_with('$') && !text.ends_with("\\$") {
+            text = &text[..text.len() - 1];
+            kind = if kind == TermKind::Prefix {
+                TermKind::Equal
+            } else {
+                TermKind::Suffix
+            };
+        }
+
+        if text.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            kind,
+            text: text.replace("\\$", "$"),
+            negated,
+        })
+    }
+
+    /// Match this term against a string (ignoring negation)
+    fn match_str(&self, matcher: &Matcher, target: &str) -> Option<Match> {
+        match self.kind {
+            TermKind::Fuzzy => matcher.fuzzy(target, &self.text),
+            TermKind::Exact => matcher.exact(target, &self.text),
+            TermKind::Prefix => matcher.prefix(target, &self.text),
+            TermKind::Suffix => matcher.suffix(target, &self.text),
+            TermKind::Equal => matcher.equal(target, &self.text),
+        }
+    }
+}
+
+/// Result of matching a query against an item
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueryMatch {
+    /// Combined score of all terms (higher is better)
+    pub score: i32,
+    /// Matched character positions in the item's text (sorted, deduplicated)
+    pub positions: Vec<usize>,
+}
+
+/// A parsed search query
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    /// AND-ed groups of OR-ed terms
+    groups: Vec<Vec<Term>>,
+    matcher: Matcher,
+}
+
+impl Query {
+    /// Parse a query string
+    pub fn parse(input: &str) -> Self {
+        let mut groups: Vec<Vec<Term>> = Vec::new();
+        let mut join_next = false;
+
+        for token in split_tokens(input) {
+            if token == "|" {
+                // Only meaningful between two terms
+                join_next = !groups.is_empty();
+                continue;
+            }
+
+            let Some(term) = Term::parse(&token) else {
+                continue;
+            };
+
+            match groups.last_mut() {
+                Some(group) if join_next => group.push(term),
+                _ => groups.push(vec![term]),
+            }
+            join_next = false;
+        }
+
+        Self {
+            groups,
+            matcher: Matcher::new(),
+        }
+    }
+
+    /// Use a specific matcher (e.g. to force case sensitivity)
+    pub fn with_matcher(mut self, matcher: Matcher) -> Self {
+        self.matcher = matcher;
+        self
+    }
+
+    /// Whether the query has no terms (matches everything)
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// The parsed term groups (AND of ORs)
+    pub fn groups(&self) -> &[Vec<Term>] {
+        &self.groups
+    }
+
+    /// Match the query against an item's text and optional subtext
+    ///
+    /// Returns None if any group fails. Subtext matches count for half so items
+    /// matching on their name rank above items matching only on their description.
+    pub fn matches(&self, text: &str, subtext: Option<&str>) -> Option<QueryMatch> {
+        let mut result = QueryMatch::default();
+
+        for group in &self.groups {
+            let mut best: Option<(i32, Vec<usize>)> = None;
+
+            for term in group {
+                let candidate = if term.negated {
+                    let excluded = term.match_str(&self.matcher, text).is_some()
+                        || subtext.is_some_and(|s| term.match_str(&self.matcher, s).is_some());
+                    (!excluded).then(|| (0, Vec::new()))
+                } else if let Some(m) = term.match_str(&self.matcher, text) {
+                    Some((m.score, m.positions))
+                } else {
+                    subtext
+                        .and_then(|s| term.match_str(&self.matcher, s))
+                        .map(|m| (m.score / 2, Vec::new()))
+                };
+
+                if let Some(candidate) = candidate {
+                    if best.as_ref().is_none_or(|b| candidate.0 > b.0) {
+                        best = Some(candidate);
+                    }
+                }
+            }
+
+            let (score, positions) = best?;
+            result.score += score;
+            result.positions.extend(positions);
+        }
+
+        result.positions.sort_unstable();
+        result.positions.dedup();
+        Some(result)
+    }
+}
+
+/// Split on unescaped whitespace, turning `\ ` into a literal space
+fn split_tokens(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek() == Some(&' ') {
+            current.push(' ');
+            chars.next();
+        } else if c.is_whitespace() {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_operators() {
+        let q = Query::parse("fire 'fox ^moz org$ ^exact$ !beta");
+        let kinds: Vec<(TermKind, &str, bool)> = q
+            .groups()
+            .iter()
+            .map(|g| (g[0].kind, g[0].text.as_str(), g[0].negated))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (TermKind::Fuzzy, "fire", false),
+                (TermKind::Exact, "fox", false),
+                (TermKind::Prefix, "moz", false),
+                (TermKind::Suffix, "org", false),
+                (TermKind::Equal, "exact", false),
+                (TermKind::Exact, "beta", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_alternation_and_escapes() {
+        let q = Query::parse("^core go$ | rb$ | py$ visual\\ studio");
+        assert_eq!(q.groups().len(), 3);
+        assert_eq!(q.groups()[1].len(), 3);
+        assert_eq!(q.groups()[2][0].text, "visual studio");
+
+        // Stray operators are ignored
+        assert!(Query::parse("  | ! ^ ").is_empty());
+    }
+
+    #[test]
+    fn test_and_terms() {
+        let q = Query::parse("win term");
+        assert!(q.matches("Windows Terminal", None).is_some());
+        assert!(q.matches("Windows Explorer", None).is_none());
+
+        let m = q.matches("Windows Terminal", None).unwrap();
+        assert_eq!(m.positions, vec![0, 1, 2, 8, 9, 10, 11]);
+    }
+
+    #[test]
+    fn test_negation_checks_subtext() {
+        let q = Query::parse("code !insiders");
+        assert!(q.matches("Visual Studio Code", Some("Stable")).is_some());
+        assert!(q
+            .matches("Visual Studio Code", Some("Code - Insiders"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_alternation() {
+        let q = Query::parse("^note | ^calc");
+        assert!(q.matches("Notepad", None).is_some());
+        assert!(q.matches("Calculator", None).is_some());
+        assert!(q.matches("Paint", None).is_none());
+    }
+
+    #[test]
+    fn test_subtext_ranks_below_text() {
+        let q = Query::parse("edge");
+        let text = q.matches("Microsoft Edge", None).unwrap();
+        let subtext = q.matches("Browser", Some("Microsoft Edge")).unwrap();
+        assert!(text.score > subtext.score);
+        assert!(subtext.positions.is_empty());
+    }
+}
@@ -0,0 +1,194 @@
+//! Bundled fonts for the software renderer
+//!
+//! DejaVu Sans and DejaVu Sans Mono, each in regular and bold, are compiled
+//! in so text looks the same on every machine. A run's font family picks the
+//! monospace face when it names a monospace font and the sans face
+//! otherwise. A character missing from that face is taken from the other
+//! one, and a character that neither face has (for example the task panel's
+//! Nerd Font icons) is skipped.
+
+use std::sync::OnceLock;
+
+use ab_glyph::{Font, FontRef, GlyphId, Outline};
+
+use super::TextMeasure;
+
+const SANS: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+const SANS_BOLD: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+const MONO: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono.ttf");
+const MONO_BOLD: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansMono-Bold.ttf");
+
+/// Words in a family name that mark a monospace font
+const MONOSPACE_WORDS: &[&str] = &["mono", "code", "consol", "courier", "terminal"];
+
+/// The bundled faces: sans, sans bold, mono, mono bold
+fn faces() -> &'static [FontRef<'static>; 4] {
+    static FACES: OnceLock<[FontRef<'static>; 4]> = OnceLock::new();
+    FACES.get_or_init(|| {
+        [SANS, SANS_BOLD, MONO, MONO_BOLD]
+            .map(|data| FontRef::try_from_slice(data).expect("bundled font is valid"))
+    })
+}
+
+/// Whether a font family is drawn with the monospace face
+pub fn is_monospace(family: &str) -> bool {
+    let family = family.to_lowercase();
+    family == "monospace" || MONOSPACE_WORDS.iter().any(|word| family.contains(word))
+}
+
+/// The face for a family and weight, then the one to fall back on
+fn face_pair(family: &str, bold: bool) -> [&'static FontRef<'static>; 2] {
+    let [sans, sans_bold, mono, mono_bold] = faces();
+    match (is_monospace(family), bold) {
+        (false, false) => [sans, mono],
+        (false, true) => [sans_bold, mono_bold],
+        (true, false) => [mono, sans],
+        (true, true) => [mono_bold, sans_bold],
+    }
+}
+
+/// Pixels per font unit of a face at a font size (the em size in pixels)
+fn units_scale(face: &FontRef<'_>, font_size: f32) -> f32 {
+    font_size / face.units_per_em().unwrap_or(2048.0)
+}
+
+/// A glyph placed on a line of text
+#[derive(Clone, Debug)]
+pub struct PlacedGlyph {
+    /// Index of the character it draws
+    pub index: usize,
+    /// Left edge of the glyph's advance, from the start of the line
+    pub x: f32,
+    face: &'static FontRef<'static>,
+    id: GlyphId,
+    scale: f32,
+}
+
+impl PlacedGlyph {
+    /// The glyph's outline, in font units with y going up
+    pub fn outline(&self) -> Option<Outline> {
+        self.face.outline(self.id)
+    }
+
+    /// Pixels per font unit
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+}
+
+/// A single line of text laid out with the bundled fonts
+#[derive(Clone, Debug, Default)]
+pub struct Line {
+    pub glyphs: Vec<PlacedGlyph>,
+    /// X of the caret before each character, and after the last
+    pub carets: Vec<f32>,
+    /// Distance from the top of the line to the baseline
+    pub ascent: f32,
+    /// Height of the line from the highest ascender to the lowest descender
+    pub height: f32,
+}
+
+impl Line {
+    pub fn width(&self) -> f32 {
+        self.carets.last().copied().unwrap_or(0.0)
+    }
+}
+
+/// Lay out a line of text from x = 0, kerning pairs of glyphs from the same
+/// face; `bold` tells which characters use the bold face
+pub fn layout(text: &str, family: &str, font_size: f32, bold: impl Fn(usize) -> bool) -> Line {
+    let [regular, _] = face_pair(family, false);
+    let line_scale = units_scale(regular, font_size);
+    let mut line = Line {
+        ascent: regular.ascent_unscaled() * line_scale,
+        height: (regular.ascent_unscaled() - regular.descent_unscaled()) * line_scale,
+        ..Default::default()
+    };
+
+    let mut x = 0.0;
+    let mut previous: Option<(&FontRef<'_>, GlyphId)> = None;
+    for (index, c) in text.chars().enumerate() {
+        line.carets.push(x);
+        let found = face_pair(family, bold(index))
+            .into_iter()
+            .map(|face| (face, face.glyph_id(c)))
+            .find(|(_, id)| id.0 != 0);
+        let Some((face, id)) = found else {
+            previous = None;
+            continue;
+        };
+
+        let scale = units_scale(face, font_size);
+        if let Some((previous_face, previous_id)) = previous {
+            if std::ptr::eq(previous_face, face) {
+                x += face.kern_unscaled(previous_id, id) * scale;
+                if let Some(caret) = line.carets.last_mut() {
+                    *caret = x;
+                }
+            }
+        }
+        line.glyphs.push(PlacedGlyph {
+            index,
+            x,
+            face,
+            id,
+            scale,
+        });
+        x += face.h_advance_unscaled(id) * scale;
+        previous = Some((face, id));
+    }
+    line.carets.push(x);
+    line
+}
+
+/// Width of a line of text in pixels
+pub fn text_width(text: &str, family: &str, font_size: f32, bold: bool) -> f32 {
+    layout(text, family, font_size, |_| bold).width()
+}
+
+/// Text measured with the bundled fonts, as the software renderer draws it
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BundledFonts;
+
+impl TextMeasure for BundledFonts {
+    fn text_width(&self, text: &str, font_family: &str, font_size: f32, bold: bool) -> f32 {
+        text_width(text, font_family, font_size, bold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_families() {
+        assert!(is_monospace("JetBrainsMono Nerd Font"));
+        assert!(is_monospace("Cascadia Code"));
+        assert!(is_monospace("monospace"));
+        assert!(!is_monospace("Segoe UI Variable Text"));
+
+        // Monospace glyphs all have the same advance
+        let mono = |text| text_width(text, "Cascadia Mono", 10.0, false);
+        assert_eq!(mono("iii"), mono("WWW"));
+        assert!(text_width("iii", "Segoe UI", 10.0, false) < mono("iii"));
+        // Bold is wider, and the width scales with the font size
+        let sans = text_width("Firefox", "Segoe UI", 20.0, false);
+        assert!(text_width("Firefox", "Segoe UI", 20.0, true) > sans);
+        assert!((text_width("Firefox", "Segoe UI", 10.0, false) * 2.0 - sans).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_layout() {
+        let line = layout("Aé€\u{f0ed}", "Segoe UI", 16.0, |_| false);
+        // Accented letters and symbols come from the font; the Nerd Font
+        // icon is left out
+        let drawn: Vec<usize> = line.glyphs.iter().map(|glyph| glyph.index).collect();
+        assert_eq!(drawn, [0, 1, 2]);
+        assert!(line.glyphs.iter().all(|glyph| glyph.outline().is_some()));
+        assert_eq!(line.carets.len(), 5);
+        assert_eq!(line.carets[3], line.carets[4]);
+        assert!(line.ascent > 0.0 && line.ascent < line.height);
+
+        assert_eq!(layout("", "Segoe UI", 16.0, |_| false).width(), 0.0);
+    }
+}
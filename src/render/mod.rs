@@ -0,0 +1,422 @@
+//! Backend-neutral display lists
+//!
+//! Widgets don't draw directly: they describe what to draw as a
+//! `DisplayList` of commands (filled and stroked rectangles, text runs,
+//! images, clips and layers) in physical pixels. A backend then plays the
+//! list back: `Renderer::draw_list` with Direct2D on Windows, or the pure
+//! Rust `software::SoftwareRenderer` everywhere else, which lets widget
+//! painting be tested without a window.
+
+#[cfg(not(windows))]
+pub mod font;
+#[cfg(not(windows))]
+pub mod software;
+
+use crate::theme::types::{Color, ImageScale, Paint, Rect, Shadow};
+use crate::widget::CornerRadii;
+
+/// Measures text the way a backend draws it, so widgets can size
+/// themselves to their text (see `LayoutContext::text_width`)
+pub trait TextMeasure: std::fmt::Debug {
+    /// Width in pixels of a single line of text
+    fn text_width(&self, text: &str, font_family: &str, font_size: f32, bold: bool) -> f32;
+}
+
+/// Horizontal alignment of a text run within its rect
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextAlign {
+    #[default]
+    Leading,
+    Center,
+    Trailing,
+}
+
+/// A single line of text, vertically centered in its rect
+///
+/// Text doesn't wrap and isn't clipped to the rect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub rect: Rect,
+    pub font_family: String,
+    pub font_size: f32,
+    pub bold: bool,
+    pub color: Color,
+    pub align: TextAlign,
+    /// Half-open `(start, end)` character ranges drawn bold in `highlight_color`
+    pub highlights: Vec<(usize, usize)>,
+    pub highlight_color: Color,
+}
+
+impl TextRun {
+    pub fn new(
+        text: impl Into<String>,
+        rect: Rect,
+        font_family: impl Into<String>,
+        font_size: f32,
+        color: Color,
+    ) -> Self {
+        Self {
+            text: text.into(),
+            rect,
+            font_family: font_family.into(),
+            font_size,
+            bold: false,
+            color,
+            align: TextAlign::Leading,
+            highlights: Vec::new(),
+            highlight_color: color,
+        }
+    }
+
+    pub fn bold(mut self, bold: bool) -> Self {
+        self.bold = bold;
+        self
+    }
+
+    pub fn align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn highlight(mut self, ranges: Vec<(usize, usize)>, color: Color) -> Self {
+        self.highlights = ranges;
+        self.highlight_color = color;
+        self
+    }
+}
+
+/// A single drawing operation, in physical pixels
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    /// Fill a rectangle with per-corner radii
+    Fill {
+        rect: Rect,
+        radii: CornerRadii,
+        paint: Paint,
+    },
+    /// Stroke the outline of a rectangle, centered on its edge
+    Stroke {
+        rect: Rect,
+        radii: CornerRadii,
+        paint: Paint,
+        width: f32,
+    },
+    /// The box shadow of a rectangle, already scaled to pixels
+    Shadow {
+        rect: Rect,
+        radii: CornerRadii,
+        shadow: Shadow,
+    },
+    Line {
+        from: (f32, f32),
+        to: (f32, f32),
+        color: Color,
+        width: f32,
+    },
+    Text(TextRun),
+    /// Fill the run's height between the caret positions of two characters
+    TextSelection {
+        run: TextRun,
+        start: usize,
+        end: usize,
+        color: Color,
+    },
+    /// A vertical line over the run's height at a character's caret position
+    Caret {
+        run: TextRun,
+        index: usize,
+        color: Color,
+        width: f32,
+    },
+    /// An image file covering `rect` (cropped to keep its aspect ratio)
+    ///
+    /// `scale` is how the file is decoded for the rect, `blur` a blur radius
+    /// in pixels and `placeholder` what to fill with if it can't be loaded.
+    Image {
+        path: String,
+        rect: Rect,
+        scale: ImageScale,
+        blur: f32,
+        placeholder: Option<Color>,
+    },
+    /// The icon of an executable or shortcut, stretched over `rect`
+    Icon {
+        path: String,
+        rect: Rect,
+    },
+    /// Clip what follows to a rounded rectangle, until the matching `Pop`
+    PushClip {
+        rect: Rect,
+        radii: CornerRadii,
+    },
+    /// Draw what follows with an opacity, until the matching `Pop`
+    PushLayer {
+        opacity: f32,
+    },
+    Pop,
+}
+
+/// A list of drawing commands built by widgets
+///
+/// The helpers skip commands that wouldn't draw anything, like invisible
+/// paints or empty text.
+#[derive(Clone, Debug, Default)]
+pub struct DisplayList {
+    commands: Vec<DrawCommand>,
+}
+
+impl DisplayList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    /// Append another list's commands
+    pub fn extend(&mut self, other: DisplayList) {
+        self.commands.extend(other.commands);
+    }
+
+    /// The text runs in the list, in drawing order
+    pub fn texts(&self) -> impl Iterator<Item = &TextRun> {
+        self.commands.iter().filter_map(|command| match command {
+            DrawCommand::Text(run) => Some(run),
+            _ => None,
+        })
+    }
+
+    pub fn fill(&mut self, rect: Rect, radii: CornerRadii, paint: &Paint) {
+        if paint.is_visible() {
+            self.push(DrawCommand::Fill {
+                rect,
+                radii,
+                paint: paint.clone(),
+            });
+        }
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        self.fill(rect, CornerRadii::zero(), &Paint::Solid(color));
+    }
+
+    pub fn fill_rounded_rect(&mut self, rect: Rect, radius: f32, color: Color) {
+        self.fill(rect, CornerRadii::uniform(radius), &Paint::Solid(color));
+    }
+
+    pub fn stroke(&mut self, rect: Rect, radii: CornerRadii, paint: &Paint, width: f32) {
+        if paint.is_visible() && width > 0.0 {
+            self.push(DrawCommand::Stroke {
+                rect,
+                radii,
+                paint: paint.clone(),
+                width,
+            });
+        }
+    }
+
+    pub fn stroke_rect(&mut self, rect: Rect, color: Color, width: f32) {
+        self.stroke(rect, CornerRadii::zero(), &Paint::Solid(color), width);
+    }
+
+    pub fn stroke_rounded_rect(&mut self, rect: Rect, radius: f32, color: Color, width: f32) {
+        self.stroke(
+            rect,
+            CornerRadii::uniform(radius),
+            &Paint::Solid(color),
+            width,
+        );
+    }
+
+    pub fn shadow(&mut self, rect: Rect, radii: CornerRadii, shadow: &Shadow) {
+        if shadow.color.a > 0.0 {
+            self.push(DrawCommand::Shadow {
+                rect,
+                radii,
+                shadow: *shadow,
+            });
+        }
+    }
+
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), color: Color, width: f32) {
+        if color.a > 0.0 && width > 0.0 {
+            self.push(DrawCommand::Line {
+                from,
+                to,
+                color,
+                width,
+            });
+        }
+    }
+
+    pub fn text(&mut self, run: TextRun) {
+        if !run.text.is_empty() {
+            self.push(DrawCommand::Text(run));
+        }
+    }
+
+    pub fn text_selection(&mut self, run: TextRun, start: usize, end: usize, color: Color) {
+        if start < end && color.a > 0.0 {
+            self.push(DrawCommand::TextSelection {
+                run,
+                start,
+                end,
+                color,
+            });
+        }
+    }
+
+    pub fn caret(&mut self, run: TextRun, index: usize, color: Color, width: f32) {
+        self.push(DrawCommand::Caret {
+            run,
+            index,
+            color,
+            width,
+        });
+    }
+
+    pub fn image(
+        &mut self,
+        path: impl Into<String>,
+        rect: Rect,
+        scale: ImageScale,
+        blur: f32,
+        placeholder: Option<Color>,
+    ) {
+        self.push(DrawCommand::Image {
+            path: path.into(),
+            rect,
+            scale,
+            blur,
+            placeholder,
+        });
+    }
+
+    pub fn icon(&mut self, path: impl Into<String>, rect: Rect) {
+        self.push(DrawCommand::Icon {
+            path: path.into(),
+            rect,
+        });
+    }
+
+    pub fn push_clip(&mut self, rect: Rect, radii: CornerRadii) {
+        self.push(DrawCommand::PushClip { rect, radii });
+    }
+
+    pub fn push_layer(&mut self, opacity: f32) {
+        self.push(DrawCommand::PushLayer { opacity });
+    }
+
+    pub fn pop(&mut self) {
+        self.push(DrawCommand::Pop);
+    }
+}
+
+/// The rectangles a shadow is drawn with, from `Shadow::layers()`
+///
+/// Each layer is the box grown by its outset and moved by the offset.
+/// Rounded corners grow and shrink with the layer, sharp ones stay sharp.
+pub fn shadow_layers(
+    rect: Rect,
+    radii: CornerRadii,
+    shadow: &Shadow,
+) -> Vec<(Rect, CornerRadii, Color)> {
+    shadow
+        .layers()
+        .into_iter()
+        .filter_map(|(outset, color)| {
+            let layer = Rect::new(
+                rect.x + shadow.offset_x - outset,
+                rect.y + shadow.offset_y - outset,
+                rect.width + outset * 2.0,
+                rect.height + outset * 2.0,
+            );
+            if layer.width <= 0.0 || layer.height <= 0.0 {
+                return None;
+            }
+            let grow = |radius: f32| {
+                if radius > 0.0 {
+                    (radius + outset).max(0.0)
+                } else {
+                    0.0
+                }
+            };
+            let radii = CornerRadii {
+                top_left: grow(radii.top_left),
+                top_right: grow(radii.top_right),
+                bottom_right: grow(radii.bottom_right),
+                bottom_left: grow(radii.bottom_left),
+            };
+            Some((layer, radii, color))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_helpers_skip_invisible_commands() {
+        let mut list = DisplayList::new();
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        list.fill_rect(rect, Color::TRANSPARENT);
+        list.stroke_rect(rect, Color::WHITE, 0.0);
+        list.line((0.0, 0.0), (1.0, 1.0), Color::TRANSPARENT, 1.0);
+        list.text(TextRun::new("", rect, "Segoe UI", 12.0, Color::WHITE));
+        assert!(list.is_empty());
+
+        list.fill_rounded_rect(rect, 4.0, Color::RED);
+        list.text(TextRun::new("hi", rect, "Segoe UI", 12.0, Color::WHITE));
+        assert_eq!(list.commands().len(), 2);
+        assert_eq!(
+            list.commands()[0],
+            DrawCommand::Fill {
+                rect,
+                radii: CornerRadii::uniform(4.0),
+                paint: Paint::Solid(Color::RED),
+            }
+        );
+        assert_eq!(
+            list.texts().map(|t| t.text.as_str()).collect::<Vec<_>>(),
+            ["hi"]
+        );
+    }
+
+    #[test]
+    fn test_shadow_layers() {
+        let rect = Rect::new(10.0, 10.0, 20.0, 20.0);
+        let shadow = Shadow {
+            offset_x: 2.0,
+            offset_y: 4.0,
+            blur: 0.0,
+            spread: 3.0,
+            color: Color::BLACK,
+        };
+        let radii = CornerRadii {
+            top_left: 4.0,
+            ..CornerRadii::zero()
+        };
+        let layers = shadow_layers(rect, radii, &shadow);
+        assert_eq!(layers.len(), 1);
+        let (layer, layer_radii, color) = layers[0];
+        assert_eq!(layer, Rect::new(9.0, 11.0, 26.0, 26.0));
+        assert_eq!(layer_radii.top_left, 7.0);
+        assert_eq!(layer_radii.top_right, 0.0);
+        assert_eq!(color, Color::BLACK);
+    }
+}
@@ -0,0 +1,886 @@
+//! Pure Rust rendering of display lists
+//!
+//! Rasterizes with tiny-skia into a `Pixmap`, with text drawn from the
+//! glyph outlines of the bundled fonts in `font`. Images and icons aren't
+//! loaded from disk: they're looked up by path among the pixmaps added with
+//! `SoftwareRenderer::add_image`, e.g. read with `Pixmap::decode_png`.
+
+use std::collections::HashMap;
+use std::io;
+
+use ab_glyph::OutlineCurve;
+use tiny_skia::{
+    FillRule, FilterQuality, GradientStop, LinearGradient, Mask, PathBuilder, Pattern, PixmapPaint,
+    RadialGradient, Shader, SpreadMode, Stroke, Transform,
+};
+
+use crate::theme::types::{Color, GradientKind, Paint, Rect};
+use crate::widget::CornerRadii;
+
+use super::font::{self, Line, PlacedGlyph};
+use super::{shadow_layers, DisplayList, DrawCommand, TextAlign, TextRun};
+
+/// Distance from a rounded corner's ends to its bezier control points, as a
+/// fraction of the radius
+const CORNER_CONTROL: f32 = 1.0 - 0.552_284_8;
+
+/// An image of premultiplied RGBA pixels
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pixmap(tiny_skia::Pixmap);
+
+impl Pixmap {
+    /// A transparent pixmap; None if a side is 0 or it's too large
+    pub fn new(width: u32, height: u32) -> Option<Self> {
+        tiny_skia::Pixmap::new(width, height).map(Self)
+    }
+
+    /// Build a pixmap from straight (not premultiplied) RGBA bytes
+    pub fn from_rgba8(width: u32, height: u32, data: &[u8]) -> Option<Self> {
+        let size = tiny_skia::IntSize::from_wh(width, height)?;
+        let data = data
+            .chunks_exact(4)
+            .flat_map(|p| {
+                let color = tiny_skia::ColorU8::from_rgba(p[0], p[1], p[2], p[3]).premultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect();
+        tiny_skia::Pixmap::from_vec(data, size).map(Self)
+    }
+
+    /// Read a PNG file's contents
+    pub fn decode_png(data: &[u8]) -> io::Result<Self> {
+        tiny_skia::Pixmap::decode_png(data)
+            .map(Self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    /// Encode as an RGBA PNG
+    pub fn encode_png(&self) -> io::Result<Vec<u8>> {
+        self.0
+            .encode_png()
+            .map_err(|e| io::Error::other(e.to_string()))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.0.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.0.height()
+    }
+
+    /// Replace every pixel with a color
+    pub fn fill(&mut self, color: Color) {
+        self.0.fill(skia_color(color));
+    }
+
+    /// The straight color of a pixel, transparent outside the pixmap
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        match self.0.pixel(x, y) {
+            Some(pixel) => {
+                let c = pixel.demultiply();
+                Color::rgba(c.red(), c.green(), c.blue(), c.alpha())
+            }
+            None => Color::TRANSPARENT,
+        }
+    }
+}
+
+fn skia_color(color: Color) -> tiny_skia::Color {
+    let channel = |v: f32| v.clamp(0.0, 1.0);
+    tiny_skia::Color::from_rgba(
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        channel(color.a),
+    )
+    .unwrap_or(tiny_skia::Color::TRANSPARENT)
+}
+
+fn solid(color: Color) -> tiny_skia::Paint<'static> {
+    tiny_skia::Paint {
+        shader: Shader::SolidColor(skia_color(color)),
+        ..Default::default()
+    }
+}
+
+/// The tiny-skia paint for a fill or stroke of `rect`
+fn skia_paint(paint: &Paint, rect: Rect) -> tiny_skia::Paint<'static> {
+    let gradient = match paint {
+        Paint::Solid(color) => return solid(*color),
+        Paint::Gradient(gradient) => gradient,
+    };
+    let stops: Vec<_> = gradient
+        .stops
+        .iter()
+        .map(|stop| GradientStop::new(stop.position, skia_color(stop.color)))
+        .collect();
+    let shader = match gradient.kind {
+        GradientKind::Linear { .. } => {
+            let ((x0, y0), (x1, y1)) = gradient.linear_points(rect);
+            LinearGradient::new(
+                tiny_skia::Point::from_xy(x0, y0),
+                tiny_skia::Point::from_xy(x1, y1),
+                stops,
+                SpreadMode::Pad,
+                Transform::identity(),
+            )
+        }
+        GradientKind::Radial { .. } => {
+            // A unit circle stretched over the ellipse
+            let (rx, ry) = gradient.radial_radii(rect);
+            let (cx, cy) = (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+            let center = tiny_skia::Point::from_xy(0.0, 0.0);
+            RadialGradient::new(
+                center,
+                center,
+                1.0,
+                stops,
+                SpreadMode::Pad,
+                Transform::from_row(rx.max(f32::EPSILON), 0.0, 0.0, ry.max(f32::EPSILON), cx, cy),
+            )
+        }
+    };
+    // Degenerate gradients show their last color
+    let shader = shader.unwrap_or_else(|| {
+        let last = gradient
+            .stops
+            .last()
+            .map_or(Color::TRANSPARENT, |s| s.color);
+        Shader::SolidColor(skia_color(last))
+    });
+    tiny_skia::Paint {
+        shader,
+        ..Default::default()
+    }
+}
+
+/// Outline of a rectangle with per-corner radii, each at most half a side
+fn rounded_rect_path(rect: Rect, radii: CornerRadii) -> Option<tiny_skia::Path> {
+    let (x, y, w, h) = (rect.x, rect.y, rect.width, rect.height);
+    let radius = |r: f32| r.min(w / 2.0).min(h / 2.0).max(0.0);
+    let (tl, tr, br, bl) = (
+        radius(radii.top_left),
+        radius(radii.top_right),
+        radius(radii.bottom_right),
+        radius(radii.bottom_left),
+    );
+    let k = CORNER_CONTROL;
+
+    let mut path = PathBuilder::new();
+    path.move_to(x + tl, y);
+    path.line_to(x + w - tr, y);
+    path.cubic_to(x + w - tr * k, y, x + w, y + tr * k, x + w, y + tr);
+    path.line_to(x + w, y + h - br);
+    path.cubic_to(
+        x + w,
+        y + h - br * k,
+        x + w - br * k,
+        y + h,
+        x + w - br,
+        y + h,
+    );
+    path.line_to(x + bl, y + h);
+    path.cubic_to(x + bl * k, y + h, x, y + h - bl * k, x, y + h - bl);
+    path.line_to(x, y + tl);
+    path.cubic_to(x, y + tl * k, x + tl * k, y, x + tl, y);
+    path.close();
+    path.finish()
+}
+
+/// Add a glyph's outline to a path, with its origin at `(x, baseline)`
+fn push_glyph(path: &mut PathBuilder, glyph: &PlacedGlyph, x: f32, baseline: f32) {
+    let Some(outline) = glyph.outline() else {
+        return;
+    };
+    let scale = glyph.scale();
+    let point = |p: ab_glyph::Point| (x + p.x * scale, baseline - p.y * scale);
+
+    // Contours are runs of curves that each start where the last one ended
+    let mut end = None;
+    for curve in &outline.curves {
+        let (start, last) = match *curve {
+            OutlineCurve::Line(a, b) => (a, b),
+            OutlineCurve::Quad(a, _, c) => (a, c),
+            OutlineCurve::Cubic(a, _, _, d) => (a, d),
+        };
+        if end != Some(start) {
+            if end.is_some() {
+                path.close();
+            }
+            let (sx, sy) = point(start);
+            path.move_to(sx, sy);
+        }
+        match *curve {
+            OutlineCurve::Line(_, b) => {
+                let (bx, by) = point(b);
+                path.line_to(bx, by);
+            }
+            OutlineCurve::Quad(_, b, c) => {
+                let ((bx, by), (cx, cy)) = (point(b), point(c));
+                path.quad_to(bx, by, cx, cy);
+            }
+            OutlineCurve::Cubic(_, b, c, d) => {
+                let ((bx, by), (cx, cy), (dx, dy)) = (point(b), point(c), point(d));
+                path.cubic_to(bx, by, cx, cy, dx, dy);
+            }
+        }
+        end = Some(last);
+    }
+    if end.is_some() {
+        path.close();
+    }
+}
+
+/// Box blur `count` lines of `length` pixels, `step` apart along a line and
+/// `stride` apart between lines
+fn blur_lines(
+    pixels: &mut [[f32; 4]],
+    length: usize,
+    count: usize,
+    step: usize,
+    stride: usize,
+    radius: usize,
+) {
+    let mut line = vec![[0.0f32; 4]; length];
+    for l in 0..count {
+        for (i, slot) in line.iter_mut().enumerate() {
+            *slot = pixels[l * stride + i * step];
+        }
+        for i in 0..length {
+            let (start, end) = (i.saturating_sub(radius), (i + radius + 1).min(length));
+            let mut sum = [0.0; 4];
+            for pixel in &line[start..end] {
+                for c in 0..4 {
+                    sum[c] += pixel[c];
+                }
+            }
+            let n = (end - start) as f32;
+            pixels[l * stride + i * step] = sum.map(|v| v / n);
+        }
+    }
+}
+
+/// Blur with three passes of a box blur, close to a gaussian
+fn blur(pixmap: &mut tiny_skia::Pixmap, radius: f32) {
+    let box_radius = (radius / 2.0).round() as usize;
+    if box_radius == 0 {
+        return;
+    }
+    let (width, height) = (pixmap.width() as usize, pixmap.height() as usize);
+    // Averaging premultiplied channels keeps them premultiplied
+    let mut pixels: Vec<[f32; 4]> = pixmap
+        .data()
+        .chunks_exact(4)
+        .map(|p| [p[0], p[1], p[2], p[3]].map(f32::from))
+        .collect();
+    for _ in 0..3 {
+        blur_lines(&mut pixels, width, height, 1, width, box_radius);
+        blur_lines(&mut pixels, height, width, width, 1, box_radius);
+    }
+    for (bytes, pixel) in pixmap.data_mut().chunks_exact_mut(4).zip(pixels) {
+        for (byte, value) in bytes.iter_mut().zip(pixel) {
+            *byte = value.round() as u8;
+        }
+    }
+}
+
+/// How a pixmap is fitted into a rect
+#[derive(Clone, Copy)]
+enum Fit {
+    /// Keep the aspect ratio and crop to fill the rect
+    Cover,
+    Stretch,
+}
+
+/// What a pushed clip or layer restores when popped
+enum Saved {
+    Clip(Option<Mask>),
+    Layer {
+        below: tiny_skia::Pixmap,
+        opacity: f32,
+    },
+}
+
+/// Draws display lists into pixmaps
+#[derive(Default)]
+pub struct SoftwareRenderer {
+    /// Images and icons by path
+    images: HashMap<String, Pixmap>,
+}
+
+impl SoftwareRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make an image available to `Image` and `Icon` commands for `path`
+    pub fn add_image(&mut self, path: impl Into<String>, image: Pixmap) {
+        self.images.insert(path.into(), image);
+    }
+
+    /// Draw a display list over what's already in `target`
+    ///
+    /// Clips and layers left open at the end of the list are closed.
+    pub fn draw_list(&self, target: &mut Pixmap, list: &DisplayList) {
+        let mut canvas = Canvas {
+            target: &mut target.0,
+            mask: None,
+            stack: Vec::new(),
+        };
+        for command in list.commands() {
+            canvas.draw(self, command);
+        }
+        while !canvas.stack.is_empty() {
+            canvas.pop();
+        }
+    }
+}
+
+/// A pixmap being drawn into, with the clips and layers pushed so far
+struct Canvas<'a> {
+    target: &'a mut tiny_skia::Pixmap,
+    /// Coverage of the current clip, `None` when unclipped
+    mask: Option<Mask>,
+    stack: Vec<Saved>,
+}
+
+impl Canvas<'_> {
+    fn draw(&mut self, renderer: &SoftwareRenderer, command: &DrawCommand) {
+        match command {
+            DrawCommand::Fill { rect, radii, paint } => {
+                self.fill(*rect, *radii, &skia_paint(paint, *rect))
+            }
+            DrawCommand::Stroke {
+                rect,
+                radii,
+                paint,
+                width,
+            } => {
+                if let Some(path) = rounded_rect_path(*rect, *radii) {
+                    let stroke = Stroke {
+                        width: *width,
+                        ..Default::default()
+                    };
+                    self.target.stroke_path(
+                        &path,
+                        &skia_paint(paint, *rect),
+                        &stroke,
+                        Transform::identity(),
+                        self.mask.as_ref(),
+                    );
+                }
+            }
+            DrawCommand::Shadow {
+                rect,
+                radii,
+                shadow,
+            } => {
+                for (layer, layer_radii, color) in shadow_layers(*rect, *radii, shadow) {
+                    self.fill(layer, layer_radii, &solid(color));
+                }
+            }
+            DrawCommand::Line {
+                from,
+                to,
+                color,
+                width,
+            } => {
+                let mut path = PathBuilder::new();
+                path.move_to(from.0, from.1);
+                path.line_to(to.0, to.1);
+                if let Some(path) = path.finish() {
+                    let stroke = Stroke {
+                        width: *width,
+                        ..Default::default()
+                    };
+                    self.target.stroke_path(
+                        &path,
+                        &solid(*color),
+                        &stroke,
+                        Transform::identity(),
+                        self.mask.as_ref(),
+                    );
+                }
+            }
+            DrawCommand::Text(run) => self.text(run),
+            DrawCommand::TextSelection {
+                run,
+                start,
+                end,
+                color,
+            } => {
+                let (x0, x1) = (caret_x(run, *start), caret_x(run, *end));
+                let rect = Rect::new(x0, run.rect.y, x1 - x0, run.rect.height);
+                self.fill(rect, CornerRadii::zero(), &solid(*color));
+            }
+            DrawCommand::Caret {
+                run,
+                index,
+                color,
+                width,
+            } => {
+                let x = caret_x(run, *index);
+                let from = (x, run.rect.y);
+                let to = (x, run.rect.y + run.rect.height);
+                self.draw(
+                    renderer,
+                    &DrawCommand::Line {
+                        from,
+                        to,
+                        color: *color,
+                        width: *width,
+                    },
+                );
+            }
+            DrawCommand::Image {
+                path,
+                rect,
+                blur,
+                placeholder,
+                ..
+            } => match renderer.images.get(path) {
+                Some(image) => self.image(image, *rect, Fit::Cover, *blur),
+                None => {
+                    if let Some(color) = placeholder {
+                        self.fill(*rect, CornerRadii::zero(), &solid(*color));
+                    }
+                }
+            },
+            DrawCommand::Icon { path, rect } => {
+                if let Some(image) = renderer.images.get(path) {
+                    self.image(image, *rect, Fit::Stretch, 0.0);
+                }
+            }
+            DrawCommand::PushClip { rect, radii } => {
+                let Some(path) = rounded_rect_path(*rect, *radii) else {
+                    // Nothing shows through an empty clip
+                    let empty = Mask::new(self.target.width(), self.target.height());
+                    let outer = std::mem::replace(&mut self.mask, empty);
+                    self.stack.push(Saved::Clip(outer));
+                    return;
+                };
+                let mask = match &self.mask {
+                    Some(outer) => {
+                        let mut mask = outer.clone();
+                        mask.intersect_path(&path, FillRule::Winding, true, Transform::identity());
+                        Some(mask)
+                    }
+                    None => Mask::new(self.target.width(), self.target.height()).map(|mut mask| {
+                        mask.fill_path(&path, FillRule::Winding, true, Transform::identity());
+                        mask
+                    }),
+                };
+                let outer = std::mem::replace(&mut self.mask, mask);
+                self.stack.push(Saved::Clip(outer));
+            }
+            DrawCommand::PushLayer { opacity } => {
+                let Some(blank) = tiny_skia::Pixmap::new(self.target.width(), self.target.height())
+                else {
+                    return;
+                };
+                let below = std::mem::replace(self.target, blank);
+                self.stack.push(Saved::Layer {
+                    below,
+                    opacity: opacity.clamp(0.0, 1.0),
+                });
+            }
+            DrawCommand::Pop => self.pop(),
+        }
+    }
+
+    fn pop(&mut self) {
+        match self.stack.pop() {
+            Some(Saved::Clip(outer)) => self.mask = outer,
+            Some(Saved::Layer { below, opacity }) => {
+                let layer = std::mem::replace(self.target, below);
+                let paint = PixmapPaint {
+                    opacity,
+                    ..Default::default()
+                };
+                self.target
+                    .draw_pixmap(0, 0, layer.as_ref(), &paint, Transform::identity(), None);
+            }
+            None => {}
+        }
+    }
+
+    fn fill(&mut self, rect: Rect, radii: CornerRadii, paint: &tiny_skia::Paint) {
+        if let Some(path) = rounded_rect_path(rect, radii) {
+            self.target.fill_path(
+                &path,
+                paint,
+                FillRule::Winding,
+                Transform::identity(),
+                self.mask.as_ref(),
+            );
+        }
+    }
+
+    fn text(&mut self, run: &TextRun) {
+        if run.font_size <= 0.0 {
+            return;
+        }
+        let line = line(run);
+        let left = line_left(run, &line);
+        let baseline = run.rect.y + (run.rect.height - line.height) / 2.0 + line.ascent;
+
+        // The plain glyphs and the highlighted ones, each as one path
+        for (highlighted, color) in [(false, run.color), (true, run.highlight_color)] {
+            let mut path = PathBuilder::new();
+            for glyph in &line.glyphs {
+                if is_highlighted(run, glyph.index) == highlighted {
+                    push_glyph(&mut path, glyph, left + glyph.x, baseline);
+                }
+            }
+            if let Some(path) = path.finish() {
+                self.target.fill_path(
+                    &path,
+                    &solid(color),
+                    FillRule::Winding,
+                    Transform::identity(),
+                    self.mask.as_ref(),
+                );
+            }
+        }
+    }
+
+    fn image(&mut self, image: &Pixmap, rect: Rect, fit: Fit, blur_radius: f32) {
+        let (scale_x, scale_y) = (
+            rect.width / image.width() as f32,
+            rect.height / image.height() as f32,
+        );
+        let (scale_x, scale_y) = match fit {
+            Fit::Cover => (scale_x.max(scale_y), scale_x.max(scale_y)),
+            Fit::Stretch => (scale_x, scale_y),
+        };
+        // Center the crop
+        let crop_x = (image.width() as f32 - rect.width / scale_x) / 2.0;
+        let crop_y = (image.height() as f32 - rect.height / scale_y) / 2.0;
+
+        // Resample the image at the rect's size before blurring it
+        let (x0, y0) = (rect.x.round(), rect.y.round());
+        let Some(mut scaled) =
+            tiny_skia::Pixmap::new(rect.width.round() as u32, rect.height.round() as u32)
+        else {
+            return;
+        };
+        let shader = Pattern::new(
+            image.0.as_ref(),
+            SpreadMode::Pad,
+            FilterQuality::Bilinear,
+            1.0,
+            Transform::from_row(
+                scale_x,
+                0.0,
+                0.0,
+                scale_y,
+                -crop_x * scale_x,
+                -crop_y * scale_y,
+            ),
+        );
+        let paint = tiny_skia::Paint {
+            shader,
+            ..Default::default()
+        };
+        if let Some(bounds) =
+            tiny_skia::Rect::from_xywh(0.0, 0.0, scaled.width() as f32, scaled.height() as f32)
+        {
+            scaled.fill_rect(bounds, &paint, Transform::identity(), None);
+        }
+        blur(&mut scaled, blur_radius);
+
+        self.target.draw_pixmap(
+            x0 as i32,
+            y0 as i32,
+            scaled.as_ref(),
+            &PixmapPaint::default(),
+            Transform::identity(),
+            self.mask.as_ref(),
+        );
+    }
+}
+
+/// Whether character `index` of a run is in a highlighted range
+fn is_highlighted(run: &TextRun, index: usize) -> bool {
+    run.highlights
+        .iter()
+        .any(|&(start, end)| (start..end).contains(&index))
+}
+
+/// A run laid out with the bundled fonts; highlights are bold
+fn line(run: &TextRun) -> Line {
+    font::layout(&run.text, &run.font_family, run.font_size, |index| {
+        run.bold || is_highlighted(run, index)
+    })
+}
+
+/// X coordinate of the start of a run's line
+fn line_left(run: &TextRun, line: &Line) -> f32 {
+    match run.align {
+        TextAlign::Leading => run.rect.x,
+        TextAlign::Center => run.rect.x + (run.rect.width - line.width()) / 2.0,
+        TextAlign::Trailing => run.rect.x + run.rect.width - line.width(),
+    }
+}
+
+/// X coordinate of the caret before character `index` of a run
+fn caret_x(run: &TextRun, index: usize) -> f32 {
+    let line = line(run);
+    let caret = line.carets[index.min(line.carets.len() - 1)];
+    line_left(run, &line) + caret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::types::{Gradient, Shadow};
+
+    fn render(list: &DisplayList, width: u32, height: u32) -> Pixmap {
+        let mut pixmap = Pixmap::new(width, height).unwrap();
+        SoftwareRenderer::new().draw_list(&mut pixmap, list);
+        pixmap
+    }
+
+    fn close(a: Color, b: Color) -> bool {
+        (a.r - b.r).abs() < 0.02
+            && (a.g - b.g).abs() < 0.02
+            && (a.b - b.b).abs() < 0.02
+            && (a.a - b.a).abs() < 0.02
+    }
+
+    /// Columns with any ink at all
+    fn ink_columns(pixmap: &Pixmap) -> Vec<u32> {
+        (0..pixmap.width())
+            .filter(|&x| (0..pixmap.height()).any(|y| pixmap.pixel(x, y).a > 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_fill_and_rounded_corners() {
+        let mut list = DisplayList::new();
+        list.fill_rounded_rect(Rect::new(0.0, 0.0, 20.0, 20.0), 8.0, Color::RED);
+        let pixmap = render(&list, 20, 20);
+
+        assert_eq!(pixmap.pixel(10, 10), Color::RED);
+        assert_eq!(pixmap.pixel(0, 10), Color::RED);
+        // The corner is cut off
+        assert_eq!(pixmap.pixel(0, 0), Color::TRANSPARENT);
+        // Anti-aliased edge
+        let edge = pixmap.pixel(1, 3);
+        assert!(edge.a > 0.0 && edge.a < 1.0, "{:?}", edge);
+    }
+
+    #[test]
+    fn test_blending_and_stroke() {
+        let mut list = DisplayList::new();
+        let rect = Rect::new(0.0, 0.0, 10.0, 10.0);
+        list.fill_rect(rect, Color::WHITE);
+        list.fill_rect(rect, Color::BLACK.with_alpha(0.5));
+        list.stroke_rect(Rect::new(2.0, 2.0, 6.0, 6.0), Color::BLUE, 2.0);
+        let pixmap = render(&list, 10, 10);
+
+        assert!(close(
+            pixmap.pixel(0, 0),
+            Color::from_f32(0.5, 0.5, 0.5, 1.0)
+        ));
+        // The stroke is centered on the edge
+        assert_eq!(pixmap.pixel(1, 5), Color::BLUE);
+        assert_eq!(pixmap.pixel(2, 5), Color::BLUE);
+        assert!(close(
+            pixmap.pixel(5, 5),
+            Color::from_f32(0.5, 0.5, 0.5, 1.0)
+        ));
+    }
+
+    #[test]
+    fn test_gradient() {
+        let gradient = Gradient::new(
+            GradientKind::Linear { angle: 90.0 },
+            &[(Color::BLACK, None), (Color::WHITE, None)],
+        );
+        let mut list = DisplayList::new();
+        list.fill(
+            Rect::new(0.0, 0.0, 100.0, 1.0),
+            CornerRadii::zero(),
+            &Paint::Gradient(gradient),
+        );
+        let pixmap = render(&list, 100, 1);
+
+        assert!(pixmap.pixel(0, 0).r < 0.02);
+        assert!((pixmap.pixel(50, 0).r - 0.5).abs() < 0.02);
+        assert!(pixmap.pixel(99, 0).r > 0.98);
+    }
+
+    #[test]
+    fn test_clip_and_layer() {
+        let mut list = DisplayList::new();
+        list.push_clip(Rect::new(0.0, 0.0, 5.0, 10.0), CornerRadii::zero());
+        list.push_layer(0.5);
+        list.fill_rect(Rect::new(0.0, 0.0, 10.0, 10.0), Color::RED);
+        list.pop();
+        list.pop();
+        list.fill_rect(Rect::new(8.0, 0.0, 2.0, 10.0), Color::GREEN);
+        let pixmap = render(&list, 10, 10);
+
+        assert!(close(pixmap.pixel(2, 2), Color::RED.with_alpha(0.5)));
+        assert_eq!(pixmap.pixel(6, 2), Color::TRANSPARENT);
+        // Drawing after the pops isn't clipped
+        assert_eq!(pixmap.pixel(9, 2), Color::GREEN);
+    }
+
+    #[test]
+    fn test_shadow() {
+        let mut list = DisplayList::new();
+        let shadow = Shadow {
+            offset_x: 0.0,
+            offset_y: 4.0,
+            blur: 4.0,
+            spread: 0.0,
+            color: Color::BLACK.with_alpha(0.5),
+        };
+        list.shadow(
+            Rect::new(10.0, 10.0, 10.0, 10.0),
+            CornerRadii::zero(),
+            &shadow,
+        );
+        let pixmap = render(&list, 30, 30);
+
+        assert!(close(pixmap.pixel(15, 18), Color::BLACK.with_alpha(0.5)));
+        // Fades out below the box
+        let fading = pixmap.pixel(15, 24);
+        assert!(fading.a > 0.0 && fading.a < 0.5, "{:?}", fading);
+        assert_eq!(pixmap.pixel(15, 28), Color::TRANSPARENT);
+        assert_eq!(pixmap.pixel(15, 11), Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn test_text_alignment_and_caret() {
+        let rect = Rect::new(0.0, 0.0, 200.0, 40.0);
+        let run = TextRun::new("Wolfy", rect, "Segoe UI", 20.0, Color::WHITE);
+        let width = font::text_width("Wolfy", "Segoe UI", 20.0, false);
+        let ink = |run: TextRun| {
+            let mut list = DisplayList::new();
+            list.text(run);
+            ink_columns(&render(&list, 200, 40))
+        };
+
+        // Glyphs sit within their advances, give or take a pixel of bearing
+        let leading = ink(run.clone());
+        assert!(leading[0] <= 2, "{:?}", leading);
+        assert!(*leading.last().unwrap() as f32 <= width + 1.0);
+        let trailing = ink(run.clone().align(TextAlign::Trailing));
+        assert!(trailing[0] as f32 >= 199.0 - width, "{:?}", trailing);
+        assert!(*trailing.last().unwrap() >= 197);
+        let center = ink(run.clone().align(TextAlign::Center));
+        let middle = (center[0] + center.last().unwrap()) as f32 / 2.0;
+        assert!((middle - 100.0).abs() < 3.0, "{:?}", center);
+
+        // The text is vertically centered
+        let mut list = DisplayList::new();
+        list.text(run.clone());
+        let pixmap = render(&list, 200, 40);
+        let rows: Vec<u32> = (0..40)
+            .filter(|&y| (0..200).any(|x| pixmap.pixel(x, y).a > 0.0))
+            .collect();
+        assert!(rows[0] >= 8 && *rows.last().unwrap() <= 32, "{:?}", rows);
+
+        // The caret sits between the characters, after kerning
+        let carets = font::layout("Wolfy", "Segoe UI", 20.0, |_| false).carets;
+        let mut list = DisplayList::new();
+        list.caret(run.clone(), 1, Color::WHITE, 1.0);
+        let caret = ink_columns(&render(&list, 200, 40));
+        let middle = caret.iter().sum::<u32>() as f32 / caret.len() as f32 + 0.5;
+        assert!(
+            (middle - carets[1]).abs() <= 0.5,
+            "{:?} {}",
+            caret,
+            carets[1]
+        );
+
+        let mut list = DisplayList::new();
+        list.text_selection(run, 1, 5, Color::BLUE);
+        let selection = ink_columns(&render(&list, 200, 40));
+        assert_eq!(selection.first(), Some(&(carets[1].floor() as u32)));
+        assert_eq!(selection.last(), Some(&(width.ceil() as u32 - 1)));
+    }
+
+    #[test]
+    fn test_highlights_are_bold_and_colored() {
+        let rect = Rect::new(0.0, 0.0, 40.0, 20.0);
+        let run = TextRun::new("II", rect, "Segoe UI", 16.0, Color::WHITE)
+            .highlight(vec![(1, 2)], Color::RED);
+        let mut list = DisplayList::new();
+        list.text(run);
+        let pixmap = render(&list, 40, 20);
+
+        let opaque = |color: Color| {
+            (0..40)
+                .filter(|&x| pixmap.pixel(x, 10) == color)
+                .collect::<Vec<_>>()
+        };
+        let (plain, bold) = (opaque(Color::WHITE), opaque(Color::RED));
+        assert!(!plain.is_empty() && !bold.is_empty());
+        assert!(plain.last() < bold.first());
+        // The bold stem is wider
+        assert!(bold.len() > plain.len(), "{:?} {:?}", plain, bold);
+    }
+
+    #[test]
+    fn test_text_beyond_ascii() {
+        let rect = Rect::new(0.0, 0.0, 100.0, 20.0);
+        let ink = |text: &str| {
+            let mut list = DisplayList::new();
+            list.text(TextRun::new(text, rect, "Segoe UI", 16.0, Color::WHITE));
+            ink_columns(&render(&list, 100, 20)).len()
+        };
+        assert!(ink("é") > 0);
+        assert!(ink("Привет") > 0);
+        // Glyphs neither bundled font has are left out
+        assert_eq!(ink("\u{f0ed}"), 0);
+    }
+
+    #[test]
+    fn test_images() {
+        // 2x1 image: red and blue halves
+        let image = Pixmap::from_rgba8(2, 1, &[255, 0, 0, 255, 0, 0, 255, 255]).unwrap();
+        let mut renderer = SoftwareRenderer::new();
+        renderer.add_image("wall.png", image);
+
+        let mut list = DisplayList::new();
+        // Covering a square crops the sides
+        list.image(
+            "wall.png",
+            Rect::new(0.0, 0.0, 20.0, 20.0),
+            Default::default(),
+            0.0,
+            None,
+        );
+        list.image(
+            "missing.png",
+            Rect::new(20.0, 0.0, 10.0, 10.0),
+            Default::default(),
+            0.0,
+            Some(Color::GREEN),
+        );
+        list.icon("wall.png", Rect::new(0.0, 20.0, 30.0, 10.0));
+        let mut pixmap = Pixmap::new(30, 30).unwrap();
+        renderer.draw_list(&mut pixmap, &list);
+
+        let (left, right) = (pixmap.pixel(0, 10), pixmap.pixel(19, 10));
+        assert!(left.r > 0.9 && left.b < 0.1, "{:?}", left);
+        assert!(right.b > 0.9 && right.r < 0.1, "{:?}", right);
+        assert_eq!(pixmap.pixel(25, 5), Color::GREEN);
+        assert!(close(pixmap.pixel(1, 25), Color::RED));
+        assert!(close(pixmap.pixel(28, 25), Color::BLUE));
+    }
+
+    #[test]
+    fn test_png_round_trip() {
+        let mut pixmap = Pixmap::new(3, 2).unwrap();
+        pixmap.fill(Color::RED.with_alpha(0.5));
+        let decoded = Pixmap::decode_png(&pixmap.encode_png().unwrap()).unwrap();
+        assert_eq!(decoded, pixmap);
+        assert!(Pixmap::decode_png(b"not a png").is_err());
+    }
+}
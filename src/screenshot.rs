@@ -0,0 +1,716 @@
+//! Headless rendering of a mode to an image (`wolfy render`)
+//!
+//! Lays out a frame the way `App::paint` does, with sample content instead
+//! of installed apps and wallpapers, and draws it with the software renderer.
+//! Theme authors can see a theme without a Windows box, and CI can build a
+//! gallery of the shipped themes and compare them against golden images: the
+//! output only depends on the theme and the fixtures.
+//!
+//! Text uses the bundled DejaVu fonts in place of the theme's, so glyphs
+//! neither has (like the task panel's Nerd Font icons) are left out. The
+//! wallpaper panel's diagonal edge and its fade aren't drawn, the panel is
+//! clipped to its rounded rectangle instead.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+
+use crate::mode::Mode;
+use crate::render::font;
+use crate::render::software::{Pixmap, SoftwareRenderer};
+use crate::render::{DisplayList, TextAlign, TextRun};
+use crate::tasks::TasksConfig;
+use crate::theme::tree::ThemeTree;
+use crate::theme::types::{Color, ImageScale, LayoutContext, Rect};
+use crate::widget::{
+    self, base_font, launcher_widget_path, ChildLayout, ClockConfig, ElementData, ElementStyle,
+    GridItem, GridView, GridViewStyle, ListView, ListViewStyle, TaskPanelState, TaskPanelStyle,
+    ThemeLayout, Widget, WidgetState,
+};
+
+/// Width of the picker windows, which the launcher sizes to the monitor
+/// (here a 1920px wide one)
+pub const PICKER_WIDTH: u32 = 1860;
+
+/// Image path of the generated wallpaper
+const SAMPLE_WALLPAPER: &str = "sample:wallpaper";
+
+/// Sample content to render a mode with
+#[derive(Clone, Debug)]
+pub struct Fixtures {
+    /// Launcher list items
+    pub items: Vec<ElementData>,
+    /// Theme picker items
+    pub themes: Vec<GridItem>,
+    /// Wallpaper picker items
+    pub wallpapers: Vec<GridItem>,
+    /// Image path of the wallpaper panel
+    pub wallpaper: String,
+    /// Images by the paths the items and wallpaper use
+    pub images: Vec<(String, Pixmap)>,
+    /// Task panel groups, shown in the launcher if any
+    pub tasks: Option<TasksConfig>,
+    /// Time shown by the clock
+    pub time: DateTime<FixedOffset>,
+}
+
+impl Fixtures {
+    /// Built-in sample apps and themes, with generated images
+    pub fn sample() -> Self {
+        let apps = [
+            ("Firefox", "Web browser"),
+            ("Visual Studio Code", "Code editor"),
+            ("Windows Terminal", "Terminal emulator"),
+            ("Spotify", "Music player"),
+            ("File Explorer", "Browse files and folders"),
+            ("Obsidian", "Notes"),
+            ("Discord", "Chat"),
+            ("Calculator", "Calculator"),
+            ("Paint", "Image editor"),
+            ("Settings", "System settings"),
+        ];
+        let items = apps
+            .iter()
+            .map(|(name, description)| {
+                ElementData::new(*name, format!("{}.exe", name.to_lowercase()))
+                    .with_subtext(*description)
+            })
+            .collect();
+
+        let names = [
+            "Catppuccin Mocha",
+            "Tokyo Night",
+            "Rose Pine",
+            "Gruvbox Retro",
+            "Nordic Blue",
+            "Synth Wave",
+            "Decay Green",
+            "Edge Runner",
+        ];
+        let mut images = vec![(SAMPLE_WALLPAPER.to_string(), sample_image(640, 400, 250.0))];
+        let mut themes = Vec::new();
+        let mut wallpapers = Vec::new();
+        for (i, name) in names.iter().enumerate() {
+            let hue = i as f32 * 360.0 / names.len() as f32;
+            let path = format!("sample:{}", i);
+            images.push((path.clone(), sample_image(320, 180, hue)));
+
+            let mut theme = GridItem::new(*name, *name).with_image(&path);
+            // The theme in use
+            if i == 0 {
+                theme = theme.with_state(WidgetState::ACTIVE);
+            }
+            themes.push(theme);
+            let file = format!("wallpaper-{:02}.png", i + 1);
+            wallpapers.push(GridItem::new(&file, &file).with_image(&path));
+        }
+
+        let time = NaiveDate::from_ymd_opt(2025, 1, 15)
+            .and_then(|date| date.and_hms_opt(9, 41, 0))
+            .and_then(|time| {
+                FixedOffset::east_opt(0)?
+                    .from_local_datetime(&time)
+                    .single()
+            })
+            .unwrap_or_default();
+
+        Self {
+            items,
+            themes,
+            wallpapers,
+            wallpaper: SAMPLE_WALLPAPER.to_string(),
+            images,
+            tasks: None,
+            time,
+        }
+    }
+
+    /// Use the PNG files in a directory, by name, as the picker items; the
+    /// first one is also the wallpaper
+    pub fn load_images(&mut self, dir: &Path) -> io::Result<()> {
+        let mut paths: Vec<_> = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        paths.retain(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+        });
+        paths.sort();
+        if paths.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no PNG files in {}", dir.display()),
+            ));
+        }
+
+        let mut images = Vec::new();
+        for path in &paths {
+            let image = Pixmap::decode_png(&fs::read(path)?).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", path.display(), e),
+                )
+            })?;
+            images.push((path.to_string_lossy().into_owned(), image));
+        }
+
+        self.themes.clear();
+        self.wallpapers.clear();
+        for (i, path) in paths.iter().enumerate() {
+            let image = path.to_string_lossy();
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let file = path.file_name().unwrap_or_default().to_string_lossy();
+            let mut theme = GridItem::new(stem.as_ref(), stem.as_ref()).with_image(image.as_ref());
+            if i == 0 {
+                theme = theme.with_state(WidgetState::ACTIVE);
+            }
+            self.themes.push(theme);
+            self.wallpapers
+                .push(GridItem::new(file.as_ref(), image.as_ref()).with_image(image.as_ref()));
+        }
+        self.wallpaper = images[0].0.clone();
+        self.images = images;
+        Ok(())
+    }
+}
+
+/// A diagonal two-tone gradient around a hue, standing in for a photo
+fn sample_image(width: u32, height: u32, hue: f32) -> Pixmap {
+    let from = Color::from_hsla(hue, 0.65, 0.6, 1.0);
+    let to = Color::from_hsla(hue + 50.0, 0.7, 0.25, 1.0);
+    let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height {
+        for x in 0..width {
+            let t = (x as f32 / width as f32 + y as f32 / height as f32) / 2.0;
+            let color = from.mix(&to, 1.0 - t);
+            data.extend([color.r, color.g, color.b].map(|v| (v * 255.0).round() as u8));
+            data.push(255);
+        }
+    }
+    Pixmap::from_rgba8(width, height, &data).expect("sample image has a size")
+}
+
+/// Window size of a mode in logical pixels, as the launcher sizes it
+pub fn window_size(theme: &ThemeTree, mode: Mode) -> (u32, u32) {
+    match mode {
+        Mode::Launcher | Mode::TailView => {
+            let (width, height) =
+                widget::window_size(theme, &monitor_context(theme), (928.0, 480.0));
+            (width.round() as u32, height.round() as u32)
+        }
+        Mode::ThemePicker | Mode::WallpaperPicker => (PICKER_WIDTH, 520),
+    }
+}
+
+/// Layout context of the monitor the launcher would be on (the default
+/// 1920x1080 viewport) in logical pixels, with `em` and `ch` units from the
+/// theme's font and text measured with the bundled fonts it's drawn in
+fn monitor_context(theme: &ThemeTree) -> LayoutContext {
+    let (font_family, font_size) = base_font(theme);
+    let default = LayoutContext::default();
+    LayoutContext {
+        base_font_size: font_size,
+        char_width: font::text_width("0", &font_family, font_size, false),
+        text_measure: Some(Rc::new(font::BundledFonts)),
+        parent_size: default.viewport_width,
+        ..default
+    }
+}
+
+/// Render a frame of a mode at a logical size and scale factor; None if the
+/// image would be empty or too large
+pub fn render(
+    theme: &ThemeTree,
+    mode: Mode,
+    fixtures: &Fixtures,
+    (width, height): (u32, u32),
+    scale: f32,
+) -> Option<Pixmap> {
+    let size = (
+        (width as f32 * scale).round() as u32,
+        (height as f32 * scale).round() as u32,
+    );
+    let list = frame(theme, mode, fixtures, size, scale);
+
+    let mut renderer = SoftwareRenderer::new();
+    for (path, image) in &fixtures.images {
+        renderer.add_image(path.clone(), image.clone());
+    }
+    let mut pixmap = Pixmap::new(size.0, size.1)?;
+    renderer.draw_list(&mut pixmap, &list);
+    Some(pixmap)
+}
+
+/// The display list of a frame in physical pixels, following `App::paint`
+pub fn frame(
+    theme: &ThemeTree,
+    mode: Mode,
+    fixtures: &Fixtures,
+    (width, height): (u32, u32),
+    scale: f32,
+) -> DisplayList {
+    let layout = ThemeLayout::from_theme(theme);
+    let monitor = monitor_context(theme);
+    let ctx = LayoutContext {
+        dpi: 96.0 * scale,
+        scale_factor: scale,
+        parent_size: width as f32,
+        viewport_width: monitor.viewport_width * scale,
+        viewport_height: monitor.viewport_height * scale,
+        ..monitor
+    };
+    let (width, height) = (width as f32, height as f32);
+    let mut list = DisplayList::new();
+
+    // Window background tint
+    let mainbox_padding = layout.mainbox_padding * scale;
+    let corner_radius = layout.window_border_radius * scale;
+    let window_bounds = Rect::new(
+        mainbox_padding / 2.0,
+        mainbox_padding / 2.0,
+        width - mainbox_padding,
+        height - mainbox_padding,
+    );
+    list.fill_rounded_rect(window_bounds, corner_radius, layout.window_background_color);
+
+    let content = Rect::new(
+        mainbox_padding,
+        mainbox_padding,
+        width - mainbox_padding * 2.0,
+        height - mainbox_padding * 2.0,
+    );
+    let children = if mode.has_wallpaper_panel() {
+        layout.calculate_mainbox_children_bounds(
+            content.x,
+            content.y,
+            content.width,
+            content.height,
+            &ctx,
+        )
+    } else {
+        vec![ChildLayout {
+            name: "listbox".to_string(),
+            bounds: content,
+            expand: true,
+            fixed_width: None,
+        }]
+    };
+
+    // Expanding children first, so the fixed ones draw over them
+    let diagonal = if mode.has_wallpaper_panel() {
+        layout.wallpaper_panel_diagonal * scale
+    } else {
+        0.0
+    };
+    let mut listbox = None;
+    for child in children.iter().filter(|child| child.expand) {
+        if child.name == "listbox" {
+            // Extended left underneath the wallpaper panel's diagonal edge
+            let bounds = child.bounds;
+            let rect = Rect::new(
+                bounds.x - diagonal,
+                bounds.y,
+                bounds.width + diagonal,
+                bounds.height,
+            );
+            list.fill(rect, layout.listbox_radii.scaled(scale), &layout.listbox_bg);
+            listbox = Some(bounds);
+        }
+    }
+    for child in children.iter().filter(|child| !child.expand) {
+        if child.name == "wallpaper-panel" {
+            wallpaper_panel(
+                &mut list,
+                theme,
+                &layout,
+                mode,
+                fixtures,
+                child.bounds,
+                scale,
+            );
+        }
+    }
+
+    if let Some(listbox) = listbox {
+        let rect = Rect::new(
+            listbox.x + layout.listview_padding_left * scale,
+            listbox.y + layout.listview_padding_top * scale,
+            listbox.width - (layout.listview_padding_left + layout.listview_padding_right) * scale,
+            listbox.height - (layout.listview_padding_top + layout.listview_padding_bottom) * scale,
+        );
+        if rect.height > 0.0 {
+            if mode.uses_grid_view() {
+                let mut gridview =
+                    GridView::new().with_style(GridViewStyle::from_theme(theme, None));
+                gridview.set_items(if mode == Mode::ThemePicker {
+                    fixtures.themes.clone()
+                } else {
+                    fixtures.wallpapers.clone()
+                });
+                gridview.arrange(rect, &ctx);
+                gridview.render(&mut list, rect, &ctx);
+            } else {
+                let path = launcher_widget_path(theme, "listview");
+                let mut listview = ListView::new()
+                    .with_style(ListViewStyle::from_theme_at(theme, &path, None))
+                    .with_element_style(ElementStyle::from_theme_at(
+                        theme,
+                        &format!("{} element", path),
+                        None,
+                    ));
+                listview.set_items(fixtures.items.clone());
+                listview.arrange(rect, &ctx);
+                listview.render(&mut list, rect, &ctx);
+            }
+        }
+    }
+
+    list.stroke_rounded_rect(
+        window_bounds,
+        corner_radius,
+        layout.window_border_color,
+        2.0 * scale,
+    );
+    list
+}
+
+/// The wallpaper with the clock and the task panel over it
+fn wallpaper_panel(
+    list: &mut DisplayList,
+    theme: &ThemeTree,
+    layout: &ThemeLayout,
+    mode: Mode,
+    fixtures: &Fixtures,
+    bounds: Rect,
+    scale: f32,
+) {
+    // Overlaps the listbox to hide the seam between them
+    let overlap = 8.0;
+    let rect = Rect::new(bounds.x, bounds.y, bounds.width + overlap, bounds.height);
+    list.push_clip(rect, layout.wallpaper_panel_radii.scaled(scale));
+    list.fill_rect(rect, Color::from_f32(0.1, 0.1, 0.1, 1.0));
+    list.image(
+        fixtures.wallpaper.clone(),
+        rect,
+        ImageScale::Both,
+        layout.wallpaper_panel_backdrop_blur * scale,
+        None,
+    );
+    list.pop();
+
+    if layout.clock_config.enabled {
+        clock(list, &layout.clock_config, rect, scale, &fixtures.time);
+    }
+
+    let style = TaskPanelStyle::from_theme(theme);
+    if style.enabled && mode.has_task_panel() {
+        if let Some(config) = fixtures.tasks.as_ref() {
+            let mut task_panel = TaskPanelState::new(config.clone());
+            if task_panel.has_tasks() {
+                task_panel.render_compact(list, rect, &style, scale);
+            }
+        }
+    }
+}
+
+/// The clock over the wallpaper panel, following `App::draw_clock` with the
+/// bundled fonts' metrics
+fn clock(
+    list: &mut DisplayList,
+    config: &ClockConfig,
+    panel: Rect,
+    scale: f32,
+    time: &DateTime<FixedOffset>,
+) {
+    // Written out so a bad format string doesn't panic
+    let format = |format: &str| {
+        let mut text = String::new();
+        let _ = write!(text, "{}", time.format(format));
+        text
+    };
+    let time_text = format(&config.time_format);
+    let date_text = format(&config.date_format);
+
+    let time_size = config.font_size * scale;
+    let date_size = config.date_font_size * scale;
+    let (time_width, date_width) = (
+        font::text_width(&time_text, &config.font_family, time_size, true),
+        font::text_width(&date_text, &config.font_family, date_size, false),
+    );
+    let (spacing, date_height) = if date_text.is_empty() {
+        (0.0, 0.0)
+    } else {
+        (4.0 * scale, date_size)
+    };
+
+    let padding = config.padding * scale;
+    let total_width = time_width.max(date_width);
+    let total_height = time_size + spacing + date_height;
+    let x = panel.x
+        + padding
+        + (panel.width - 2.0 * padding - total_width) * config.position.horizontal_align();
+    let y = panel.y
+        + padding
+        + (panel.height - 2.0 * padding - total_height) * config.position.vertical_align();
+
+    let shadow = (
+        config.shadow_offset.0 * scale,
+        config.shadow_offset.1 * scale,
+    );
+    let lines = [
+        (
+            time_text,
+            Rect::new(x, y, total_width, time_size),
+            time_size,
+            true,
+        ),
+        (
+            date_text,
+            Rect::new(x, y + time_size + spacing, total_width, date_height),
+            date_size,
+            false,
+        ),
+    ];
+    for (text, rect, size, bold) in lines {
+        let run = |rect: Rect, color: Color| {
+            TextRun::new(text.clone(), rect, &config.font_family, size, color)
+                .bold(bold)
+                .align(TextAlign::Center)
+        };
+        let shadow_rect = Rect::new(
+            rect.x + shadow.0,
+            rect.y + shadow.1,
+            rect.width,
+            rect.height,
+        );
+        list.text(run(shadow_rect, config.shadow_color));
+        list.text(run(rect, config.text_color));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::DrawCommand;
+    use crate::tasks::{TaskGroup, TaskPanelSettings};
+
+    fn root() -> &'static Path {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn shipped_theme() -> ThemeTree {
+        ThemeTree::load_layered(&[
+            &root().join("core.rasi"),
+            &root().join("themes/catppuccin_mocha.rasi"),
+        ])
+        .unwrap()
+    }
+
+    /// The sample fixtures with the shipped task panel
+    fn fixtures_with_tasks() -> Fixtures {
+        let source = fs::read_to_string(root().join("tasks.toml")).unwrap();
+        let mut fixtures = Fixtures::sample();
+        fixtures.tasks = Some(toml::from_str(&source).unwrap());
+        fixtures
+    }
+
+    /// Compare a rendering with its golden image in `tests/golden`, or write
+    /// the golden image when `WOLFY_UPDATE_GOLDEN` is set
+    ///
+    /// Pixels may differ slightly, since floating point rounding varies
+    /// between platforms.
+    fn assert_golden(name: &str, pixmap: &Pixmap) {
+        let path = root().join("tests/golden").join(name);
+        if std::env::var_os("WOLFY_UPDATE_GOLDEN").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, pixmap.encode_png().unwrap()).unwrap();
+            return;
+        }
+        let data = fs::read(&path).unwrap_or_else(|e| {
+            panic!(
+                "{}: {} (run with WOLFY_UPDATE_GOLDEN=1 to write it)",
+                path.display(),
+                e
+            )
+        });
+        let golden = Pixmap::decode_png(&data).unwrap();
+        assert_eq!(
+            (pixmap.width(), pixmap.height()),
+            (golden.width(), golden.height()),
+            "{}",
+            name
+        );
+
+        let mut differing = 0;
+        for y in 0..golden.height() {
+            for x in 0..golden.width() {
+                let (a, b) = (pixmap.pixel(x, y), golden.pixel(x, y));
+                let diff = [a.r - b.r, a.g - b.g, a.b - b.b, a.a - b.a]
+                    .iter()
+                    .fold(0.0f32, |max, d| max.max(d.abs()));
+                if diff > 3.0 / 255.0 {
+                    differing += 1;
+                }
+            }
+        }
+        let total = golden.width() * golden.height();
+        assert!(
+            differing * 200 < total,
+            "{}: {} of {} pixels differ from the golden image (run with \
+             WOLFY_UPDATE_GOLDEN=1 to update it)",
+            name,
+            differing,
+            total
+        );
+    }
+
+    #[test]
+    fn test_launcher_frame() {
+        let theme = shipped_theme();
+        let mut fixtures = Fixtures::sample();
+        fixtures.tasks = Some(TasksConfig {
+            settings: TaskPanelSettings::default(),
+            groups: vec![TaskGroup {
+                name: "Dev".to_string(),
+                icon: "D".to_string(),
+                expanded: false,
+                tasks: vec![],
+            }],
+        });
+        let size = window_size(&theme, Mode::Launcher);
+        let list = frame(&theme, Mode::Launcher, &fixtures, size, 1.0);
+
+        let texts: Vec<_> = list.texts().map(|run| run.text.as_str()).collect();
+        assert!(texts.contains(&"Firefox"), "{:?}", texts);
+        // The task panel's group icon
+        assert!(texts.contains(&"D"), "{:?}", texts);
+        assert!(list.commands().iter().any(|command| matches!(
+            command,
+            DrawCommand::Image { path, .. } if path == SAMPLE_WALLPAPER
+        )));
+
+        let pixmap = render(&theme, Mode::Launcher, &fixtures, size, 1.0).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), size);
+        // Transparent outside the rounded window
+        assert_eq!(pixmap.pixel(0, 0), Color::TRANSPARENT);
+        assert!(pixmap.pixel(size.0 - 30, size.1 - 30).a > 0.5);
+    }
+
+    #[test]
+    fn test_picker_frames() {
+        let theme = shipped_theme();
+        let fixtures = Fixtures::sample();
+        for (mode, title) in [
+            (Mode::ThemePicker, "Tokyo Night"),
+            (Mode::WallpaperPicker, "wallpaper-02.png"),
+        ] {
+            let size = window_size(&theme, mode);
+            assert_eq!(size.0, PICKER_WIDTH);
+            let list = frame(&theme, mode, &fixtures, size, 1.5);
+            assert!(list.texts().any(|run| run.text == title), "{:?}", mode);
+            // No wallpaper panel or task panel in the pickers
+            assert!(!list.commands().iter().any(|command| matches!(
+                command,
+                DrawCommand::Image { path, .. } if path == SAMPLE_WALLPAPER
+            )));
+        }
+    }
+
+    #[test]
+    fn test_load_images() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut image = Pixmap::new(4, 3).unwrap();
+        image.fill(Color::RED);
+        let data = image.encode_png().unwrap();
+        fs::write(dir.path().join("b.png"), &data).unwrap();
+        fs::write(dir.path().join("a.PNG"), &data).unwrap();
+        fs::write(dir.path().join("notes.txt"), "not an image").unwrap();
+
+        let mut fixtures = Fixtures::sample();
+        fixtures.load_images(dir.path()).unwrap();
+        let titles: Vec<_> = fixtures.themes.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["a", "b"]);
+        assert_eq!(fixtures.wallpapers[1].title, "b.png");
+        assert!(fixtures.wallpaper.ends_with("a.PNG"));
+        assert_eq!(fixtures.images.len(), 2);
+
+        fs::write(dir.path().join("c.png"), "not a png").unwrap();
+        assert!(fixtures.load_images(dir.path()).is_err());
+        let empty = tempfile::TempDir::new().unwrap();
+        assert!(fixtures.load_images(empty.path()).is_err());
+    }
+
+    #[test]
+    fn test_golden_images() {
+        let theme = shipped_theme();
+        let fixtures = fixtures_with_tasks();
+
+        let size = window_size(&theme, Mode::Launcher);
+        let launcher = render(&theme, Mode::Launcher, &fixtures, size, 1.0).unwrap();
+        assert_golden("launcher.png", &launcher);
+
+        let size = window_size(&theme, Mode::ThemePicker);
+        let picker = render(&theme, Mode::ThemePicker, &fixtures, size, 0.5).unwrap();
+        assert_golden("theme_picker.png", &picker);
+    }
+
+    #[test]
+    fn test_every_shipped_theme_renders() {
+        let mut themes: Vec<_> = fs::read_dir(root().join("themes"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rasi"))
+            .collect();
+        themes.sort();
+        assert!(themes.len() > 1);
+        let fixtures = fixtures_with_tasks();
+
+        for path in &themes {
+            let theme = ThemeTree::load_layered(&[&root().join("core.rasi"), path]).unwrap();
+            for (mode, title) in [
+                (Mode::Launcher, "Firefox"),
+                (Mode::ThemePicker, "Tokyo Night"),
+                (Mode::WallpaperPicker, "wallpaper-02.png"),
+            ] {
+                let name = format!("{} {:?}", path.display(), mode);
+                let size = window_size(&theme, mode);
+                let scale = 0.5;
+                let pixmap = render(&theme, mode, &fixtures, size, scale).unwrap();
+                let (width, height) = (pixmap.width(), pixmap.height());
+                assert!(pixmap.pixel(width / 2, height / 2).a > 0.5, "{}", name);
+
+                // The item's title draws over whatever is behind it: render
+                // the frame again without it and compare
+                let list = frame(&theme, mode, &fixtures, (width, height), scale);
+                let run = list.texts().find(|run| run.text == title).cloned();
+                let run = run.unwrap_or_else(|| panic!("{}: no {}", name, title));
+                let mut untitled = DisplayList::new();
+                for command in list.commands() {
+                    if !matches!(command, DrawCommand::Text(text) if *text == run) {
+                        untitled.push(command.clone());
+                    }
+                }
+                let mut renderer = SoftwareRenderer::new();
+                for (path, image) in &fixtures.images {
+                    renderer.add_image(path.clone(), image.clone());
+                }
+                let mut without = Pixmap::new(width, height).unwrap();
+                renderer.draw_list(&mut without, &untitled);
+
+                let rect = run.rect;
+                let (right, bottom) = (rect.x + rect.width, rect.y + rect.height);
+                let ink = (rect.y.max(0.0) as u32..(bottom as u32).min(height))
+                    .flat_map(|y| {
+                        (rect.x.max(0.0) as u32..(right as u32).min(width)).map(move |x| (x, y))
+                    })
+                    .filter(|&(x, y)| pixmap.pixel(x, y) != without.pixel(x, y))
+                    .count();
+                assert!(ink > 10, "{}: {} draws {} pixels", name, title, ink);
+            }
+        }
+    }
+}
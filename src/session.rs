@@ -0,0 +1,202 @@
+//! Session state persisted across restarts
+//!
+//! Stored as state.toml in the user config directory:
+//! ```toml
+//! current-theme = "Catppuccin Mocha"
+//! last-mode = "wallpaper-picker"
+//!
+//! [wallpapers]
+//! "Catppuccin Mocha" = "C:\\Users\\me\\.config\\hyde\\themes\\Catppuccin Mocha\\wallpapers\\cat.png"
+//!
+//! [selections]
+//! theme-picker = "C:\\Users\\me\\.config\\hyde\\themes\\Catppuccin Mocha"
+//! ```
+//!
+//! Written through `persist` so a crash never leaves a truncated file.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::log::user_config_dir;
+use crate::persist;
+
+/// File name of the session state in the user config directory
+const STATE_FILENAME: &str = "state.toml";
+
+/// State restored when Wolfy starts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct SessionState {
+    /// Currently selected HyDE theme name
+    pub current_theme: Option<String>,
+    /// Last wallpaper path chosen for each theme (theme name -> path)
+    pub wallpapers: BTreeMap<String, String>,
+    /// Last shown mode (see `Mode::state_key`)
+    pub last_mode: Option<String>,
+    /// Last selected item per mode (mode key -> item user data)
+    pub selections: BTreeMap<String, String>,
+}
+
+impl SessionState {
+    /// Default location of state.toml (None if there is no config directory)
+    pub fn default_path() -> Option<PathBuf> {
+        user_config_dir().map(|dir| dir.join(STATE_FILENAME))
+    }
+
+    /// Load session state, falling back to defaults if missing or invalid
+    pub fn load(path: &Path) -> Self {
+        match persist::read(path) {
+            Ok(content) => match toml::from_str(&content) {
+                Ok(state) => {
+                    log!("Loaded session state from {:?}", path);
+                    state
+                }
+                Err(e) => {
+                    log!("Failed to parse {}: {}", STATE_FILENAME, e);
+                    Self::default()
+                }
+            },
+            Err(e) => {
+                log!("No session state loaded: {} (using defaults)", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Save session state atomically
+    pub fn save(&self, path: &Path) {
+        let content = match toml::to_string(self) {
+            Ok(content) => content,
+            Err(e) => {
+                log!("Failed to serialize session state: {}", e);
+                return;
+            }
+        };
+
+        let _lock = persist::lock(path)
+            .map_err(|e| log!("Failed to lock session state: {:?}", e))
+            .ok();
+        if let Err(e) = persist::write_atomic(path, content.as_bytes()) {
+            log!("Failed to write session state: {:?}", e);
+        }
+    }
+
+    /// Drop references to themes that no longer exist
+    ///
+    /// Returns true if anything was removed.
+    pub fn retain_themes(&mut self, theme_names: &[&str]) -> bool {
+        let mut changed = false;
+
+        if let Some(theme) = &self.current_theme {
+            if !theme_names.contains(&theme.as_str()) {
+                log!("Saved theme '{}' no longer exists, clearing it", theme);
+                self.current_theme = None;
+                changed = true;
+            }
+        }
+
+        let before = self.wallpapers.len();
+        self.wallpapers.retain(|theme, path| {
+            theme_names.contains(&theme.as_str()) && Path::new(path).exists()
+        });
+        changed |= self.wallpapers.len() != before;
+
+        changed
+    }
+
+    /// Last wallpaper chosen for a theme
+    pub fn wallpaper_for(&self, theme: &str) -> Option<&str> {
+        self.wallpapers.get(theme).map(String::as_str)
+    }
+
+    /// Remember the wallpaper chosen for a theme
+    pub fn set_wallpaper(&mut self, theme: &str, path: &str) {
+        self.wallpapers.insert(theme.to_string(), path.to_string());
+    }
+
+    /// Last selected item for a mode
+    pub fn selection(&self, mode_key: &str) -> Option<&str> {
+        self.selections.get(mode_key).map(String::as_str)
+    }
+
+    /// Remember the selected item for a mode
+    pub fn set_selection(&mut self, mode_key: &str, user_data: &str) {
+        self.selections
+            .insert(mode_key.to_string(), user_data.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_session_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.toml");
+
+        // Missing file gives defaults
+        assert_eq!(SessionState::load(&path), SessionState::default());
+
+        let mut state = SessionState {
+            current_theme: Some("Catppuccin Mocha".to_string()),
+            last_mode: Some("theme-picker".to_string()),
+            ..Default::default()
+        };
+        state.set_wallpaper("Catppuccin Mocha", "C:\\walls\\cat.png");
+        state.set_selection("theme-picker", "C:\\themes\\Catppuccin Mocha");
+        state.save(&path);
+
+        let loaded = SessionState::load(&path);
+        assert_eq!(loaded, state);
+        assert_eq!(
+            loaded.wallpaper_for("Catppuccin Mocha"),
+            Some("C:\\walls\\cat.png")
+        );
+        assert_eq!(
+            loaded.selection("theme-picker"),
+            Some("C:\\themes\\Catppuccin Mocha")
+        );
+        assert_eq!(loaded.selection("launcher"), None);
+    }
+
+    #[test]
+    fn test_session_invalid_file_uses_defaults() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.toml");
+        fs::write(&path, "current-theme = [").unwrap();
+        assert_eq!(SessionState::load(&path), SessionState::default());
+
+        // Unknown keys and missing sections are tolerated
+        fs::write(&path, "current-theme = \"Nord\"\nfuture-key = 1\n").unwrap();
+        let loaded = SessionState::load(&path);
+        assert_eq!(loaded.current_theme.as_deref(), Some("Nord"));
+        assert!(loaded.wallpapers.is_empty());
+    }
+
+    #[test]
+    fn test_retain_themes() {
+        let dir = TempDir::new().unwrap();
+        let wallpaper = dir.path().join("nord.png");
+        fs::write(&wallpaper, b"").unwrap();
+
+        let mut state = SessionState {
+            current_theme: Some("Removed".to_string()),
+            ..Default::default()
+        };
+        state.set_wallpaper("Nord", &wallpaper.to_string_lossy());
+        state.set_wallpaper("Removed", &wallpaper.to_string_lossy());
+        state.set_wallpaper("Gruvbox", "C:\\missing\\wall.png");
+
+        assert!(state.retain_themes(&["Nord", "Gruvbox"]));
+        assert_eq!(state.current_theme, None);
+        assert_eq!(state.wallpapers.len(), 1);
+        assert!(state.wallpaper_for("Nord").is_some());
+
+        // Nothing left to remove
+        assert!(!state.retain_themes(&["Nord", "Gruvbox"]));
+    }
+}
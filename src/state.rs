@@ -4,11 +4,16 @@
 //! - Launcher (app search with task panel)
 //! - ThemePicker (grid of HyDE themes)
 //! - WallpaperPicker (grid of wallpapers)
+//!
+//! The selected theme, wallpapers and per-mode selections are persisted to
+//! state.toml (see `session`) so they survive restarts.
 
 use std::path::PathBuf;
 
 use crate::history::History;
 use crate::log::exe_dir;
+use crate::mode::Mode;
+use crate::session::SessionState;
 use crate::widget::ElementData;
 
 /// Get the HyDE themes directory path
@@ -122,7 +127,7 @@ pub fn scan_hyde_themes() -> Vec<HydeTheme> {
     }
 
     // Sort alphabetically
-    themes.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    themes.sort_by_key(|theme| theme.name.to_lowercase());
 
     log!("Found {} HyDE themes", themes.len());
     themes
@@ -189,18 +194,82 @@ pub struct AppState {
 
     /// Flag indicating the main app should reload its theme styling
     pub theme_needs_reload: bool,
+
+    /// Persisted session state (theme, wallpapers, last mode and selections)
+    session: SessionState,
+
+    /// Where the session state is saved (None = don't persist)
+    session_path: Option<PathBuf>,
 }
 
 impl AppState {
-    /// Create new shared state
+    /// Create new shared state, restoring the session from state.toml
     pub fn new() -> Self {
-        Self {
+        Self::with_session_path(SessionState::default_path())
+    }
+
+    /// Create new shared state with the session stored at a specific path
+    pub fn with_session_path(session_path: Option<PathBuf>) -> Self {
+        Self::restored(session_path, None)
+    }
+
+    /// Create new shared state with the session stored at a specific path,
+    /// restoring it against a known list of HyDE themes instead of scanning
+    pub fn with_themes(session_path: Option<PathBuf>, themes: Vec<HydeTheme>) -> Self {
+        Self::restored(session_path, Some(themes))
+    }
+
+    fn restored(session_path: Option<PathBuf>, themes: Option<Vec<HydeTheme>>) -> Self {
+        let session = session_path
+            .as_deref()
+            .map(SessionState::load)
+            .unwrap_or_default();
+
+        let mut state = Self {
             current_theme: None,
             history: History::load_default(),
             all_apps: Vec::new(),
-            hyde_themes: Vec::new(),
-            themes_loaded: false,
+            themes_loaded: themes.is_some(),
+            hyde_themes: themes.unwrap_or_default(),
             theme_needs_reload: false,
+            session,
+            session_path,
+        };
+        state.restore_session();
+        state
+    }
+
+    /// Restore the persisted theme, forgetting themes whose directory is gone
+    ///
+    /// Nothing is forgotten when no themes are found at all, since that is
+    /// more likely a missing or unmounted HyDE directory than every theme
+    /// being deleted. The cleanup isn't saved here either; it reaches
+    /// state.toml with the next change the user makes.
+    fn restore_session(&mut self) {
+        if self.session.current_theme.is_none() && self.session.wallpapers.is_empty() {
+            return;
+        }
+
+        self.ensure_themes_loaded();
+        if self.hyde_themes.is_empty() {
+            log!("AppState: no HyDE themes found, keeping the saved session as it is");
+        } else {
+            let theme_names: Vec<&str> = self.hyde_themes.iter().map(|t| t.name.as_str()).collect();
+            self.session.retain_themes(&theme_names);
+        }
+
+        self.current_theme = self.session.current_theme.clone();
+        if let Some(ref theme) = self.current_theme {
+            log!("AppState: restored theme {:?} from session", theme);
+            // Let the launcher pick up the restored theme on first show
+            self.theme_needs_reload = true;
+        }
+    }
+
+    /// Write the session state to state.toml
+    fn save_session(&self) {
+        if let Some(ref path) = self.session_path {
+            self.session.save(path);
         }
     }
 
@@ -215,8 +284,88 @@ impl AppState {
     /// Set the currently selected theme
     pub fn set_current_theme(&mut self, theme_name: Option<String>) {
         log!("AppState: setting current theme to {:?}", theme_name);
+        self.session.current_theme = theme_name.clone();
         self.current_theme = theme_name;
         self.theme_needs_reload = true;
+        self.save_session();
+    }
+
+    /// Remember the wallpaper chosen for the current theme
+    pub fn remember_wallpaper(&mut self, wallpaper_path: &str) {
+        if let Some(ref theme) = self.current_theme {
+            self.session.set_wallpaper(theme, wallpaper_path);
+            self.save_session();
+        }
+    }
+
+    /// Last wallpaper chosen for a theme
+    pub fn wallpaper_for_theme(&self, theme_name: &str) -> Option<&str> {
+        self.session.wallpaper_for(theme_name)
+    }
+
+    /// Remember the mode that was last shown
+    pub fn remember_mode(&mut self, mode: Mode) {
+        let key = Some(mode.state_key().to_string());
+        if self.session.last_mode != key {
+            self.session.last_mode = key;
+            self.save_session();
+        }
+    }
+
+    /// The mode that was last shown
+    pub fn last_mode(&self) -> Option<Mode> {
+        self.session
+            .last_mode
+            .as_deref()
+            .and_then(Mode::from_state_key)
+    }
+
+    /// Remember the item selected in a mode (by its user data)
+    pub fn remember_selection(&mut self, mode: Mode, user_data: &str) {
+        self.session.set_selection(mode.state_key(), user_data);
+        self.save_session();
+    }
+
+    /// The item last selected in a mode (by its user data)
+    pub fn last_selection(&self, mode: Mode) -> Option<&str> {
+        self.session.selection(mode.state_key())
+    }
+
+    /// The item to select when a mode is shown (by its user data)
+    ///
+    /// The wallpaper picker prefers the wallpaper last chosen for the
+    /// current theme; otherwise it's the mode's last selection.
+    pub fn preferred_selection(&self, mode: Mode) -> Option<&str> {
+        match mode {
+            Mode::WallpaperPicker => self
+                .current_theme
+                .as_deref()
+                .and_then(|theme| self.wallpaper_for_theme(theme))
+                .or_else(|| self.last_selection(mode)),
+            _ => self.last_selection(mode),
+        }
+    }
+
+    /// Choose a theme in a picker, remembering it and its picker item
+    ///
+    /// Returns the wallpaper that goes with it: the one last used with the
+    /// theme, or its first.
+    pub fn choose_theme(&mut self, theme_name: &str, user_data: &str) -> Option<String> {
+        self.set_current_theme(Some(theme_name.to_string()));
+        self.remember_selection(Mode::ThemePicker, user_data);
+        self.wallpaper_for_theme(theme_name)
+            .map(str::to_string)
+            .or_else(|| {
+                scan_theme_wallpapers(theme_name)
+                    .first()
+                    .map(|p| p.to_string_lossy().to_string())
+            })
+    }
+
+    /// Choose a wallpaper in a picker, remembering it for the current theme
+    pub fn choose_wallpaper(&mut self, wallpaper_path: &str) {
+        self.remember_wallpaper(wallpaper_path);
+        self.remember_selection(Mode::WallpaperPicker, wallpaper_path);
     }
 
     /// Get wallpapers for the currently selected theme
@@ -237,18 +386,123 @@ impl Default for AppState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::Path;
 
     #[test]
     fn test_app_state_creation() {
-        let state = AppState::new();
+        let state = AppState::with_session_path(None);
         assert!(state.current_theme.is_none());
         assert!(!state.themes_loaded);
         assert!(state.hyde_themes.is_empty());
     }
 
+    #[test]
+    fn test_app_state_persists_session() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.toml");
+
+        let mut state = AppState::with_session_path(Some(path.clone()));
+        state.remember_mode(Mode::WallpaperPicker);
+        state.remember_selection(Mode::ThemePicker, "C:\\themes\\Nord");
+        drop(state);
+
+        let restored = AppState::with_session_path(Some(path));
+        assert_eq!(restored.last_mode(), Some(Mode::WallpaperPicker));
+        assert_eq!(
+            restored.last_selection(Mode::ThemePicker),
+            Some("C:\\themes\\Nord")
+        );
+    }
+
+    /// HyDE themes with a wallpaper each, both files made in `dir`
+    fn themes_with_wallpapers(dir: &Path, names: &[&str]) -> (Vec<HydeTheme>, Vec<String>) {
+        names
+            .iter()
+            .map(|name| {
+                let wallpaper = dir.join(format!("{}.png", name));
+                std::fs::write(&wallpaper, b"").unwrap();
+                let theme = HydeTheme {
+                    name: name.to_string(),
+                    path: dir.join(name),
+                    thumbnail: None,
+                };
+                (theme, wallpaper.to_string_lossy().to_string())
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn test_app_state_preferred_selection() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.toml");
+        let (themes, wallpapers) = themes_with_wallpapers(dir.path(), &["Nord", "Gruvbox"]);
+
+        let mut state = AppState::with_themes(Some(path.clone()), themes.clone());
+        state.remember_selection(Mode::Launcher, "C:\\apps\\code.lnk");
+        state.current_theme = Some("Nord".to_string());
+        state.choose_wallpaper(&wallpapers[0]);
+        state.set_current_theme(Some("Gruvbox".to_string()));
+        state.choose_wallpaper(&wallpapers[1]);
+
+        // The wallpaper goes with the theme, the last one chosen without
+        state.current_theme = Some("Nord".to_string());
+        assert_eq!(
+            state.preferred_selection(Mode::WallpaperPicker),
+            Some(wallpapers[0].as_str())
+        );
+        state.current_theme = Some("Latte".to_string());
+        assert_eq!(
+            state.preferred_selection(Mode::WallpaperPicker),
+            Some(wallpapers[1].as_str())
+        );
+        drop(state);
+
+        // Both themes still exist, so their wallpapers are restored too
+        let restored = AppState::with_themes(Some(path), themes);
+        assert_eq!(restored.current_theme.as_deref(), Some("Gruvbox"));
+        assert_eq!(
+            restored.preferred_selection(Mode::Launcher),
+            Some("C:\\apps\\code.lnk")
+        );
+        assert_eq!(
+            restored.preferred_selection(Mode::WallpaperPicker),
+            Some(wallpapers[1].as_str())
+        );
+        assert_eq!(
+            restored.wallpaper_for_theme("Nord"),
+            Some(wallpapers[0].as_str())
+        );
+    }
+
+    #[test]
+    fn test_app_state_restore_keeps_session_on_disk() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("state.toml");
+        let (themes, wallpapers) = themes_with_wallpapers(dir.path(), &["Nord", "Gruvbox"]);
+
+        let mut state = AppState::with_themes(Some(path.clone()), themes.clone());
+        state.set_current_theme(Some("Nord".to_string()));
+        state.choose_wallpaper(&wallpapers[0]);
+        drop(state);
+        let saved = std::fs::read_to_string(&path).unwrap();
+
+        // No themes found (HyDE missing or not mounted yet): nothing is forgotten
+        let restored = AppState::with_themes(Some(path.clone()), Vec::new());
+        assert_eq!(restored.current_theme.as_deref(), Some("Nord"));
+        assert_eq!(
+            restored.wallpaper_for_theme("Nord"),
+            Some(wallpapers[0].as_str())
+        );
+
+        // A theme that's gone is forgotten, but only in memory
+        let restored = AppState::with_themes(Some(path.clone()), themes[1..].to_vec());
+        assert_eq!(restored.current_theme, None);
+        assert_eq!(restored.wallpaper_for_theme("Nord"), None);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), saved);
+    }
+
     #[test]
     fn test_is_image_file() {
-        use std::path::Path;
         assert!(is_image_file(Path::new("test.png")));
         assert!(is_image_file(Path::new("test.jpg")));
         assert!(is_image_file(Path::new("test.JPEG")));
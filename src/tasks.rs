@@ -6,7 +6,6 @@ use serde::Deserialize;
 use std::fs;
 use std::path::Path;
 
-use crate::log;
 use crate::theme::types::Rect;
 
 /// Position of the task panel within the wallpaper panel
@@ -19,7 +18,7 @@ pub enum TaskPanelPosition {
 }
 
 /// Root configuration structure
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct TasksConfig {
     #[serde(default)]
     pub settings: TaskPanelSettings,
@@ -27,15 +26,6 @@ pub struct TasksConfig {
     pub groups: Vec<TaskGroup>,
 }
 
-impl Default for TasksConfig {
-    fn default() -> Self {
-        Self {
-            settings: TaskPanelSettings::default(),
-            groups: Vec::new(),
-        }
-    }
-}
-
 impl TasksConfig {
     /// Find a task by group name and task name
     pub fn find_task(&self, group_name: &str, task_name: &str) -> Option<&Task> {
@@ -5,19 +5,18 @@
 
 use std::sync::Arc;
 
-use alacritty_terminal::event::{Event, EventListener, WindowSize};
+use alacritty_terminal::event::{Event, EventListener};
 use alacritty_terminal::grid::Dimensions;
 use alacritty_terminal::term::cell::Cell;
 use alacritty_terminal::term::{Config, Term, TermMode};
 use alacritty_terminal::vte::ansi::{Color as AnsiColor, CursorShape, NamedColor, Rgb};
 use parking_lot::Mutex;
 
+#[cfg(windows)]
 use crate::pty::Pty;
 use crate::theme::tree::ThemeTree;
 use crate::theme::types::Color;
 
-use crate::log;
-
 /// Default scrollback lines
 const DEFAULT_SCROLLBACK: usize = 1000;
 
@@ -204,6 +203,12 @@ impl TerminalEventListener {
     }
 }
 
+impl Default for TerminalEventListener {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EventListener for TerminalEventListener {
     fn send_event(&self, event: Event) {
         match event {
@@ -301,7 +306,8 @@ pub struct Terminal {
     pub colors: TerminalColors,
     /// Configuration
     pub config: TerminalConfig,
-    /// Connected PTY
+    /// Connected PTY (PTYs are only available on Windows)
+    #[cfg(windows)]
     pty: Option<Pty>,
 }
 
@@ -333,6 +339,7 @@ impl Terminal {
             listener,
             colors,
             config,
+            #[cfg(windows)]
             pty: None,
         }
     }
@@ -345,16 +352,19 @@ impl Terminal {
     }
 
     /// Attach a PTY to this terminal
+    #[cfg(windows)]
     pub fn attach_pty(&mut self, pty: Pty) {
         self.pty = Some(pty);
     }
 
     /// Get the attached PTY
+    #[cfg(windows)]
     pub fn pty(&self) -> Option<&Pty> {
         self.pty.as_ref()
     }
 
     /// Get mutable access to the attached PTY
+    #[cfg(windows)]
     pub fn pty_mut(&mut self) -> Option<&mut Pty> {
         self.pty.as_mut()
     }
@@ -369,6 +379,7 @@ impl Terminal {
 
     /// Poll and process any available PTY output
     pub fn poll_pty(&mut self) -> bool {
+        #[cfg(windows)]
         if let Some(ref pty) = self.pty {
             let data = pty.read_all();
             if !data.is_empty() {
@@ -380,12 +391,13 @@ impl Terminal {
     }
 
     /// Write input to the PTY
+    #[cfg_attr(not(windows), allow(unused_variables))]
     pub fn write_to_pty(&self, data: &[u8]) -> Result<(), String> {
+        #[cfg(windows)]
         if let Some(ref pty) = self.pty {
-            pty.write(data)
-        } else {
-            Err("No PTY attached".to_string())
+            return pty.write(data);
         }
+        Err("No PTY attached".to_string())
     }
 
     /// Write a string to the PTY
@@ -412,6 +424,7 @@ impl Terminal {
         self.term.resize(size);
 
         // Also resize PTY
+        #[cfg(windows)]
         if let Some(ref mut pty) = self.pty {
             let _ = pty.resize(cols, rows);
         }
@@ -505,7 +518,11 @@ impl Terminal {
 
     /// Check if PTY is still alive
     pub fn is_alive(&self) -> bool {
-        self.pty.as_ref().map(|p| p.is_alive()).unwrap_or(false)
+        #[cfg(windows)]
+        let alive = self.pty.as_ref().map(|p| p.is_alive()).unwrap_or(false);
+        #[cfg(not(windows))]
+        let alive = false;
+        alive
     }
 }
 
@@ -1,15 +1,186 @@
 //! AST types for the theme parser
 
-use std::collections::HashMap;
+use std::fmt;
+use std::iter::Peekable;
+use std::ops::Range;
 
+use lalrpop_util::{ErrorRecovery, ParseError};
+
+use crate::animation::{Easing, Transition};
+use crate::theme::functions;
+use crate::theme::lexer::{LexerError, LexerErrorKind, Token};
 use crate::theme::types::{
-    Color, Distance, DistanceUnit, ImageScale, ImageSource, Orientation, Padding,
+    CalcError, CalcExpr, Color, Distance, GradientKind, ImageScale, ImageSource, Orientation,
+    Padding,
 };
 
 /// A complete stylesheet
+///
+/// Its `Display` is the canonical formatting, which parses back to the same
+/// stylesheet.
 #[derive(Debug, Clone)]
 pub struct Stylesheet {
+    /// `@import` / `@theme` directives, in source order
+    pub imports: Vec<ImportDirective>,
     pub rules: Vec<Rule>,
+    /// Comments in source order; see `lexer::comments`
+    pub comments: Vec<Comment>,
+}
+
+/// A comment kept for formatting, placed by its position in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    /// The comment as written, including `/* */` or `//`
+    pub text: String,
+    /// Byte range in the source
+    pub span: Range<usize>,
+    /// Whether it ends a line after other code, like `width: 10; // px`
+    pub trailing: bool,
+    /// Whether it is inside a rule's braces
+    pub nested: bool,
+    /// Whether a blank line separates it from the code or comment before it
+    pub blank_before: bool,
+}
+
+impl fmt::Display for Stylesheet {
+    /// Write the stylesheet in canonical form: rules separated by blank
+    /// lines, with four-space indented properties
+    ///
+    /// Directives stay between the rules they were written between, since an
+    /// import overrides the rules before it. Comments are kept next to the
+    /// rule or property that follows them, along with a blank line before
+    /// them, and comments ending a line stay at the end of that line. Comments
+    /// before a rule that follows directives are written before the directives.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut comments = self.comments.iter().peekable();
+        let mut imports = self.imports.iter().peekable();
+        let rule_start = |i: usize| self.rules.get(i).map_or(usize::MAX, |r| r.span.start);
+        let mut blank = false;
+        for i in 0..=self.rules.len() {
+            let start = rule_start(i);
+            if imports.peek().is_some_and(|d| d.span.start < start) {
+                if blank {
+                    writeln!(f)?;
+                }
+                blank = !comment_lines(f, &mut comments, start, "", true)?;
+                while let Some(directive) = imports.next_if(|d| d.span.start < start) {
+                    if blank {
+                        writeln!(f)?;
+                        blank = false;
+                    }
+                    writeln!(f, "{}", directive.import)?;
+                }
+                blank = true;
+            }
+
+            let Some(rule) = self.rules.get(i) else {
+                break;
+            };
+            let next_rule = rule_start(i + 1);
+            if blank {
+                writeln!(f)?;
+            }
+            blank = true;
+            comment_lines(f, &mut comments, rule.span.start, "", true)?;
+
+            let selectors: Vec<_> = rule.selectors.iter().map(|s| s.to_string()).collect();
+            write!(f, "{} {{", selectors.join(", "))?;
+            let first_property = rule.properties.first().map_or(next_rule, |p| p.span.start);
+            end_line(f, &mut comments, true, first_property)?;
+
+            let mut first = true;
+            for (j, property) in rule.properties.iter().enumerate() {
+                comment_lines(f, &mut comments, property.span.start, "    ", first)?;
+                write!(f, "    {}: {};", property.name, property.value)?;
+                let next = rule
+                    .properties
+                    .get(j + 1)
+                    .map_or(next_rule, |p| p.span.start);
+                end_line(f, &mut comments, true, next)?;
+                first = false;
+            }
+            let end = comments
+                .clone()
+                .take_while(|c| c.nested && c.span.start < next_rule)
+                .last()
+                .map_or(0, |c| c.span.end);
+            comment_lines(f, &mut comments, end, "    ", first)?;
+
+            write!(f, "}}")?;
+            end_line(f, &mut comments, false, next_rule)?;
+        }
+
+        comment_lines(f, &mut comments, usize::MAX, "", !blank)?;
+        Ok(())
+    }
+}
+
+/// Write the comments before `before` on their own lines
+///
+/// `first` is whether they start a section, where a blank line before them
+/// isn't kept; returns whether nothing was written.
+fn comment_lines<'a>(
+    f: &mut fmt::Formatter<'_>,
+    comments: &mut Peekable<impl Iterator<Item = &'a Comment>>,
+    before: usize,
+    indent: &str,
+    mut first: bool,
+) -> Result<bool, fmt::Error> {
+    while let Some(c) = comments.next_if(|c| c.span.start < before) {
+        if c.blank_before && !first {
+            writeln!(f)?;
+        }
+        writeln!(f, "{}{}", indent, c.text)?;
+        first = false;
+    }
+    Ok(first)
+}
+
+/// End the line just written, with the comment that ends it in the source
+/// if there is one before `before`
+fn end_line<'a>(
+    f: &mut fmt::Formatter<'_>,
+    comments: &mut Peekable<impl Iterator<Item = &'a Comment>>,
+    nested: bool,
+    before: usize,
+) -> fmt::Result {
+    match comments.next_if(|c| c.trailing && c.nested == nested && c.span.start < before) {
+        Some(c) => writeln!(f, " {}", c.text),
+        None => writeln!(f),
+    }
+}
+
+/// An include directive
+#[derive(Debug, Clone, PartialEq)]
+pub enum Import {
+    /// `@import "file"`: a path relative to the importing file or the config dir
+    File(String),
+    /// `@theme "name"`: a theme from a `themes/` directory, by name or file name
+    Theme(String),
+}
+
+impl fmt::Display for Import {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Import::File(path) => write!(f, "@import \"{}\";", path),
+            Import::Theme(name) => write!(f, "@theme \"{}\";", name),
+        }
+    }
+}
+
+/// An include directive, placed by its position in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDirective {
+    pub import: Import,
+    /// Byte range in the source
+    pub span: Range<usize>,
+}
+
+/// A top-level stylesheet item, as parsed
+#[derive(Debug, Clone)]
+pub enum Item {
+    Import(ImportDirective),
+    Rule(Rule),
 }
 
 /// A single rule: selector(s) + properties
@@ -17,38 +188,229 @@ pub struct Stylesheet {
 pub struct Rule {
     pub selectors: Vec<Selector>,
     pub properties: Vec<Property>,
+    /// Byte range of the selector list in the source
+    pub span: Range<usize>,
 }
 
+/// Widget states in order of precedence, lowest first
+///
+/// When equally specific rules for different states all match a widget, the
+/// one for the higher state wins: a selected row under the mouse keeps its
+/// selected colours, and a pressed one shows that it is pressed. `normal`
+/// is not listed; it matches any state.
+pub const STATE_PRECEDENCE: &[&str] = &[
+    "alternate",
+    "active",
+    "urgent",
+    "hover",
+    "focused",
+    "selected",
+    "pressed",
+    "disabled",
+];
+
+/// Rofi's row states, which may follow an element name after a space:
+/// `element selected.urgent` is `element` in the states `selected` and `urgent`
+const ROFI_STATES: &[&str] = &["normal", "selected", "alternate"];
+
 /// Widget selector
 #[derive(Debug, Clone, PartialEq)]
 pub enum Selector {
     /// Universal selector: *
     Universal,
-    /// Element selector with optional state: `textbox` or `textbox.focused`
-    Element { name: String, state: Option<String> },
+    /// Element selector with a set of states: `textbox`, `textbox.focused`
+    /// or `element.selected.urgent`
+    Element { name: String, states: Vec<String> },
+    /// Descendant selector: `mainbox listview element`, outermost ancestor
+    /// first; each part is `Universal` or `Element`
+    Descendant(Vec<Selector>),
 }
 
 impl Selector {
     pub fn element(name: impl Into<String>) -> Self {
         Selector::Element {
             name: name.into(),
-            state: None,
+            states: Vec::new(),
         }
     }
 
     pub fn element_with_state(name: impl Into<String>, state: impl Into<String>) -> Self {
         Selector::Element {
             name: name.into(),
-            state: Some(state.into()),
+            states: vec![state.into()],
+        }
+    }
+
+    /// Build a selector from whitespace-separated parts, outermost first
+    ///
+    /// A part named after a rofi row state (`normal`, `selected`,
+    /// `alternate`) adds its states to the part before it instead of being
+    /// a descendant.
+    pub fn from_parts(parts: Vec<Selector>) -> Self {
+        let mut merged: Vec<Selector> = Vec::with_capacity(parts.len());
+        for part in parts {
+            if let (
+                Some(Selector::Element { states, .. }),
+                Selector::Element { name, states: more },
+            ) = (merged.last_mut(), &part)
+            {
+                if ROFI_STATES.contains(&name.as_str()) {
+                    states.push(name.clone());
+                    states.extend(more.iter().cloned());
+                    continue;
+                }
+            }
+            merged.push(part);
+        }
+
+        if merged.len() == 1 {
+            merged.remove(0)
+        } else {
+            Selector::Descendant(merged)
+        }
+    }
+
+    /// The parts of the selector, outermost first
+    pub fn parts(&self) -> &[Selector] {
+        match self {
+            Selector::Descendant(parts) => parts,
+            _ => std::slice::from_ref(self),
+        }
+    }
+
+    /// The states the styled widget must be in (the last part's)
+    pub fn target_states(&self) -> &[String] {
+        match self.parts().last() {
+            Some(Selector::Element { states, .. }) => states,
+            _ => &[],
+        }
+    }
+
+    /// CSS specificity as (states, widget names), compared in that order
+    ///
+    /// `listview element.selected` (1, 2) beats `element.selected` (1, 1),
+    /// which beats `listview element` (0, 2). `normal` doesn't count.
+    pub fn specificity(&self) -> (u32, u32) {
+        self.parts()
+            .iter()
+            .fold((0, 0), |(count, names), part| match part {
+                Selector::Element { states, .. } => {
+                    let real = states.iter().filter(|s| *s != "normal").count() as u32;
+                    (count + real, names + 1)
+                }
+                _ => (count, names),
+            })
+    }
+
+    /// Rank of the highest-precedence state the target requires, 0 for none
+    ///
+    /// Breaks ties between equally specific rules; see `STATE_PRECEDENCE`.
+    pub fn state_rank(&self) -> usize {
+        self.target_states()
+            .iter()
+            .filter_map(|s| STATE_PRECEDENCE.iter().position(|p| p == s))
+            .map(|i| i + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Whether the selector matches the last widget of `path` in `state`
+    ///
+    /// `state` may be compound, like `selected.urgent`; a part matches if the
+    /// widget is in all of its states. Ancestor parts match any ancestor, not
+    /// just the parent, as in CSS.
+    pub fn matches(&self, path: &[PathStep], state: Option<&str>) -> bool {
+        let (Some((target, ancestors)), Some((last, outer))) =
+            (path.split_last(), self.parts().split_last())
+        else {
+            return false;
+        };
+        if !last.matches_step(target, state) {
+            return false;
+        }
+
+        // Match the remaining parts right to left, each against the nearest
+        // ancestor it fits
+        let mut ancestors = ancestors.iter().rev();
+        outer
+            .iter()
+            .rev()
+            .all(|part| ancestors.any(|step| part.matches_step(step, None)))
+    }
+
+    /// Whether a single part matches one widget on the path
+    fn matches_step(&self, step: &PathStep, state: Option<&str>) -> bool {
+        match self {
+            Selector::Universal => true,
+            Selector::Element { name, states } => {
+                let current = step
+                    .state
+                    .into_iter()
+                    .chain(state)
+                    .flat_map(|s| s.split('.'));
+                name == step.name
+                    && states
+                        .iter()
+                        .all(|want| want == "normal" || current.clone().any(|s| s == want))
+            }
+            Selector::Descendant(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selector::Universal => write!(f, "*"),
+            Selector::Element { name, states } => {
+                write!(f, "{}", name)?;
+                for state in states {
+                    write!(f, ".{}", state)?;
+                }
+                Ok(())
+            }
+            Selector::Descendant(parts) => {
+                let parts: Vec<_> = parts.iter().map(|p| p.to_string()).collect();
+                write!(f, "{}", parts.join(" "))
+            }
         }
     }
 }
 
+/// One widget on the path from the root to a styled widget
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathStep<'a> {
+    pub name: &'a str,
+    /// State or class the widget is in, e.g. the mode on `window.launcher`
+    pub state: Option<&'a str>,
+}
+
+/// Split a widget path written like a selector into steps
+///
+/// `window.launcher mainbox listview` is `listview` inside `mainbox` inside
+/// `window` in the `launcher` state. A plain name is a path of one step.
+pub fn parse_path(path: &str) -> Vec<PathStep<'_>> {
+    path.split_whitespace()
+        .map(|step| match step.split_once('.') {
+            Some((name, state)) => PathStep {
+                name,
+                state: Some(state),
+            },
+            None => PathStep {
+                name: step,
+                state: None,
+            },
+        })
+        .collect()
+}
+
 /// A property: name-value pair
 #[derive(Debug, Clone)]
 pub struct Property {
     pub name: String,
     pub value: Value,
+    /// Byte range of `name: value` in the source
+    pub span: Range<usize>,
 }
 
 /// Property value
@@ -76,6 +438,196 @@ pub enum Value {
     Image(ImageSource),
     /// Orientation (horizontal/vertical)
     Orientation(Orientation),
+    /// Variable reference: `var(name)` or `var(name, fallback)`
+    Var(String, Option<Box<Value>>),
+    /// Function call such as `lighten(accent-primary, 10%)`, evaluated on resolution
+    Function(String, Vec<Value>),
+    /// `linear-gradient(...)` / `radial-gradient(...)`; stop colors are
+    /// resolved with `ThemeTree::resolve_paint`
+    Gradient(GradientKind, Vec<ColorStop>),
+    /// `box-shadow: x y [blur [spread]] color`
+    Shadow(Box<ShadowValue>),
+    /// `transition: background-color 120ms ease-out, border-color 200ms`
+    Transition(Vec<TransitionValue>),
+}
+
+/// One entry of a `transition` list, as written
+#[derive(Debug, Clone)]
+pub struct TransitionValue {
+    /// The property, or `all`
+    pub property: String,
+    pub duration_ms: f64,
+    /// An easing name or `cubic-bezier()`; CSS's `ease` if None
+    pub easing: Option<Value>,
+}
+
+impl TransitionValue {
+    /// The easing curve; None for an unknown name or a bad `cubic-bezier()`
+    pub fn easing(&self) -> Option<Easing> {
+        match &self.easing {
+            None => Some(Easing::EASE),
+            Some(Value::Ident(name)) => Easing::named(name),
+            Some(Value::Function(name, args)) if name == "cubic-bezier" => {
+                let points: Vec<f32> = args
+                    .iter()
+                    .map(|arg| arg.as_number().map(|n| n as f32))
+                    .collect::<Option<_>>()?;
+                match points[..] {
+                    // The x coordinates are times, which must stay in 0-1
+                    [x1, y1, x2, y2] if (0.0..=1.0).contains(&x1) && (0.0..=1.0).contains(&x2) => {
+                        Some(Easing::CubicBezier(x1, y1, x2, y2))
+                    }
+                    _ => None,
+                }
+            }
+            Some(_) => None,
+        }
+    }
+
+    /// Resolve the entry; None if its easing is invalid
+    pub fn to_transition(&self) -> Option<Transition> {
+        Some(Transition {
+            property: self.property.clone(),
+            duration_ms: self.duration_ms.max(0.0).round() as u32,
+            easing: self.easing()?,
+        })
+    }
+}
+
+impl fmt::Display for TransitionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}ms", self.property, self.duration_ms)?;
+        if let Some(easing) = &self.easing {
+            write!(f, " {}", easing)?;
+        }
+        Ok(())
+    }
+}
+
+/// A gradient color stop as written: a color and an optional position in percent
+#[derive(Debug, Clone)]
+pub struct ColorStop {
+    pub color: Value,
+    pub position: Option<f64>,
+}
+
+/// A `box-shadow` as written; the color may be a token or a function
+#[derive(Debug, Clone)]
+pub struct ShadowValue {
+    pub offset_x: Distance,
+    pub offset_y: Distance,
+    pub blur: Distance,
+    pub spread: Distance,
+    pub color: Value,
+}
+
+/// Build a `box-shadow` value
+pub fn shadow(
+    offset_x: Distance,
+    offset_y: Distance,
+    blur: Distance,
+    spread: Distance,
+    color: Value,
+) -> Value {
+    Value::Shadow(Box::new(ShadowValue {
+        offset_x,
+        offset_y,
+        blur,
+        spread,
+        color,
+    }))
+}
+
+/// A word with its byte range in the source
+pub type SpannedWord = (usize, String, usize);
+
+/// Angle of a `linear-gradient(to <side> [<side>], ...)` direction
+///
+/// `to right` is 90deg and `to bottom` 180deg; `to top right` points at the
+/// corner of a square box (45deg). On error, returns the first word that
+/// doesn't fit.
+pub fn gradient_side_angle(to: SpannedWord, sides: Vec<SpannedWord>) -> Result<f32, SpannedWord> {
+    if to.1 != "to" {
+        return Err(to);
+    }
+    let (mut dx, mut dy) = (0.0f32, 0.0f32);
+    for (i, side) in sides.into_iter().enumerate() {
+        let (x, y) = match side.1.as_str() {
+            "top" => (0.0, -1.0),
+            "bottom" => (0.0, 1.0),
+            "left" => (-1.0, 0.0),
+            "right" => (1.0, 0.0),
+            _ => return Err(side),
+        };
+        // At most one horizontal and one vertical side
+        if i > 1 || (x != 0.0 && dx != 0.0) || (y != 0.0 && dy != 0.0) {
+            return Err(side);
+        }
+        dx += x;
+        dy += y;
+    }
+    Ok(dx.atan2(-dy).to_degrees().rem_euclid(360.0))
+}
+
+/// Build a `radial-gradient(...)`, taking a leading `circle` or `ellipse`
+/// as the shape
+///
+/// The shape can't be told apart from a color name by the grammar.
+pub fn radial_gradient(mut stops: Vec<ColorStop>) -> Value {
+    let shape = match stops.first() {
+        Some(ColorStop {
+            color: Value::Ident(word),
+            position: None,
+        }) if stops.len() > 1 && (word == "circle" || word == "ellipse") => Some(word == "circle"),
+        _ => None,
+    };
+    if shape.is_some() {
+        stops.remove(0);
+    }
+    Value::Gradient(
+        GradientKind::Radial {
+            circle: shape.unwrap_or(false),
+        },
+        stops,
+    )
+}
+
+/// A color constructor such as `hsl(...)`: folded to a color when its
+/// channels are literals, otherwise evaluated on resolution
+pub fn color_function(name: &str, args: Vec<Value>) -> Value {
+    let literal = args
+        .iter()
+        .all(|arg| matches!(arg, Value::Number(_) | Value::Distance(_)));
+    if literal {
+        if let Ok(color) = functions::call(name, &args) {
+            return color;
+        }
+    }
+    // Errors are reported when the value is resolved or linted
+    Value::Function(name.to_string(), args)
+}
+
+/// The distance for a `calc(...)` expression, which must be a length
+pub fn calc_distance(expr: CalcExpr) -> Result<Distance, CalcError> {
+    if expr.is_length() {
+        Ok(Distance::calc(expr))
+    } else {
+        Err(CalcError::NotALength)
+    }
+}
+
+/// A recovered error for a badly typed `calc()` expression at `span`
+pub fn calc_error(span: Range<usize>, error: CalcError) -> ErrorRecovery<usize, Token, LexerError> {
+    ErrorRecovery {
+        error: ParseError::User {
+            error: LexerError {
+                span,
+                slice: String::new(),
+                kind: LexerErrorKind::Calc(error),
+            },
+        },
+        dropped_tokens: Vec::new(),
+    }
 }
 
 impl Value {
@@ -88,23 +640,6 @@ impl Value {
         }
     }
 
-    /// Try to convert to Color, resolving identifiers against globals
-    /// This allows theme tokens like `accent-primary` to be used in place of colors
-    pub fn as_color_resolved(&self, globals: &HashMap<String, Value>) -> Option<Color> {
-        match self {
-            Value::Color(c) => Some(*c),
-            Value::Ident(name) => {
-                // First check named CSS colors
-                if let Some(c) = named_color(name) {
-                    return Some(c);
-                }
-                // Then check global theme tokens
-                globals.get(name).and_then(|v| v.as_color())
-            }
-            _ => None,
-        }
-    }
-
     /// Try to convert to Distance
     pub fn as_distance(&self) -> Option<Distance> {
         match self {
@@ -140,7 +675,7 @@ impl Value {
     pub fn as_number(&self) -> Option<f64> {
         match self {
             Value::Number(n) => Some(*n),
-            Value::Distance(d) => Some(d.value),
+            Value::Distance(d) => d.as_number(),
             _ => None,
         }
     }
@@ -181,6 +716,79 @@ impl Value {
     }
 }
 
+impl fmt::Display for Value {
+    /// Write the value as it would appear in a theme; parsing the output
+    /// gives back the same value
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Color(c) => write_color(f, c),
+            Value::Distance(d) => write!(f, "{}", d),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write!(f, "\"{}\"", s),
+            Value::Ident(s) => write!(f, "{}", s),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Padding2(v, h) => write!(f, "{} {}", v, h),
+            Value::Padding4(t, r, b, l) => write!(f, "{} {} {} {}", t, r, b, l),
+            Value::Array(items) if items.is_empty() => write!(f, "[]"),
+            Value::Array(items) => {
+                let items: Vec<_> = items.iter().map(|s| format!("\"{}\"", s)).collect();
+                write!(f, "[ {} ]", items.join(", "))
+            }
+            Value::Image(image) => match image.scale {
+                ImageScale::None => write!(f, "url(\"{}\")", image.path),
+                scale => write!(f, "url(\"{}\", {})", image.path, scale.as_str()),
+            },
+            Value::Orientation(o) => write!(f, "{}", o.as_str()),
+            Value::Var(name, None) => write!(f, "var({})", name),
+            Value::Var(name, Some(fallback)) => write!(f, "var({}, {})", name, fallback),
+            Value::Function(name, args) => {
+                let args: Vec<_> = args.iter().map(|a| a.to_string()).collect();
+                write!(f, "{}({})", name, args.join(", "))
+            }
+            Value::Gradient(kind, stops) => {
+                match kind {
+                    GradientKind::Linear { angle } => write!(f, "linear-gradient({}deg", angle)?,
+                    GradientKind::Radial { circle: true } => write!(f, "radial-gradient(circle")?,
+                    GradientKind::Radial { circle: false } => write!(f, "radial-gradient(ellipse")?,
+                }
+                for stop in stops {
+                    write!(f, ", {}", stop.color)?;
+                    if let Some(position) = stop.position {
+                        write!(f, " {}%", position)?;
+                    }
+                }
+                write!(f, ")")
+            }
+            Value::Shadow(shadow) => write!(
+                f,
+                "{} {} {} {} {}",
+                shadow.offset_x, shadow.offset_y, shadow.blur, shadow.spread, shadow.color
+            ),
+            Value::Transition(transitions) => {
+                let transitions: Vec<_> = transitions.iter().map(|t| t.to_string()).collect();
+                write!(f, "{}", transitions.join(", "))
+            }
+        }
+    }
+}
+
+/// Write a color as hex, or with fractional channels if hex would round it
+fn write_color(f: &mut fmt::Formatter<'_>, c: &Color) -> fmt::Result {
+    let hex = c.to_hex();
+    if Color::from_hex(&hex).ok().as_ref() == Some(c) {
+        write!(f, "{}", hex)
+    } else {
+        write!(
+            f,
+            "rgba({}, {}, {}, {})",
+            c.r * 255.0,
+            c.g * 255.0,
+            c.b * 255.0,
+            c.a
+        )
+    }
+}
+
 /// Get color from CSS color name
 fn named_color(name: &str) -> Option<Color> {
     match name.to_lowercase().as_str() {
@@ -218,6 +826,10 @@ pub fn make_distance(value: f64, unit: &str) -> Distance {
         "em" => Distance::em(value),
         "%" => Distance::percent(value),
         "mm" => Distance::mm(value),
+        "ch" => Distance::ch(value),
+        "vw" => Distance::vw(value),
+        "vh" => Distance::vh(value),
+        "dp" => Distance::dp(value),
         _ => Distance::px(value), // Default fallback
     }
 }
@@ -0,0 +1,213 @@
+//! Source-located theme diagnostics
+//!
+//! Parse errors carry the file, a 1-based line/column span and a rendered
+//! snippet pointing at the offending text:
+//! ```text
+//! core.rasi:12:11: unexpected `10px`, expected `:`
+//!    |
+//! 12 |     width 10px;
+//!    |           ^^^^
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use lalrpop_util::ParseError;
+
+use crate::theme::lexer::{LexerError, Token};
+
+/// How many expected tokens to list before eliding the rest
+const MAX_EXPECTED: usize = 6;
+
+/// An error located in theme source text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// File the error is in (None when parsing a string)
+    pub file: Option<PathBuf>,
+    /// 1-based start line
+    pub line: usize,
+    /// 1-based start column, in characters
+    pub column: usize,
+    /// 1-based end line
+    pub end_line: usize,
+    /// 1-based end column (exclusive), in characters
+    pub end_column: usize,
+    pub message: String,
+    /// Source line with a caret marker under the span
+    pub snippet: String,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic for a byte range of `source`
+    pub fn new(
+        source: &str,
+        span: Range<usize>,
+        message: impl Into<String>,
+        file: Option<&Path>,
+    ) -> Self {
+        let start = clamp_to_char_boundary(source, span.start);
+        let end = clamp_to_char_boundary(source, span.end.max(start));
+        let (line, column) = line_column(source, start);
+        let (end_line, end_column) = line_column(source, end);
+
+        Self {
+            file: file.map(Path::to_path_buf),
+            line,
+            column,
+            end_line,
+            end_column,
+            message: message.into(),
+            snippet: render_snippet(source, start, end, line),
+        }
+    }
+
+    /// Create a diagnostic from a lalrpop parse error
+    pub fn from_parse_error(
+        source: &str,
+        error: &ParseError<usize, Token, LexerError>,
+        file: Option<&Path>,
+    ) -> Self {
+        let (span, message) = match error {
+            ParseError::InvalidToken { location } => {
+                (*location..*location, "invalid token".to_string())
+            }
+            ParseError::UnrecognizedEof { location, expected } => (
+                *location..*location,
+                format!("unexpected end of file{}", describe_expected(expected)),
+            ),
+            ParseError::UnrecognizedToken {
+                token: (start, _, end),
+                expected,
+            } => (
+                *start..*end,
+                format!(
+                    "unexpected `{}`{}",
+                    &source[*start..*end],
+                    describe_expected(expected)
+                ),
+            ),
+            ParseError::ExtraToken {
+                token: (start, _, end),
+            } => (
+                *start..*end,
+                format!("unexpected `{}`", &source[*start..*end]),
+            ),
+            ParseError::User { error } => (error.span.clone(), error.message()),
+        };
+
+        Self::new(source, span, message, file)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = self
+            .file
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "<input>".to_string());
+        write!(
+            f,
+            "{}:{}:{}: {}\n{}",
+            file, self.line, self.column, self.message, self.snippet
+        )
+    }
+}
+
+fn clamp_to_char_boundary(source: &str, mut offset: usize) -> usize {
+    offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// 1-based line and character column of a byte offset
+pub(crate) fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, source[line_start..offset].chars().count() + 1)
+}
+
+/// Render the start line of the span with carets under it
+fn render_snippet(source: &str, start: usize, end: usize, line: usize) -> String {
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let text = source[line_start..line_end].trim_end_matches('\r');
+
+    let indent: String = source[line_start..start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let width = source[start..end.min(line_end)]
+        .trim_end_matches('\r')
+        .chars()
+        .count()
+        .max(1);
+
+    let number = line.to_string();
+    let gutter = " ".repeat(number.len());
+    format!(
+        "{} |\n{} | {}\n{} | {}{}",
+        gutter,
+        number,
+        text,
+        gutter,
+        indent,
+        "^".repeat(width)
+    )
+}
+
+/// ", expected `:`" / ", expected one of `;`, `}`, ..."
+fn describe_expected(expected: &[String]) -> String {
+    // lalrpop quotes literal terminals: "\"{\"" -> `{`
+    let names: Vec<String> = expected
+        .iter()
+        .take(MAX_EXPECTED)
+        .map(|e| format!("`{}`", e.trim_matches('"')))
+        .collect();
+
+    match expected.len() {
+        0 => String::new(),
+        1 => format!(", expected {}", names[0]),
+        n if n > MAX_EXPECTED => format!(", expected one of {}, ...", names.join(", ")),
+        _ => format!(", expected one of {}", names.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_location_and_snippet() {
+        let source = "* {\r\n    width 10px;\r\n}";
+        let start = source.find("10px").unwrap();
+        let diag = Diagnostic::new(
+            source,
+            start..start + 4,
+            "unexpected `10px`",
+            Some(Path::new("core.rasi")),
+        );
+
+        assert_eq!((diag.line, diag.column), (2, 11));
+        assert_eq!((diag.end_line, diag.end_column), (2, 15));
+        assert_eq!(diag.snippet, "  |\n2 |     width 10px;\n  |           ^^^^");
+        assert!(diag
+            .to_string()
+            .starts_with("core.rasi:2:11: unexpected `10px`\n"));
+    }
+
+    #[test]
+    fn test_diagnostic_at_end_of_input() {
+        let source = "window {";
+        let diag = Diagnostic::new(source, source.len()..source.len(), "eof", None);
+        assert_eq!((diag.line, diag.column), (1, 9));
+        assert!(diag.snippet.ends_with("|         ^"));
+        assert!(diag.to_string().starts_with("<input>:1:9: eof"));
+    }
+}
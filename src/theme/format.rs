@@ -0,0 +1,188 @@
+//! Canonical formatting of Wolfy theme files (`wolfy --fmt`)
+//!
+//! The canonical form is `Stylesheet`'s `Display`: directives first, one
+//! property per line indented by four spaces, a blank line between rules,
+//! colors as lowercase hex and shorthand values written out in full. Comments
+//! and the order of rules are kept.
+
+use std::path::Path;
+
+use crate::theme::tree::{Dialect, ThemeError, ThemeTree};
+
+/// Format theme source text
+///
+/// Fails on any syntax error rather than formatting the rules that did
+/// parse, which would drop the broken ones. Windows line endings are kept.
+pub fn format(source: &str, file: Option<&Path>) -> Result<String, ThemeError> {
+    let (stylesheet, diagnostics, _) = ThemeTree::parse_stylesheet(source, file, Dialect::Wolfy)?;
+    if let Some(diagnostic) = diagnostics.into_iter().next() {
+        return Err(ThemeError::ParseError(diagnostic));
+    }
+
+    // Block comments keep the line endings they were written with
+    let formatted = stylesheet.to_string().replace("\r\n", "\n");
+    Ok(if source.contains("\r\n") {
+        formatted.replace('\n', "\r\n")
+    } else {
+        formatted
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::ast::{Rule, Stylesheet};
+    use crate::theme::lexer::Lexer;
+    use crate::theme::theme_parser;
+    use std::fs;
+
+    fn parse(source: &str) -> Stylesheet {
+        let mut errors = Vec::new();
+        let stylesheet = theme_parser::StylesheetParser::new()
+            .parse(&mut errors, Lexer::new(source))
+            .unwrap();
+        assert!(errors.is_empty(), "{:?}\n{}", errors, source);
+        stylesheet
+    }
+
+    /// Rules without their source positions
+    fn shape(rules: &[Rule]) -> Vec<String> {
+        rules
+            .iter()
+            .map(|rule| {
+                let properties: Vec<_> = rule
+                    .properties
+                    .iter()
+                    .map(|p| format!("{}: {:?}", p.name, p.value))
+                    .collect();
+                format!("{:?} {:?}", rule.selectors, properties)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_values_round_trip() {
+        let source = r#"
+            @import "core.rasi";
+            @theme "tokyo_night";
+            * { accent: #7aa2f7; faded: #7aa2f780; exact: rgba(30, 30, 30, 0.8); odd: oklch(70% 0.1 200); }
+            window, #mainbox listview element.selected.urgent {
+                width: 60vw;
+                height: calc(100% - 2 * (8px + 4px) - 10px / 2);
+                nested: calc(calc(1em * 2) - (4px - 2px));
+                number: -0.25;
+                name: "Segoe UI";
+                easing: ease-out-expo;
+                enabled: false;
+                padding: 4px 8em;
+                margin: 1px 2% 3mm 4ch;
+                children: [ "a", b ];
+                empty: [];
+                image: url("auto", both);
+                plain-image: url("wall.png");
+                orientation: horizontal;
+                color: var(accent);
+                fallback: var(missing, lighten(accent, 10%));
+                hue: hsl(var(hue) 50% 50% / 0.5);
+                paint: linear-gradient(to top right, #fff 20%, accent);
+                glow: radial-gradient(circle, red, blue 75%);
+                wash: radial-gradient(#000, #fff);
+                box-shadow: 0 4px 12px 1dp rgba(0, 0, 0, 50%);
+                transition: background-color 120ms ease-out, all 0.5s cubic-bezier(0.2, 0, 0, 1);
+                inherited: inherit;
+            }
+        "#;
+        let stylesheet = parse(source);
+        let formatted = stylesheet.to_string();
+        let reparsed = parse(&formatted);
+
+        let imports = |s: &Stylesheet| {
+            s.imports
+                .iter()
+                .map(|d| d.import.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(imports(&reparsed), imports(&stylesheet));
+        assert_eq!(shape(&reparsed.rules), shape(&stylesheet.rules));
+        // Formatting is stable
+        assert_eq!(reparsed.to_string(), formatted);
+    }
+
+    #[test]
+    fn test_canonical_layout_and_comments() {
+        let source = "/* Header */\n\
+                      @import \"core.rasi\"\n\
+                      * { accent:#fff;   // the accent\n\n\
+                      /* Text */ text:   accent; }\n\
+                      // Window\n\
+                      window:focused{width:10px;/* end */} // after\n\n\
+                      // Footer\n";
+        assert_eq!(
+            format(source, None).unwrap(),
+            "/* Header */\n\
+             \n\
+             @import \"core.rasi\";\n\
+             \n\
+             * {\n    \
+                 accent: #ffffff; // the accent\n\
+             \n    \
+                 /* Text */\n    \
+                 text: accent;\n\
+             }\n\
+             \n\
+             // Window\n\
+             window.focused {\n    \
+                 width: 10px; /* end */\n\
+             } // after\n\
+             \n\
+             // Footer\n"
+        );
+    }
+
+    #[test]
+    fn test_windows_line_endings() {
+        let source =
+            "// Colors\r\n* {\r\n    /* one\r\n       two */\r\n    accent: #ffffff;\r\n}\r\n";
+        assert_eq!(format(source, None).unwrap(), source);
+    }
+
+    #[test]
+    fn test_format_errors() {
+        assert!(matches!(
+            format("window { width 10px; }", None),
+            Err(ThemeError::ParseError(d)) if d.line == 1
+        ));
+    }
+
+    #[test]
+    fn test_shipped_themes_format_stably() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut files = vec![root.join("core.rasi"), root.join("launcher.rasi")];
+        for entry in fs::read_dir(root.join("themes")).unwrap() {
+            files.push(entry.unwrap().path());
+        }
+
+        for file in files {
+            let source = fs::read_to_string(&file).unwrap();
+            let formatted = format(&source, Some(&file)).unwrap();
+            assert_eq!(
+                shape(&parse(&formatted).rules),
+                shape(&parse(&source).rules),
+                "{}",
+                file.display()
+            );
+            assert_eq!(
+                format(&formatted, None).unwrap(),
+                formatted,
+                "{}",
+                file.display()
+            );
+            assert_eq!(
+                formatted.matches("/*").count(),
+                source.matches("/*").count(),
+                "{}",
+                file.display()
+            );
+        }
+    }
+}
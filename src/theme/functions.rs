@@ -0,0 +1,260 @@
+//! Built-in theme functions
+//!
+//! Functions are evaluated when a value is resolved, after their arguments
+//! have been resolved (so arguments can be `var()` references or color tokens):
+//! - `lighten(color, amount)` / `darken(color, amount)`: adjust HSL lightness
+//! - `alpha(color, amount)`: replace the alpha channel
+//! - `mix(a, b, weight)`: blend two colors, `weight` of `a` (default 50%)
+//! - `rgb(red, green, blue)` / `rgba(..., alpha)`: channels 0-255 or percentages
+//! - `hsl(hue, saturation, lightness)` / `hsla(..., alpha)`: a color from
+//!   HSL, hue in degrees (as written by rofi themes)
+//! - `hwb(hue, whiteness, blackness)`: a hue mixed with white and black
+//! - `oklch(lightness, chroma, hue)`: a perceptually uniform color, chroma
+//!   0-0.4 or a percentage of 0.4
+//!
+//! Amounts are percentages (`10%`) or fractions (`0.1`). The color
+//! constructors take an optional fourth alpha amount; the parser turns the
+//! CSS4 form `hsl(120deg 50% 50% / 0.5)` into the same arguments.
+
+use crate::theme::ast::Value;
+use crate::theme::types::{Color, DistanceUnit};
+
+/// Evaluate a function call with already-resolved arguments
+pub fn call(name: &str, args: &[Value]) -> Result<Value, String> {
+    let color = match name {
+        "lighten" => {
+            expect_args(name, args, 2)?;
+            color_arg(name, &args[0])?.lighten(amount_arg(name, &args[1])?)
+        }
+        "darken" => {
+            expect_args(name, args, 2)?;
+            color_arg(name, &args[0])?.darken(amount_arg(name, &args[1])?)
+        }
+        "alpha" => {
+            expect_args(name, args, 2)?;
+            color_arg(name, &args[0])?.with_alpha(amount_arg(name, &args[1])?)
+        }
+        "mix" => {
+            if args.len() != 2 && args.len() != 3 {
+                return Err(format!(
+                    "{}() takes 2 or 3 arguments, got {}",
+                    name,
+                    args.len()
+                ));
+            }
+            let weight = match args.get(2) {
+                Some(w) => amount_arg(name, w)?,
+                None => 0.5,
+            };
+            color_arg(name, &args[0])?.mix(&color_arg(name, &args[1])?, weight)
+        }
+        "rgb" | "rgba" => {
+            let alpha = color_args(name, args)?;
+            Color::from_f32(
+                rgb_arg(name, &args[0])?,
+                rgb_arg(name, &args[1])?,
+                rgb_arg(name, &args[2])?,
+                alpha,
+            )
+        }
+        "hsl" | "hsla" => {
+            let alpha = color_args(name, args)?;
+            Color::from_hsla(
+                hue_arg(name, &args[0])?,
+                amount_arg(name, &args[1])?,
+                amount_arg(name, &args[2])?,
+                alpha,
+            )
+        }
+        "hwb" => {
+            let alpha = color_args(name, args)?;
+            Color::from_hwba(
+                hue_arg(name, &args[0])?,
+                amount_arg(name, &args[1])?,
+                amount_arg(name, &args[2])?,
+                alpha,
+            )
+        }
+        "oklch" => {
+            let alpha = color_args(name, args)?;
+            let chroma = match &args[1] {
+                Value::Distance(d) if d.unit == DistanceUnit::Percent => d.value as f32 * 0.004,
+                Value::Number(n) => *n as f32,
+                other => return Err(format!("{}() expected a chroma, got {:?}", name, other)),
+            };
+            Color::from_oklcha(
+                amount_arg(name, &args[0])?,
+                chroma,
+                hue_arg(name, &args[2])?,
+                alpha,
+            )
+        }
+        _ => return Err(format!("unknown function {}()", name)),
+    };
+
+    Ok(Value::Color(color))
+}
+
+fn expect_args(name: &str, args: &[Value], count: usize) -> Result<(), String> {
+    if args.len() == count {
+        Ok(())
+    } else {
+        Err(format!(
+            "{}() takes {} arguments, got {}",
+            name,
+            count,
+            args.len()
+        ))
+    }
+}
+
+/// Check a color constructor has three channels and an optional alpha,
+/// returning the alpha (1.0 if absent)
+fn color_args(name: &str, args: &[Value]) -> Result<f32, String> {
+    match args.len() {
+        3 => Ok(1.0),
+        4 => Ok(amount_arg(name, &args[3])?.clamp(0.0, 1.0)),
+        n => Err(format!("{}() takes 3 or 4 arguments, got {}", name, n)),
+    }
+}
+
+/// An RGB channel, 0-255 or a percentage, as 0.0-1.0
+fn rgb_arg(name: &str, value: &Value) -> Result<f32, String> {
+    match value {
+        Value::Distance(d) if d.unit == DistanceUnit::Percent => Ok(d.value as f32 / 100.0),
+        Value::Number(n) => Ok((*n as f32 / 255.0).clamp(0.0, 1.0)),
+        _ => Err(format!(
+            "{}() expected a channel 0-255 or a percentage, got {:?}",
+            name, value
+        )),
+    }
+}
+
+/// A hue in degrees
+fn hue_arg(name: &str, value: &Value) -> Result<f32, String> {
+    match value {
+        Value::Number(n) => Ok(*n as f32),
+        _ => Err(format!("{}() expected a hue, got {:?}", name, value)),
+    }
+}
+
+fn color_arg(name: &str, value: &Value) -> Result<Color, String> {
+    value
+        .as_color()
+        .ok_or_else(|| format!("{}() expected a color, got {:?}", name, value))
+}
+
+/// A percentage or fraction as 0.0-1.0
+fn amount_arg(name: &str, value: &Value) -> Result<f32, String> {
+    match value {
+        Value::Distance(d) if d.unit == DistanceUnit::Percent => Ok(d.value as f32 / 100.0),
+        Value::Number(n) => Ok(*n as f32),
+        _ => Err(format!(
+            "{}() expected a percentage or number, got {:?}",
+            name, value
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::types::Distance;
+
+    fn color(value: Result<Value, String>) -> Color {
+        value.unwrap().as_color().unwrap()
+    }
+
+    #[test]
+    fn test_color_functions() {
+        let gray = Value::Color(Color::rgb(128, 128, 128));
+
+        let light = color(call("lighten", &[gray.clone(), Value::Number(0.1)]));
+        assert!(light.r > 0.6 && light.r < 0.61);
+
+        let dark = color(call(
+            "darken",
+            &[gray.clone(), Value::Distance(Distance::percent(10.0))],
+        ));
+        assert!(dark.r > 0.4 && dark.r < 0.41);
+
+        let faded = color(call("alpha", &[gray.clone(), Value::Number(0.25)]));
+        assert_eq!(faded.a, 0.25);
+
+        let mixed = color(call(
+            "mix",
+            &[
+                Value::Ident("white".to_string()),
+                Value::Ident("black".to_string()),
+                Value::Distance(Distance::percent(30.0)),
+            ],
+        ));
+        assert!((mixed.r - 0.3).abs() < 0.001);
+        assert_eq!(
+            color(call("mix", &[gray.clone(), gray.clone()])),
+            gray.as_color().unwrap()
+        );
+
+        let percent = |p| Value::Distance(Distance::percent(p));
+        let green = color(call(
+            "hsl",
+            &[Value::Number(120.0), percent(100.0), percent(50.0)],
+        ));
+        assert_eq!(green.to_u32(), 0xff00ff00);
+        let faded = color(call(
+            "hsla",
+            &[
+                Value::Number(0.0),
+                percent(0.0),
+                percent(0.0),
+                percent(50.0),
+            ],
+        ));
+        assert_eq!(faded.a, 0.5);
+    }
+
+    #[test]
+    fn test_color_constructors() {
+        let percent = |p| Value::Distance(Distance::percent(p));
+        let n = Value::Number;
+
+        let c = color(call("rgb", &[n(255.0), n(0.0), percent(50.0)]));
+        assert_eq!(c.to_hex(), "#ff0080");
+        // Alpha is kept exactly rather than rounded to a byte
+        let c = color(call("rgba", &[n(30.0), n(30.0), n(30.0), n(0.8)]));
+        assert_eq!(c.a, 0.8);
+
+        let c = color(call("hwb", &[n(240.0), percent(0.0), percent(50.0)]));
+        assert_eq!(c.to_hex(), "#000080");
+
+        let c = color(call(
+            "oklch",
+            &[percent(62.8), n(0.2577), n(29.23), percent(50.0)],
+        ));
+        assert_eq!(c.to_hex(), "#ff000080");
+        let white = color(call("oklch", &[n(1.0), percent(0.0), n(0.0)]));
+        assert_eq!(white.to_hex(), "#ffffff");
+    }
+
+    #[test]
+    fn test_function_errors() {
+        assert!(call("brighten", &[]).is_err());
+        assert!(call("alpha", &[Value::Number(1.0)]).is_err());
+        assert!(call("alpha", &[Value::Number(1.0), Value::Number(0.5)]).is_err());
+        assert!(call("rgb", &[Value::Number(1.0), Value::Number(0.5)]).is_err());
+        assert!(call(
+            "hsl",
+            &[
+                Value::Distance(Distance::percent(10.0)),
+                Value::Number(0.5),
+                Value::Number(0.5)
+            ]
+        )
+        .is_err());
+        assert!(call(
+            "lighten",
+            &[Value::Color(Color::RED), Value::String("x".into())]
+        )
+        .is_err());
+    }
+}
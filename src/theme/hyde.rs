@@ -0,0 +1,448 @@
+//! Wolfy themes generated from HyDE theme directories
+//!
+//! Every HyDE theme (`hyde/themes/<Name>/`) ships configs for other apps that
+//! already hold its palette:
+//! - `rofi.theme`: `main-bg`, `main-fg`, `main-br`, `main-ex`, `select-bg` and
+//!   `select-fg` become the surface, text and accent tokens
+//! - `kitty.theme`: `color0`-`color15`, `foreground`, `background` and
+//!   `cursor` become the `term-*` tokens
+//! - `waybar.theme` (optional): `@define-color` entries are kept as
+//!   `waybar-*` tokens
+//!
+//! `generate` writes them out in the format of the hand-written
+//! `themes/*.rasi`; the launcher uses it for themes that have none.
+
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::theme::ast::Value;
+use crate::theme::tree::{ThemeError, ThemeTree};
+use crate::theme::types::Color;
+
+/// Terminal color names, in kitty's `color0`-`color15` order
+const TERM_COLORS: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// Font for the terminal tokens; HyDE themes don't name one
+const TERM_FONT: &str = "Cascadia Mono";
+
+/// Error generating a theme from a HyDE directory
+#[derive(Debug)]
+pub enum GenerateError {
+    /// A theme file couldn't be read or parsed
+    Theme(PathBuf, Box<ThemeError>),
+    /// A theme file lacks a color the token set is built from
+    MissingColor { file: PathBuf, name: String },
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::Theme(path, e) => write!(f, "{}: {}", path.display(), e),
+            GenerateError::MissingColor { file, name } => {
+                write!(f, "{}: no `{}` color", file.display(), name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+/// The colors of a HyDE theme
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    /// rofi `main-bg`, made opaque
+    pub background: Color,
+    /// rofi `main-fg`
+    pub foreground: Color,
+    /// rofi `select-bg`, `main-br` and `main-ex`, made opaque
+    pub accents: [Color; 3],
+    /// rofi `select-fg`
+    pub select_foreground: Color,
+    /// kitty `color0`-`color15`
+    pub term: [Color; 16],
+    pub term_foreground: Color,
+    pub term_background: Color,
+    pub term_cursor: Color,
+    /// waybar `@define-color`s, in file order
+    pub waybar: Vec<(String, Color)>,
+}
+
+impl Palette {
+    /// Read the palette from a HyDE theme directory
+    pub fn load(dir: &Path) -> Result<Self, GenerateError> {
+        let rofi_path = dir.join("rofi.theme");
+        let rofi = ThemeTree::load_rofi(&rofi_path)
+            .map_err(|e| GenerateError::Theme(rofi_path.clone(), Box::new(e)))?;
+        let rofi_color = |name: &str| {
+            rofi.globals
+                .get(name)
+                .and_then(|v| rofi.resolve_color(v))
+                .map(opaque)
+                .ok_or_else(|| missing(&rofi_path, name))
+        };
+
+        let kitty_path = dir.join("kitty.theme");
+        let kitty = fs::read_to_string(&kitty_path)
+            .map(|source| parse_kitty(&source))
+            .map_err(|e| GenerateError::Theme(kitty_path.clone(), Box::new(e.into())))?;
+        let kitty_color = |name: &str| {
+            kitty
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, c)| *c)
+                .ok_or_else(|| missing(&kitty_path, name))
+        };
+
+        let mut term = [Color::BLACK; 16];
+        for (i, color) in term.iter_mut().enumerate() {
+            *color = kitty_color(&format!("color{}", i))?;
+        }
+        let term_foreground = kitty_color("foreground")?;
+
+        // waybar.theme only adds extras, so it may be missing
+        let waybar = fs::read_to_string(dir.join("waybar.theme"))
+            .map(|source| parse_waybar(&source))
+            .unwrap_or_default();
+
+        Ok(Self {
+            background: rofi_color("main-bg")?,
+            foreground: rofi_color("main-fg")?,
+            accents: [
+                rofi_color("select-bg")?,
+                rofi_color("main-br")?,
+                rofi_color("main-ex")?,
+            ],
+            select_foreground: rofi_color("select-fg")?,
+            term,
+            term_foreground,
+            term_background: kitty_color("background")?,
+            term_cursor: kitty_color("cursor").unwrap_or(term_foreground),
+            waybar,
+        })
+    }
+
+    /// Write the palette as a token file like the hand-written `themes/*.rasi`
+    pub fn to_rasi(&self, name: &str) -> String {
+        let (bg, fg) = (self.background, self.foreground);
+        let rule = "═".repeat(75);
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "/* {rule}\n   {} — Generated from HyDE\n   {rule}\n\n   \
+             Generated by `wolfy --generate-theme` from the theme's rofi.theme,\n   \
+             kitty.theme and waybar.theme. Save it as themes/{}.rasi to\n   \
+             tune it by hand.\n   {rule} */\n\n* {{",
+            name.to_uppercase(),
+            ThemeTree::theme_name_to_filename(name),
+        );
+
+        let _ = writeln!(out, "    /* ─── Terminal Font ─── */");
+        token(&mut out, "term-font", &format!("\"{}\"", TERM_FONT), "");
+
+        section(&mut out, "Terminal ANSI Colors");
+        for (name, color) in TERM_COLORS.iter().zip(self.term) {
+            token(&mut out, &format!("term-{}", name), &color.to_hex(), "");
+        }
+        token(
+            &mut out,
+            "term-foreground",
+            &self.term_foreground.to_hex(),
+            "",
+        );
+        token(
+            &mut out,
+            "term-background",
+            &self.term_background.to_hex(),
+            "",
+        );
+        token(&mut out, "term-cursor", &self.term_cursor.to_hex(), "");
+
+        section(&mut out, "Surface Colors");
+        token(&mut out, "surface-deep", &bg.to_hex(), "rofi main-bg");
+        token(
+            &mut out,
+            "surface-elevated",
+            &bg.mix(&fg, 0.92).to_hex(),
+            "8% towards text",
+        );
+        token(
+            &mut out,
+            "surface-subtle",
+            &bg.mix(&fg, 0.84).to_hex(),
+            "16% towards text",
+        );
+
+        section(&mut out, "Accent Colors");
+        let [primary, secondary, tertiary] = self.accents;
+        token(
+            &mut out,
+            "accent-primary",
+            &primary.to_hex(),
+            "rofi select-bg",
+        );
+        token(
+            &mut out,
+            "accent-secondary",
+            &secondary.to_hex(),
+            "rofi main-br",
+        );
+        token(
+            &mut out,
+            "accent-tertiary",
+            &tertiary.to_hex(),
+            "rofi main-ex",
+        );
+
+        section(&mut out, "Text Hierarchy");
+        token(&mut out, "text-primary", &fg.to_hex(), "rofi main-fg");
+        token(&mut out, "text-secondary", &fg.mix(&bg, 0.85).to_hex(), "");
+        token(&mut out, "text-muted", &fg.mix(&bg, 0.6).to_hex(), "");
+        token(&mut out, "text-disabled", &fg.mix(&bg, 0.35).to_hex(), "");
+
+        // Tints are taken from the text color rather than white so they
+        // show on light themes too
+        section(&mut out, "Derived Colors (with alpha)");
+        for (name, value) in [
+            ("window-bg", "alpha(surface-deep, 13%)"),
+            ("surface-panel", "alpha(surface-deep, 91%)"),
+            ("taskpanel-bg", "alpha(surface-deep, 75%)"),
+            ("tailview-bg", "alpha(surface-deep, 94%)"),
+            ("input-bg", "alpha(text-primary, 3%)"),
+            ("hover-bg", "alpha(text-primary, 7%)"),
+            ("item-hover-bg", "alpha(text-primary, 6%)"),
+            ("accent-primary-alpha", "alpha(accent-primary, 25%)"),
+            ("text-muted-alpha", "alpha(text-muted, 50%)"),
+            ("tree-line", "alpha(text-muted, 38%)"),
+            ("chevron", "alpha(text-muted, 50%)"),
+        ] {
+            token(&mut out, name, value, "");
+        }
+
+        section(&mut out, "Clock Colors");
+        token(&mut out, "clock-text", "alpha(white, 98%)", "");
+        token(&mut out, "clock-shadow", "alpha(black, 70%)", "");
+
+        section(&mut out, "Selection Colors");
+        token(&mut out, "select-bg", "accent-primary", "");
+        token(
+            &mut out,
+            "select-fg",
+            &self.select_foreground.to_hex(),
+            "rofi select-fg",
+        );
+
+        if !self.waybar.is_empty() {
+            section(&mut out, "Waybar Colors");
+            for (name, color) in &self.waybar {
+                token(&mut out, &format!("waybar-{}", name), &color.to_hex(), "");
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Generate a Wolfy token file from a HyDE theme directory
+///
+/// The theme is named after the directory, like the theme picker does.
+pub fn generate(dir: &Path) -> Result<String, GenerateError> {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(Palette::load(dir)?.to_rasi(&name))
+}
+
+/// Colors from a kitty config: `name #rrggbb` lines
+///
+/// Other lines, including HyDE's install-path header, are ignored.
+pub fn parse_kitty(source: &str) -> Vec<(String, Color)> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            let (name, value) = (words.next()?, words.next()?);
+            if name.starts_with('#') || !value.starts_with('#') {
+                return None;
+            }
+            Some((name.to_string(), Color::from_hex(value).ok()?))
+        })
+        .collect()
+}
+
+/// Colors from waybar's GTK CSS: `@define-color name value;`
+///
+/// Values may be anything a theme color can be, and refer to earlier
+/// definitions as `@name`; ones that don't resolve are skipped.
+pub fn parse_waybar(source: &str) -> Vec<(String, Color)> {
+    let definitions: Vec<(&str, String)> = source
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("@define-color")?;
+            let (name, value) = rest.trim().split_once(char::is_whitespace)?;
+            let value = value.split(';').next()?.trim().replace('@', "");
+            Some((name, value))
+        })
+        .collect();
+
+    // One rule per definition, so a broken one doesn't take the rest along
+    let rules: String = definitions
+        .iter()
+        .map(|(name, value)| format!("* {{ {}: {}; }}\n", name, value))
+        .collect();
+    let Ok(theme) = ThemeTree::parse(&rules) else {
+        return Vec::new();
+    };
+
+    definitions
+        .iter()
+        .filter_map(|(name, _)| {
+            let value = Value::Ident(name.to_string());
+            let color = theme.resolve_color(&value);
+            if color.is_none() {
+                crate::log!("waybar.theme: skipping unresolved color `{}`", name);
+            }
+            Some((name.to_string(), color?))
+        })
+        .collect()
+}
+
+fn missing(file: &Path, name: &str) -> GenerateError {
+    GenerateError::MissingColor {
+        file: file.to_path_buf(),
+        name: name.to_string(),
+    }
+}
+
+fn opaque(color: Color) -> Color {
+    Color { a: 1.0, ..color }
+}
+
+/// Start a commented group of tokens
+fn section(out: &mut String, title: &str) {
+    let _ = writeln!(out, "\n    /* ─── {} ─── */", title);
+}
+
+/// Write one aligned `name: value;` line, with an optional trailing comment
+fn token(out: &mut String, name: &str, value: &str, comment: &str) {
+    let name = format!("{}:", name);
+    let value = format!("{};", value);
+    let _ = if comment.is_empty() {
+        writeln!(out, "    {:<24}{}", name, value)
+    } else {
+        writeln!(out, "    {:<24}{:<28}/* {} */", name, value, comment)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::schema::Severity;
+
+    fn hyde_dir(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("hyde/themes")
+            .join(name)
+    }
+
+    #[test]
+    fn test_parse_kitty_and_waybar() {
+        let kitty = parse_kitty(
+            "$HOME/.config/kitty/theme.conf|killall -SIGUSR1 kitty\n\
+             # The basic colors\n\
+             foreground              #CDD6F4\n\
+             wayland_titlebar_color system\n\
+             color0 #43465A",
+        );
+        assert_eq!(
+            kitty,
+            vec![
+                (
+                    "foreground".to_string(),
+                    Color::from_hex("#cdd6f4").unwrap()
+                ),
+                ("color0".to_string(), Color::from_hex("#43465a").unwrap()),
+            ]
+        );
+
+        let waybar = parse_waybar(
+            "$HOME/.config/waybar/theme.css|${scrDir}/wbarconfgen.sh\n\
+             @define-color bar-bg rgba(0, 0, 0, 0.1); \n\
+             @define-color main-bg #11111b;\n\
+             @define-color dim alpha(@main-bg, 50%);\n\
+             @define-color broken shade(@main-bg, 1.2);",
+        );
+        let names: Vec<_> = waybar.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["bar-bg", "main-bg", "dim"]);
+        assert_eq!(waybar[2].1.to_hex(), "#11111b80");
+    }
+
+    #[test]
+    fn test_palette_from_hyde() {
+        let palette = Palette::load(&hyde_dir("Catppuccin Mocha")).unwrap();
+        assert_eq!(palette.background.to_hex(), "#11111b");
+        assert_eq!(palette.accents[0].to_hex(), "#b4befe");
+        assert_eq!(palette.term[1].to_hex(), "#f38ba8");
+        assert_eq!(palette.term_cursor.to_hex(), "#f5e0dc");
+        assert!(palette.waybar.iter().any(|(n, _)| n == "wb-hvr-bg"));
+
+        let error = Palette::load(&hyde_dir("No Such Theme")).unwrap_err();
+        assert!(error.to_string().contains("rofi.theme"));
+    }
+
+    #[test]
+    fn test_generated_themes() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let core = ThemeTree::load(&root.join("core.rasi")).unwrap();
+        // Every token a hand-written theme defines
+        let reference = ThemeTree::load(&root.join("themes/catppuccin_mocha.rasi")).unwrap();
+
+        for entry in fs::read_dir(root.join("hyde/themes")).unwrap().flatten() {
+            let source = generate(&entry.path()).unwrap();
+            let generated = ThemeTree::parse(&source).unwrap();
+            assert!(generated.diagnostics.is_empty(), "{}", source);
+            for token in reference.globals.keys() {
+                assert!(
+                    generated.globals.contains_key(token),
+                    "{:?} lacks {}",
+                    entry.path(),
+                    token
+                );
+            }
+
+            let mut layered = ThemeTree::new();
+            layered.merge(ThemeTree::load(&root.join("core.rasi")).unwrap());
+            layered.merge(generated);
+            let errors: Vec<_> = layered
+                .validate()
+                .into_iter()
+                .filter(|i| i.severity == Severity::Error)
+                .collect();
+            assert!(errors.is_empty(), "{:?}: {:?}", entry.path(), errors);
+            assert_ne!(
+                layered.get_color_opt("element", Some("selected"), "background-color"),
+                core.get_color_opt("element", Some("selected"), "background-color")
+            );
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Lexer for rasi-like theme files using logos
 
-use crate::theme::types::Color;
+use crate::theme::ast::Comment;
+use crate::theme::types::{CalcError, Color};
 use logos::Logos;
 
 /// Token type for the theme lexer
@@ -34,6 +35,15 @@ pub enum Token {
     #[token("*")]
     Star,
 
+    #[token("+")]
+    Plus,
+
+    #[token("-")]
+    Minus,
+
+    #[token("/")]
+    Slash,
+
     #[token(".")]
     Dot,
 
@@ -43,6 +53,27 @@ pub enum Token {
     #[token("]")]
     BracketClose,
 
+    // Directives
+    #[token("@import")]
+    AtImport,
+
+    #[token("@theme")]
+    AtTheme,
+
+    // rofi-only: media queries, `@name` references and environment
+    // variables (see `theme::rofi`)
+    #[token("@media")]
+    AtMedia,
+
+    #[regex(r"@[a-zA-Z_][a-zA-Z0-9_-]*", |lex| lex.slice()[1..].to_string())]
+    AtName(String),
+
+    #[regex(r"\$\{[a-zA-Z_][a-zA-Z0-9_]*\}", |lex| {
+        let s = lex.slice();
+        s[2..s.len() - 1].to_string()
+    })]
+    EnvVar(String),
+
     // Keywords
     #[token("rgb")]
     Rgb,
@@ -50,9 +81,33 @@ pub enum Token {
     #[token("rgba")]
     Rgba,
 
+    #[token("hsl")]
+    Hsl,
+
+    #[token("hsla")]
+    Hsla,
+
+    #[token("hwb")]
+    Hwb,
+
+    #[token("oklch")]
+    Oklch,
+
     #[token("url")]
     Url,
 
+    #[token("linear-gradient")]
+    LinearGradient,
+
+    #[token("radial-gradient")]
+    RadialGradient,
+
+    #[token("var")]
+    Var,
+
+    #[token("calc")]
+    Calc,
+
     #[token("true")]
     True,
 
@@ -81,6 +136,28 @@ pub enum Token {
     #[token("mm")]
     UnitMm,
 
+    #[token("ch")]
+    UnitCh,
+
+    #[token("vw")]
+    UnitVw,
+
+    #[token("vh")]
+    UnitVh,
+
+    #[token("dp")]
+    UnitDp,
+
+    #[token("deg")]
+    UnitDeg,
+
+    // Durations, for `transition`
+    #[token("ms")]
+    UnitMs,
+
+    #[token("s", priority = 3)]
+    UnitS,
+
     // Hex colors - parsed directly to Color
     #[regex(r"#[0-9a-fA-F]{3}", |lex| parse_hex_color(lex.slice()))]
     #[regex(r"#[0-9a-fA-F]{4}", |lex| parse_hex_color(lex.slice()))]
@@ -88,6 +165,11 @@ pub enum Token {
     #[regex(r"#[0-9a-fA-F]{8}", |lex| parse_hex_color(lex.slice()))]
     HexColor(Color),
 
+    // rofi-style widget names in selectors: `#window`
+    // (names made only of 3/4/6/8 hex digits lex as colors instead)
+    #[regex(r"#[a-zA-Z_][a-zA-Z0-9_-]*", |lex| lex.slice()[1..].to_string())]
+    HashName(String),
+
     // Numbers (integer or float)
     #[regex(r"-?[0-9]+\.[0-9]+", |lex| lex.slice().parse::<f64>().ok())]
     Float(f64),
@@ -142,24 +224,115 @@ impl<'a> Iterator for Lexer<'a> {
             Err(_) => Some(Err(LexerError {
                 span: span.clone(),
                 slice: self.source[span].to_string(),
+                kind: LexerErrorKind::UnexpectedCharacter,
             })),
         }
     }
 }
 
+/// Find the comments the lexer skips, for the formatter
+///
+/// Comments are in the gaps between tokens; a comment is `trailing` if code
+/// comes before it on its line and `nested` if it is inside braces.
+pub fn comments(source: &str) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    let mut depth = 0usize;
+    let mut gap_start = 0;
+    let mut code_on_line = false;
+    let mut newlines = 0;
+
+    let mut tokens = Token::lexer(source).spanned();
+    loop {
+        let next = tokens.next();
+        let gap_end = next.as_ref().map_or(source.len(), |(_, span)| span.start);
+
+        let mut pos = gap_start;
+        while pos < gap_end {
+            let rest = &source[pos..gap_end];
+            let len = if rest.starts_with("//") {
+                let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+                line.trim_end_matches('\r').len()
+            } else if rest.starts_with("/*") {
+                rest.find("*/").map_or(rest.len(), |end| end + 2)
+            } else {
+                // Whitespace
+                if rest.starts_with('\n') {
+                    code_on_line = false;
+                    newlines += 1;
+                }
+                pos += rest.chars().next().map_or(1, char::len_utf8);
+                continue;
+            };
+            comments.push(Comment {
+                text: rest[..len].to_string(),
+                span: pos..pos + len,
+                trailing: code_on_line,
+                nested: depth > 0,
+                blank_before: newlines > 1,
+            });
+            pos += len;
+            newlines = 0;
+        }
+
+        let Some((token, span)) = next else { break };
+        match token {
+            Ok(Token::BraceOpen) => depth += 1,
+            Ok(Token::BraceClose) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        code_on_line = true;
+        newlines = 0;
+        gap_start = span.end;
+    }
+    comments
+}
+
+/// An error raised while tokenizing, or by a grammar action
 #[derive(Debug)]
 pub struct LexerError {
     pub span: std::ops::Range<usize>,
     pub slice: String,
+    pub kind: LexerErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexerErrorKind {
+    /// A character no token starts with
+    UnexpectedCharacter,
+    /// A badly typed `calc()` expression
+    Calc(CalcError),
+    /// A rofi `@media` block still open at the end of the file
+    UnclosedMedia,
+}
+
+impl LexerError {
+    /// The error message, without its position
+    pub fn message(&self) -> String {
+        match &self.kind {
+            LexerErrorKind::UnexpectedCharacter => {
+                format!("unexpected character `{}`", self.slice)
+            }
+            LexerErrorKind::Calc(e) => e.to_string(),
+            LexerErrorKind::UnclosedMedia => {
+                "`@media` block is never closed; the rest of the file is skipped".to_string()
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for LexerError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Unexpected token '{}' at position {}",
-            self.slice, self.span.start
-        )
+        match &self.kind {
+            LexerErrorKind::UnexpectedCharacter => write!(
+                f,
+                "Unexpected token '{}' at position {}",
+                self.slice, self.span.start
+            ),
+            LexerErrorKind::Calc(e) => write!(f, "{} at position {}", e, self.span.start),
+            LexerErrorKind::UnclosedMedia => {
+                write!(f, "Unclosed @media block at position {}", self.span.start)
+            }
+        }
     }
 }
 
@@ -192,6 +365,20 @@ mod tests {
         assert!(matches!(tokens[2], Token::HexColor(_)));
     }
 
+    #[test]
+    fn test_hash_names() {
+        let source = "#window #fff #accent";
+        let tokens: Vec<_> = Lexer::new(source)
+            .filter_map(|r| r.ok())
+            .map(|(_, t, _)| t)
+            .collect();
+
+        assert_eq!(tokens[0], Token::HashName("window".to_string()));
+        assert!(matches!(tokens[1], Token::HexColor(_)));
+        // Longer than a hex color, so it's a name
+        assert_eq!(tokens[2], Token::HashName("accent".to_string()));
+    }
+
     #[test]
     fn test_numbers_and_units() {
         let source = "12px 1.5em 50%";
@@ -208,6 +395,20 @@ mod tests {
         assert!(matches!(tokens[5], Token::UnitPercent));
     }
 
+    #[test]
+    fn test_gradient_tokens() {
+        let source = "linear-gradient(45deg, #000, #fff 50%) radial-gradient";
+        let tokens: Vec<_> = Lexer::new(source)
+            .filter_map(|r| r.ok())
+            .map(|(_, t, _)| t)
+            .collect();
+
+        assert_eq!(tokens[0], Token::LinearGradient);
+        assert!(matches!(tokens[2], Token::Integer(45)));
+        assert_eq!(tokens[3], Token::UnitDeg);
+        assert_eq!(tokens.last(), Some(&Token::RadialGradient));
+    }
+
     #[test]
     fn test_comments() {
         let source = r#"
@@ -226,6 +427,62 @@ mod tests {
             .all(|t| !matches!(t, Token::Ident(s) if s.contains("comment"))));
     }
 
+    #[test]
+    fn test_var_keyword() {
+        let source = "var(accent, #fff) variable";
+        let tokens: Vec<_> = Lexer::new(source)
+            .filter_map(|r| r.ok())
+            .map(|(_, t, _)| t)
+            .collect();
+
+        assert_eq!(tokens[0], Token::Var);
+        assert_eq!(tokens[1], Token::ParenOpen);
+        // Longer identifiers starting with "var" are still identifiers
+        assert_eq!(tokens[6], Token::Ident("variable".to_string()));
+    }
+
+    #[test]
+    fn test_directives() {
+        let source = r#"@import "colors" @theme "Nord";"#;
+        let tokens: Vec<_> = Lexer::new(source)
+            .filter_map(|r| r.ok())
+            .map(|(_, t, _)| t)
+            .collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::AtImport,
+                Token::String("colors".to_string()),
+                Token::AtTheme,
+                Token::String("Nord".to_string()),
+                Token::Semicolon,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rofi_tokens() {
+        let source = "@media (min-width: 100) ${ROFI_FONT} @fg @import-ed $HOME";
+        let tokens: Vec<_> = Lexer::new(source).map(|r| r.map(|(_, t, _)| t)).collect();
+
+        assert_eq!(tokens[0].as_ref().unwrap(), &Token::AtMedia);
+        assert_eq!(
+            tokens[6].as_ref().unwrap(),
+            &Token::EnvVar("ROFI_FONT".to_string())
+        );
+        assert_eq!(
+            tokens[7].as_ref().unwrap(),
+            &Token::AtName("fg".to_string())
+        );
+        assert_eq!(
+            tokens[8].as_ref().unwrap(),
+            &Token::AtName("import-ed".to_string())
+        );
+        // Only the braced form is a variable
+        assert!(tokens[9].is_err());
+    }
+
     #[test]
     fn test_strings() {
         let source = r#"font: "Segoe UI";"#;
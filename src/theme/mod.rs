@@ -1,7 +1,13 @@
 //! Theme module - parser and styling system
 
 pub mod ast;
+pub mod diagnostic;
+pub mod format;
+pub mod functions;
+pub mod hyde;
 pub mod lexer;
+pub mod rofi;
+pub mod schema;
 pub mod tree;
 pub mod types;
 
@@ -10,7 +16,8 @@ pub mod types;
 lalrpop_mod!(#[allow(clippy::all)] pub theme_parser, "/theme/theme.rs");
 
 // Public API re-exports
-pub use ast::{Property, Rule, Selector, Stylesheet, Value};
-pub use lexer::{Lexer, LexerError, Token};
-pub use tree::{ThemeError, ThemeNode, ThemeTree};
+pub use ast::{Comment, Import, PathStep, Property, Rule, Selector, Stylesheet, Value};
+pub use diagnostic::Diagnostic;
+pub use lexer::{Lexer, LexerError, LexerErrorKind, Token};
+pub use tree::{Dialect, Explanation, Origin, StyleRule, ThemeError, ThemeNode, ThemeTree};
 pub use types::{Border, Color, Distance, DistanceUnit, LayoutContext, Padding, Rect};
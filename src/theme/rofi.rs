@@ -0,0 +1,909 @@
+//! Rofi `.rasi` compatibility
+//!
+//! Stock rofi themes (and HyDE's `rofi.theme` files) load through
+//! `ThemeTree::load_rofi`, which runs them through three passes around the
+//! regular parser:
+//! 1. `strip_target_line` blanks the install path HyDE puts on the first line
+//! 2. `tokens` rewrites the token stream: `${VAR}` and `env(VAR, default)` are
+//!    substituted, `@name` becomes `var(name)`, `@media` blocks are skipped
+//!    and border / highlight styles Wolfy can't draw are dropped
+//! 3. `translate` maps rofi widgets and properties onto Wolfy's:
+//!    `entry` -> `textbox`, `element-text { highlight }` ->
+//!    `element { highlight-color }`, `font: "Name 12"` -> `font-family` and
+//!    `font-size`, `x-offset` -> `offset-x`, ...
+//!
+//! Anything dropped or approximated is reported as a warning.
+
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::theme::ast::{Property, Rule, Selector, Stylesheet, Value};
+use crate::theme::diagnostic::Diagnostic;
+use crate::theme::lexer::{Lexer, LexerError, LexerErrorKind, Token};
+use crate::theme::types::Distance;
+
+/// A token with its byte range, as fed to the parser
+pub type Spanned = Result<(usize, Token, usize), LexerError>;
+
+/// Rofi widgets styled through a differently named Wolfy widget
+const RENAMED_WIDGETS: &[(&str, &str)] = &[
+    ("entry", "textbox"),
+    // rofi's `textbox` is the message text; Wolfy's is the input
+    ("textbox", "message"),
+    ("element-text", "element"),
+    ("element-icon", "element"),
+    ("scrollbar", "listview"),
+];
+
+/// Rofi child widgets merged into their parent, and the properties that
+/// carry over (everything else would restyle the whole parent)
+const MERGED_WIDGETS: &[(&str, &[&str])] = &[
+    ("element-text", &["text-color", "highlight", "font"]),
+    ("element-icon", &["size"]),
+    (
+        "scrollbar",
+        &["handle-color", "handle-width", "background-color"],
+    ),
+];
+
+/// Rofi widgets with no Wolfy counterpart; their rules are skipped
+const UNSUPPORTED_WIDGETS: &[&str] = &[
+    "configuration",
+    "prompt",
+    "textbox-prompt-colon",
+    "case-indicator",
+    "num-rows",
+    "num-filtered-rows",
+    "textbox-num-sep",
+    "mode-switcher",
+    "button",
+    "sidebar",
+    "overlay",
+    "element-index",
+    "error-message",
+    "icon",
+];
+
+/// Properties renamed on the way: (rofi widget, rofi property, Wolfy property)
+const RENAMED_PROPERTIES: &[(&str, &str, &str)] = &[
+    ("element-icon", "size", "icon-size"),
+    ("scrollbar", "handle-color", "scrollbar-color"),
+    ("scrollbar", "handle-width", "scrollbar-width"),
+    ("scrollbar", "background-color", "scrollbar-track-color"),
+];
+
+/// Rofi properties Wolfy has no equivalent for
+const UNSUPPORTED_PROPERTIES: &[&str] = &[
+    "cursor",
+    "fullscreen",
+    "transparency",
+    "columns",
+    "cycle",
+    "dynamic",
+    "fixed-height",
+    "fixed-columns",
+    "scrollbar",
+    "reverse",
+    "require-input",
+    "vertical-align",
+    "horizontal-align",
+    "placeholder",
+    "blink",
+    "str",
+    "content",
+    "tab-stops",
+];
+
+/// Rofi's numbered window locations, 0 to 8, as `location` names
+const LOCATIONS: [&str; 9] = [
+    "center",
+    "north-west",
+    "north",
+    "north-east",
+    "east",
+    "south-east",
+    "south",
+    "south-west",
+    "west",
+];
+
+/// Border line styles; borders are always drawn solid
+const LINE_STYLES: &[&str] = &["solid", "dash"];
+
+/// Text styles a rofi `highlight` may combine with its color
+const HIGHLIGHT_STYLES: &[&str] = &["bold", "italic", "underline", "strikethrough", "none"];
+
+/// Pango style and weight words that may end a rofi font name
+const FONT_STYLES: &[&str] = &[
+    "thin",
+    "ultra-light",
+    "extra-light",
+    "light",
+    "semi-light",
+    "book",
+    "regular",
+    "medium",
+    "semi-bold",
+    "demi-bold",
+    "bold",
+    "ultra-bold",
+    "extra-bold",
+    "heavy",
+    "italic",
+    "oblique",
+    "condensed",
+    "semi-condensed",
+    "expanded",
+];
+
+/// Pango sizes are in points, Wolfy's in pixels at 96 DPI
+const POINTS_TO_PIXELS: f64 = 96.0 / 72.0;
+
+/// Blank out the install path HyDE writes on the first line of its theme
+/// files (`$HOME/.config/rofi/theme.rasi`); offsets are kept intact
+pub fn strip_target_line(source: &str) -> Cow<'_, str> {
+    let first = source.lines().next().unwrap_or("");
+    let is_path = first.starts_with(['$', '~', '/']) && !first.contains(['{', ';']);
+    if !is_path {
+        return Cow::Borrowed(source);
+    }
+    let blank = " ".repeat(first.trim_end_matches('\r').len());
+    Cow::Owned(format!("{}{}", blank, &source[blank.len()..]))
+}
+
+/// Lex rofi source into tokens the regular grammar understands
+///
+/// `env` looks up environment variables (`std::env::var` outside tests).
+pub fn tokens(
+    source: &str,
+    file: Option<&Path>,
+    env: &dyn Fn(&str) -> Option<String>,
+    warnings: &mut Vec<Diagnostic>,
+) -> Vec<Spanned> {
+    let mut warn = |span: Range<usize>, message: String| {
+        warnings.push(Diagnostic::new(source, span, message, file));
+    };
+
+    let mut out: Vec<Spanned> = Vec::new();
+    let mut input = Lexer::new(source).peekable();
+    // Property whose value is being lexed, to spot highlight styles
+    let mut property: Option<String> = None;
+    let mut depth = 0usize;
+
+    while let Some(token) = input.next() {
+        let Ok((start, token, end)) = token else {
+            out.push(token);
+            continue;
+        };
+        match token {
+            Token::AtMedia => {
+                let mut block_depth = 0usize;
+                let mut header_end = end;
+                loop {
+                    match input.peek() {
+                        // A stray `}` belongs to whatever encloses the query
+                        Some(Ok((_, Token::BraceClose, _))) if block_depth == 0 => break,
+                        Some(_) => {}
+                        None if block_depth == 0 => break,
+                        None => {
+                            out.push(Err(LexerError {
+                                span: start..end,
+                                slice: "@media".to_string(),
+                                kind: LexerErrorKind::UnclosedMedia,
+                            }));
+                            break;
+                        }
+                    }
+                    match input.next() {
+                        Some(Ok((s, Token::BraceOpen, _))) => {
+                            if block_depth == 0 {
+                                header_end = s;
+                            }
+                            block_depth += 1;
+                        }
+                        Some(Ok((_, Token::BraceClose, _))) => {
+                            block_depth -= 1;
+                            if block_depth == 0 {
+                                break;
+                            }
+                        }
+                        // A query without a block ends at its `;`
+                        Some(Ok((s, Token::Semicolon, _))) if block_depth == 0 => {
+                            header_end = s;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                if block_depth == 0 {
+                    warn(
+                        start..header_end,
+                        "`@media` queries are not supported; the block is skipped".to_string(),
+                    );
+                }
+            }
+            Token::AtName(name) => {
+                out.push(Ok((start, Token::Var, end)));
+                out.push(Ok((start, Token::ParenOpen, end)));
+                out.push(Ok((start, Token::Ident(name), end)));
+                out.push(Ok((start, Token::ParenClose, end)));
+            }
+            Token::EnvVar(name) => match env(&name) {
+                Some(value) => out.extend(lex_substitute(&value, start..end)),
+                None => {
+                    warn(
+                        start..end,
+                        format!(
+                            "environment variable `{}` is not set; using `inherit`",
+                            name
+                        ),
+                    );
+                    out.push(Ok((start, Token::Inherit, end)));
+                }
+            },
+            Token::Ident(ref name) if name == "env" && next_is(&mut input, &Token::ParenOpen) => {
+                input.next();
+                let var = match input.next() {
+                    Some(Ok((_, Token::Ident(var), _))) => var,
+                    _ => String::new(),
+                };
+                // Fallback tokens up to the matching `)`
+                let mut fallback = Vec::new();
+                let mut paren_depth = 0;
+                let mut call_end = end;
+                for token in input.by_ref() {
+                    if let Ok((_, ref t, e)) = token {
+                        call_end = e;
+                        match t {
+                            Token::ParenOpen => paren_depth += 1,
+                            Token::ParenClose if paren_depth == 0 => break,
+                            Token::ParenClose => paren_depth -= 1,
+                            Token::Comma if paren_depth == 0 && fallback.is_empty() => continue,
+                            _ => {}
+                        }
+                    }
+                    fallback.push(token);
+                }
+
+                match env(&var) {
+                    Some(value) => out.extend(lex_substitute(&value, start..call_end)),
+                    None if !fallback.is_empty() => out.extend(fallback),
+                    None => {
+                        warn(
+                            start..call_end,
+                            format!("environment variable `{}` is not set; using `inherit`", var),
+                        );
+                        out.push(Ok((start, Token::Inherit, call_end)));
+                    }
+                }
+            }
+            // `border: 2px dash`: the style follows a width
+            Token::Ident(ref name)
+                if LINE_STYLES.contains(&name.as_str())
+                    && matches!(
+                        out.last(),
+                        Some(Ok((
+                            _,
+                            Token::UnitPx
+                                | Token::UnitEm
+                                | Token::UnitPercent
+                                | Token::UnitMm
+                                | Token::UnitCh
+                                | Token::UnitVw
+                                | Token::UnitVh
+                                | Token::UnitDp
+                                | Token::Integer(_)
+                                | Token::Float(_),
+                            _
+                        )))
+                    ) =>
+            {
+                if name == "dash" {
+                    warn(
+                        start..end,
+                        "dashed borders are not supported; drawn solid".to_string(),
+                    );
+                }
+            }
+            // `highlight: bold #ff0000`: keep the color; a lone style is left
+            // for `translate` to report
+            Token::Ident(ref name)
+                if property.as_deref() == Some("highlight")
+                    && HIGHLIGHT_STYLES.contains(&name.as_str())
+                    && !next_is(&mut input, &Token::Semicolon) =>
+            {
+                warn(
+                    start..end,
+                    format!(
+                        "highlight style `{}` is not supported; only its color is used",
+                        name
+                    ),
+                );
+            }
+            token => {
+                match &token {
+                    Token::BraceOpen => depth += 1,
+                    Token::BraceClose => depth = depth.saturating_sub(1),
+                    Token::Ident(name) if depth > 0 && next_is(&mut input, &Token::Colon) => {
+                        property = Some(name.clone());
+                    }
+                    _ => {}
+                }
+                if matches!(
+                    token,
+                    Token::Semicolon | Token::BraceOpen | Token::BraceClose
+                ) {
+                    property = None;
+                }
+                out.push(Ok((start, token, end)));
+            }
+        }
+    }
+
+    out
+}
+
+fn next_is(input: &mut Peekable<Lexer<'_>>, want: &Token) -> bool {
+    matches!(input.peek(), Some(Ok((_, token, _))) if token == want)
+}
+
+/// Lex a substituted value, every token spanning the reference it replaces
+fn lex_substitute(value: &str, span: Range<usize>) -> Vec<Spanned> {
+    Lexer::new(value)
+        .map(|token| match token {
+            Ok((_, token, _)) => Ok((span.start, token, span.end)),
+            Err(e) => Err(LexerError {
+                span: span.clone(),
+                ..e
+            }),
+        })
+        .collect()
+}
+
+/// Map rofi widgets and properties in a parsed stylesheet onto Wolfy's
+pub fn translate(
+    stylesheet: Stylesheet,
+    source: &str,
+    file: Option<&Path>,
+    warnings: &mut Vec<Diagnostic>,
+) -> Stylesheet {
+    let mut warn = |span: &Range<usize>, message: String| {
+        warnings.push(Diagnostic::new(source, span.clone(), message, file));
+    };
+
+    let mut rules = Vec::new();
+    for rule in stylesheet.rules {
+        // Selectors are grouped by the rofi widget they style, which decides
+        // how their properties translate
+        let mut groups: Vec<(String, Vec<Selector>)> = Vec::new();
+        for selector in &rule.selectors {
+            match translate_selector(selector) {
+                Ok(translated) => {
+                    let widget = match selector.parts().last() {
+                        Some(Selector::Element { name, .. }) => name.clone(),
+                        _ => "*".to_string(),
+                    };
+                    match groups.iter_mut().find(|(w, _)| *w == widget) {
+                        Some((_, selectors)) => selectors.push(translated),
+                        None => groups.push((widget, vec![translated])),
+                    }
+                }
+                Err(name) if name == "configuration" => warn(
+                    &rule.span,
+                    "rofi `configuration` is not part of a theme; skipped".to_string(),
+                ),
+                Err(name) => warn(
+                    &rule.span,
+                    format!("rofi widget `{}` has no Wolfy equivalent; skipped", name),
+                ),
+            }
+        }
+
+        for (widget, selectors) in groups {
+            let properties = rule
+                .properties
+                .iter()
+                .flat_map(|p| translate_property(&widget, p, &mut warn))
+                .collect();
+            rules.push(Rule {
+                selectors,
+                properties,
+                span: rule.span.clone(),
+            });
+        }
+    }
+
+    Stylesheet {
+        imports: stylesheet.imports,
+        rules,
+        comments: stylesheet.comments,
+    }
+}
+
+/// The Wolfy widget styling what a rofi widget name refers to
+fn wolfy_widget(name: &str) -> &str {
+    RENAMED_WIDGETS
+        .iter()
+        .find(|(rofi, _)| *rofi == name)
+        .map_or(name, |(_, wolfy)| wolfy)
+}
+
+/// Rename the parts of a selector; Err names a widget Wolfy doesn't have
+///
+/// A child merged into its parent folds into the parent's part:
+/// `element selected element-text` is `element.selected`.
+fn translate_selector(selector: &Selector) -> Result<Selector, String> {
+    let mut parts: Vec<Selector> = Vec::new();
+    for part in selector.parts() {
+        let Selector::Element { name, states } = part else {
+            parts.push(part.clone());
+            continue;
+        };
+        if UNSUPPORTED_WIDGETS.contains(&name.as_str()) {
+            return Err(name.clone());
+        }
+
+        let wolfy = wolfy_widget(name);
+        if let Some(Selector::Element {
+            name: parent,
+            states: parent_states,
+        }) = parts.last_mut()
+        {
+            if wolfy != name && parent == wolfy {
+                parent_states.extend(states.iter().cloned());
+                continue;
+            }
+        }
+        parts.push(Selector::Element {
+            name: wolfy.to_string(),
+            states: states.clone(),
+        });
+    }
+
+    Ok(if parts.len() == 1 {
+        parts.remove(0)
+    } else {
+        Selector::Descendant(parts)
+    })
+}
+
+/// Translate one property set on a rofi widget; may expand to several
+fn translate_property(
+    widget: &str,
+    property: &Property,
+    warn: &mut impl FnMut(&Range<usize>, String),
+) -> Vec<Property> {
+    let name = property.name.as_str();
+    let with = |name: &str, value: Value| Property {
+        name: name.to_string(),
+        value,
+        span: property.span.clone(),
+    };
+
+    if let Some((_, carried)) = MERGED_WIDGETS.iter().find(|(w, _)| *w == widget) {
+        // Inheriting from the widget it's merged into changes nothing
+        if matches!(&property.value, Value::Ident(s) if s == "inherit") {
+            return Vec::new();
+        }
+        if !carried.contains(&name) {
+            warn(
+                &property.span,
+                format!(
+                    "`{}` on rofi's `{}` has no Wolfy equivalent; ignored",
+                    name, widget
+                ),
+            );
+            return Vec::new();
+        }
+    }
+    if UNSUPPORTED_PROPERTIES.contains(&name) {
+        warn(
+            &property.span,
+            format!("rofi property `{}` is not supported; ignored", name),
+        );
+        return Vec::new();
+    }
+    if let Some((_, _, renamed)) = RENAMED_PROPERTIES
+        .iter()
+        .find(|(w, rofi, _)| *w == widget && *rofi == name)
+    {
+        return vec![with(renamed, property.value.clone())];
+    }
+
+    match (name, &property.value) {
+        ("font", Value::String(font)) => {
+            let mut words: Vec<&str> = font.split_whitespace().collect();
+            let size = words.last().and_then(|w| w.parse::<f64>().ok());
+            if size.is_some() {
+                words.pop();
+            }
+            let mut styles = Vec::new();
+            while words.len() > 1
+                && words
+                    .last()
+                    .is_some_and(|w| FONT_STYLES.contains(&w.to_lowercase().as_str()))
+            {
+                styles.extend(words.pop());
+            }
+            if !styles.is_empty() {
+                styles.reverse();
+                warn(
+                    &property.span,
+                    format!(
+                        "font style `{}` is not supported; using the regular face",
+                        styles.join(" ")
+                    ),
+                );
+            }
+
+            let mut properties = Vec::new();
+            if !words.is_empty() {
+                properties.push(with("font-family", Value::String(words.join(" "))));
+            }
+            if let Some(size) = size {
+                properties.push(with("font-size", Value::Number(size * POINTS_TO_PIXELS)));
+            }
+            properties
+        }
+        ("border", Value::Padding2(top, right)) => {
+            warn(
+                &property.span,
+                "per-side borders are not supported; using the widest side".to_string(),
+            );
+            let widest = if top.value >= right.value { top } else { right };
+            vec![with("border-width", Value::Distance(widest.clone()))]
+        }
+        ("border", Value::Padding4(top, right, bottom, left)) => {
+            warn(
+                &property.span,
+                "per-side borders are not supported; using the widest side".to_string(),
+            );
+            let widest = [top, right, bottom, left]
+                .into_iter()
+                .max_by(|a, b| a.value.total_cmp(&b.value))
+                .unwrap_or(top);
+            vec![with("border-width", Value::Distance(widest.clone()))]
+        }
+        ("border", value) => vec![with("border-width", value.clone())],
+        // Wolfy reads a single padding, or one per side
+        ("padding", Value::Padding2(v, h)) => sides(PADDING_SIDES, [v, h, v, h], &with),
+        ("padding", Value::Padding4(t, r, b, l)) => sides(PADDING_SIDES, [t, r, b, l], &with),
+        // CSS corner order: top-left, top-right, bottom-right, bottom-left
+        ("border-radius", Value::Padding2(a, b)) => sides(RADIUS_CORNERS, [a, b, a, b], &with),
+        ("border-radius", Value::Padding4(tl, tr, br, bl)) => {
+            sides(RADIUS_CORNERS, [tl, tr, br, bl], &with)
+        }
+        ("highlight", Value::Ident(style)) if HIGHLIGHT_STYLES.contains(&style.as_str()) => {
+            warn(
+                &property.span,
+                format!(
+                    "highlight style `{}` is not supported; only highlight colors are",
+                    style
+                ),
+            );
+            Vec::new()
+        }
+        ("highlight", value) => vec![with("highlight-color", value.clone())],
+        // `location: 2` is rofi's old numbered form of `location: north`
+        ("location", Value::Number(n)) => match LOCATIONS.get(*n as usize) {
+            Some(location) if *n >= 0.0 && n.fract() == 0.0 => {
+                vec![with("location", Value::Ident(location.to_string()))]
+            }
+            _ => {
+                warn(
+                    &property.span,
+                    format!("rofi location `{}` is out of range; ignored", n),
+                );
+                Vec::new()
+            }
+        },
+        ("x-offset", value) => vec![with("offset-x", value.clone())],
+        ("y-offset", value) => vec![with("offset-y", value.clone())],
+        ("children", Value::Array(names)) => {
+            let own = wolfy_widget(widget);
+            let mut children = Vec::new();
+            for child in names {
+                if UNSUPPORTED_WIDGETS.contains(&child.as_str()) {
+                    warn(
+                        &property.span,
+                        format!("rofi widget `{}` has no Wolfy equivalent; left out", child),
+                    );
+                    continue;
+                }
+                // `element { children: [ element-icon, element-text ] }`:
+                // the element draws those itself
+                let child = wolfy_widget(child);
+                if child != own {
+                    children.push(child.to_string());
+                }
+            }
+            if children.is_empty() && !names.is_empty() {
+                Vec::new()
+            } else {
+                vec![with("children", Value::Array(children))]
+            }
+        }
+        _ => vec![property.clone()],
+    }
+}
+
+const PADDING_SIDES: [&str; 4] = [
+    "padding-top",
+    "padding-right",
+    "padding-bottom",
+    "padding-left",
+];
+
+const RADIUS_CORNERS: [&str; 4] = [
+    "border-top-left-radius",
+    "border-top-right-radius",
+    "border-bottom-right-radius",
+    "border-bottom-left-radius",
+];
+
+/// Split a shorthand into one property per side or corner
+fn sides(
+    names: [&str; 4],
+    values: [&Distance; 4],
+    with: &impl Fn(&str, Value) -> Property,
+) -> Vec<Property> {
+    names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| with(name, Value::Distance(value.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::tree::ThemeTree;
+    use crate::theme::types::Color;
+    use crate::widget::{Anchor, LayoutProps};
+
+    fn rofi_tokens(source: &str, env: &dyn Fn(&str) -> Option<String>) -> Vec<Token> {
+        tokens(source, None, env, &mut Vec::new())
+            .into_iter()
+            .map(|t| t.unwrap().1)
+            .collect()
+    }
+
+    #[test]
+    fn test_environment_variables() {
+        let env = |name: &str| (name == "FONT_SIZE").then(|| "12px".to_string());
+        let ident = |s: &str| Token::Ident(s.to_string());
+
+        assert_eq!(
+            rofi_tokens("${FONT_SIZE}", &env),
+            vec![Token::Integer(12), Token::UnitPx]
+        );
+        assert_eq!(
+            rofi_tokens("env(FONT_SIZE, 1px)", &env),
+            vec![Token::Integer(12), Token::UnitPx]
+        );
+        assert_eq!(
+            rofi_tokens("env(MISSING, lighten(fg, 10%))", &env),
+            vec![
+                ident("lighten"),
+                Token::ParenOpen,
+                ident("fg"),
+                Token::Comma,
+                Token::Integer(10),
+                Token::UnitPercent,
+                Token::ParenClose,
+            ]
+        );
+
+        let mut warnings = Vec::new();
+        let substituted = tokens("a: ${MISSING};", None, &env, &mut warnings);
+        assert!(matches!(substituted[2], Ok((3, Token::Inherit, 13))));
+        assert!(warnings[0].message.contains("`MISSING` is not set"));
+    }
+
+    #[test]
+    fn test_token_rewrites() {
+        let none = |_: &str| None;
+        let mut warnings = Vec::new();
+        let source = "@media (min-width: 800) { window { width: 50%; } }\n\
+                      window { border: 2px dash; text-color: @fg; }\n\
+                      element-text { highlight: bold italic #ff0000; }";
+        let tokens: Vec<Token> = tokens(source, None, &none, &mut warnings)
+            .into_iter()
+            .map(|t| t.unwrap().1)
+            .collect();
+
+        // The media block is gone, the line style dropped, `@fg` a `var()`
+        assert_eq!(tokens[0], Token::Ident("window".to_string()));
+        assert_eq!(&tokens[5..7], &[Token::UnitPx, Token::Semicolon]);
+        assert_eq!(&tokens[9..11], &[Token::Var, Token::ParenOpen]);
+        assert!(tokens.contains(&Token::HexColor(Color::RED)));
+        assert!(!tokens.contains(&Token::Ident("bold".to_string())));
+
+        let messages: Vec<_> = warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(messages.len(), 4, "{:?}", messages);
+        assert!(messages[0].starts_with("`@media`"));
+        assert_eq!(warnings[0].line, 1);
+        assert!(messages[1].starts_with("dashed borders"));
+        assert!(messages[2].contains("`bold`"));
+    }
+
+    #[test]
+    fn test_media_skip_stops_at_its_end() {
+        let none = |_: &str| None;
+        let lex = |source: &str, warnings: &mut Vec<Diagnostic>| -> Vec<Spanned> {
+            tokens(source, None, &none, warnings)
+        };
+
+        // A query without a block ends at its `;`, and a stray `}` is left
+        // for the parser, so the rules after it still load
+        let mut warnings = Vec::new();
+        let tokens: Vec<Token> = lex("@media x; }\nwindow { width: 50%; }", &mut warnings)
+            .into_iter()
+            .map(|t| t.unwrap().1)
+            .collect();
+        assert_eq!(tokens[0], Token::BraceClose);
+        assert_eq!(tokens[1], Token::Ident("window".to_string()));
+        assert_eq!(warnings.len(), 1);
+
+        // An unterminated block is an error, not just a skipped query
+        let mut warnings = Vec::new();
+        let tokens = lex("window { width: 50%; }\n@media x { window {", &mut warnings);
+        let error = tokens.last().unwrap().as_ref().unwrap_err();
+        assert_eq!(error.kind, LexerErrorKind::UnclosedMedia);
+        assert_eq!(error.span, 23..29);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_target_line() {
+        let source = "$HOME/.config/rofi/theme.rasi\r\n* { a: #fff; }";
+        let stripped = strip_target_line(source);
+        assert_eq!(stripped.len(), source.len());
+        assert!(stripped.trim_start().starts_with('*'));
+        assert_eq!(strip_target_line("* { }"), "* { }");
+    }
+
+    #[test]
+    fn test_translate_widgets() {
+        let theme = ThemeTree::parse_rofi(
+            r#"
+            * { font: "JetBrainsMono Nerd Font Bold 12"; fg: #cdd6f4; }
+            mainbox { children: [ inputbar, message, listview, mode-switcher ]; }
+            inputbar { children: [ prompt, entry ]; border: 0px 0px 2px 0px; }
+            entry { placeholder: "Search"; text-color: @fg; }
+            element {
+                children: [ element-icon, element-text ];
+                border-radius: 8px 0px;
+                padding: 4px 8px 6px;
+            }
+            element-icon { size: 24px; background-color: transparent; }
+            element selected.normal element-text { text-color: #ff0000; }
+            element-text { highlight: underline #00ff00; text-color: inherit; }
+            scrollbar { handle-color: @fg; }
+            prompt { text-color: red; }
+            configuration { show-icons: true; }
+            "#,
+        )
+        .unwrap();
+        assert!(theme.diagnostics.is_empty(), "{:?}", theme.diagnostics);
+
+        assert_eq!(
+            theme.get_string("window", None, "font-family", ""),
+            "JetBrainsMono Nerd Font"
+        );
+        assert_eq!(theme.get_number("element", None, "font-size", 0.0), 16.0);
+        assert_eq!(
+            theme.get_children("mainbox"),
+            vec!["inputbar", "message", "listview"]
+        );
+        assert_eq!(theme.get_children("inputbar"), vec!["textbox"]);
+        assert!(theme.get_children("element").is_empty());
+        assert_eq!(theme.get_number("inputbar", None, "border-width", 0.0), 2.0);
+        assert_eq!(
+            theme.get_number("element", None, "border-top-right-radius", 1.0),
+            0.0
+        );
+        assert_eq!(theme.get_number("element", None, "padding-left", 0.0), 8.0);
+        assert_eq!(
+            theme.get_number("element", None, "padding-bottom", 0.0),
+            6.0
+        );
+
+        let fg = Color::from_hex("#cdd6f4").unwrap();
+        assert_eq!(
+            theme.get_color("textbox", None, "text-color", Color::RED),
+            fg
+        );
+        assert_eq!(
+            theme.get_color("listview", None, "scrollbar-color", Color::RED),
+            fg
+        );
+        assert_eq!(theme.get_number("element", None, "icon-size", 0.0), 24.0);
+        assert_eq!(
+            theme.get_color("element", None, "highlight-color", Color::RED),
+            Color::GREEN
+        );
+        // element-text's `inherit` doesn't reset the element's text color
+        assert_eq!(
+            theme.get_color("element", Some("selected"), "text-color", Color::BLACK),
+            Color::RED
+        );
+        assert!(theme
+            .get_value("element", None, "background-color")
+            .is_none());
+        assert!(!theme.widgets.contains_key("prompt"));
+
+        let messages: Vec<_> = theme.warnings.iter().map(|w| w.message.as_str()).collect();
+        for expected in [
+            "font style `Bold` is not supported",
+            "rofi widget `mode-switcher` has no Wolfy equivalent; left out",
+            "rofi widget `prompt` has no Wolfy equivalent; left out",
+            "per-side borders are not supported",
+            "rofi property `placeholder` is not supported",
+            "`background-color` on rofi's `element-icon` has no Wolfy equivalent",
+            "highlight style `underline` is not supported",
+            "rofi widget `prompt` has no Wolfy equivalent; skipped",
+            "rofi `configuration` is not part of a theme",
+        ] {
+            assert!(
+                messages.iter().any(|m| m.starts_with(expected)),
+                "missing {:?} in {:#?}",
+                expected,
+                messages
+            );
+        }
+        assert_eq!(messages.len(), 9, "{:#?}", messages);
+    }
+
+    #[test]
+    fn test_translate_margin_and_location() {
+        let theme = ThemeTree::parse_rofi(
+            r#"
+            window { location: north; anchor: north; y-offset: 40px; x-offset: -8px; }
+            listview { margin: 4px 8px; }
+            element { margin: 2px; }
+            message { location: 3; }
+            "#,
+        )
+        .unwrap();
+        assert!(theme.warnings.is_empty(), "{:#?}", theme.warnings);
+
+        let layout = |path| LayoutProps::from_theme(&theme, path, &LayoutProps::default());
+        let placement = layout("window").placement;
+        assert_eq!(placement.location, Anchor::North);
+        assert_eq!(placement.anchor, Some(Anchor::North));
+        assert_eq!(placement.offset, (-8.0, 40.0));
+        assert_eq!(
+            theme.get_string("message", None, "location", ""),
+            "north-east"
+        );
+
+        assert_eq!(layout("listview").margin, (4.0, 8.0, 4.0, 8.0));
+        assert_eq!(layout("element").margin, (2.0, 2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn test_hyde_rofi_themes() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("hyde/themes");
+        let mut count = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path().join("rofi.theme");
+            if !path.is_file() {
+                continue;
+            }
+            let theme = ThemeTree::load_rofi(&path).unwrap();
+            assert!(theme.diagnostics.is_empty(), "{:?}", theme.diagnostics);
+            assert!(theme.warnings.is_empty(), "{:?}", theme.warnings);
+            assert!(theme.validate().is_empty(), "{}", path.display());
+            assert!(theme
+                .globals
+                .get("main-bg")
+                .and_then(|v| v.as_color())
+                .is_some());
+            count += 1;
+        }
+        assert!(count > 0);
+    }
+}
@@ -0,0 +1,858 @@
+//! Schema of the widgets and properties Wolfy reads from themes
+//!
+//! Every property read by a `*Style::from_theme` (or `ThemeLayout`, the task
+//! panel, terminal, ...) is listed here with the value type its getter
+//! accepts. `ThemeTree::validate` checks a theme against it, so a misspelled
+//! property is reported instead of silently falling back to a default.
+//!
+//! When adding a property to a style loader, add it here too.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::theme::ast::{Selector, Value};
+use crate::theme::tree::ThemeTree;
+use crate::theme::types::Paint;
+
+use PropertyType as P;
+
+/// Expected type of a property value, matching the `ThemeTree` getter used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyType {
+    /// `get_color`: hex, rgb(a), named color, token or color function
+    Color,
+    /// `get_paint`: a color or a `linear-gradient()` / `radial-gradient()`
+    Paint,
+    /// `get_shadow`: `x y [blur [spread]] color`, or `none`
+    Shadow,
+    /// `get_number`: plain number or distance (unit ignored)
+    Number,
+    /// `get_distance`: distance or plain number (px)
+    Distance,
+    /// `get_padding`: 1, 2 or 4 distances
+    Padding,
+    /// `get_string`: string or identifier
+    String,
+    /// `get_bool`
+    Boolean,
+    /// `get_image`: `url(...)`
+    Image,
+    /// `get_children`: `[ "a", "b" ]`
+    Children,
+    /// `get_orientation`: horizontal / vertical
+    Orientation,
+    /// `get_transitions`: `background-color 120ms ease-out, ...`
+    Transition,
+}
+
+impl PropertyType {
+    /// Human-readable name for messages ("a color", "a number", ...)
+    pub fn describe(&self) -> &'static str {
+        match self {
+            PropertyType::Color => "a color",
+            PropertyType::Paint => "a color or gradient",
+            PropertyType::Shadow => "a shadow",
+            PropertyType::Number => "a number",
+            PropertyType::Distance => "a distance",
+            PropertyType::Padding => "a padding",
+            PropertyType::String => "a string",
+            PropertyType::Boolean => "a boolean",
+            PropertyType::Image => "an image url()",
+            PropertyType::Children => "a children array",
+            PropertyType::Orientation => "horizontal or vertical",
+            PropertyType::Transition => "a transition list",
+        }
+    }
+
+    /// Whether a resolved value is accepted by this type's getter
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            PropertyType::Color => value.as_color().is_some(),
+            PropertyType::Paint => {
+                value.as_color().is_some() || matches!(value, Value::Gradient(..))
+            }
+            PropertyType::Shadow => {
+                matches!(value, Value::Shadow(_)) || matches!(value, Value::Ident(s) if s == "none")
+            }
+            PropertyType::Number => value.as_number().is_some(),
+            PropertyType::Distance => value.as_distance().is_some(),
+            PropertyType::Padding => value.as_padding().is_some(),
+            PropertyType::String => value.as_string().is_some(),
+            PropertyType::Boolean => value.as_bool().is_some(),
+            PropertyType::Image => value.as_image().is_some(),
+            PropertyType::Children => value.as_array().is_some(),
+            PropertyType::Orientation => value.as_orientation().is_some(),
+            PropertyType::Transition => {
+                matches!(value, Value::Transition(_))
+                    || matches!(value, Value::Ident(s) if s == "none")
+            }
+        }
+    }
+}
+
+/// A known property and its expected type
+#[derive(Debug, Clone, Copy)]
+pub struct PropertySchema {
+    pub name: &'static str,
+    pub ty: PropertyType,
+}
+
+/// A known widget and the properties read for it
+#[derive(Debug, Clone, Copy)]
+pub struct WidgetSchema {
+    pub name: &'static str,
+    pub properties: &'static [PropertySchema],
+}
+
+impl WidgetSchema {
+    /// Look up a property, including the layout properties every widget accepts
+    pub fn property(&self, name: &str) -> Option<&'static PropertySchema> {
+        self.properties
+            .iter()
+            .chain(LAYOUT_PROPERTIES)
+            .find(|p| p.name == name)
+    }
+}
+
+const fn prop(name: &'static str, ty: PropertyType) -> PropertySchema {
+    PropertySchema { name, ty }
+}
+
+/// Widget states selectable as `widget.state`, or combined as
+/// `widget.selected.urgent`
+pub const STATES: &[&str] = &[
+    "normal",
+    "focused",
+    "selected",
+    "hover",
+    "pressed",
+    "disabled",
+    "urgent",
+    "active",
+    "alternate",
+];
+
+/// Classes an ancestor in a descendant selector may also carry: the mode on
+/// `window`, as in `window.launcher textbox`
+pub const MODE_CLASSES: &[&str] = &["launcher", "theme-picker", "wallpaper-picker", "tail-view"];
+
+/// Layout and box properties read for any widget in the layout tree
+/// (`Container`, `Panel`, `ThemeLayout`)
+pub const LAYOUT_PROPERTIES: &[PropertySchema] = &[
+    prop("children", P::Children),
+    prop("orientation", P::Orientation),
+    prop("expand", P::Boolean),
+    prop("spacing", P::Distance),
+    prop("width", P::Distance),
+    prop("height", P::Distance),
+    prop("padding", P::Number),
+    prop("padding-top", P::Number),
+    prop("padding-right", P::Number),
+    prop("padding-bottom", P::Number),
+    prop("padding-left", P::Number),
+    // Flexbox: read by the parent container when laying out its children
+    prop("flex-grow", P::Number),
+    prop("flex-shrink", P::Number),
+    prop("flex-basis", P::Distance),
+    prop("min-width", P::Distance),
+    prop("max-width", P::Distance),
+    prop("min-height", P::Distance),
+    prop("max-height", P::Distance),
+    prop("margin", P::Padding),
+    prop("margin-top", P::Number),
+    prop("margin-right", P::Number),
+    prop("margin-bottom", P::Number),
+    prop("margin-left", P::Number),
+    prop("align-self", P::String),
+    prop("justify-content", P::String),
+    prop("align-items", P::String),
+    prop("flex-wrap", P::String),
+    // Absolute positioning, for overlays like tooltips and badges
+    prop("position", P::String),
+    prop("anchor-to", P::String),
+    prop("location", P::String),
+    prop("anchor", P::String),
+    prop("offset", P::Padding),
+    prop("offset-x", P::Number),
+    prop("offset-y", P::Number),
+    prop("z-index", P::Number),
+    prop("background-color", P::Paint),
+    prop("background-image", P::Image),
+    prop("border-color", P::Paint),
+    prop("border-width", P::Number),
+    prop("border-radius", P::Number),
+    prop("border-top-left-radius", P::Number),
+    prop("border-top-right-radius", P::Number),
+    prop("border-bottom-right-radius", P::Number),
+    prop("border-bottom-left-radius", P::Number),
+    prop("box-shadow", P::Shadow),
+    // Panels: blur radius for the background image under the background color
+    prop("backdrop-blur", P::Number),
+    // Elements and grid tiles animate their colors between states
+    prop("transition", P::Transition),
+];
+
+/// Properties of label widgets, like the tooltip, the version watermark and
+/// the grid's filter query
+const LABEL_PROPERTIES: &[PropertySchema] = &[
+    prop("content", P::String),
+    prop("text-color", P::Color),
+    prop("text-align", P::String),
+    prop("font-family", P::String),
+    prop("font-size", P::Number),
+];
+
+/// Global (`*`) properties with a fixed meaning; other globals are tokens
+pub const GLOBAL_PROPERTIES: &[PropertySchema] = &[
+    prop("background-color", P::Color),
+    prop("background-image", P::Image),
+    prop("opacity", P::Number),
+    prop("term-foreground", P::Color),
+    prop("term-background", P::Color),
+    prop("term-cursor", P::Color),
+    prop("term-black", P::Color),
+    prop("term-red", P::Color),
+    prop("term-green", P::Color),
+    prop("term-yellow", P::Color),
+    prop("term-blue", P::Color),
+    prop("term-magenta", P::Color),
+    prop("term-cyan", P::Color),
+    prop("term-white", P::Color),
+    prop("term-bright-black", P::Color),
+    prop("term-bright-red", P::Color),
+    prop("term-bright-green", P::Color),
+    prop("term-bright-yellow", P::Color),
+    prop("term-bright-blue", P::Color),
+    prop("term-bright-magenta", P::Color),
+    prop("term-bright-cyan", P::Color),
+    prop("term-bright-white", P::Color),
+];
+
+/// All widgets with properties of their own
+pub const WIDGETS: &[WidgetSchema] = &[
+    WidgetSchema {
+        name: "window",
+        properties: &[
+            // Widgets drawing plain colors override the layout paints
+            prop("background-color", P::Color),
+            prop("border-color", P::Color),
+            prop("width", P::Number),
+            prop("height", P::Number),
+            prop("hotkey", P::String),
+            prop("animation-duration", P::Number),
+            prop("animation-easing", P::String),
+        ],
+    },
+    WidgetSchema {
+        name: "mainbox",
+        properties: &[],
+    },
+    WidgetSchema {
+        name: "inputbar",
+        properties: &[],
+    },
+    WidgetSchema {
+        name: "listbox",
+        properties: &[],
+    },
+    WidgetSchema {
+        name: "dummy",
+        properties: &[],
+    },
+    WidgetSchema {
+        name: "overlay",
+        properties: &[],
+    },
+    WidgetSchema {
+        name: "tooltip",
+        properties: LABEL_PROPERTIES,
+    },
+    WidgetSchema {
+        name: "version",
+        properties: LABEL_PROPERTIES,
+    },
+    WidgetSchema {
+        name: "filter",
+        properties: LABEL_PROPERTIES,
+    },
+    WidgetSchema {
+        name: "textbox",
+        properties: &[
+            prop("text-color", P::Color),
+            prop("placeholder-color", P::Color),
+            prop("cursor-color", P::Color),
+            prop("selection-color", P::Color),
+            prop("font-family", P::String),
+            prop("font-size", P::Number),
+        ],
+    },
+    WidgetSchema {
+        name: "listview",
+        properties: &[
+            prop("background-color", P::Color),
+            prop("border-color", P::Color),
+            prop("lines", P::Number),
+            prop("scrollbar-width", P::Number),
+            prop("scrollbar-color", P::Color),
+            prop("scrollbar-track-color", P::Color),
+        ],
+    },
+    WidgetSchema {
+        name: "element",
+        properties: &[
+            prop("text-color", P::Color),
+            prop("subtext-color", P::Color),
+            prop("highlight-color", P::Color),
+            prop("font-family", P::String),
+            prop("font-size", P::Number),
+            prop("subtext-font-size", P::Number),
+            prop("padding-horizontal", P::Number),
+            prop("padding-vertical", P::Number),
+            prop("icon-size", P::Number),
+            prop("icon-spacing", P::Number),
+        ],
+    },
+    WidgetSchema {
+        name: "gridview",
+        properties: &[
+            prop("background-color", P::Color),
+            prop("border-color", P::Color),
+            prop("layout", P::String),
+            prop("selection-style", P::String),
+            prop("thumb-size", P::Number),
+            prop("thumb-width", P::Number),
+            prop("thumb-height", P::Number),
+            prop("thumb-radius", P::Number),
+            prop("label-width", P::Number),
+            prop("label-height", P::Number),
+            prop("label-color", P::Color),
+            prop("label-background-color", P::Color),
+            prop("max-columns", P::Number),
+            prop("font-family", P::String),
+            prop("font-size", P::Number),
+        ],
+    },
+    WidgetSchema {
+        name: "message",
+        properties: &[
+            prop("text", P::String),
+            prop("text-color", P::Color),
+            prop("font-size", P::Number),
+        ],
+    },
+    WidgetSchema {
+        name: "tailview",
+        properties: &[
+            prop("background-color", P::Color),
+            prop("text-color", P::Color),
+            prop("font-family", P::String),
+            prop("font-size", P::Number),
+            prop("line-spacing", P::Number),
+        ],
+    },
+    WidgetSchema {
+        name: "terminal",
+        properties: &[
+            prop("font-family", P::String),
+            prop("font-size", P::Number),
+            prop("scrollback-lines", P::Number),
+        ],
+    },
+    WidgetSchema {
+        name: "task-panel",
+        properties: &[
+            prop("background-color", P::Color),
+            prop("enabled", P::Boolean),
+            prop("position", P::String),
+            prop("compact-width", P::Number),
+            prop("expanded-width", P::Number),
+            prop("icon-size", P::Number),
+            prop("icon-font-family", P::String),
+            prop("icon-color", P::Color),
+            prop("icon-color-hover", P::Color),
+            prop("group-icon-color", P::Color),
+            prop("group-spacing", P::Number),
+            prop("text-size", P::Number),
+            prop("text-font-family", P::String),
+            prop("text-color", P::Color),
+            prop("text-color-hover", P::Color),
+            prop("item-height", P::Number),
+            prop("item-spacing", P::Number),
+            prop("item-corner-radius", P::Number),
+            prop("item-background-color", P::Color),
+            prop("selected-background-color", P::Color),
+            prop("sub-item-indent", P::Number),
+            prop("chevron-color", P::Color),
+            prop("chevron-expanded", P::String),
+            prop("chevron-collapsed", P::String),
+            prop("tree-line-color", P::Color),
+            prop("tree-branch", P::String),
+            prop("tree-corner", P::String),
+        ],
+    },
+    WidgetSchema {
+        name: "wallpaper-panel",
+        properties: &[
+            prop("width", P::Number),
+            prop("diagonal-edge", P::Number),
+            prop("fade-width", P::Number),
+            prop("fade-color", P::Color),
+            prop("fade-opacity", P::Number),
+            prop("clock-enabled", P::Boolean),
+            prop("clock-format", P::String),
+            prop("clock-date-format", P::String),
+            prop("clock-position", P::String),
+            prop("clock-font-family", P::String),
+            prop("clock-font-size", P::Number),
+            prop("clock-date-font-size", P::Number),
+            prop("clock-padding", P::Number),
+            prop("clock-text-color", P::Color),
+            prop("clock-shadow-color", P::Color),
+            prop("clock-shadow-offset-x", P::Number),
+            prop("clock-shadow-offset-y", P::Number),
+        ],
+    },
+];
+
+/// Layout-only schema for widgets that only appear in a `children` array
+const LAYOUT_WIDGET: WidgetSchema = WidgetSchema {
+    name: "",
+    properties: &[],
+};
+
+/// Look up a widget by name
+pub fn widget(name: &str) -> Option<&'static WidgetSchema> {
+    WIDGETS.iter().find(|w| w.name == name)
+}
+
+/// How serious a lint issue is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Probably a mistake, but the theme still works (unknown names)
+    Warning,
+    /// A value that will be ignored (type mismatch, unresolved token)
+    Error,
+}
+
+/// A problem found by `ThemeTree::validate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    pub severity: Severity,
+    /// Widget the issue is in (None for the global `*` rule)
+    pub widget: Option<String>,
+    pub state: Option<String>,
+    pub property: Option<String>,
+    pub message: String,
+}
+
+impl LintIssue {
+    /// The selector and property the issue is about, e.g. `textbox.focused/font-size`
+    pub fn location(&self) -> String {
+        let mut location = self.widget.clone().unwrap_or_else(|| "*".to_string());
+        if let Some(state) = &self.state {
+            location.push('.');
+            location.push_str(state);
+        }
+        if let Some(property) = &self.property {
+            location.push_str(" { ");
+            location.push_str(property);
+            location.push_str(" }");
+        }
+        location
+    }
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}: {}", severity, self.location(), self.message)
+    }
+}
+
+/// Check a theme against the schema
+///
+/// Issues are sorted by widget, state and property.
+pub fn validate(theme: &ThemeTree) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+
+    // Widgets referenced from a layout are valid layout nodes
+    let mut layout_widgets: HashSet<&str> = HashSet::new();
+    for node in theme.widgets.values() {
+        let all_props = std::iter::once(&node.properties).chain(node.states.values());
+        for props in all_props {
+            if let Some(children) = props.get("children").and_then(|v| v.as_array()) {
+                layout_widgets.extend(children.iter().map(|s| s.as_str()));
+            }
+        }
+    }
+
+    for (name, value) in &theme.globals {
+        let schema = GLOBAL_PROPERTIES.iter().find(|p| p.name == name);
+        check_value(theme, None, None, name, value, schema, &mut issues);
+    }
+
+    for (widget_name, node) in &theme.widgets {
+        let schema = match widget(widget_name) {
+            Some(schema) => schema,
+            None if layout_widgets.contains(widget_name.as_str()) => &LAYOUT_WIDGET,
+            None => {
+                issues.push(LintIssue {
+                    severity: Severity::Warning,
+                    widget: Some(widget_name.clone()),
+                    state: None,
+                    property: None,
+                    message: "unknown widget (not read by Wolfy or used in any children list)"
+                        .to_string(),
+                });
+                continue;
+            }
+        };
+
+        let states = node.states.iter().map(|(s, p)| (Some(s.as_str()), p));
+        for (state, props) in std::iter::once((None, &node.properties)).chain(states) {
+            check_rule(theme, widget_name, state, props, Some(schema), &mut issues);
+        }
+    }
+
+    // Descendant rules are checked against the widget they style; their
+    // simple parts were already covered above
+    for rule in &theme.rules {
+        let Selector::Descendant(parts) = &rule.selector else {
+            continue;
+        };
+        let Some((target, ancestors)) = parts.split_last() else {
+            continue;
+        };
+
+        let mut label: Vec<String> = ancestors.iter().map(|p| p.to_string()).collect();
+        let (state, schema) = match target {
+            Selector::Element { name, states } => {
+                label.push(name.clone());
+                let schema = widget(name).or_else(|| {
+                    layout_widgets
+                        .contains(name.as_str())
+                        .then_some(&LAYOUT_WIDGET)
+                });
+                (Some(states.join(".")).filter(|s| !s.is_empty()), schema)
+            }
+            _ => {
+                label.push("*".to_string());
+                (None, None)
+            }
+        };
+        let label = label.join(" ");
+
+        for (i, part) in parts.iter().enumerate() {
+            let Selector::Element { name, states } = part else {
+                continue;
+            };
+            if widget(name).is_none() && !layout_widgets.contains(name.as_str()) {
+                issues.push(LintIssue {
+                    severity: Severity::Warning,
+                    widget: Some(label.clone()),
+                    state: None,
+                    property: None,
+                    message: format!("unknown widget `{}`", name),
+                });
+            }
+            // The target's own state is checked with its properties below
+            let known = |s: &str| STATES.contains(&s) || MODE_CLASSES.contains(&s);
+            let ancestor_states = states.iter().filter(|_| i < ancestors.len());
+            for state in ancestor_states.filter(|s| !known(s.as_str())) {
+                issues.push(LintIssue {
+                    severity: Severity::Warning,
+                    widget: Some(label.clone()),
+                    state: None,
+                    property: None,
+                    message: format!("unknown state `{}` on `{}`", state, name),
+                });
+            }
+        }
+
+        check_rule(
+            theme,
+            &label,
+            state.as_deref(),
+            &rule.properties,
+            schema,
+            &mut issues,
+        );
+    }
+
+    issues.sort_by(|a, b| {
+        (&a.widget, &a.state, &a.property).cmp(&(&b.widget, &b.state, &b.property))
+    });
+    issues
+}
+
+/// Check the state and properties of one rule
+///
+/// Without a widget schema (a `*` target) only the values are checked.
+fn check_rule(
+    theme: &ThemeTree,
+    widget_name: &str,
+    state: Option<&str>,
+    props: &HashMap<String, Value>,
+    schema: Option<&WidgetSchema>,
+    issues: &mut Vec<LintIssue>,
+) {
+    let unknown = state.is_some_and(|s| s.split('.').any(|s| !STATES.contains(&s)));
+    if let Some(state) = state.filter(|_| unknown) {
+        issues.push(LintIssue {
+            severity: Severity::Warning,
+            widget: Some(widget_name.to_string()),
+            state: Some(state.to_string()),
+            property: None,
+            message: format!("unknown state (expected one of {})", STATES.join(", ")),
+        });
+    }
+
+    for (prop_name, value) in props {
+        let property = schema.and_then(|schema| schema.property(prop_name));
+        if schema.is_some() && property.is_none() {
+            issues.push(LintIssue {
+                severity: Severity::Warning,
+                widget: Some(widget_name.to_string()),
+                state: state.map(str::to_string),
+                property: Some(prop_name.clone()),
+                message: "unknown property".to_string(),
+            });
+        }
+        check_value(
+            theme,
+            Some(widget_name),
+            state,
+            prop_name,
+            value,
+            property,
+            issues,
+        );
+    }
+}
+
+/// Check that a value resolves and has the expected type
+fn check_value(
+    theme: &ThemeTree,
+    widget: Option<&str>,
+    state: Option<&str>,
+    property: &str,
+    value: &Value,
+    schema: Option<&PropertySchema>,
+    issues: &mut Vec<LintIssue>,
+) {
+    let mut error = |message: String| {
+        issues.push(LintIssue {
+            severity: Severity::Error,
+            widget: widget.map(str::to_string),
+            state: state.map(str::to_string),
+            property: Some(property.to_string()),
+            message,
+        })
+    };
+
+    if matches!(value, Value::Ident(s) if s == "inherit") {
+        return;
+    }
+
+    let Some(resolved) = theme.resolve(value) else {
+        error(match value {
+            Value::Var(name, _) => format!("undefined variable `{}`", name),
+            _ => "value could not be resolved (see the function or variable it uses)".to_string(),
+        });
+        return;
+    };
+
+    let Some(schema) = schema else {
+        return;
+    };
+
+    let resolves = match schema.ty {
+        PropertyType::Color => Some(theme.resolve_color(&resolved).is_some()),
+        PropertyType::Paint => Some(theme.resolve_paint(&resolved).is_some()),
+        PropertyType::Shadow if !matches!(&resolved, Value::Ident(s) if s == "none") => {
+            Some(theme.resolve_shadow(&resolved).is_some())
+        }
+        _ => None,
+    };
+    if let Some(resolves) = resolves {
+        if !resolves {
+            error(match (&resolved, theme.resolve_paint(&resolved)) {
+                // A gradient, possibly held by a token, where it isn't accepted
+                (_, Some(Paint::Gradient(_))) => {
+                    format!("expected {}, got a gradient", schema.ty.describe())
+                }
+                (Value::Ident(token), _) => format!("unresolved color token `{}`", token),
+                (Value::Gradient(..) | Value::Shadow(_), _) => {
+                    "a color in it could not be resolved".to_string()
+                }
+                (other, _) => format!(
+                    "expected {}, got {}",
+                    schema.ty.describe(),
+                    describe_value(other)
+                ),
+            });
+        }
+    } else if !schema.ty.accepts(&resolved) {
+        error(format!(
+            "expected {}, got {}",
+            schema.ty.describe(),
+            describe_value(&resolved)
+        ));
+    } else if let Value::Transition(transitions) = &resolved {
+        for transition in transitions.iter().filter(|t| t.easing().is_none()) {
+            if let Some(easing) = &transition.easing {
+                error(format!(
+                    "unknown easing `{}` for `{}`",
+                    easing, transition.property
+                ));
+            }
+        }
+    }
+}
+
+/// Short description of a value for messages
+fn describe_value(value: &Value) -> String {
+    match value {
+        Value::Color(_) => "a color".to_string(),
+        Value::Distance(_) => "a distance".to_string(),
+        Value::Number(n) => format!("number {}", n),
+        Value::String(s) => format!("string \"{}\"", s),
+        Value::Ident(s) => format!("`{}`", s),
+        Value::Boolean(b) => format!("boolean {}", b),
+        Value::Padding2(..) | Value::Padding4(..) => "a padding".to_string(),
+        Value::Array(_) => "an array".to_string(),
+        Value::Image(_) => "an image".to_string(),
+        Value::Orientation(_) => "an orientation".to_string(),
+        Value::Var(name, _) => format!("var({})", name),
+        Value::Function(name, _) => format!("{}()", name),
+        Value::Gradient(..) => "a gradient".to_string(),
+        Value::Shadow(_) => "a shadow".to_string(),
+        Value::Transition(_) => "a transition".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues_for(input: &str) -> Vec<String> {
+        let theme = ThemeTree::parse(input).unwrap();
+        validate(&theme).iter().map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn test_valid_theme_has_no_issues() {
+        let issues = issues_for(
+            r#"
+            * { accent: #cba6f7; background-color: #1e1e2e; }
+            mainbox { children: [ "listbox", "spacer" ]; }
+            spacer { expand: true; }
+            element.selected { background-color: alpha(accent, 20%); text-color: accent; }
+            gridview { thumb-radius: 8; label-color: white; }
+        "#,
+        );
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_reports_unknown_names_and_type_errors() {
+        let issues = issues_for(
+            r#"
+            * { accent: #cba6f7; }
+            gridview { thumb-raduis: 8; label-color: accent-primary; }
+            textbox { font-size: "big"; }
+            textbox.clicked { text-color: accent; }
+            sidebar { width: 10px; }
+            listview { scrollbar-color: var(missing); }
+        "#,
+        );
+
+        let expected = [
+            "error: gridview { label-color }: unresolved color token `accent-primary`",
+            "warning: gridview { thumb-raduis }: unknown property",
+            "error: listview { scrollbar-color }: undefined variable `missing`",
+            "warning: sidebar: unknown widget (not read by Wolfy or used in any children list)",
+            "error: textbox { font-size }: expected a number, got string \"big\"",
+            "warning: textbox.clicked: unknown state (expected one of normal, focused, selected, hover, pressed, disabled, urgent, active, alternate)",
+        ];
+        assert_eq!(issues, expected);
+    }
+
+    #[test]
+    fn test_descendant_rules() {
+        let issues = issues_for(
+            r#"
+            #window.launcher listview element.selected { text-color: white; }
+            listview element { text-colour: white; }
+            listview element selected.urgent, element alternate.active { text-color: white; }
+            window.clicked sidebar textbox { font-size: "big"; }
+        "#,
+        );
+
+        let expected = [
+            "warning: listview element { text-colour }: unknown property",
+            "warning: window.clicked sidebar textbox: unknown state `clicked` on `window`",
+            "warning: window.clicked sidebar textbox: unknown widget `sidebar`",
+            "error: window.clicked sidebar textbox { font-size }: expected a number, got string \"big\"",
+        ];
+        assert_eq!(issues, expected);
+    }
+
+    #[test]
+    fn test_paint_properties() {
+        let issues = issues_for(
+            r#"
+            * { sunset: linear-gradient(45deg, #ff7e5f, #feb47b); }
+            mainbox { background-color: sunset; box-shadow: 0 4px 12px #00000080; }
+            element.selected { background-color: radial-gradient(accent, #000000); }
+            listview { background-color: sunset; }
+            tailview { background-color: linear-gradient(red, blue); }
+            inputbar { box-shadow: 4px; backdrop-blur: 12; }
+        "#,
+        );
+
+        let expected = [
+            "error: element.selected { background-color }: a color in it could not be resolved",
+            "error: inputbar { box-shadow }: expected a shadow, got a distance",
+            "error: listview { background-color }: expected a color, got a gradient",
+            "error: tailview { background-color }: expected a color, got a gradient",
+        ];
+        assert_eq!(issues, expected);
+    }
+
+    #[test]
+    fn test_transition_property() {
+        let issues = issues_for(
+            r#"
+            element { transition: background-color 120ms ease-out, text-color 1s; }
+            gridview { transition: all 200ms bounce; }
+            textbox { transition: 4px; }
+            listview { transition: none; }
+        "#,
+        );
+
+        let expected = [
+            "error: gridview { transition }: unknown easing `bounce` for `all`",
+            "error: textbox { transition }: expected a transition list, got a distance",
+        ];
+        assert_eq!(issues, expected);
+    }
+
+    #[test]
+    fn test_shipped_themes_have_no_errors() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let core = root.join("core.rasi");
+        for entry in std::fs::read_dir(root.join("themes")).unwrap().flatten() {
+            let mut theme = ThemeTree::load(&core).unwrap();
+            theme.merge(ThemeTree::load(&entry.path()).unwrap());
+            let errors: Vec<_> = validate(&theme)
+                .into_iter()
+                .filter(|i| i.severity == Severity::Error)
+                .collect();
+            assert!(errors.is_empty(), "{:?}: {:?}", entry.path(), errors);
+        }
+    }
+}
@@ -1,12 +1,23 @@
 //! Theme tree with property resolution and inheritance
 
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
-
-use crate::theme::ast::{Property, Rule, Selector, Stylesheet, Value};
-use crate::theme::lexer::Lexer;
-use crate::theme::types::{Color, Distance, ImageSource, Orientation, Padding};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use crate::animation::Transition;
+use crate::log::user_config_dir;
+use crate::theme::ast::{
+    parse_path, Import, Property, Rule, Selector, Stylesheet, TransitionValue, Value,
+};
+use crate::theme::diagnostic::{line_column, Diagnostic};
+use crate::theme::functions;
+use crate::theme::lexer::{self, Lexer};
+use crate::theme::rofi;
+use crate::theme::schema::{self, LintIssue};
+use crate::theme::types::{
+    Color, Distance, Gradient, ImageSource, Orientation, Padding, Paint, Shadow,
+};
 
 // Import the generated parser
 use crate::theme::theme_parser;
@@ -15,7 +26,15 @@ use crate::theme::theme_parser;
 #[derive(Debug)]
 pub enum ThemeError {
     IoError(std::io::Error),
-    ParseError(String),
+    /// A syntax error that could not be recovered from
+    ParseError(Diagnostic),
+    /// An `@import` / `@theme` target could not be found
+    ImportNotFound {
+        import: String,
+        from: PathBuf,
+    },
+    /// Files including each other; the chain ends with the repeated file
+    ImportCycle(Vec<PathBuf>),
 }
 
 impl std::fmt::Display for ThemeError {
@@ -23,6 +42,18 @@ impl std::fmt::Display for ThemeError {
         match self {
             ThemeError::IoError(e) => write!(f, "IO error: {}", e),
             ThemeError::ParseError(e) => write!(f, "Parse error: {}", e),
+            ThemeError::ImportNotFound { import, from } => {
+                write!(
+                    f,
+                    "Cannot find \"{}\" imported from {}",
+                    import,
+                    from.display()
+                )
+            }
+            ThemeError::ImportCycle(chain) => {
+                let chain: Vec<_> = chain.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "Import cycle: {}", chain.join(" -> "))
+            }
         }
     }
 }
@@ -35,12 +66,23 @@ impl From<std::io::Error> for ThemeError {
     }
 }
 
+/// The language a theme file is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    /// Wolfy's own rasi
+    #[default]
+    Wolfy,
+    /// A stock rofi theme, translated on load (see `theme::rofi`)
+    Rofi,
+}
+
 /// A node in the theme tree representing a widget's styling
 #[derive(Debug, Clone, Default)]
 pub struct ThemeNode {
     /// Properties for the base state
     pub properties: HashMap<String, Value>,
-    /// Properties for specific states (e.g., "focused", "selected")
+    /// Properties for specific states (e.g., "focused", "selected",
+    /// "selected.urgent")
     pub states: HashMap<String, HashMap<String, Value>>,
 }
 
@@ -77,13 +119,119 @@ impl ThemeNode {
     }
 }
 
+/// A rule for one selector, as it takes part in the cascade
+#[derive(Debug, Clone)]
+pub struct StyleRule {
+    pub selector: Selector,
+    pub properties: HashMap<String, Value>,
+    /// Where each property was declared
+    pub origins: HashMap<String, Origin>,
+}
+
+/// Where a property was declared
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Origin {
+    /// The file it was loaded from; None for a theme parsed from a string
+    pub file: Option<PathBuf>,
+    /// 1-based line of the declaration, if the source text was known
+    pub line: Option<usize>,
+    /// Index of the merged layer it came from, in merge order: with
+    /// `load_layered(&[core, theme])`, core.rasi is layer 0 and the theme
+    /// layer 1. An import is its own layer, between the layers of the
+    /// importing file's rules before and after it.
+    pub layer: usize,
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}", file.display())?,
+            None => write!(f, "<string>")?,
+        }
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        write!(f, " (layer {})", self.layer)
+    }
+}
+
+/// How a property of a widget got its value (see `ThemeTree::explain`)
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    pub widget: String,
+    pub state: Option<String>,
+    pub property: String,
+    /// The rules matching the widget that set the property, the winner first
+    pub rules: Vec<(Selector, Value, Origin)>,
+    /// The global (`* { }`) value, used when no rule sets the property or
+    /// the winning rule says `inherit`
+    pub global: Option<(Value, Origin)>,
+    /// Global tokens and `var()` references followed to resolve the value, in
+    /// order; None for one that isn't defined
+    pub tokens: Vec<(String, Option<(Value, Origin)>)>,
+    /// The resolved value; None if the theme doesn't set the property, so the
+    /// widget's built-in default applies
+    pub resolved: Option<Value>,
+}
+
+impl std::fmt::Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.as_deref().map(|s| format!(".{}", s));
+        writeln!(
+            f,
+            "{}{} {{ {} }}",
+            self.widget,
+            state.unwrap_or_default(),
+            self.property
+        )?;
+
+        for (i, (selector, value, origin)) in self.rules.iter().enumerate() {
+            let note = if i == 0 { "wins" } else { "overridden" };
+            writeln!(
+                f,
+                "  {} {{ {}: {}; }}  {}, {}",
+                selector, self.property, value, origin, note
+            )?;
+        }
+        if self.rules.is_empty() {
+            writeln!(f, "  no rule sets it")?;
+        }
+        if let Some((value, origin)) = &self.global {
+            writeln!(f, "  * {{ {}: {}; }}  {}", self.property, value, origin)?;
+        }
+        for (name, definition) in &self.tokens {
+            match definition {
+                Some((value, origin)) => writeln!(f, "  {}: {};  {}", name, value, origin)?,
+                None => writeln!(f, "  {}: not defined", name)?,
+            }
+        }
+        match &self.resolved {
+            Some(value) => writeln!(f, "  = {}", value),
+            None => writeln!(f, "  = unset, the widget's built-in default applies"),
+        }
+    }
+}
+
 /// The complete theme tree with property resolution
 #[derive(Debug, Default)]
 pub struct ThemeTree {
     /// Global properties (from * selector)
     pub globals: HashMap<String, Value>,
-    /// Named widget nodes
+    /// Where each global was declared
+    pub global_origins: HashMap<String, Origin>,
+    /// Named widget nodes (simple `name` / `name.state` selectors only)
     pub widgets: HashMap<String, ThemeNode>,
+    /// Every non-universal rule in source order, later layers last; a rule
+    /// with several selectors appears once per selector
+    pub rules: Vec<StyleRule>,
+    /// Every file this theme was loaded from, including imports
+    pub sources: Vec<PathBuf>,
+    /// Recovered syntax errors; the rules they occurred in were skipped
+    pub diagnostics: Vec<Diagnostic>,
+    /// Rofi constructs that were dropped or only approximated
+    pub warnings: Vec<Diagnostic>,
+    /// Number of layers merged into this tree (see `Origin::layer`)
+    pub layers: usize,
 }
 
 impl ThemeTree {
@@ -93,13 +241,22 @@ impl ThemeTree {
     }
 
     /// Parse a theme from a string
+    ///
+    /// `@import` / `@theme` directives are resolved against the user config dir.
     pub fn parse(input: &str) -> Result<Self, ThemeError> {
-        let lexer = Lexer::new(input);
-        let stylesheet = theme_parser::StylesheetParser::new()
-            .parse(lexer)
-            .map_err(|e| ThemeError::ParseError(format!("{:?}", e)))?;
+        Self::parse_dialect(input, Dialect::Wolfy)
+    }
 
-        let tree = Self::from_stylesheet(stylesheet);
+    /// Parse a stock rofi theme from a string
+    pub fn parse_rofi(input: &str) -> Result<Self, ThemeError> {
+        Self::parse_dialect(input, Dialect::Rofi)
+    }
+
+    fn parse_dialect(input: &str, dialect: Dialect) -> Result<Self, ThemeError> {
+        let (stylesheet, diagnostics, warnings) = Self::parse_stylesheet(input, None, dialect)?;
+        let mut tree = Self::build(stylesheet, input, None, dialect, &mut Vec::new())?;
+        tree.diagnostics.splice(0..0, diagnostics);
+        tree.warnings.splice(0..0, warnings);
 
         // Debug: log what we parsed (only in non-test builds to avoid issues)
         #[cfg(not(test))]
@@ -133,37 +290,203 @@ impl ThemeTree {
     }
 
     /// Load a theme from a file
+    ///
+    /// `@import` / `@theme` directives are resolved relative to the importing
+    /// file first, then the user config dir. Imports are applied in source
+    /// order, so rules after an import override it and rules before it don't.
     pub fn load(path: &Path) -> Result<Self, ThemeError> {
-        let content = fs::read_to_string(path)?;
-        Self::parse(&content)
+        Self::load_file(path, Dialect::Wolfy, &mut Vec::new())
     }
 
-    /// Build theme tree from parsed stylesheet
-    pub fn from_stylesheet(stylesheet: Stylesheet) -> Self {
+    /// Load a stock rofi theme (or a HyDE `rofi.theme`) from a file
+    ///
+    /// Rofi widgets and properties are mapped onto Wolfy's; whatever can't
+    /// be is listed in `warnings`. Its imports are read as rofi themes too.
+    pub fn load_rofi(path: &Path) -> Result<Self, ThemeError> {
+        Self::load_file(path, Dialect::Rofi, &mut Vec::new())
+    }
+
+    /// Parse source text, recovering from errors in individual rules
+    ///
+    /// Returns the stylesheet along with diagnostics for skipped rules and
+    /// invalid characters, and warnings from the rofi translation; only
+    /// errors that cannot be recovered fail.
+    pub(crate) fn parse_stylesheet(
+        input: &str,
+        file: Option<&Path>,
+        dialect: Dialect,
+    ) -> Result<(Stylesheet, Vec<Diagnostic>, Vec<Diagnostic>), ThemeError> {
+        let mut warnings = Vec::new();
+        let (input, tokens) = match dialect {
+            Dialect::Wolfy => (Cow::Borrowed(input), Lexer::new(input).collect()),
+            Dialect::Rofi => {
+                let input = rofi::strip_target_line(input);
+                let tokens = rofi::tokens(&input, file, &|name| env::var(name).ok(), &mut warnings);
+                (input, tokens)
+            }
+        };
+        let input = input.as_ref();
+
+        // Invalid characters are dropped so the parser can carry on
+        let mut lexer_errors = Vec::new();
+        let tokens = tokens.into_iter().filter(|token| match token {
+            Err(e) => {
+                lexer_errors.push(Diagnostic::new(input, e.span.clone(), e.message(), file));
+                false
+            }
+            Ok(_) => true,
+        });
+
+        let mut recovered = Vec::new();
+        let mut stylesheet = theme_parser::StylesheetParser::new()
+            .parse(&mut recovered, tokens)
+            .map_err(|e| ThemeError::ParseError(Diagnostic::from_parse_error(input, &e, file)))?;
+        match dialect {
+            Dialect::Wolfy => stylesheet.comments = lexer::comments(input),
+            Dialect::Rofi => stylesheet = rofi::translate(stylesheet, input, file, &mut warnings),
+        }
+
+        let mut diagnostics = lexer_errors;
+        diagnostics.extend(
+            recovered
+                .iter()
+                .map(|r| Diagnostic::from_parse_error(input, &r.error, file)),
+        );
+        diagnostics.sort_by_key(|d| (d.line, d.column));
+        warnings.sort_by_key(|d| (d.line, d.column));
+        for diagnostic in &diagnostics {
+            crate::log!("Theme error (rule skipped): {}", diagnostic);
+        }
+        for warning in &warnings {
+            crate::log!("Rofi theme warning: {}", warning);
+        }
+
+        Ok((stylesheet, diagnostics, warnings))
+    }
+
+    /// Load one file, with `chain` holding the files currently being imported
+    fn load_file(
+        path: &Path,
+        dialect: Dialect,
+        chain: &mut Vec<PathBuf>,
+    ) -> Result<Self, ThemeError> {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if chain.contains(&path) {
+            let mut cycle = chain.clone();
+            cycle.push(path);
+            return Err(ThemeError::ImportCycle(cycle));
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let (stylesheet, diagnostics, warnings) =
+            Self::parse_stylesheet(&content, Some(&path), dialect)?;
+
+        chain.push(path.clone());
+        let result = Self::build(stylesheet, &content, Some(&path), dialect, chain);
+        chain.pop();
+
+        let mut tree = result?;
+        tree.sources.insert(0, path);
+        tree.diagnostics.splice(0..0, diagnostics);
+        tree.warnings.splice(0..0, warnings);
+        Ok(tree)
+    }
+
+    /// Build a tree from a stylesheet parsed from `input`, loading its imports
+    ///
+    /// Imports are applied in source order: one overrides the rules above it,
+    /// and the rules below it override the import.
+    fn build(
+        stylesheet: Stylesheet,
+        input: &str,
+        path: Option<&Path>,
+        dialect: Dialect,
+        chain: &mut Vec<PathBuf>,
+    ) -> Result<Self, ThemeError> {
+        let base_dir = path.and_then(Path::parent);
         let mut tree = Self::new();
+        let mut rules = stylesheet.rules.into_iter().peekable();
+
+        for directive in &stylesheet.imports {
+            let before: Vec<Rule> =
+                std::iter::from_fn(|| rules.next_if(|r| r.span.start < directive.span.start))
+                    .collect();
+            if !before.is_empty() {
+                tree.merge(Self::from_source(before, Some(input), path));
+            }
+
+            let import = &directive.import;
+            let import_path =
+                resolve_import(import, base_dir).ok_or_else(|| ThemeError::ImportNotFound {
+                    import: match import {
+                        Import::File(name) | Import::Theme(name) => name.clone(),
+                    },
+                    from: path.map(Path::to_path_buf).unwrap_or_default(),
+                })?;
+            tree.merge(Self::load_file(&import_path, dialect, chain)?);
+        }
+
+        tree.merge(Self::from_source(rules.collect(), Some(input), path));
+        Ok(tree)
+    }
 
-        for rule in stylesheet.rules {
+    /// Build theme tree from parsed stylesheet (directives are ignored)
+    ///
+    /// Without the source text, origins have no line numbers.
+    pub fn from_stylesheet(stylesheet: Stylesheet) -> Self {
+        Self::from_source(stylesheet.rules, None, None)
+    }
+
+    /// Build a single-layer tree, recording where each property came from
+    fn from_source(rules: Vec<Rule>, input: Option<&str>, file: Option<&Path>) -> Self {
+        let mut tree = Self::new();
+        tree.layers = 1;
+        let origin = |prop: &Property| Origin {
+            file: file.map(Path::to_path_buf),
+            line: input.map(|input| line_column(input, prop.span.start).0),
+            layer: 0,
+        };
+
+        for rule in rules {
             for selector in &rule.selectors {
+                if *selector != Selector::Universal {
+                    tree.rules.push(StyleRule {
+                        selector: selector.clone(),
+                        properties: rule
+                            .properties
+                            .iter()
+                            .map(|p| (p.name.clone(), p.value.clone()))
+                            .collect(),
+                        origins: rule
+                            .properties
+                            .iter()
+                            .map(|p| (p.name.clone(), origin(p)))
+                            .collect(),
+                    });
+                }
+
                 match selector {
                     Selector::Universal => {
                         // Add to globals
                         for prop in &rule.properties {
                             tree.globals.insert(prop.name.clone(), prop.value.clone());
+                            tree.global_origins.insert(prop.name.clone(), origin(prop));
                         }
                     }
-                    Selector::Element { name, state } => {
-                        let node = tree
-                            .widgets
-                            .entry(name.clone())
-                            .or_insert_with(ThemeNode::new);
+                    Selector::Element { name, states } => {
+                        let node = tree.widgets.entry(name.clone()).or_default();
+                        let state = states.join(".");
 
                         for prop in &rule.properties {
-                            match state {
-                                Some(s) => node.set_state(s, prop.name.clone(), prop.value.clone()),
-                                None => node.set(prop.name.clone(), prop.value.clone()),
+                            if states.is_empty() {
+                                node.set(prop.name.clone(), prop.value.clone());
+                            } else {
+                                node.set_state(&state, prop.name.clone(), prop.value.clone());
                             }
                         }
                     }
+                    // Only reachable through `rules`
+                    Selector::Descendant(_) => {}
                 }
             }
         }
@@ -171,23 +494,296 @@ impl ThemeTree {
         tree
     }
 
-    /// Get a value with inheritance: widget.state -> widget -> globals
+    /// Get a value by CSS cascade: matching rules -> globals
+    ///
+    /// `widget` is a widget name or its path from the root, written like a
+    /// selector: `window mainbox listview element`. `state` may combine
+    /// several states, like `selected.urgent`. Of the rules that match the
+    /// widget in `state` and set `property`, the most specific wins, then the
+    /// one for the highest state in `STATE_PRECEDENCE`, then the last one in
+    /// source order.
     pub fn get_value(&self, widget: &str, state: Option<&str>, property: &str) -> Option<&Value> {
-        // First, try widget-specific properties
-        if let Some(node) = self.widgets.get(widget) {
-            if let Some(value) = node.get(property, state) {
-                // Check for "inherit" keyword
-                if let Value::Ident(s) = value {
-                    if s == "inherit" {
-                        // Fall through to globals
-                        return self.globals.get(property);
+        match self.cascade(widget, state, property, false) {
+            // "inherit" falls through to globals
+            Some(Value::Ident(s)) if s == "inherit" => self.globals.get(property),
+            Some(value) => Some(value),
+            None => self.globals.get(property),
+        }
+    }
+
+    /// Get a value set by a rule for a state of the widget, like
+    /// `element.selected` or `element selected.urgent`
+    ///
+    /// Unlike `get_value`, rules without a state and globals are ignored, so
+    /// callers can tell a themed state apart from the widget's base style.
+    pub fn get_state_value(&self, widget: &str, state: &str, property: &str) -> Option<&Value> {
+        self.cascade(widget, Some(state), property, true)
+            .filter(|v| !matches!(v, Value::Ident(s) if s == "inherit"))
+    }
+
+    /// Get a color set by a rule for a state of the widget (see `get_state_value`)
+    pub fn get_state_color(&self, widget: &str, state: &str, property: &str) -> Option<Color> {
+        self.get_state_value(widget, state, property)
+            .and_then(|v| self.resolve_color(v))
+    }
+
+    /// The winning rule's value for a property, if any rule sets it
+    fn cascade(
+        &self,
+        widget: &str,
+        state: Option<&str>,
+        property: &str,
+        state_rules_only: bool,
+    ) -> Option<&Value> {
+        self.candidates(widget, state, property, state_rules_only)
+            .into_iter()
+            .max_by_key(|(key, _)| *key)
+            .map(|(_, rule)| &rule.properties[property])
+    }
+
+    /// The rules matching the widget that set a property, keyed by cascade
+    /// order (the highest key wins)
+    #[allow(clippy::type_complexity)]
+    fn candidates(
+        &self,
+        widget: &str,
+        state: Option<&str>,
+        property: &str,
+        state_rules_only: bool,
+    ) -> Vec<(((u32, u32), usize, usize), &StyleRule)> {
+        let path = parse_path(widget);
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| {
+                !state_rules_only || rule.selector.target_states().iter().any(|s| s != "normal")
+            })
+            .filter(|(_, rule)| rule.properties.contains_key(property))
+            .filter_map(|(order, rule)| {
+                let selector = &rule.selector;
+                let key = (selector.specificity(), selector.state_rank(), order);
+                selector.matches(&path, state).then_some((key, rule))
+            })
+            .collect()
+    }
+
+    /// Explain how a property of a widget gets its value
+    ///
+    /// Lists every matching rule that sets it with the file, line and layer
+    /// it came from, the global used if the rules don't decide it, and the
+    /// tokens the value was resolved through. `widget` and `state` are as
+    /// for `get_value`.
+    pub fn explain(&self, widget: &str, state: Option<&str>, property: &str) -> Explanation {
+        let mut candidates = self.candidates(widget, state, property, false);
+        candidates.sort_by_key(|(key, _)| std::cmp::Reverse(*key));
+        let rules: Vec<_> = candidates
+            .into_iter()
+            .map(|(_, rule)| {
+                (
+                    rule.selector.clone(),
+                    rule.properties[property].clone(),
+                    rule.origins.get(property).cloned().unwrap_or_default(),
+                )
+            })
+            .collect();
+
+        // As in `get_value`, `inherit` falls through to globals
+        let global = match rules.first() {
+            Some((_, Value::Ident(s), _)) if s == "inherit" => self.global_definition(property),
+            Some(_) => None,
+            None => self.global_definition(property),
+        };
+
+        let mut tokens = Vec::new();
+        let value = self.get_value(widget, state, property);
+        if let Some(value) = value {
+            self.trace_tokens(value, &mut tokens);
+        }
+
+        Explanation {
+            widget: widget.to_string(),
+            state: state.map(str::to_string),
+            property: property.to_string(),
+            rules,
+            global,
+            tokens,
+            resolved: value.and_then(|v| self.resolve_token(v)),
+        }
+    }
+
+    /// A global's value and where it was declared
+    fn global_definition(&self, name: &str) -> Option<(Value, Origin)> {
+        let value = self.globals.get(name)?.clone();
+        let origin = self.global_origins.get(name).cloned().unwrap_or_default();
+        Some((value, origin))
+    }
+
+    /// Collect the globals a value refers to, recursively, each once
+    fn trace_tokens(&self, value: &Value, tokens: &mut Vec<(String, Option<(Value, Origin)>)>) {
+        let follow = |name: &str, tokens: &mut Vec<_>| {
+            if tokens.iter().any(|(n, _)| n == name) {
+                return None;
+            }
+            let definition = self.global_definition(name);
+            tokens.push((name.to_string(), definition.clone()));
+            definition.map(|(value, _)| value)
+        };
+
+        match value {
+            Value::Var(name, fallback) => match follow(name, tokens) {
+                Some(value) => self.trace_tokens(&value, tokens),
+                None if !self.globals.contains_key(name) => {
+                    if let Some(fallback) = fallback {
+                        self.trace_tokens(fallback, tokens);
                     }
                 }
-                return Some(value);
+                None => {}
+            },
+            Value::Ident(name) if self.is_token(name) => {
+                if let Some(value) = follow(name, tokens) {
+                    self.trace_tokens(&value, tokens);
+                }
+            }
+            Value::Function(_, args) => {
+                for arg in args {
+                    self.trace_tokens(arg, tokens);
+                }
+            }
+            Value::Gradient(_, stops) => {
+                for stop in stops {
+                    self.trace_tokens(&stop.color, tokens);
+                }
             }
+            Value::Shadow(shadow) => self.trace_tokens(&shadow.color, tokens),
+            _ => {}
         }
-        // Fall back to globals
-        self.globals.get(property)
+    }
+
+    /// Get a value with `var()` references and functions resolved
+    pub fn get_resolved(&self, widget: &str, state: Option<&str>, property: &str) -> Option<Value> {
+        self.get_value(widget, state, property)
+            .and_then(|v| self.resolve(v))
+    }
+
+    /// Resolve `var()` references and function calls in a value
+    ///
+    /// References are followed recursively through globals. A reference cycle
+    /// or a missing variable uses the `var()` fallback if there is one.
+    pub fn resolve(&self, value: &Value) -> Option<Value> {
+        self.resolve_with(value, &mut Vec::new())
+    }
+
+    /// Resolve a value to a color, treating bare identifiers as color tokens
+    /// (like `accent-primary`) defined in globals
+    pub fn resolve_color(&self, value: &Value) -> Option<Color> {
+        let mut stack = Vec::new();
+        let value = self.resolve_with(value, &mut stack)?;
+        value.as_color().or_else(|| match value {
+            Value::Ident(ref name) => self
+                .resolve_global(name, &mut stack)
+                .and_then(|v| v.as_color()),
+            _ => None,
+        })
+    }
+
+    /// Resolve a value to a paint: a gradient, or a color as for `resolve_color`
+    ///
+    /// A bare identifier may name a global token holding a gradient.
+    pub fn resolve_paint(&self, value: &Value) -> Option<Paint> {
+        match self.resolve_token(value)? {
+            Value::Gradient(kind, stops) => {
+                let stops = stops
+                    .iter()
+                    .map(|stop| {
+                        let color = self.resolve_color(&stop.color)?;
+                        Some((color, stop.position.map(|p| p as f32 / 100.0)))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Paint::Gradient(Gradient::new(kind, &stops)))
+            }
+            value => self.resolve_color(&value).map(Paint::Solid),
+        }
+    }
+
+    /// Resolve a `box-shadow` value; `none` and anything else give None
+    pub fn resolve_shadow(&self, value: &Value) -> Option<Shadow> {
+        match self.resolve_token(value)? {
+            Value::Shadow(shadow) => Some(Shadow {
+                offset_x: shadow.offset_x.value as f32,
+                offset_y: shadow.offset_y.value as f32,
+                blur: shadow.blur.value.max(0.0) as f32,
+                spread: shadow.spread.value as f32,
+                color: self.resolve_color(&shadow.color)?,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Resolve a value to a number, following variables and global tokens
+    pub fn resolve_number(&self, value: &Value) -> Option<f64> {
+        self.resolve_token(value)?.as_number()
+    }
+
+    /// Resolve a value, following a bare identifier naming a global token
+    fn resolve_token(&self, value: &Value) -> Option<Value> {
+        let mut stack = Vec::new();
+        match self.resolve_with(value, &mut stack)? {
+            Value::Ident(name) if self.is_token(&name) => self.resolve_global(&name, &mut stack),
+            value => Some(value),
+        }
+    }
+
+    fn resolve_with(&self, value: &Value, stack: &mut Vec<String>) -> Option<Value> {
+        match value {
+            Value::Var(name, fallback) => self.resolve_global(name, stack).or_else(|| {
+                fallback
+                    .as_deref()
+                    .and_then(|f| self.resolve_with(f, stack))
+            }),
+            Value::Function(name, args) => {
+                // Bare identifiers in arguments may name color tokens
+                let args = args
+                    .iter()
+                    .map(|arg| match arg {
+                        Value::Ident(token) if self.is_token(token) => {
+                            self.resolve_global(token, stack)
+                        }
+                        _ => self.resolve_with(arg, stack),
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                match functions::call(name, &args) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        crate::log!("Theme function error: {}", e);
+                        None
+                    }
+                }
+            }
+            _ => Some(value.clone()),
+        }
+    }
+
+    /// Resolve a global by name, following token aliases and detecting cycles
+    fn resolve_global(&self, name: &str, stack: &mut Vec<String>) -> Option<Value> {
+        if stack.iter().any(|n| n == name) {
+            crate::log!("Theme variable cycle: {} -> {}", stack.join(" -> "), name);
+            return None;
+        }
+        let value = self.globals.get(name)?;
+
+        stack.push(name.to_string());
+        let resolved = match value {
+            // `accent: accent-primary;` aliases another token
+            Value::Ident(token) if self.is_token(token) => self.resolve_global(token, stack),
+            _ => self.resolve_with(value, stack),
+        };
+        stack.pop();
+        resolved
+    }
+
+    /// Whether an identifier names a global token (named CSS colors take precedence)
+    fn is_token(&self, name: &str) -> bool {
+        self.globals.contains_key(name) && Value::Ident(name.to_string()).as_color().is_none()
     }
 
     /// Get a color property with default
@@ -199,8 +795,7 @@ impl ThemeTree {
         property: &str,
         default: Color,
     ) -> Color {
-        self.get_value(widget, state, property)
-            .and_then(|v| v.as_color_resolved(&self.globals))
+        self.get_color_opt(widget, state, property)
             .unwrap_or(default)
     }
 
@@ -213,7 +808,42 @@ impl ThemeTree {
         property: &str,
     ) -> Option<Color> {
         self.get_value(widget, state, property)
-            .and_then(|v| v.as_color_resolved(&self.globals))
+            .and_then(|v| self.resolve_color(v))
+    }
+
+    /// Get a paint property (color or gradient) with default
+    pub fn get_paint(
+        &self,
+        widget: &str,
+        state: Option<&str>,
+        property: &str,
+        default: Paint,
+    ) -> Paint {
+        self.get_paint_opt(widget, state, property)
+            .unwrap_or(default)
+    }
+
+    /// Get an optional paint property (returns None if not set)
+    pub fn get_paint_opt(
+        &self,
+        widget: &str,
+        state: Option<&str>,
+        property: &str,
+    ) -> Option<Paint> {
+        self.get_value(widget, state, property)
+            .and_then(|v| self.resolve_paint(v))
+    }
+
+    /// Get a paint set by a rule for a state of the widget (see `get_state_value`)
+    pub fn get_state_paint(&self, widget: &str, state: &str, property: &str) -> Option<Paint> {
+        self.get_state_value(widget, state, property)
+            .and_then(|v| self.resolve_paint(v))
+    }
+
+    /// Get a `box-shadow` property; None if unset or `none`
+    pub fn get_shadow(&self, widget: &str, state: Option<&str>, property: &str) -> Option<Shadow> {
+        self.get_value(widget, state, property)
+            .and_then(|v| self.resolve_shadow(v))
     }
 
     /// Get a distance property with default
@@ -224,7 +854,7 @@ impl ThemeTree {
         property: &str,
         default: Distance,
     ) -> Distance {
-        self.get_value(widget, state, property)
+        self.get_resolved(widget, state, property)
             .and_then(|v| v.as_distance())
             .unwrap_or(default)
     }
@@ -237,7 +867,7 @@ impl ThemeTree {
         property: &str,
         default: Padding,
     ) -> Padding {
-        self.get_value(widget, state, property)
+        self.get_resolved(widget, state, property)
             .and_then(|v| v.as_padding())
             .unwrap_or(default)
     }
@@ -250,7 +880,7 @@ impl ThemeTree {
         property: &str,
         default: &str,
     ) -> String {
-        self.get_value(widget, state, property)
+        self.get_resolved(widget, state, property)
             .and_then(|v| v.as_string())
             .unwrap_or_else(|| default.to_string())
     }
@@ -263,7 +893,7 @@ impl ThemeTree {
         property: &str,
         default: f64,
     ) -> f64 {
-        self.get_value(widget, state, property)
+        self.get_resolved(widget, state, property)
             .and_then(|v| v.as_number())
             .unwrap_or(default)
     }
@@ -276,7 +906,7 @@ impl ThemeTree {
         property: &str,
         default: bool,
     ) -> bool {
-        self.get_value(widget, state, property)
+        self.get_resolved(widget, state, property)
             .and_then(|v| v.as_bool())
             .unwrap_or(default)
     }
@@ -284,15 +914,14 @@ impl ThemeTree {
     /// Get the children array for a widget (for layout composition)
     /// Returns the widget names listed in the `children` property
     pub fn get_children(&self, widget: &str) -> Vec<String> {
-        self.get_value(widget, None, "children")
-            .and_then(|v| v.as_array())
-            .map(|arr| arr.to_vec())
+        self.get_resolved(widget, None, "children")
+            .and_then(|v| v.as_array().map(|arr| arr.to_vec()))
             .unwrap_or_default()
     }
 
     /// Get the orientation for a widget (horizontal/vertical)
     pub fn get_orientation(&self, widget: &str, default: Orientation) -> Orientation {
-        self.get_value(widget, None, "orientation")
+        self.get_resolved(widget, None, "orientation")
             .and_then(|v| v.as_orientation())
             .unwrap_or(default)
     }
@@ -304,9 +933,8 @@ impl ThemeTree {
         state: Option<&str>,
         property: &str,
     ) -> Option<ImageSource> {
-        self.get_value(widget, state, property)
-            .and_then(|v| v.as_image())
-            .cloned()
+        self.get_resolved(widget, state, property)
+            .and_then(|v| v.as_image().cloned())
     }
 
     /// Get the expand property (whether widget should expand to fill space)
@@ -319,6 +947,19 @@ impl ThemeTree {
         self.get_distance(widget, None, "spacing", default)
     }
 
+    /// Get the `transition` list for a widget; empty if it has none
+    ///
+    /// Entries with an unknown easing are left out.
+    pub fn get_transitions(&self, widget: &str, state: Option<&str>) -> Vec<Transition> {
+        match self.get_resolved(widget, state, "transition") {
+            Some(Value::Transition(transitions)) => transitions
+                .iter()
+                .filter_map(TransitionValue::to_transition)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
     /// Get the hotkey configuration from the window section
     /// Format: "alt+<key>" where <key> is a-z, 0-9, or "space"
     /// Returns the default hotkey if not specified or invalid
@@ -326,13 +967,46 @@ impl ThemeTree {
         self.get_string("window", None, "hotkey", default)
     }
 
+    /// Check the theme against the widget/property schema
+    ///
+    /// Reports unknown widgets, states and properties, type mismatches and
+    /// unresolved color tokens or variables. See `schema` for the known names.
+    pub fn validate(&self) -> Vec<LintIssue> {
+        schema::validate(self)
+    }
+
     /// Merge another theme into this one (other takes precedence)
-    /// This allows layering: load core.rasi first, then overlay theme colors
-    pub fn merge(&mut self, other: ThemeTree) {
+    /// This allows layering: load core.rasi first, then overlay theme colors.
+    /// Other's rules come later in source order, so they win over equally
+    /// specific rules here but not over more specific ones.
+    ///
+    /// Other's layers are numbered after this tree's in property origins.
+    pub fn merge(&mut self, mut other: ThemeTree) {
+        let offset = self.layers;
+        let other_origins = other
+            .rules
+            .iter_mut()
+            .flat_map(|rule| rule.origins.values_mut())
+            .chain(other.global_origins.values_mut());
+        for origin in other_origins {
+            origin.layer += offset;
+        }
+        self.layers += other.layers;
+
+        for source in other.sources {
+            if !self.sources.contains(&source) {
+                self.sources.push(source);
+            }
+        }
+        self.diagnostics.extend(other.diagnostics);
+        self.warnings.extend(other.warnings);
+        self.rules.extend(other.rules);
+
         // Merge globals (other overwrites)
         for (key, value) in other.globals {
             self.globals.insert(key, value);
         }
+        self.global_origins.extend(other.global_origins);
 
         // Merge widgets
         for (name, other_node) in other.widgets {
@@ -373,17 +1047,58 @@ impl ThemeTree {
     pub fn theme_name_to_filename(theme_name: &str) -> String {
         theme_name
             .to_lowercase()
-            .replace(' ', "_")
-            .replace('-', "_")
+            .replace([' ', '-'], "_")
             .chars()
             .filter(|c| c.is_alphanumeric() || *c == '_')
             .collect()
     }
 }
 
+/// Find the file an `@import` / `@theme` directive refers to
+///
+/// - `@import "name"` looks for `name` (`.rasi` added if there is no extension)
+///   next to the importing file, then in the user config dir
+/// - `@theme "Name"` looks for `themes/name.rasi` in the same places, plus the
+///   importing file's own directory (for themes importing sibling themes)
+fn resolve_import(import: &Import, base_dir: Option<&Path>) -> Option<PathBuf> {
+    let config_dir = user_config_dir();
+    let dirs: Vec<&Path> = base_dir.into_iter().chain(config_dir.as_deref()).collect();
+
+    let candidates: Vec<PathBuf> = match import {
+        Import::File(name) => {
+            let mut file = PathBuf::from(name);
+            if file.extension().is_none() {
+                file.set_extension("rasi");
+            }
+            if file.is_absolute() {
+                vec![file]
+            } else {
+                dirs.iter().map(|dir| dir.join(&file)).collect()
+            }
+        }
+        Import::Theme(name) => {
+            let file = if name.ends_with(".rasi") {
+                name.clone()
+            } else {
+                format!("{}.rasi", ThemeTree::theme_name_to_filename(name))
+            };
+            let mut candidates: Vec<PathBuf> = dirs
+                .iter()
+                .map(|dir| dir.join("themes").join(&file))
+                .collect();
+            candidates.extend(base_dir.map(|dir| dir.join(&file)));
+            candidates
+        }
+    };
+
+    candidates.into_iter().find(|p| p.is_file())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::animation::Easing;
+    use crate::theme::types::{GradientKind, LayoutContext};
 
     #[test]
     fn test_parse_simple_theme() {
@@ -684,6 +1399,10 @@ textbox.focused {
             overlay {
                 background-color: rgba(255, 0, 128, 0.5);
             }
+
+            shade {
+                background-color: rgba(0, 0, 0, 25%);
+            }
         "#,
         )
         .unwrap();
@@ -725,6 +1444,208 @@ textbox.focused {
             "overlay alpha should be ~0.5, got {}",
             overlay.a
         );
+
+        // rofi writes the alpha as a percentage
+        let shade = theme.get_color("shade", None, "background-color", Color::WHITE);
+        assert!((shade.a - 0.25).abs() < 0.01, "shade alpha {}", shade.a);
+    }
+
+    #[test]
+    fn test_css_color_syntax() {
+        let theme = ThemeTree::parse(
+            r#"
+            * {
+                hue: 240;
+                accent: hsl(var(hue) 100% 50%);
+            }
+            a { text-color: rgb(255 0 128 / 50%); }
+            b { text-color: rgb(100%, 0%, 50%); }
+            c { text-color: hsl(120deg 100% 25% / 0.5); }
+            d { text-color: hwb(0 0% 0%); }
+            e { text-color: oklch(100% 0 0); }
+            f { text-color: var(accent); }
+        "#,
+        )
+        .unwrap();
+        assert!(theme.diagnostics.is_empty(), "{:?}", theme.diagnostics);
+
+        let hex = |widget| {
+            theme
+                .get_color(widget, None, "text-color", Color::TRANSPARENT)
+                .to_hex()
+        };
+        assert_eq!(hex("a"), "#ff008080");
+        assert_eq!(hex("b"), "#ff0080");
+        assert_eq!(hex("c"), "#00800080");
+        assert_eq!(hex("d"), "#ff0000");
+        assert_eq!(hex("e"), "#ffffff");
+        assert_eq!(hex("f"), "#0000ff");
+    }
+
+    #[test]
+    fn test_var_references() {
+        let theme = ThemeTree::parse(
+            r#"
+            * {
+                base-size: 14;
+                font-size: var(base-size);
+                gap: 8px;
+                spacing: var(gap);
+                loop-a: var(loop-b);
+                loop-b: var(loop-a);
+            }
+
+            textbox {
+                font-size: var(font-size);
+                padding: var(missing, 4px);
+                spacing: var(loop-a, 2px);
+                orientation: var(missing-orientation, horizontal);
+            }
+        "#,
+        )
+        .unwrap();
+
+        // Nested references resolve through globals
+        assert_eq!(theme.get_number("textbox", None, "font-size", 0.0), 14.0);
+        assert_eq!(theme.get_spacing("unknown", Distance::px(0.0)).value, 8.0);
+
+        // Missing variables and cycles use the fallback
+        assert_eq!(
+            theme.get_padding("textbox", None, "padding", Padding::default()),
+            Padding::uniform(Distance::px(4.0))
+        );
+        assert_eq!(theme.get_spacing("textbox", Distance::px(0.0)).value, 2.0);
+        assert!(theme.get_resolved("unknown", None, "loop-a").is_none());
+        assert_eq!(
+            theme.get_orientation("textbox", Orientation::Vertical),
+            Orientation::Horizontal
+        );
+    }
+
+    #[test]
+    fn test_color_functions_and_tokens() {
+        let theme = ThemeTree::parse(
+            r#"
+            * {
+                accent-primary: #808080;
+                accent: accent-primary;
+                accent-hover: lighten(accent, 10%);
+                panel-bg: alpha(var(accent-primary), 0.5);
+            }
+
+            element {
+                background-color: accent-hover;
+                border-color: darken(accent-primary, 0.1);
+                text-color: mix(white, black, 30%);
+            }
+
+            element.selected {
+                background-color: panel-bg;
+                text-color: lighten(unknown-token, 10%);
+            }
+        "#,
+        )
+        .unwrap();
+
+        // Token -> function -> alias -> color
+        let bg = theme.get_color("element", None, "background-color", Color::BLACK);
+        assert!(bg.r > 0.6 && bg.r < 0.61, "got {}", bg.r);
+
+        let border = theme.get_color("element", None, "border-color", Color::BLACK);
+        assert!(border.r > 0.4 && border.r < 0.41, "got {}", border.r);
+
+        let text = theme.get_color("element", None, "text-color", Color::BLACK);
+        assert!((text.r - 0.3).abs() < 0.001);
+
+        let selected = theme.get_color(
+            "element",
+            Some("selected"),
+            "background-color",
+            Color::BLACK,
+        );
+        assert!((selected.a - 0.5).abs() < 0.001);
+        assert!((selected.r - 128.0 / 255.0).abs() < 0.001);
+
+        // Functions with unresolvable arguments fall back to the default
+        assert_eq!(
+            theme.get_color("element", Some("selected"), "text-color", Color::RED),
+            Color::RED
+        );
+    }
+
+    #[test]
+    fn test_gradients_and_shadows() {
+        let theme = ThemeTree::parse(
+            r#"
+            * { glass: linear-gradient(to top right, #ffffff33, #ffffff00); }
+            panel { background-color: linear-gradient(90deg, #000000, #ff0000 25%, #ffffff); }
+            listbox { background-color: glass; border-color: radial-gradient(circle, red, blue); }
+            inputbar { background-color: linear-gradient(#000000, #ffffff); }
+            element { box-shadow: 0 4px 8px rgba(0, 0, 0, 50%); }
+            textbox { box-shadow: 2px 3px #000000; }
+        "#,
+        )
+        .unwrap();
+        assert!(theme.diagnostics.is_empty(), "{:?}", theme.diagnostics);
+
+        let gradient =
+            |widget: &str, property: &str| match theme.get_paint_opt(widget, None, property) {
+                Some(Paint::Gradient(gradient)) => gradient,
+                other => panic!("expected a gradient, got {:?}", other),
+            };
+
+        let panel = gradient("panel", "background-color");
+        assert_eq!(panel.kind, GradientKind::Linear { angle: 90.0 });
+        let positions: Vec<f32> = panel.stops.iter().map(|s| s.position).collect();
+        assert_eq!(positions, [0.0, 0.25, 1.0]);
+        assert_eq!(panel.stops[1].color, Color::RED);
+
+        // Side keywords, tokens holding gradients and the default direction
+        let glass = gradient("listbox", "background-color");
+        assert_eq!(glass.kind, GradientKind::Linear { angle: 45.0 });
+        assert_eq!(
+            gradient("listbox", "border-color").kind,
+            GradientKind::Radial { circle: true }
+        );
+        assert_eq!(
+            gradient("inputbar", "background-color").kind,
+            GradientKind::Linear { angle: 180.0 }
+        );
+
+        // Plain colors are solid paints
+        assert_eq!(
+            theme.get_paint("element", None, "background-color", Color::RED.into()),
+            Paint::Solid(Color::RED)
+        );
+
+        let shadow = theme.get_shadow("element", None, "box-shadow").unwrap();
+        assert_eq!(
+            (shadow.offset_x, shadow.offset_y, shadow.blur),
+            (0.0, 4.0, 8.0)
+        );
+        assert!((shadow.color.a - 0.5).abs() < 0.01);
+        let shadow = theme.get_shadow("textbox", None, "box-shadow").unwrap();
+        assert_eq!(
+            (shadow.offset_x, shadow.blur, shadow.spread),
+            (2.0, 0.0, 0.0)
+        );
+
+        // A bad direction is reported and the gradient still loads
+        let theme = ThemeTree::parse(
+            "panel { background-color: linear-gradient(to middle, #000000, #ffffff); }",
+        )
+        .unwrap();
+        assert_eq!(theme.diagnostics.len(), 1);
+        assert!(
+            theme.diagnostics[0]
+                .message
+                .starts_with("unexpected `middle`"),
+            "{}",
+            theme.diagnostics[0].message
+        );
+        assert!(theme
+            .get_paint_opt("panel", None, "background-color")
+            .is_some());
     }
 
     #[test]
@@ -784,4 +1705,488 @@ textbox.focused {
             panel.a
         );
     }
+
+    #[test]
+    fn test_import_and_theme_directives() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("themes")).unwrap();
+        fs::write(
+            dir.path().join("themes").join("catppuccin_mocha.rasi"),
+            "* { accent: #cba6f7; text: #cdd6f4; }",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("layout.rasi"),
+            "window { width: 800px; height: 400px; }",
+        )
+        .unwrap();
+        let core = dir.path().join("core.rasi");
+        fs::write(
+            &core,
+            r#"
+            @import "layout"
+            window { height: 600px; }
+            @theme "Catppuccin Mocha"
+            * { text: #ffffff; }
+        "#,
+        )
+        .unwrap();
+
+        let theme = ThemeTree::load(&core).unwrap();
+        let px = |w| {
+            theme
+                .get_distance("window", None, w, Distance::px(0.0))
+                .value
+        };
+        assert_eq!(px("width"), 800.0);
+        // The importing file overrides its imports
+        assert_eq!(px("height"), 600.0);
+        assert_eq!(
+            theme.get_color("x", None, "text", Color::BLACK),
+            Color::WHITE
+        );
+        assert_eq!(
+            theme.get_color("x", None, "accent", Color::BLACK),
+            Color::from_hex("#cba6f7").unwrap()
+        );
+
+        let names: Vec<_> = theme
+            .sources
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["core.rasi", "layout.rasi", "catppuccin_mocha.rasi"]);
+    }
+
+    #[test]
+    fn test_late_import_overrides_rules_above_it() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("colors.rasi"),
+            "* { accent: #ff0000; } window { width: 500px; }",
+        )
+        .unwrap();
+        let theme_path = dir.path().join("theme.rasi");
+        fs::write(
+            &theme_path,
+            r#"
+            * { accent: #00ff00; }
+            window { width: 300px; height: 200px; }
+            @import "colors"
+            window { height: 100px; }
+        "#,
+        )
+        .unwrap();
+
+        let theme = ThemeTree::load(&theme_path).unwrap();
+        // The import overrides the rules above it...
+        assert_eq!(
+            theme.get_color("x", None, "accent", Color::BLACK),
+            Color::from_hex("#ff0000").unwrap()
+        );
+        let px = |w| {
+            theme
+                .get_distance("window", None, w, Distance::px(0.0))
+                .value
+        };
+        assert_eq!(px("width"), 500.0);
+        // ...and the rules below it override the import
+        assert_eq!(px("height"), 100.0);
+
+        // The rules before and after the import are layers either side of it
+        let explanation = theme.explain("window", None, "width");
+        assert_eq!(explanation.rules[0].2.layer, 1);
+        assert_eq!(explanation.rules[1].2.layer, 0);
+
+        // Formatting keeps the import between the rules
+        let formatted =
+            crate::theme::format::format(&fs::read_to_string(&theme_path).unwrap(), None).unwrap();
+        let import = formatted.find("@import").unwrap();
+        assert!(formatted.find("300px").unwrap() < import, "{}", formatted);
+        assert!(import < formatted.find("100px").unwrap(), "{}", formatted);
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let a = dir.path().join("a.rasi");
+        fs::write(&a, "@import \"b.rasi\"\n* { x: 1; }").unwrap();
+        fs::write(dir.path().join("b.rasi"), "@import \"a\";").unwrap();
+
+        match ThemeTree::load(&a) {
+            Err(ThemeError::ImportCycle(chain)) => {
+                assert_eq!(chain.len(), 3);
+                assert_eq!(chain.first(), chain.last());
+                let message = ThemeError::ImportCycle(chain).to_string();
+                assert!(message.contains("a.rasi -> "), "{}", message);
+            }
+            other => panic!("expected import cycle, got {:?}", other),
+        }
+
+        let c = dir.path().join("c.rasi");
+        fs::write(&c, "@import \"does-not-exist\"").unwrap();
+        assert!(matches!(
+            ThemeTree::load(&c),
+            Err(ThemeError::ImportNotFound { ref import, .. }) if import == "does-not-exist"
+        ));
+    }
+
+    #[test]
+    fn test_parse_error_recovery() {
+        let theme = ThemeTree::parse(
+            "* { text: #ffffff; }\nwindow { width 10px; }\nentry { height: 20px; }\n",
+        )
+        .unwrap();
+
+        // The broken rule is skipped, the rest still loads
+        assert!(!theme.widgets.contains_key("window"));
+        assert_eq!(
+            theme
+                .get_distance("entry", None, "height", Distance::px(0.0))
+                .value,
+            20.0
+        );
+        assert_eq!(theme.diagnostics.len(), 1);
+        let diag = &theme.diagnostics[0];
+        assert_eq!((diag.line, diag.column), (2, 16));
+        assert!(
+            diag.message.starts_with("unexpected `10`"),
+            "{}",
+            diag.message
+        );
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("broken.rasi");
+        fs::write(&path, "* {\n  text: #fff;\n\nwindow {\n  width: 10px;\n").unwrap();
+
+        match ThemeTree::load(&path) {
+            Err(ThemeError::ParseError(diag)) => {
+                assert_eq!(
+                    diag.file.as_deref(),
+                    Some(path.canonicalize().unwrap().as_path())
+                );
+                assert_eq!(diag.line, 4);
+                assert!(diag.snippet.contains("4 | window {"), "{}", diag.snippet);
+            }
+            other => panic!("expected parse error, got {:?}", other),
+        }
+
+        // Stray characters are reported but do not stop parsing
+        let theme = ThemeTree::parse("window { width: 10px; } $ entry { height: 5px; }").unwrap();
+        assert!(theme.widgets.contains_key("window") && theme.widgets.contains_key("entry"));
+        assert_eq!(theme.diagnostics[0].message, "unexpected character `$`");
+    }
+
+    #[test]
+    fn test_calc_and_viewport_units() {
+        let theme = ThemeTree::parse(
+            "window { width: 60vw; height: calc(100% - 2 * (8px + 4px)); }\n\
+             textbox { width: 40ch; padding: calc(1em / 2); }\n",
+        )
+        .unwrap();
+        assert!(theme.diagnostics.is_empty(), "{:?}", theme.diagnostics);
+
+        let ctx = LayoutContext {
+            parent_size: 500.0,
+            viewport_width: 2000.0,
+            ..Default::default()
+        };
+        let distance = |widget, property| {
+            theme
+                .get_distance(widget, None, property, Distance::px(0.0))
+                .to_pixels(&ctx)
+        };
+        assert_eq!(distance("window", "width"), 1200.0);
+        assert_eq!(distance("window", "height"), 476.0);
+        assert_eq!(distance("textbox", "width"), 320.0);
+        assert_eq!(distance("textbox", "padding"), 8.0);
+    }
+
+    #[test]
+    fn test_transitions() {
+        let theme = ThemeTree::parse(
+            "element { transition: background-color 120ms, text-color 0.2s ease-in-out; }\n\
+             element.selected { transition: all 1s cubic-bezier(0.3, 0, 0.2, 1.4); }\n\
+             textbox { transition: none; }\n",
+        )
+        .unwrap();
+        assert!(theme.diagnostics.is_empty(), "{:?}", theme.diagnostics);
+
+        let transitions = theme.get_transitions("element", None);
+        assert_eq!(
+            transitions,
+            [
+                Transition {
+                    property: "background-color".to_string(),
+                    duration_ms: 120,
+                    easing: Easing::EASE,
+                },
+                Transition {
+                    property: "text-color".to_string(),
+                    duration_ms: 200,
+                    easing: Easing::EASE_IN_OUT,
+                },
+            ]
+        );
+        assert_eq!(
+            theme.get_transitions("element", Some("selected")),
+            [Transition {
+                property: "all".to_string(),
+                duration_ms: 1000,
+                easing: Easing::CubicBezier(0.3, 0.0, 0.2, 1.4),
+            }]
+        );
+        assert!(theme.get_transitions("textbox", None).is_empty());
+        assert!(theme.get_transitions("listview", None).is_empty());
+    }
+
+    #[test]
+    fn test_calc_errors() {
+        let theme = ThemeTree::parse(
+            "window { width: calc(100% - 8); }\n\
+             entry { height: calc(10px * 2px); margin: calc(4px / 0); }\n",
+        )
+        .unwrap();
+
+        let messages: Vec<_> = theme
+            .diagnostics
+            .iter()
+            .map(|d| (d.line, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (
+                    1,
+                    "cannot subtract a length and a plain number in calc() \
+                     (give the number a unit, like 8px)"
+                ),
+                (2, "cannot multiply two lengths in calc()"),
+                (2, "division by zero in calc()"),
+            ]
+        );
+
+        // The rest of the theme still loads
+        assert!(theme.widgets.contains_key("entry"));
+    }
+
+    #[test]
+    fn test_descendant_selectors() {
+        let theme = ThemeTree::parse(
+            r#"
+            * { text-color: white; }
+            listview element { text-color: #ff0000; height: 30; }
+            element { text-color: #0000ff; height: 20; }
+            element.selected { text-color: #00ff00; }
+            mainbox listview element.selected { text-color: #ffff00; }
+            #window.launcher textbox { font-size: 18; }
+            textbox { font-size: 14; }
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            theme.rules[0].selector,
+            Selector::Descendant(vec![
+                Selector::element("listview"),
+                Selector::element("element")
+            ])
+        );
+
+        // The more specific rule wins even though `element` comes later
+        let path = "window mainbox listbox listview element";
+        let c = theme.get_color(path, None, "text-color", Color::BLACK);
+        assert_eq!((c.r, c.b), (1.0, 0.0));
+        assert_eq!(theme.get_number(path, None, "height", 0.0), 30.0);
+        assert_eq!(theme.get_number("element", None, "height", 0.0), 20.0);
+
+        // A state outweighs an extra ancestor; both together beat either
+        let c = theme.get_color(path, Some("selected"), "text-color", Color::BLACK);
+        assert_eq!((c.r, c.g, c.b), (1.0, 1.0, 0.0));
+        let c = theme.get_color(
+            "listview element",
+            Some("selected"),
+            "text-color",
+            Color::BLACK,
+        );
+        assert_eq!((c.r, c.g, c.b), (0.0, 1.0, 0.0));
+
+        // Ancestor states come from the path
+        let textbox = "window.launcher mainbox inputbar textbox";
+        assert_eq!(theme.get_number(textbox, None, "font-size", 0.0), 18.0);
+        let textbox = "window.theme-picker mainbox inputbar textbox";
+        assert_eq!(theme.get_number(textbox, None, "font-size", 0.0), 14.0);
+
+        // Globals still apply to anything not matched
+        let c = theme.get_color("gridview element", None, "text-color", Color::BLACK);
+        assert_eq!(c, Color::rgb(0, 0, 255));
+        let c = theme.get_color(path, None, "border-color", Color::WHITE);
+        assert_eq!(c, Color::WHITE);
+    }
+
+    #[test]
+    fn test_specificity_across_layers() {
+        let mut theme = ThemeTree::parse(
+            "listview element { text-color: red; } element { background-color: red; }",
+        )
+        .unwrap();
+        theme.merge(
+            ThemeTree::parse("element { text-color: blue; background-color: blue; }").unwrap(),
+        );
+
+        // A later layer overrides equally specific rules, not more specific ones
+        let path = "listview element";
+        assert_eq!(
+            theme.get_color(path, None, "text-color", Color::BLACK),
+            Color::RED
+        );
+        assert_eq!(
+            theme.get_color(path, None, "background-color", Color::BLACK),
+            Color::BLUE
+        );
+    }
+
+    #[test]
+    fn test_compound_states() {
+        let theme = ThemeTree::parse(
+            r#"
+            element { background-color: #000000; }
+            element normal.urgent { background-color: #ff0000; }
+            element selected.normal { background-color: #0000ff; }
+            element selected.urgent { background-color: #ff00ff; }
+            element alternate.normal { background-color: #111111; }
+            element.hover { text-color: #00ff00; }
+            element:selected { text-color: #0000ff; }
+        "#,
+        )
+        .unwrap();
+
+        // Rofi's `element selected.urgent` is one widget in two states
+        assert_eq!(
+            theme.rules[3].selector,
+            Selector::Element {
+                name: "element".to_string(),
+                states: vec!["selected".to_string(), "urgent".to_string()],
+            }
+        );
+        assert_eq!(
+            theme.rules[3].selector.to_string(),
+            "element.selected.urgent"
+        );
+
+        let bg = |state| theme.get_color("element", state, "background-color", Color::WHITE);
+        assert_eq!(bg(None), Color::BLACK);
+        assert_eq!(bg(Some("urgent")), Color::RED);
+        assert_eq!(bg(Some("selected")), Color::BLUE);
+        assert_eq!(bg(Some("selected.urgent")), Color::rgb(255, 0, 255));
+        assert_eq!(bg(Some("alternate")), Color::rgb(17, 17, 17));
+
+        // Equally specific: selected outranks hover regardless of source order
+        let text = |state| theme.get_color("element", state, "text-color", Color::WHITE);
+        assert_eq!(text(Some("hover")), Color::GREEN);
+        assert_eq!(text(Some("hover.selected")), Color::BLUE);
+        assert_eq!(text(Some("urgent.hover")), Color::GREEN);
+
+        // State lookups ignore the base rule
+        let state_bg = |state| theme.get_state_color("element", state, "background-color");
+        assert_eq!(state_bg("hover"), None);
+        assert_eq!(state_bg("alternate.hover"), Some(Color::rgb(17, 17, 17)));
+        assert_eq!(state_bg("selected.hover"), Some(Color::BLUE));
+    }
+
+    #[test]
+    fn test_property_origins() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let base = dir.path().join("base.rasi");
+        let core = dir.path().join("core.rasi");
+        let colors = dir.path().join("colors.rasi");
+        fs::write(&base, "* { accent: red; }").unwrap();
+        fs::write(
+            &core,
+            "@import \"base\"\n\
+             * {\n    accent: blue;\n    select-bg: accent;\n}\n\
+             element { background-color: transparent; }\n\
+             element.selected {\n    background-color: var(select-bg);\n}\n",
+        )
+        .unwrap();
+        fs::write(&colors, "\n* { accent: #7aa2f7; }").unwrap();
+
+        let theme = ThemeTree::load_layered(&[&core, &colors]).unwrap();
+        let core = fs::canonicalize(&core).unwrap();
+        let colors = fs::canonicalize(&colors).unwrap();
+        let origin = |file: &Path, line, layer| Origin {
+            file: Some(file.to_path_buf()),
+            line: Some(line),
+            layer,
+        };
+        // base.rasi is layer 0, core.rasi 1 and the overlay 2
+        assert_eq!(theme.layers, 3);
+        assert_eq!(theme.global_origins["accent"], origin(&colors, 2, 2));
+        assert_eq!(theme.global_origins["select-bg"], origin(&core, 4, 1));
+
+        let explanation = theme.explain("element", Some("selected"), "background-color");
+        let rules: Vec<_> = explanation
+            .rules
+            .iter()
+            .map(|(selector, _, origin)| (selector.to_string(), origin.clone()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                ("element.selected".to_string(), origin(&core, 8, 1)),
+                ("element".to_string(), origin(&core, 6, 1)),
+            ]
+        );
+        assert!(explanation.global.is_none());
+        let tokens: Vec<_> = explanation
+            .tokens
+            .iter()
+            .map(|(name, definition)| (name.as_str(), definition.as_ref().map(|d| d.1.clone())))
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                ("select-bg", Some(origin(&core, 4, 1))),
+                ("accent", Some(origin(&colors, 2, 2))),
+            ]
+        );
+        assert_eq!(
+            explanation.resolved.and_then(|v| v.as_color()),
+            Some(Color::rgb(0x7a, 0xa2, 0xf7))
+        );
+    }
+
+    #[test]
+    fn test_explain_fallbacks() {
+        let theme = ThemeTree::parse(
+            "* { text-color: #fff; }\n\
+             element { text-color: inherit; border-color: var(missing, red); }",
+        )
+        .unwrap();
+
+        let explanation = theme.explain("element", None, "text-color");
+        assert_eq!(explanation.rules.len(), 1);
+        let (value, origin) = explanation.global.clone().unwrap();
+        assert_eq!(value.as_color(), Some(Color::WHITE));
+        assert_eq!((origin.file, origin.line, origin.layer), (None, Some(1), 0));
+        assert!(explanation
+            .to_string()
+            .contains("* { text-color: #ffffff; }  <string>:1"));
+
+        let explanation = theme.explain("element", None, "border-color");
+        assert!(matches!(&explanation.tokens[..], [(name, None)] if name == "missing"));
+        assert_eq!(
+            explanation.resolved.and_then(|v| v.as_color()),
+            Some(Color::RED)
+        );
+
+        let explanation = theme.explain("listview", None, "border-radius");
+        assert!(explanation.rules.is_empty() && explanation.resolved.is_none());
+        assert!(explanation
+            .to_string()
+            .ends_with("unset, the widget's built-in default applies\n"));
+    }
 }
@@ -1,7 +1,12 @@
-//! Core theme types: Color, Distance, Padding, Border
+//! Core theme types: Color, Distance, Padding, Border, Paint, Shadow
+
+use std::fmt;
+use std::rc::Rc;
 
 use thiserror::Error;
 
+use crate::render::TextMeasure;
+
 #[derive(Error, Debug)]
 pub enum ParseError {
     #[error("Invalid hex color: {0}")]
@@ -129,14 +134,244 @@ impl Color {
         }
     }
 
+    /// Format as `#rrggbb`, or `#rrggbbaa` if not opaque
+    pub fn to_hex(self) -> String {
+        let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let hex = format!(
+            "#{:02x}{:02x}{:02x}",
+            byte(self.r),
+            byte(self.g),
+            byte(self.b)
+        );
+        if byte(self.a) == 255 {
+            hex
+        } else {
+            format!("{}{:02x}", hex, byte(self.a))
+        }
+    }
+
     /// Convert to packed u32 (ARGB format)
-    pub fn to_u32(&self) -> u32 {
+    pub fn to_u32(self) -> u32 {
         let r = (self.r * 255.0) as u32;
         let g = (self.g * 255.0) as u32;
         let b = (self.b * 255.0) as u32;
         let a = (self.a * 255.0) as u32;
         (a << 24) | (r << 16) | (g << 8) | b
     }
+
+    /// Convert to HSL (hue in degrees 0-360, saturation and lightness 0.0-1.0)
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let l = (max + min) / 2.0;
+        let d = max - min;
+
+        if d.abs() < f32::EPSILON {
+            return (0.0, 0.0, l);
+        }
+
+        let s = if l > 0.5 {
+            d / (2.0 - max - min)
+        } else {
+            d / (max + min)
+        };
+        let h = if max == self.r {
+            (self.g - self.b) / d + if self.g < self.b { 6.0 } else { 0.0 }
+        } else if max == self.g {
+            (self.b - self.r) / d + 2.0
+        } else {
+            (self.r - self.g) / d + 4.0
+        };
+
+        (h * 60.0, s, l)
+    }
+
+    /// Create color from HSL (hue in degrees, saturation and lightness 0.0-1.0) and alpha
+    pub fn from_hsla(h: f32, s: f32, l: f32, a: f32) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+        let h = h.rem_euclid(360.0) / 360.0;
+
+        if s == 0.0 {
+            return Self::from_f32(l, l, l, a);
+        }
+
+        let q = if l < 0.5 {
+            l * (1.0 + s)
+        } else {
+            l + s - l * s
+        };
+        let p = 2.0 * l - q;
+        let channel = |t: f32| {
+            let t = t.rem_euclid(1.0);
+            if t < 1.0 / 6.0 {
+                p + (q - p) * 6.0 * t
+            } else if t < 0.5 {
+                q
+            } else if t < 2.0 / 3.0 {
+                p + (q - p) * (2.0 / 3.0 - t) * 6.0
+            } else {
+                p
+            }
+        };
+
+        Self::from_f32(
+            channel(h + 1.0 / 3.0),
+            channel(h),
+            channel(h - 1.0 / 3.0),
+            a,
+        )
+    }
+
+    /// Increase HSL lightness by `amount` (0.0-1.0)
+    pub fn lighten(&self, amount: f32) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self::from_hsla(h, s, l + amount, self.a)
+    }
+
+    /// Decrease HSL lightness by `amount` (0.0-1.0)
+    pub fn darken(&self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Same color with a different alpha (0.0-1.0)
+    pub fn with_alpha(&self, alpha: f32) -> Self {
+        Self {
+            a: alpha.clamp(0.0, 1.0),
+            ..*self
+        }
+    }
+
+    /// Blend with another color; `weight` is the proportion of `self` (0.0-1.0)
+    pub fn mix(&self, other: &Color, weight: f32) -> Self {
+        let w = weight.clamp(0.0, 1.0);
+        let blend = |a: f32, b: f32| a * w + b * (1.0 - w);
+        Self {
+            r: blend(self.r, other.r),
+            g: blend(self.g, other.g),
+            b: blend(self.b, other.b),
+            a: blend(self.a, other.a),
+        }
+    }
+
+    /// Create color from HWB (hue in degrees, whiteness and blackness 0.0-1.0) and alpha
+    pub fn from_hwba(h: f32, w: f32, b: f32, a: f32) -> Self {
+        let w = w.clamp(0.0, 1.0);
+        let b = b.clamp(0.0, 1.0);
+        if w + b >= 1.0 {
+            // Too much white and black leaves a gray, as in CSS
+            let gray = w / (w + b);
+            return Self::from_f32(gray, gray, gray, a);
+        }
+
+        let pure = Self::from_hsla(h, 1.0, 0.5, a);
+        let channel = |c: f32| c * (1.0 - w - b) + w;
+        Self::from_f32(channel(pure.r), channel(pure.g), channel(pure.b), a)
+    }
+
+    /// Convert to OKLab (lightness 0.0-1.0, a and b roughly -0.4-0.4)
+    pub fn to_oklab(self) -> (f32, f32, f32) {
+        let (r, g, b) = (
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+        );
+
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+        (
+            0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        )
+    }
+
+    /// Create color from OKLab and alpha, clipped to the sRGB gamut
+    pub fn from_oklab(l: f32, a: f32, b: f32, alpha: f32) -> Self {
+        let l_ = (l + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+        let m_ = (l - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+        let s_ = (l - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+
+        let channel = |c: f32| linear_to_srgb(c).clamp(0.0, 1.0);
+        Self::from_f32(
+            channel(4.076_741_7 * l_ - 3.307_711_6 * m_ + 0.230_969_94 * s_),
+            channel(-1.268_438 * l_ + 2.609_757_4 * m_ - 0.341_319_38 * s_),
+            channel(-0.004_196_086_3 * l_ - 0.703_418_6 * m_ + 1.707_614_7 * s_),
+            alpha.clamp(0.0, 1.0),
+        )
+    }
+
+    /// Convert to OKLCH (lightness 0.0-1.0, chroma roughly 0.0-0.4, hue in degrees 0-360)
+    pub fn to_oklch(self) -> (f32, f32, f32) {
+        let (l, a, b) = self.to_oklab();
+        let c = (a * a + b * b).sqrt();
+        let h = if c < 1e-4 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        };
+        (l, c, h)
+    }
+
+    /// Create color from OKLCH (lightness 0.0-1.0, chroma, hue in degrees) and alpha
+    pub fn from_oklcha(l: f32, c: f32, h: f32, a: f32) -> Self {
+        let (sin, cos) = h.to_radians().sin_cos();
+        Self::from_oklab(l, c.max(0.0) * cos, c.max(0.0) * sin, a)
+    }
+
+    /// Blend with another color in OKLab, which keeps perceived lightness
+    /// even; `weight` is the proportion of `self` (0.0-1.0)
+    pub fn mix_oklab(&self, other: &Color, weight: f32) -> Self {
+        let w = weight.clamp(0.0, 1.0);
+        let blend = |a: f32, b: f32| a * w + b * (1.0 - w);
+        let (l1, a1, b1) = self.to_oklab();
+        let (l2, a2, b2) = other.to_oklab();
+        Self::from_oklab(
+            blend(l1, l2),
+            blend(a1, a2),
+            blend(b1, b2),
+            blend(self.a, other.a),
+        )
+    }
+
+    /// WCAG relative luminance (0.0 for black, 1.0 for white), ignoring alpha
+    pub fn relative_luminance(&self) -> f32 {
+        0.2126 * srgb_to_linear(self.r)
+            + 0.7152 * srgb_to_linear(self.g)
+            + 0.0722 * srgb_to_linear(self.b)
+    }
+
+    /// WCAG contrast ratio with another color, from 1.0 to 21.0
+    pub fn contrast_ratio(&self, other: &Color) -> f32 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Whether the color is light enough to need dark text on it
+    pub fn is_light(&self) -> bool {
+        self.contrast_ratio(&Color::BLACK) > self.contrast_ratio(&Color::WHITE)
+    }
+}
+
+/// Decode an sRGB channel to linear light
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a linear light channel as sRGB
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
 }
 
 fn parse_hex_digit(c: char) -> Result<u8, ParseError> {
@@ -159,6 +394,26 @@ pub enum DistanceUnit {
     Em,      // Relative to font size
     Percent, // Percentage of parent
     Mm,      // Physical millimeters
+    Ch,      // Width of "0" in the base font
+    Vw,      // Percentage of the viewport (monitor) width
+    Vh,      // Percentage of the viewport (monitor) height
+    Dp,      // Density-independent pixels, the same as px here
+}
+
+impl DistanceUnit {
+    /// The unit as written in a theme
+    pub fn suffix(self) -> &'static str {
+        match self {
+            DistanceUnit::Px => "px",
+            DistanceUnit::Em => "em",
+            DistanceUnit::Percent => "%",
+            DistanceUnit::Mm => "mm",
+            DistanceUnit::Ch => "ch",
+            DistanceUnit::Vw => "vw",
+            DistanceUnit::Vh => "vh",
+            DistanceUnit::Dp => "dp",
+        }
+    }
 }
 
 /// A distance value with unit
@@ -166,6 +421,8 @@ pub enum DistanceUnit {
 pub struct Distance {
     pub value: f64,
     pub unit: DistanceUnit,
+    /// A `calc()` expression; when set, `value` and `unit` are unused
+    pub calc: Option<Box<CalcExpr>>,
 }
 
 impl Default for Distance {
@@ -175,41 +432,247 @@ impl Default for Distance {
 }
 
 impl Distance {
-    pub fn px(value: f64) -> Self {
+    pub fn new(value: f64, unit: DistanceUnit) -> Self {
         Self {
             value,
-            unit: DistanceUnit::Px,
+            unit,
+            calc: None,
         }
     }
 
+    pub fn px(value: f64) -> Self {
+        Self::new(value, DistanceUnit::Px)
+    }
+
     pub fn em(value: f64) -> Self {
-        Self {
-            value,
-            unit: DistanceUnit::Em,
-        }
+        Self::new(value, DistanceUnit::Em)
     }
 
     pub fn percent(value: f64) -> Self {
-        Self {
-            value,
-            unit: DistanceUnit::Percent,
-        }
+        Self::new(value, DistanceUnit::Percent)
     }
 
     pub fn mm(value: f64) -> Self {
+        Self::new(value, DistanceUnit::Mm)
+    }
+
+    pub fn ch(value: f64) -> Self {
+        Self::new(value, DistanceUnit::Ch)
+    }
+
+    pub fn vw(value: f64) -> Self {
+        Self::new(value, DistanceUnit::Vw)
+    }
+
+    pub fn vh(value: f64) -> Self {
+        Self::new(value, DistanceUnit::Vh)
+    }
+
+    pub fn dp(value: f64) -> Self {
+        Self::new(value, DistanceUnit::Dp)
+    }
+
+    /// A `calc()` distance; the expression must be a length (see `CalcExpr::is_length`)
+    pub fn calc(expr: CalcExpr) -> Self {
         Self {
-            value,
-            unit: DistanceUnit::Mm,
+            value: 0.0,
+            unit: DistanceUnit::Px,
+            calc: Some(Box::new(expr)),
+        }
+    }
+
+    /// The distance in unscaled pixels, if it doesn't depend on the layout
+    ///
+    /// Like plain numbers, a distance's value is taken as pixels whatever
+    /// its unit, but `calc()` expressions only resolve when made of pixels.
+    pub fn as_number(&self) -> Option<f64> {
+        match &self.calc {
+            Some(expr) => expr.as_number(),
+            None => Some(self.value),
         }
     }
 
     /// Resolve to physical pixels given context
     pub fn to_pixels(&self, ctx: &LayoutContext) -> f32 {
+        if let Some(ref expr) = self.calc {
+            return expr.to_pixels(ctx);
+        }
         match self.unit {
-            DistanceUnit::Px => (self.value as f32) * ctx.scale_factor,
+            DistanceUnit::Px | DistanceUnit::Dp => (self.value as f32) * ctx.scale_factor,
             DistanceUnit::Em => (self.value as f32) * ctx.base_font_size * ctx.scale_factor,
             DistanceUnit::Percent => (self.value as f32 / 100.0) * ctx.parent_size,
             DistanceUnit::Mm => (self.value as f32) * ctx.dpi / 25.4,
+            DistanceUnit::Ch => (self.value as f32) * ctx.char_width * ctx.scale_factor,
+            DistanceUnit::Vw => (self.value as f32 / 100.0) * ctx.viewport_width,
+            DistanceUnit::Vh => (self.value as f32 / 100.0) * ctx.viewport_height,
+        }
+    }
+}
+
+impl fmt::Display for Distance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.calc {
+            Some(expr) => write!(f, "calc({})", expr),
+            None => write!(f, "{}{}", self.value, self.unit.suffix()),
+        }
+    }
+}
+
+/// Operator in a `calc()` expression
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CalcOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl CalcOp {
+    pub fn symbol(self) -> char {
+        match self {
+            CalcOp::Add => '+',
+            CalcOp::Sub => '-',
+            CalcOp::Mul => '*',
+            CalcOp::Div => '/',
+        }
+    }
+
+    /// Binding strength: `*` and `/` bind tighter than `+` and `-`
+    fn precedence(self) -> u8 {
+        match self {
+            CalcOp::Add | CalcOp::Sub => 1,
+            CalcOp::Mul | CalcOp::Div => 2,
+        }
+    }
+
+    fn verb(self) -> &'static str {
+        match self {
+            CalcOp::Add => "add",
+            CalcOp::Sub => "subtract",
+            CalcOp::Mul => "multiply",
+            CalcOp::Div => "divide",
+        }
+    }
+
+    fn apply(self, left: f64, right: f64) -> f64 {
+        match self {
+            CalcOp::Add => left + right,
+            CalcOp::Sub => left - right,
+            CalcOp::Mul => left * right,
+            CalcOp::Div => left / right,
+        }
+    }
+}
+
+/// Why a `calc()` expression is invalid
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CalcError {
+    #[error("cannot {} a length and a plain number in calc() (give the number a unit, like 8px)", .0.verb())]
+    MixedSum(CalcOp),
+    #[error("cannot multiply two lengths in calc()")]
+    LengthTimesLength,
+    #[error("cannot divide by a length in calc()")]
+    DivideByLength,
+    #[error("division by zero in calc()")]
+    DivisionByZero,
+    #[error("calc() must result in a length, not a plain number")]
+    NotALength,
+}
+
+/// A `calc()` expression over lengths and plain numbers
+///
+/// Expressions are type checked as they are built, as in CSS: sums need
+/// two lengths or two numbers, products at least one number and divisors
+/// must be non-zero numbers.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CalcExpr {
+    Length(Distance),
+    Number(f64),
+    Binary(CalcOp, Box<CalcExpr>, Box<CalcExpr>),
+}
+
+impl CalcExpr {
+    /// Combine two expressions, checking their types
+    pub fn binary(op: CalcOp, left: CalcExpr, right: CalcExpr) -> Result<Self, CalcError> {
+        match op {
+            CalcOp::Add | CalcOp::Sub if left.is_length() != right.is_length() => {
+                return Err(CalcError::MixedSum(op));
+            }
+            CalcOp::Mul if left.is_length() && right.is_length() => {
+                return Err(CalcError::LengthTimesLength);
+            }
+            CalcOp::Div if right.is_length() => return Err(CalcError::DivideByLength),
+            CalcOp::Div if right.as_number() == Some(0.0) => {
+                return Err(CalcError::DivisionByZero);
+            }
+            _ => {}
+        }
+        Ok(CalcExpr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    /// Whether the expression is a length rather than a plain number
+    pub fn is_length(&self) -> bool {
+        match self {
+            CalcExpr::Length(_) => true,
+            CalcExpr::Number(_) => false,
+            CalcExpr::Binary(CalcOp::Mul, left, right) => left.is_length() || right.is_length(),
+            CalcExpr::Binary(_, left, _) => left.is_length(),
+        }
+    }
+
+    /// Evaluate without a layout: plain numbers and lengths in pixels
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            CalcExpr::Length(d) => match (&d.calc, d.unit) {
+                (None, DistanceUnit::Px | DistanceUnit::Dp) => Some(d.value),
+                (Some(expr), _) => expr.as_number(),
+                _ => None,
+            },
+            CalcExpr::Number(n) => Some(*n),
+            CalcExpr::Binary(op, left, right) => {
+                Some(op.apply(left.as_number()?, right.as_number()?))
+            }
+        }
+    }
+
+    /// Resolve to physical pixels given context
+    pub fn to_pixels(&self, ctx: &LayoutContext) -> f32 {
+        match self {
+            CalcExpr::Length(d) => d.to_pixels(ctx),
+            CalcExpr::Number(n) => *n as f32,
+            CalcExpr::Binary(op, left, right) => {
+                op.apply(left.to_pixels(ctx) as f64, right.to_pixels(ctx) as f64) as f32
+            }
+        }
+    }
+}
+
+impl fmt::Display for CalcExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalcExpr::Length(d) => write!(f, "{}", d),
+            CalcExpr::Number(n) => write!(f, "{}", n),
+            CalcExpr::Binary(op, left, right) => {
+                // Operators are left-associative, so a right operand of the
+                // same precedence needs parentheses to keep its grouping
+                let operand = |expr: &CalcExpr, f: &mut fmt::Formatter<'_>, tight: bool| {
+                    let loose = match expr {
+                        CalcExpr::Binary(inner, ..) => {
+                            inner.precedence() < op.precedence()
+                                || (tight && inner.precedence() == op.precedence())
+                        }
+                        _ => false,
+                    };
+                    if loose {
+                        write!(f, "({})", expr)
+                    } else {
+                        write!(f, "{}", expr)
+                    }
+                };
+                operand(left, f, false)?;
+                write!(f, " {} ", op.symbol())?;
+                operand(right, f, true)
+            }
         }
     }
 }
@@ -221,6 +684,14 @@ pub struct LayoutContext {
     pub scale_factor: f32,
     pub base_font_size: f32,
     pub parent_size: f32, // Width or height depending on orientation
+    /// Viewport (monitor) size in physical pixels, for `vw` and `vh`
+    pub viewport_width: f32,
+    pub viewport_height: f32,
+    /// Width of "0" in the base font in unscaled pixels, for `ch`
+    pub char_width: f32,
+    /// Measures text as the backend draws it; without one, text widths are
+    /// estimated from `char_width`
+    pub text_measure: Option<Rc<dyn TextMeasure>>,
 }
 
 impl Default for LayoutContext {
@@ -230,6 +701,23 @@ impl Default for LayoutContext {
             scale_factor: 1.0,
             base_font_size: 16.0,
             parent_size: 100.0,
+            viewport_width: 1920.0,
+            viewport_height: 1080.0,
+            char_width: 8.0,
+            text_measure: None,
+        }
+    }
+}
+
+impl LayoutContext {
+    /// Width in pixels of a single line of text, as the backend draws it
+    pub fn text_width(&self, text: &str, font_family: &str, font_size: f32, bold: bool) -> f32 {
+        match &self.text_measure {
+            Some(measure) => measure.text_width(text, font_family, font_size, bold),
+            None => {
+                let char_width = self.char_width * font_size / self.base_font_size.max(1.0);
+                text.chars().count() as f32 * char_width
+            }
         }
     }
 }
@@ -332,6 +820,7 @@ pub enum Orientation {
 
 impl Orientation {
     /// Parse from string (used in theme)
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "horizontal" => Some(Orientation::Horizontal),
@@ -339,10 +828,18 @@ impl Orientation {
             _ => None,
         }
     }
+
+    /// The keyword as written in a theme
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Orientation::Horizontal => "horizontal",
+            Orientation::Vertical => "vertical",
+        }
+    }
 }
 
 /// Image scaling mode for background images
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub enum ImageScale {
     /// No scaling, display at native size
     #[default]
@@ -357,6 +854,7 @@ pub enum ImageScale {
 
 impl ImageScale {
     /// Parse from string (used in theme)
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "none" => Some(ImageScale::None),
@@ -366,6 +864,16 @@ impl ImageScale {
             _ => None,
         }
     }
+
+    /// The keyword as written in a theme
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImageScale::None => "none",
+            ImageScale::Width => "width",
+            ImageScale::Height => "height",
+            ImageScale::Both => "both",
+        }
+    }
 }
 
 /// Image source for background-image property
@@ -377,8 +885,202 @@ pub struct ImageSource {
     pub scale: ImageScale,
 }
 
+/// Shape of a gradient
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientKind {
+    /// `linear-gradient`: CSS angle in degrees, 0 pointing up and 90 right
+    Linear { angle: f32 },
+    /// `radial-gradient`: from the center to the farthest corner, as a
+    /// circle or an ellipse with the box's aspect ratio
+    Radial { circle: bool },
+}
+
+/// A color stop of a resolved gradient
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientStop {
+    /// Position along the gradient, 0.0-1.0
+    pub position: f32,
+    pub color: Color,
+}
+
+/// A resolved `linear-gradient()` or `radial-gradient()`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<GradientStop>,
+}
+
+impl Gradient {
+    /// Build a gradient from colors with optional positions (0.0-1.0)
+    ///
+    /// Missing positions are filled in as CSS does: the first stop defaults
+    /// to 0, the last to 1 and the ones between are spread evenly between
+    /// their neighbours. A stop before the previous one is moved up to it.
+    pub fn new(kind: GradientKind, stops: &[(Color, Option<f32>)]) -> Self {
+        let mut positions: Vec<Option<f32>> = stops.iter().map(|(_, p)| *p).collect();
+        if let Some(first) = positions.first_mut() {
+            first.get_or_insert(0.0);
+        }
+        if let Some(end) = positions.last_mut() {
+            end.get_or_insert(1.0);
+        }
+
+        let mut resolved = vec![0.0; positions.len()];
+        let mut previous = 0;
+        for i in 0..positions.len() {
+            let Some(position) = positions[i] else {
+                continue;
+            };
+            // Spread the stops without a position since the previous one
+            let start = resolved[previous];
+            let position = if i == 0 {
+                position
+            } else {
+                position.max(start)
+            };
+            for (j, slot) in resolved.iter_mut().enumerate().take(i).skip(previous + 1) {
+                *slot = start + (position - start) * (j - previous) as f32 / (i - previous) as f32;
+            }
+            resolved[i] = position;
+            previous = i;
+        }
+
+        Self {
+            kind,
+            stops: stops
+                .iter()
+                .zip(resolved)
+                .map(|((color, _), position)| GradientStop {
+                    position,
+                    color: *color,
+                })
+                .collect(),
+        }
+    }
+
+    /// Start and end points of a linear gradient's line across `rect`
+    ///
+    /// As in CSS, the line runs through the center at the gradient's angle
+    /// and is long enough for the corners to get the first and last colors.
+    pub fn linear_points(&self, rect: Rect) -> ((f32, f32), (f32, f32)) {
+        let angle = match self.kind {
+            GradientKind::Linear { angle } => angle,
+            GradientKind::Radial { .. } => 180.0,
+        };
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let half = (rect.width * sin.abs() + rect.height * cos.abs()) / 2.0;
+        let (cx, cy) = (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+        (
+            (cx - sin * half, cy + cos * half),
+            (cx + sin * half, cy - cos * half),
+        )
+    }
+
+    /// Horizontal and vertical radius of a radial gradient over `rect`
+    pub fn radial_radii(&self, rect: Rect) -> (f32, f32) {
+        match self.kind {
+            GradientKind::Radial { circle: true } => {
+                let r = rect.width.hypot(rect.height) / 2.0;
+                (r, r)
+            }
+            _ => (
+                rect.width / std::f32::consts::SQRT_2,
+                rect.height / std::f32::consts::SQRT_2,
+            ),
+        }
+    }
+}
+
+/// What a background or border is filled with
+#[derive(Clone, Debug, PartialEq)]
+pub enum Paint {
+    Solid(Color),
+    Gradient(Gradient),
+}
+
+impl Default for Paint {
+    fn default() -> Self {
+        Paint::Solid(Color::TRANSPARENT)
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint::Solid(color)
+    }
+}
+
+impl Paint {
+    /// Whether anything would be drawn (some color isn't fully transparent)
+    pub fn is_visible(&self) -> bool {
+        match self {
+            Paint::Solid(color) => color.a > 0.0,
+            Paint::Gradient(gradient) => gradient.stops.iter().any(|s| s.color.a > 0.0),
+        }
+    }
+
+    /// The color of a solid paint, or the first stop of a gradient
+    pub fn color(&self) -> Color {
+        match self {
+            Paint::Solid(color) => *color,
+            Paint::Gradient(gradient) => gradient
+                .stops
+                .first()
+                .map_or(Color::TRANSPARENT, |s| s.color),
+        }
+    }
+}
+
+/// A resolved `box-shadow`, in unscaled pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shadow {
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub blur: f32,
+    pub spread: f32,
+    pub color: Color,
+}
+
+impl Shadow {
+    /// Most rectangles a blurred shadow is drawn with
+    const MAX_LAYERS: usize = 8;
+
+    /// Concentric layers approximating the shadow's blur, as (outset, color)
+    ///
+    /// Each layer is the box grown by `outset` (before the offset). Layers
+    /// overlap towards the inside, so drawn on top of each other they fade
+    /// from the full color within `spread - blur / 2` to nothing at
+    /// `spread + blur / 2`.
+    pub fn layers(&self) -> Vec<(f32, Color)> {
+        if self.blur <= 0.0 {
+            return vec![(self.spread, self.color)];
+        }
+        let count = ((self.blur / 2.0).ceil() as usize).clamp(1, Self::MAX_LAYERS);
+        // Alpha per layer such that all of them together give the color's
+        let alpha = 1.0 - (1.0 - self.color.a).powf(1.0 / count as f32);
+        let step = self.blur / count as f32;
+        (0..count)
+            .map(|i| {
+                let outset = self.spread + self.blur / 2.0 - step * (i as f32 + 0.5);
+                (outset, self.color.with_alpha(alpha))
+            })
+            .collect()
+    }
+
+    /// Scale the offsets, blur and spread by a DPI factor
+    pub fn scaled(&self, scale: f32) -> Self {
+        Self {
+            offset_x: self.offset_x * scale,
+            offset_y: self.offset_y * scale,
+            blur: self.blur * scale,
+            spread: self.spread * scale,
+            color: self.color,
+        }
+    }
+}
+
 /// Rectangle for layout
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Rect {
     pub x: f32,
     pub y: f32,
@@ -441,6 +1143,66 @@ mod tests {
         // 8-digit with alpha
         let c = Color::from_hex("#ff000080").unwrap();
         assert!((c.a - 0.5).abs() < 0.01);
+
+        for hex in ["#1e1e2e", "#cba6f7e6", "#000000"] {
+            assert_eq!(Color::from_hex(hex).unwrap().to_hex(), hex);
+        }
+    }
+
+    #[test]
+    fn test_color_hsl_round_trip() {
+        let c = Color::from_hex("#03edf9").unwrap();
+        let (h, s, l) = c.to_hsl();
+        let back = Color::from_hsla(h, s, l, c.a);
+        assert!((back.r - c.r).abs() < 0.001);
+        assert!((back.g - c.g).abs() < 0.001);
+        assert!((back.b - c.b).abs() < 0.001);
+
+        // Lightness adjustments clamp at white/black
+        assert_eq!(Color::RED.lighten(1.0), Color::WHITE);
+        assert_eq!(Color::RED.darken(1.0), Color::BLACK);
+        assert_eq!(Color::RED.mix(&Color::BLUE, 1.0), Color::RED);
+    }
+
+    #[test]
+    fn test_color_spaces() {
+        let close = |a: Color, b: Color| {
+            (a.r - b.r).abs() < 0.002 && (a.g - b.g).abs() < 0.002 && (a.b - b.b).abs() < 0.002
+        };
+
+        // HWB: no white or black is the pure hue; too much is a gray
+        assert_eq!(Color::from_hwba(120.0, 0.0, 0.0, 1.0), Color::GREEN);
+        assert_eq!(
+            Color::from_hwba(0.0, 0.6, 0.6, 1.0),
+            Color::from_f32(0.5, 0.5, 0.5, 1.0)
+        );
+
+        // OKLab round trips, and white is L=1 with no chroma
+        for hex in ["#1e1e2e", "#cba6f7", "#03edf9", "#ff0000"] {
+            let c = Color::from_hex(hex).unwrap();
+            let (l, a, b) = c.to_oklab();
+            assert!(close(Color::from_oklab(l, a, b, 1.0), c), "{}", hex);
+            let (l, ch, h) = c.to_oklch();
+            assert!(close(Color::from_oklcha(l, ch, h, 1.0), c), "{}", hex);
+        }
+        let (l, c, _) = Color::WHITE.to_oklch();
+        assert!((l - 1.0).abs() < 0.001 && c < 0.001);
+
+        // Perceptual mixing blends lightness, not sRGB values
+        let mid = Color::BLACK.mix_oklab(&Color::WHITE, 0.5);
+        assert_eq!(mid.to_hex(), "#636363");
+        assert_eq!(Color::RED.mix_oklab(&Color::BLUE, 1.0).to_hex(), "#ff0000");
+    }
+
+    #[test]
+    fn test_color_contrast() {
+        assert!((Color::WHITE.relative_luminance() - 1.0).abs() < 0.001);
+        assert_eq!(Color::BLACK.relative_luminance(), 0.0);
+        assert!((Color::BLACK.contrast_ratio(&Color::WHITE) - 21.0).abs() < 0.01);
+        assert_eq!(Color::RED.contrast_ratio(&Color::RED), 1.0);
+
+        assert!(Color::from_hex("#eff1f5").unwrap().is_light());
+        assert!(!Color::from_hex("#1e1e2e").unwrap().is_light());
     }
 
     #[test]
@@ -450,15 +1212,165 @@ mod tests {
             scale_factor: 1.5,
             base_font_size: 16.0,
             parent_size: 200.0,
+            viewport_width: 1000.0,
+            viewport_height: 800.0,
+            char_width: 8.0,
+            text_measure: None,
         };
 
         // Px scales with scale_factor
         assert_eq!(Distance::px(10.0).to_pixels(&ctx), 15.0);
+        assert_eq!(Distance::dp(10.0).to_pixels(&ctx), 15.0);
 
         // Em is relative to font size
         assert_eq!(Distance::em(1.0).to_pixels(&ctx), 24.0); // 16 * 1.5
+        assert_eq!(Distance::ch(2.0).to_pixels(&ctx), 24.0); // 2 * 8 * 1.5
 
-        // Percent is relative to parent
+        // Percent is relative to parent, vw/vh to the viewport
         assert_eq!(Distance::percent(50.0).to_pixels(&ctx), 100.0);
+        assert_eq!(Distance::vw(60.0).to_pixels(&ctx), 600.0);
+        assert_eq!(Distance::vh(25.0).to_pixels(&ctx), 200.0);
+    }
+
+    #[test]
+    fn test_calc_expressions() {
+        let ctx = LayoutContext {
+            scale_factor: 2.0,
+            parent_size: 400.0,
+            ..Default::default()
+        };
+        let length = |d| CalcExpr::Length(d);
+
+        // calc(100% - 48px)
+        let expr = CalcExpr::binary(
+            CalcOp::Sub,
+            length(Distance::percent(100.0)),
+            length(Distance::px(48.0)),
+        )
+        .unwrap();
+        assert!(expr.is_length());
+        assert_eq!(Distance::calc(expr).to_pixels(&ctx), 304.0);
+
+        // calc(10px * 3 / 2) is still a length
+        let product = CalcExpr::binary(
+            CalcOp::Mul,
+            length(Distance::px(10.0)),
+            CalcExpr::Number(3.0),
+        )
+        .unwrap();
+        let quotient = CalcExpr::binary(CalcOp::Div, product, CalcExpr::Number(2.0)).unwrap();
+        assert_eq!(quotient.as_number(), Some(15.0));
+        assert_eq!(Distance::calc(quotient.clone()).as_number(), Some(15.0));
+        assert_eq!(Distance::calc(quotient).to_pixels(&ctx), 30.0);
+
+        // Layout-dependent expressions have no fixed value
+        let relative = CalcExpr::binary(
+            CalcOp::Add,
+            length(Distance::em(1.0)),
+            length(Distance::px(2.0)),
+        )
+        .unwrap();
+        assert_eq!(relative.as_number(), None);
+
+        // Type errors
+        assert_eq!(
+            CalcExpr::binary(
+                CalcOp::Add,
+                length(Distance::px(1.0)),
+                CalcExpr::Number(2.0)
+            ),
+            Err(CalcError::MixedSum(CalcOp::Add))
+        );
+        assert_eq!(
+            CalcExpr::binary(
+                CalcOp::Mul,
+                length(Distance::px(1.0)),
+                length(Distance::px(2.0))
+            ),
+            Err(CalcError::LengthTimesLength)
+        );
+        assert_eq!(
+            CalcExpr::binary(
+                CalcOp::Div,
+                CalcExpr::Number(1.0),
+                length(Distance::px(2.0))
+            ),
+            Err(CalcError::DivideByLength)
+        );
+        assert_eq!(
+            CalcExpr::binary(
+                CalcOp::Div,
+                length(Distance::px(1.0)),
+                CalcExpr::Number(0.0)
+            ),
+            Err(CalcError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_gradient_stop_positions() {
+        let linear = GradientKind::Linear { angle: 90.0 };
+        let positions = |stops: &[(Color, Option<f32>)]| -> Vec<f32> {
+            Gradient::new(linear, stops)
+                .stops
+                .iter()
+                .map(|s| s.position)
+                .collect()
+        };
+
+        let c = Color::RED;
+        assert_eq!(
+            positions(&[(c, None), (c, None), (c, None)]),
+            [0.0, 0.5, 1.0]
+        );
+        assert_eq!(
+            positions(&[(c, None), (c, Some(0.2)), (c, None), (c, None)]),
+            [0.0, 0.2, 0.6, 1.0]
+        );
+        // A stop before the previous one is moved up to it
+        assert_eq!(positions(&[(c, Some(0.5)), (c, Some(0.3))]), [0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_gradient_geometry() {
+        let rect = Rect::new(0.0, 0.0, 200.0, 100.0);
+        let point = |angle: f32| {
+            let ((x0, y0), (x1, y1)) =
+                Gradient::new(GradientKind::Linear { angle }, &[]).linear_points(rect);
+            [x0.round(), y0.round(), x1.round(), y1.round()]
+        };
+        // 180deg (to bottom) runs from the top edge to the bottom edge
+        assert_eq!(point(180.0), [100.0, 0.0, 100.0, 100.0]);
+        assert_eq!(point(90.0), [0.0, 50.0, 200.0, 50.0]);
+
+        let circle = Gradient::new(GradientKind::Radial { circle: true }, &[]);
+        let (rx, ry) = circle.radial_radii(rect);
+        assert_eq!(rx, ry);
+        assert!((rx - 111.8).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_shadow_layers() {
+        let shadow = Shadow {
+            offset_x: 0.0,
+            offset_y: 4.0,
+            blur: 8.0,
+            spread: 2.0,
+            color: Color::BLACK.with_alpha(0.5),
+        };
+        let layers = shadow.layers();
+        assert_eq!(layers.len(), 4);
+        // From the faint outer edge inwards
+        assert_eq!(layers[0].0, 5.0);
+        assert_eq!(layers[3].0, -1.0);
+        // All layers together give the shadow color's alpha
+        let covered = 1.0 - layers.iter().fold(1.0, |t, (_, c)| t * (1.0 - c.a));
+        assert!((covered - 0.5).abs() < 0.001);
+
+        let sharp = Shadow {
+            blur: 0.0,
+            ..shadow
+        };
+        assert_eq!(sharp.layers(), vec![(2.0, shadow.color)]);
     }
 }
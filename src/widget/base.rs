@@ -1,9 +1,199 @@
 //! Base widget types and traits for the layout system
 
-use crate::theme::types::{LayoutContext, Orientation, Rect};
+use crate::theme::ast::{Value, STATE_PRECEDENCE};
+use crate::theme::tree::ThemeTree;
+use crate::theme::types::{Color, Distance, LayoutContext, Orientation, Paint, Rect, Shadow};
+
+use super::flex::{Align, FlexWrap, JustifyContent};
+use super::overlay::Placement;
+
+bitflags::bitflags! {
+    /// Widget rendering state: a set of states, empty when normal
+    ///
+    /// Flags are declared in `STATE_PRECEDENCE` order, lowest first, so a
+    /// larger bit outranks the smaller ones.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct WidgetState: u16 {
+        const ALTERNATE = 1 << 0;
+        const ACTIVE = 1 << 1;
+        const URGENT = 1 << 2;
+        const HOVER = 1 << 3;
+        const FOCUSED = 1 << 4;
+        const SELECTED = 1 << 5;
+        const PRESSED = 1 << 6;
+        const DISABLED = 1 << 7;
+    }
+}
+
+impl WidgetState {
+    pub const NORMAL: Self = Self::empty();
+
+    /// Get the CSS state for theme lookups, e.g. `selected.urgent`
+    pub fn selector(&self) -> Option<String> {
+        let names: Vec<&str> = STATE_PRECEDENCE
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.bits() & (1 << i) != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        (!names.is_empty()).then(|| names.join("."))
+    }
+
+    /// Every combination of the states in `self`, including normal
+    pub fn subsets(self) -> impl Iterator<Item = WidgetState> {
+        let all = self.bits();
+        let mut next = Some(all);
+        std::iter::from_fn(move || {
+            let bits = next?;
+            next = (bits != 0).then(|| (bits - 1) & all);
+            Some(WidgetState::from_bits_retain(bits))
+        })
+    }
+}
+
+/// A style value that can be read from a theme property
+pub trait ThemeValue: Clone + PartialEq {
+    /// Resolve a property's value; None if it isn't a valid value of this type
+    fn resolve(theme: &ThemeTree, value: &Value) -> Option<Self>;
+}
+
+impl ThemeValue for Color {
+    fn resolve(theme: &ThemeTree, value: &Value) -> Option<Self> {
+        theme.resolve_color(value)
+    }
+}
+
+impl ThemeValue for Paint {
+    fn resolve(theme: &ThemeTree, value: &Value) -> Option<Self> {
+        theme.resolve_paint(value)
+    }
+}
+
+impl ThemeValue for f32 {
+    fn resolve(theme: &ThemeTree, value: &Value) -> Option<Self> {
+        theme.resolve_number(value).map(|n| n as f32)
+    }
+}
+
+/// A `box-shadow`, where any value that isn't a shadow (like `none`) turns
+/// it off
+impl ThemeValue for Option<Shadow> {
+    fn resolve(theme: &ThemeTree, value: &Value) -> Option<Self> {
+        Some(theme.resolve_shadow(value))
+    }
+}
+
+/// A style value that varies with widget state
+///
+/// Looking up a combination like `SELECTED | HOVER` picks the entry with the
+/// most matching states, then the one with the highest state, so every
+/// widget resolves overlapping states the same way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateValues<T> {
+    normal: T,
+    states: Vec<(WidgetState, T)>,
+}
+
+/// A color that varies with widget state
+pub type StateColors = StateValues<Color>;
+
+/// A background or border paint that varies with widget state
+pub type StatePaints = StateValues<Paint>;
+
+/// A distance, opacity or other number that varies with widget state
+pub type StateNumbers = StateValues<f32>;
+
+impl<T: ThemeValue> StateValues<T> {
+    pub fn new(normal: T) -> Self {
+        Self {
+            normal,
+            states: Vec::new(),
+        }
+    }
+
+    /// Add or replace the value for a state or combination of states
+    pub fn with(mut self, state: WidgetState, value: T) -> Self {
+        if state.is_empty() {
+            self.normal = value;
+        } else {
+            self.states.retain(|(s, _)| *s != state);
+            self.states.push((state, value));
+        }
+        self
+    }
+
+    /// Get the value for a widget in `state`
+    pub fn get(&self, state: WidgetState) -> T {
+        self.get_state(state).unwrap_or_else(|| self.normal.clone())
+    }
+
+    /// Get the best entry other than normal for `state`
+    fn get_state(&self, state: WidgetState) -> Option<T> {
+        self.states
+            .iter()
+            .filter(|(s, _)| state.contains(*s))
+            .max_by_key(|(s, _)| (s.bits().count_ones(), s.bits()))
+            .map(|(_, value)| value.clone())
+    }
+
+    /// Load a property for every combination of `states` from the theme
+    ///
+    /// For each combination a rule for that state (`element.selected`) wins,
+    /// then the matching entry of `defaults`, then the normal value; the
+    /// normal value itself comes from the usual cascade. Defaults stand in
+    /// for a missing state rule so a widget's base color doesn't hide its
+    /// built-in selection or hover color.
+    pub fn from_theme(
+        theme: &ThemeTree,
+        path: &str,
+        property: &str,
+        states: WidgetState,
+        defaults: &Self,
+    ) -> Self {
+        let normal = theme
+            .get_value(path, None, property)
+            .and_then(|v| T::resolve(theme, v))
+            .unwrap_or_else(|| defaults.normal.clone());
+        let defaults = defaults.clone().with(WidgetState::NORMAL, normal);
+        Self::from_theme_states(theme, path, property, states, &defaults)
+    }
+
+    /// Like `from_theme`, but only rules for states are read; the normal
+    /// value is always the default's
+    ///
+    /// For properties whose plain form means something else, like the task
+    /// panel's `item-background-color` (the hover background).
+    pub fn from_theme_states(
+        theme: &ThemeTree,
+        path: &str,
+        property: &str,
+        states: WidgetState,
+        defaults: &Self,
+    ) -> Self {
+        // Fewest states first, keeping only entries that change the result
+        let mut combinations: Vec<_> = states.subsets().collect();
+        combinations.sort_by_key(|s| s.bits().count_ones());
+
+        let mut values = Self::new(defaults.normal.clone());
+        for state in combinations {
+            let Some(selector) = state.selector() else {
+                continue;
+            };
+            let value = theme
+                .get_state_value(path, &selector, property)
+                .and_then(|v| T::resolve(theme, v))
+                .or_else(|| defaults.get_state(state))
+                .unwrap_or_else(|| defaults.normal.clone());
+            if values.get(state) != value {
+                values.states.push((state, value));
+            }
+        }
+        values
+    }
+}
 
 /// Per-corner border radii for rounded rectangles
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct CornerRadii {
     pub top_left: f32,
     pub top_right: f32,
@@ -54,7 +244,7 @@ impl CornerRadii {
 }
 
 /// Size constraint for layout calculations
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Size {
     pub width: f32,
     pub height: f32,
@@ -169,11 +359,16 @@ impl Constraints {
 }
 
 /// Layout properties extracted from theme
-#[derive(Clone, Debug, Default)]
+///
+/// Containers lay out their children as a flexbox (see `flex`); the flex
+/// properties of a child are read by its parent.
+#[derive(Clone, Debug)]
 pub struct LayoutProps {
     /// Orientation for containers (horizontal/vertical)
     pub orientation: Orientation,
-    /// Whether widget should expand to fill available space
+    /// Whether widget should expand to fill available space, like
+    /// `flex: 1`: grow from a zero basis unless `flex-grow`/`flex-basis` say
+    /// otherwise
     pub expand: bool,
     /// Spacing between children (for containers)
     pub spacing: f32,
@@ -181,11 +376,155 @@ pub struct LayoutProps {
     pub fixed_width: Option<f32>,
     /// Fixed height (None = auto)
     pub fixed_height: Option<f32>,
+    /// The theme's `width`, which may be relative (`50%`, `40ch`, `60vw`,
+    /// `calc()`); `resolved` turns it into `fixed_width`
+    pub width: Option<Distance>,
+    /// The theme's `height`; `resolved` turns it into `fixed_height`
+    pub height: Option<Distance>,
     /// Padding (top, right, bottom, left)
     pub padding: (f32, f32, f32, f32),
+    /// `flex-grow` (None = from `expand`)
+    pub grow: Option<f32>,
+    /// `flex-shrink`, 1 by default
+    pub shrink: f32,
+    /// `flex-basis` (None = from `expand`, the fixed size or the measured size)
+    pub basis: Option<f32>,
+    pub min_width: Option<f32>,
+    pub max_width: Option<f32>,
+    pub min_height: Option<f32>,
+    pub max_height: Option<f32>,
+    /// Margin outside the widget (top, right, bottom, left)
+    pub margin: (f32, f32, f32, f32),
+    /// Placement of leftover space between children (for containers)
+    pub justify: JustifyContent,
+    /// Placement of children across the orientation (for containers)
+    pub align_items: Align,
+    /// Overrides the parent's `align_items` for this widget
+    pub align_self: Option<Align>,
+    /// Whether children wrap onto new lines (for containers)
+    pub wrap: FlexWrap,
+    /// `position: absolute` and where to place the widget (see `overlay`)
+    pub placement: Placement,
+}
+
+impl Default for LayoutProps {
+    fn default() -> Self {
+        Self {
+            orientation: Orientation::default(),
+            expand: false,
+            spacing: 0.0,
+            fixed_width: None,
+            fixed_height: None,
+            width: None,
+            height: None,
+            padding: (0.0, 0.0, 0.0, 0.0),
+            grow: None,
+            shrink: 1.0,
+            basis: None,
+            min_width: None,
+            max_width: None,
+            min_height: None,
+            max_height: None,
+            margin: (0.0, 0.0, 0.0, 0.0),
+            justify: JustifyContent::default(),
+            align_items: Align::default(),
+            align_self: None,
+            wrap: FlexWrap::default(),
+            placement: Placement::default(),
+        }
+    }
 }
 
 impl LayoutProps {
+    /// Load the layout properties of the widget at `path` from theme
+    pub fn from_theme(theme: &ThemeTree, path: &str, default: &LayoutProps) -> Self {
+        let mut props = LayoutProps {
+            orientation: theme.get_orientation(path, default.orientation),
+            expand: theme.get_expand(path, default.expand),
+            spacing: theme
+                .get_spacing(path, Distance::px(default.spacing as f64))
+                .value as f32,
+            width: theme
+                .get_resolved(path, None, "width")
+                .and_then(|v| v.as_distance())
+                .or(default.width.clone()),
+            height: theme
+                .get_resolved(path, None, "height")
+                .and_then(|v| v.as_distance())
+                .or(default.height.clone()),
+            padding: (
+                theme.get_number(path, None, "padding-top", default.padding.0 as f64) as f32,
+                theme.get_number(path, None, "padding-right", default.padding.1 as f64) as f32,
+                theme.get_number(path, None, "padding-bottom", default.padding.2 as f64) as f32,
+                theme.get_number(path, None, "padding-left", default.padding.3 as f64) as f32,
+            ),
+            ..default.clone()
+        };
+        props.load_flex(theme, path);
+        props.placement.load(theme, path);
+        props
+    }
+
+    /// Load only the flexbox properties (`flex-grow`, `margin`,
+    /// `justify-content`, ...) of the widget at `path`, keeping the others
+    pub fn load_flex(&mut self, theme: &ThemeTree, path: &str) {
+        let number = |property: &str| {
+            theme
+                .get_resolved(path, None, property)
+                .and_then(|v| v.as_number())
+                .map(|n| n as f32)
+        };
+        let keyword = |property: &str| {
+            theme
+                .get_resolved(path, None, property)
+                .and_then(|v| v.as_string())
+        };
+
+        self.grow = number("flex-grow").or(self.grow);
+        self.shrink = number("flex-shrink").unwrap_or(self.shrink);
+        self.basis = number("flex-basis").or(self.basis);
+        self.min_width = number("min-width").or(self.min_width);
+        self.max_width = number("max-width").or(self.max_width);
+        self.min_height = number("min-height").or(self.min_height);
+        self.max_height = number("max-height").or(self.max_height);
+
+        if let Some(margin) = theme
+            .get_resolved(path, None, "margin")
+            .and_then(|v| v.as_padding())
+        {
+            let px = |d: &Distance| d.as_number().unwrap_or(0.0) as f32;
+            self.margin = (
+                px(&margin.top),
+                px(&margin.right),
+                px(&margin.bottom),
+                px(&margin.left),
+            );
+        }
+        self.margin = (
+            number("margin-top").unwrap_or(self.margin.0),
+            number("margin-right").unwrap_or(self.margin.1),
+            number("margin-bottom").unwrap_or(self.margin.2),
+            number("margin-left").unwrap_or(self.margin.3),
+        );
+
+        if let Some(justify) = keyword("justify-content").and_then(|s| JustifyContent::from_str(&s))
+        {
+            self.justify = justify;
+        }
+        if let Some(align) = keyword("align-items").and_then(|s| Align::from_str(&s)) {
+            self.align_items = align;
+        }
+        match keyword("align-self").as_deref() {
+            // `auto` defers to the parent's `align-items`
+            Some("auto") => self.align_self = None,
+            Some(align) => self.align_self = Align::from_str(align).or(self.align_self),
+            None => {}
+        }
+        if let Some(wrap) = keyword("flex-wrap").and_then(|s| FlexWrap::from_str(&s)) {
+            self.wrap = wrap;
+        }
+    }
+
     pub fn padding_horizontal(&self) -> f32 {
         self.padding.1 + self.padding.3
     }
@@ -193,6 +532,58 @@ impl LayoutProps {
     pub fn padding_vertical(&self) -> f32 {
         self.padding.0 + self.padding.2
     }
+
+    /// The `flex-grow` factor, 1 for `expand` unless set
+    pub fn flex_grow(&self) -> f32 {
+        self.grow.unwrap_or(if self.expand { 1.0 } else { 0.0 })
+    }
+
+    /// The `flex-basis`, 0 for `expand` unless set
+    pub fn flex_basis(&self) -> Option<f32> {
+        self.basis.or(if self.expand { Some(0.0) } else { None })
+    }
+
+    /// The props with every length multiplied by `scale`
+    pub fn scaled(&self, factor: f32) -> Self {
+        let scale = |v: Option<f32>| v.map(|v| v * factor);
+        let scale4 =
+            |(t, r, b, l): (f32, f32, f32, f32)| (t * factor, r * factor, b * factor, l * factor);
+        Self {
+            spacing: self.spacing * factor,
+            fixed_width: scale(self.fixed_width),
+            fixed_height: scale(self.fixed_height),
+            padding: scale4(self.padding),
+            basis: scale(self.basis),
+            min_width: scale(self.min_width),
+            max_width: scale(self.max_width),
+            min_height: scale(self.min_height),
+            max_height: scale(self.max_height),
+            margin: scale4(self.margin),
+            placement: self.placement.scaled(factor),
+            ..self.clone()
+        }
+    }
+
+    /// The props in physical pixels: lengths scaled by the context's scale
+    /// factor, and the theme's `width`/`height` resolved against it, with
+    /// percentages of `parent`
+    pub fn resolved(&self, ctx: &LayoutContext, parent: Size) -> Self {
+        let resolve = |distance: &Option<Distance>, parent_size: f32| {
+            let ctx = LayoutContext {
+                parent_size,
+                ..ctx.clone()
+            };
+            distance.as_ref().map(|d| d.to_pixels(&ctx))
+        };
+        let scaled = self.scaled(ctx.scale_factor);
+        Self {
+            fixed_width: resolve(&self.width, parent.width).or(scaled.fixed_width),
+            fixed_height: resolve(&self.height, parent.height).or(scaled.fixed_height),
+            width: None,
+            height: None,
+            ..scaled
+        }
+    }
 }
 
 /// Measured size from a widget
@@ -263,6 +654,104 @@ mod tests {
         assert_eq!(size.height, 100.0);
     }
 
+    #[test]
+    fn test_widget_state_selector() {
+        // Flag order follows STATE_PRECEDENCE
+        let names: Vec<String> = WidgetState::all()
+            .iter_names()
+            .map(|(name, _)| name.to_lowercase())
+            .collect();
+        assert_eq!(names, STATE_PRECEDENCE);
+
+        assert_eq!(WidgetState::NORMAL.selector(), None);
+        let state = WidgetState::URGENT | WidgetState::SELECTED;
+        assert_eq!(state.selector().as_deref(), Some("urgent.selected"));
+        assert_eq!(state.subsets().count(), 4);
+    }
+
+    #[test]
+    fn test_state_colors() {
+        let theme = ThemeTree::parse(
+            r#"
+            element { background-color: #000000; }
+            element normal.urgent { background-color: #ff0000; }
+            element selected.normal { background-color: #0000ff; }
+        "#,
+        )
+        .unwrap();
+        let defaults = StateColors::new(Color::WHITE)
+            .with(WidgetState::HOVER, Color::GREEN)
+            .with(WidgetState::SELECTED, Color::WHITE);
+        let mask = WidgetState::SELECTED | WidgetState::HOVER | WidgetState::URGENT;
+        let colors =
+            StateColors::from_theme(&theme, "element", "background-color", mask, &defaults);
+
+        assert_eq!(colors.get(WidgetState::NORMAL), Color::BLACK);
+        // The hover default survives the base rule
+        assert_eq!(colors.get(WidgetState::HOVER), Color::GREEN);
+        assert_eq!(colors.get(WidgetState::URGENT), Color::RED);
+        // Selected outranks hover and urgent
+        assert_eq!(
+            colors.get(WidgetState::SELECTED | WidgetState::HOVER),
+            Color::BLUE
+        );
+        assert_eq!(
+            colors.get(WidgetState::SELECTED | WidgetState::URGENT),
+            Color::BLUE
+        );
+        // States outside the mask are ignored
+        assert_eq!(
+            colors.get(WidgetState::URGENT | WidgetState::FOCUSED),
+            Color::RED
+        );
+
+        // Only state rules are read; the base rule doesn't replace the default
+        let colors =
+            StateColors::from_theme_states(&theme, "element", "background-color", mask, &defaults);
+        assert_eq!(colors.get(WidgetState::NORMAL), Color::WHITE);
+        assert_eq!(colors.get(WidgetState::URGENT), Color::RED);
+    }
+
+    #[test]
+    fn test_state_paints() {
+        let theme = ThemeTree::parse(
+            r#"
+            element { background-color: linear-gradient(90deg, #000000, #ffffff); box-shadow: 0 2px 4px #000000; }
+            element selected.normal { background-color: #0000ff; box-shadow: none; }
+        "#,
+        )
+        .unwrap();
+        let mask = WidgetState::SELECTED | WidgetState::HOVER;
+
+        let paints = StatePaints::from_theme(
+            &theme,
+            "element",
+            "background-color",
+            mask,
+            &StatePaints::new(Paint::default()),
+        );
+        assert!(matches!(
+            paints.get(WidgetState::NORMAL),
+            Paint::Gradient(_)
+        ));
+        assert!(matches!(paints.get(WidgetState::HOVER), Paint::Gradient(_)));
+        assert_eq!(
+            paints.get(WidgetState::SELECTED | WidgetState::HOVER),
+            Paint::Solid(Color::BLUE)
+        );
+
+        // `none` in a state rule turns the base shadow off
+        let shadows = StateValues::<Option<Shadow>>::from_theme(
+            &theme,
+            "element",
+            "box-shadow",
+            mask,
+            &StateValues::new(None),
+        );
+        assert_eq!(shadows.get(WidgetState::NORMAL).map(|s| s.blur), Some(4.0));
+        assert_eq!(shadows.get(WidgetState::SELECTED), None);
+    }
+
     #[test]
     fn test_layout_props_padding() {
         let props = LayoutProps {
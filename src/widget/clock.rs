@@ -19,6 +19,7 @@ pub enum ClockPosition {
 
 impl ClockPosition {
     /// Parse position from string (e.g., "top-right", "middle-center")
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "top-left" => ClockPosition::TopLeft,
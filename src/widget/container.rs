@@ -1,11 +1,14 @@
 //! Container widget - HBox/VBox layout for child widgets
+//!
+//! Children are laid out as a flexbox, see `flex`.
 
-use crate::platform::win32::Renderer;
 use crate::platform::Event;
+use crate::render::DisplayList;
 use crate::theme::tree::ThemeTree;
-use crate::theme::types::{Color, LayoutContext, Orientation, Rect};
+use crate::theme::types::{LayoutContext, Orientation, Paint, Rect, Shadow};
 
 use super::base::{ArrangedBounds, Constraints, CornerRadii, LayoutProps, MeasuredSize, Size};
+use super::flex::{self, FlexItem};
 use super::{EventResult, Widget, WidgetState, WidgetStyle};
 
 /// A container that arranges children horizontally or vertically
@@ -27,20 +30,22 @@ pub struct Container {
 /// Style for container widget
 #[derive(Clone, Debug)]
 pub struct ContainerStyle {
-    pub background_color: Color,
-    pub border_color: Color,
+    pub background_color: Paint,
+    pub border_color: Paint,
     pub border_width: f32,
     /// Per-corner border radii
     pub border_radii: CornerRadii,
+    pub box_shadow: Option<Shadow>,
 }
 
 impl Default for ContainerStyle {
     fn default() -> Self {
         Self {
-            background_color: Color::TRANSPARENT,
-            border_color: Color::TRANSPARENT,
+            background_color: Paint::default(),
+            border_color: Paint::default(),
             border_width: 0.0,
             border_radii: CornerRadii::zero(),
+            box_shadow: None,
         }
     }
 }
@@ -72,16 +77,17 @@ impl ContainerStyle {
         };
 
         Self {
-            background_color: theme.get_color(
+            background_color: theme.get_paint(
                 name,
                 state,
                 "background-color",
                 default.background_color,
             ),
-            border_color: theme.get_color(name, state, "border-color", default.border_color),
+            border_color: theme.get_paint(name, state, "border-color", default.border_color),
             border_width: theme.get_number(name, state, "border-width", default.border_width as f64)
                 as f32,
             border_radii,
+            box_shadow: theme.get_shadow(name, state, "box-shadow"),
         }
     }
 }
@@ -94,7 +100,7 @@ impl Container {
             children: Vec::new(),
             layout: LayoutProps::default(),
             style: ContainerStyle::default(),
-            state: WidgetState::Normal,
+            state: WidgetState::NORMAL,
             child_bounds: Vec::new(),
         }
     }
@@ -151,30 +157,23 @@ impl Container {
 
     /// Load layout properties from theme
     pub fn load_from_theme(&mut self, theme: &ThemeTree) {
-        self.layout.orientation = theme.get_orientation(&self.name, Orientation::Vertical);
-        self.layout.expand = theme.get_expand(&self.name, false);
-
-        let spacing = theme.get_spacing(&self.name, crate::theme::types::Distance::px(0.0));
-        self.layout.spacing = spacing.value as f32;
-
-        // Load padding
-        let default_padding = 0.0;
-        self.layout.padding = (
-            theme.get_number(&self.name, None, "padding-top", default_padding) as f32,
-            theme.get_number(&self.name, None, "padding-right", default_padding) as f32,
-            theme.get_number(&self.name, None, "padding-bottom", default_padding) as f32,
-            theme.get_number(&self.name, None, "padding-left", default_padding) as f32,
-        );
+        let name = self.name.clone();
+        self.load_from_theme_at(theme, &name);
+    }
+
+    /// Load layout properties from theme for the container at `path`, its name
+    /// preceded by its ancestors (see `ThemeTree::get_value`)
+    pub fn load_from_theme_at(&mut self, theme: &ThemeTree, path: &str) {
+        self.layout = LayoutProps::from_theme(theme, path, &LayoutProps::default());
 
         // Load style
-        self.style = ContainerStyle::from_theme(theme, &self.name, None);
+        self.style = ContainerStyle::from_theme(theme, path, None);
     }
 
     /// Measure the container and its children
     pub fn measure(&self, constraints: Constraints, ctx: &LayoutContext) -> MeasuredSize {
-        let (pad_t, pad_r, pad_b, pad_l) = self.layout.padding;
-        let pad_h = pad_l + pad_r;
-        let pad_v = pad_t + pad_b;
+        let pad_h = self.layout.padding_horizontal();
+        let pad_v = self.layout.padding_vertical();
 
         // Adjust constraints for padding
         let inner_constraints = Constraints {
@@ -188,179 +187,49 @@ impl Container {
             ),
         };
 
-        if self.children.is_empty() {
-            return MeasuredSize::new(pad_h, pad_v);
-        }
-
-        let spacing = self.layout.spacing;
-        let num_gaps = (self.children.len() - 1) as f32;
-        let total_spacing = spacing * num_gaps;
-
-        match self.layout.orientation {
-            Orientation::Horizontal => {
-                // Horizontal: children side by side
-                let mut total_width = 0.0f32;
-                let mut max_height = 0.0f32;
-
-                for child in &self.children {
-                    let child_size = child.measure(inner_constraints, ctx);
-                    total_width += child_size.size.width;
-                    max_height = max_height.max(child_size.size.height);
-                }
-
-                total_width += total_spacing;
-
-                MeasuredSize::new(total_width + pad_h, max_height + pad_v)
-            }
-            Orientation::Vertical => {
-                // Vertical: children stacked
-                let mut max_width = 0.0f32;
-                let mut total_height = 0.0f32;
-
-                for child in &self.children {
-                    let child_size = child.measure(inner_constraints, ctx);
-                    max_width = max_width.max(child_size.size.width);
-                    total_height += child_size.size.height;
-                }
-
-                total_height += total_spacing;
-
-                MeasuredSize::new(max_width + pad_h, total_height + pad_v)
-            }
-        }
+        let measurements = self.measure_children(inner_constraints, ctx);
+        let size = flex::measure(
+            &self.layout,
+            &self.flex_items(&measurements),
+            constraints.max,
+        );
+        MeasuredSize::new(size.width, size.height)
     }
 
     /// Arrange children within the given bounds
     pub fn arrange(&mut self, bounds: Rect, ctx: &LayoutContext) {
-        let (pad_t, pad_r, pad_b, pad_l) = self.layout.padding;
-
-        // Inner content area
-        let content = Rect::new(
-            bounds.x + pad_l,
-            bounds.y + pad_t,
-            bounds.width - pad_l - pad_r,
-            bounds.height - pad_t - pad_b,
+        let content = Size::new(
+            (bounds.width - self.layout.padding_horizontal()).max(0.0),
+            (bounds.height - self.layout.padding_vertical()).max(0.0),
         );
 
-        self.child_bounds.clear();
-
-        if self.children.is_empty() {
-            return;
-        }
-
-        let spacing = self.layout.spacing;
-
-        // First pass: measure all children to determine sizes
-        let constraints = Constraints::loose(Size::new(content.width, content.height));
-        let measurements: Vec<MeasuredSize> = self
-            .children
-            .iter()
-            .map(|c| c.measure(constraints, ctx))
+        // First pass: measure all children to determine their basis
+        let measurements = self.measure_children(Constraints::loose(content), ctx);
+        self.child_bounds = flex::arrange(&self.layout, bounds, &self.flex_items(&measurements))
+            .into_iter()
+            .map(ArrangedBounds::from_rect)
             .collect();
 
-        // Count expanding children
-        let expanding_count = self
-            .children
-            .iter()
-            .filter(|c| c.layout_props().expand)
-            .count();
-
-        match self.layout.orientation {
-            Orientation::Horizontal => {
-                let num_gaps = (self.children.len() - 1) as f32;
-                let total_spacing = spacing * num_gaps;
-
-                // Calculate total fixed width
-                let fixed_width: f32 = measurements
-                    .iter()
-                    .zip(self.children.iter())
-                    .filter(|(_, c)| !c.layout_props().expand)
-                    .map(|(m, _)| m.size.width)
-                    .sum();
-
-                // Remaining space for expanding children
-                let remaining = (content.width - fixed_width - total_spacing).max(0.0);
-                let expand_width = if expanding_count > 0 {
-                    remaining / expanding_count as f32
-                } else {
-                    0.0
-                };
-
-                // Position children
-                let mut x = content.x;
-                for (i, (child, measured)) in
-                    self.children.iter().zip(measurements.iter()).enumerate()
-                {
-                    let width = if child.layout_props().expand {
-                        expand_width
-                    } else {
-                        measured.size.width
-                    };
-
-                    self.child_bounds.push(ArrangedBounds::new(
-                        x,
-                        content.y,
-                        width,
-                        content.height,
-                    ));
-
-                    x += width;
-                    if i < self.children.len() - 1 {
-                        x += spacing;
-                    }
-                }
-            }
-            Orientation::Vertical => {
-                let num_gaps = (self.children.len() - 1) as f32;
-                let total_spacing = spacing * num_gaps;
-
-                // Calculate total fixed height
-                let fixed_height: f32 = measurements
-                    .iter()
-                    .zip(self.children.iter())
-                    .filter(|(_, c)| !c.layout_props().expand)
-                    .map(|(m, _)| m.size.height)
-                    .sum();
-
-                // Remaining space for expanding children
-                let remaining = (content.height - fixed_height - total_spacing).max(0.0);
-                let expand_height = if expanding_count > 0 {
-                    remaining / expanding_count as f32
-                } else {
-                    0.0
-                };
-
-                // Position children
-                let mut y = content.y;
-                for (i, (child, measured)) in
-                    self.children.iter().zip(measurements.iter()).enumerate()
-                {
-                    let height = if child.layout_props().expand {
-                        expand_height
-                    } else {
-                        measured.size.height
-                    };
-
-                    self.child_bounds.push(ArrangedBounds::new(
-                        content.x,
-                        y,
-                        content.width,
-                        height,
-                    ));
-
-                    y += height;
-                    if i < self.children.len() - 1 {
-                        y += spacing;
-                    }
-                }
-            }
-        }
-
         // Recursively arrange children that are containers
         for (child, bounds) in self.children.iter_mut().zip(self.child_bounds.iter()) {
             child.arrange(bounds.rect, ctx);
         }
     }
+
+    fn measure_children(&self, constraints: Constraints, ctx: &LayoutContext) -> Vec<Size> {
+        self.children
+            .iter()
+            .map(|child| child.measure(constraints, ctx).size)
+            .collect()
+    }
+
+    fn flex_items<'a>(&'a self, measurements: &[Size]) -> Vec<FlexItem<'a>> {
+        self.children
+            .iter()
+            .zip(measurements)
+            .map(|(child, &size)| FlexItem::new(child.layout_props(), size))
+            .collect()
+    }
 }
 
 impl Widget for Container {
@@ -384,52 +253,29 @@ impl Widget for Container {
         result
     }
 
-    fn render(
-        &self,
-        renderer: &mut Renderer,
-        rect: Rect,
-        ctx: &LayoutContext,
-    ) -> Result<(), windows::core::Error> {
-        use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-
-        let bounds = D2D_RECT_F {
-            left: rect.x,
-            top: rect.y,
-            right: rect.x + rect.width,
-            bottom: rect.y + rect.height,
-        };
-
+    fn render(&self, list: &mut DisplayList, rect: Rect, ctx: &LayoutContext) {
         let radii = self.style.border_radii;
 
-        // Draw background if not transparent
-        if self.style.background_color.a > 0.0 {
-            if !radii.is_zero() {
-                renderer.fill_rounded_rect_corners(bounds, radii, self.style.background_color)?;
-            } else {
-                renderer.fill_rect(bounds, self.style.background_color)?;
-            }
+        // Draw shadow
+        if let Some(ref shadow) = self.style.box_shadow {
+            list.shadow(rect, radii, shadow);
         }
 
+        // Draw background
+        list.fill(rect, radii, &self.style.background_color);
+
         // Draw border if present
-        if self.style.border_width > 0.0 && self.style.border_color.a > 0.0 {
-            if !radii.is_zero() {
-                renderer.draw_rounded_rect_corners(
-                    bounds,
-                    radii,
-                    self.style.border_color,
-                    self.style.border_width,
-                )?;
-            } else {
-                renderer.draw_rect(bounds, self.style.border_color, self.style.border_width)?;
-            }
-        }
+        list.stroke(
+            rect,
+            radii,
+            &self.style.border_color,
+            self.style.border_width,
+        );
 
         // Render children at their arranged positions
         for (child, child_bounds) in self.children.iter().zip(self.child_bounds.iter()) {
-            child.render(renderer, child_bounds.rect, ctx)?;
+            child.render(list, child_bounds.rect, ctx);
         }
-
-        Ok(())
     }
 
     fn state(&self) -> WidgetState {
@@ -458,6 +304,10 @@ impl Widget for Container {
         Container::arrange(self, bounds, ctx)
     }
 
+    fn restyle(&mut self, theme: &ThemeTree, path: &str) {
+        self.load_from_theme_at(theme, path);
+    }
+
     fn layout_props(&self) -> &LayoutProps {
         &self.layout
     }
@@ -470,6 +320,44 @@ impl Widget for Container {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::widget::Panel;
+
+    #[test]
+    fn test_arrange_from_theme() {
+        let theme = ThemeTree::parse(
+            r#"
+            inputbar {
+                orientation: horizontal;
+                padding-left: 10;
+                spacing: 5;
+                align-items: center;
+                children: [ "prompt", "entry" ];
+            }
+            prompt { width: 40; height: 20; margin: 0 5; }
+            entry { flex-grow: 1; max-width: 200; }
+        "#,
+        )
+        .unwrap();
+        let mut container = Container::new("inputbar");
+        container.load_from_theme(&theme);
+        for name in ["prompt", "entry"] {
+            let mut panel = Panel::new(name);
+            panel.load_from_theme(&theme);
+            container.add_child(Box::new(panel));
+        }
+
+        let ctx = LayoutContext::default();
+        container.arrange(Rect::new(0.0, 0.0, 400.0, 50.0), &ctx);
+        let rects: Vec<Rect> = container.child_bounds.iter().map(|b| b.rect).collect();
+        // The panel measures at its full height, so it fills the row
+        assert_eq!(
+            rects,
+            [
+                Rect::new(15.0, 15.0, 40.0, 20.0),
+                Rect::new(65.0, 0.0, 200.0, 50.0)
+            ]
+        );
+    }
 
     #[test]
     fn test_container_creation() {
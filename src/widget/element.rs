@@ -1,13 +1,16 @@
 //! Element widget - a single row item with optional icon and text
 
-use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-
-use crate::platform::win32::Renderer;
+use crate::animation::{AnimatedProperties, AnimatedValue, Transition};
+use crate::matcher::positions_to_ranges;
 use crate::platform::Event;
+use crate::render::{DisplayList, TextRun};
 use crate::theme::tree::ThemeTree;
-use crate::theme::types::{Color, LayoutContext, Rect};
+use crate::theme::types::{Color, LayoutContext, Paint, Rect, Shadow};
 
-use super::base::{Constraints, LayoutProps, MeasuredSize};
+use super::base::{
+    Constraints, CornerRadii, LayoutProps, MeasuredSize, StateColors, StateNumbers, StatePaints,
+    StateValues,
+};
 use super::{EventResult, Widget, WidgetState, WidgetStyle};
 
 /// Data for a single element/row
@@ -21,6 +24,10 @@ pub struct ElementData {
     pub icon_path: Option<String>,
     /// User data (e.g., launch command)
     pub user_data: String,
+    /// Character indices in `text` matched by the current search query (for highlighting)
+    pub match_positions: Vec<usize>,
+    /// States that come from the item itself, e.g. `URGENT` or `ACTIVE`
+    pub state: WidgetState,
 }
 
 impl ElementData {
@@ -30,6 +37,8 @@ impl ElementData {
             subtext: None,
             icon_path: None,
             user_data: user_data.into(),
+            match_positions: Vec::new(),
+            state: WidgetState::NORMAL,
         }
     }
 
@@ -42,130 +51,149 @@ impl ElementData {
         self.icon_path = Some(icon_path.into());
         self
     }
+
+    pub fn with_match_positions(mut self, positions: Vec<usize>) -> Self {
+        self.match_positions = positions;
+        self
+    }
+
+    /// Mark the item as urgent, active, etc.
+    pub fn with_state(mut self, state: WidgetState) -> Self {
+        self.state = state;
+        self
+    }
 }
 
 /// Style for element widget
 #[derive(Clone, Debug)]
 pub struct ElementStyle {
-    pub background_color: Color,
-    pub background_color_selected: Color,
-    pub background_color_hover: Color,
-    pub text_color: Color,
-    pub text_color_selected: Color,
-    pub subtext_color: Color,
+    pub background_color: StatePaints,
+    pub border_color: StatePaints,
+    pub border_width: StateNumbers,
+    pub box_shadow: StateValues<Option<Shadow>>,
+    pub text_color: StateColors,
+    pub subtext_color: StateColors,
+    pub highlight_color: StateColors,
     pub font_family: String,
     pub font_size: f32,
     pub subtext_font_size: f32,
-    pub padding_horizontal: f32,
+    pub padding_horizontal: StateNumbers,
     pub padding_vertical: f32,
     pub icon_size: f32,
     pub icon_spacing: f32,
     pub height: f32,
-    pub border_radius: f32,
+    pub border_radius: StateNumbers,
+    pub opacity: StateNumbers,
+    /// How long color and size changes between states take
+    pub transition: Vec<Transition>,
 }
 
 impl Default for ElementStyle {
     fn default() -> Self {
         Self {
-            background_color: Color::TRANSPARENT,
-            background_color_selected: Color::from_hex("#264f78").unwrap_or(Color::BLUE),
-            background_color_hover: Color::from_hex("#3c3c3c").unwrap_or(Color::BLACK),
-            text_color: Color::from_hex("#d4d4d4").unwrap_or(Color::WHITE),
-            text_color_selected: Color::WHITE,
-            subtext_color: Color::from_hex("#808080").unwrap_or(Color::WHITE),
+            background_color: StatePaints::new(Paint::default())
+                .with(
+                    WidgetState::SELECTED,
+                    Color::from_hex("#264f78").unwrap_or(Color::BLUE).into(),
+                )
+                .with(
+                    WidgetState::HOVER,
+                    Color::from_hex("#3c3c3c").unwrap_or(Color::BLACK).into(),
+                ),
+            border_color: StatePaints::new(Paint::default()),
+            border_width: StateNumbers::new(0.0),
+            box_shadow: StateValues::new(None),
+            text_color: StateColors::new(Color::from_hex("#d4d4d4").unwrap_or(Color::WHITE))
+                .with(WidgetState::SELECTED, Color::WHITE),
+            subtext_color: StateColors::new(Color::from_hex("#808080").unwrap_or(Color::WHITE)),
+            highlight_color: StateColors::new(Color::from_hex("#4fc1ff").unwrap_or(Color::WHITE))
+                .with(WidgetState::SELECTED, Color::WHITE),
             font_family: "Segoe UI".to_string(),
             font_size: 14.0,
             subtext_font_size: 11.0,
-            padding_horizontal: 12.0,
+            padding_horizontal: StateNumbers::new(12.0),
             padding_vertical: 8.0,
             icon_size: 24.0,
             icon_spacing: 8.0,
             height: 40.0,
-            border_radius: 0.0,
+            border_radius: StateNumbers::new(0.0),
+            opacity: StateNumbers::new(1.0),
+            transition: Vec::new(),
         }
     }
 }
 
 impl ElementStyle {
+    /// States an element's colors and sizes can be themed for
+    pub const STATES: WidgetState = WidgetState::SELECTED
+        .union(WidgetState::HOVER)
+        .union(WidgetState::PRESSED)
+        .union(WidgetState::URGENT)
+        .union(WidgetState::ACTIVE)
+        .union(WidgetState::ALTERNATE);
+
     /// Load style from theme
     pub fn from_theme(theme: &ThemeTree, state: Option<&str>) -> Self {
+        Self::from_theme_at(theme, "element", state)
+    }
+
+    /// Load style from theme for elements at `path`, e.g. `listview element`
+    pub fn from_theme_at(theme: &ThemeTree, path: &str, state: Option<&str>) -> Self {
         let default = Self::default();
+        let colors = |property: &str, defaults: &StateColors| {
+            StateColors::from_theme(theme, path, property, Self::STATES, defaults)
+        };
+        let paints = |property: &str, defaults: &StatePaints| {
+            StatePaints::from_theme(theme, path, property, Self::STATES, defaults)
+        };
+        let numbers = |property: &str, defaults: &StateNumbers| {
+            StateNumbers::from_theme(theme, path, property, Self::STATES, defaults)
+        };
         let style = Self {
-            background_color: theme.get_color(
-                "element",
-                state,
-                "background-color",
-                default.background_color,
-            ),
-            background_color_selected: theme.get_color(
-                "element",
-                Some("selected"),
-                "background-color",
-                default.background_color_selected,
-            ),
-            background_color_hover: theme.get_color(
-                "element",
-                Some("hover"),
-                "background-color",
-                default.background_color_hover,
-            ),
-            text_color: theme.get_color("element", state, "text-color", default.text_color),
-            text_color_selected: theme.get_color(
-                "element",
-                Some("selected"),
-                "text-color",
-                default.text_color_selected,
+            background_color: paints("background-color", &default.background_color),
+            border_color: paints("border-color", &default.border_color),
+            border_width: numbers("border-width", &default.border_width),
+            box_shadow: StateValues::from_theme(
+                theme,
+                path,
+                "box-shadow",
+                Self::STATES,
+                &default.box_shadow,
             ),
-            subtext_color: theme.get_color(
-                "element",
-                state,
-                "subtext-color",
-                default.subtext_color,
-            ),
-            font_family: theme.get_string("element", state, "font-family", &default.font_family),
-            font_size: theme.get_number("element", state, "font-size", default.font_size as f64)
-                as f32,
+            text_color: colors("text-color", &default.text_color),
+            subtext_color: colors("subtext-color", &default.subtext_color),
+            highlight_color: colors("highlight-color", &default.highlight_color),
+            font_family: theme.get_string(path, state, "font-family", &default.font_family),
+            font_size: theme.get_number(path, state, "font-size", default.font_size as f64) as f32,
             subtext_font_size: theme.get_number(
-                "element",
+                path,
                 state,
                 "subtext-font-size",
                 default.subtext_font_size as f64,
             ) as f32,
-            padding_horizontal: theme.get_number(
-                "element",
-                state,
-                "padding-horizontal",
-                default.padding_horizontal as f64,
-            ) as f32,
+            padding_horizontal: numbers("padding-horizontal", &default.padding_horizontal),
             padding_vertical: theme.get_number(
-                "element",
+                path,
                 state,
                 "padding-vertical",
                 default.padding_vertical as f64,
             ) as f32,
-            icon_size: theme.get_number("element", state, "icon-size", default.icon_size as f64)
+            icon_size: theme.get_number(path, state, "icon-size", default.icon_size as f64) as f32,
+            icon_spacing: theme.get_number(path, state, "icon-spacing", default.icon_spacing as f64)
                 as f32,
-            icon_spacing: theme.get_number(
-                "element",
-                state,
-                "icon-spacing",
-                default.icon_spacing as f64,
-            ) as f32,
-            height: theme.get_number("element", state, "height", default.height as f64) as f32,
-            border_radius: theme.get_number(
-                "element",
-                state,
-                "border-radius",
-                default.border_radius as f64,
-            ) as f32,
+            height: theme.get_number(path, state, "height", default.height as f64) as f32,
+            border_radius: numbers("border-radius", &default.border_radius),
+            opacity: numbers("opacity", &default.opacity),
+            transition: theme.get_transitions(path, state),
         };
+        let hover_bg = style.background_color.get(WidgetState::HOVER).color();
         crate::log!(
             "ElementStyle::from_theme - border_radius={}, hover_bg=({},{},{},{})",
-            style.border_radius,
-            style.background_color_hover.r,
-            style.background_color_hover.g,
-            style.background_color_hover.b,
-            style.background_color_hover.a
+            style.border_radius.get(WidgetState::NORMAL),
+            hover_bg.r,
+            hover_bg.g,
+            hover_bg.b,
+            hover_bg.a
         );
         style
     }
@@ -179,12 +207,10 @@ pub struct Element {
     layout: LayoutProps,
     /// Visual style
     style: ElementStyle,
-    /// Widget state
+    /// Widget state: selected, hovered, alternate row, ...
     state: WidgetState,
-    /// Whether this element is selected
-    selected: bool,
-    /// Whether mouse is hovering
-    hovered: bool,
+    /// Color and size transitions running after a state change
+    animated: AnimatedProperties,
 }
 
 impl Element {
@@ -194,9 +220,8 @@ impl Element {
             data,
             layout: LayoutProps::default(),
             style: ElementStyle::default(),
-            state: WidgetState::Normal,
-            selected: false,
-            hovered: false,
+            state: WidgetState::NORMAL,
+            animated: AnimatedProperties::new(),
         }
     }
 
@@ -213,17 +238,88 @@ impl Element {
 
     /// Set selected state
     pub fn set_selected(&mut self, selected: bool) {
-        self.selected = selected;
+        let mut state = self.state;
+        state.set(WidgetState::SELECTED, selected);
+        self.transition_to(state);
     }
 
     /// Check if selected
     pub fn is_selected(&self) -> bool {
-        self.selected
+        self.state.contains(WidgetState::SELECTED)
     }
 
     /// Set hovered state
     pub fn set_hovered(&mut self, hovered: bool) {
-        self.hovered = hovered;
+        let mut state = self.state;
+        state.set(WidgetState::HOVER, hovered);
+        self.transition_to(state);
+    }
+
+    /// Set pressed state (the mouse button is held down on it)
+    pub fn set_pressed(&mut self, pressed: bool) {
+        let mut state = self.state;
+        state.set(WidgetState::PRESSED, pressed);
+        self.transition_to(state);
+    }
+
+    /// Mark the element as an odd row
+    pub fn set_alternate(&mut self, alternate: bool) {
+        self.state.set(WidgetState::ALTERNATE, alternate);
+    }
+
+    /// The state to style with: the widget's own plus the item's
+    fn render_state(&self) -> WidgetState {
+        self.state | self.data.state
+    }
+
+    /// Change state, starting the theme's transitions for the colors and
+    /// sizes that change
+    fn transition_to(&mut self, state: WidgetState) {
+        let from = self.render_state();
+        self.state = state;
+        let to = self.render_state();
+        if from == to {
+            return;
+        }
+
+        let transitions = &self.style.transition;
+        for ((property, from), (_, to)) in self.values(from).into_iter().zip(self.values(to)) {
+            self.animated.change(transitions, property, from, to);
+        }
+    }
+
+    /// The animated colors and sizes in a state, by property
+    fn values(&self, state: WidgetState) -> [(&'static str, AnimatedValue); 8] {
+        let style = &self.style;
+        [
+            ("background-color", style.background_color.get(state).into()),
+            ("border-color", style.border_color.get(state).into()),
+            ("text-color", style.text_color.get(state).into()),
+            ("highlight-color", style.highlight_color.get(state).into()),
+            (
+                "padding-horizontal",
+                style.padding_horizontal.get(state).into(),
+            ),
+            ("border-width", style.border_width.get(state).into()),
+            ("border-radius", style.border_radius.get(state).into()),
+            ("opacity", style.opacity.get(state).into()),
+        ]
+    }
+
+    /// The value to draw a per-state number in, following its transition
+    fn number(&self, property: &str, numbers: &StateNumbers) -> f32 {
+        self.animated
+            .get(property, numbers.get(self.render_state()))
+    }
+
+    /// Advance running transitions; returns true while any are running
+    pub fn tick(&mut self) -> bool {
+        self.animated.tick()
+    }
+
+    /// Show the current state at once, ending running transitions
+    pub fn skip_transitions(&mut self) {
+        self.animated.clear();
     }
 
     /// Get the configured height
@@ -231,9 +327,14 @@ impl Element {
         self.style.height
     }
 
-    /// Update style (for hot-reload)
+    /// Update style (for hot-reload), turning running transitions towards
+    /// the new style's values
     pub fn update_style(&mut self, style: ElementStyle) {
         self.style = style;
+        let transitions = &self.style.transition;
+        for (property, to) in self.values(self.render_state()) {
+            self.animated.retarget(transitions, property, to);
+        }
     }
 }
 
@@ -243,106 +344,100 @@ impl Widget for Element {
         EventResult::none()
     }
 
-    fn render(
-        &self,
-        renderer: &mut Renderer,
-        rect: Rect,
-        _ctx: &LayoutContext,
-    ) -> Result<(), windows::core::Error> {
-        let bounds = D2D_RECT_F {
-            left: rect.x,
-            top: rect.y,
-            right: rect.x + rect.width,
-            bottom: rect.y + rect.height,
-        };
+    fn render(&self, list: &mut DisplayList, rect: Rect, _ctx: &LayoutContext) {
+        // Choose colors based on state
+        let state = self.render_state();
+        let background = self
+            .animated
+            .get("background-color", self.style.background_color.get(state));
+
+        // Fade the whole row with its opacity
+        let opacity = self.number("opacity", &self.style.opacity).clamp(0.0, 1.0);
+        if opacity < 1.0 {
+            list.push_layer(opacity);
+        }
 
-        // Choose background color based on state
-        let bg_color = if self.selected {
-            self.style.background_color_selected
-        } else if self.hovered {
-            self.style.background_color_hover
-        } else {
-            self.style.background_color
-        };
+        // Scale border_radius with DPI
+        let border_radius = self.number("border-radius", &self.style.border_radius);
+        let radii = CornerRadii::uniform(border_radius * _ctx.scale_factor);
+
+        // Draw shadow under the background
+        if let Some(shadow) = self.style.box_shadow.get(state) {
+            list.shadow(rect, radii, &shadow.scaled(_ctx.scale_factor));
+        }
 
         // Draw background (rounded if border_radius > 0)
-        if bg_color.a > 0.0 {
-            // Scale border_radius with DPI
-            let scaled_radius = self.style.border_radius * _ctx.scale_factor;
+        if background.is_visible() {
+            let bg_color = background.color();
             log!(
-                "Element::render bg - selected={}, hovered={}, border_radius={} (scaled={}), color=({},{},{},{})",
-                self.selected,
-                self.hovered,
-                self.style.border_radius,
-                scaled_radius,
+                "Element::render bg - state={:?}, border_radius={} (scaled={}), color=({},{},{},{})",
+                state,
+                border_radius,
+                radii.top_left,
                 bg_color.r,
                 bg_color.g,
                 bg_color.b,
                 bg_color.a
             );
-            if scaled_radius > 0.0 {
-                renderer.fill_rounded_rect(bounds, scaled_radius, scaled_radius, bg_color)?;
-            } else {
-                renderer.fill_rect(bounds, bg_color)?;
-            }
+            list.fill(rect, radii, &background);
         }
 
-        // Choose text color
-        let text_color = if self.selected {
-            self.style.text_color_selected
-        } else {
-            self.style.text_color
-        };
+        let border_width =
+            self.number("border-width", &self.style.border_width) * _ctx.scale_factor;
+        let border = self
+            .animated
+            .get("border-color", self.style.border_color.get(state));
+        list.stroke(rect, radii, &border, border_width);
+
+        let text_color = self
+            .animated
+            .get("text-color", self.style.text_color.get(state));
 
         // Scale all dimensions with DPI (like task panel does)
         let scale = _ctx.scale_factor;
-        let scaled_padding_h = self.style.padding_horizontal * scale;
+        let scaled_padding_h =
+            self.number("padding-horizontal", &self.style.padding_horizontal) * scale;
         let scaled_icon_size = self.style.icon_size * scale;
         let scaled_icon_spacing = self.style.icon_spacing * scale;
         let scaled_font_size = self.style.font_size * scale;
 
         // Calculate text position (leaving room for icon)
         let text_x = rect.x + scaled_padding_h + scaled_icon_size + scaled_icon_spacing;
-        let text_width = rect.width
-            - scaled_padding_h * 2.0
-            - scaled_icon_size
-            - scaled_icon_spacing;
-
-        // Create text format fresh (like Textbox does)
-        let format = match renderer.create_text_format(
-            &self.style.font_family,
-            scaled_font_size,
-            false,
-            false,
-        ) {
-            Ok(f) => f,
-            Err(e) => {
-                log!("Element::render - failed to create text format: {:?}", e);
-                return Ok(());
-            }
-        };
+        let text_width =
+            rect.width - scaled_padding_h * 2.0 - scaled_icon_size - scaled_icon_spacing;
 
         // Draw main text - always single line centered (no subtext)
-        let text_rect = D2D_RECT_F {
-            left: text_x,
-            top: rect.y,
-            right: text_x + text_width,
-            bottom: rect.y + rect.height,
-        };
+        let text_rect = Rect::new(text_x, rect.y, text_width, rect.height);
 
         log!(
             "Element::render drawing '{}' at rect ({},{},{},{}) color=({},{},{},{})",
             self.data.text,
-            text_rect.left,
-            text_rect.top,
-            text_rect.right,
-            text_rect.bottom,
+            text_rect.x,
+            text_rect.y,
+            text_rect.width,
+            text_rect.height,
             text_color.r,
             text_color.g,
             text_color.b,
             text_color.a
         );
-        renderer.draw_text(&self.data.text, &format, text_rect, text_color)?;
+        let mut run = TextRun::new(
+            self.data.text.as_str(),
+            text_rect,
+            self.style.font_family.as_str(),
+            scaled_font_size,
+            text_color,
+        );
+        if !self.data.match_positions.is_empty() {
+            let highlight_color = self
+                .animated
+                .get("highlight-color", self.style.highlight_color.get(state));
+            run = run.highlight(
+                positions_to_ranges(&self.data.match_positions),
+                highlight_color,
+            );
+        }
+        list.text(run);
 
         // Draw icon if we have an icon path
         if let Some(ref icon_path) = self.data.icon_path {
@@ -350,17 +445,13 @@ impl Widget for Element {
             let icon_x = rect.x + scaled_padding_h;
             let icon_y = rect.y + (rect.height - scaled_icon_size) / 2.0;
 
-            let icon_rect = D2D_RECT_F {
-                left: icon_x,
-                top: icon_y,
-                right: icon_x + scaled_icon_size,
-                bottom: icon_y + scaled_icon_size,
-            };
-
-            renderer.draw_icon(icon_path, icon_rect, 1.0);
+            let icon_rect = Rect::new(icon_x, icon_y, scaled_icon_size, scaled_icon_size);
+            list.icon(icon_path.as_str(), icon_rect);
         }
 
-        Ok(())
+        if opacity < 1.0 {
+            list.pop();
+        }
     }
 
     fn state(&self) -> WidgetState {
@@ -368,7 +459,7 @@ impl Widget for Element {
     }
 
     fn set_state(&mut self, state: WidgetState) {
-        self.state = state;
+        self.transition_to(state);
     }
 
     fn style(&self) -> &WidgetStyle {
@@ -399,3 +490,144 @@ impl Widget for Element {
         "element"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::DrawCommand;
+
+    #[test]
+    fn test_element_size_transitions() {
+        let theme = ThemeTree::parse(
+            r#"
+            element {
+                border-radius: 2;
+                transition: border-radius 60s linear, opacity 60s linear;
+            }
+            element selected.normal { border-radius: 12; opacity: 0.5; padding-horizontal: 20; }
+        "#,
+        )
+        .unwrap();
+        let style = ElementStyle::from_theme(&theme, None);
+        assert_eq!(style.border_radius.get(WidgetState::SELECTED), 12.0);
+        assert_eq!(style.opacity.get(WidgetState::NORMAL), 1.0);
+
+        let mut element = Element::new(ElementData::new("Firefox", "firefox")).with_style(style);
+        element.set_selected(true);
+        assert!(element.tick());
+
+        let render = |element: &Element| {
+            let mut list = DisplayList::new();
+            let rect = Rect::new(0.0, 0.0, 300.0, 40.0);
+            element.render(&mut list, rect, &LayoutContext::default());
+            list
+        };
+        let radius = |list: &DisplayList| {
+            list.commands().iter().find_map(|command| match command {
+                DrawCommand::Fill { radii, .. } => Some(radii.top_left),
+                _ => None,
+            })
+        };
+
+        // Radius and opacity start from the normal state's values...
+        let list = render(&element);
+        assert!(radius(&list).unwrap() < 3.0, "{:?}", list.commands());
+        assert!(element.number("opacity", &element.style.opacity) > 0.9);
+        // ...while padding, which has no transition, changes at once
+        assert_eq!(list.texts().next().unwrap().rect.x, 52.0);
+
+        element.skip_transitions();
+        let list = render(&element);
+        assert_eq!(radius(&list), Some(12.0));
+        assert!(matches!(
+            list.commands().first(),
+            Some(DrawCommand::PushLayer { opacity }) if *opacity == 0.5
+        ));
+        assert!(matches!(list.commands().last(), Some(DrawCommand::Pop)));
+    }
+
+    fn render(element: &Element, ctx: &LayoutContext) -> DisplayList {
+        let mut list = DisplayList::new();
+        element.render(&mut list, Rect::new(10.0, 20.0, 300.0, 40.0), ctx);
+        list
+    }
+
+    #[test]
+    fn test_element_render_text_and_icon() {
+        let data = ElementData::new("Firefox", "firefox.exe")
+            .with_icon("firefox.ico")
+            .with_match_positions(vec![0, 1, 3]);
+        let element = Element::new(data);
+        let ctx = LayoutContext {
+            scale_factor: 2.0,
+            ..Default::default()
+        };
+        let list = render(&element, &ctx);
+
+        // A normal row has no background, just its icon and text
+        assert!(!list
+            .commands()
+            .iter()
+            .any(|command| matches!(command, DrawCommand::Fill { .. })));
+        let icon = list.commands().iter().find_map(|command| match command {
+            DrawCommand::Icon { path, rect } => Some((path.as_str(), *rect)),
+            _ => None,
+        });
+        // Padding and icon size scale with DPI, centered in the row
+        assert_eq!(
+            icon,
+            Some(("firefox.ico", Rect::new(34.0, 16.0, 48.0, 48.0)))
+        );
+
+        let run = list.texts().next().unwrap();
+        assert_eq!(run.text, "Firefox");
+        assert_eq!(run.rect, Rect::new(98.0, 20.0, 188.0, 40.0));
+        assert_eq!(run.font_size, 28.0);
+        // Matched characters are highlighted in runs
+        assert_eq!(run.highlights, [(0, 2), (3, 4)]);
+        assert_eq!(run.color, Color::from_hex("#d4d4d4").unwrap());
+    }
+
+    #[test]
+    fn test_element_render_states() {
+        let theme = ThemeTree::parse(
+            r#"
+            element { background-color: #000000; text-color: #ffffff; }
+            element selected.normal {
+                background-color: #0000ff;
+                box-shadow: 0 2px 4px #00000080;
+                border-radius: 6;
+            }
+            element alternate { background-color: #ff0000; }
+        "#,
+        )
+        .unwrap();
+        let mut element = Element::new(ElementData::new("Notepad", "notepad.exe"))
+            .with_style(ElementStyle::from_theme(&theme, None));
+        let ctx = LayoutContext::default();
+        let background = |list: &DisplayList| {
+            list.commands().iter().find_map(|command| match command {
+                DrawCommand::Fill { paint, radii, .. } => Some((paint.color(), radii.top_left)),
+                _ => None,
+            })
+        };
+
+        assert_eq!(
+            background(&render(&element, &ctx)),
+            Some((Color::BLACK, 0.0))
+        );
+
+        element.set_alternate(true);
+        assert_eq!(background(&render(&element, &ctx)), Some((Color::RED, 0.0)));
+
+        // Selected outranks alternate, and draws its shadow under the background
+        element.set_selected(true);
+        let list = render(&element, &ctx);
+        assert_eq!(background(&list), Some((Color::BLUE, 6.0)));
+        assert!(matches!(
+            list.commands().first(),
+            Some(DrawCommand::Shadow { .. })
+        ));
+        assert_eq!(list.texts().next().unwrap().color, Color::WHITE);
+    }
+}
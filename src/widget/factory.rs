@@ -4,16 +4,23 @@
 //! - Widget names/prefixes determine widget type
 //! - Theme's `children` property defines the widget tree
 //! - Known containers have default children if not specified
+//! - The `overlay` layer above the window holds positioned widgets (see
+//!   `overlay`)
 
-use crate::platform::win32::Renderer;
+use crate::render::DisplayList;
+use crate::theme::ast::parse_path;
 use crate::theme::tree::ThemeTree;
-use crate::theme::types::{LayoutContext, Orientation, Rect};
+use crate::theme::types::{LayoutContext, Rect};
 
+use super::base::{Constraints, LayoutProps, Size};
 use super::container::Container;
+use super::flex::{self, FlexItem};
+use super::label::Label;
 use super::listview::ListView;
+use super::overlay::DEFAULT_OVERLAYS;
 use super::panel::Panel;
 use super::textbox::Textbox;
-use super::{ElementStyle, EventResult, ListViewStyle, Widget, WidgetStyle};
+use super::{EventResult, Widget};
 use crate::platform::Event;
 
 /// Widget types that can be created
@@ -29,6 +36,8 @@ pub enum WidgetType {
     ListView,
     /// Dummy - spacer widget
     Dummy,
+    /// Label - a line of static text, e.g. a tooltip
+    Label,
 }
 
 /// Default children for known container widgets
@@ -38,6 +47,7 @@ fn default_children(name: &str) -> Option<Vec<&'static str>> {
         "mainbox" => Some(vec!["wallpaper-panel", "listbox"]),
         "listbox" => Some(vec!["dummy", "listview", "dummy"]),
         "inputbar" => Some(vec!["textbox"]),
+        "overlay" => Some(DEFAULT_OVERLAYS.to_vec()),
         _ => None,
     }
 }
@@ -49,6 +59,7 @@ fn widget_type_from_name(name: &str) -> WidgetType {
         "listview" => return WidgetType::ListView,
         "textbox" | "entry" | "prompt" => return WidgetType::Textbox,
         "dummy" => return WidgetType::Dummy,
+        "tooltip" | "version" | "filter" => return WidgetType::Label,
         _ => {}
     }
 
@@ -57,6 +68,8 @@ fn widget_type_from_name(name: &str) -> WidgetType {
         WidgetType::Textbox
     } else if name.starts_with("listview") {
         WidgetType::ListView
+    } else if name.starts_with("label") || name.starts_with("badge") || name.ends_with("-badge") {
+        WidgetType::Label
     } else if name.ends_with("-panel") || name == "wallpaper-panel" {
         // Panels are for backgrounds (wallpaper, images)
         WidgetType::Panel
@@ -68,24 +81,32 @@ fn widget_type_from_name(name: &str) -> WidgetType {
 
 /// A node in the widget tree
 pub struct WidgetNode {
-    /// Widget name (for theme lookups)
+    /// Widget name
     pub name: String,
+    /// Path from the root for theme lookups, e.g. `window mainbox listbox`
+    pub path: String,
     /// The actual widget
     pub widget: Box<dyn Widget>,
     /// Child nodes
     pub children: Vec<WidgetNode>,
-    /// Cached bounds from last layout
+    /// Cached bounds from last layout, None while hidden
     pub bounds: Option<Rect>,
+    /// Taken out of its parent's flow and placed by its anchor instead
+    /// (`position: absolute`, or a child of the overlay layer)
+    pub positioned: bool,
 }
 
 impl WidgetNode {
     /// Create a new widget node
     pub fn new(name: impl Into<String>, widget: Box<dyn Widget>) -> Self {
+        let name = name.into();
         Self {
-            name: name.into(),
+            path: name.clone(),
+            name,
             widget,
             children: Vec::new(),
             bounds: None,
+            positioned: false,
         }
     }
 
@@ -122,12 +143,13 @@ impl WidgetNode {
 }
 
 /// Factory context for creating widgets
+///
+/// Each widget is styled through its path from the root (`window mainbox
+/// listbox listview`), so descendant rules like `listbox listview` apply.
 pub struct WidgetFactory<'a> {
     theme: &'a ThemeTree,
-    /// Shared state that special widgets need access to
-    listview_style: ListViewStyle,
-    element_style: ElementStyle,
-    textbox_style: WidgetStyle,
+    /// First step of every path, e.g. `window.launcher`
+    root: String,
 }
 
 impl<'a> WidgetFactory<'a> {
@@ -135,56 +157,61 @@ impl<'a> WidgetFactory<'a> {
     pub fn new(theme: &'a ThemeTree) -> Self {
         Self {
             theme,
-            listview_style: ListViewStyle::from_theme(theme, None),
-            element_style: ElementStyle::from_theme(theme, None),
-            textbox_style: WidgetStyle::from_theme_textbox(theme, None),
+            root: "window".to_string(),
         }
     }
 
+    /// Give the root window a state, e.g. the mode (`window.launcher`), so
+    /// rules like `window.launcher textbox` apply to the whole tree
+    pub fn with_root_state(mut self, state: &str) -> Self {
+        self.root = format!("window.{}", state);
+        self
+    }
+
     /// Build the complete widget tree starting from "window"
     pub fn build_tree(&self) -> WidgetNode {
-        self.build_widget("window")
+        self.build_widget("window", self.root.clone())
     }
 
-    /// Build a widget and its children recursively
-    fn build_widget(&self, name: &str) -> WidgetNode {
-        let widget_type = widget_type_from_name(name);
-        crate::log!("WidgetFactory: building '{}' as {:?}", name, widget_type);
+    /// Build the overlay layer, styled as the last child of `window` so
+    /// rules like `window.launcher tooltip` apply
+    pub fn build_overlay(&self) -> WidgetNode {
+        let mut overlay = self.build_widget("overlay", format!("{} overlay", self.root));
+        for child in &mut overlay.children {
+            child.positioned = true;
+        }
+        overlay
+    }
 
-        // Create the widget based on type
-        let widget: Box<dyn Widget> = match widget_type {
-            WidgetType::Container => {
-                let mut container = Container::new(name);
-                container.load_from_theme(self.theme);
-                Box::new(container)
-            }
-            WidgetType::Panel => {
-                let mut panel = Panel::new(name);
-                panel.load_from_theme(self.theme);
-                Box::new(panel)
-            }
-            WidgetType::Textbox => {
-                let textbox = Textbox::new().with_style(self.textbox_style.clone());
-                Box::new(textbox)
-            }
-            WidgetType::ListView => {
-                let listview = ListView::new()
-                    .with_style(self.listview_style.clone())
-                    .with_element_style(self.element_style.clone());
-                Box::new(listview)
-            }
-            WidgetType::Dummy => {
-                // Dummy is just an expanding panel with no background
-                let mut panel = Panel::new(name);
-                panel.load_from_theme(self.theme);
-                Box::new(panel)
-            }
-        };
+    /// Path of the first widget called `name` in the tree
+    ///
+    /// For widgets the app draws itself, e.g. the launcher's listview, so
+    /// their styles can be loaded in place.
+    pub fn path_of(&self, name: &str) -> Option<String> {
+        self.find_path("window", self.root.clone(), name)
+    }
 
-        let mut node = WidgetNode::new(name, widget);
+    fn find_path(&self, current: &str, path: String, name: &str) -> Option<String> {
+        if current == name {
+            return Some(path);
+        }
+        if widget_type_from_name(current) != WidgetType::Container {
+            return None;
+        }
+        self.children_of(current, &path)
+            .into_iter()
+            .find_map(|child| {
+                let child_path = format!("{} {}", path, child);
+                self.find_path(&child, child_path, name)
+            })
+    }
 
-        // Get children from theme, or use defaults
-        let children_names = self.theme.get_children(name);
+    /// Children from the theme, or the defaults for known containers
+    ///
+    /// Children already on the path are dropped so a cyclic layout can't
+    /// recurse forever.
+    fn children_of(&self, name: &str, path: &str) -> Vec<String> {
+        let children_names = self.theme.get_children(path);
         let children: Vec<String> = if children_names.is_empty() {
             // Use defaults if available
             default_children(name)
@@ -194,13 +221,47 @@ impl<'a> WidgetFactory<'a> {
             children_names
         };
 
-        crate::log!("  '{}' children: {:?}", name, children);
+        let ancestors = parse_path(path);
+        children
+            .into_iter()
+            .filter(|child| {
+                let cyclic = ancestors.iter().any(|step| step.name == child);
+                if cyclic {
+                    crate::log!("WidgetFactory: '{}' contains itself, skipped", child);
+                }
+                !cyclic
+            })
+            .collect()
+    }
+
+    /// Build a widget and its children recursively
+    fn build_widget(&self, name: &str, path: String) -> WidgetNode {
+        let widget_type = widget_type_from_name(name);
+        crate::log!("WidgetFactory: building '{}' as {:?}", path, widget_type);
+
+        // Create the widget based on type, then style it
+        let mut widget: Box<dyn Widget> = match widget_type {
+            WidgetType::Container => Box::new(Container::new(name)),
+            WidgetType::Panel => Box::new(Panel::new(name)),
+            WidgetType::Textbox => Box::new(Textbox::new()),
+            WidgetType::ListView => Box::new(ListView::new()),
+            // Dummy is just an expanding panel with no background
+            WidgetType::Dummy => Box::new(Panel::new(name)),
+            WidgetType::Label => Box::new(Label::new(name)),
+        };
+        widget.restyle(self.theme, &path);
+
+        let mut node = WidgetNode::new(name, widget);
+        node.positioned = node.widget.layout_props().placement.is_absolute();
 
         // Recursively build children (but not for leaf widgets)
         match widget_type {
             WidgetType::Container => {
+                let children = self.children_of(name, &path);
+                crate::log!("  '{}' children: {:?}", name, children);
                 for child_name in children {
-                    let child_node = self.build_widget(&child_name);
+                    let child_path = format!("{} {}", path, child_name);
+                    let child_node = self.build_widget(&child_name, child_path);
                     node.add_child(child_node);
                 }
             }
@@ -209,14 +270,80 @@ impl<'a> WidgetFactory<'a> {
             }
         }
 
+        node.path = path;
         node
     }
 }
 
+/// Bring a live node in line with a freshly built one
+///
+/// The live widget is restyled in place from the theme, keeping its state.
+/// Children are matched by name in order, so a fresh child takes the first
+/// live sibling of the same name; the rest are created or destroyed. The
+/// widget type follows from the name, so a match never changes type.
+fn reconcile(live: &mut WidgetNode, fresh: WidgetNode, theme: &ThemeTree) {
+    live.widget.restyle(theme, &fresh.path);
+    live.path = fresh.path;
+    live.positioned = fresh.positioned;
+
+    let mut old: Vec<Option<WidgetNode>> = live.children.drain(..).map(Some).collect();
+    for child in fresh.children {
+        let kept = old
+            .iter_mut()
+            .find(|slot| slot.as_ref().is_some_and(|node| node.name == child.name))
+            .and_then(Option::take);
+        match kept {
+            Some(mut node) => {
+                reconcile(&mut node, child, theme);
+                live.children.push(node);
+            }
+            None => {
+                crate::log!("UITree: created '{}'", child.path);
+                live.children.push(child);
+            }
+        }
+    }
+    for node in old.into_iter().flatten() {
+        crate::log!("UITree: destroyed '{}'", node.path);
+    }
+}
+
+/// Index path to a node: its layer (0 = window, 1 = overlay), then the
+/// index of each child on the way down
+type NodePath = Vec<usize>;
+
+/// What's at a point of the window, from `UITree::hit_test`
+#[derive(Clone, Copy)]
+pub enum Hit<'a> {
+    /// A widget of the tree
+    Widget(&'a WidgetNode),
+    /// An anchor set with `UITree::set_anchor`, by name
+    Anchor(&'a str),
+}
+
+impl Hit<'_> {
+    /// Name of the widget or anchor
+    pub fn name(&self) -> &str {
+        match self {
+            Hit::Widget(node) => &node.name,
+            Hit::Anchor(name) => name,
+        }
+    }
+}
+
 /// The complete UI tree with layout and rendering
+///
+/// The `window` tree is drawn first, then the overlay layer over it, then
+/// every positioned widget by `z-index` (tree order among equals).
 pub struct UITree {
     /// Root widget node
     root: WidgetNode,
+    /// Overlay layer covering the window
+    overlay: WidgetNode,
+    /// Bounds of things outside the tree that widgets can anchor to, like
+    /// the list or the hovered row of a panel the app draws itself, in the
+    /// order they were set (the last on top)
+    anchors: Vec<(String, Rect)>,
     /// Cached layout context
     layout_ctx: LayoutContext,
 }
@@ -225,14 +352,22 @@ impl UITree {
     /// Create a new UI tree from theme
     pub fn from_theme(theme: &ThemeTree, layout_ctx: LayoutContext) -> Self {
         let factory = WidgetFactory::new(theme);
-        let root = factory.build_tree();
-        Self { root, layout_ctx }
+        Self {
+            root: factory.build_tree(),
+            overlay: factory.build_overlay(),
+            anchors: Vec::new(),
+            layout_ctx,
+        }
     }
 
     /// Rebuild the tree from theme (for hot-reload)
+    ///
+    /// The new tree is reconciled with the live one, so widgets that are
+    /// still there keep their state (see `reconcile`).
     pub fn rebuild(&mut self, theme: &ThemeTree) {
         let factory = WidgetFactory::new(theme);
-        self.root = factory.build_tree();
+        reconcile(&mut self.root, factory.build_tree(), theme);
+        reconcile(&mut self.overlay, factory.build_overlay(), theme);
     }
 
     /// Update the layout context
@@ -240,14 +375,47 @@ impl UITree {
         self.layout_ctx = ctx;
     }
 
+    /// Set the bounds of an anchor outside the tree, for `anchor-to` and
+    /// hit-testing; it goes on top of the other anchors
+    pub fn set_anchor(&mut self, name: impl Into<String>, bounds: Rect) {
+        let name = name.into();
+        self.clear_anchor(&name);
+        self.anchors.push((name, bounds));
+    }
+
+    /// Remove an anchor, hiding the widgets anchored to it
+    pub fn clear_anchor(&mut self, name: &str) {
+        self.anchors.retain(|(anchor, _)| anchor != name);
+    }
+
+    /// Bounds of an anchor set with `set_anchor`
+    pub fn anchor(&self, name: &str) -> Option<Rect> {
+        self.anchors
+            .iter()
+            .find(|(anchor, _)| anchor == name)
+            .map(|(_, bounds)| *bounds)
+    }
+
     /// Get a reference to a widget by name
     pub fn find_widget(&self, name: &str) -> Option<&WidgetNode> {
-        self.root.find_child(name)
+        self.root
+            .find_child(name)
+            .or_else(|| self.overlay.find_child(name))
     }
 
     /// Get a mutable reference to a widget by name
     pub fn find_widget_mut(&mut self, name: &str) -> Option<&mut WidgetNode> {
-        self.root.find_child_mut(name)
+        match self.root.find_child_mut(name) {
+            Some(node) => Some(node),
+            None => self.overlay.find_child_mut(name),
+        }
+    }
+
+    /// Set the text of a widget by name; false if there's no such widget
+    pub fn set_text(&mut self, name: &str, text: &str) -> bool {
+        self.find_widget_mut(name)
+            .map(|node| node.widget.set_text(text))
+            .is_some()
     }
 
     /// Handle an event, propagating through the tree
@@ -285,8 +453,23 @@ impl UITree {
     }
 
     /// Layout the tree within the given bounds
+    ///
+    /// The flow is laid out first, then positioned widgets in tree order, so
+    /// a widget can anchor to anything in the flow or to a positioned widget
+    /// before it. Widgets whose anchor doesn't exist are hidden.
     pub fn layout(&mut self, bounds: Rect) {
-        Self::layout_recursive(&mut self.root, bounds, &self.layout_ctx);
+        let layout_ctx = self.layout_ctx.clone();
+        Self::layout_recursive(&mut self.root, bounds, &layout_ctx);
+        Self::layout_recursive(&mut self.overlay, bounds, &layout_ctx);
+
+        for path in self.positioned_paths() {
+            let placed = self.place(&path);
+            let node = self.node_mut(&path);
+            match placed {
+                Some(rect) => Self::layout_recursive(node, rect, &layout_ctx),
+                None => Self::hide(node),
+            }
+        }
     }
 
     fn layout_recursive(node: &mut WidgetNode, bounds: Rect, layout_ctx: &LayoutContext) {
@@ -295,135 +478,231 @@ impl UITree {
         // Let the widget arrange itself and determine child bounds
         node.widget.arrange(bounds, layout_ctx);
 
-        // For containers, we need to calculate child bounds based on orientation
-        if !node.children.is_empty() {
-            let child_bounds = Self::calculate_child_bounds(node, bounds);
-            for (child, child_rect) in node.children.iter_mut().zip(child_bounds) {
-                Self::layout_recursive(child, child_rect, layout_ctx);
-            }
+        // For containers, lay the flowing children out as a flexbox
+        let child_bounds = Self::calculate_child_bounds(node, bounds, layout_ctx);
+        let flow = node.children.iter_mut().filter(|child| !child.positioned);
+        for (child, child_rect) in flow.zip(child_bounds) {
+            Self::layout_recursive(child, child_rect, layout_ctx);
+        }
+    }
+
+    fn hide(node: &mut WidgetNode) {
+        node.bounds = None;
+        for child in &mut node.children {
+            Self::hide(child);
         }
     }
 
-    /// Calculate bounds for each child of a container
-    fn calculate_child_bounds(node: &WidgetNode, bounds: Rect) -> Vec<Rect> {
-        let props = node.widget.layout_props();
-        let (pad_t, pad_r, pad_b, pad_l) = props.padding;
-        let spacing = props.spacing;
+    /// Bounds of a positioned widget from its anchor, None if the anchor is
+    /// missing or hidden
+    fn place(&self, path: &[usize]) -> Option<Rect> {
+        let node = self.node(path);
+        let placement = node
+            .widget
+            .layout_props()
+            .placement
+            .scaled(self.layout_ctx.scale_factor);
+        let target = match &placement.anchor_to {
+            None => self.node(&path[..path.len() - 1]).bounds?,
+            Some(name) => self
+                .anchor(name)
+                .or_else(|| self.find_widget(name).and_then(|anchor| anchor.bounds))?,
+        };
+
+        // Measured within the window, whatever it's anchored to
+        let window = self.root.bounds?;
+        let constraints = Constraints::loose(Size::new(window.width, window.height));
+        let size = Self::measure_node(node, constraints, &self.layout_ctx);
+        Some(placement.place(size, target))
+    }
+
+    /// Calculate bounds for each flowing child of a container
+    fn calculate_child_bounds(
+        node: &WidgetNode,
+        bounds: Rect,
+        layout_ctx: &LayoutContext,
+    ) -> Vec<Rect> {
+        let props = node
+            .widget
+            .layout_props()
+            .resolved(layout_ctx, Size::new(bounds.width, bounds.height));
 
-        // Content area (inside padding)
-        let content = Rect::new(
-            bounds.x + pad_l,
-            bounds.y + pad_t,
-            bounds.width - pad_l - pad_r,
-            bounds.height - pad_t - pad_b,
+        // Children measure within the content area (inside padding)
+        let content = Size::new(
+            (bounds.width - props.padding_horizontal()).max(0.0),
+            (bounds.height - props.padding_vertical()).max(0.0),
         );
+        let children = Self::flex_children(node, Constraints::loose(content), layout_ctx);
+        if children.is_empty() {
+            return Vec::new();
+        }
+        flex::arrange(&props, bounds, &Self::flex_items(&children))
+    }
 
-        if node.children.is_empty() {
-            return vec![];
+    /// Size a node wants: containers measure around their child nodes
+    fn measure_node(
+        node: &WidgetNode,
+        constraints: Constraints,
+        layout_ctx: &LayoutContext,
+    ) -> Size {
+        if node.children.iter().all(|child| child.positioned) {
+            return node.widget.measure(constraints, layout_ctx).size;
         }
+        let props = node
+            .widget
+            .layout_props()
+            .resolved(layout_ctx, constraints.max);
+        let inner = Constraints::loose(Size::new(
+            (constraints.max.width - props.padding_horizontal()).max(0.0),
+            (constraints.max.height - props.padding_vertical()).max(0.0),
+        ));
+        let children = Self::flex_children(node, inner, layout_ctx);
+        flex::measure(&props, &Self::flex_items(&children), constraints.max)
+    }
 
-        let num_children = node.children.len();
-        let num_gaps = (num_children.saturating_sub(1)) as f32;
-        let total_spacing = spacing * num_gaps;
-
-        // Count expanding vs fixed children
-        let mut expanding_count = 0;
-        let mut fixed_size = 0.0;
-
-        for child in &node.children {
-            let child_props = child.widget.layout_props();
-            if child_props.expand {
-                expanding_count += 1;
-            } else {
-                // Get fixed size from layout props
-                match props.orientation {
-                    Orientation::Horizontal => {
-                        fixed_size += child_props.fixed_width.unwrap_or(0.0);
-                    }
-                    Orientation::Vertical => {
-                        fixed_size += child_props.fixed_height.unwrap_or(0.0);
-                    }
-                }
-            }
+    /// The flowing children's props in pixels, resolved against the content
+    /// area, and the sizes they measure at
+    fn flex_children(
+        node: &WidgetNode,
+        constraints: Constraints,
+        layout_ctx: &LayoutContext,
+    ) -> Vec<(LayoutProps, Size)> {
+        node.children
+            .iter()
+            .filter(|child| !child.positioned)
+            .map(|child| {
+                let size = Self::measure_node(child, constraints, layout_ctx);
+                let props = child
+                    .widget
+                    .layout_props()
+                    .resolved(layout_ctx, constraints.max);
+                (props, size)
+            })
+            .collect()
+    }
+
+    fn flex_items(children: &[(LayoutProps, Size)]) -> Vec<FlexItem<'_>> {
+        children
+            .iter()
+            .map(|(props, size)| FlexItem::new(props, *size))
+            .collect()
+    }
+
+    /// Render the tree
+    pub fn render(&self, list: &mut DisplayList) {
+        self.render_recursive(&self.root, list);
+        self.render_overlays(list);
+    }
+
+    /// Render only the overlay layer and positioned widgets, for callers that
+    /// draw the window's contents themselves
+    pub fn render_overlays(&self, list: &mut DisplayList) {
+        self.render_recursive(&self.overlay, list);
+        for path in self.stacking_order() {
+            self.render_recursive(self.node(&path), list);
         }
+    }
 
-        // Calculate size for expanding children
-        let available = match props.orientation {
-            Orientation::Horizontal => content.width - fixed_size - total_spacing,
-            Orientation::Vertical => content.height - fixed_size - total_spacing,
-        };
-        let expand_size = if expanding_count > 0 {
-            available / expanding_count as f32
-        } else {
-            0.0
+    /// Render a node and its flowing descendants
+    fn render_recursive(&self, node: &WidgetNode, list: &mut DisplayList) {
+        // Render this widget at its bounds
+        let Some(bounds) = node.bounds else {
+            return;
         };
+        node.widget.render(list, bounds, &self.layout_ctx);
 
-        // Generate bounds for each child
-        let mut result = Vec::with_capacity(num_children);
-        let mut pos = match props.orientation {
-            Orientation::Horizontal => content.x,
-            Orientation::Vertical => content.y,
-        };
+        // Render children; positioned ones are drawn in stacking order
+        for child in node.children.iter().filter(|child| !child.positioned) {
+            self.render_recursive(child, list);
+        }
+    }
 
-        for (i, child) in node.children.iter().enumerate() {
-            let child_props = child.widget.layout_props();
-            let is_expand = child_props.expand;
-
-            let child_rect = match props.orientation {
-                Orientation::Horizontal => {
-                    let width = if is_expand {
-                        expand_size
-                    } else {
-                        child_props.fixed_width.unwrap_or(expand_size)
-                    };
-                    let rect = Rect::new(pos, content.y, width, content.height);
-                    pos += width;
-                    rect
-                }
-                Orientation::Vertical => {
-                    let height = if is_expand {
-                        expand_size
-                    } else {
-                        child_props.fixed_height.unwrap_or(expand_size)
-                    };
-                    let rect = Rect::new(content.x, pos, content.width, height);
-                    pos += height;
-                    rect
-                }
-            };
+    /// The topmost thing at a point: positioned widgets by stacking order,
+    /// then the overlay layer, then the anchors (what the app draws over the
+    /// window), then the window tree; the deepest widget in each
+    pub fn hit_test(&self, x: f32, y: f32) -> Option<Hit<'_>> {
+        let positioned = self.stacking_order();
+        positioned
+            .iter()
+            .rev()
+            .find_map(|path| Self::hit_test_recursive(self.node(path), x, y))
+            .or_else(|| {
+                // The overlay layer itself covers the window but isn't a target
+                self.overlay
+                    .children
+                    .iter()
+                    .rev()
+                    .filter(|child| !child.positioned)
+                    .find_map(|child| Self::hit_test_recursive(child, x, y))
+            })
+            .map(Hit::Widget)
+            .or_else(|| {
+                self.anchors
+                    .iter()
+                    .rev()
+                    .find(|(_, bounds)| bounds.contains(x, y))
+                    .map(|(name, _)| Hit::Anchor(name))
+            })
+            .or_else(|| Self::hit_test_recursive(&self.root, x, y).map(Hit::Widget))
+    }
 
-            result.push(child_rect);
+    fn hit_test_recursive(node: &WidgetNode, x: f32, y: f32) -> Option<&WidgetNode> {
+        if !node.bounds?.contains(x, y) {
+            return None;
+        }
+        node.children
+            .iter()
+            .rev()
+            .filter(|child| !child.positioned)
+            .find_map(|child| Self::hit_test_recursive(child, x, y))
+            .or(Some(node))
+    }
 
-            // Add spacing (except after last child)
-            if i < num_children - 1 {
-                pos += spacing;
+    /// Paths of the positioned widgets in tree order, window tree first
+    fn positioned_paths(&self) -> Vec<NodePath> {
+        fn collect(node: &WidgetNode, path: &NodePath, paths: &mut Vec<NodePath>) {
+            for (i, child) in node.children.iter().enumerate() {
+                let mut child_path = path.clone();
+                child_path.push(i);
+                if child.positioned {
+                    paths.push(child_path.clone());
+                }
+                collect(child, &child_path, paths);
             }
         }
 
-        result
+        let mut paths = Vec::new();
+        collect(&self.root, &vec![0], &mut paths);
+        collect(&self.overlay, &vec![1], &mut paths);
+        paths
     }
 
-    /// Render the tree
-    pub fn render(&self, renderer: &mut Renderer) -> Result<(), windows::core::Error> {
-        self.render_recursive(&self.root, renderer)
+    /// Positioned widgets from bottom to top
+    fn stacking_order(&self) -> Vec<NodePath> {
+        let mut paths = self.positioned_paths();
+        // Stable, so tree order breaks ties
+        paths.sort_by_key(|path| self.node(path).widget.layout_props().placement.z_index);
+        paths
     }
 
-    fn render_recursive(
-        &self,
-        node: &WidgetNode,
-        renderer: &mut Renderer,
-    ) -> Result<(), windows::core::Error> {
-        // Render this widget at its bounds
-        if let Some(bounds) = node.bounds {
-            node.widget.render(renderer, bounds, &self.layout_ctx)?;
-        }
-
-        // Render children
-        for child in &node.children {
-            self.render_recursive(child, renderer)?;
-        }
+    fn node(&self, path: &[usize]) -> &WidgetNode {
+        let (layer, path) = path.split_first().expect("node paths start with a layer");
+        let root = if *layer == 0 {
+            &self.root
+        } else {
+            &self.overlay
+        };
+        path.iter().fold(root, |node, &i| &node.children[i])
+    }
 
-        Ok(())
+    fn node_mut(&mut self, path: &[usize]) -> &mut WidgetNode {
+        let (layer, path) = path.split_first().expect("node paths start with a layer");
+        let root = if *layer == 0 {
+            &mut self.root
+        } else {
+            &mut self.overlay
+        };
+        path.iter().fold(root, |node, &i| &mut node.children[i])
     }
 
     /// Get the root node
@@ -435,11 +714,17 @@ impl UITree {
     pub fn root_mut(&mut self) -> &mut WidgetNode {
         &mut self.root
     }
+
+    /// Get the overlay layer
+    pub fn overlay(&self) -> &WidgetNode {
+        &self.overlay
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::widget::WidgetState;
 
     #[test]
     fn test_widget_type_from_name() {
@@ -451,6 +736,9 @@ mod tests {
         assert_eq!(widget_type_from_name("mainbox"), WidgetType::Container);
         assert_eq!(widget_type_from_name("listbox"), WidgetType::Container);
         assert_eq!(widget_type_from_name("dummy"), WidgetType::Dummy);
+        assert_eq!(widget_type_from_name("tooltip"), WidgetType::Label);
+        assert_eq!(widget_type_from_name("filter"), WidgetType::Label);
+        assert_eq!(widget_type_from_name("task-badge"), WidgetType::Label);
     }
 
     #[test]
@@ -460,6 +748,287 @@ mod tests {
             default_children("mainbox"),
             Some(vec!["wallpaper-panel", "listbox"])
         );
+        assert_eq!(
+            default_children("overlay"),
+            Some(vec!["tooltip", "version", "filter"])
+        );
         assert_eq!(default_children("unknown"), None);
     }
+
+    #[test]
+    fn test_overlay_layout() {
+        let theme = ThemeTree::parse(
+            r#"
+            mainbox { orientation: horizontal; children: [ "wallpaper-panel", "listbox" ]; }
+            wallpaper-panel { width: 200; }
+            listbox { expand: true; children: [ "listview", "badge" ]; }
+            badge {
+                content: "3";
+                font-size: 10;
+                position: absolute;
+                anchor-to: "wallpaper-panel";
+                location: north-east;
+                anchor: center;
+            }
+            version { content: "v1"; offset: -10 -10; }
+        "#,
+        )
+        .unwrap();
+        let mut tree = UITree::from_theme(&theme, LayoutContext::default());
+        tree.layout(Rect::new(0.0, 0.0, 600.0, 300.0));
+        let bounds = |tree: &UITree, name| tree.find_widget(name).and_then(|node| node.bounds);
+
+        // The badge is out of the listbox's flow, centered on the panel's corner
+        assert_eq!(
+            bounds(&tree, "listbox"),
+            Some(Rect::new(200.0, 0.0, 400.0, 300.0))
+        );
+        let width = |text| LayoutContext::default().text_width(text, "Segoe UI", 10.0, false);
+        assert_eq!(
+            bounds(&tree, "badge"),
+            Some(Rect::new(200.0 - width("3") / 2.0, -6.5, width("3"), 13.0))
+        );
+        // The watermark sits in the window's corner
+        assert_eq!(
+            bounds(&tree, "version"),
+            Some(Rect::new(590.0 - width("v1"), 277.0, width("v1"), 13.0))
+        );
+
+        // The tooltip shows while the hovered anchor is set
+        assert_eq!(bounds(&tree, "tooltip"), None);
+        tree.set_anchor("hovered", Rect::new(50.0, 40.0, 100.0, 20.0));
+        assert!(tree.set_text("tooltip", "Build"));
+        tree.layout(Rect::new(0.0, 0.0, 600.0, 300.0));
+        let tooltip = bounds(&tree, "tooltip").unwrap();
+        assert_eq!((tooltip.x, tooltip.y), (156.0, 40.0));
+
+        // Positioned widgets draw after the flow, the tooltip on top
+        let mut list = DisplayList::new();
+        tree.render(&mut list);
+        let texts: Vec<&str> = list.texts().map(|run| run.text.as_str()).collect();
+        assert_eq!(texts, ["3", "v1", "Build"]);
+
+        tree.clear_anchor("hovered");
+        tree.layout(Rect::new(0.0, 0.0, 600.0, 300.0));
+        assert_eq!(bounds(&tree, "tooltip"), None);
+    }
+
+    #[test]
+    fn test_hit_test_stacking() {
+        let theme = ThemeTree::parse(
+            r#"
+            mainbox { expand: true; children: [ "listbox" ]; }
+            listbox { expand: true; children: [ "image-panel", "label-low", "label-high" ]; }
+            image-panel { expand: true; }
+            label-low { content: "low"; position: absolute; z-index: 2; width: 100; height: 100; }
+            label-high { content: "high"; position: absolute; z-index: 1; width: 50; height: 50; }
+        "#,
+        )
+        .unwrap();
+        let mut tree = UITree::from_theme(&theme, LayoutContext::default());
+        tree.layout(Rect::new(0.0, 0.0, 400.0, 300.0));
+        let hit = |tree: &UITree, x, y| {
+            tree.hit_test(x, y).map(|hit| match hit {
+                Hit::Widget(node) => node.name.clone(),
+                Hit::Anchor(name) => format!("anchor {}", name),
+            })
+        };
+
+        // The higher z-index wins even though it comes first in the tree
+        assert_eq!(hit(&tree, 10.0, 10.0).as_deref(), Some("label-low"));
+        assert_eq!(hit(&tree, 80.0, 80.0).as_deref(), Some("label-low"));
+        assert_eq!(hit(&tree, 200.0, 200.0).as_deref(), Some("image-panel"));
+        assert_eq!(hit(&tree, 500.0, 10.0), None);
+
+        // What the app draws is over the window tree and under positioned
+        // widgets, the anchor set last on top
+        tree.set_anchor("list", Rect::new(0.0, 0.0, 300.0, 300.0));
+        tree.set_anchor("panel", Rect::new(250.0, 0.0, 100.0, 300.0));
+        assert_eq!(hit(&tree, 80.0, 80.0).as_deref(), Some("label-low"));
+        assert_eq!(hit(&tree, 200.0, 200.0).as_deref(), Some("anchor list"));
+        assert_eq!(hit(&tree, 260.0, 200.0).as_deref(), Some("anchor panel"));
+        assert_eq!(hit(&tree, 380.0, 200.0).as_deref(), Some("image-panel"));
+        tree.set_anchor("list", Rect::new(0.0, 0.0, 300.0, 300.0));
+        assert_eq!(hit(&tree, 260.0, 200.0).as_deref(), Some("anchor list"));
+        tree.clear_anchor("list");
+        assert_eq!(hit(&tree, 200.0, 200.0).as_deref(), Some("image-panel"));
+    }
+
+    #[test]
+    fn test_layout() {
+        let theme = ThemeTree::parse(
+            r#"
+            window { padding-top: 10; padding-bottom: 10; }
+            mainbox {
+                orientation: horizontal;
+                justify-content: space-between;
+                children: [ "wallpaper-panel", "listbox" ];
+            }
+            wallpaper-panel { width: 300; min-width: 200; margin: 0 20 0 0; }
+            listbox { width: 200; flex-shrink: 0; children: [ "listview" ]; }
+        "#,
+        )
+        .unwrap();
+        let mut tree = UITree::from_theme(&theme, LayoutContext::default());
+        tree.layout(Rect::new(0.0, 0.0, 600.0, 300.0));
+        let bounds = |tree: &UITree, name| tree.find_widget(name).and_then(|node| node.bounds);
+
+        assert_eq!(
+            bounds(&tree, "mainbox"),
+            Some(Rect::new(0.0, 10.0, 600.0, 280.0))
+        );
+        assert_eq!(
+            bounds(&tree, "wallpaper-panel"),
+            Some(Rect::new(0.0, 10.0, 300.0, 280.0))
+        );
+        assert_eq!(
+            bounds(&tree, "listbox"),
+            Some(Rect::new(400.0, 10.0, 200.0, 280.0))
+        );
+
+        // Too narrow: the panel shrinks down to its min width
+        tree.layout(Rect::new(0.0, 0.0, 300.0, 300.0));
+        assert_eq!(
+            bounds(&tree, "wallpaper-panel").map(|r| r.width),
+            Some(200.0)
+        );
+        assert_eq!(bounds(&tree, "listbox").map(|r| r.x), Some(220.0));
+    }
+
+    #[test]
+    fn test_layout_relative_sizes() {
+        let theme = ThemeTree::parse(
+            r#"
+            mainbox {
+                orientation: horizontal;
+                height: calc(100% - 48px);
+                children: [ "wallpaper-panel", "listbox" ];
+            }
+            wallpaper-panel { width: 10vw; }
+            listbox { width: 40ch; flex-shrink: 0; children: [ "listview" ]; }
+        "#,
+        )
+        .unwrap();
+        let ctx = LayoutContext {
+            viewport_width: 2000.0,
+            char_width: 7.5,
+            ..LayoutContext::default()
+        };
+        let mut tree = UITree::from_theme(&theme, ctx);
+        tree.layout(Rect::new(0.0, 0.0, 800.0, 400.0));
+        let bounds = |name| tree.find_widget(name).and_then(|node| node.bounds);
+
+        assert_eq!(bounds("mainbox"), Some(Rect::new(0.0, 0.0, 800.0, 352.0)));
+        assert_eq!(
+            bounds("wallpaper-panel"),
+            Some(Rect::new(0.0, 0.0, 200.0, 352.0))
+        );
+        assert_eq!(bounds("listbox"), Some(Rect::new(200.0, 0.0, 300.0, 352.0)));
+    }
+
+    #[test]
+    fn test_rebuild_keeps_state() {
+        let theme = ThemeTree::parse(
+            r#"
+            mainbox { children: [ "inputbar", "listbox" ]; }
+            textbox { font-size: 14; }
+        "#,
+        )
+        .unwrap();
+        let mut tree = UITree::from_theme(&theme, LayoutContext::default());
+        assert!(tree.set_text("textbox", "fire"));
+        assert!(tree.set_text("version", "v1"));
+        let textbox = tree.find_widget_mut("textbox").unwrap();
+        textbox.widget.set_state(WidgetState::FOCUSED);
+
+        let restyled = ThemeTree::parse(
+            r#"
+            mainbox { children: [ "inputbar", "listbox" ]; }
+            textbox { font-size: 20; }
+            version { font-size: 12; }
+        "#,
+        )
+        .unwrap();
+        tree.rebuild(&restyled);
+        tree.layout(Rect::new(0.0, 0.0, 600.0, 300.0));
+
+        // Same widgets with their text and state, in the new style
+        let textbox = tree.find_widget("textbox").unwrap();
+        assert_eq!(textbox.widget.state(), WidgetState::FOCUSED);
+        let mut list = DisplayList::new();
+        tree.render(&mut list);
+        let texts: Vec<(&str, f32)> = list
+            .texts()
+            .map(|run| (run.text.as_str(), run.font_size))
+            .collect();
+        assert_eq!(texts, [("fire", 20.0), ("v1", 12.0)]);
+    }
+
+    #[test]
+    fn test_rebuild_changed_children() {
+        let theme = ThemeTree::parse(
+            r#"
+            mainbox { children: [ "inputbar", "listbox" ]; }
+        "#,
+        )
+        .unwrap();
+        let mut tree = UITree::from_theme(&theme, LayoutContext::default());
+        assert!(tree.set_text("textbox", "fire"));
+        let listview = tree.find_widget_mut("listview").unwrap();
+        listview.widget.set_state(WidgetState::SELECTED);
+
+        // The inputbar goes, a panel comes, the listbox stays
+        let changed = ThemeTree::parse(
+            r#"
+            mainbox { children: [ "listbox", "status-panel" ]; }
+            listbox { children: [ "listview", "dummy" ]; }
+        "#,
+        )
+        .unwrap();
+        tree.rebuild(&changed);
+        assert!(tree.find_widget("inputbar").is_none());
+        assert!(tree.find_widget("textbox").is_none());
+        let status = tree.find_widget("status-panel").unwrap();
+        assert_eq!(status.path, "window mainbox status-panel");
+        let listview = tree.find_widget("listview").unwrap();
+        assert_eq!(listview.widget.state(), WidgetState::SELECTED);
+        let listbox = tree.find_widget("listbox").unwrap();
+        let children: Vec<&str> = listbox.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(children, ["listview", "dummy"]);
+
+        // A textbox that comes back is a new one
+        tree.rebuild(&theme);
+        tree.layout(Rect::new(0.0, 0.0, 600.0, 300.0));
+        let mut list = DisplayList::new();
+        tree.render(&mut list);
+        assert!(list.texts().all(|run| run.text != "fire"));
+    }
+
+    #[test]
+    fn test_path_of() {
+        let theme = ThemeTree::parse(
+            r#"
+            mainbox { children: [ "inputbar", "listbox" ]; }
+            listbox { children: [ "listview", "listbox" ]; }
+        "#,
+        )
+        .unwrap();
+        let factory = WidgetFactory::new(&theme);
+
+        assert_eq!(
+            factory.path_of("listview").as_deref(),
+            Some("window mainbox listbox listview")
+        );
+        assert_eq!(
+            factory.path_of("textbox").as_deref(),
+            Some("window mainbox inputbar textbox")
+        );
+        assert_eq!(factory.path_of("gridview"), None);
+
+        let factory = WidgetFactory::new(&theme).with_root_state("launcher");
+        assert_eq!(
+            factory.path_of("mainbox").as_deref(),
+            Some("window.launcher mainbox")
+        );
+    }
 }
@@ -0,0 +1,755 @@
+//! Flexbox layout of a container's children
+//!
+//! The measure/arrange pass behind `Container` and `UITree::layout`, a
+//! subset of CSS flexbox driven by `LayoutProps`. Children are laid out
+//! along the container's `orientation` (the main axis): each starts at its
+//! `flex-basis`, then `flex-grow` shares out free space and `flex-shrink`
+//! takes back overflow, within the child's `min-*`/`max-*` sizes. Leftover
+//! space is placed by `justify-content`, and children are placed across the
+//! axis by `align-items`/`align-self`. With `flex-wrap: wrap`, children that
+//! don't fit start a new line.
+//!
+//! `spacing` is the gap between children, and between wrapped lines.
+//! Margins are outside a child's rect. Everything is pure geometry, in the
+//! units of the props.
+
+use crate::theme::types::{Orientation, Rect};
+
+use super::base::{LayoutProps, Size};
+
+/// How leftover space on the main axis is placed around children
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JustifyContent {
+    #[default]
+    Start,
+    End,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+    SpaceEvenly,
+}
+
+impl JustifyContent {
+    /// Parse from a theme keyword (`start`, `space-between`, ...)
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "start" | "flex-start" => Some(Self::Start),
+            "end" | "flex-end" => Some(Self::End),
+            "center" => Some(Self::Center),
+            "space-between" => Some(Self::SpaceBetween),
+            "space-around" => Some(Self::SpaceAround),
+            "space-evenly" => Some(Self::SpaceEvenly),
+            _ => None,
+        }
+    }
+}
+
+/// Placement of a child across the main axis (`align-items`, `align-self`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    End,
+    Center,
+    /// Fill the line, unless the child has a fixed cross size
+    #[default]
+    Stretch,
+}
+
+impl Align {
+    /// Parse from a theme keyword (`start`, `center`, `stretch`, ...)
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "start" | "flex-start" => Some(Self::Start),
+            "end" | "flex-end" => Some(Self::End),
+            "center" => Some(Self::Center),
+            "stretch" => Some(Self::Stretch),
+            _ => None,
+        }
+    }
+}
+
+/// Whether children that don't fit start a new line
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlexWrap {
+    #[default]
+    NoWrap,
+    Wrap,
+}
+
+impl FlexWrap {
+    /// Parse from a theme keyword (`nowrap`, `wrap`)
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "nowrap" => Some(Self::NoWrap),
+            "wrap" => Some(Self::Wrap),
+            _ => None,
+        }
+    }
+}
+
+/// A child to lay out: its layout props and the size it measured at
+#[derive(Clone, Copy, Debug)]
+pub struct FlexItem<'a> {
+    pub props: &'a LayoutProps,
+    pub measured: Size,
+}
+
+impl<'a> FlexItem<'a> {
+    pub fn new(props: &'a LayoutProps, measured: Size) -> Self {
+        Self { props, measured }
+    }
+}
+
+/// A child's sizes along the container's axes
+struct Resolved {
+    basis: f32,
+    grow: f32,
+    shrink: f32,
+    min_main: f32,
+    max_main: f32,
+    /// Margins before and after the child on each axis
+    margin_main: (f32, f32),
+    margin_cross: (f32, f32),
+    /// Cross size unless stretched
+    cross: f32,
+    fixed_cross: bool,
+    min_cross: f32,
+    max_cross: f32,
+    align: Align,
+}
+
+impl Resolved {
+    fn new(container: &LayoutProps, item: &FlexItem) -> Self {
+        let props = item.props;
+        let (top, right, bottom, left) = props.margin;
+        let horizontal = container.orientation == Orientation::Horizontal;
+        let pick = |h: Option<f32>, v: Option<f32>| if horizontal { (h, v) } else { (v, h) };
+
+        let (fixed_main, fixed_cross) = pick(props.fixed_width, props.fixed_height);
+        let (min_main, min_cross) = pick(props.min_width, props.min_height);
+        let (max_main, max_cross) = pick(props.max_width, props.max_height);
+        let (measured_main, measured_cross) = if horizontal {
+            (item.measured.width, item.measured.height)
+        } else {
+            (item.measured.height, item.measured.width)
+        };
+
+        let min_main = min_main.unwrap_or(0.0);
+        let max_main = max_main.unwrap_or(f32::INFINITY).max(min_main);
+        let min_cross = min_cross.unwrap_or(0.0);
+        let max_cross = max_cross.unwrap_or(f32::INFINITY).max(min_cross);
+        Self {
+            basis: props
+                .flex_basis()
+                .or(fixed_main)
+                .unwrap_or(measured_main)
+                .max(0.0),
+            grow: props.flex_grow().max(0.0),
+            shrink: props.shrink.max(0.0),
+            min_main,
+            max_main,
+            margin_main: if horizontal {
+                (left, right)
+            } else {
+                (top, bottom)
+            },
+            margin_cross: if horizontal {
+                (top, bottom)
+            } else {
+                (left, right)
+            },
+            cross: fixed_cross
+                .unwrap_or(measured_cross)
+                .clamp(min_cross, max_cross),
+            fixed_cross: fixed_cross.is_some(),
+            min_cross,
+            max_cross,
+            align: props.align_self.unwrap_or(container.align_items),
+        }
+    }
+
+    /// The basis within the child's min and max size
+    fn hypothetical(&self) -> f32 {
+        self.basis.clamp(self.min_main, self.max_main)
+    }
+
+    fn outer_main(&self, main: f32) -> f32 {
+        main + self.margin_main.0 + self.margin_main.1
+    }
+
+    fn outer_cross(&self) -> f32 {
+        self.cross + self.margin_cross.0 + self.margin_cross.1
+    }
+}
+
+/// Break children into lines of at most `available` along the main axis
+///
+/// Returns index ranges into `items`; without wrapping there's one line.
+fn break_lines(
+    container: &LayoutProps,
+    items: &[Resolved],
+    available: f32,
+) -> Vec<std::ops::Range<usize>> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+    if container.wrap == FlexWrap::NoWrap {
+        return std::iter::once(0..items.len()).collect();
+    }
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut used = 0.0;
+    for (i, item) in items.iter().enumerate() {
+        let outer = item.outer_main(item.hypothetical());
+        let needed = if i == start {
+            outer
+        } else {
+            used + container.spacing + outer
+        };
+        if i > start && needed > available {
+            lines.push(start..i);
+            start = i;
+            used = outer;
+        } else {
+            used = needed;
+        }
+    }
+    lines.push(start..items.len());
+    lines
+}
+
+/// Main sizes of a line's children after growing or shrinking them to fill
+/// `available`, following CSS's resolution of flexible lengths
+fn flex_line(items: &[Resolved], available: f32, spacing: f32) -> Vec<f32> {
+    let hypothetical: Vec<f32> = items.iter().map(Resolved::hypothetical).collect();
+    if !available.is_finite() {
+        return hypothetical;
+    }
+
+    let gaps = spacing * items.len().saturating_sub(1) as f32;
+    let margins: f32 = items.iter().map(|item| item.outer_main(0.0)).sum();
+    let growing = hypothetical.iter().sum::<f32>() + margins + gaps < available;
+
+    // Children that can't flex keep their hypothetical size
+    let mut sizes = hypothetical.clone();
+    let mut frozen: Vec<bool> = items
+        .iter()
+        .zip(&hypothetical)
+        .map(|(item, &hypothetical)| {
+            let factor = if growing { item.grow } else { item.shrink };
+            factor == 0.0
+                || (growing && item.basis > hypothetical)
+                || (!growing && item.basis < hypothetical)
+        })
+        .collect();
+
+    while frozen.contains(&false) {
+        let used: f32 = (0..items.len())
+            .map(|i| if frozen[i] { sizes[i] } else { items[i].basis })
+            .sum();
+        let mut free = available - margins - gaps - used;
+        let unfrozen = || (0..items.len()).filter(|&i| !frozen[i]);
+
+        if growing {
+            // Factors summing to less than 1 only take that share
+            let total: f32 = unfrozen().map(|i| items[i].grow).sum();
+            if total < 1.0 {
+                free *= total;
+            }
+            for i in unfrozen() {
+                sizes[i] = items[i].basis + free * items[i].grow / total;
+            }
+        } else {
+            // Shrink in proportion to the basis, so small children keep more
+            let total: f32 = unfrozen().map(|i| items[i].shrink * items[i].basis).sum();
+            for i in unfrozen() {
+                let share = if total > 0.0 {
+                    items[i].shrink * items[i].basis / total
+                } else {
+                    0.0
+                };
+                sizes[i] = items[i].basis + free * share;
+            }
+        }
+
+        // Clamp to min/max, then freeze the side that was violated
+        let mut violation = 0.0;
+        let clamped: Vec<f32> = (0..items.len())
+            .map(|i| {
+                let clamped = sizes[i].clamp(items[i].min_main, items[i].max_main);
+                if !frozen[i] {
+                    violation += clamped - sizes[i];
+                }
+                clamped
+            })
+            .collect();
+        for i in 0..items.len() {
+            if frozen[i] {
+                continue;
+            }
+            let freeze = if violation == 0.0 {
+                true
+            } else if violation > 0.0 {
+                clamped[i] > sizes[i]
+            } else {
+                clamped[i] < sizes[i]
+            };
+            if freeze {
+                sizes[i] = clamped[i];
+                frozen[i] = true;
+            }
+        }
+    }
+    sizes
+}
+
+/// Offset of the first child and extra gap between children for
+/// `justify-content`, given the space left on a line
+fn justify(justify: JustifyContent, remaining: f32, count: usize) -> (f32, f32) {
+    if !remaining.is_finite() {
+        return (0.0, 0.0);
+    }
+    let n = count as f32;
+    match justify {
+        JustifyContent::Start => (0.0, 0.0),
+        JustifyContent::End => (remaining, 0.0),
+        JustifyContent::Center => (remaining / 2.0, 0.0),
+        // Overflow can't be distributed, so the space-* modes start aligned
+        _ if remaining <= 0.0 => (0.0, 0.0),
+        JustifyContent::SpaceBetween if count > 1 => (0.0, remaining / (n - 1.0)),
+        JustifyContent::SpaceBetween => (0.0, 0.0),
+        JustifyContent::SpaceAround => (remaining / n / 2.0, remaining / n),
+        JustifyContent::SpaceEvenly => (remaining / (n + 1.0), remaining / (n + 1.0)),
+    }
+}
+
+/// Size of a container around its children, including its padding
+///
+/// `max` bounds the container (`f32::INFINITY` if unbounded); wrapping
+/// containers break lines at its main size.
+pub fn measure(container: &LayoutProps, items: &[FlexItem], max: Size) -> Size {
+    let resolved: Vec<Resolved> = items
+        .iter()
+        .map(|item| Resolved::new(container, item))
+        .collect();
+    let horizontal = container.orientation == Orientation::Horizontal;
+    let available = if horizontal {
+        max.width - container.padding_horizontal()
+    } else {
+        max.height - container.padding_vertical()
+    };
+
+    let lines = break_lines(container, &resolved, available);
+    let mut main = 0.0f32;
+    let mut cross = 0.0;
+    for line in &lines {
+        let items = &resolved[line.clone()];
+        let line_main: f32 = items
+            .iter()
+            .map(|item| item.outer_main(item.hypothetical()))
+            .sum();
+        let gaps = container.spacing * items.len().saturating_sub(1) as f32;
+        main = main.max(line_main + gaps);
+        cross += items.iter().map(Resolved::outer_cross).fold(0.0, f32::max);
+    }
+    cross += container.spacing * lines.len().saturating_sub(1) as f32;
+
+    if horizontal {
+        Size::new(
+            main + container.padding_horizontal(),
+            cross + container.padding_vertical(),
+        )
+    } else {
+        Size::new(
+            cross + container.padding_horizontal(),
+            main + container.padding_vertical(),
+        )
+    }
+}
+
+/// Rects of a container's children within its `bounds`, in child order
+///
+/// Children are placed inside the container's padding, their margins
+/// outside the returned rects.
+pub fn arrange(container: &LayoutProps, bounds: Rect, items: &[FlexItem]) -> Vec<Rect> {
+    let resolved: Vec<Resolved> = items
+        .iter()
+        .map(|item| Resolved::new(container, item))
+        .collect();
+    let (pad_t, pad_r, pad_b, pad_l) = container.padding;
+    let content = Rect::new(
+        bounds.x + pad_l,
+        bounds.y + pad_t,
+        (bounds.width - pad_l - pad_r).max(0.0),
+        (bounds.height - pad_t - pad_b).max(0.0),
+    );
+    let horizontal = container.orientation == Orientation::Horizontal;
+    let (main_start, cross_start, available_main, available_cross) = if horizontal {
+        (content.x, content.y, content.width, content.height)
+    } else {
+        (content.y, content.x, content.height, content.width)
+    };
+    let spacing = container.spacing;
+
+    let lines = break_lines(container, &resolved, available_main);
+    let line_sizes: Vec<Vec<f32>> = lines
+        .iter()
+        .map(|line| flex_line(&resolved[line.clone()], available_main, spacing))
+        .collect();
+
+    // A single line fills the container; wrapped lines fit their children
+    // and share what's left equally
+    let mut line_crosses: Vec<f32> = lines
+        .iter()
+        .map(|line| {
+            resolved[line.clone()]
+                .iter()
+                .map(Resolved::outer_cross)
+                .fold(0.0, f32::max)
+        })
+        .collect();
+    if container.wrap == FlexWrap::NoWrap {
+        if let Some(cross) = line_crosses.first_mut() {
+            *cross = available_cross;
+        }
+    } else if !lines.is_empty() {
+        let used: f32 = line_crosses.iter().sum::<f32>() + spacing * (lines.len() - 1) as f32;
+        let extra = (available_cross - used).max(0.0) / lines.len() as f32;
+        for cross in &mut line_crosses {
+            *cross += extra;
+        }
+    }
+
+    let mut rects = vec![Rect::zero(); items.len()];
+    let mut line_start = cross_start;
+    for ((line, sizes), line_cross) in lines.iter().zip(&line_sizes).zip(&line_crosses) {
+        let items = &resolved[line.clone()];
+        let used: f32 = items
+            .iter()
+            .zip(sizes)
+            .map(|(item, &size)| item.outer_main(size))
+            .sum();
+        let gaps = spacing * items.len().saturating_sub(1) as f32;
+        let (lead, between) = justify(container.justify, available_main - used - gaps, items.len());
+
+        let mut pos = main_start + lead;
+        for ((index, item), &main) in line.clone().zip(items).zip(sizes) {
+            let (margin_start, margin_end) = item.margin_cross;
+            let cross = if item.align == Align::Stretch && !item.fixed_cross {
+                (line_cross - margin_start - margin_end).clamp(item.min_cross, item.max_cross)
+            } else {
+                item.cross
+            };
+            let offset = match item.align {
+                Align::Start | Align::Stretch => margin_start,
+                Align::End => line_cross - cross - margin_end,
+                Align::Center => {
+                    margin_start + (line_cross - cross - margin_start - margin_end) / 2.0
+                }
+            };
+
+            pos += item.margin_main.0;
+            rects[index] = if horizontal {
+                Rect::new(pos, line_start + offset, main, cross)
+            } else {
+                Rect::new(line_start + offset, pos, cross, main)
+            };
+            pos += main + item.margin_main.1 + spacing + between;
+        }
+        line_start += line_cross + spacing;
+    }
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row() -> LayoutProps {
+        LayoutProps {
+            orientation: Orientation::Horizontal,
+            ..Default::default()
+        }
+    }
+
+    fn fixed(width: f32, height: f32) -> LayoutProps {
+        LayoutProps {
+            fixed_width: Some(width),
+            fixed_height: Some(height),
+            ..Default::default()
+        }
+    }
+
+    fn layout(container: &LayoutProps, bounds: Rect, children: &[LayoutProps]) -> Vec<Rect> {
+        let items: Vec<FlexItem> = children
+            .iter()
+            .map(|props| FlexItem::new(props, Size::ZERO))
+            .collect();
+        arrange(container, bounds, &items)
+    }
+
+    #[test]
+    fn test_grow_by_weight() {
+        let container = LayoutProps {
+            spacing: 10.0,
+            padding: (5.0, 5.0, 5.0, 5.0),
+            ..row()
+        };
+        let children = [
+            LayoutProps {
+                fixed_width: Some(90.0),
+                ..Default::default()
+            },
+            LayoutProps {
+                grow: Some(1.0),
+                ..Default::default()
+            },
+            LayoutProps {
+                grow: Some(2.0),
+                ..Default::default()
+            },
+        ];
+        let rects = layout(&container, Rect::new(0.0, 0.0, 420.0, 60.0), &children);
+        assert_eq!(
+            rects,
+            [
+                Rect::new(5.0, 5.0, 90.0, 50.0),
+                Rect::new(105.0, 5.0, 100.0, 50.0),
+                Rect::new(215.0, 5.0, 200.0, 50.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_is_flex_one() {
+        // `expand: true` grows from a zero basis, whatever the measured size
+        let container = row();
+        let children = [
+            LayoutProps {
+                expand: true,
+                ..Default::default()
+            },
+            LayoutProps {
+                expand: true,
+                ..Default::default()
+            },
+        ];
+        let items = [
+            FlexItem::new(&children[0], Size::new(150.0, 0.0)),
+            FlexItem::new(&children[1], Size::new(10.0, 0.0)),
+        ];
+        let rects = arrange(&container, Rect::new(0.0, 0.0, 200.0, 10.0), &items);
+        assert_eq!(rects[0].width, 100.0);
+        assert_eq!(rects[1], Rect::new(100.0, 0.0, 100.0, 10.0));
+    }
+
+    #[test]
+    fn test_min_max_sizes() {
+        let container = row();
+        let children = [
+            LayoutProps {
+                grow: Some(1.0),
+                max_width: Some(50.0),
+                ..Default::default()
+            },
+            LayoutProps {
+                grow: Some(1.0),
+                ..Default::default()
+            },
+        ];
+        let rects = layout(&container, Rect::new(0.0, 0.0, 300.0, 10.0), &children);
+        // The first child stops at its max, the second takes the rest
+        assert_eq!(rects[0].width, 50.0);
+        assert_eq!(rects[1].width, 250.0);
+
+        let children = [
+            LayoutProps {
+                fixed_width: Some(200.0),
+                min_width: Some(150.0),
+                ..Default::default()
+            },
+            LayoutProps {
+                fixed_width: Some(200.0),
+                ..Default::default()
+            },
+        ];
+        let rects = layout(&container, Rect::new(0.0, 0.0, 200.0, 10.0), &children);
+        // Shrinking stops at the min width, the other child gives up the rest
+        assert_eq!(rects[0].width, 150.0);
+        assert_eq!(rects[1].width, 50.0);
+    }
+
+    #[test]
+    fn test_shrink() {
+        let container = row();
+        let children = [
+            LayoutProps {
+                basis: Some(300.0),
+                ..Default::default()
+            },
+            LayoutProps {
+                basis: Some(100.0),
+                ..Default::default()
+            },
+            LayoutProps {
+                basis: Some(100.0),
+                shrink: 0.0,
+                ..Default::default()
+            },
+        ];
+        let rects = layout(&container, Rect::new(0.0, 0.0, 400.0, 10.0), &children);
+        // 100px of overflow is taken in proportion to shrink * basis
+        let widths: Vec<f32> = rects.iter().map(|r| r.width).collect();
+        assert_eq!(widths, [225.0, 75.0, 100.0]);
+    }
+
+    #[test]
+    fn test_justify_content() {
+        let children = [fixed(20.0, 10.0), fixed(20.0, 10.0), fixed(20.0, 10.0)];
+        let bounds = Rect::new(0.0, 0.0, 120.0, 10.0);
+        let positions = |justify| -> Vec<f32> {
+            let container = LayoutProps { justify, ..row() };
+            layout(&container, bounds, &children)
+                .iter()
+                .map(|r| r.x)
+                .collect()
+        };
+        assert_eq!(positions(JustifyContent::Start), [0.0, 20.0, 40.0]);
+        assert_eq!(positions(JustifyContent::End), [60.0, 80.0, 100.0]);
+        assert_eq!(positions(JustifyContent::Center), [30.0, 50.0, 70.0]);
+        assert_eq!(positions(JustifyContent::SpaceBetween), [0.0, 50.0, 100.0]);
+        assert_eq!(positions(JustifyContent::SpaceAround), [10.0, 50.0, 90.0]);
+        assert_eq!(positions(JustifyContent::SpaceEvenly), [15.0, 50.0, 85.0]);
+    }
+
+    #[test]
+    fn test_align_items_and_self() {
+        let container = LayoutProps {
+            align_items: Align::Center,
+            ..row()
+        };
+        let children = [
+            fixed(10.0, 20.0),
+            LayoutProps {
+                align_self: Some(Align::End),
+                ..fixed(10.0, 20.0)
+            },
+            LayoutProps {
+                fixed_width: Some(10.0),
+                align_self: Some(Align::Stretch),
+                margin: (5.0, 0.0, 5.0, 0.0),
+                ..Default::default()
+            },
+            // Stretching doesn't override a fixed height
+            LayoutProps {
+                align_self: Some(Align::Stretch),
+                ..fixed(10.0, 20.0)
+            },
+        ];
+        let rects = layout(&container, Rect::new(0.0, 0.0, 40.0, 100.0), &children);
+        assert_eq!(rects[0], Rect::new(0.0, 40.0, 10.0, 20.0));
+        assert_eq!(rects[1], Rect::new(10.0, 80.0, 10.0, 20.0));
+        assert_eq!(rects[2], Rect::new(20.0, 5.0, 10.0, 90.0));
+        assert_eq!(rects[3], Rect::new(30.0, 0.0, 10.0, 20.0));
+    }
+
+    #[test]
+    fn test_margins_vertical() {
+        let container = LayoutProps {
+            spacing: 4.0,
+            ..Default::default()
+        };
+        let children = [
+            LayoutProps {
+                fixed_height: Some(20.0),
+                margin: (2.0, 3.0, 6.0, 1.0),
+                ..Default::default()
+            },
+            LayoutProps {
+                expand: true,
+                ..Default::default()
+            },
+        ];
+        let rects = layout(&container, Rect::new(0.0, 0.0, 50.0, 100.0), &children);
+        assert_eq!(rects[0], Rect::new(1.0, 2.0, 46.0, 20.0));
+        assert_eq!(rects[1], Rect::new(0.0, 32.0, 50.0, 68.0));
+    }
+
+    #[test]
+    fn test_wrap() {
+        let container = LayoutProps {
+            wrap: FlexWrap::Wrap,
+            spacing: 10.0,
+            align_items: Align::Start,
+            ..row()
+        };
+        let children = [fixed(40.0, 20.0), fixed(40.0, 30.0), fixed(40.0, 20.0)];
+        let rects = layout(&container, Rect::new(0.0, 0.0, 100.0, 100.0), &children);
+        // Two fit on the first line; the spare 40px is shared by both lines
+        assert_eq!(rects[0], Rect::new(0.0, 0.0, 40.0, 20.0));
+        assert_eq!(rects[1], Rect::new(50.0, 0.0, 40.0, 30.0));
+        assert_eq!(rects[2], Rect::new(0.0, 60.0, 40.0, 20.0));
+
+        let items: Vec<FlexItem> = children
+            .iter()
+            .map(|props| FlexItem::new(props, Size::ZERO))
+            .collect();
+        assert_eq!(
+            measure(&container, &items, Size::new(100.0, f32::INFINITY)),
+            Size::new(90.0, 60.0)
+        );
+        // Without a bound everything fits on one line
+        assert_eq!(
+            measure(&container, &items, Size::unconstrained()),
+            Size::new(140.0, 30.0)
+        );
+    }
+
+    #[test]
+    fn test_measure() {
+        let container = LayoutProps {
+            spacing: 5.0,
+            padding: (1.0, 2.0, 3.0, 4.0),
+            ..Default::default()
+        };
+        let children = [
+            LayoutProps::default(),
+            LayoutProps {
+                margin: (0.0, 10.0, 0.0, 0.0),
+                max_height: Some(15.0),
+                ..Default::default()
+            },
+        ];
+        let items = [
+            FlexItem::new(&children[0], Size::new(30.0, 20.0)),
+            FlexItem::new(&children[1], Size::new(40.0, 20.0)),
+        ];
+        assert_eq!(
+            measure(&container, &items, Size::unconstrained()),
+            Size::new(56.0, 44.0)
+        );
+        assert_eq!(
+            measure(&container, &[], Size::unconstrained()),
+            Size::new(6.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn test_keywords() {
+        assert_eq!(
+            JustifyContent::from_str("space-between"),
+            Some(JustifyContent::SpaceBetween)
+        );
+        assert_eq!(
+            JustifyContent::from_str("flex-end"),
+            Some(JustifyContent::End)
+        );
+        assert_eq!(Align::from_str("center"), Some(Align::Center));
+        assert_eq!(Align::from_str("baseline"), None);
+        assert_eq!(FlexWrap::from_str("wrap"), Some(FlexWrap::Wrap));
+    }
+}
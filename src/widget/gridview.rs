@@ -4,19 +4,16 @@
 //! - Vertical (default): Thumbnail on TOP, label on BOTTOM (for wallpapers)
 //! - Horizontal: Thumbnail on LEFT, label on RIGHT (for themes)
 
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::path::Path;
 
-use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-use windows::Win32::Graphics::Direct2D::ID2D1Bitmap;
-
-use crate::platform::win32::{ImageLoader, Renderer};
+use crate::animation::{AnimatedProperties, AnimatedValue, Transition};
 use crate::platform::Event;
+use crate::query::Query;
+use crate::render::{DisplayList, TextAlign, TextRun};
 use crate::theme::tree::ThemeTree;
-use crate::theme::types::{Color, LayoutContext, Rect};
+use crate::theme::types::{Color, ImageScale, LayoutContext, Rect};
 
-use super::base::{Constraints, LayoutProps, MeasuredSize};
+use super::base::{Constraints, CornerRadii, LayoutProps, MeasuredSize, StateColors};
 use super::{EventResult, Widget, WidgetState, WidgetStyle};
 
 /// Layout direction for grid items
@@ -30,6 +27,7 @@ pub enum GridLayout {
 }
 
 impl GridLayout {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "horizontal" => GridLayout::Horizontal,
@@ -49,6 +47,7 @@ pub enum SelectionStyle {
 }
 
 impl SelectionStyle {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "label-background" | "label_background" | "labelbackground" => {
@@ -68,6 +67,8 @@ pub struct GridItem {
     pub image_path: Option<String>,
     /// Opaque user payload (e.g. theme dir, wallpaper file)
     pub user_data: String,
+    /// States that come from the item itself, e.g. `ACTIVE` for the current theme
+    pub state: WidgetState,
 }
 
 impl GridItem {
@@ -77,6 +78,7 @@ impl GridItem {
             subtitle: None,
             image_path: None,
             user_data: user_data.into(),
+            state: WidgetState::NORMAL,
         }
     }
 
@@ -89,6 +91,31 @@ impl GridItem {
         self.image_path = Some(image_path.into());
         self
     }
+
+    pub fn with_state(mut self, state: WidgetState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// The items matching `query` by title or subtitle, best first
+    ///
+    /// Shared by the launcher's grid modes and the picker windows. Items
+    /// that score the same keep their order, and an empty query keeps them
+    /// all.
+    pub fn filter(items: &[GridItem], query: &Query) -> Vec<GridItem> {
+        if query.is_empty() {
+            return items.to_vec();
+        }
+        let mut scored: Vec<(i32, &GridItem)> = items
+            .iter()
+            .filter_map(|item| {
+                let m = query.matches(&item.title, item.subtitle.as_deref())?;
+                Some((m.score, item))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, item)| item.clone()).collect()
+    }
 }
 
 /// Style for GridView widget
@@ -118,12 +145,9 @@ pub struct GridViewStyle {
     /// Label width (used for horizontal layout - label to the right of thumbnail)
     pub label_width: f32,
     pub label_height: f32,
-    pub label_color: Color,
-    pub label_color_selected: Color,
+    pub label_color: StateColors,
     /// Background color for label area (used with LabelBackground selection style)
-    pub label_background_color: Color,
-    /// Selected background color for label area
-    pub label_background_color_selected: Color,
+    pub label_background_color: StateColors,
 
     pub selection_color: Color,
     pub selection_width: f32,
@@ -137,6 +161,9 @@ pub struct GridViewStyle {
     pub message_text: String,
     pub message_color: Color,
     pub message_font_size: f32,
+
+    /// Transitions of the tile colors and selection border between states
+    pub transition: Vec<Transition>,
 }
 
 impl Default for GridViewStyle {
@@ -159,10 +186,12 @@ impl Default for GridViewStyle {
 
             label_width: 0.0, // 0 means not applicable (vertical layout)
             label_height: 28.0,
-            label_color: Color::from_hex("#d4d4d4").unwrap_or(Color::WHITE),
-            label_color_selected: Color::WHITE,
-            label_background_color: Color::TRANSPARENT,
-            label_background_color_selected: Color::from_hex("#89b4fa").unwrap_or(Color::BLUE),
+            label_color: StateColors::new(Color::from_hex("#d4d4d4").unwrap_or(Color::WHITE))
+                .with(WidgetState::SELECTED, Color::WHITE),
+            label_background_color: StateColors::new(Color::TRANSPARENT).with(
+                WidgetState::SELECTED,
+                Color::from_hex("#89b4fa").unwrap_or(Color::BLUE),
+            ),
 
             selection_color: Color::from_hex("#264f78").unwrap_or(Color::BLUE),
             selection_width: 3.0,
@@ -174,11 +203,20 @@ impl Default for GridViewStyle {
             message_text: String::new(),
             message_color: Color::from_hex("#6c7086").unwrap_or(Color::WHITE),
             message_font_size: 24.0,
+
+            transition: Vec::new(),
         }
     }
 }
 
 impl GridViewStyle {
+    /// States a tile's label colors can be themed for
+    pub const STATES: WidgetState = WidgetState::SELECTED
+        .union(WidgetState::HOVER)
+        .union(WidgetState::PRESSED)
+        .union(WidgetState::URGENT)
+        .union(WidgetState::ACTIVE);
+
     pub fn from_theme(theme: &ThemeTree, state: Option<&str>) -> Self {
         let default = Self::default();
 
@@ -255,24 +293,19 @@ impl GridViewStyle {
                 "label-height",
                 default.label_height as f64,
             ) as f32,
-            label_color: theme.get_color("gridview", state, "label-color", default.label_color),
-            label_color_selected: theme.get_color(
+            label_color: StateColors::from_theme(
+                theme,
                 "gridview",
-                Some("selected"),
                 "label-color",
-                default.label_color_selected,
+                Self::STATES,
+                &default.label_color,
             ),
-            label_background_color: theme.get_color(
+            label_background_color: StateColors::from_theme(
+                theme,
                 "gridview",
-                state,
                 "label-background-color",
-                default.label_background_color,
-            ),
-            label_background_color_selected: theme.get_color(
-                "gridview",
-                Some("selected"),
-                "label-background-color",
-                default.label_background_color_selected,
+                Self::STATES,
+                &default.label_background_color,
             ),
 
             selection_color: theme.get_color(
@@ -306,6 +339,17 @@ impl GridViewStyle {
                 "font-size",
                 default.message_font_size as f64,
             ) as f32,
+
+            transition: theme.get_transitions("gridview", state),
+        }
+    }
+
+    /// Width of the selection border of a tile in `state`: none unless selected
+    pub fn border_width(&self, state: WidgetState) -> f32 {
+        if state.contains(WidgetState::SELECTED) {
+            self.selection_width
+        } else {
+            0.0
         }
     }
 
@@ -344,18 +388,16 @@ impl GridViewStyle {
     }
 }
 
-#[derive(Clone)]
-struct CachedBitmap {
-    bitmap: ID2D1Bitmap,
-    source_path: String,
-}
-
 /// A scrollable grid of tiles with horizontal scrolling.
 /// Items are laid out in rows (fixed number of rows based on height),
 /// with columns scrolling horizontally.
 pub struct GridView {
     items: Vec<GridItem>,
     selected_index: Option<usize>,
+    /// Item under the mouse
+    hovered_index: Option<usize>,
+    /// Item the mouse button went down on, until it's released
+    pressed_index: Option<usize>,
     /// Horizontal scroll offset in columns
     scroll_col: usize,
     layout: LayoutProps,
@@ -364,8 +406,9 @@ pub struct GridView {
     /// Cached bounds after arrange (used for ensuring selected visibility)
     bounds: Option<Rect>,
     last_scale_factor: f32,
-    /// Thumbnail cache (path -> bitmap)
-    bitmap_cache: RefCell<HashMap<String, CachedBitmap>>,
+    /// Label color and border transitions running after tiles changed state,
+    /// by index
+    animated: HashMap<usize, AnimatedProperties>,
 }
 
 impl GridView {
@@ -373,13 +416,15 @@ impl GridView {
         Self {
             items: Vec::new(),
             selected_index: None,
+            hovered_index: None,
+            pressed_index: None,
             scroll_col: 0,
             layout: LayoutProps::default(),
-            state: WidgetState::Normal,
+            state: WidgetState::NORMAL,
             style: GridViewStyle::default(),
             bounds: None,
             last_scale_factor: 1.0,
-            bitmap_cache: RefCell::new(HashMap::new()),
+            animated: HashMap::new(),
         }
     }
 
@@ -388,12 +433,32 @@ impl GridView {
         self
     }
 
+    /// Update the style (for hot-reload), turning running tile transitions
+    /// towards the new style's values
     pub fn set_style(&mut self, style: GridViewStyle) {
         self.style = style;
+        let tiles: Vec<(usize, WidgetState)> = self
+            .animated
+            .keys()
+            .filter(|&&index| index < self.items.len())
+            .map(|&index| {
+                let state = self.tile_state(index, self.marks());
+                (index, state)
+            })
+            .collect();
+        for (index, state) in tiles {
+            let animated = self.animated.entry(index).or_default();
+            for (property, to) in tile_values(&self.style, state) {
+                animated.retarget(&self.style.transition, property, to);
+            }
+        }
     }
 
     pub fn set_items(&mut self, items: Vec<GridItem>) {
         self.items = items;
+        self.hovered_index = None;
+        self.pressed_index = None;
+        self.animated.clear();
 
         if self.items.is_empty() {
             self.selected_index = None;
@@ -428,11 +493,195 @@ impl GridView {
 
     pub fn select(&mut self, index: usize) {
         if index < self.items.len() {
-            self.selected_index = Some(index);
+            self.transition_tiles(TileMarks {
+                selected: Some(index),
+                ..self.marks()
+            });
             self.ensure_selected_visible();
         }
     }
 
+    /// Set the item under the mouse; returns whether it changed
+    ///
+    /// Moving off the pressed item releases it without a click.
+    pub fn set_hovered(&mut self, index: Option<usize>) -> bool {
+        let changed = self.hovered_index != index;
+        let mut marks = TileMarks {
+            hovered: index,
+            ..self.marks()
+        };
+        if marks.pressed != index {
+            marks.pressed = None;
+        }
+        self.transition_tiles(marks);
+        changed
+    }
+
+    /// Press an item with the mouse, selecting it
+    pub fn press(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.transition_tiles(TileMarks {
+                selected: Some(index),
+                pressed: Some(index),
+                ..self.marks()
+            });
+            self.ensure_selected_visible();
+        }
+    }
+
+    /// Release the mouse button over `index`; returns true if that's the
+    /// item it was pressed on, which makes it a click
+    pub fn release(&mut self, index: Option<usize>) -> bool {
+        let clicked = self.pressed_index.is_some() && self.pressed_index == index;
+        self.transition_tiles(TileMarks {
+            pressed: None,
+            ..self.marks()
+        });
+        clicked
+    }
+
+    /// Get the item the mouse button is held down on
+    pub fn pressed_index(&self) -> Option<usize> {
+        self.pressed_index
+    }
+
+    /// The items currently selected, hovered and pressed
+    fn marks(&self) -> TileMarks {
+        TileMarks {
+            selected: self.selected_index,
+            hovered: self.hovered_index,
+            pressed: self.pressed_index,
+        }
+    }
+
+    /// The state a tile is drawn in, given the selected, hovered and
+    /// pressed items
+    fn tile_state(&self, index: usize, marks: TileMarks) -> WidgetState {
+        let mut state = self.state | self.items[index].state;
+        state.set(WidgetState::SELECTED, marks.selected == Some(index));
+        state.set(WidgetState::HOVER, marks.hovered == Some(index));
+        state.set(WidgetState::PRESSED, marks.pressed == Some(index));
+        state
+    }
+
+    /// Move the selection, hover and press, starting the theme's
+    /// transitions for the tiles whose colors or border change
+    fn transition_tiles(&mut self, marks: TileMarks) {
+        let old = self.marks();
+        self.selected_index = marks.selected;
+        self.hovered_index = marks.hovered;
+        self.pressed_index = marks.pressed;
+
+        let mut tiles = vec![
+            old.selected,
+            old.hovered,
+            old.pressed,
+            marks.selected,
+            marks.hovered,
+            marks.pressed,
+        ];
+        tiles.sort();
+        tiles.dedup();
+        for index in tiles.into_iter().flatten() {
+            if index >= self.items.len() {
+                continue;
+            }
+            let from = self.tile_state(index, old);
+            let to = self.tile_state(index, marks);
+            if from == to {
+                continue;
+            }
+
+            let style = &self.style;
+            let animated = self.animated.entry(index).or_default();
+            for ((property, from), (_, to)) in tile_values(style, from)
+                .into_iter()
+                .zip(tile_values(style, to))
+            {
+                animated.change(&style.transition, property, from, to);
+            }
+        }
+    }
+
+    /// Advance the tiles' state transitions; returns true while any are running
+    pub fn tick(&mut self) -> bool {
+        self.animated.retain(|_, animated| animated.tick());
+        !self.animated.is_empty()
+    }
+
+    /// The value to draw a tile's property with, following its transition
+    fn tile_value<T>(&self, index: usize, property: &str, value: T) -> T
+    where
+        T: Clone + Into<AnimatedValue> + TryFrom<AnimatedValue>,
+    {
+        match self.animated.get(&index) {
+            Some(animated) => animated.get(property, value),
+            None => value,
+        }
+    }
+
+    /// Index of the item whose card contains the point, if any
+    pub fn index_at(&self, x: f32, y: f32) -> Option<usize> {
+        let bounds = self.bounds?;
+        let scale = self.last_scale_factor.max(0.0001);
+        let rows = self.visible_rows(bounds, scale);
+        let gap = self.style.tile_gap * scale;
+        let card_w = self.style.card_width() * scale;
+        let card_h = self.style.card_height() * scale;
+        let (origin_x, origin_y) = self.grid_origin(bounds, scale);
+        if x < origin_x || y < origin_y {
+            return None;
+        }
+
+        let col = ((x - origin_x) / (card_w + gap)) as usize;
+        let row = ((y - origin_y) / (card_h + gap)) as usize;
+        // Points in the gaps between cards hit nothing
+        let in_card_x = x - origin_x - col as f32 * (card_w + gap) <= card_w;
+        let in_card_y = y - origin_y - row as f32 * (card_h + gap) <= card_h;
+        if row >= rows || !in_card_x || !in_card_y {
+            return None;
+        }
+
+        let start_col = self
+            .scroll_col
+            .min(self.total_columns(rows).saturating_sub(1));
+        let index = self.index_for_col_row(start_col + col, row, rows);
+        (index < self.items.len()).then_some(index)
+    }
+
+    /// Top-left corner of the first visible card, centering the grid in
+    /// `rect` where it doesn't fill it
+    fn grid_origin(&self, rect: Rect, scale: f32) -> (f32, f32) {
+        let rows = self.visible_rows(rect, scale);
+        let visible_cols = self.visible_columns(rect, scale);
+        let total_cols = self.total_columns(rows);
+        let gap = self.style.tile_gap * scale;
+        let card_w = self.style.card_width() * scale;
+        let card_h = self.style.card_height() * scale;
+
+        let pad_l = self.style.padding_left * scale;
+        let pad_r = self.style.padding_right * scale;
+        let pad_t = self.style.padding_top * scale;
+        let pad_b = self.style.padding_bottom * scale;
+        let content_w = (rect.width - pad_l - pad_r).max(0.0);
+        let content_h = (rect.height - pad_t - pad_b).max(0.0);
+
+        // Center grid vertically if it doesn't fill the height
+        let grid_h = rows as f32 * card_h + (rows.saturating_sub(1) as f32) * gap;
+        let extra_y = ((content_h - grid_h).max(0.0)) / 2.0;
+
+        // Center grid horizontally if all columns fit
+        let grid_w = visible_cols.min(total_cols) as f32 * card_w
+            + (visible_cols.min(total_cols).saturating_sub(1) as f32) * gap;
+        let extra_x = if total_cols <= visible_cols {
+            ((content_w - grid_w).max(0.0)) / 2.0
+        } else {
+            0.0
+        };
+
+        (rect.x + pad_l + extra_x, rect.y + pad_t + extra_y)
+    }
+
     /// Calculate the number of rows that fit in the visible area
     fn visible_rows(&self, bounds: Rect, scale: f32) -> usize {
         let gap = self.style.tile_gap * scale;
@@ -470,7 +719,7 @@ impl GridView {
         if rows == 0 || self.items.is_empty() {
             return 0;
         }
-        (self.items.len() + rows - 1) / rows
+        self.items.len().div_ceil(rows)
     }
 
     /// Convert item index to (col, row) in column-major order
@@ -580,56 +829,26 @@ impl GridView {
         self.select(new_idx);
     }
 
-    fn load_bitmap(&self, renderer: &Renderer, path: &str) -> Option<ID2D1Bitmap> {
-        {
-            let cache = self.bitmap_cache.borrow();
-            if let Some(cached) = cache.get(path) {
-                if cached.source_path == path {
-                    return Some(cached.bitmap.clone());
-                }
-            }
+    /// Draw a tile's thumbnail with rounded corners, or a placeholder
+    fn render_thumbnail(&self, list: &mut DisplayList, item: &GridItem, rect: Rect, radius: f32) {
+        let placeholder = Color::from_f32(0.12, 0.12, 0.12, 1.0);
+        if radius > 0.0 {
+            list.push_clip(rect, CornerRadii::uniform(radius));
+        }
+        match item.image_path {
+            // Decode at the tile's width rather than the full image size
+            Some(ref path) => list.image(
+                path.as_str(),
+                rect,
+                ImageScale::Width,
+                0.0,
+                Some(placeholder),
+            ),
+            None => list.fill_rect(rect, placeholder),
+        }
+        if radius > 0.0 {
+            list.pop();
         }
-
-        let loader = match ImageLoader::new() {
-            Ok(l) => l,
-            Err(e) => {
-                crate::log!("GridView: ImageLoader::new failed: {:?}", e);
-                return None;
-            }
-        };
-
-        // Load at thumbnail size for performance (480px width = 2x typical 240px display)
-        // This avoids loading full 4K wallpapers into memory
-        let thumb_target_width = 480u32;
-        let loaded = match loader.load_scaled(
-            Path::new(path),
-            thumb_target_width,
-            0, // Height auto-calculated to maintain aspect ratio
-            crate::theme::types::ImageScale::Width,
-        ) {
-            Ok(img) => img,
-            Err(e) => {
-                crate::log!("GridView: failed to load image '{}': {:?}", path, e);
-                return None;
-            }
-        };
-
-        let bitmap = match renderer.create_bitmap(&loaded) {
-            Ok(b) => b,
-            Err(e) => {
-                crate::log!("GridView: failed to create bitmap '{}': {:?}", path, e);
-                return None;
-            }
-        };
-
-        self.bitmap_cache.borrow_mut().insert(
-            path.to_string(),
-            CachedBitmap {
-                bitmap: bitmap.clone(),
-                source_path: path.to_string(),
-            },
-        );
-        Some(bitmap)
     }
 }
 
@@ -639,9 +858,29 @@ impl Default for GridView {
     }
 }
 
+/// The items a grid marks as selected, under the mouse and held down
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct TileMarks {
+    selected: Option<usize>,
+    hovered: Option<usize>,
+    pressed: Option<usize>,
+}
+
+/// The animated label colors and border of a tile in a state, by property
+fn tile_values(style: &GridViewStyle, state: WidgetState) -> [(&'static str, AnimatedValue); 3] {
+    [
+        ("label-color", style.label_color.get(state).into()),
+        (
+            "label-background-color",
+            style.label_background_color.get(state).into(),
+        ),
+        ("border-width", style.border_width(state).into()),
+    ]
+}
+
 impl Widget for GridView {
     fn handle_event(&mut self, event: &Event, ctx: &LayoutContext) -> EventResult {
-        use crate::platform::win32::event::KeyCode;
+        use crate::platform::KeyCode;
 
         match event {
             Event::KeyDown { key, .. } => {
@@ -717,6 +956,38 @@ impl Widget for GridView {
                     _ => EventResult::none(),
                 }
             }
+            Event::MouseMove { x, y } => {
+                if self.set_hovered(self.index_at(*x as f32, *y as f32)) {
+                    EventResult::repaint()
+                } else {
+                    EventResult::none()
+                }
+            }
+            // Mouse down presses and selects a tile, and releasing it over
+            // the same tile is a click
+            Event::MouseDown { x, y, .. } => match self.index_at(*x as f32, *y as f32) {
+                Some(index) => {
+                    self.press(index);
+                    EventResult::repaint()
+                }
+                None => EventResult::none(),
+            },
+            Event::MouseUp { x, y, .. } => {
+                let was_pressed = self.pressed_index.is_some();
+                if self.release(self.index_at(*x as f32, *y as f32)) {
+                    EventResult {
+                        needs_repaint: true,
+                        consumed: true,
+                        text_changed: false,
+                        submit: true,
+                        cancel: false,
+                    }
+                } else if was_pressed {
+                    EventResult::repaint()
+                } else {
+                    EventResult::none()
+                }
+            }
             Event::MouseWheel { delta, .. } => {
                 // Scroll the grid horizontally - delta > 0 means scroll left, delta < 0 means scroll right
                 let Some(bounds) = self.bounds else {
@@ -746,41 +1017,25 @@ impl Widget for GridView {
         }
     }
 
-    fn render(
-        &self,
-        renderer: &mut Renderer,
-        rect: Rect,
-        ctx: &LayoutContext,
-    ) -> Result<(), windows::core::Error> {
-        let bounds = D2D_RECT_F {
-            left: rect.x,
-            top: rect.y,
-            right: rect.x + rect.width,
-            bottom: rect.y + rect.height,
-        };
-
-        if self.style.background_color.a > 0.0 {
-            renderer.fill_rect(bounds, self.style.background_color)?;
-        }
+    fn render(&self, list: &mut DisplayList, rect: Rect, ctx: &LayoutContext) {
+        list.fill_rect(rect, self.style.background_color);
 
         // Show message if empty and message_text is set
         if self.items.is_empty() {
             if !self.style.message_text.is_empty() {
                 let scale = ctx.scale_factor;
-                let msg_format = renderer.create_text_format(
-                    &self.style.font_family,
-                    (self.style.message_font_size * scale).max(12.0),
-                    false,
-                    false,
-                )?;
-                renderer.draw_text_centered(
-                    &self.style.message_text,
-                    &msg_format,
-                    bounds,
-                    self.style.message_color,
-                )?;
+                list.text(
+                    TextRun::new(
+                        self.style.message_text.as_str(),
+                        rect,
+                        self.style.font_family.as_str(),
+                        (self.style.message_font_size * scale).max(12.0),
+                        self.style.message_color,
+                    )
+                    .align(TextAlign::Center),
+                );
             }
-            return Ok(());
+            return;
         }
 
         let scale = ctx.scale_factor;
@@ -798,45 +1053,18 @@ impl Widget for GridView {
 
         let pad_l = self.style.padding_left * scale;
         let pad_r = self.style.padding_right * scale;
-        let pad_t = self.style.padding_top * scale;
-        let pad_b = self.style.padding_bottom * scale;
 
         let content_x = rect.x + pad_l;
-        let content_y = rect.y + pad_t;
         let content_w = (rect.width - pad_l - pad_r).max(0.0);
-        let content_h = (rect.height - pad_t - pad_b).max(0.0);
 
         let total_cols = self.total_columns(rows);
         let start_col = self.scroll_col.min(total_cols.saturating_sub(1));
         let end_col = (start_col + visible_cols + 1).min(total_cols); // +1 for partial column
 
-        // Center grid vertically if it doesn't fill the height
-        let grid_h = rows as f32 * card_h + (rows.saturating_sub(1) as f32) * gap;
-        let extra_y = ((content_h - grid_h).max(0.0)) / 2.0;
-        let origin_y = content_y + extra_y;
+        let (origin_x, origin_y) = self.grid_origin(rect, scale);
 
-        // Center grid horizontally if all columns fit
-        let grid_w = visible_cols.min(total_cols) as f32 * card_w
-            + (visible_cols.min(total_cols).saturating_sub(1) as f32) * gap;
-        let extra_x = if total_cols <= visible_cols {
-            ((content_w - grid_w).max(0.0)) / 2.0
-        } else {
-            0.0
-        };
-        let origin_x = content_x + extra_x;
-
-        // Shared text format
-        let format = match renderer.create_text_format(
-            &self.style.font_family,
-            (self.style.font_size * scale).max(10.0),
-            false,
-            false,
-        ) {
-            Ok(f) => f,
-            Err(_) => {
-                return Ok(());
-            }
-        };
+        // Shared label font
+        let font_size = (self.style.font_size * scale).max(10.0);
 
         let radius = self.style.thumb_radius * scale;
 
@@ -855,175 +1083,89 @@ impl Widget for GridView {
 
                 let y = origin_y + row as f32 * (card_h + gap);
                 let item = &self.items[index];
-                let selected = self.selected_index == Some(index);
+                let state = self.tile_state(index, self.marks());
+                let border_width =
+                    self.tile_value(index, "border-width", self.style.border_width(state)) * scale;
+                let label_color =
+                    self.tile_value(index, "label-color", self.style.label_color.get(state));
 
-                match self.style.layout {
-                    GridLayout::Vertical => {
-                        // Vertical layout: thumbnail on top, label on bottom
-                        let thumb_rect = D2D_RECT_F {
-                            left: x,
-                            top: y,
-                            right: x + thumb_w,
-                            bottom: y + thumb_h,
-                        };
-
-                        // Draw thumbnail with rounded corners
-                        let _layer = if radius > 0.0 {
-                            renderer.push_rounded_clip(thumb_rect, radius, radius)?
-                        } else {
-                            None
-                        };
+                let thumb_rect = Rect::new(x, y, thumb_w, thumb_h);
+                self.render_thumbnail(list, item, thumb_rect, radius);
 
-                        if let Some(ref path) = item.image_path {
-                            if let Some(bitmap) = self.load_bitmap(renderer, path) {
-                                renderer.draw_bitmap_cover(&bitmap, thumb_rect, 1.0)?;
-                            } else {
-                                renderer.fill_rect(
-                                    thumb_rect,
-                                    Color::from_f32(0.12, 0.12, 0.12, 1.0),
-                                )?;
-                            }
-                        } else {
-                            renderer
-                                .fill_rect(thumb_rect, Color::from_f32(0.12, 0.12, 0.12, 1.0))?;
-                        }
-
-                        if _layer.is_some() {
-                            renderer.pop_layer();
-                        }
-
-                        // Label below thumbnail
-                        let label_rect = D2D_RECT_F {
-                            left: x,
-                            top: y + thumb_h,
-                            right: x + thumb_w,
-                            bottom: y + thumb_h + label_h,
-                        };
+                let label_rect = match self.style.layout {
+                    GridLayout::Vertical => {
+                        // Vertical layout: label below the thumbnail
+                        let label_rect = Rect::new(x, y + thumb_h, thumb_w, label_h);
 
                         // Selection rendering based on style
                         match self.style.selection_style {
                             SelectionStyle::Border => {
-                                if selected {
+                                if border_width > 0.0 {
                                     // Draw border around entire card (thumbnail)
-                                    let inset = (self.style.selection_width * scale) / 2.0;
-                                    let ring = D2D_RECT_F {
-                                        left: thumb_rect.left + inset,
-                                        top: thumb_rect.top + inset,
-                                        right: thumb_rect.right - inset,
-                                        bottom: thumb_rect.bottom - inset,
-                                    };
-                                    renderer.draw_rounded_rect(
+                                    let inset = border_width / 2.0;
+                                    let ring = Rect::new(
+                                        thumb_rect.x + inset,
+                                        thumb_rect.y + inset,
+                                        thumb_rect.width - inset * 2.0,
+                                        thumb_rect.height - inset * 2.0,
+                                    );
+                                    list.stroke_rounded_rect(
                                         ring,
                                         radius.max(0.0),
-                                        radius.max(0.0),
                                         self.style.selection_color,
-                                        (self.style.selection_width * scale).max(1.0),
-                                    )?;
+                                        border_width.max(1.0),
+                                    );
                                 }
                             }
                             SelectionStyle::LabelBackground => {
                                 // Draw label background
-                                let bg_color = if selected {
-                                    self.style.label_background_color_selected
-                                } else {
-                                    self.style.label_background_color
-                                };
-                                if bg_color.a > 0.0 {
-                                    renderer.fill_rect(label_rect, bg_color)?;
-                                }
+                                let bg_color = self.tile_value(
+                                    index,
+                                    "label-background-color",
+                                    self.style.label_background_color.get(state),
+                                );
+                                list.fill_rect(label_rect, bg_color);
                             }
                         }
-
-                        let label_color = if selected {
-                            self.style.label_color_selected
-                        } else {
-                            self.style.label_color
-                        };
-                        renderer.draw_text_centered(
-                            &item.title,
-                            &format,
-                            label_rect,
-                            label_color,
-                        )?;
+                        label_rect
                     }
 
                     GridLayout::Horizontal => {
-                        // Horizontal layout: thumbnail on left, label on right
-                        let thumb_rect = D2D_RECT_F {
-                            left: x,
-                            top: y,
-                            right: x + thumb_w,
-                            bottom: y + thumb_h,
-                        };
-
-                        // Draw thumbnail with rounded corners
-                        let _layer = if radius > 0.0 {
-                            renderer.push_rounded_clip(thumb_rect, radius, radius)?
-                        } else {
-                            None
-                        };
-
-                        if let Some(ref path) = item.image_path {
-                            if let Some(bitmap) = self.load_bitmap(renderer, path) {
-                                renderer.draw_bitmap_cover(&bitmap, thumb_rect, 1.0)?;
-                            } else {
-                                renderer.fill_rect(
-                                    thumb_rect,
-                                    Color::from_f32(0.12, 0.12, 0.12, 1.0),
-                                )?;
-                            }
-                        } else {
-                            renderer
-                                .fill_rect(thumb_rect, Color::from_f32(0.12, 0.12, 0.12, 1.0))?;
-                        }
-
-                        if _layer.is_some() {
-                            renderer.pop_layer();
-                        }
-
-                        // Label to the right of thumbnail
-                        let label_rect = D2D_RECT_F {
-                            left: x + thumb_w,
-                            top: y,
-                            right: x + thumb_w + label_w,
-                            bottom: y + thumb_h,
-                        };
+                        // Horizontal layout: label to the right of the thumbnail
+                        let label_rect = Rect::new(x + thumb_w, y, label_w, thumb_h);
 
                         // Selection rendering - border around entire card for horizontal
-                        if selected {
-                            let inset = (self.style.selection_width * scale) / 2.0;
-                            let card_rect = D2D_RECT_F {
-                                left: x + inset,
-                                top: y + inset,
-                                right: x + card_w - inset,
-                                bottom: y + card_h - inset,
-                            };
-                            renderer.draw_rounded_rect(
+                        if border_width > 0.0 {
+                            let inset = border_width / 2.0;
+                            let card_rect = Rect::new(
+                                x + inset,
+                                y + inset,
+                                card_w - inset * 2.0,
+                                card_h - inset * 2.0,
+                            );
+                            list.stroke_rounded_rect(
                                 card_rect,
                                 radius.max(0.0),
-                                radius.max(0.0),
                                 self.style.selection_color,
-                                (self.style.selection_width * scale).max(1.0),
-                            )?;
+                                border_width.max(1.0),
+                            );
                         }
-
-                        let label_color = if selected {
-                            self.style.label_color_selected
-                        } else {
-                            self.style.label_color
-                        };
-                        renderer.draw_text_centered(
-                            &item.title,
-                            &format,
-                            label_rect,
-                            label_color,
-                        )?;
+                        label_rect
                     }
-                }
+                };
+
+                list.text(
+                    TextRun::new(
+                        item.title.as_str(),
+                        label_rect,
+                        self.style.font_family.as_str(),
+                        font_size,
+                        label_color,
+                    )
+                    .align(TextAlign::Center),
+                );
             }
         }
-
-        Ok(())
     }
 
     fn state(&self) -> WidgetState {
@@ -1043,6 +1185,11 @@ impl Widget for GridView {
         // GridView uses GridViewStyle
     }
 
+    fn restyle(&mut self, theme: &ThemeTree, _path: &str) {
+        // The grid is styled by its name, wherever it sits in the layout
+        GridView::set_style(self, GridViewStyle::from_theme(theme, None));
+    }
+
     fn measure(&self, constraints: Constraints, _ctx: &LayoutContext) -> MeasuredSize {
         MeasuredSize::new(constraints.max.width, constraints.max.height)
     }
@@ -1064,6 +1211,8 @@ impl Widget for GridView {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::animation::Easing;
+    use crate::render::DrawCommand;
 
     #[test]
     fn test_gridview_column_major_layout() {
@@ -1150,4 +1299,152 @@ mod tests {
         gv.move_up(2);
         assert_eq!(gv.selected_index(), Some(1)); // Index 1 is (col 0, row 1)
     }
+
+    #[test]
+    fn test_grid_item_filter() {
+        let items = vec![
+            GridItem::new("Nord", "nord").with_subtitle("dark blue"),
+            GridItem::new("Gruvbox", "gruvbox").with_subtitle("dark warm"),
+            GridItem::new("Latte", "latte").with_subtitle("light"),
+        ];
+        let titles = |query: &str| -> Vec<String> {
+            GridItem::filter(&items, &Query::parse(query))
+                .into_iter()
+                .map(|item| item.title)
+                .collect()
+        };
+
+        assert_eq!(titles(""), ["Nord", "Gruvbox", "Latte"]);
+        assert_eq!(titles("dark"), ["Nord", "Gruvbox"]);
+        assert_eq!(titles("!dark"), ["Latte"]);
+        assert_eq!(titles("'gruv"), ["Gruvbox"]);
+    }
+
+    #[test]
+    fn test_gridview_render_thumbnails() {
+        let mut gv = GridView::new();
+        gv.set_items(vec![
+            GridItem::new("Forest", "a").with_image("forest.png"),
+            GridItem::new("Plain", "b"),
+        ]);
+
+        let mut list = DisplayList::new();
+        gv.render(
+            &mut list,
+            Rect::new(0.0, 0.0, 1000.0, 800.0),
+            &LayoutContext::default(),
+        );
+
+        // Only the item with an image draws one, falling back to grey
+        let images: Vec<_> = list
+            .commands()
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Image {
+                    path, placeholder, ..
+                } => Some((path.as_str(), placeholder.is_some())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(images, [("forest.png", true)]);
+
+        let titles: Vec<_> = list.texts().map(|run| run.text.as_str()).collect();
+        assert_eq!(titles, ["Forest", "Plain"]);
+    }
+
+    #[test]
+    fn test_gridview_pressed_tile() {
+        let theme = ThemeTree::parse(
+            "gridview { label-color: #ffffff; } gridview.pressed { label-color: #ff0000; }",
+        )
+        .unwrap();
+        let mut gv = GridView::new().with_style(GridViewStyle::from_theme(&theme, None));
+        gv.set_items(vec![GridItem::new("A", "a"), GridItem::new("B", "b")]);
+        let label_color = |gv: &GridView, title: &str| {
+            let mut list = DisplayList::new();
+            gv.render(
+                &mut list,
+                Rect::new(0.0, 0.0, 1000.0, 800.0),
+                &LayoutContext::default(),
+            );
+            let color = list.texts().find(|run| run.text == title).unwrap().color;
+            color
+        };
+
+        // The tile's pressed color shows while the button is held down on it
+        gv.press(1);
+        assert_eq!(gv.selected_index(), Some(1));
+        assert_eq!(label_color(&gv, "B"), Color::RED);
+        assert_eq!(label_color(&gv, "A"), Color::WHITE);
+
+        // Releasing it over the same tile is a click; anywhere else isn't
+        assert!(gv.release(Some(1)));
+        assert_eq!(label_color(&gv, "B"), Color::WHITE);
+        gv.press(0);
+        assert!(!gv.release(Some(1)));
+
+        // Moving off the tile releases it
+        gv.press(0);
+        gv.set_hovered(Some(1));
+        assert_eq!(gv.pressed_index(), None);
+        assert_eq!(label_color(&gv, "A"), Color::WHITE);
+    }
+
+    #[test]
+    fn test_gridview_render_empty_message() {
+        let mut gv = GridView::new();
+        gv.style.message_text = "No wallpapers".to_string();
+
+        let mut list = DisplayList::new();
+        gv.render(
+            &mut list,
+            Rect::new(0.0, 0.0, 400.0, 300.0),
+            &LayoutContext::default(),
+        );
+        let run = list.texts().next().unwrap();
+        assert_eq!(run.text, "No wallpapers");
+        assert_eq!(run.align, TextAlign::Center);
+    }
+
+    #[test]
+    fn test_gridview_border_width_transition() {
+        let mut gv = GridView::new();
+        gv.style.transition = vec![Transition {
+            property: "border-width".to_string(),
+            duration_ms: 60_000,
+            easing: Easing::Linear,
+        }];
+        gv.set_items(vec![GridItem::new("A", "a"), GridItem::new("B", "b")]);
+        gv.select(1);
+        assert!(gv.tick());
+
+        // The ring around the old selection thins out rather than vanishing
+        let mut list = DisplayList::new();
+        gv.render(
+            &mut list,
+            Rect::new(0.0, 0.0, 1000.0, 800.0),
+            &LayoutContext::default(),
+        );
+        let widths: Vec<f32> = list
+            .commands()
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Stroke { width, .. } => Some(*width),
+                _ => None,
+            })
+            .collect();
+        assert!(widths.iter().any(|width| *width > 2.9), "{:?}", widths);
+
+        // And the new one grows from nothing
+        let selected = gv.tile_state(
+            1,
+            TileMarks {
+                selected: Some(1),
+                ..TileMarks::default()
+            },
+        );
+        let width = gv.style.border_width(selected);
+        assert_eq!(width, 3.0);
+        assert!(gv.tile_value(1, "border-width", width) < 0.1);
+    }
 }
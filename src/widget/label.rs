@@ -0,0 +1,331 @@
+//! Label widget - a single line of static text on a box
+//!
+//! Used for overlays like the tooltip, the version watermark and the grid's
+//! filter query. The text comes from the theme's `content` or is set by the
+//! app.
+
+use crate::platform::Event;
+use crate::render::{DisplayList, TextAlign, TextRun};
+use crate::theme::tree::ThemeTree;
+use crate::theme::types::{Color, LayoutContext, Paint, Rect, Shadow};
+
+use super::base::{Constraints, CornerRadii, LayoutProps, MeasuredSize, Size};
+use super::{overlay, EventResult, Widget, WidgetState, WidgetStyle};
+
+/// Height of a line of text relative to the font size
+const LINE_HEIGHT: f32 = 1.3;
+
+/// A widget that displays a line of text
+pub struct Label {
+    /// Widget name (for theme lookups)
+    name: String,
+    /// Text shown, nothing is drawn while it's empty
+    text: String,
+    /// Layout properties
+    layout: LayoutProps,
+    /// Visual style
+    style: LabelStyle,
+    /// Widget state
+    state: WidgetState,
+}
+
+/// Style for label widget
+#[derive(Clone, Debug)]
+pub struct LabelStyle {
+    pub background_color: Paint,
+    pub text_color: Color,
+    pub border_color: Paint,
+    pub border_width: f32,
+    /// Per-corner border radii
+    pub border_radii: CornerRadii,
+    pub box_shadow: Option<Shadow>,
+    pub font_family: String,
+    pub font_size: f32,
+    pub text_align: TextAlign,
+}
+
+impl Default for LabelStyle {
+    fn default() -> Self {
+        Self {
+            background_color: Paint::default(),
+            text_color: Color::WHITE,
+            border_color: Paint::default(),
+            border_width: 0.0,
+            border_radii: CornerRadii::zero(),
+            box_shadow: None,
+            font_family: "Segoe UI".to_string(),
+            font_size: 12.0,
+            text_align: TextAlign::Leading,
+        }
+    }
+}
+
+impl LabelStyle {
+    /// Style of a built-in label before the theme's properties
+    pub fn default_for(name: &str) -> Self {
+        match name {
+            "tooltip" => Self {
+                background_color: Color::from_f32(0.1, 0.1, 0.1, 0.95).into(),
+                border_radii: CornerRadii::uniform(4.0),
+                ..Default::default()
+            },
+            "version" => Self {
+                text_color: Color::from_f32(1.0, 1.0, 1.0, 0.3),
+                font_size: 10.0,
+                text_align: TextAlign::Trailing,
+                ..Default::default()
+            },
+            "filter" => Self {
+                text_color: Color::from_f32(1.0, 1.0, 1.0, 0.7),
+                ..Default::default()
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// Load style from theme for the label at `path`
+    pub fn from_theme(theme: &ThemeTree, path: &str, state: Option<&str>, default: &Self) -> Self {
+        let base_radius = theme.get_number(
+            path,
+            state,
+            "border-radius",
+            default.border_radii.top_left as f64,
+        );
+        let radius = |property: &str| theme.get_number(path, state, property, base_radius) as f32;
+
+        Self {
+            background_color: theme.get_paint(
+                path,
+                state,
+                "background-color",
+                default.background_color.clone(),
+            ),
+            text_color: theme.get_color(path, state, "text-color", default.text_color),
+            border_color: theme.get_paint(
+                path,
+                state,
+                "border-color",
+                default.border_color.clone(),
+            ),
+            border_width: theme.get_number(path, state, "border-width", default.border_width as f64)
+                as f32,
+            border_radii: CornerRadii {
+                top_left: radius("border-top-left-radius"),
+                top_right: radius("border-top-right-radius"),
+                bottom_right: radius("border-bottom-right-radius"),
+                bottom_left: radius("border-bottom-left-radius"),
+            },
+            box_shadow: theme.get_shadow(path, state, "box-shadow"),
+            font_family: theme.get_string(path, state, "font-family", &default.font_family),
+            font_size: theme.get_number(path, state, "font-size", default.font_size as f64) as f32,
+            text_align: match theme.get_string(path, state, "text-align", "").as_str() {
+                "left" | "start" => TextAlign::Leading,
+                "center" => TextAlign::Center,
+                "right" | "end" => TextAlign::Trailing,
+                _ => default.text_align,
+            },
+        }
+    }
+}
+
+impl Label {
+    /// Create a new label with a name
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            style: LabelStyle::default_for(&name),
+            layout: overlay::default_layout(&name),
+            name,
+            text: String::new(),
+            state: WidgetState::NORMAL,
+        }
+    }
+
+    /// Set the text
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = text.into();
+        self
+    }
+
+    /// Get the widget name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the style
+    pub fn label_style(&self) -> &LabelStyle {
+        &self.style
+    }
+
+    /// Load layout, style and `content` from theme for the label at `path`,
+    /// on top of the defaults for its name
+    pub fn load_from_theme_at(&mut self, theme: &ThemeTree, path: &str) {
+        self.layout = LayoutProps::from_theme(theme, path, &overlay::default_layout(&self.name));
+        self.style =
+            LabelStyle::from_theme(theme, path, None, &LabelStyle::default_for(&self.name));
+        if let Some(content) = theme
+            .get_resolved(path, None, "content")
+            .and_then(|v| v.as_string())
+        {
+            self.text = content;
+        }
+    }
+}
+
+impl Widget for Label {
+    fn handle_event(&mut self, _event: &Event, _ctx: &LayoutContext) -> EventResult {
+        // Labels don't handle events
+        EventResult::none()
+    }
+
+    fn render(&self, list: &mut DisplayList, rect: Rect, ctx: &LayoutContext) {
+        if self.text.is_empty() {
+            return;
+        }
+        let scale = ctx.scale_factor;
+        let radii = self.style.border_radii.scaled(scale);
+
+        if let Some(ref shadow) = self.style.box_shadow {
+            list.shadow(rect, radii, &shadow.scaled(scale));
+        }
+        list.fill(rect, radii, &self.style.background_color);
+        list.stroke(
+            rect,
+            radii,
+            &self.style.border_color,
+            self.style.border_width * scale,
+        );
+
+        let (top, right, bottom, left) = self.layout.padding;
+        let text_rect = Rect::new(
+            rect.x + left * scale,
+            rect.y + top * scale,
+            (rect.width - (left + right) * scale).max(0.0),
+            (rect.height - (top + bottom) * scale).max(0.0),
+        );
+        list.text(
+            TextRun::new(
+                self.text.as_str(),
+                text_rect,
+                &self.style.font_family,
+                self.style.font_size * scale,
+                self.style.text_color,
+            )
+            .align(self.style.text_align),
+        );
+    }
+
+    fn state(&self) -> WidgetState {
+        self.state
+    }
+
+    fn set_state(&mut self, state: WidgetState) {
+        self.state = state;
+    }
+
+    fn style(&self) -> &WidgetStyle {
+        // Labels don't use WidgetStyle directly, return default
+        static DEFAULT: std::sync::OnceLock<WidgetStyle> = std::sync::OnceLock::new();
+        DEFAULT.get_or_init(WidgetStyle::default)
+    }
+
+    fn set_style(&mut self, _style: WidgetStyle) {
+        // Labels use LabelStyle instead
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+    }
+
+    fn measure(&self, constraints: Constraints, ctx: &LayoutContext) -> MeasuredSize {
+        if self.text.is_empty() {
+            return MeasuredSize::new(0.0, 0.0);
+        }
+        let layout = self.layout.resolved(ctx, constraints.max);
+        let font_size = self.style.font_size * ctx.scale_factor;
+        let width = ctx.text_width(&self.text, &self.style.font_family, font_size, false)
+            + layout.padding_horizontal();
+        let height = font_size * LINE_HEIGHT + layout.padding_vertical();
+
+        let size = constraints.constrain(Size::new(
+            layout.fixed_width.unwrap_or(width),
+            layout.fixed_height.unwrap_or(height),
+        ));
+        MeasuredSize::new(size.width, size.height)
+    }
+
+    fn restyle(&mut self, theme: &ThemeTree, path: &str) {
+        self.load_from_theme_at(theme, path);
+    }
+
+    fn layout_props(&self) -> &LayoutProps {
+        &self.layout
+    }
+
+    fn widget_name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::TextMeasure;
+    use std::rc::Rc;
+
+    /// Measures every character at the same advance
+    #[derive(Debug)]
+    struct FixedAdvance(f32);
+
+    impl TextMeasure for FixedAdvance {
+        fn text_width(&self, text: &str, _: &str, _: f32, _: bool) -> f32 {
+            text.chars().count() as f32 * self.0
+        }
+    }
+
+    #[test]
+    fn test_label_from_theme() {
+        let theme = ThemeTree::parse(
+            r#"
+            tooltip { text-color: #ff0000; padding-left: 4; padding-right: 4; font-size: 10; }
+            version { content: "v1.0"; text-align: left; }
+        "#,
+        )
+        .unwrap();
+
+        let mut tooltip = Label::new("tooltip");
+        tooltip.load_from_theme_at(&theme, "tooltip");
+        assert_eq!(tooltip.label_style().text_color, Color::RED);
+        // The built-in background survives the theme's other properties
+        assert!(tooltip.label_style().background_color.is_visible());
+        assert!(tooltip.layout_props().placement.is_absolute());
+
+        // Nothing to show, nothing measured
+        let ctx = LayoutContext::default();
+        let size = tooltip.measure(Constraints::default(), &ctx).size;
+        assert_eq!((size.width, size.height), (0.0, 0.0));
+        tooltip.set_text("Build");
+        let size = tooltip.measure(Constraints::default(), &ctx).size;
+        let text_width = ctx.text_width("Build", "Segoe UI", 10.0, false);
+        assert_eq!((size.width, size.height), (text_width + 8.0, 29.0));
+
+        // The backend's text metrics decide the width
+        let ctx = LayoutContext {
+            text_measure: Some(Rc::new(FixedAdvance(6.0))),
+            ..LayoutContext::default()
+        };
+        let size = tooltip.measure(Constraints::default(), &ctx).size;
+        assert_eq!((size.width, size.height), (38.0, 29.0));
+
+        let mut version = Label::new("version");
+        version.load_from_theme_at(&theme, "version");
+        assert_eq!(version.text(), "v1.0");
+        assert_eq!(version.label_style().text_align, TextAlign::Leading);
+        assert_eq!(version.label_style().font_size, 10.0);
+    }
+}
@@ -0,0 +1,679 @@
+//! Theme-derived layout of the launcher window
+//!
+//! The window is a `mainbox` laid out horizontally: a fixed-width
+//! `wallpaper-panel` and an expanding `listbox` that holds the list or grid.
+//! Their flex properties (`flex-grow`, `min-width`, `margin`, ...) and the
+//! mainbox's `justify-content`/`align-items` apply on top of that.
+
+use crate::mode::Mode;
+use crate::theme::tree::ThemeTree;
+use crate::theme::types::{Color, Distance, LayoutContext, Orientation, Paint, Rect};
+
+use super::base::{CornerRadii, LayoutProps, Size};
+use super::clock::{ClockConfig, ClockPosition};
+use super::factory::WidgetFactory;
+use super::flex::{self, FlexItem};
+use super::gridview::GridView;
+use super::listview::ListView;
+use super::textbox::Textbox;
+use super::Widget;
+
+/// Child widget layout info
+#[derive(Clone, Debug)]
+pub struct ChildLayout {
+    /// Widget name
+    pub name: String,
+    /// Calculated bounds
+    pub bounds: Rect,
+    /// Whether this widget expands
+    pub expand: bool,
+    /// Fixed width (if not expanding)
+    pub fixed_width: Option<f32>,
+}
+
+/// Theme-derived layout settings
+#[derive(Clone, Debug)]
+pub struct ThemeLayout {
+    /// Window border radius
+    pub window_border_radius: f32,
+    /// Window border color
+    pub window_border_color: Color,
+    /// Window background color (tint behind everything)
+    pub window_background_color: Color,
+    /// Mainbox padding
+    pub mainbox_padding: f32,
+    /// Mainbox children names (from theme)
+    pub mainbox_children: Vec<String>,
+    /// Mainbox flex properties, unscaled (the padding is applied by the caller)
+    pub mainbox_layout: LayoutProps,
+    /// Layout of each of `mainbox_children`, unscaled
+    pub children_layout: Vec<LayoutProps>,
+    /// Wallpaper panel width, resolved when laid out
+    pub wallpaper_panel_width: Distance,
+    /// Wallpaper panel background color (overlay)
+    pub wallpaper_panel_bg: Color,
+    /// Wallpaper panel corner radii (per-corner)
+    pub wallpaper_panel_radii: CornerRadii,
+    /// Wallpaper panel diagonal edge offset (0 = no diagonal, positive = slant from top-right)
+    pub wallpaper_panel_diagonal: f32,
+    /// Wallpaper panel fade width (how wide the feathered edge is along the diagonal)
+    pub wallpaper_panel_fade_width: f32,
+    /// Wallpaper panel fade color (color to fade TO at the diagonal edge)
+    pub wallpaper_panel_fade_color: Option<Color>,
+    /// Wallpaper panel fade opacity multiplier (0.0-1.0, controls how solid the fade is)
+    pub wallpaper_panel_fade_opacity: f32,
+    /// Wallpaper panel blur radius for the wallpaper (`backdrop-blur`, 0 = sharp)
+    pub wallpaper_panel_backdrop_blur: f32,
+    /// Listbox background color or gradient
+    pub listbox_bg: Paint,
+    /// Listbox corner radii (per-corner)
+    pub listbox_radii: CornerRadii,
+    /// Listbox padding
+    pub listbox_padding: f32,
+    /// Listview padding
+    pub listview_padding_top: f32,
+    pub listview_padding_right: f32,
+    pub listview_padding_bottom: f32,
+    pub listview_padding_left: f32,
+    /// Animation duration in milliseconds (0 = no animation)
+    pub animation_duration_ms: u32,
+    /// Animation easing type (ease-out, ease-in, ease-in-out, linear)
+    pub animation_easing: String,
+    /// Clock configuration for wallpaper panel overlay
+    pub clock_config: ClockConfig,
+}
+
+impl ThemeLayout {
+    /// Check if wallpaper panel should be shown
+    pub fn show_wallpaper_panel(&self) -> bool {
+        self.mainbox_children.iter().any(|c| c == "wallpaper-panel")
+    }
+
+    /// Check if a specific widget is in mainbox children
+    pub fn has_child(&self, name: &str) -> bool {
+        self.mainbox_children.iter().any(|c| c == name)
+    }
+
+    /// Calculate bounds for each child in mainbox (horizontal layout)
+    pub fn calculate_mainbox_children_bounds(
+        &self,
+        content_x: f32,
+        content_y: f32,
+        content_width: f32,
+        content_height: f32,
+        ctx: &LayoutContext,
+    ) -> Vec<ChildLayout> {
+        let content_size = Size::new(content_width, content_height);
+        let mainbox = self.mainbox_layout.resolved(ctx, content_size);
+        let children: Vec<LayoutProps> = self
+            .children_layout
+            .iter()
+            .map(|props| props.resolved(ctx, content_size))
+            .collect();
+        // Nothing is measured: children without a width or basis only grow
+        let items: Vec<FlexItem> = children
+            .iter()
+            .map(|props| FlexItem::new(props, Size::ZERO))
+            .collect();
+        let content = Rect::new(content_x, content_y, content_width, content_height);
+        let rects = flex::arrange(&mainbox, content, &items);
+
+        self.mainbox_children
+            .iter()
+            .zip(&children)
+            .zip(rects)
+            .map(|((name, props), bounds)| ChildLayout {
+                name: name.clone(),
+                bounds,
+                expand: props.flex_grow() > 0.0,
+                fixed_width: props.fixed_width,
+            })
+            .collect()
+    }
+}
+
+/// Layout of a mainbox child before the theme's flex properties: the
+/// wallpaper panel has a fixed width, everything else expands
+fn default_child_layout(name: &str, wallpaper_panel_width: &Distance) -> LayoutProps {
+    match name {
+        "wallpaper-panel" => LayoutProps {
+            width: Some(wallpaper_panel_width.clone()),
+            ..Default::default()
+        },
+        _ => LayoutProps {
+            expand: true,
+            ..Default::default()
+        },
+    }
+}
+
+impl Default for ThemeLayout {
+    fn default() -> Self {
+        let mainbox_children = vec!["wallpaper-panel".to_string(), "listbox".to_string()];
+        let wallpaper_panel_width = Distance::px(456.0);
+        Self {
+            children_layout: mainbox_children
+                .iter()
+                .map(|name| default_child_layout(name, &wallpaper_panel_width))
+                .collect(),
+            mainbox_children,
+            mainbox_layout: LayoutProps {
+                orientation: Orientation::Horizontal,
+                ..Default::default()
+            },
+            window_border_radius: 16.0,
+            window_border_color: Color::from_hex("#f97e72").unwrap_or(Color::WHITE),
+            window_background_color: Color::TRANSPARENT, // Default to no tint
+            mainbox_padding: 20.0,
+            wallpaper_panel_width,
+            wallpaper_panel_bg: Color::from_hex("#262335e6").unwrap_or(Color::BLACK),
+            wallpaper_panel_radii: CornerRadii::uniform(16.0),
+            wallpaper_panel_diagonal: 0.0,     // No diagonal by default
+            wallpaper_panel_fade_width: 100.0, // Fade gradient width along diagonal edge
+            wallpaper_panel_fade_color: None,  // None = use listbox_bg color
+            wallpaper_panel_fade_opacity: 0.7, // Default opacity multiplier (0.7 = softer fade)
+            wallpaper_panel_backdrop_blur: 0.0,
+            listbox_bg: Color::from_hex("#262335e6").unwrap_or(Color::BLACK).into(),
+            listbox_radii: CornerRadii::uniform(16.0),
+            listbox_padding: 0.0,
+            listview_padding_top: 16.0,
+            listview_padding_right: 32.0,
+            listview_padding_bottom: 16.0,
+            listview_padding_left: 32.0,
+            animation_duration_ms: 200,
+            animation_easing: "ease-out-expo".to_string(),
+            clock_config: ClockConfig::default(),
+        }
+    }
+}
+
+impl ThemeLayout {
+    /// Load layout settings from theme
+    pub fn from_theme(theme: &ThemeTree) -> Self {
+        let default = Self::default();
+
+        // Get mainbox children from theme (or use defaults)
+        let mainbox_children = {
+            let children = theme.get_children("mainbox");
+            if children.is_empty() {
+                default.mainbox_children.clone()
+            } else {
+                children
+            }
+        };
+
+        // Helper to read corner radii from theme
+        let read_corner_radii = |widget: &str, default_radii: CornerRadii| -> CornerRadii {
+            let base =
+                theme.get_number(widget, None, "border-radius", default_radii.top_left as f64)
+                    as f32;
+            CornerRadii {
+                top_left: theme.get_number(widget, None, "border-top-left-radius", base as f64)
+                    as f32,
+                top_right: theme.get_number(widget, None, "border-top-right-radius", base as f64)
+                    as f32,
+                bottom_right: theme.get_number(
+                    widget,
+                    None,
+                    "border-bottom-right-radius",
+                    base as f64,
+                ) as f32,
+                bottom_left: theme.get_number(
+                    widget,
+                    None,
+                    "border-bottom-left-radius",
+                    base as f64,
+                ) as f32,
+            }
+        };
+
+        let wallpaper_panel_width = theme.get_distance(
+            "wallpaper-panel",
+            None,
+            "width",
+            default.wallpaper_panel_width.clone(),
+        );
+        let mut mainbox_layout = default.mainbox_layout.clone();
+        mainbox_layout.load_flex(theme, "mainbox");
+        let children_layout = mainbox_children
+            .iter()
+            .map(|name| {
+                let mut props = default_child_layout(name, &wallpaper_panel_width);
+                props.load_flex(theme, name);
+                props
+            })
+            .collect();
+
+        let layout = Self {
+            window_border_radius: theme.get_number(
+                "window",
+                None,
+                "border-radius",
+                default.window_border_radius as f64,
+            ) as f32,
+            window_border_color: theme.get_color(
+                "window",
+                None,
+                "border-color",
+                default.window_border_color,
+            ),
+            window_background_color: theme.get_color(
+                "window",
+                None,
+                "background-color",
+                default.window_background_color,
+            ),
+            mainbox_padding: theme.get_number(
+                "mainbox",
+                None,
+                "padding",
+                default.mainbox_padding as f64,
+            ) as f32,
+            mainbox_children,
+            mainbox_layout,
+            children_layout,
+            wallpaper_panel_width,
+            wallpaper_panel_bg: theme.get_color(
+                "wallpaper-panel",
+                None,
+                "background-color",
+                default.wallpaper_panel_bg,
+            ),
+            wallpaper_panel_radii: read_corner_radii(
+                "wallpaper-panel",
+                default.wallpaper_panel_radii,
+            ),
+            wallpaper_panel_diagonal: {
+                let val = theme.get_number(
+                    "wallpaper-panel",
+                    None,
+                    "diagonal-edge",
+                    default.wallpaper_panel_diagonal as f64,
+                ) as f32;
+                log!("ThemeLayout: wallpaper_panel_diagonal = {}", val);
+                val
+            },
+            wallpaper_panel_fade_width: {
+                let val = theme.get_number(
+                    "wallpaper-panel",
+                    None,
+                    "fade-width",
+                    default.wallpaper_panel_fade_width as f64,
+                ) as f32;
+                log!("ThemeLayout: wallpaper_panel_fade_width = {}", val);
+                val
+            },
+            wallpaper_panel_fade_color: theme.get_color_opt("wallpaper-panel", None, "fade-color"),
+            wallpaper_panel_fade_opacity: theme.get_number(
+                "wallpaper-panel",
+                None,
+                "fade-opacity",
+                default.wallpaper_panel_fade_opacity as f64,
+            ) as f32,
+            wallpaper_panel_backdrop_blur: theme
+                .get_number(
+                    "wallpaper-panel",
+                    None,
+                    "backdrop-blur",
+                    default.wallpaper_panel_backdrop_blur as f64,
+                )
+                .max(0.0) as f32,
+            listbox_bg: theme.get_paint("listbox", None, "background-color", default.listbox_bg),
+            listbox_radii: read_corner_radii("listbox", default.listbox_radii),
+            listbox_padding: theme.get_number(
+                "listbox",
+                None,
+                "padding-top",
+                default.listbox_padding as f64,
+            ) as f32,
+            listview_padding_top: theme.get_number(
+                "listview",
+                None,
+                "padding-top",
+                default.listview_padding_top as f64,
+            ) as f32,
+            listview_padding_right: theme.get_number(
+                "listview",
+                None,
+                "padding-right",
+                default.listview_padding_right as f64,
+            ) as f32,
+            listview_padding_bottom: theme.get_number(
+                "listview",
+                None,
+                "padding-bottom",
+                default.listview_padding_bottom as f64,
+            ) as f32,
+            listview_padding_left: theme.get_number(
+                "listview",
+                None,
+                "padding-left",
+                default.listview_padding_left as f64,
+            ) as f32,
+            animation_duration_ms: theme.get_number(
+                "window",
+                None,
+                "animation-duration",
+                default.animation_duration_ms as f64,
+            ) as u32,
+            animation_easing: theme.get_string(
+                "window",
+                None,
+                "animation-easing",
+                &default.animation_easing,
+            ),
+            clock_config: ClockConfig {
+                enabled: theme.get_bool(
+                    "wallpaper-panel",
+                    None,
+                    "clock-enabled",
+                    default.clock_config.enabled,
+                ),
+                position: ClockPosition::from_str(&theme.get_string(
+                    "wallpaper-panel",
+                    None,
+                    "clock-position",
+                    "top-right",
+                )),
+                time_format: theme.get_string(
+                    "wallpaper-panel",
+                    None,
+                    "clock-format",
+                    &default.clock_config.time_format,
+                ),
+                date_format: theme.get_string(
+                    "wallpaper-panel",
+                    None,
+                    "clock-date-format",
+                    &default.clock_config.date_format,
+                ),
+                font_family: theme.get_string(
+                    "wallpaper-panel",
+                    None,
+                    "clock-font-family",
+                    &default.clock_config.font_family,
+                ),
+                font_size: theme.get_number(
+                    "wallpaper-panel",
+                    None,
+                    "clock-font-size",
+                    default.clock_config.font_size as f64,
+                ) as f32,
+                date_font_size: theme.get_number(
+                    "wallpaper-panel",
+                    None,
+                    "clock-date-font-size",
+                    default.clock_config.date_font_size as f64,
+                ) as f32,
+                text_color: theme.get_color(
+                    "wallpaper-panel",
+                    None,
+                    "clock-text-color",
+                    default.clock_config.text_color,
+                ),
+                shadow_color: theme.get_color(
+                    "wallpaper-panel",
+                    None,
+                    "clock-shadow-color",
+                    default.clock_config.shadow_color,
+                ),
+                shadow_offset: (
+                    theme.get_number(
+                        "wallpaper-panel",
+                        None,
+                        "clock-shadow-offset-x",
+                        default.clock_config.shadow_offset.0 as f64,
+                    ) as f32,
+                    theme.get_number(
+                        "wallpaper-panel",
+                        None,
+                        "clock-shadow-offset-y",
+                        default.clock_config.shadow_offset.1 as f64,
+                    ) as f32,
+                ),
+                padding: theme.get_number(
+                    "wallpaper-panel",
+                    None,
+                    "clock-padding",
+                    default.clock_config.padding as f64,
+                ) as f32,
+            },
+        };
+
+        // Debug log the loaded theme layout
+        log!(
+            "ThemeLayout::from_theme - window_background_color: r={:.3} g={:.3} b={:.3} a={:.3}",
+            layout.window_background_color.r,
+            layout.window_background_color.g,
+            layout.window_background_color.b,
+            layout.window_background_color.a
+        );
+        log!(
+            "ThemeLayout::from_theme - animation: {}ms, easing={}",
+            layout.animation_duration_ms,
+            layout.animation_easing
+        );
+
+        layout
+    }
+}
+
+/// The font `em` and `ch` units are relative to: the window's `font-family`
+/// and `font-size`
+pub fn base_font(theme: &ThemeTree) -> (String, f32) {
+    let default_size = LayoutContext::default().base_font_size;
+    (
+        theme.get_string("window", None, "font-family", "Segoe UI"),
+        theme.get_number("window", None, "font-size", default_size as f64) as f32,
+    )
+}
+
+/// Size of a window from its theme's `window { width; height }`, `default`
+/// where unset
+///
+/// `ctx` is in logical pixels (a scale factor of 1) with the monitor as its
+/// viewport: as in rofi, percentages are of the monitor's size.
+pub fn window_size(theme: &ThemeTree, ctx: &LayoutContext, default: (f32, f32)) -> (f32, f32) {
+    let size = |property, default: f32, parent_size| {
+        let ctx = LayoutContext {
+            parent_size,
+            ..ctx.clone()
+        };
+        theme
+            .get_distance("window", None, property, Distance::px(default as f64))
+            .to_pixels(&ctx)
+    };
+    (
+        size("width", default.0, ctx.viewport_width),
+        size("height", default.1, ctx.viewport_height),
+    )
+}
+
+/// Theme path of a widget in the launcher layout (`window.launcher mainbox
+/// listbox listview`), or its bare name if the layout doesn't contain it
+pub fn launcher_widget_path(theme: &ThemeTree, name: &str) -> String {
+    WidgetFactory::new(theme)
+        .with_root_state(Mode::Launcher.state_key())
+        .path_of(name)
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// Restyle the launcher's textbox, list and grid from a reloaded theme,
+/// each at its place in the launcher layout
+///
+/// The widgets are restyled in place, so the query, the selection, the
+/// scroll position and running transitions carry on under the new look.
+pub fn restyle_launcher(
+    theme: &ThemeTree,
+    textbox: &mut Textbox,
+    listview: &mut ListView,
+    gridview: &mut GridView,
+) {
+    let widgets: [(&str, &mut dyn Widget); 3] = [
+        ("textbox", textbox),
+        ("listview", listview),
+        ("gridview", gridview),
+    ];
+    for (name, widget) in widgets {
+        widget.restyle(theme, &launcher_widget_path(theme, name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scaled(scale_factor: f32) -> LayoutContext {
+        LayoutContext {
+            scale_factor,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mainbox_children_bounds() {
+        let layout = ThemeLayout::default();
+        let bounds: Vec<Rect> = layout
+            .calculate_mainbox_children_bounds(10.0, 10.0, 1000.0, 400.0, &scaled(2.0))
+            .iter()
+            .map(|child| child.bounds)
+            .collect();
+        assert_eq!(
+            bounds,
+            [
+                Rect::new(10.0, 10.0, 912.0, 400.0),
+                Rect::new(922.0, 10.0, 88.0, 400.0)
+            ]
+        );
+
+        let theme = ThemeTree::parse(
+            r#"
+            mainbox { children: [ "listbox", "wallpaper-panel" ]; }
+            wallpaper-panel { width: 300; flex-grow: 1; max-width: 400; }
+            listbox { min-width: 250; margin: 0 10; }
+        "#,
+        )
+        .unwrap();
+        let layout = ThemeLayout::from_theme(&theme);
+        let children =
+            layout.calculate_mainbox_children_bounds(0.0, 0.0, 800.0, 400.0, &scaled(1.0));
+        assert_eq!(children[0].name, "listbox");
+        assert_eq!(children[0].bounds, Rect::new(10.0, 0.0, 380.0, 400.0));
+        // The panel grows from its width up to its max width
+        assert!(children[1].expand);
+        assert_eq!(children[1].fixed_width, Some(300.0));
+        assert_eq!(children[1].bounds, Rect::new(400.0, 0.0, 400.0, 400.0));
+    }
+
+    #[test]
+    fn test_relative_sizes() {
+        let theme = ThemeTree::parse(
+            r#"
+            * { font-size: 12; }
+            window { width: 50%; height: calc(20em + 8px); }
+            wallpaper-panel { width: 25vw; }
+        "#,
+        )
+        .unwrap();
+        let ctx = LayoutContext {
+            base_font_size: base_font(&theme).1,
+            viewport_width: 1600.0,
+            viewport_height: 900.0,
+            ..scaled(1.0)
+        };
+        let monitor = LayoutContext {
+            parent_size: ctx.viewport_width,
+            ..ctx.clone()
+        };
+        assert_eq!(window_size(&theme, &monitor, (0.0, 0.0)), (800.0, 248.0));
+
+        // At twice the scale, the panel is still a quarter of the viewport
+        let layout = ThemeLayout::from_theme(&theme);
+        let children = layout.calculate_mainbox_children_bounds(
+            0.0,
+            0.0,
+            1000.0,
+            400.0,
+            &LayoutContext {
+                scale_factor: 2.0,
+                ..ctx
+            },
+        );
+        assert_eq!(children[0].bounds.width, 400.0);
+    }
+
+    #[test]
+    fn test_restyle_launcher_keeps_state() {
+        use crate::render::{DisplayList, DrawCommand};
+        use crate::widget::{ElementData, ElementStyle, GridItem, GridViewStyle, ListViewStyle};
+
+        let theme = |selected: &str| {
+            ThemeTree::parse(&format!(
+                r#"
+                textbox {{ font-size: {size}; }}
+                listview {{ lines: 3; }}
+                element {{ transition: background-color 60s linear; }}
+                element selected.normal {{ background-color: {selected}; }}
+                gridview {{ transition: border-width 60s linear; }}
+            "#,
+                size = if selected == "#ff0000" { 14 } else { 20 },
+            ))
+            .unwrap()
+        };
+        let old = theme("#ff0000");
+        let mut textbox = Textbox::new();
+        let mut listview = ListView::new()
+            .with_style(ListViewStyle::from_theme(&old, None))
+            .with_element_style(ElementStyle::from_theme(&old, None));
+        let mut gridview = GridView::new().with_style(GridViewStyle::from_theme(&old, None));
+        restyle_launcher(&old, &mut textbox, &mut listview, &mut gridview);
+
+        textbox.set_text("fire");
+        listview.set_items(
+            (0..10)
+                .map(|i| ElementData::new(format!("App {}", i), "app.exe"))
+                .collect(),
+        );
+        listview.select(7);
+        gridview.set_items(vec![GridItem::new("A", "a"), GridItem::new("B", "b")]);
+        gridview.select(1);
+
+        restyle_launcher(
+            &theme("#00ff00"),
+            &mut textbox,
+            &mut listview,
+            &mut gridview,
+        );
+
+        // The new look applies...
+        assert_eq!(textbox.style().font_size, 20.0);
+        // ...without losing the query, the selections or the scroll position
+        assert_eq!(textbox.text(), "fire");
+        assert_eq!(listview.selected_index(), Some(7));
+        assert_eq!(listview.scroll_offset(), 5);
+        assert_eq!(gridview.selected_index(), Some(1));
+
+        // The selected row is still fading in: its transition was turned
+        // towards the new color rather than ended or left on the old one
+        assert!(listview.tick());
+        assert!(gridview.tick());
+        let mut list = DisplayList::new();
+        listview.render(
+            &mut list,
+            Rect::new(0.0, 0.0, 400.0, 300.0),
+            &LayoutContext::default(),
+        );
+        let fill = list.commands().iter().find_map(|command| match command {
+            DrawCommand::Fill {
+                paint: Paint::Solid(color),
+                ..
+            } => Some(color.a),
+            _ => None,
+        });
+        assert!(
+            fill.is_some_and(|alpha| alpha < 0.1),
+            "{:?}",
+            list.commands()
+        );
+    }
+}
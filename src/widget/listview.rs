@@ -1,9 +1,7 @@
 //! ListView widget - a scrollable list of elements
 
-use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-
-use crate::platform::win32::Renderer;
 use crate::platform::Event;
+use crate::render::DisplayList;
 use crate::theme::tree::ThemeTree;
 use crate::theme::types::{Color, LayoutContext, Rect};
 
@@ -52,77 +50,70 @@ impl Default for ListViewStyle {
 impl ListViewStyle {
     /// Load style from theme
     pub fn from_theme(theme: &ThemeTree, state: Option<&str>) -> Self {
+        Self::from_theme_at(theme, "listview", state)
+    }
+
+    /// Load style from theme for the listview at `path` (see `ThemeTree::get_value`)
+    pub fn from_theme_at(theme: &ThemeTree, path: &str, state: Option<&str>) -> Self {
         let default = Self::default();
         Self {
             background_color: theme.get_color(
-                "listview",
+                path,
                 state,
                 "background-color",
                 default.background_color,
             ),
-            border_color: theme.get_color("listview", state, "border-color", default.border_color),
-            border_width: theme.get_number(
-                "listview",
-                state,
-                "border-width",
-                default.border_width as f64,
-            ) as f32,
+            border_color: theme.get_color(path, state, "border-color", default.border_color),
+            border_width: theme.get_number(path, state, "border-width", default.border_width as f64)
+                as f32,
             border_radius: theme.get_number(
-                "listview",
+                path,
                 state,
                 "border-radius",
                 default.border_radius as f64,
             ) as f32,
-            padding_top: theme.get_number(
-                "listview",
-                state,
-                "padding-top",
-                default.padding_top as f64,
-            ) as f32,
+            padding_top: theme.get_number(path, state, "padding-top", default.padding_top as f64)
+                as f32,
             padding_right: theme.get_number(
-                "listview",
+                path,
                 state,
                 "padding-right",
                 default.padding_right as f64,
             ) as f32,
             padding_bottom: theme.get_number(
-                "listview",
+                path,
                 state,
                 "padding-bottom",
                 default.padding_bottom as f64,
             ) as f32,
-            padding_left: theme.get_number(
-                "listview",
-                state,
-                "padding-left",
-                default.padding_left as f64,
-            ) as f32,
+            padding_left: theme.get_number(path, state, "padding-left", default.padding_left as f64)
+                as f32,
             element_spacing: theme.get_number(
-                "listview",
+                path,
                 state,
                 "spacing",
                 default.element_spacing as f64,
             ) as f32,
             max_visible_items: theme.get_number(
-                "listview",
+                path,
                 state,
                 "lines",
                 default.max_visible_items as f64,
             ) as usize,
             scrollbar_width: theme.get_number(
-                "listview",
+                path,
                 state,
                 "scrollbar-width",
                 default.scrollbar_width as f64,
             ) as f32,
             scrollbar_color: theme.get_color(
-                "listview",
+                path,
                 state,
                 "scrollbar-color",
                 default.scrollbar_color,
             ),
             scrollbar_track_color: theme.get_color(
-                "listview",
+                path,
                 state,
                 "scrollbar-track-color",
                 default.scrollbar_track_color,
@@ -137,6 +128,10 @@ pub struct ListView {
     elements: Vec<Element>,
     /// Currently selected index
     selected_index: Option<usize>,
+    /// Element under the mouse
+    hovered_index: Option<usize>,
+    /// Element the mouse button went down on, until it's released
+    pressed_index: Option<usize>,
     /// Scroll offset (in items, not pixels)
     scroll_offset: usize,
     /// Layout properties
@@ -159,9 +154,11 @@ impl ListView {
         Self {
             elements: Vec::new(),
             selected_index: None,
+            hovered_index: None,
+            pressed_index: None,
             scroll_offset: 0,
             layout: LayoutProps::default(),
-            state: WidgetState::Normal,
+            state: WidgetState::NORMAL,
             style: ListViewStyle::default(),
             element_style: ElementStyle::default(),
             bounds: None,
@@ -199,8 +196,15 @@ impl ListView {
     pub fn set_items(&mut self, items: Vec<ElementData>) {
         self.elements = items
             .into_iter()
-            .map(|data| Element::new(data).with_style(self.element_style.clone()))
+            .enumerate()
+            .map(|(i, data)| {
+                let mut elem = Element::new(data).with_style(self.element_style.clone());
+                elem.set_alternate(i % 2 == 1);
+                elem
+            })
             .collect();
+        self.hovered_index = None;
+        self.pressed_index = None;
 
         // Reset selection if out of bounds
         if let Some(idx) = self.selected_index {
@@ -217,6 +221,19 @@ impl ListView {
 
         self.update_selection_state();
         self.ensure_selected_visible();
+        // New rows appear in their state at once
+        for elem in &mut self.elements {
+            elem.skip_transitions();
+        }
+    }
+
+    /// Advance the rows' state transitions; returns true while any are running
+    pub fn tick(&mut self) -> bool {
+        let mut running = false;
+        for elem in &mut self.elements {
+            running |= elem.tick();
+        }
+        running
     }
 
     /// Get the number of elements
@@ -241,6 +258,11 @@ impl ListView {
             .map(|e| e.data())
     }
 
+    /// Index of the first row shown
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll_offset
+    }
+
     /// Select an item by index
     pub fn select(&mut self, index: usize) {
         if index < self.elements.len() {
@@ -347,6 +369,51 @@ impl ListView {
         }
     }
 
+    /// Set the element under the mouse; returns whether it changed
+    ///
+    /// Moving off the pressed element releases it without a click.
+    pub fn set_hovered(&mut self, index: Option<usize>) -> bool {
+        if self.hovered_index == index {
+            return false;
+        }
+        self.hovered_index = index;
+        for (i, elem) in self.elements.iter_mut().enumerate() {
+            elem.set_hovered(index == Some(i));
+        }
+        if self.pressed_index.is_some() && self.pressed_index != index {
+            self.set_pressed(None);
+        }
+        true
+    }
+
+    /// Press an element with the mouse, selecting it
+    pub fn press(&mut self, index: usize) {
+        if index < self.elements.len() {
+            self.select(index);
+            self.set_pressed(Some(index));
+        }
+    }
+
+    /// Release the mouse button over `index`; returns true if that's the
+    /// element it was pressed on, which makes it a click
+    pub fn release(&mut self, index: Option<usize>) -> bool {
+        let clicked = self.pressed_index.is_some() && self.pressed_index == index;
+        self.set_pressed(None);
+        clicked
+    }
+
+    /// Get the element the mouse button is held down on
+    pub fn pressed_index(&self) -> Option<usize> {
+        self.pressed_index
+    }
+
+    fn set_pressed(&mut self, index: Option<usize>) {
+        self.pressed_index = index;
+        for (i, elem) in self.elements.iter_mut().enumerate() {
+            elem.set_pressed(index == Some(i));
+        }
+    }
+
     /// Update selected state on all elements
     fn update_selection_state(&mut self) {
         for (i, elem) in self.elements.iter_mut().enumerate() {
@@ -391,13 +458,6 @@ impl ListView {
         self.element_style.height + self.style.element_spacing
     }
 
-    /// Calculate the total content height
-    fn content_height(&self) -> f32 {
-        let element_height = self.element_total_height();
-        let visible = self.visible_count();
-        (visible as f32 * element_height - self.style.element_spacing).max(0.0)
-    }
-
     /// Hit test to find which element is at the given point
     /// Returns the absolute index (not relative to scroll)
     pub fn hit_test(&self, x: f32, y: f32) -> Option<usize> {
@@ -471,7 +531,7 @@ impl Default for ListView {
 
 impl Widget for ListView {
     fn handle_event(&mut self, event: &Event, _ctx: &LayoutContext) -> EventResult {
-        use crate::platform::win32::event::KeyCode;
+        use crate::platform::KeyCode;
 
         crate::log!("ListView::handle_event called with {:?}", event);
 
@@ -536,11 +596,27 @@ impl Widget for ListView {
                 }
                 EventResult::repaint()
             }
-            // Mouse click to select
+            // Hover highlight
+            Event::MouseMove { x, y } => {
+                if self.set_hovered(self.hit_test(*x as f32, *y as f32)) {
+                    EventResult::repaint()
+                } else {
+                    EventResult::none()
+                }
+            }
+            // Mouse down presses and selects a row...
             Event::MouseDown { x, y, .. } => {
                 if let Some(idx) = self.hit_test(*x as f32, *y as f32) {
-                    self.selected_index = Some(idx);
-                    self.update_selection_state();
+                    self.press(idx);
+                    EventResult::repaint()
+                } else {
+                    EventResult::none()
+                }
+            }
+            // ...and releasing it over the same row is a click
+            Event::MouseUp { x, y, .. } => {
+                let was_pressed = self.pressed_index.is_some();
+                if self.release(self.hit_test(*x as f32, *y as f32)) {
                     // Return submit to trigger action on click
                     EventResult {
                         needs_repaint: true,
@@ -549,6 +625,8 @@ impl Widget for ListView {
                         submit: true,
                         cancel: false,
                     }
+                } else if was_pressed {
+                    EventResult::repaint()
                 } else {
                     EventResult::none()
                 }
@@ -557,21 +635,9 @@ impl Widget for ListView {
         }
     }
 
-    fn render(
-        &self,
-        renderer: &mut Renderer,
-        rect: Rect,
-        ctx: &LayoutContext,
-    ) -> Result<(), windows::core::Error> {
+    fn render(&self, list: &mut DisplayList, rect: Rect, ctx: &LayoutContext) {
         let scale = ctx.scale_factor;
 
-        let bounds = D2D_RECT_F {
-            left: rect.x,
-            top: rect.y,
-            right: rect.x + rect.width,
-            bottom: rect.y + rect.height,
-        };
-
         // Scale dimensions for DPI
         let scaled_border_radius = self.style.border_radius * scale;
         let scaled_border_width = self.style.border_width * scale;
@@ -583,33 +649,15 @@ impl Widget for ListView {
         let scaled_scrollbar_width = self.style.scrollbar_width * scale;
 
         // Draw background
-        if self.style.background_color.a > 0.0 {
-            if scaled_border_radius > 0.0 {
-                renderer.fill_rounded_rect(
-                    bounds,
-                    scaled_border_radius,
-                    scaled_border_radius,
-                    self.style.background_color,
-                )?;
-            } else {
-                renderer.fill_rect(bounds, self.style.background_color)?;
-            }
-        }
+        list.fill_rounded_rect(rect, scaled_border_radius, self.style.background_color);
 
         // Draw border
-        if scaled_border_width > 0.0 && self.style.border_color.a > 0.0 {
-            if scaled_border_radius > 0.0 {
-                renderer.draw_rounded_rect(
-                    bounds,
-                    scaled_border_radius,
-                    scaled_border_radius,
-                    self.style.border_color,
-                    scaled_border_width,
-                )?;
-            } else {
-                renderer.draw_rect(bounds, self.style.border_color, scaled_border_width)?;
-            }
-        }
+        list.stroke_rounded_rect(
+            rect,
+            scaled_border_radius,
+            self.style.border_color,
+            scaled_border_width,
+        );
 
         // Calculate content area with scaled padding
         let content_x = rect.x + scaled_padding_left;
@@ -649,31 +697,30 @@ impl Widget for ListView {
                 elem_rect.width,
                 elem_rect.height
             );
-            elem.render(renderer, elem_rect, ctx)?;
+            elem.render(list, elem_rect, ctx);
         }
 
         // Draw scrollbar if needed
         if self.needs_scrollbar() {
-            let scrollbar_x =
-                rect.x + rect.width - scaled_padding_right - scaled_scrollbar_width;
+            let scrollbar_x = rect.x + rect.width - scaled_padding_right - scaled_scrollbar_width;
             let scrollbar_y = rect.y + scaled_padding_top;
             let visible = self.visible_count();
-            let scrollbar_height = (visible as f32 * element_total_height - scaled_element_spacing).max(0.0);
+            let scrollbar_height =
+                (visible as f32 * element_total_height - scaled_element_spacing).max(0.0);
 
             // Track
-            let track_rect = D2D_RECT_F {
-                left: scrollbar_x,
-                top: scrollbar_y,
-                right: scrollbar_x + scaled_scrollbar_width,
-                bottom: scrollbar_y + scrollbar_height,
-            };
+            let track_rect = Rect::new(
+                scrollbar_x,
+                scrollbar_y,
+                scaled_scrollbar_width,
+                scrollbar_height,
+            );
             let scrollbar_radius = scaled_scrollbar_width / 2.0;
-            renderer.fill_rounded_rect(
+            list.fill_rounded_rect(
                 track_rect,
                 scrollbar_radius,
-                scrollbar_radius,
                 self.style.scrollbar_track_color,
-            )?;
+            );
 
             // Thumb
             let total_items = self.elements.len() as f32;
@@ -683,21 +730,14 @@ impl Widget for ListView {
             let scroll_ratio = self.scroll_offset as f32 / (total_items - visible_items).max(1.0);
             let thumb_y = scrollbar_y + scroll_ratio * (scrollbar_height - thumb_height);
 
-            let thumb_rect = D2D_RECT_F {
-                left: scrollbar_x,
-                top: thumb_y,
-                right: scrollbar_x + self.style.scrollbar_width,
-                bottom: thumb_y + thumb_height,
-            };
-            renderer.fill_rounded_rect(
-                thumb_rect,
-                scrollbar_radius,
-                scrollbar_radius,
-                self.style.scrollbar_color,
-            )?;
+            let thumb_rect = Rect::new(
+                scrollbar_x,
+                thumb_y,
+                self.style.scrollbar_width,
+                thumb_height,
+            );
+            list.fill_rounded_rect(thumb_rect, scrollbar_radius, self.style.scrollbar_color);
         }
-
-        Ok(())
     }
 
     fn state(&self) -> WidgetState {
@@ -735,6 +775,12 @@ impl Widget for ListView {
         self.cached_scale = ctx.scale_factor;
     }
 
+    fn restyle(&mut self, theme: &ThemeTree, path: &str) {
+        self.set_style(ListViewStyle::from_theme_at(theme, path, None));
+        let element_path = format!("{} element", path);
+        self.set_element_style(ElementStyle::from_theme_at(theme, &element_path, None));
+    }
+
     fn layout_props(&self) -> &LayoutProps {
         &self.layout
     }
@@ -843,4 +889,85 @@ mod tests {
         lv.page_up();
         assert_eq!(lv.selected_index(), Some(0));
     }
+
+    #[test]
+    fn test_listview_render_visible_rows() {
+        let mut lv = ListView::new();
+        lv.style.max_visible_items = 3;
+        lv.set_items(
+            (0..10)
+                .map(|i| ElementData::new(format!("App {}", i), format!("app{}.exe", i)))
+                .collect(),
+        );
+        lv.select(4);
+
+        let mut list = DisplayList::new();
+        lv.render(
+            &mut list,
+            Rect::new(0.0, 0.0, 400.0, 300.0),
+            &LayoutContext::default(),
+        );
+        let titles: Vec<_> = list.texts().map(|run| run.text.as_str()).collect();
+        assert_eq!(titles, ["App 2", "App 3", "App 4"]);
+    }
+
+    #[test]
+    fn test_listview_pressed_row() {
+        use crate::platform::MouseButton;
+        use crate::render::DrawCommand;
+        use crate::theme::types::Paint;
+
+        let theme = ThemeTree::parse("element.pressed { background-color: #ff0000; }").unwrap();
+        let mut lv = ListView::new().with_element_style(ElementStyle::from_theme(&theme, None));
+        lv.set_items(vec![
+            ElementData::new("App 1", "app1.exe"),
+            ElementData::new("App 2", "app2.exe"),
+        ]);
+        lv.arrange(Rect::new(0.0, 0.0, 400.0, 300.0), &LayoutContext::default());
+        let pressed_fills = |lv: &ListView| {
+            let mut list = DisplayList::new();
+            lv.render(
+                &mut list,
+                Rect::new(0.0, 0.0, 400.0, 300.0),
+                &LayoutContext::default(),
+            );
+            list.commands()
+                .iter()
+                .filter(|command| {
+                    matches!(command, DrawCommand::Fill { paint: Paint::Solid(color), .. }
+                        if *color == Color::RED)
+                })
+                .count()
+        };
+
+        // The row's pressed color shows while the button is held down on it
+        let (x, y) = (200, 30);
+        let row = lv.hit_test(x as f32, y as f32).unwrap();
+        let down = Event::MouseDown {
+            x,
+            y,
+            button: MouseButton::Left,
+        };
+        assert!(!lv.handle_event(&down, &LayoutContext::default()).submit);
+        assert_eq!(lv.pressed_index(), Some(row));
+        assert_eq!(lv.selected_index(), Some(row));
+        assert_eq!(pressed_fills(&lv), 1);
+
+        // Releasing it over the same row is a click
+        let up = Event::MouseUp {
+            x,
+            y,
+            button: MouseButton::Left,
+        };
+        assert!(lv.handle_event(&up, &LayoutContext::default()).submit);
+        assert_eq!(lv.pressed_index(), None);
+        assert_eq!(pressed_fills(&lv), 0);
+
+        // Leaving the row releases it without one
+        lv.press(row);
+        lv.set_hovered(Some(row));
+        lv.set_hovered(None);
+        assert_eq!(pressed_fills(&lv), 0);
+        assert!(!lv.release(Some(row)));
+    }
 }
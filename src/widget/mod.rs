@@ -12,56 +12,50 @@ pub mod clock;
 pub mod container;
 pub mod element;
 pub mod factory;
+pub mod flex;
 pub mod gridview;
+pub mod label;
+pub mod layout;
 pub mod listview;
+pub mod overlay;
 pub mod panel;
 pub mod tailview;
 pub mod taskpanel;
 pub mod textbox;
 
-use crate::platform::win32::Renderer;
 use crate::platform::Event;
+use crate::render::DisplayList;
 use crate::theme::tree::ThemeTree;
-use crate::theme::types::{Color, ImageSource, LayoutContext, Rect};
+use crate::theme::types::{Color, ImageSource, LayoutContext, Paint, Rect, Shadow};
 
-pub use base::{ArrangedBounds, Constraints, CornerRadii, LayoutProps, MeasuredSize, Size};
+pub use base::{
+    ArrangedBounds, Constraints, CornerRadii, LayoutProps, MeasuredSize, Size, StateColors,
+    WidgetState,
+};
 pub use clock::{ClockConfig, ClockPosition};
 pub use container::{Container, ContainerStyle};
 pub use element::{Element, ElementData, ElementStyle};
-pub use factory::{UITree, WidgetFactory, WidgetNode, WidgetType};
+pub use factory::{Hit, UITree, WidgetFactory, WidgetNode, WidgetType};
+pub use flex::{Align, FlexItem, FlexWrap, JustifyContent};
 pub use gridview::{GridItem, GridLayout, GridView, GridViewStyle, SelectionStyle};
+pub use label::{Label, LabelStyle};
+pub use layout::{
+    base_font, launcher_widget_path, restyle_launcher, window_size, ChildLayout, ThemeLayout,
+};
 pub use listview::{ListView, ListViewStyle};
+pub use overlay::{Anchor, Placement, Position};
 pub use panel::{Panel, PanelStyle};
 pub use tailview::{TailView, TailViewHit, TailViewStyle};
 pub use taskpanel::{TaskPanelState, TaskPanelStyle};
 pub use textbox::Textbox;
 
-/// Widget rendering state
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-pub enum WidgetState {
-    #[default]
-    Normal,
-    Focused,
-    Disabled,
-}
-
-impl WidgetState {
-    /// Get the CSS state suffix for theme lookups
-    pub fn as_suffix(&self) -> Option<&'static str> {
-        match self {
-            WidgetState::Normal => None,
-            WidgetState::Focused => Some("focused"),
-            WidgetState::Disabled => Some("disabled"),
-        }
-    }
-}
-
 /// Widget style properties resolved from theme
 #[derive(Clone, Debug)]
 pub struct WidgetStyle {
-    pub background_color: Color,
+    pub background_color: Paint,
     pub text_color: Color,
-    pub border_color: Color,
+    pub border_color: Paint,
+    pub box_shadow: Option<Shadow>,
     pub border_width: f32,
     pub border_radius: f32,
     pub padding_top: f32,
@@ -82,9 +76,10 @@ pub struct WidgetStyle {
 impl Default for WidgetStyle {
     fn default() -> Self {
         Self {
-            background_color: Color::from_hex("#2d2d2d").unwrap_or(Color::BLACK),
+            background_color: Color::from_hex("#2d2d2d").unwrap_or(Color::BLACK).into(),
             text_color: Color::WHITE,
-            border_color: Color::from_hex("#555555").unwrap_or(Color::WHITE),
+            border_color: Color::from_hex("#555555").unwrap_or(Color::WHITE).into(),
+            box_shadow: None,
             border_width: 1.0,
             border_radius: 4.0,
             padding_top: 8.0,
@@ -106,72 +101,65 @@ impl Default for WidgetStyle {
 impl WidgetStyle {
     /// Load textbox style from theme
     pub fn from_theme_textbox(theme: &ThemeTree, state: Option<&str>) -> Self {
+        Self::from_theme_textbox_at(theme, "textbox", state)
+    }
+
+    /// Load textbox style from theme for the textbox at `path`
+    pub fn from_theme_textbox_at(theme: &ThemeTree, path: &str, state: Option<&str>) -> Self {
         let default = Self::default();
 
         // Debug: log what we're extracting
-        crate::log!("from_theme_textbox: state={:?}", state);
-        let font_size_val = theme.get_value("textbox", state, "font-size");
+        crate::log!("from_theme_textbox: path={}, state={:?}", path, state);
+        let font_size_val = theme.get_value(path, state, "font-size");
         crate::log!("  font-size value from theme: {:?}", font_size_val);
-        let font_size =
-            theme.get_number("textbox", state, "font-size", default.font_size as f64) as f32;
+        let font_size = theme.get_number(path, state, "font-size", default.font_size as f64) as f32;
         crate::log!("  font-size after get_number: {}", font_size);
 
         Self {
-            background_color: theme.get_color(
-                "textbox",
+            background_color: theme.get_paint(
+                path,
                 state,
                 "background-color",
                 default.background_color,
             ),
-            text_color: theme.get_color("textbox", state, "text-color", default.text_color),
-            border_color: theme.get_color("textbox", state, "border-color", default.border_color),
-            border_width: theme.get_number(
-                "textbox",
-                state,
-                "border-width",
-                default.border_width as f64,
-            ) as f32,
+            text_color: theme.get_color(path, state, "text-color", default.text_color),
+            border_color: theme.get_paint(path, state, "border-color", default.border_color),
+            box_shadow: theme.get_shadow(path, state, "box-shadow"),
+            border_width: theme.get_number(path, state, "border-width", default.border_width as f64)
+                as f32,
             border_radius: theme.get_number(
-                "textbox",
+                path,
                 state,
                 "border-radius",
                 default.border_radius as f64,
             ) as f32,
-            padding_top: theme.get_number(
-                "textbox",
-                state,
-                "padding-top",
-                default.padding_top as f64,
-            ) as f32,
+            padding_top: theme.get_number(path, state, "padding-top", default.padding_top as f64)
+                as f32,
             padding_right: theme.get_number(
-                "textbox",
+                path,
                 state,
                 "padding-right",
                 default.padding_right as f64,
             ) as f32,
             padding_bottom: theme.get_number(
-                "textbox",
+                path,
                 state,
                 "padding-bottom",
                 default.padding_bottom as f64,
             ) as f32,
-            padding_left: theme.get_number(
-                "textbox",
-                state,
-                "padding-left",
-                default.padding_left as f64,
-            ) as f32,
-            font_family: theme.get_string("textbox", state, "font-family", &default.font_family),
+            padding_left: theme.get_number(path, state, "padding-left", default.padding_left as f64)
+                as f32,
+            font_family: theme.get_string(path, state, "font-family", &default.font_family),
             font_size,
             placeholder_color: theme.get_color(
-                "textbox",
+                path,
                 state,
                 "placeholder-color",
                 default.placeholder_color,
             ),
-            cursor_color: theme.get_color("textbox", state, "cursor-color", default.cursor_color),
+            cursor_color: theme.get_color(path, state, "cursor-color", default.cursor_color),
             selection_color: theme.get_color(
-                "textbox",
+                path,
                 state,
                 "selection-color",
                 default.selection_color,
@@ -249,13 +237,8 @@ pub trait Widget {
     /// Handle an event
     fn handle_event(&mut self, event: &Event, ctx: &LayoutContext) -> EventResult;
 
-    /// Render the widget
-    fn render(
-        &self,
-        renderer: &mut Renderer,
-        rect: Rect,
-        ctx: &LayoutContext,
-    ) -> Result<(), windows::core::Error>;
+    /// Paint the widget into a display list
+    fn render(&self, list: &mut DisplayList, rect: Rect, ctx: &LayoutContext);
 
     /// Get the current widget state
     fn state(&self) -> WidgetState;
@@ -269,10 +252,17 @@ pub trait Widget {
     /// Set the widget's style
     fn set_style(&mut self, style: WidgetStyle);
 
+    /// Set the text the widget shows, for widgets that show text
+    fn set_text(&mut self, _text: &str) {}
+
+    /// Reload style and layout from the theme for the widget at `path`,
+    /// keeping its state (text, selection, scroll offset)
+    fn restyle(&mut self, _theme: &ThemeTree, _path: &str) {}
+
     // --- Layout system methods ---
 
     /// Measure the widget's desired size given constraints
-    fn measure(&self, constraints: Constraints, ctx: &LayoutContext) -> MeasuredSize {
+    fn measure(&self, constraints: Constraints, _ctx: &LayoutContext) -> MeasuredSize {
         // Default: return minimum or constrained size
         MeasuredSize::new(constraints.min.width, constraints.min.height)
     }
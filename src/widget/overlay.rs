@@ -0,0 +1,300 @@
+//! Absolute positioning for overlays: tooltips, badges, the watermark and
+//! the grid's filter query
+//!
+//! A widget with `position: absolute` is taken out of its parent's flow and
+//! placed against an anchor target instead, rofi-style:
+//!
+//! - `anchor-to`: the widget (or app-registered anchor, see
+//!   `UITree::set_anchor`) to place against; the parent if unset
+//! - `location`: the point of the target, e.g. `north-east`
+//! - `anchor`: the point of the widget put at that location, `location` if
+//!   unset
+//! - `offset`: `x y` moved from there, in pixels
+//! - `z-index`: stacking order among positioned widgets, higher on top
+//!
+//! Children of the `overlay` layer, which covers the whole window above the
+//! `window` tree, are always positioned this way.
+
+use crate::theme::tree::ThemeTree;
+use crate::theme::types::{Distance, Rect};
+
+use super::base::{LayoutProps, Size};
+
+/// Children of the overlay layer when the theme doesn't list any
+pub const DEFAULT_OVERLAYS: &[&str] = &["tooltip", "version", "filter"];
+
+/// App-registered anchor for the item under the mouse, which tooltips use
+pub const HOVERED_ANCHOR: &str = "hovered";
+
+/// Whether a widget flows in its parent or is placed by its anchor
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Position {
+    #[default]
+    Static,
+    Absolute,
+}
+
+impl Position {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "static" | "relative" => Some(Self::Static),
+            "absolute" => Some(Self::Absolute),
+            _ => None,
+        }
+    }
+}
+
+/// A point on a rectangle, named like rofi's `location` and `anchor`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Anchor {
+    #[default]
+    NorthWest,
+    North,
+    NorthEast,
+    West,
+    Center,
+    East,
+    SouthWest,
+    South,
+    SouthEast,
+}
+
+impl Anchor {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "north-west" | "northwest" | "top-left" => Some(Self::NorthWest),
+            "north" | "top" => Some(Self::North),
+            "north-east" | "northeast" | "top-right" => Some(Self::NorthEast),
+            "west" | "left" => Some(Self::West),
+            "center" => Some(Self::Center),
+            "east" | "right" => Some(Self::East),
+            "south-west" | "southwest" | "bottom-left" => Some(Self::SouthWest),
+            "south" | "bottom" => Some(Self::South),
+            "south-east" | "southeast" | "bottom-right" => Some(Self::SouthEast),
+            _ => None,
+        }
+    }
+
+    /// Position of the point across and down a rectangle, from 0 to 1
+    pub fn fractions(self) -> (f32, f32) {
+        match self {
+            Self::NorthWest => (0.0, 0.0),
+            Self::North => (0.5, 0.0),
+            Self::NorthEast => (1.0, 0.0),
+            Self::West => (0.0, 0.5),
+            Self::Center => (0.5, 0.5),
+            Self::East => (1.0, 0.5),
+            Self::SouthWest => (0.0, 1.0),
+            Self::South => (0.5, 1.0),
+            Self::SouthEast => (1.0, 1.0),
+        }
+    }
+}
+
+/// Absolute positioning properties of a widget
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Placement {
+    pub position: Position,
+    /// Target to place against (None = the parent)
+    pub anchor_to: Option<String>,
+    /// Point of the target
+    pub location: Anchor,
+    /// Point of the widget put at `location` (None = `location`)
+    pub anchor: Option<Anchor>,
+    /// Moved from the anchored position, in pixels
+    pub offset: (f32, f32),
+    pub z_index: i32,
+}
+
+impl Placement {
+    /// Load the positioning properties of the widget at `path`, keeping the
+    /// others
+    pub fn load(&mut self, theme: &ThemeTree, path: &str) {
+        let keyword = |property: &str| {
+            theme
+                .get_resolved(path, None, property)
+                .and_then(|v| v.as_string())
+        };
+
+        if let Some(position) = keyword("position").and_then(|s| Position::from_name(&s)) {
+            self.position = position;
+        }
+        if let Some(target) = keyword("anchor-to") {
+            self.anchor_to = Some(target).filter(|t| !t.is_empty() && t != "parent");
+        }
+        if let Some(location) = keyword("location").and_then(|s| Anchor::from_name(&s)) {
+            self.location = location;
+        }
+        if let Some(anchor) = keyword("anchor").and_then(|s| Anchor::from_name(&s)) {
+            self.anchor = Some(anchor);
+        }
+
+        // One distance moves both ways, two are `x y`
+        if let Some(offset) = theme
+            .get_resolved(path, None, "offset")
+            .and_then(|v| v.as_padding())
+        {
+            let px = |d: &Distance| d.as_number().unwrap_or(0.0) as f32;
+            self.offset = (px(&offset.top), px(&offset.right));
+        }
+        let number = |property: &str| {
+            theme
+                .get_resolved(path, None, property)
+                .and_then(|v| v.as_number())
+                .map(|n| n as f32)
+        };
+        self.offset = (
+            number("offset-x").unwrap_or(self.offset.0),
+            number("offset-y").unwrap_or(self.offset.1),
+        );
+        if let Some(z) = number("z-index") {
+            self.z_index = z as i32;
+        }
+    }
+
+    pub fn is_absolute(&self) -> bool {
+        self.position == Position::Absolute
+    }
+
+    /// The placement with its offset multiplied by `factor`
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            offset: (self.offset.0 * factor, self.offset.1 * factor),
+            ..self.clone()
+        }
+    }
+
+    /// The rect of a widget of `size` placed against `target`
+    pub fn place(&self, size: Size, target: Rect) -> Rect {
+        let (lx, ly) = self.location.fractions();
+        let (ax, ay) = self.anchor.unwrap_or(self.location).fractions();
+        Rect::new(
+            target.x + target.width * lx - size.width * ax + self.offset.0,
+            target.y + target.height * ly - size.height * ay + self.offset.1,
+            size.width,
+            size.height,
+        )
+    }
+}
+
+/// Layout of a built-in overlay before the theme's properties
+///
+/// The tooltip sits to the right of the hovered item, top-aligned; the
+/// version watermark in the window's bottom right corner and the filter
+/// query in the bottom left one.
+pub fn default_layout(name: &str) -> LayoutProps {
+    let padding = match name {
+        "tooltip" => (8.0, 8.0, 8.0, 8.0),
+        _ => (0.0, 0.0, 0.0, 0.0),
+    };
+    let placement = match name {
+        "tooltip" => Placement {
+            position: Position::Absolute,
+            anchor_to: Some(HOVERED_ANCHOR.to_string()),
+            location: Anchor::NorthEast,
+            anchor: Some(Anchor::NorthWest),
+            offset: (6.0, 0.0),
+            z_index: 100,
+        },
+        "version" => Placement {
+            position: Position::Absolute,
+            location: Anchor::SouthEast,
+            offset: (-8.0, -8.0),
+            ..Default::default()
+        },
+        "filter" => Placement {
+            position: Position::Absolute,
+            location: Anchor::SouthWest,
+            offset: (8.0, -8.0),
+            ..Default::default()
+        },
+        _ => Placement::default(),
+    };
+    LayoutProps {
+        padding,
+        placement,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place() {
+        let target = Rect::new(100.0, 100.0, 200.0, 50.0);
+        let size = Size::new(40.0, 20.0);
+
+        // Same point on both by default: inside the target's corner
+        let placement = Placement {
+            location: Anchor::SouthEast,
+            offset: (-4.0, -4.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            placement.scaled(2.0).place(size, target),
+            Rect::new(252.0, 122.0, 40.0, 20.0)
+        );
+
+        // The tooltip's top left at the target's top right
+        let tooltip = default_layout("tooltip").placement;
+        assert_eq!(
+            tooltip.place(size, target),
+            Rect::new(306.0, 100.0, 40.0, 20.0)
+        );
+
+        // The filter query inside the bottom left corner
+        let filter = default_layout("filter").placement;
+        assert_eq!(
+            filter.place(size, target),
+            Rect::new(108.0, 122.0, 40.0, 20.0)
+        );
+
+        let centered = Placement {
+            location: Anchor::Center,
+            ..Default::default()
+        };
+        assert_eq!(
+            centered.place(size, target),
+            Rect::new(180.0, 115.0, 40.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn test_load() {
+        let theme = ThemeTree::parse(
+            r#"
+            badge {
+                position: absolute;
+                anchor-to: "listbox";
+                location: north-east;
+                anchor: center;
+                offset: -4 8;
+                z-index: 3;
+            }
+            tooltip { offset-y: 2; anchor-to: parent; }
+        "#,
+        )
+        .unwrap();
+        let mut placement = Placement::default();
+        placement.load(&theme, "badge");
+        assert_eq!(
+            placement,
+            Placement {
+                position: Position::Absolute,
+                anchor_to: Some("listbox".to_string()),
+                location: Anchor::NorthEast,
+                anchor: Some(Anchor::Center),
+                offset: (-4.0, 8.0),
+                z_index: 3,
+            }
+        );
+
+        let mut tooltip = default_layout("tooltip").placement;
+        tooltip.load(&theme, "tooltip");
+        assert_eq!(tooltip.anchor_to, None);
+        assert_eq!(tooltip.offset, (6.0, 2.0));
+        assert!(tooltip.is_absolute());
+    }
+}
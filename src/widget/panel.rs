@@ -1,14 +1,11 @@
 //! Panel widget - background container with color or image
 
-use std::cell::RefCell;
-use std::path::Path;
-
-use windows::Win32::Graphics::Direct2D::ID2D1Bitmap;
-
-use crate::platform::win32::{get_wallpaper_path, ImageLoader, Renderer};
+#[cfg(windows)]
+use crate::platform::win32::get_wallpaper_path;
 use crate::platform::Event;
+use crate::render::DisplayList;
 use crate::theme::tree::ThemeTree;
-use crate::theme::types::{Color, ImageScale, ImageSource, LayoutContext, Rect};
+use crate::theme::types::{Color, ImageSource, LayoutContext, Paint, Rect, Shadow};
 
 use super::base::{Constraints, CornerRadii, LayoutProps, MeasuredSize};
 use super::{EventResult, Widget, WidgetState, WidgetStyle};
@@ -23,37 +20,34 @@ pub struct Panel {
     style: PanelStyle,
     /// Widget state
     state: WidgetState,
-    /// Cached bitmap for background image (RefCell for interior mutability in render)
-    cached_bitmap: RefCell<Option<CachedBitmap>>,
-}
-
-/// Cached bitmap with its source info for invalidation
-struct CachedBitmap {
-    bitmap: ID2D1Bitmap,
-    source_path: String,
-    width: u32,
-    height: u32,
+    /// Background image path with "auto" resolved to the wallpaper
+    image_path: Option<String>,
 }
 
 /// Style for panel widget
 #[derive(Clone, Debug)]
 pub struct PanelStyle {
-    pub background_color: Color,
+    pub background_color: Paint,
     pub background_image: Option<ImageSource>,
-    pub border_color: Color,
+    /// Blur radius in pixels for the background image (`backdrop-blur`)
+    pub backdrop_blur: f32,
+    pub border_color: Paint,
     pub border_width: f32,
     /// Per-corner border radii
     pub border_radii: CornerRadii,
+    pub box_shadow: Option<Shadow>,
 }
 
 impl Default for PanelStyle {
     fn default() -> Self {
         Self {
-            background_color: Color::TRANSPARENT,
+            background_color: Paint::default(),
             background_image: None,
-            border_color: Color::TRANSPARENT,
+            backdrop_blur: 0.0,
+            border_color: Paint::default(),
             border_width: 0.0,
             border_radii: CornerRadii::zero(),
+            box_shadow: None,
         }
     }
 }
@@ -85,17 +79,21 @@ impl PanelStyle {
         };
 
         Self {
-            background_color: theme.get_color(
+            background_color: theme.get_paint(
                 name,
                 state,
                 "background-color",
                 default.background_color,
             ),
             background_image: theme.get_image(name, state, "background-image"),
-            border_color: theme.get_color(name, state, "border-color", default.border_color),
+            backdrop_blur: theme
+                .get_number(name, state, "backdrop-blur", default.backdrop_blur as f64)
+                .max(0.0) as f32,
+            border_color: theme.get_paint(name, state, "border-color", default.border_color),
             border_width: theme.get_number(name, state, "border-width", default.border_width as f64)
                 as f32,
             border_radii,
+            box_shadow: theme.get_shadow(name, state, "box-shadow"),
         }
     }
 }
@@ -107,22 +105,21 @@ impl Panel {
             name: name.into(),
             layout: LayoutProps::default(),
             style: PanelStyle::default(),
-            state: WidgetState::Normal,
-            cached_bitmap: RefCell::new(None),
+            state: WidgetState::NORMAL,
+            image_path: None,
         }
     }
 
     /// Set background color
     pub fn with_background_color(mut self, color: Color) -> Self {
-        self.style.background_color = color;
+        self.style.background_color = color.into();
         self
     }
 
     /// Set background image
     pub fn with_background_image(mut self, image: ImageSource) -> Self {
         self.style.background_image = Some(image);
-        // Clear cached bitmap when image changes
-        *self.cached_bitmap.borrow_mut() = None;
+        self.resolve_background_image();
         self
     }
 
@@ -147,8 +144,7 @@ impl Panel {
     /// Set style
     pub fn with_style(mut self, style: PanelStyle) -> Self {
         self.style = style;
-        // Clear cached bitmap when style changes
-        *self.cached_bitmap.borrow_mut() = None;
+        self.resolve_background_image();
         self
     }
 
@@ -159,24 +155,19 @@ impl Panel {
 
     /// Load layout properties from theme
     pub fn load_from_theme(&mut self, theme: &ThemeTree) {
-        self.layout.expand = theme.get_expand(&self.name, false);
+        let name = self.name.clone();
+        self.load_from_theme_at(theme, &name);
+    }
 
-        // Load fixed dimensions if specified
-        if let Some(val) = theme.get_value(&self.name, None, "width") {
-            if let Some(w) = val.as_number() {
-                self.layout.fixed_width = Some(w as f32);
-            }
-        }
-        if let Some(val) = theme.get_value(&self.name, None, "height") {
-            if let Some(h) = val.as_number() {
-                self.layout.fixed_height = Some(h as f32);
-            }
-        }
+    /// Load layout properties from theme for the panel at `path`, its name
+    /// preceded by its ancestors (see `ThemeTree::get_value`)
+    pub fn load_from_theme_at(&mut self, theme: &ThemeTree, path: &str) {
+        // Expand, fixed dimensions and flex properties
+        self.layout = LayoutProps::from_theme(theme, path, &LayoutProps::default());
 
         // Load style
-        self.style = PanelStyle::from_theme(theme, &self.name, None);
-        // Clear cached bitmap when loading from theme
-        *self.cached_bitmap.borrow_mut() = None;
+        self.style = PanelStyle::from_theme(theme, path, None);
+        self.resolve_background_image();
     }
 
     /// Get the background image source (for loading)
@@ -184,106 +175,35 @@ impl Panel {
         self.style.background_image.as_ref()
     }
 
-    /// Invalidate the cached bitmap (call when window resizes)
-    pub fn invalidate_bitmap(&self) {
-        *self.cached_bitmap.borrow_mut() = None;
+    /// Resolve the background image's path once, rather than every frame
+    fn resolve_background_image(&mut self) {
+        self.image_path = self
+            .style
+            .background_image
+            .as_ref()
+            .and_then(|image| Self::resolve_image_path(&image.path));
     }
 
     /// Resolve the image path, handling "auto" for wallpaper
     fn resolve_image_path(path: &str) -> Option<String> {
         if path.eq_ignore_ascii_case("auto") {
             // Get system wallpaper
-            get_wallpaper_path().map(|p| p.to_string_lossy().into_owned())
+            system_wallpaper()
         } else {
             Some(path.to_string())
         }
     }
+}
 
-    /// Load and cache the background bitmap
-    fn ensure_bitmap(
-        &self,
-        renderer: &Renderer,
-        rect_width: u32,
-        rect_height: u32,
-    ) -> Option<ID2D1Bitmap> {
-        let image_source = self.style.background_image.as_ref()?;
-
-        // Check if we have a valid cached bitmap
-        {
-            let cache = self.cached_bitmap.borrow();
-            if let Some(ref cached) = *cache {
-                // Check if cache is still valid (same path and reasonable size)
-                if cached.source_path == image_source.path {
-                    return Some(cached.bitmap.clone());
-                }
-            }
-        }
-
-        // Need to load the image
-        let resolved_path = Self::resolve_image_path(&image_source.path)?;
-        crate::log!(
-            "Panel '{}' loading image: {} (resolved from {})",
-            self.name,
-            resolved_path,
-            image_source.path
-        );
-
-        // Create image loader
-        let loader = match ImageLoader::new() {
-            Ok(l) => l,
-            Err(e) => {
-                crate::log!("Failed to create ImageLoader: {:?}", e);
-                return None;
-            }
-        };
-
-        // Load with appropriate scaling
-        let loaded = match image_source.scale {
-            ImageScale::None => loader.load_from_file(Path::new(&resolved_path)),
-            ImageScale::Width => {
-                loader.load_scaled(Path::new(&resolved_path), rect_width, 0, ImageScale::Width)
-            }
-            ImageScale::Height => loader.load_scaled(
-                Path::new(&resolved_path),
-                0,
-                rect_height,
-                ImageScale::Height,
-            ),
-            ImageScale::Both => {
-                loader.load_cover(Path::new(&resolved_path), rect_width, rect_height)
-            }
-        };
-
-        let loaded = match loaded {
-            Ok(l) => l,
-            Err(e) => {
-                crate::log!("Failed to load image '{}': {:?}", resolved_path, e);
-                return None;
-            }
-        };
-
-        crate::log!("  Loaded image: {}x{}", loaded.width(), loaded.height());
-
-        // Create D2D bitmap
-        let bitmap = match renderer.create_bitmap(&loaded) {
-            Ok(b) => b,
-            Err(e) => {
-                crate::log!("Failed to create D2D bitmap: {:?}", e);
-                return None;
-            }
-        };
-
-        // Cache it
-        let cached = CachedBitmap {
-            bitmap: bitmap.clone(),
-            source_path: image_source.path.clone(),
-            width: loaded.width(),
-            height: loaded.height(),
-        };
-        *self.cached_bitmap.borrow_mut() = Some(cached);
+#[cfg(windows)]
+fn system_wallpaper() -> Option<String> {
+    get_wallpaper_path().map(|p| p.to_string_lossy().into_owned())
+}
 
-        Some(bitmap)
-    }
+/// There's no wallpaper to find on other platforms
+#[cfg(not(windows))]
+fn system_wallpaper() -> Option<String> {
+    None
 }
 
 impl Widget for Panel {
@@ -292,69 +212,45 @@ impl Widget for Panel {
         EventResult::none()
     }
 
-    fn render(
-        &self,
-        renderer: &mut Renderer,
-        rect: Rect,
-        _ctx: &LayoutContext,
-    ) -> Result<(), windows::core::Error> {
-        use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-
-        let bounds = D2D_RECT_F {
-            left: rect.x,
-            top: rect.y,
-            right: rect.x + rect.width,
-            bottom: rect.y + rect.height,
-        };
-
+    fn render(&self, list: &mut DisplayList, rect: Rect, _ctx: &LayoutContext) {
         let radii = self.style.border_radii;
 
+        // Draw shadow under everything else
+        if let Some(ref shadow) = self.style.box_shadow {
+            list.shadow(rect, radii, shadow);
+        }
+
         // Draw background image if present
-        if self.style.background_image.is_some() {
+        if let (Some(image), Some(path)) = (&self.style.background_image, &self.image_path) {
             // If we have rounded corners, clip the image
-            let _layer = if !radii.is_zero() {
-                renderer.push_rounded_clip_corners(bounds, radii)?
-            } else {
-                None
-            };
-
-            if let Some(bitmap) =
-                self.ensure_bitmap(renderer, rect.width as u32, rect.height as u32)
-            {
-                // Draw the bitmap covering the entire panel
-                renderer.draw_bitmap_cover(&bitmap, bounds, 1.0)?;
+            if !radii.is_zero() {
+                list.push_clip(rect, radii);
             }
 
-            // Pop the clip layer if we pushed one
-            if _layer.is_some() {
-                renderer.pop_layer();
-            }
-        }
+            // Draw the image covering the entire panel
+            list.image(
+                path.as_str(),
+                rect,
+                image.scale,
+                self.style.backdrop_blur,
+                None,
+            );
 
-        // Draw background color (can overlay on top of image for tinting)
-        if self.style.background_color.a > 0.0 {
             if !radii.is_zero() {
-                renderer.fill_rounded_rect_corners(bounds, radii, self.style.background_color)?;
-            } else {
-                renderer.fill_rect(bounds, self.style.background_color)?;
+                list.pop();
             }
         }
 
-        // Draw border if present
-        if self.style.border_width > 0.0 && self.style.border_color.a > 0.0 {
-            if !radii.is_zero() {
-                renderer.draw_rounded_rect_corners(
-                    bounds,
-                    radii,
-                    self.style.border_color,
-                    self.style.border_width,
-                )?;
-            } else {
-                renderer.draw_rect(bounds, self.style.border_color, self.style.border_width)?;
-            }
-        }
+        // Draw background color (can overlay on top of image for tinting)
+        list.fill(rect, radii, &self.style.background_color);
 
-        Ok(())
+        // Draw border if present
+        list.stroke(
+            rect,
+            radii,
+            &self.style.border_color,
+            self.style.border_width,
+        );
     }
 
     fn state(&self) -> WidgetState {
@@ -375,10 +271,11 @@ impl Widget for Panel {
         // Panels use PanelStyle instead
     }
 
-    fn measure(&self, constraints: Constraints, _ctx: &LayoutContext) -> MeasuredSize {
+    fn measure(&self, constraints: Constraints, ctx: &LayoutContext) -> MeasuredSize {
         // Use fixed dimensions if specified, otherwise use constraints
-        let width = self.layout.fixed_width.unwrap_or(constraints.max.width);
-        let height = self.layout.fixed_height.unwrap_or(constraints.max.height);
+        let layout = self.layout.resolved(ctx, constraints.max);
+        let width = layout.fixed_width.unwrap_or(constraints.max.width);
+        let height = layout.fixed_height.unwrap_or(constraints.max.height);
 
         MeasuredSize::new(
             width.min(constraints.max.width).max(constraints.min.width),
@@ -392,6 +289,10 @@ impl Widget for Panel {
         // Panels have no children to arrange
     }
 
+    fn restyle(&mut self, theme: &ThemeTree, path: &str) {
+        self.load_from_theme_at(theme, path);
+    }
+
     fn layout_props(&self) -> &LayoutProps {
         &self.layout
     }
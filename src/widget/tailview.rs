@@ -13,16 +13,12 @@ use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 use std::time::Instant;
 
-use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-
-use crate::platform::win32::event::KeyCode;
-use crate::platform::win32::Renderer;
-use crate::platform::Event;
-use crate::terminal::{Terminal, TerminalColors, TerminalConfig};
+use crate::platform::KeyCode;
+use crate::render::{DisplayList, TextAlign, TextRun};
+use crate::terminal::Terminal;
 use crate::theme::tree::ThemeTree;
 use crate::theme::types::{Color, LayoutContext, Rect};
 
-use super::base::CornerRadii;
 use super::taskpanel::TaskPanelStyle;
 
 /// Maximum number of lines to keep in buffer
@@ -273,7 +269,7 @@ impl TailView {
         };
 
         let reader = BufReader::new(file);
-        let mut new_lines: Vec<String> = reader.lines().flatten().collect();
+        let mut new_lines: Vec<String> = reader.lines().map_while(Result::ok).collect();
 
         // Cap at MAX_LINES
         if new_lines.len() > MAX_LINES {
@@ -378,7 +374,7 @@ impl TailView {
         if let Some(ref mut terminal) = self.terminal {
             let mut buf = [0u8; 4];
             let s = c.encode_utf8(&mut buf);
-            terminal.write_to_pty(s.as_bytes());
+            let _ = terminal.write_to_pty(s.as_bytes());
         }
     }
 
@@ -475,7 +471,7 @@ impl TailView {
         };
 
         if let Some(data) = seq {
-            terminal.write_to_pty(data);
+            let _ = terminal.write_to_pty(data);
             true
         } else {
             false
@@ -527,12 +523,7 @@ impl TailView {
     }
 
     /// Render the tail view
-    pub fn render(
-        &mut self,
-        renderer: &mut Renderer,
-        rect: Rect,
-        ctx: &LayoutContext,
-    ) -> Result<(), windows::core::Error> {
+    pub fn render(&mut self, list: &mut DisplayList, rect: Rect, ctx: &LayoutContext) {
         // Auto-refresh from file if enough time has passed
         self.maybe_refresh();
 
@@ -547,195 +538,77 @@ impl TailView {
         let line_spacing = self.style.line_spacing * scale;
         let button_width = self.button_style.compact_width * scale;
         let button_height = self.button_style.item_height * scale;
-        let button_radius = self.button_style.item_corner_radius * scale;
         let button_padding = self.button_style.padding * scale;
-        let icon_size = self.button_style.icon_size * scale;
 
         // Draw background
-        let bounds = D2D_RECT_F {
-            left: rect.x,
-            top: rect.y,
-            right: rect.x + rect.width,
-            bottom: rect.y + rect.height,
-        };
-
-        renderer.fill_rounded_rect(
-            bounds,
-            border_radius,
-            border_radius,
-            self.style.background_color,
-        )?;
+        list.fill_rounded_rect(rect, border_radius, self.style.background_color);
 
         // === Button Bar (left side) ===
         let bar_x = rect.x + button_padding;
         let bar_y = rect.y + button_padding;
-
-        // Selection border color (cyan accent like task panel)
-        let selection_color = Color::from_hex("#03edf9").unwrap_or(Color::WHITE);
-        let border_width = 2.0 * scale;
-
-        // Create icon format
-        let icon_format = renderer
-            .create_text_format(&self.button_style.icon_font_family, icon_size, false, false)
-            .ok();
+        let button_step = button_height + self.button_style.item_spacing * scale;
 
         // Back button
         let back_rect = Rect::new(bar_x, bar_y, button_width, button_height);
         self.back_button_rect = Some(back_rect);
-
-        let back_bounds = D2D_RECT_F {
-            left: back_rect.x,
-            top: back_rect.y,
-            right: back_rect.x + back_rect.width,
-            bottom: back_rect.y + back_rect.height,
-        };
-
-        renderer.fill_rounded_rect(
-            back_bounds,
-            button_radius,
-            button_radius,
+        self.render_button(
+            list,
+            back_rect,
+            0,
             self.button_style.item_background_color,
-        )?;
-
-        // Draw selection border if this button is selected
-        if self.selected_button == 0 {
-            renderer.draw_rounded_rect(
-                back_bounds,
-                button_radius,
-                button_radius,
-                selection_color,
-                border_width,
-            )?;
-        }
-
-        // Draw back icon (nerd font left arrow)
-        if let Some(ref fmt) = icon_format {
-            let icon = "󰁍"; // nf-md-arrow_left
-            renderer.draw_text_centered(icon, fmt, back_bounds, self.button_style.icon_color)?;
-        }
+            "󰁍", // nf-md-arrow_left
+            self.button_style.icon_color,
+            scale,
+        );
 
         // Terminal button (below back)
-        let term_rect = Rect::new(
-            bar_x,
-            bar_y + button_height + self.button_style.item_spacing * scale,
-            button_width,
-            button_height,
-        );
+        let term_rect = Rect::new(bar_x, bar_y + button_step, button_width, button_height);
         self.terminal_button_rect = Some(term_rect);
-
-        let term_bounds = D2D_RECT_F {
-            left: term_rect.x,
-            top: term_rect.y,
-            right: term_rect.x + term_rect.width,
-            bottom: term_rect.y + term_rect.height,
-        };
-
-        renderer.fill_rounded_rect(
-            term_bounds,
-            button_radius,
-            button_radius,
+        self.render_button(
+            list,
+            term_rect,
+            1,
             self.button_style.item_background_color,
-        )?;
-
-        // Draw selection border if this button is selected
-        if self.selected_button == 1 {
-            renderer.draw_rounded_rect(
-                term_bounds,
-                button_radius,
-                button_radius,
-                selection_color,
-                border_width,
-            )?;
-        }
-
-        // Draw terminal icon (using > character as fallback)
-        if let Some(ref fmt) = icon_format {
-            let icon = ">"; // Simple terminal prompt character
-            renderer.draw_text_centered(icon, fmt, term_bounds, self.button_style.icon_color)?;
-        }
+            ">", // Simple terminal prompt character
+            self.button_style.icon_color,
+            scale,
+        );
 
         // Rerun button (below terminal)
         let rerun_rect = Rect::new(
             bar_x,
-            bar_y + (button_height + self.button_style.item_spacing * scale) * 2.0,
+            bar_y + button_step * 2.0,
             button_width,
             button_height,
         );
         self.rerun_button_rect = Some(rerun_rect);
-
-        let rerun_bounds = D2D_RECT_F {
-            left: rerun_rect.x,
-            top: rerun_rect.y,
-            right: rerun_rect.x + rerun_rect.width,
-            bottom: rerun_rect.y + rerun_rect.height,
-        };
-
-        renderer.fill_rounded_rect(
-            rerun_bounds,
-            button_radius,
-            button_radius,
+        self.render_button(
+            list,
+            rerun_rect,
+            2,
             self.button_style.item_background_color,
-        )?;
-
-        // Draw selection border if this button is selected
-        if self.selected_button == 2 {
-            renderer.draw_rounded_rect(
-                rerun_bounds,
-                button_radius,
-                button_radius,
-                selection_color,
-                border_width,
-            )?;
-        }
-
-        // Draw rerun icon (nf-md-refresh)
-        if let Some(ref fmt) = icon_format {
-            let icon = "󰑓"; // nf-md-refresh
-            renderer.draw_text_centered(icon, fmt, rerun_bounds, self.button_style.icon_color)?;
-        }
+            "󰑓", // nf-md-refresh
+            self.button_style.icon_color,
+            scale,
+        );
 
-        // Kill button (below rerun)
+        // Kill button (below rerun), red-ish to indicate danger
         let kill_rect = Rect::new(
             bar_x,
-            bar_y + (button_height + self.button_style.item_spacing * scale) * 3.0,
+            bar_y + button_step * 3.0,
             button_width,
             button_height,
         );
         self.kill_button_rect = Some(kill_rect);
-
-        let kill_bounds = D2D_RECT_F {
-            left: kill_rect.x,
-            top: kill_rect.y,
-            right: kill_rect.x + kill_rect.width,
-            bottom: kill_rect.y + kill_rect.height,
-        };
-
-        // Use red-ish background for kill button to indicate danger
-        let kill_bg_color = Color::from_hex("#f97e7230").unwrap_or(self.button_style.item_background_color);
-        renderer.fill_rounded_rect(
-            kill_bounds,
-            button_radius,
-            button_radius,
-            kill_bg_color,
-        )?;
-
-        // Draw selection border if this button is selected
-        if self.selected_button == 3 {
-            renderer.draw_rounded_rect(
-                kill_bounds,
-                button_radius,
-                button_radius,
-                selection_color,
-                border_width,
-            )?;
-        }
-
-        // Draw kill icon (X or stop symbol)
-        if let Some(ref fmt) = icon_format {
-            let icon = "󰅖"; // nf-md-close
-            let kill_icon_color = Color::from_hex("#f97e72").unwrap_or(self.button_style.icon_color);
-            renderer.draw_text_centered(icon, fmt, kill_bounds, kill_icon_color)?;
-        }
+        self.render_button(
+            list,
+            kill_rect,
+            3,
+            Color::from_hex("#f97e7230").unwrap_or(self.button_style.item_background_color),
+            "󰅖", // nf-md-close
+            Color::from_hex("#f97e72").unwrap_or(self.button_style.icon_color),
+            scale,
+        );
 
         // === Content Area ===
         let content_x = rect.x + button_width + button_padding * 2.0 + padding;
@@ -747,17 +620,17 @@ impl TailView {
         match self.mode {
             TailViewMode::Interactive => {
                 self.render_terminal_content(
-                    renderer,
+                    list,
                     content_x,
                     content_y,
                     content_width,
                     content_height,
                     scale,
-                )?;
+                );
             }
             TailViewMode::LogFile => {
                 self.render_log_content(
-                    renderer,
+                    list,
                     content_x,
                     content_y,
                     content_width,
@@ -766,17 +639,50 @@ impl TailView {
                     line_spacing,
                     padding,
                     rect,
-                )?;
+                );
             }
         }
+    }
 
-        Ok(())
+    /// Render a button of the button bar with its icon centered
+    #[allow(clippy::too_many_arguments)]
+    fn render_button(
+        &self,
+        list: &mut DisplayList,
+        rect: Rect,
+        index: usize,
+        background: Color,
+        icon: &str,
+        icon_color: Color,
+        scale: f32,
+    ) {
+        let radius = self.button_style.item_corner_radius * scale;
+        list.fill_rounded_rect(rect, radius, background);
+
+        // Draw selection border if this button is selected
+        // (cyan accent like task panel)
+        if self.selected_button == index {
+            let selection_color = Color::from_hex("#03edf9").unwrap_or(Color::WHITE);
+            list.stroke_rounded_rect(rect, radius, selection_color, 2.0 * scale);
+        }
+
+        list.text(
+            TextRun::new(
+                icon,
+                rect,
+                self.button_style.icon_font_family.as_str(),
+                self.button_style.icon_size * scale,
+                icon_color,
+            )
+            .align(TextAlign::Center),
+        );
     }
 
     /// Render log file content (text lines)
+    #[allow(clippy::too_many_arguments)]
     fn render_log_content(
         &mut self,
-        renderer: &mut Renderer,
+        list: &mut DisplayList,
         content_x: f32,
         content_y: f32,
         content_width: f32,
@@ -785,18 +691,7 @@ impl TailView {
         line_spacing: f32,
         padding: f32,
         rect: Rect,
-    ) -> Result<(), windows::core::Error> {
-        // Create text format WITHOUT word wrapping (terminal-style, clip long lines)
-        let text_format = match renderer.create_text_format(
-            &self.style.font_family,
-            font_size,
-            false,
-            false,
-        ) {
-            Ok(f) => f,
-            Err(_) => return Ok(()),
-        };
-
+    ) {
         // Calculate line height
         let line_height = font_size + line_spacing;
 
@@ -809,7 +704,7 @@ impl TailView {
             self.scroll_offset = max_scroll;
         }
 
-        // Render visible lines
+        // Render visible lines (without wrapping, terminal-style)
         let start_line = self.scroll_offset;
         let end_line = (start_line + self.visible_lines + 1).min(self.lines.len());
 
@@ -820,15 +715,14 @@ impl TailView {
                 break;
             }
 
-            let line = &self.lines[line_idx];
-            let text_rect = D2D_RECT_F {
-                left: content_x,
-                top: y,
-                right: content_x + content_width,
-                bottom: y + line_height,
-            };
-
-            renderer.draw_text(line, &text_format, text_rect, self.style.text_color)?;
+            let text_rect = Rect::new(content_x, y, content_width, line_height);
+            list.text(TextRun::new(
+                self.lines[line_idx].as_str(),
+                text_rect,
+                self.style.font_family.as_str(),
+                font_size,
+                self.style.text_color,
+            ));
         }
 
         // Draw scrollbar if needed
@@ -839,19 +733,9 @@ impl TailView {
             let scrollbar_height = content_height;
 
             // Track
-            let track_rect = D2D_RECT_F {
-                left: scrollbar_x,
-                top: scrollbar_y,
-                right: scrollbar_x + scrollbar_width,
-                bottom: scrollbar_y + scrollbar_height,
-            };
+            let track_rect = Rect::new(scrollbar_x, scrollbar_y, scrollbar_width, scrollbar_height);
             let track_color = Color::from_f32(1.0, 1.0, 1.0, 0.1);
-            renderer.fill_rounded_rect(
-                track_rect,
-                scrollbar_width / 2.0,
-                scrollbar_width / 2.0,
-                track_color,
-            )?;
+            list.fill_rounded_rect(track_rect, scrollbar_width / 2.0, track_color);
 
             // Thumb
             let max_scroll = self.lines.len().saturating_sub(self.visible_lines);
@@ -864,48 +748,33 @@ impl TailView {
             };
             let thumb_y = scrollbar_y + scroll_ratio * (scrollbar_height - thumb_height);
 
-            let thumb_rect = D2D_RECT_F {
-                left: scrollbar_x,
-                top: thumb_y,
-                right: scrollbar_x + scrollbar_width,
-                bottom: thumb_y + thumb_height,
-            };
+            let thumb_rect = Rect::new(scrollbar_x, thumb_y, scrollbar_width, thumb_height);
             let thumb_color = Color::from_f32(1.0, 1.0, 1.0, 0.3);
-            renderer.fill_rounded_rect(
-                thumb_rect,
-                scrollbar_width / 2.0,
-                scrollbar_width / 2.0,
-                thumb_color,
-            )?;
+            list.fill_rounded_rect(thumb_rect, scrollbar_width / 2.0, thumb_color);
         }
-
-        Ok(())
     }
 
     /// Render interactive terminal content (using alacritty_terminal grid)
     fn render_terminal_content(
         &mut self,
-        renderer: &mut Renderer,
+        list: &mut DisplayList,
         content_x: f32,
         content_y: f32,
         content_width: f32,
         content_height: f32,
         scale: f32,
-    ) -> Result<(), windows::core::Error> {
+    ) {
+        let content_rect = Rect::new(content_x, content_y, content_width, content_height);
         let Some(ref mut terminal) = self.terminal else {
             // No terminal attached, show placeholder
-            let text = "Connecting...";
-            let font_size = self.style.font_size * scale;
-            if let Ok(fmt) = renderer.create_text_format(&self.style.font_family, font_size, false, false) {
-                let text_rect = D2D_RECT_F {
-                    left: content_x,
-                    top: content_y,
-                    right: content_x + content_width,
-                    bottom: content_y + content_height,
-                };
-                renderer.draw_text(text, &fmt, text_rect, self.style.text_color)?;
-            }
-            return Ok(());
+            list.text(TextRun::new(
+                "Connecting...",
+                content_rect,
+                self.style.font_family.as_str(),
+                self.style.font_size * scale,
+                self.style.text_color,
+            ));
+            return;
         };
 
         // Calculate cell dimensions based on font
@@ -928,25 +797,8 @@ impl TailView {
         let term_rows = terminal.rows();
         let colors = &terminal.colors;
 
-        // Create monospace text format
-        let text_format = match renderer.create_text_format(
-            &terminal.config.font_family,
-            font_size,
-            false,
-            false,
-        ) {
-            Ok(f) => f,
-            Err(_) => return Ok(()),
-        };
-
         // Draw terminal background
-        let term_bg_rect = D2D_RECT_F {
-            left: content_x,
-            top: content_y,
-            right: content_x + content_width,
-            bottom: content_y + content_height,
-        };
-        renderer.fill_rect(term_bg_rect, colors.background)?;
+        list.fill_rect(content_rect, colors.background);
 
         // Get cursor position for highlighting
         let (cursor_col, cursor_row) = terminal.cursor_position();
@@ -959,43 +811,40 @@ impl TailView {
             for col in 0..term_cols {
                 if let Some(cell) = terminal.cell(col, row) {
                     let x = content_x + (col as f32) * cell_width;
-
-                    let cell_rect = D2D_RECT_F {
-                        left: x,
-                        top: y,
-                        right: x + cell_width,
-                        bottom: y + cell_height,
-                    };
+                    let cell_rect = Rect::new(x, y, cell_width, cell_height);
 
                     // Draw cell background if not default
                     let bg_color = colors.resolve_color(cell.bg);
                     if bg_color != colors.background {
-                        renderer.fill_rect(cell_rect, bg_color)?;
+                        list.fill_rect(cell_rect, bg_color);
                     }
 
                     // Draw cursor
-                    if cursor_visible && col == cursor_col && row == cursor_row {
-                        let cursor_color = colors.cursor;
-                        renderer.fill_rect(cell_rect, cursor_color)?;
+                    let is_cursor = cursor_visible && col == cursor_col && row == cursor_row;
+                    if is_cursor {
+                        list.fill_rect(cell_rect, colors.cursor);
                     }
 
                     // Draw character
                     let c = cell.c;
                     if c != ' ' && c != '\0' {
-                        let fg_color = if cursor_visible && col == cursor_col && row == cursor_row {
+                        let fg_color = if is_cursor {
                             colors.background // Inverted for cursor
                         } else {
                             colors.resolve_color(cell.fg)
                         };
 
-                        let char_str = c.to_string();
-                        renderer.draw_text(&char_str, &text_format, cell_rect, fg_color)?;
+                        list.text(TextRun::new(
+                            c.to_string(),
+                            cell_rect,
+                            terminal.config.font_family.as_str(),
+                            font_size,
+                            fg_color,
+                        ));
                     }
                 }
             }
         }
-
-        Ok(())
     }
 }
 
@@ -1004,3 +853,126 @@ impl Default for TailView {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::DrawCommand;
+    use tempfile::TempDir;
+
+    /// A tail view following a log of `count` numbered lines
+    fn tail_log(count: usize) -> (TempDir, TailView) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("build.log");
+        let log: String = (0..count).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(&path, log).unwrap();
+
+        let mut view = TailView::new();
+        view.start_tail("dev:build".to_string(), path);
+        (dir, view)
+    }
+
+    fn render(view: &mut TailView) -> DisplayList {
+        let mut list = DisplayList::new();
+        view.render(
+            &mut list,
+            Rect::new(0.0, 0.0, 800.0, 400.0),
+            &LayoutContext::default(),
+        );
+        list
+    }
+
+    /// The log lines drawn, leaving out the button icons
+    fn lines(list: &DisplayList) -> Vec<&str> {
+        list.texts()
+            .filter(|run| run.text.starts_with("line"))
+            .map(|run| run.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_tailview_render_follows_the_end() {
+        let (_dir, mut view) = tail_log(100);
+        // The first render finds out how many lines fit
+        render(&mut view);
+        view.scroll_to_bottom();
+        let list = render(&mut view);
+
+        // 368px of content at 16px a line
+        let shown = lines(&list);
+        assert_eq!(shown.len(), 23);
+        assert_eq!(shown.first(), Some(&"line 77"));
+        assert_eq!(shown.last(), Some(&"line 99"));
+
+        // Background, four buttons, then the scrollbar's track and thumb
+        // with the thumb at the bottom
+        let fills: Vec<Rect> = list
+            .commands()
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Fill { rect, .. } => Some(*rect),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fills.len(), 7);
+        assert_eq!(fills[0], Rect::new(0.0, 0.0, 800.0, 400.0));
+        let (track, thumb) = (fills[5], fills[6]);
+        assert_eq!(thumb.x, track.x);
+        assert_eq!(thumb.y + thumb.height, track.y + track.height);
+    }
+
+    #[test]
+    fn test_tailview_render_scrolled() {
+        let (_dir, mut view) = tail_log(100);
+        render(&mut view);
+        view.scroll_to_top();
+        view.scroll_by(5);
+        let list = render(&mut view);
+        assert_eq!(lines(&list).first(), Some(&"line 5"));
+
+        // A short log fits without a scrollbar
+        let (_dir, mut view) = tail_log(3);
+        let list = render(&mut view);
+        assert_eq!(lines(&list), ["line 0", "line 1", "line 2"]);
+        let fills = list
+            .commands()
+            .iter()
+            .filter(|command| matches!(command, DrawCommand::Fill { .. }))
+            .count();
+        assert_eq!(fills, 5);
+    }
+
+    #[test]
+    fn test_tailview_render_buttons() {
+        let (_dir, mut view) = tail_log(0);
+        let list = render(&mut view);
+
+        // Only the selected button has the accent ring
+        let rings: Vec<Rect> = list
+            .commands()
+            .iter()
+            .filter_map(|command| match command {
+                DrawCommand::Stroke { rect, .. } => Some(*rect),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rings.len(), 1);
+        assert_eq!(
+            view.hit_test(rings[0].x + 1.0, rings[0].y + 1.0),
+            TailViewHit::BackButton
+        );
+
+        view.select_next_button();
+        let list = render(&mut view);
+        let ring = list.commands().iter().find_map(|command| match command {
+            DrawCommand::Stroke { rect, .. } => Some(*rect),
+            _ => None,
+        });
+        let ring = ring.unwrap();
+        assert_eq!(
+            view.hit_test(ring.x + 1.0, ring.y + 1.0),
+            TailViewHit::OpenTerminalButton
+        );
+        assert_eq!(view.get_selected_button(), TailViewHit::OpenTerminalButton);
+    }
+}
@@ -4,9 +4,13 @@
 //! - Compact mode (unfocused): Icons only in a narrow strip
 //! - Expanded mode (focused): Full sidebar with icons, labels, and expandable groups
 
+use crate::render::{DisplayList, TextAlign, TextRun};
 use crate::tasks::{TaskGroup, TaskItemState, TaskPanelPosition, TasksConfig};
+use crate::theme::tree::ThemeTree;
 use crate::theme::types::{Color, Rect};
 
+use super::base::{StateColors, WidgetState};
+
 /// Task panel runtime state
 #[derive(Debug, Clone)]
 pub struct TaskPanelState {
@@ -211,6 +215,69 @@ impl TaskPanelState {
             ))
         }
     }
+
+    /// Paint the compact panel (group icons only) over the wallpaper panel
+    /// at `area`, recording the rows' bounds for hit-testing
+    pub fn render_compact(
+        &mut self,
+        list: &mut DisplayList,
+        area: Rect,
+        style: &TaskPanelStyle,
+        scale: f32,
+    ) {
+        let padding = style.padding * scale;
+        let width = style.compact_width * scale;
+        let row_height = style.item_height * scale;
+        let item_radius = style.item_corner_radius * scale;
+
+        let left = match style.position {
+            TaskPanelPosition::Left => area.x + padding,
+            TaskPanelPosition::Right => area.x + area.width - width - padding,
+        };
+        self.panel_bounds = Rect::new(left, area.y + padding, width, area.height - 2.0 * padding);
+        list.fill_rounded_rect(
+            self.panel_bounds,
+            style.border_radius * scale,
+            style.background_color,
+        );
+
+        self.item_states.clear();
+        let mut y = self.panel_bounds.y + padding;
+        for (group_index, group) in self.config.groups.iter().enumerate() {
+            let index = self.item_states.len();
+            let mut state = WidgetState::NORMAL;
+            state.set(WidgetState::HOVER, self.hovered_item == Some(index));
+            state.set(
+                WidgetState::SELECTED,
+                self.focused && self.selected_item == Some(index),
+            );
+
+            let row = Rect::new(left + padding, y, width - 2.0 * padding, row_height);
+            list.fill_rounded_rect(row, item_radius, style.row_background_color.get(state));
+
+            // Shift the icon left a few pixels so it looks centered in the row
+            let icon_offset = 3.0 * scale;
+            let icon_rect = Rect::new(row.x - icon_offset, row.y, row.width, row.height);
+            list.text(
+                TextRun::new(
+                    &group.icon,
+                    icon_rect,
+                    &style.icon_font_family,
+                    style.icon_size * scale,
+                    style.group_icon_color.get(state),
+                )
+                .align(TextAlign::Center),
+            );
+
+            self.item_states.push(TaskItemState {
+                group_index,
+                task_index: None,
+                bounds: row,
+                is_group_header: true,
+            });
+            y += row_height + style.group_spacing * scale;
+        }
+    }
 }
 
 /// Task panel style configuration (from theme)
@@ -224,20 +291,16 @@ pub struct TaskPanelStyle {
     // === Colors ===
     /// Background color for the panel
     pub background_color: Color,
-    /// Icon color (normal)
+    /// Icon color for buttons outside the panel (tail view)
     pub icon_color: Color,
-    /// Icon color (hovered/selected)
-    pub icon_color_hover: Color,
-    /// Group header icon color
-    pub group_icon_color: Color,
-    /// Text color for labels (normal)
-    pub text_color: Color,
-    /// Text color for labels (hovered/selected)
-    pub text_color_hover: Color,
-    /// Background color for selected/hovered items
+    /// Background color for buttons outside the panel (tail view)
     pub item_background_color: Color,
-    /// Background color for selected item (accent color)
-    pub selected_background_color: Color,
+    /// Row icon color, by row state
+    pub group_icon_color: StateColors,
+    /// Text color for labels, by row state
+    pub text_color: StateColors,
+    /// Row background color, by row state (transparent when normal)
+    pub row_background_color: StateColors,
     /// Color for tree line connectors (├─ └─)
     pub tree_line_color: Color,
     /// Color for expand/collapse chevron
@@ -293,14 +356,24 @@ impl Default for TaskPanelStyle {
             // Colors - dark theme inspired by screenshot
             background_color: Color::from_f32(0.1, 0.11, 0.14, 0.95), // Dark blue-gray
             icon_color: Color::from_f32(0.6, 0.63, 0.69, 1.0),        // Muted gray
-            icon_color_hover: Color::from_f32(0.54, 0.71, 0.98, 1.0), // Blue accent #89b4fa
-            group_icon_color: Color::from_f32(0.8, 0.84, 0.96, 1.0),  // Light gray
-            text_color: Color::from_f32(0.8, 0.84, 0.96, 1.0),        // Light gray #cdd6f4
-            text_color_hover: Color::from_f32(1.0, 1.0, 1.0, 1.0),    // White
             item_background_color: Color::from_f32(1.0, 1.0, 1.0, 0.08), // Subtle hover bg
-            selected_background_color: Color::from_f32(0.22, 0.35, 0.55, 1.0), // Blue selection
-            tree_line_color: Color::from_f32(0.4, 0.42, 0.48, 1.0),   // Muted gray for lines
-            chevron_color: Color::from_f32(0.6, 0.63, 0.69, 1.0),     // Muted gray
+            group_icon_color: TaskPanelStyle::row_colors(
+                Color::from_f32(0.8, 0.84, 0.96, 1.0),  // Light gray
+                Color::from_f32(0.54, 0.71, 0.98, 1.0), // Blue accent #89b4fa
+                None,
+            ),
+            text_color: TaskPanelStyle::row_colors(
+                Color::from_f32(0.8, 0.84, 0.96, 1.0), // Light gray #cdd6f4
+                Color::from_f32(1.0, 1.0, 1.0, 1.0),   // White
+                None,
+            ),
+            row_background_color: TaskPanelStyle::row_colors(
+                Color::TRANSPARENT,
+                Color::from_f32(1.0, 1.0, 1.0, 0.08), // Subtle hover bg
+                Some(Color::from_f32(0.22, 0.35, 0.55, 1.0)), // Blue selection
+            ),
+            tree_line_color: Color::from_f32(0.4, 0.42, 0.48, 1.0), // Muted gray for lines
+            chevron_color: Color::from_f32(0.6, 0.63, 0.69, 1.0),   // Muted gray
 
             // Typography
             icon_font_family: "JetBrainsMono NF".to_string(),
@@ -328,6 +401,220 @@ impl Default for TaskPanelStyle {
     }
 }
 
+impl TaskPanelStyle {
+    /// Row states the colors can be themed for, as `task-panel.hover`;
+    /// `active` is a running task, `urgent` a failed one
+    pub const ROW_STATES: WidgetState = WidgetState::HOVER
+        .union(WidgetState::SELECTED)
+        .union(WidgetState::ACTIVE)
+        .union(WidgetState::URGENT);
+
+    /// Row colors from the older per-state properties: `hover` applies to
+    /// hovered and selected rows, `selected` (if given) to selected rows only
+    pub fn row_colors(normal: Color, hover: Color, selected: Option<Color>) -> StateColors {
+        StateColors::new(normal)
+            .with(WidgetState::HOVER, hover)
+            .with(WidgetState::SELECTED, selected.unwrap_or(hover))
+    }
+
+    /// Load the style from the theme's `task-panel`
+    pub fn from_theme(theme: &ThemeTree) -> Self {
+        let default = Self::default();
+
+        // Row colors: `task-panel.hover { text-color: ... }` and the like, with
+        // the older `*-hover` / `selected-*` properties as the defaults
+        let color =
+            |property: &str, default: Color| theme.get_color("task-panel", None, property, default);
+        let text_defaults = Self::row_colors(
+            color("text-color", default.text_color.get(WidgetState::NORMAL)),
+            color(
+                "text-color-hover",
+                default.text_color.get(WidgetState::HOVER),
+            ),
+            None,
+        );
+        let icon_defaults = Self::row_colors(
+            color(
+                "group-icon-color",
+                default.group_icon_color.get(WidgetState::NORMAL),
+            ),
+            color(
+                "icon-color-hover",
+                default.group_icon_color.get(WidgetState::HOVER),
+            ),
+            None,
+        );
+        let background_defaults = Self::row_colors(
+            Color::TRANSPARENT,
+            color(
+                "item-background-color",
+                default.row_background_color.get(WidgetState::HOVER),
+            ),
+            Some(color(
+                "selected-background-color",
+                default.row_background_color.get(WidgetState::SELECTED),
+            )),
+        );
+        let states = Self::ROW_STATES;
+
+        let style = Self {
+            enabled: theme.get_bool("task-panel", None, "enabled", default.enabled),
+            position: {
+                let pos = theme.get_string("task-panel", None, "position", "left");
+                match pos.to_lowercase().as_str() {
+                    "right" => TaskPanelPosition::Right,
+                    _ => TaskPanelPosition::Left,
+                }
+            },
+
+            // Colors
+            background_color: theme.get_color(
+                "task-panel",
+                None,
+                "background-color",
+                default.background_color,
+            ),
+            icon_color: theme.get_color("task-panel", None, "icon-color", default.icon_color),
+            item_background_color: theme.get_color(
+                "task-panel",
+                None,
+                "item-background-color",
+                default.item_background_color,
+            ),
+            group_icon_color: StateColors::from_theme(
+                theme,
+                "task-panel",
+                "group-icon-color",
+                states,
+                &icon_defaults,
+            ),
+            text_color: StateColors::from_theme(
+                theme,
+                "task-panel",
+                "text-color",
+                states,
+                &text_defaults,
+            ),
+            // The plain `item-background-color` is the hover color, so only
+            // state rules override the row background
+            row_background_color: StateColors::from_theme_states(
+                theme,
+                "task-panel",
+                "item-background-color",
+                states,
+                &background_defaults,
+            ),
+            tree_line_color: theme.get_color(
+                "task-panel",
+                None,
+                "tree-line-color",
+                default.tree_line_color,
+            ),
+            chevron_color: theme.get_color(
+                "task-panel",
+                None,
+                "chevron-color",
+                default.chevron_color,
+            ),
+
+            // Typography
+            icon_font_family: theme.get_string(
+                "task-panel",
+                None,
+                "icon-font-family",
+                &default.icon_font_family,
+            ),
+            text_font_family: theme.get_string(
+                "task-panel",
+                None,
+                "text-font-family",
+                &default.text_font_family,
+            ),
+            icon_size: theme.get_number("task-panel", None, "icon-size", default.icon_size as f64)
+                as f32,
+            text_size: theme.get_number("task-panel", None, "text-size", default.text_size as f64)
+                as f32,
+
+            // Dimensions
+            compact_width: theme.get_number(
+                "task-panel",
+                None,
+                "compact-width",
+                default.compact_width as f64,
+            ) as f32,
+            expanded_width: theme.get_number(
+                "task-panel",
+                None,
+                "expanded-width",
+                default.expanded_width as f64,
+            ) as f32,
+            item_height: theme.get_number(
+                "task-panel",
+                None,
+                "item-height",
+                default.item_height as f64,
+            ) as f32,
+            item_corner_radius: theme.get_number(
+                "task-panel",
+                None,
+                "item-corner-radius",
+                default.item_corner_radius as f64,
+            ) as f32,
+            padding: theme.get_number("task-panel", None, "padding", default.padding as f64) as f32,
+            group_spacing: theme.get_number(
+                "task-panel",
+                None,
+                "group-spacing",
+                default.group_spacing as f64,
+            ) as f32,
+            item_spacing: theme.get_number(
+                "task-panel",
+                None,
+                "item-spacing",
+                default.item_spacing as f64,
+            ) as f32,
+            border_radius: theme.get_number(
+                "task-panel",
+                None,
+                "border-radius",
+                default.border_radius as f64,
+            ) as f32,
+            sub_item_indent: theme.get_number(
+                "task-panel",
+                None,
+                "sub-item-indent",
+                default.sub_item_indent as f64,
+            ) as f32,
+
+            // Icons
+            chevron_collapsed: theme.get_string(
+                "task-panel",
+                None,
+                "chevron-collapsed",
+                &default.chevron_collapsed,
+            ),
+            chevron_expanded: theme.get_string(
+                "task-panel",
+                None,
+                "chevron-expanded",
+                &default.chevron_expanded,
+            ),
+            tree_branch: theme.get_string("task-panel", None, "tree-branch", &default.tree_branch),
+            tree_corner: theme.get_string("task-panel", None, "tree-corner", &default.tree_corner),
+        };
+
+        log!(
+            "Task panel style loaded: icon_font='{}', compact_width={}, expanded_width={}, enabled={}",
+            style.icon_font_family,
+            style.compact_width,
+            style.expanded_width,
+            style.enabled
+        );
+
+        style
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,11 +633,13 @@ mod tests {
                             name: "Docker".to_string(),
                             icon: "\u{f308}".to_string(),
                             script: "docker.ps1".to_string(),
+                            interactive: false,
                         },
                         Task {
                             name: "Server".to_string(),
                             icon: "\u{f6ff}".to_string(),
                             script: "server.ps1".to_string(),
+                            interactive: false,
                         },
                     ],
                 },
@@ -362,12 +651,44 @@ mod tests {
                         name: "Cleanup".to_string(),
                         icon: "\u{f1f8}".to_string(),
                         script: "cleanup.ps1".to_string(),
+                        interactive: false,
                     }],
                 },
             ],
         }
     }
 
+    #[test]
+    fn test_render_compact() {
+        let mut state = TaskPanelState::new(create_test_config());
+        state.hovered_item = Some(1);
+        let style = TaskPanelStyle::default();
+
+        let mut list = DisplayList::new();
+        let area = Rect::new(100.0, 0.0, 400.0, 300.0);
+        state.render_compact(&mut list, area, &style, 2.0);
+
+        // Panel inset by the padding, one header row per group
+        assert_eq!(state.panel_bounds, Rect::new(116.0, 16.0, 96.0, 268.0));
+        let rows: Vec<_> = state.item_states.iter().map(|s| s.bounds).collect();
+        assert_eq!(
+            rows,
+            [
+                Rect::new(132.0, 32.0, 64.0, 80.0),
+                Rect::new(132.0, 128.0, 64.0, 80.0)
+            ]
+        );
+        assert!(state.item_states.iter().all(|s| s.is_group_header));
+        let icons: Vec<_> = list.texts().map(|run| run.color).collect();
+        assert_eq!(
+            icons,
+            [
+                style.group_icon_color.get(WidgetState::NORMAL),
+                style.group_icon_color.get(WidgetState::HOVER)
+            ]
+        );
+    }
+
     #[test]
     fn test_task_panel_state_new() {
         let config = create_test_config();
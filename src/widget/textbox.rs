@@ -1,13 +1,11 @@
 //! Textbox widget - single-line text input with cursor and selection
 
-use windows::Win32::Graphics::Direct2D::Common::D2D_RECT_F;
-use windows::Win32::Graphics::DirectWrite::IDWriteTextFormat;
-
-use crate::platform::win32::{render::rect as d2d_rect, Renderer};
 use crate::platform::{Event, KeyCode};
+use crate::render::{DisplayList, TextRun};
+use crate::theme::tree::ThemeTree;
 use crate::theme::types::{LayoutContext, Rect};
 
-use super::base::{Constraints, LayoutProps, MeasuredSize};
+use super::base::{Constraints, CornerRadii, LayoutProps, MeasuredSize};
 use super::{EventResult, Widget, WidgetState, WidgetStyle};
 
 /// A single-line text input widget
@@ -26,8 +24,6 @@ pub struct Textbox {
     style: WidgetStyle,
     /// Layout properties
     layout: LayoutProps,
-    /// Cached text format
-    text_format: Option<IDWriteTextFormat>,
     /// Cursor blink state
     cursor_visible: bool,
     /// Scroll offset for long text
@@ -47,10 +43,9 @@ impl Textbox {
             cursor: 0,
             selection_anchor: None,
             placeholder: String::new(),
-            state: WidgetState::Normal,
+            state: WidgetState::NORMAL,
             style: WidgetStyle::default(),
             layout: LayoutProps::default(),
-            text_format: None,
             cursor_visible: true,
             scroll_offset: 0.0,
         }
@@ -107,6 +102,11 @@ impl Textbox {
         self.cursor_visible = true;
     }
 
+    /// Convert a byte offset into the text to a character index
+    fn char_index(&self, offset: usize) -> usize {
+        self.text[..offset].chars().count()
+    }
+
     /// Get selection range (start, end) if any
     pub fn selection(&self) -> Option<(usize, usize)> {
         self.selection_anchor.map(|anchor| {
@@ -276,15 +276,6 @@ impl Textbox {
         self.insert_text(text);
     }
 
-    /// Ensure text format is created/cached
-    fn ensure_text_format(&mut self, renderer: &Renderer) {
-        if self.text_format.is_none() {
-            self.text_format = renderer
-                .create_text_format(&self.style.font_family, self.style.font_size, false, false)
-                .ok();
-        }
-    }
-
     /// Get inner content rect (after padding)
     fn content_rect(&self, rect: Rect) -> Rect {
         Rect {
@@ -377,12 +368,12 @@ impl Widget for Textbox {
                 }
             }
             Event::FocusGained => {
-                self.state = WidgetState::Focused;
+                self.state.insert(WidgetState::FOCUSED);
                 self.show_cursor();
                 EventResult::repaint()
             }
             Event::FocusLost => {
-                self.state = WidgetState::Normal;
+                self.state.remove(WidgetState::FOCUSED);
                 self.selection_anchor = None;
                 EventResult::repaint()
             }
@@ -390,126 +381,79 @@ impl Widget for Textbox {
         }
     }
 
-    fn render(
-        &self,
-        renderer: &mut Renderer,
-        rect: Rect,
-        _ctx: &LayoutContext,
-    ) -> Result<(), windows::core::Error> {
-        let bounds = D2D_RECT_F {
-            left: rect.x,
-            top: rect.y,
-            right: rect.x + rect.width,
-            bottom: rect.y + rect.height,
-        };
+    fn render(&self, list: &mut DisplayList, rect: Rect, _ctx: &LayoutContext) {
+        let radii = CornerRadii::uniform(self.style.border_radius);
 
-        // Draw background
-        if self.style.border_radius > 0.0 {
-            renderer.fill_rounded_rect(
-                bounds,
-                self.style.border_radius,
-                self.style.border_radius,
-                self.style.background_color,
-            )?;
-        } else {
-            renderer.fill_rect(bounds, self.style.background_color)?;
+        // Draw shadow
+        if let Some(ref shadow) = self.style.box_shadow {
+            list.shadow(rect, radii, shadow);
         }
 
-        // Draw border
-        if self.style.border_width > 0.0 {
-            if self.style.border_radius > 0.0 {
-                renderer.draw_rounded_rect(
-                    bounds,
-                    self.style.border_radius,
-                    self.style.border_radius,
-                    self.style.border_color,
-                    self.style.border_width,
-                )?;
-            } else {
-                renderer.draw_rect(bounds, self.style.border_color, self.style.border_width)?;
-            }
-        }
+        // Draw background
+        list.fill(rect, radii, &self.style.background_color);
 
-        // Get text format - need to create if not exists
-        let format = match renderer.create_text_format(
-            &self.style.font_family,
-            self.style.font_size,
-            false,
-            false,
-        ) {
-            Ok(f) => f,
-            Err(_) => return Ok(()),
-        };
+        // Draw border
+        list.stroke(
+            rect,
+            radii,
+            &self.style.border_color,
+            self.style.border_width,
+        );
 
         let content = self.content_rect(rect);
-        let text_rect = d2d_rect(content.x, content.y, content.width, content.height);
-
-        // Draw text or placeholder
-        let (display_text, text_color) = if self.text.is_empty() {
-            (&self.placeholder, self.style.placeholder_color)
-        } else {
-            (&self.text, self.style.text_color)
+        // The entered text, for placing the selection and cursor
+        let text_run = |rect: Rect| {
+            TextRun::new(
+                self.text.as_str(),
+                rect,
+                self.style.font_family.as_str(),
+                self.style.font_size,
+                self.style.text_color,
+            )
         };
 
         // Draw selection highlight if any
-        if self.state == WidgetState::Focused {
+        if self.state.contains(WidgetState::FOCUSED) {
             if let Some((start, end)) = self.selection() {
-                if let (Ok(start_x), Ok(end_x)) = (
-                    renderer.get_caret_position(
-                        &self.text,
-                        &format,
-                        start,
-                        content.width,
-                        content.height,
-                    ),
-                    renderer.get_caret_position(
-                        &self.text,
-                        &format,
-                        end,
-                        content.width,
-                        content.height,
-                    ),
-                ) {
-                    let sel_rect = d2d_rect(
-                        content.x + start_x,
-                        content.y,
-                        end_x - start_x,
-                        content.height,
-                    );
-                    renderer.fill_rect(sel_rect, self.style.selection_color)?;
-                }
+                list.text_selection(
+                    text_run(content),
+                    self.char_index(start),
+                    self.char_index(end),
+                    self.style.selection_color,
+                );
             }
         }
 
-        // Draw text
-        if !display_text.is_empty() {
-            renderer.draw_text(display_text, &format, text_rect, text_color)?;
+        // Draw text or placeholder
+        if self.text.is_empty() {
+            list.text(TextRun::new(
+                self.placeholder.as_str(),
+                content,
+                self.style.font_family.as_str(),
+                self.style.font_size,
+                self.style.placeholder_color,
+            ));
+        } else {
+            list.text(text_run(content));
         }
 
         // Draw cursor
-        if self.state == WidgetState::Focused && self.cursor_visible {
-            if let Ok(cursor_x) = renderer.get_caret_position(
-                &self.text,
-                &format,
-                self.cursor,
+        if self.state.contains(WidgetState::FOCUSED) && self.cursor_visible {
+            let cursor_rect = Rect::new(
+                content.x,
+                content.y + 2.0,
                 content.width,
-                content.height,
-            ) {
-                let cursor_x = content.x + cursor_x;
-                // Scale cursor width with font size (min 2px)
-                let cursor_width = (self.style.font_size / 12.0).max(2.0);
-                renderer.draw_line(
-                    cursor_x,
-                    content.y + 2.0,
-                    cursor_x,
-                    content.y + content.height - 2.0,
-                    self.style.cursor_color,
-                    cursor_width,
-                )?;
-            }
+                content.height - 4.0,
+            );
+            // Scale cursor width with font size (min 2px)
+            let cursor_width = (self.style.font_size / 12.0).max(2.0);
+            list.caret(
+                text_run(cursor_rect),
+                self.char_index(self.cursor),
+                self.style.cursor_color,
+                cursor_width,
+            );
         }
-
-        Ok(())
     }
 
     fn state(&self) -> WidgetState {
@@ -526,10 +470,17 @@ impl Widget for Textbox {
 
     fn set_style(&mut self, style: WidgetStyle) {
         self.style = style;
-        self.text_format = None; // Invalidate cached format
     }
 
-    fn measure(&self, constraints: Constraints, _ctx: &LayoutContext) -> MeasuredSize {
+    fn set_text(&mut self, text: &str) {
+        Textbox::set_text(self, text);
+    }
+
+    fn restyle(&mut self, theme: &ThemeTree, path: &str) {
+        self.style = WidgetStyle::from_theme_textbox_at(theme, path, None);
+    }
+
+    fn measure(&self, constraints: Constraints, ctx: &LayoutContext) -> MeasuredSize {
         // Calculate desired height based on font size + padding
         let height = self.style.font_size
             + self.style.padding_top
@@ -540,6 +491,7 @@ impl Widget for Textbox {
         // Width: use available width or fixed if specified
         let width = self
             .layout
+            .resolved(ctx, constraints.max)
             .fixed_width
             .unwrap_or(constraints.max.width)
             .min(constraints.max.width)
@@ -556,3 +508,43 @@ impl Widget for Textbox {
         "textbox"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::DrawCommand;
+
+    #[test]
+    fn test_textbox_render_selection_and_caret() {
+        let mut textbox = Textbox::new().with_placeholder("Search");
+        let rect = Rect::new(0.0, 0.0, 200.0, 30.0);
+        let ctx = LayoutContext::default();
+
+        // Unfocused and empty: just the placeholder
+        let mut list = DisplayList::new();
+        textbox.render(&mut list, rect, &ctx);
+        assert_eq!(list.texts().next().unwrap().text, "Search");
+        assert!(!list
+            .commands()
+            .iter()
+            .any(|command| matches!(command, DrawCommand::Caret { .. })));
+
+        // Selection and caret positions are characters, not bytes
+        textbox.set_text("café bar");
+        textbox.set_state(WidgetState::FOCUSED);
+        textbox.selection_anchor = Some(0);
+        let mut list = DisplayList::new();
+        textbox.render(&mut list, rect, &ctx);
+        assert_eq!(list.texts().next().unwrap().text, "café bar");
+        let selection = list.commands().iter().find_map(|command| match command {
+            DrawCommand::TextSelection { start, end, .. } => Some((*start, *end)),
+            _ => None,
+        });
+        assert_eq!(selection, Some((0, 8)));
+        let caret = list.commands().iter().find_map(|command| match command {
+            DrawCommand::Caret { index, .. } => Some(*index),
+            _ => None,
+        });
+        assert_eq!(caret, Some(8));
+    }
+}